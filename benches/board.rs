@@ -0,0 +1,98 @@
+//! Timed suite over the board and solver modules. Every board comes from `TileMap::seeded`, so
+//! runs measure the same work and can be compared
+
+use bevy::prelude::IVec2;
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use minesweeper::{
+    resources::{
+        board::{TileMap, TileState},
+        board_scratch::BoardScratch,
+        board_state::BoardState,
+    },
+    solver::ConstraintGraph,
+};
+
+const SEED: u64 = 0x5eed;
+
+/// Square boards with the density of expert games
+const SIZES: [u32; 4] = [16, 64, 256, 1024];
+
+fn generation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("generation");
+    for size in SIZES {
+        let n_mines = size * size / 5;
+        group.bench_with_input(BenchmarkId::from_parameter(size), &size, |b, &size| {
+            b.iter(|| TileMap::seeded(size, size, n_mines, SEED).unwrap())
+        });
+    }
+    group.finish();
+}
+
+/// Worst case: a single mine in a corner, so one click opens the whole board
+fn flood_fill(c: &mut Criterion) {
+    let mut group = c.benchmark_group("flood_fill");
+    group.sample_size(10);
+    for size in SIZES {
+        let mut tiles = TileMap::with_mines(size, size, [[0, 0]]).unwrap();
+        let state = BoardState::new(&mut tiles);
+
+        // grown by the first run, like the buffers of the game after its first cascade
+        let mut scratch = BoardScratch::default();
+
+        group.bench_with_input(BenchmarkId::from_parameter(size), &state, |b, state| {
+            b.iter_batched(
+                || (state.clone(), ConstraintGraph::new(size, size, 1)),
+                |(mut state, mut graph)| {
+                    let last = IVec2::splat(size as i32 - 1);
+                    for &(coord, tile) in state.click_into(last, false, &mut scratch) {
+                        if let TileState::Clear(n) = tile {
+                            graph.reveal(coord, n);
+                        }
+                    }
+                    black_box(state.is_cleared())
+                },
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+/// Every other safe tile of a dense board revealed, leaving frontiers all over it
+fn dense_frontier(width: u32, height: u32, n_mines: u32) -> ConstraintGraph {
+    let mut tiles = TileMap::seeded(width, height, n_mines, SEED).unwrap();
+    let mut graph = ConstraintGraph::new(width, height, n_mines);
+    for tile in tiles.all_tiles() {
+        let coord = tile.coord();
+        if let (TileState::Clear(n), 0) = (tile.state(), (coord.x + coord.y) % 2) {
+            graph.reveal(coord, n);
+        }
+    }
+    graph
+}
+
+fn analysis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("dense_analysis");
+    group.sample_size(10);
+    for (name, width, height, n_mines) in [("expert", 30, 16, 130), ("large", 100, 100, 2500)] {
+        let graph = dense_frontier(width, height, n_mines);
+        group.bench_function(BenchmarkId::new("incremental", name), |b| {
+            b.iter_batched(
+                || graph.clone(),
+                |mut graph| black_box(graph.analyze()),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+        group.bench_function(BenchmarkId::new("full", name), |b| {
+            b.iter_batched(
+                || graph.clone(),
+                |mut graph| black_box(graph.analyze_full()),
+                criterion::BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, generation, flood_fill, analysis);
+criterion_main!(benches);
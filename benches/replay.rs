@@ -0,0 +1,78 @@
+//! Seeking in long replays, from the start against from the keyframes of the file. Every board
+//! comes from `TileMap::seeded`, so runs measure the same work and can be compared
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use minesweeper::{
+    resources::{
+        board::{TileMap, TileState},
+        board_options::GameMode,
+    },
+    save::{Action, Replay, ReplayMove},
+};
+
+const SEED: u64 = 0x5eed;
+
+/// A move on every tile in row order, a flag on the mines and a click elsewhere, a second apart
+fn long_replay(width: u32, height: u32, n_mines: u32) -> Replay {
+    let mut tiles = TileMap::seeded(width, height, n_mines, SEED).unwrap();
+    let mut mines = Vec::new();
+    let mut moves = Vec::new();
+    for tile in tiles.all_tiles() {
+        let coord = tile.coord();
+        let action = match tile.state() {
+            TileState::Mine => {
+                mines.push(coord);
+                Action::Flag(coord)
+            }
+            TileState::Clear(_) => Action::Uncover(coord),
+        };
+        moves.push(ReplayMove {
+            time: moves.len() as f32,
+            action,
+        });
+    }
+
+    Replay {
+        width,
+        height,
+        mines,
+        moves,
+        topology: None,
+        mode: GameMode::Classic,
+        outcome: None,
+        keyframes: Vec::new(),
+        annotations: Vec::new(),
+    }
+}
+
+fn seek(c: &mut Criterion) {
+    let mut group = c.benchmark_group("replay_seek");
+    for (name, width, height, n_mines) in [("expert", 30, 16, 99), ("large", 100, 100, 2000)] {
+        let replay = long_replay(width, height, n_mines);
+        let mut indexed = replay.clone();
+        indexed.add_keyframes().unwrap();
+        // the furthest from a keyframe
+        let target = replay.moves.len() - 1;
+
+        let from_start = replay.keyframes_every(usize::MAX).unwrap();
+        group.bench_function(BenchmarkId::new("from_start", name), |b| {
+            b.iter(|| black_box(from_start.seek(&replay, target)))
+        });
+        let keyframes = indexed.keyframes().unwrap();
+        group.bench_function(BenchmarkId::new("keyframes", name), |b| {
+            b.iter(|| black_box(keyframes.seek(&indexed, target)))
+        });
+
+        // opening the replay in the viewer
+        group.bench_function(BenchmarkId::new("load_played", name), |b| {
+            b.iter(|| black_box(replay.keyframes().unwrap()))
+        });
+        group.bench_function(BenchmarkId::new("load_stored", name), |b| {
+            b.iter(|| black_box(indexed.keyframes().unwrap()))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, seek);
+criterion_main!(benches);
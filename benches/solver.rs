@@ -0,0 +1,73 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::IVec2;
+use criterion::{black_box, criterion_group, criterion_main, Bencher, Criterion};
+use minesweeper::{
+    resources::board::{TileMap, TileState},
+    solver::ConstraintGraph,
+};
+
+/// Reveals of a game played by the solver itself: open a zero, then keep revealing proven safe
+/// tiles (cascading through zeros) until it gets stuck
+fn solver_game(width: u32, height: u32, n_mines: u32, seed: u64) -> Vec<(IVec2, u8)> {
+    let mut tiles = TileMap::seeded(width, height, n_mines, seed).unwrap();
+    let mut graph = ConstraintGraph::new(width, height, n_mines);
+    let mut reveals = Vec::new();
+
+    let start = tiles
+        .all_tiles()
+        .find(|tile| tile.state() == TileState::Clear(0))
+        .map(|tile| tile.coord())
+        .expect("board without any zero");
+
+    let mut queue = VecDeque::from([start]);
+    loop {
+        while let Some(coord) = queue.pop_front() {
+            let tile = tiles.tile(coord);
+            let TileState::Clear(n) = tile.state() else {
+                panic!("solver revealed a mine at {coord:?}");
+            };
+            if graph.knowledge(coord) != minesweeper::solver::Knowledge::Covered {
+                continue;
+            }
+
+            graph.reveal(coord, n);
+            reveals.push((coord, n));
+            if n == 0 {
+                queue.extend(tile.neighbors().map(|tile| tile.coord()));
+            }
+        }
+
+        match graph.analyze().safe().next() {
+            Some(coord) => queue.push_back(coord),
+            None => break reveals,
+        }
+    }
+}
+
+fn replay(bencher: &mut Bencher, full: bool) {
+    let reveals = solver_game(30, 16, 99, 0x5eed);
+
+    bencher.iter(|| {
+        let mut graph = ConstraintGraph::new(30, 16, 99);
+        for &(coord, n) in &reveals {
+            graph.reveal(coord, n);
+            let analysis = if full {
+                graph.analyze_full()
+            } else {
+                graph.analyze()
+            };
+            black_box(analysis);
+        }
+    });
+}
+
+fn expert_analysis(c: &mut Criterion) {
+    let mut group = c.benchmark_group("expert_analysis");
+    group.bench_function("full", |b| replay(b, true));
+    group.bench_function("incremental", |b| replay(b, false));
+    group.finish();
+}
+
+criterion_group!(benches, expert_analysis);
+criterion_main!(benches);
@@ -0,0 +1,48 @@
+//! Agent of the engine mode playing random covered tiles, to test the protocol end to end:
+//!
+//! ```text
+//! cargo build --example random_agent
+//! cargo run -- --engine target/debug/examples/random_agent
+//! ```
+//!
+//! Agents in other languages follow the same loop: read a line of JSON, answer with a move.
+
+use std::io::{self, BufRead, Write};
+
+use bevy::prelude::IVec2;
+use minesweeper::{
+    engine::format_move,
+    save::Action,
+    snapshot::{BoardSnapshot, SnapshotStatus, COVERED},
+};
+use nanorand::{Rng, WyRand};
+
+fn main() -> io::Result<()> {
+    let mut rng = WyRand::new();
+    let mut out = io::stdout().lock();
+    for line in io::stdin().lock().lines() {
+        let snapshot = match BoardSnapshot::from_json(&line?) {
+            Ok(snapshot) => snapshot,
+            Err(error) => {
+                eprintln!("random agent: unreadable snapshot: {error}");
+                continue;
+            }
+        };
+        if snapshot.status != SnapshotStatus::Playing {
+            writeln!(out, "# game over, {:?}", snapshot.status)?;
+            continue;
+        }
+
+        let covered = (0..snapshot.height as i32)
+            .flat_map(|y| (0..snapshot.width as i32).map(move |x| IVec2::new(x, y)))
+            .filter(|&coord| snapshot.tile(coord) == Some(COVERED))
+            .collect::<Vec<_>>();
+        if covered.is_empty() {
+            continue;
+        }
+        let coord = covered[rng.generate_range(0..covered.len())];
+        writeln!(out, "{}", format_move(Action::Uncover(coord)))?;
+        out.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,172 @@
+//! Plain text reports making a bug reproducible: the seed and options of the board, the last
+//! events of the game and an ASCII dump of the board, written on panic or on demand, see
+//! `BugReportPlugin`.
+
+use std::{collections::VecDeque, fmt::Write};
+
+use crate::{
+    resources::{board_options::BoardOptions, board_state::BoardState},
+    storage::{Location, Storage},
+};
+
+/// Directory of the reports, in the data directory
+pub const REPORT_DIR: &str = "reports";
+
+/// Events kept in the log, the most recent ones
+pub const LOG_SIZE: usize = 200;
+
+/// Last events of the game, oldest first. Must be used as a resource
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct EventLog {
+    entries: VecDeque<(f64, String)>,
+}
+
+impl EventLog {
+    /// Log `entry` as happening `time` seconds after startup
+    pub fn push(&mut self, time: f64, entry: impl Into<String>) {
+        if self.entries.len() == LOG_SIZE {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((time, entry.into()));
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (f64, &str)> {
+        self.entries
+            .iter()
+            .map(|(time, entry)| (*time, entry.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+/// Everything needed to replay a game up to a bug
+#[derive(Debug, Default, Clone)]
+pub struct BugReport {
+    /// Seconds since the Unix epoch
+    pub created: u64,
+    /// Message and location of the panic, `None` for reports asked by the player
+    pub panic: Option<String>,
+    /// `None` for boards loaded from a file, see `BoardSeed`
+    pub seed: Option<u64>,
+    pub options: Option<BoardOptions>,
+    pub log: Vec<String>,
+    /// Player view and solution, see [`BoardState::to_ascii`]
+    pub board: Option<(String, String)>,
+}
+
+impl BugReport {
+    pub fn capture(
+        created: u64,
+        seed: Option<u64>,
+        options: Option<&BoardOptions>,
+        log: &EventLog,
+        state: Option<&BoardState>,
+    ) -> Self {
+        Self {
+            created,
+            panic: None,
+            seed,
+            options: options.cloned(),
+            log: log
+                .iter()
+                .map(|(time, entry)| format!("{time:>10.3}s {entry}"))
+                .collect(),
+            board: state.map(|state| (state.to_ascii(false), state.to_ascii(true))),
+        }
+    }
+
+    /// Name of the report file, in [`REPORT_DIR`]
+    pub fn file_name(&self) -> String {
+        format!("report-{}.txt", self.created)
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut text = format!(
+            "Minesweeper {} bug report, created at {}\n",
+            env!("CARGO_PKG_VERSION"),
+            self.created
+        );
+        // writing to a string cannot fail
+        if let Some(panic) = &self.panic {
+            let _ = writeln!(text, "\nPanic: {panic}");
+        }
+        match self.seed {
+            Some(seed) => {
+                let _ = writeln!(text, "\nSeed: {seed:#x}");
+            }
+            None => text.push_str("\nSeed: none, the board was loaded from a file\n"),
+        }
+        if let Some(options) = &self.options {
+            let options = ron::ser::to_string_pretty(options, Default::default())
+                .unwrap_or_else(|error| error.to_string());
+            let _ = writeln!(text, "\nOptions:\n{options}");
+        }
+        let _ = writeln!(text, "\nEvents, oldest first:");
+        for entry in &self.log {
+            let _ = writeln!(text, "{entry}");
+        }
+        if let Some((view, solution)) = &self.board {
+            let _ = write!(text, "\nBoard:\n{view}\nSolution:\n{solution}");
+        }
+        text
+    }
+
+    /// Write the report in [`REPORT_DIR`], returning the name of the file
+    pub fn write(&self, storage: &Storage) -> std::io::Result<String> {
+        let file = self.file_name();
+        storage.write(
+            Location::Data,
+            &format!("{REPORT_DIR}/{file}"),
+            &self.to_text(),
+        )?;
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BugReport, EventLog, LOG_SIZE};
+    use crate::resources::{board::TileMap, board_options::BoardOptions, board_state::BoardState};
+
+    #[test]
+    fn test_log() {
+        let mut log = EventLog::default();
+        for i in 0..LOG_SIZE + 5 {
+            log.push(i as f64, format!("event {i}"));
+        }
+        assert_eq!(log.len(), LOG_SIZE);
+        assert_eq!(log.iter().next(), Some((5.0, "event 5")));
+    }
+
+    #[test]
+    fn test_report() {
+        let mut log = EventLog::default();
+        log.push(1.5, "uncover (2, 0)");
+        let mut tile_map = TileMap::with_mines(3, 2, [[0, 0]]).unwrap();
+        let mut state = BoardState::new(&mut tile_map);
+        state.uncover([2, 0]);
+
+        let options = BoardOptions::default();
+        let report = BugReport::capture(42, Some(255), Some(&options), &log, Some(&state));
+        assert_eq!(report.file_name(), "report-42.txt");
+
+        let text = report.to_text();
+        assert!(text.contains("Seed: 0xff"));
+        assert!(text.contains("     1.500s uncover (2, 0)"));
+        assert!(text.ends_with("Board:\n# # .\n# # #\n\nSolution:\n* 1 .\n1 1 .\n"));
+        assert!(text.contains("difficulty:"));
+
+        let report = BugReport {
+            panic: Some("boom".to_owned()),
+            ..BugReport::capture(42, None, None, &log, None)
+        };
+        let text = report.to_text();
+        assert!(text.contains("Panic: boom") && text.contains("Seed: none"));
+    }
+}
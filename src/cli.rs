@@ -0,0 +1,477 @@
+//! Subcommands run without opening a window, e.g. `minesweeper verify-replay <file>`. Any other
+//! command line starts the game, see `BoardOptions::from_args`.
+
+use std::{fmt::Write as _, io::Write};
+
+use bevy::prelude::IVec2;
+
+use crate::{
+    import::parse_mask,
+    resources::board::TileState,
+    save::{self, Profiles, Replay, SaveGame, KEY_FILE, PROFILES_FILE, REPLAY_DIR, SLOTS_DIR},
+    solver::{Analysis, ConstraintGraph},
+    storage::{Location, Storage},
+};
+
+/// Exit code of a subcommand that succeeded
+pub const SUCCESS: i32 = 0;
+/// Exit code of a check that failed, e.g. a replay that does not play back as recorded
+pub const FAILURE: i32 = 1;
+/// Exit code of a malformed command line or an unreadable file
+pub const USAGE: i32 = 2;
+
+/// Run the subcommand of `args` (program name excluded), writing its output to `out`. Returns
+/// the exit code of the process, `None` if `args` is not a subcommand and the game should start
+pub fn run(args: &[String], out: &mut impl Write) -> Option<i32> {
+    let (command, rest) = args.split_first()?;
+    let code = match command.as_str() {
+        "verify-replay" => verify_replay(rest, out),
+        "keyframe-replay" => keyframe_replay(rest, out),
+        "solve" => solve(rest, out),
+        "sign-saves" => sign_saves(rest, out),
+        _ => return None,
+    };
+    Some(code.unwrap_or(USAGE))
+}
+
+/// `verify-replay <file>`: play the replay back and compare it with its recorded outcome
+fn verify_replay(args: &[String], out: &mut impl Write) -> std::io::Result<i32> {
+    let [path] = args else {
+        writeln!(out, "usage: minesweeper verify-replay <file>")?;
+        return Ok(USAGE);
+    };
+
+    let replay = match save::load::<Replay>(path) {
+        Ok(replay) => replay,
+        Err(error) => {
+            writeln!(out, "{path}: {error}")?;
+            return Ok(USAGE);
+        }
+    };
+    match replay.check() {
+        Ok(outcome) => {
+            let result = if outcome.won { "won" } else { "lost" };
+            writeln!(
+                out,
+                "VALID: {result} in {:.3}s, {} moves, board {:#018x}",
+                outcome.time,
+                replay.moves.len(),
+                outcome.board
+            )?;
+            Ok(SUCCESS)
+        }
+        Err(error) => {
+            writeln!(out, "INVALID: {error}")?;
+            Ok(FAILURE)
+        }
+    }
+}
+
+/// `keyframe-replay <file>`: write keyframes in the replay, so that seeking in it is fast. The
+/// moves are left alone, and so is the fingerprint
+fn keyframe_replay(args: &[String], out: &mut impl Write) -> std::io::Result<i32> {
+    let [path] = args else {
+        writeln!(out, "usage: minesweeper keyframe-replay <file>")?;
+        return Ok(USAGE);
+    };
+
+    let written = save::load::<Replay>(path).and_then(|mut replay| {
+        replay.add_keyframes()?;
+        save::save(path, &replay)?;
+        Ok(replay.keyframes.len())
+    });
+    match written {
+        Ok(keyframes) => {
+            writeln!(out, "{path}: {keyframes} keyframes written")?;
+            Ok(SUCCESS)
+        }
+        Err(error) => {
+            writeln!(out, "{path}: {error}")?;
+            Ok(USAGE)
+        }
+    }
+}
+
+/// `solve <board> [--mask <file>] [--json]`: run the solver on a saved board, a game in progress
+/// or a library board, and print the tiles it proves and the mine probability of every tile.
+/// The mask replaces the revealed tiles of the board, see [`parse_mask`]
+fn solve(args: &[String], out: &mut impl Write) -> std::io::Result<i32> {
+    let usage = "usage: minesweeper solve <board> [--mask <file>] [--json]";
+    let (mut board, mut mask, mut json) = (None, None, false);
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--json" => json = true,
+            "--mask" => match args.next() {
+                Some(path) => mask = Some(path),
+                None => {
+                    writeln!(out, "{usage}")?;
+                    return Ok(USAGE);
+                }
+            },
+            path if board.is_none() && !path.starts_with("--") => board = Some(path),
+            _ => {
+                writeln!(out, "{usage}")?;
+                return Ok(USAGE);
+            }
+        }
+    }
+    let Some(path) = board else {
+        writeln!(out, "{usage}")?;
+        return Ok(USAGE);
+    };
+
+    let mut board = match save::load::<SaveGame>(path) {
+        Ok(board) => board,
+        Err(error) => {
+            writeln!(out, "{path}: {error}")?;
+            return Ok(USAGE);
+        }
+    };
+    if let Some(mask) = mask {
+        let revealed = std::fs::read_to_string(mask)
+            .map_err(|error| error.to_string())
+            .and_then(|source| parse_mask(&source, board.width, board.height));
+        match revealed {
+            Ok(revealed) => board.revealed = revealed,
+            Err(error) => {
+                writeln!(out, "{mask}: {error}")?;
+                return Ok(USAGE);
+            }
+        }
+    }
+
+    let (_, state) = match board.restore() {
+        Ok(restored) => restored,
+        Err(error) => {
+            writeln!(out, "{path}: {error}")?;
+            return Ok(USAGE);
+        }
+    };
+    if let Some(coord) = board
+        .revealed
+        .iter()
+        .find(|&&coord| state.tile(coord) == Some(TileState::Mine))
+    {
+        writeln!(out, "the mine at ({}, {}) is revealed", coord.x, coord.y)?;
+        return Ok(USAGE);
+    }
+    // on the tiling of the board, with the gaps and line hints of a puzzle
+    let mut graph =
+        ConstraintGraph::new(board.width, board.height, board.mines.len() as u32).rebuilt(&state);
+
+    let analysis = graph.analyze();
+    let report = if json {
+        analysis_json(&analysis)
+    } else {
+        analysis_text(&analysis)
+    };
+    out.write_all(report.as_bytes())?;
+    Ok(SUCCESS)
+}
+
+/// `sign-saves`: create the key of the player if there is none, and sign every file of the game
+/// with it. From then on, files of the player that are not signed are refused, see `save`
+fn sign_saves(args: &[String], out: &mut impl Write) -> std::io::Result<i32> {
+    if !args.is_empty() {
+        writeln!(out, "usage: minesweeper sign-saves")?;
+        return Ok(USAGE);
+    }
+    let Some(storage) = Storage::detect() else {
+        writeln!(out, "no home directory to keep the files of the game in")?;
+        return Ok(USAGE);
+    };
+    sign_storage(&storage, out)
+}
+
+/// Sign the files of the players without profiles and of every profile of `storage`. Files
+/// failing their checksum are reported and left alone
+fn sign_storage(storage: &Storage, out: &mut impl Write) -> std::io::Result<i32> {
+    let key = save::load_key(storage)
+        .transpose()
+        .unwrap_or_else(|| save::create_key(storage));
+    let key = match key {
+        Ok(key) => key,
+        Err(error) => {
+            writeln!(out, "{KEY_FILE}: {error}")?;
+            return Ok(USAGE);
+        }
+    };
+
+    let shared = storage.shared();
+    let profiles = match save::read::<Profiles>(&shared, Location::Config, PROFILES_FILE) {
+        Ok(profiles) => profiles.unwrap_or_default(),
+        Err(error) => {
+            writeln!(out, "{PROFILES_FILE}: {error}")?;
+            return Ok(FAILURE);
+        }
+    };
+    let mut files = vec![(shared.clone(), Location::Config, PROFILES_FILE.to_owned())];
+    let players = std::iter::once(shared).chain(
+        profiles
+            .profiles
+            .iter()
+            .map(|profile| storage.for_profile(&profile.id)),
+    );
+    for player in players {
+        for dir in ["", REPLAY_DIR, SLOTS_DIR] {
+            for file in player.list(Location::Data, dir)? {
+                if file.ends_with(".ron") {
+                    let name = match dir {
+                        "" => file,
+                        dir => format!("{dir}/{file}"),
+                    };
+                    files.push((player.clone(), Location::Data, name));
+                }
+            }
+        }
+    }
+
+    let (mut signed, mut damaged) = (0, 0);
+    for (player, location, name) in files {
+        match save::resign(&player, location, &name, &key) {
+            Ok(true) => signed += 1,
+            Ok(false) => {}
+            Err(error) => {
+                damaged += 1;
+                writeln!(out, "{name}: {error}")?;
+            }
+        }
+    }
+    writeln!(out, "Signed {signed} files, {damaged} damaged")?;
+    Ok(if damaged == 0 { SUCCESS } else { FAILURE })
+}
+
+/// Proven tiles, then a grid of mine probabilities in percent, `.` for revealed tiles
+fn analysis_text(analysis: &Analysis) -> String {
+    let coords = |coords: Vec<IVec2>| {
+        coords
+            .iter()
+            .map(|c| format!("({}, {})", c.x, c.y))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    // writing to a string cannot fail
+    let mut text = String::new();
+    let _ = writeln!(text, "Safe: {}", coords(analysis.safe().collect()));
+    let _ = writeln!(text, "Mines: {}", coords(analysis.mines().collect()));
+    if let Some(confidence) = analysis.confidence() {
+        let _ = writeln!(
+            text,
+            "Sampled probabilities, {:.0} samples, standard error up to {:.3}",
+            confidence.samples, confidence.std_error
+        );
+    }
+
+    let dim = analysis.dim();
+    text.push_str("Probabilities:\n");
+    for y in 0..dim.y {
+        let row = (0..dim.x)
+            .map(|x| match analysis.probability([x, y]) {
+                Some(p) => format!("{:>4.0}%", p * 100.0),
+                None => format!("{:>5}", "."),
+            })
+            .collect::<String>();
+        let _ = writeln!(text, "{row}");
+    }
+    text
+}
+
+/// Same as [`analysis_text`], as a JSON object with `null` probabilities for revealed tiles
+fn analysis_json(analysis: &Analysis) -> String {
+    let coords = |coords: Vec<IVec2>| {
+        coords
+            .iter()
+            .map(|c| format!("[{},{}]", c.x, c.y))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+    let dim = analysis.dim();
+    let rows = (0..dim.y)
+        .map(|y| {
+            let row = (0..dim.x)
+                .map(|x| match analysis.probability([x, y]) {
+                    Some(p) => format!("{p}"),
+                    None => "null".to_owned(),
+                })
+                .collect::<Vec<_>>();
+            format!("[{}]", row.join(","))
+        })
+        .collect::<Vec<_>>();
+
+    format!(
+        "{{\"width\":{},\"height\":{},\"safe\":[{}],\"mines\":[{}],\"probabilities\":[{}]}}\n",
+        dim.x,
+        dim.y,
+        coords(analysis.safe().collect()),
+        coords(analysis.mines().collect()),
+        rows.join(",")
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{run, sign_storage, FAILURE, SUCCESS, USAGE};
+    use crate::{
+        resources::board_options::GameMode,
+        save::{self, Action, Profiles, Replay, ReplayMove, SaveGame, Stats, STATS_FILE},
+        storage::{Location, Storage},
+    };
+
+    fn run_str(args: &[&str]) -> (Option<i32>, String) {
+        let args = args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let mut out = Vec::new();
+        let code = run(&args, &mut out);
+        (code, String::from_utf8(out).unwrap())
+    }
+
+    #[test]
+    fn test_verify_replay() {
+        let root = std::env::temp_dir().join(format!("minesweeper-cli-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        // * 1 .
+        // 1 1 .
+        let mut replay = Replay {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 0)],
+            moves: [(0.0, [2, 0]), (1.5, [0, 1])]
+                .into_iter()
+                .map(|(time, coord)| ReplayMove {
+                    time,
+                    action: Action::Uncover(coord.into()),
+                })
+                .collect(),
+            topology: None,
+            mode: GameMode::Classic,
+            outcome: None,
+            keyframes: Vec::new(),
+            annotations: Vec::new(),
+        };
+        replay.outcome = Some(replay.simulate().unwrap());
+        let valid = root.join("valid.ron");
+        save::save(&valid, &replay).unwrap();
+
+        let (code, out) = run_str(&["verify-replay", valid.to_str().unwrap()]);
+        assert_eq!(code, Some(SUCCESS));
+        assert!(out.starts_with("VALID: won in 1.500s, 2 moves"), "{out}");
+
+        // keyframes change nothing the checks look at
+        let (code, out) = run_str(&["keyframe-replay", valid.to_str().unwrap()]);
+        assert_eq!(code, Some(SUCCESS));
+        assert!(out.ends_with("0 keyframes written\n"), "{out}");
+        assert_eq!(save::load::<Replay>(&valid), Ok(replay.clone()));
+        assert_eq!(
+            run_str(&["verify-replay", valid.to_str().unwrap()]).0,
+            Some(SUCCESS)
+        );
+
+        replay.moves[1].time = 3.0;
+        let tampered = root.join("tampered.ron");
+        save::save(&tampered, &replay).unwrap();
+        let (code, out) = run_str(&["verify-replay", tampered.to_str().unwrap()]);
+        assert_eq!(code, Some(FAILURE));
+        assert!(out.starts_with("INVALID:"));
+
+        let missing = root.join("missing.ron");
+        assert_eq!(
+            run_str(&["verify-replay", missing.to_str().unwrap()]).0,
+            Some(USAGE)
+        );
+        assert_eq!(
+            run_str(&["keyframe-replay", missing.to_str().unwrap()]).0,
+            Some(USAGE)
+        );
+        assert_eq!(run_str(&["verify-replay"]).0, Some(USAGE));
+        assert_eq!(run_str(&["--difficulty", "easy"]).0, None);
+        assert_eq!(run_str(&[]).0, None);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_solve() {
+        let root = std::env::temp_dir().join(format!("minesweeper-solve-{}", std::process::id()));
+        std::fs::create_dir_all(&root).unwrap();
+
+        // * 1 .
+        // 1 1 .
+        let board = SaveGame {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 0)],
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        let path = root.join("board.ron");
+        save::save(&path, &board).unwrap();
+        let mask = root.join("mask.txt");
+        std::fs::write(&mask, "# 1 .\n1 # .\n").unwrap();
+        let (path, mask) = (path.to_str().unwrap(), mask.to_str().unwrap());
+
+        let (code, out) = run_str(&["solve", path, "--mask", mask]);
+        assert_eq!(code, Some(SUCCESS));
+        assert!(out.starts_with("Safe: (1, 1)\nMines: (0, 0)\n"), "{out}");
+        assert!(out.ends_with("Probabilities:\n 100%    .    .\n    .   0%    .\n"));
+
+        let (code, out) = run_str(&["solve", "--json", path, "--mask", mask]);
+        assert_eq!(code, Some(SUCCESS));
+        assert_eq!(
+            out,
+            "{\"width\":3,\"height\":2,\"safe\":[[1,1]],\"mines\":[[0,0]],\
+             \"probabilities\":[[1,null,null],[null,0,null]]}\n"
+        );
+
+        // nothing revealed, every tile has the same odds
+        let (_, out) = run_str(&["solve", path]);
+        assert!(out.contains("Probabilities:\n  17%  17%  17%\n"), "{out}");
+
+        assert_eq!(run_str(&["solve"]).0, Some(USAGE));
+        assert_eq!(run_str(&["solve", path, "--mask"]).0, Some(USAGE));
+        std::fs::write(mask, "* 1 .\n1 1 .\n").unwrap();
+        assert_eq!(run_str(&["solve", path, "--mask", mask]).0, Some(USAGE));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_sign_saves() {
+        let root = std::env::temp_dir().join(format!("minesweeper-sign-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        let mut profiles = Profiles::default();
+        let alice = storage.for_profile(&profiles.select("Alice").id);
+        save::write(&storage, Location::Config, save::PROFILES_FILE, &profiles).unwrap();
+        save::write(&storage, Location::Data, STATS_FILE, &Stats::default()).unwrap();
+        save::write(&alice, Location::Data, STATS_FILE, &Stats::default()).unwrap();
+        alice
+            .write(
+                Location::Data,
+                "replays/damaged.ron",
+                "// checksum: fnv1a 0\n()",
+            )
+            .unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(sign_storage(&storage, &mut out).unwrap(), FAILURE);
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.ends_with("Signed 3 files, 1 damaged\n"), "{out}");
+
+        let key = save::load_key(&storage).unwrap().unwrap();
+        for player in [&storage, &alice] {
+            let stats = player.read(Location::Data, STATS_FILE).unwrap().unwrap();
+            assert!(save::unseal(&stats, Some(&key)).unwrap().1);
+        }
+        // the key is kept, signing again changes nothing
+        let mut out = Vec::new();
+        sign_storage(&storage, &mut out).unwrap();
+        assert_eq!(save::load_key(&storage).unwrap(), Some(key));
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,69 @@
+//! Text copied to the clipboard of the system, to paste results and boards in other programs,
+//! and read from it to play the boards pasted back.
+//!
+//! On X11 and Wayland the clipboard belongs to the program offering its content, so the handle
+//! is kept for the whole run: dropping it would empty the clipboard. The web build has no access
+//! to the clipboard of the browser without a click handled by the page, so copying and pasting
+//! fail there.
+
+use std::io;
+
+/// Put `text` on the clipboard, in place of what it held
+pub fn copy(text: &str) -> io::Result<()> {
+    system::copy(text)
+}
+
+/// Text held by the clipboard
+pub fn paste() -> io::Result<String> {
+    system::paste()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod system {
+    use std::{io, sync::Mutex};
+
+    use arboard::Clipboard;
+
+    /// Opened on first use, see the module documentation
+    static CLIPBOARD: Mutex<Option<Clipboard>> = Mutex::new(None);
+
+    fn error(error: arboard::Error) -> io::Error {
+        io::Error::other(error.to_string())
+    }
+
+    fn with_clipboard<T>(
+        f: impl FnOnce(&mut Clipboard) -> Result<T, arboard::Error>,
+    ) -> io::Result<T> {
+        let mut clipboard = CLIPBOARD.lock().unwrap_or_else(|e| e.into_inner());
+        let clipboard = match &mut *clipboard {
+            Some(clipboard) => clipboard,
+            empty => empty.insert(Clipboard::new().map_err(error)?),
+        };
+        f(clipboard).map_err(error)
+    }
+
+    pub fn copy(text: &str) -> io::Result<()> {
+        with_clipboard(|clipboard| clipboard.set_text(text))
+    }
+
+    pub fn paste() -> io::Result<String> {
+        with_clipboard(|clipboard| clipboard.get_text())
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod system {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(io::ErrorKind::Unsupported, "no clipboard on the web")
+    }
+
+    pub fn copy(_: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn paste() -> io::Result<String> {
+        Err(unsupported())
+    }
+}
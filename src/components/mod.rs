@@ -1,13 +1,27 @@
-use bevy::prelude::{Component, IVec2, Plugin};
+use bevy::prelude::{Component, IVec2, Plugin, Timer};
+
+use crate::{
+    events::{PauseActionEvent, SlotEvent},
+    save::{Annotation, LibrarySort},
+    topology::TileCoord,
+};
 
 #[cfg(feature = "debug")]
-use bevy_inspector_egui::Inspectable;
-use bevy_inspector_egui::RegisterInspectable;
+use bevy_inspector_egui::{Inspectable, RegisterInspectable};
+
+/// Root entity of the board, parent of every tile
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Board;
 
+/// Coordinate of a tile entity, see `topology`
 #[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
 pub struct BoardCoordinate {
+    /// Storage coordinate, the one of events and saves
     pub inner: IVec2,
+    /// The same tile in the coordinates of its tiling
+    pub native: TileCoord,
 }
 
 #[cfg_attr(feature = "debug", derive(Inspectable))]
@@ -22,16 +36,274 @@ pub struct MineNeighbor(pub u8);
 #[derive(Debug, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Component)]
 pub struct Uncover;
 
+/// Tile the player has not opened yet
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Covered;
+
+/// Opened tile, showing its number or mine
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Revealed;
+
+/// Covered tile marked as a mine by the player
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Flagged;
+
+/// Covered tile marked as uncertain by the player
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct Questioned;
+
+/// Sprite hiding the content of a covered tile, child of the tile entity
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct TileCover;
+
+/// Node asking whether to resume the saved game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ResumePromptText;
+
+/// Text node of the hint panel
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct HintText;
+
+/// Text node counting the placed flags
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct FlagCounterText;
+
+/// Number of the HUD drawn with the seven segment digits of the skin, over one [`SegmentDigit`]
+/// child per digit. The skin decides the look, see `HudPlugin::draw_segments`, and skins
+/// without digits keep the text counters instead
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SegmentDisplay {
+    /// Number of digits, the minus sign taking the first one
+    pub digits: usize,
+    pub value: i32,
+}
+
+/// Image node of a [`SegmentDisplay`], its position from the left
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SegmentDigit(pub usize);
+
+/// [`SegmentDisplay`] of the mines left to flag
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct MineCounter;
+
+/// [`SegmentDisplay`] of the seconds since the first click
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct GameTimer;
+
+/// Text node of the split times
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SplitText;
+
+/// Sprite behind the tiles, as large as the board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct BoardBackground;
+
+/// Empty part of the progress bar under the board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ProgressTrack;
+
+/// Sprite filling the progress bar under the board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ProgressBar;
+
+/// Outline of a tile changed by the last move, fading out
+#[derive(Debug, Default, Clone, Component)]
+pub struct MoveHighlight {
+    pub fade: Timer,
+}
+
+/// Tile popping out as it is revealed, back to its size when the timer ends
+#[derive(Debug, Default, Clone, Component)]
+pub struct Pop {
+    pub timer: Timer,
+}
+
+/// Callout of the annotation of a replay up at the time of the replay, over its tile or the
+/// board
+#[derive(Debug, Clone, PartialEq, Component)]
+pub struct AnnotationCallout(pub Annotation);
+
+/// Emote over a pinged tile, fading out
+#[derive(Debug, Default, Clone, Component)]
+pub struct PingMarker {
+    pub fade: Timer,
+}
+
+/// Scores and turn of a versus game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct VersusText;
+
+/// Root node of the insights screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct InsightsScreen;
+
+/// Root node of the trophies screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct TrophiesScreen;
+
+/// Root node of the board library screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct LibraryScreen;
+
+/// Clickable part of the board library screen
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Component)]
+pub enum LibraryButton {
+    Sort(LibrarySort),
+    /// Play the board saved in this file
+    Play(String),
+    Export,
+}
+
+/// Root node of the save slots screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SlotsScreen;
+
+/// Clickable part of the save slots screen
+#[derive(Debug, Clone, Component)]
+pub struct SlotButton(pub SlotEvent);
+
+/// Root node of the result of a finished game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ResultPanel;
+
+/// Button of the result copying it, with the board file when `board`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ShareButton {
+    pub board: bool,
+}
+
+/// Root node of the pause menu
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct PauseScreen;
+
+/// Clickable part of the pause menu, resuming the game when it has no action
+#[derive(Debug, Clone, Component)]
+pub struct PauseButton(pub Option<PauseActionEvent>);
+
+/// Root node of the confirmation dialog
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ConfirmScreen;
+
+/// Button of the confirmation dialog, carrying out the action or backing out
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ConfirmButton {
+    pub confirmed: bool,
+}
+
+/// Root node of the performance HUD, debug builds only
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct PerfHud;
+
+/// Counters of the performance HUD
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct PerfHudText;
+
+/// Bar of the frame time graph, with its index in `FrameTimes`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct FrameBar(pub usize);
+
+/// Banner of the attract mode, see `DemoPlugin`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct DemoBanner;
+
+/// Root node of the console, debug builds only, see `ConsolePlugin`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ConsoleRoot;
+
+/// Lines and prompt of the console
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ConsoleText;
+
+/// Faint mine over the cover of a tile, debug builds only, see `XrayPlugin`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct XrayMarker;
+
+/// Dot in the corner of a number too faint on its tile, debug builds only, see
+/// `ContrastAuditPlugin`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ContrastMarker;
+
+/// Mine image of a tile, restyled by skin packs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct MineSprite;
+
+/// Flag image over a cover, restyled by skin packs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct FlagSprite;
+
+/// Number of a tile written with the font, hidden while the numbers are images
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct NumberText(pub u8);
+
+/// Number of a tile drawn with a digit image of the skin or a die, hidden without one
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct NumberSprite(pub u8);
+
+/// Root node of the loading screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct LoadingScreen;
+
+/// Filled part of the loading bar
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct LoadingBar;
+
+/// Root node of the error screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ErrorScreenRoot;
+
+/// Button of an error screen that can be retried
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct RetryButton;
+
+/// Column of the toasts on screen
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ToastStack;
+
+/// A toast on screen, dismissed when clicked, with its index in `Toasts::shown`
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct ToastNode(pub usize);
+
+/// Row or column of the board
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Line {
+    Row(u32),
+    Column(u32),
+}
+
+/// Text along the border with the mines left on a line, see `BoardState::line_counts`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct LineCountText(pub Line);
+
+/// Letters of a column or number of a row along the border, see `RulerPlugin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct RulerText(pub Line);
+
+/// Reset button above the board, showing the face of the game
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Component)]
+pub struct SmileyButton;
+
 pub struct InspectablePlugin;
 
 impl Plugin for InspectablePlugin {
+    #[cfg_attr(not(feature = "debug"), allow(unused_variables))]
     fn build(&self, app: &mut bevy::prelude::App) {
         #[cfg(feature = "debug")]
         {
-            app.register_inspectable::<BoardCoordinate>()
+            app.register_inspectable::<Board>()
+                .register_inspectable::<BoardCoordinate>()
                 .register_inspectable::<Mine>()
                 .register_inspectable::<MineNeighbor>()
-                .register_inspectable::<Uncover>();
+                .register_inspectable::<Uncover>()
+                .register_inspectable::<Covered>()
+                .register_inspectable::<Revealed>()
+                .register_inspectable::<Flagged>()
+                .register_inspectable::<Questioned>()
+                .register_inspectable::<TileCover>();
         }
     }
 }
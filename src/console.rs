@@ -0,0 +1,429 @@
+//! Commands of the debug console, see `ConsolePlugin`. A command is a name and a function
+//! running on the `World` with the words after the name, registered in a [`CommandRegistry`]:
+//! adding one is writing the function and a line of [`CommandRegistry::default`].
+//!
+//! Scripts are text files of commands, one per line, with `#` starting a comment. They run with
+//! `exec <path>`, and each line is answered as if typed in the console.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::{
+    ecs::{event::Events, world::World},
+    prelude::IVec2,
+};
+
+use crate::{
+    events::{BoardRegenerateEvent, OptionsApplyEvent, TileMarkEvent, TileTriggerEvent},
+    resources::{
+        board_options::{BoardOptions, Difficulty},
+        board_seed::SeededBoard,
+        board_state::{BoardState, Cover},
+    },
+    solver::ConstraintGraph,
+};
+
+/// Scripts run by scripts stop there, a script running itself would never end
+pub const MAX_SCRIPT_DEPTH: usize = 8;
+
+/// Answer of a command, a line for the console, or why it failed
+pub type CommandResult = Result<String, String>;
+
+/// Body of a command, given the registry for the commands running others
+pub type CommandFn = fn(&CommandRegistry, &mut World, &[&str]) -> CommandResult;
+
+/// A command of the console
+#[derive(Debug, Clone, Copy)]
+pub struct ConsoleCommand {
+    pub name: &'static str,
+    /// Arguments, as `help` shows them
+    pub usage: &'static str,
+    pub help: &'static str,
+    pub run: CommandFn,
+}
+
+/// Commands of the console by name. Must be used as a resource
+#[derive(Debug)]
+pub struct CommandRegistry {
+    commands: Vec<ConsoleCommand>,
+    // scripts running, see `MAX_SCRIPT_DEPTH`
+    depth: AtomicUsize,
+}
+
+impl Default for CommandRegistry {
+    fn default() -> Self {
+        let mut registry = Self {
+            commands: Vec::new(),
+            depth: Default::default(),
+        };
+        registry
+            .register("help", "", "list the commands", help)
+            .register("seed", "<seed>", "new board from a seed", seed)
+            .register(
+                "reveal",
+                "<x> <y>",
+                "uncover a tile as a click does",
+                reveal,
+            )
+            .register("flag", "<x> <y>", "toggle the mark of a tile", flag)
+            .register("solve", "", "play every move the solver proves", solve)
+            .register(
+                "regen",
+                "[difficulty]",
+                "new board, e.g. `regen expert`",
+                regen,
+            )
+            .register(
+                "set",
+                "<option> <value>",
+                "change `density` or `mines`",
+                set,
+            )
+            .register("exec", "<path>", "run the commands of a script file", exec);
+        registry
+    }
+}
+
+impl CommandRegistry {
+    /// Add a command, in place of the one of the same name if any
+    pub fn register(
+        &mut self,
+        name: &'static str,
+        usage: &'static str,
+        help: &'static str,
+        run: CommandFn,
+    ) -> &mut Self {
+        let command = ConsoleCommand {
+            name,
+            usage,
+            help,
+            run,
+        };
+        match self.commands.iter_mut().find(|c| c.name == name) {
+            Some(existing) => *existing = command,
+            None => self.commands.push(command),
+        }
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConsoleCommand> {
+        self.commands.iter().find(|command| command.name == name)
+    }
+
+    /// Every command, in the order they were registered
+    pub fn commands(&self) -> impl Iterator<Item = &ConsoleCommand> {
+        self.commands.iter()
+    }
+
+    /// Run a line of the console. Empty lines and comments answer nothing
+    pub fn run(&self, world: &mut World, line: &str) -> CommandResult {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return Ok(String::new());
+        }
+        let words = line.split_whitespace().collect::<Vec<_>>();
+        let command = self
+            .get(words[0])
+            .ok_or_else(|| format!("Unknown command `{}`, try `help`", words[0]))?;
+        (command.run)(self, world, &words[1..])
+    }
+
+    /// Run every line of `script`, on and on past the lines that fail, and answer them in order
+    pub fn run_script(&self, world: &mut World, script: &str) -> Vec<(String, CommandResult)> {
+        if self.depth.load(Ordering::Relaxed) >= MAX_SCRIPT_DEPTH {
+            return vec![(
+                String::new(),
+                Err(format!("Scripts nested more than {MAX_SCRIPT_DEPTH} deep")),
+            )];
+        }
+        self.depth.fetch_add(1, Ordering::Relaxed);
+        let answers = script
+            .lines()
+            .filter(|line| !line.trim().is_empty() && !line.trim().starts_with('#'))
+            .map(|line| (line.trim().to_owned(), self.run(world, line)))
+            .collect();
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+        answers
+    }
+}
+
+fn usage(command: &str, usage: &str) -> String {
+    format!("Usage: {command} {usage}")
+}
+
+/// Coordinate of `[x, y]`
+fn coord(args: &[&str], usage_line: &str) -> Result<IVec2, String> {
+    let [x, y] = args else {
+        return Err(usage_line.to_owned());
+    };
+    match (x.parse(), y.parse()) {
+        (Ok(x), Ok(y)) => Ok(IVec2::new(x, y)),
+        _ => Err("Coordinates are whole numbers".to_owned()),
+    }
+}
+
+fn send<E: bevy::ecs::event::Event>(world: &mut World, event: E) -> Result<(), String> {
+    let mut events = world
+        .get_resource_mut::<Events<E>>()
+        .ok_or("No board is running")?;
+    events.send(event);
+    Ok(())
+}
+
+fn help(registry: &CommandRegistry, _: &mut World, _: &[&str]) -> CommandResult {
+    let lines = registry
+        .commands()
+        .map(|command| {
+            let name = format!("{} {}", command.name, command.usage);
+            format!("{:<24} {}", name.trim(), command.help)
+        })
+        .collect::<Vec<_>>();
+    Ok(lines.join("\n"))
+}
+
+fn seed(_: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    let [seed] = args else {
+        return Err(usage("seed", "<seed>"));
+    };
+    let seed = seed
+        .parse()
+        .map_err(|_| format!("`{seed}` is not a seed"))?;
+    let options = world
+        .get_resource::<BoardOptions>()
+        .cloned()
+        .unwrap_or_default();
+    world.insert_resource(SeededBoard {
+        seed,
+        mode: options.mode,
+        difficulty: options.difficulty,
+    });
+    send(world, BoardRegenerateEvent)?;
+    Ok(format!("New board of seed {seed}"))
+}
+
+fn reveal(_: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    let coord = coord(args, &usage("reveal", "<x> <y>"))?;
+    send(world, TileTriggerEvent { coord })?;
+    Ok(format!("Revealing ({}, {})", coord.x, coord.y))
+}
+
+fn flag(_: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    let coord = coord(args, &usage("flag", "<x> <y>"))?;
+    send(world, TileMarkEvent { coord })?;
+    Ok(format!("Marking ({}, {})", coord.x, coord.y))
+}
+
+fn solve(_: &CommandRegistry, world: &mut World, _: &[&str]) -> CommandResult {
+    let analysis = world
+        .get_resource_mut::<ConstraintGraph>()
+        .ok_or("No board is running")?
+        .analyze();
+    let state = world
+        .get_resource::<BoardState>()
+        .ok_or("No board is running")?;
+    let safe = analysis.safe().collect::<Vec<_>>();
+    // flags already down stay
+    let mines = analysis
+        .mines()
+        .filter(|&coord| state.cover(coord) == Some(Cover::Covered))
+        .collect::<Vec<_>>();
+    if safe.is_empty() && mines.is_empty() {
+        return Err("Nothing is proven, the next move is a guess".to_owned());
+    }
+    for &coord in &safe {
+        send(world, TileTriggerEvent { coord })?;
+    }
+    for &coord in &mines {
+        send(world, TileMarkEvent { coord })?;
+    }
+    Ok(format!(
+        "Revealing {} safe tiles, flagging {} mines",
+        safe.len(),
+        mines.len()
+    ))
+}
+
+fn regen(_: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    match args {
+        [] => {
+            send(world, BoardRegenerateEvent)?;
+            Ok("New board".to_owned())
+        }
+        [name] => {
+            let difficulty = Difficulty::from_name(name).ok_or_else(|| {
+                format!("`{name}` is not a difficulty, e.g. easy, expert or 20x10/30")
+            })?;
+            let mut options = world
+                .get_resource::<BoardOptions>()
+                .cloned()
+                .unwrap_or_default();
+            options.difficulty = difficulty;
+            let name = options.difficulty.name();
+            apply(world, options)?;
+            Ok(format!("New {name} board"))
+        }
+        _ => Err(usage("regen", "[difficulty]")),
+    }
+}
+
+fn set(_: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    let [option, value] = args else {
+        return Err(usage("set", "<option> <value>"));
+    };
+    let mut options = world
+        .get_resource::<BoardOptions>()
+        .cloned()
+        .unwrap_or_default();
+    let dim = options.difficulty.dim;
+    let n_tiles = dim.x * dim.y;
+    let n_mines = match *option {
+        "density" => match value.parse::<f32>() {
+            Ok(density) if (0.0..1.0).contains(&density) => {
+                (density * n_tiles as f32).round() as u32
+            }
+            _ => return Err(format!("`{value}` is not a density between 0 and 1")),
+        },
+        "mines" => value
+            .parse()
+            .map_err(|_| format!("`{value}` is not a number of mines"))?,
+        _ => return Err(format!("Unknown option `{option}`, `density` or `mines`")),
+    };
+    options.difficulty.n_mines = n_mines.clamp(1, n_tiles - 1);
+    let n_mines = options.difficulty.n_mines;
+    apply(world, options)?;
+    Ok(format!("New board with {n_mines} mines"))
+}
+
+/// Start a new board with `options`, refused when they are not valid
+fn apply(world: &mut World, options: BoardOptions) -> Result<(), String> {
+    options.validate().map_err(|error| error.to_string())?;
+    send(world, OptionsApplyEvent { options })
+}
+
+fn exec(registry: &CommandRegistry, world: &mut World, args: &[&str]) -> CommandResult {
+    let [path] = args else {
+        return Err(usage("exec", "<path>"));
+    };
+    let script =
+        std::fs::read_to_string(path).map_err(|error| format!("Cannot read {path}: {error}"))?;
+    let answers = registry.run_script(world, &script);
+    let failed = answers.iter().filter(|(_, answer)| answer.is_err()).count();
+    let lines = answers
+        .into_iter()
+        .map(|(line, answer)| match answer {
+            Ok(answer) => format!("> {line}\n{answer}"),
+            Err(error) => format!("> {line}\nError: {error}"),
+        })
+        .collect::<Vec<_>>();
+    match failed {
+        0 => Ok(lines.join("\n")),
+        _ => Err(format!(
+            "{}\n{failed} lines of {path} failed",
+            lines.join("\n")
+        )),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        ecs::{event::Events, world::World},
+        prelude::IVec2,
+    };
+
+    use super::{CommandRegistry, CommandResult};
+    use crate::{
+        events::{BoardRegenerateEvent, OptionsApplyEvent, TileTriggerEvent},
+        resources::{
+            board_options::{BoardOptions, Difficulty},
+            board_seed::SeededBoard,
+        },
+    };
+
+    fn world() -> World {
+        let mut world = World::new();
+        world.insert_resource(BoardOptions::default());
+        world.init_resource::<Events<TileTriggerEvent>>();
+        world.init_resource::<Events<BoardRegenerateEvent>>();
+        world.init_resource::<Events<OptionsApplyEvent>>();
+        world
+    }
+
+    fn applied(world: &mut World) -> Vec<BoardOptions> {
+        let mut events = world.resource_mut::<Events<OptionsApplyEvent>>();
+        events.drain().map(|event| event.options).collect()
+    }
+
+    #[test]
+    fn test_commands() {
+        let registry = CommandRegistry::default();
+        let mut world = world();
+
+        assert_eq!(registry.run(&mut world, "  # comment"), Ok(String::new()));
+        assert!(registry.run(&mut world, "frobnicate").is_err());
+        assert!(registry
+            .run(&mut world, "help")
+            .unwrap()
+            .contains("reveal <x> <y>"));
+
+        assert!(registry.run(&mut world, "reveal 3").is_err());
+        assert!(registry.run(&mut world, "reveal 3 4").is_ok());
+        let mut triggers = world.resource_mut::<Events<TileTriggerEvent>>();
+        let coords = triggers.drain().map(|e| e.coord).collect::<Vec<_>>();
+        assert_eq!(coords, [IVec2::new(3, 4)]);
+
+        assert!(registry.run(&mut world, "seed 42").is_ok());
+        assert_eq!(world.resource::<SeededBoard>().seed, 42);
+        assert_eq!(world.resource::<Events<BoardRegenerateEvent>>().len(), 1);
+
+        assert!(registry.run(&mut world, "regen expert").is_ok());
+        assert_eq!(
+            applied(&mut world)[0].difficulty.name(),
+            Difficulty::EXPERT.name()
+        );
+        assert!(registry.run(&mut world, "regen nightmare").is_err());
+
+        assert!(registry.run(&mut world, "set density 0.25").is_ok());
+        let dim = BoardOptions::default().difficulty.dim;
+        let expected = (0.25 * (dim.x * dim.y) as f32).round() as u32;
+        assert_eq!(applied(&mut world)[0].difficulty.n_mines, expected);
+        assert!(registry.run(&mut world, "set density 2").is_err());
+        assert!(registry.run(&mut world, "set colour red").is_err());
+        // no board to solve
+        assert!(registry.run(&mut world, "solve").is_err());
+    }
+
+    #[test]
+    fn test_register() {
+        fn ping(_: &CommandRegistry, _: &mut World, args: &[&str]) -> CommandResult {
+            Ok(format!("pong {}", args.join(" ")))
+        }
+        let mut registry = CommandRegistry::default();
+        let n_commands = registry.commands().count();
+        registry.register("ping", "[words]", "answer", ping);
+        assert_eq!(
+            registry.run(&mut World::new(), "ping a b"),
+            Ok("pong a b".to_owned())
+        );
+        registry.register("help", "", "replaced", ping);
+        assert_eq!(registry.commands().count(), n_commands + 1);
+
+        let answers = registry.run_script(&mut world(), "ping\n\n# skipped\nreveal x y\n");
+        assert_eq!(answers.len(), 2);
+        assert_eq!(answers[0], ("ping".to_owned(), Ok("pong ".to_owned())));
+        assert!(answers[1].1.is_err());
+    }
+
+    #[test]
+    fn test_exec() {
+        let path = std::env::temp_dir().join(format!("minesweeper-console-{}", std::process::id()));
+        std::fs::write(&path, format!("reveal 1 2\nexec {}\n", path.display())).unwrap();
+        let registry = CommandRegistry::default();
+        let mut world = world();
+        // the script runs itself until the depth limit
+        let answer = registry.run(&mut world, &format!("exec {}", path.display()));
+        assert!(answer.unwrap_err().contains("failed"));
+        let triggers = world.resource::<Events<TileTriggerEvent>>();
+        assert_eq!(triggers.len(), super::MAX_SCRIPT_DEPTH);
+        std::fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,137 @@
+//! Contrast of colors as the WCAG measure it, to check that the numbers of a skin stay readable
+//! on its opened tiles. Images count as their average color, a rough measure that still catches
+//! the unreadable pairs.
+
+use bevy::{
+    prelude::{Color, Image},
+    render::render_resource::TextureFormat,
+};
+
+/// Ratio of normal text, WCAG level AA
+pub const AA_TEXT: f32 = 4.5;
+
+/// Ratio of large text and graphics, WCAG level AA. The numbers fill their tile, they are held
+/// to this one
+pub const AA_LARGE: f32 = 3.0;
+
+/// How a pair of colors fares against the WCAG thresholds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    /// Readable as normal text
+    Text,
+    /// Readable as large text only
+    Large,
+    Fail,
+}
+
+impl Grade {
+    pub fn of(ratio: f32) -> Self {
+        if ratio >= AA_TEXT {
+            Grade::Text
+        } else if ratio >= AA_LARGE {
+            Grade::Large
+        } else {
+            Grade::Fail
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Grade::Text => "AA",
+            Grade::Large => "AA large",
+            Grade::Fail => "fail",
+        }
+    }
+}
+
+/// Relative luminance of the sRGB color, from 0 for black to 1 for white. Alpha is ignored
+pub fn relative_luminance(color: Color) -> f32 {
+    let [r, g, b, _] = color.as_rgba_f32();
+    let linear = |c: f32| {
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+/// From 1 for the same colors to 21 for black on white, in either order
+pub fn contrast_ratio(a: Color, b: Color) -> f32 {
+    let (a, b) = (relative_luminance(a), relative_luminance(b));
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Mean of the pixels weighted by their alpha, `None` for a fully transparent image or a format
+/// other than 8 bit RGBA, the one of PNG images
+pub fn average_color(image: &Image) -> Option<Color> {
+    let format = image.texture_descriptor.format;
+    if !matches!(
+        format,
+        TextureFormat::Rgba8UnormSrgb | TextureFormat::Rgba8Unorm
+    ) {
+        return None;
+    }
+
+    let (mut sum, mut weight) = ([0.0; 3], 0.0);
+    for pixel in image.data.chunks_exact(4) {
+        let alpha = f32::from(pixel[3]) / 255.0;
+        for (sum, channel) in sum.iter_mut().zip(pixel) {
+            *sum += f32::from(*channel) / 255.0 * alpha;
+        }
+        weight += alpha;
+    }
+    (weight > 0.0).then(|| Color::rgb(sum[0] / weight, sum[1] / weight, sum[2] / weight))
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        prelude::{Color, Image},
+        render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    };
+
+    use super::{average_color, contrast_ratio, Grade};
+
+    #[test]
+    fn test_contrast() {
+        assert!((contrast_ratio(Color::BLACK, Color::WHITE) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::WHITE, Color::BLACK) - 21.0).abs() < 0.01);
+        assert!((contrast_ratio(Color::RED, Color::RED) - 1.0).abs() < 0.01);
+        // the numbers of the built-in look
+        assert_eq!(
+            Grade::of(contrast_ratio(Color::BLUE, Color::GRAY)),
+            Grade::Fail
+        );
+        assert_eq!(
+            Grade::of(contrast_ratio(Color::BLACK, Color::GRAY)),
+            Grade::Text
+        );
+        assert_eq!(Grade::of(3.2), Grade::Large);
+
+        // a transparent pixel counts for nothing
+        let image = Image::new(
+            Extent3d {
+                width: 2,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            vec![255, 0, 0, 255, 0, 0, 255, 0],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        assert_eq!(average_color(&image), Some(Color::rgb(1.0, 0.0, 0.0)));
+        let transparent = Image::new(
+            Extent3d {
+                width: 1,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            vec![255, 255, 255, 0],
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        assert_eq!(average_color(&transparent), None);
+    }
+}
@@ -0,0 +1,171 @@
+//! Daily challenge: one board a day, the same for every player wherever they live.
+//!
+//! The day is the UTC date, never the local one. A player in Tokyo and one in New York at the
+//! same instant get the same board even when their calendars show different dates, and the
+//! next board comes at midnight UTC for everybody. Code starting from a local clock goes
+//! through [`LocalTime`], which makes the offset explicit. The seed is a hash of the date, so
+//! anybody can check which board a day had with [`DailyChallenge::on`]. Boards of the challenge
+//! come from the default backend of `rng`, whatever `--rng` picks.
+
+use crate::{
+    hash::StableHasher,
+    insights::DAY,
+    resources::{
+        board_options::{Difficulty, GameMode},
+        board_seed::SeededBoard,
+    },
+    save::{format_date, parse_date},
+};
+
+/// Mixed into the seeds, so that they differ from the seeds of other features hashing dates
+const SEED_KEY: &[u8] = b"minesweeper daily challenge";
+
+pub const DAILY_MODE: GameMode = GameMode::Classic;
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::MEDIUM;
+
+/// Wall clock of a player: seconds since the Unix epoch as the local calendar counts them, and
+/// the offset of the timezone from UTC, e.g. `9 * 3600` in Tokyo
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LocalTime {
+    pub local_secs: i64,
+    pub utc_offset_secs: i64,
+}
+
+impl LocalTime {
+    /// The same instant in UTC seconds since the Unix epoch, 0 before it
+    pub fn unix_secs(self) -> u64 {
+        (self.local_secs - self.utc_offset_secs).max(0) as u64
+    }
+}
+
+/// Board of a UTC day
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyChallenge {
+    /// `YYYY-MM-DD` in UTC
+    pub date: String,
+    /// Days since the Unix epoch
+    pub day: u64,
+    pub seed: u64,
+}
+
+impl DailyChallenge {
+    /// Challenge of `day`, in days since the Unix epoch
+    pub fn of_day(day: u64) -> Self {
+        let date = format_date(day);
+        let mut hash = StableHasher::new();
+        hash.write(SEED_KEY);
+        hash.write(date.as_bytes());
+        Self {
+            date,
+            day,
+            seed: hash.finish(),
+        }
+    }
+
+    /// Challenge of a `YYYY-MM-DD` UTC date
+    pub fn on(date: &str) -> Option<Self> {
+        parse_date(date).map(Self::of_day)
+    }
+
+    /// Challenge current at `unix_secs`, seconds since the Unix epoch
+    pub fn at(unix_secs: u64) -> Self {
+        Self::of_day(unix_secs / DAY)
+    }
+
+    /// Challenge current at a time read on a local clock
+    pub fn at_local(time: LocalTime) -> Self {
+        Self::at(time.unix_secs())
+    }
+
+    /// Board to generate for the challenge
+    pub fn board(&self) -> SeededBoard {
+        SeededBoard {
+            seed: self.seed,
+            mode: DAILY_MODE,
+            difficulty: DAILY_DIFFICULTY,
+        }
+    }
+
+    /// Practice board of the dimensions and mines of the challenge, from any seed but the one
+    /// of the challenge
+    pub fn warm_up(&self, seed: u64) -> SeededBoard {
+        SeededBoard {
+            seed: if seed == self.seed { !seed } else { seed },
+            ..self.board()
+        }
+    }
+}
+
+/// Seconds from `unix_secs` until the next challenge, 1 to [`DAY`]
+pub fn until_next(unix_secs: u64) -> u64 {
+    DAY - unix_secs % DAY
+}
+
+/// `HH:MM:SS` of a countdown
+pub fn format_countdown(secs: u64) -> String {
+    format!("{:02}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_countdown, until_next, DailyChallenge, LocalTime};
+    use crate::{insights::DAY, save::parse_date};
+
+    const HOUR: i64 = 3600;
+
+    #[test]
+    fn test_timezones() {
+        // 2024-03-09 23:30 UTC: already the 10th in Tokyo, still the 9th in New York
+        let instant = parse_date("2024-03-09").unwrap() * DAY + 23 * 3600 + 1800;
+        let local = |offset: i64| LocalTime {
+            local_secs: instant as i64 + offset,
+            utc_offset_secs: offset,
+        };
+        let tokyo = DailyChallenge::at_local(local(9 * HOUR));
+        let new_york = DailyChallenge::at_local(local(-5 * HOUR));
+        let kiritimati = DailyChallenge::at_local(local(14 * HOUR));
+        assert_eq!(tokyo, new_york);
+        assert_eq!(tokyo, kiritimati);
+        assert_eq!(tokyo.date, "2024-03-09");
+        assert_eq!(DailyChallenge::on("2024-03-09"), Some(tokyo));
+    }
+
+    #[test]
+    fn test_midnight() {
+        let midnight = parse_date("2024-03-10").unwrap() * DAY;
+        let before = DailyChallenge::at(midnight - 1);
+        let after = DailyChallenge::at(midnight);
+        assert_eq!(
+            (before.date.as_str(), after.date.as_str()),
+            ("2024-03-09", "2024-03-10")
+        );
+        assert_ne!(before.seed, after.seed);
+        assert_eq!(DailyChallenge::at(midnight + DAY - 1), after);
+
+        assert_eq!(until_next(midnight - 1), 1);
+        assert_eq!(until_next(midnight), DAY);
+        assert_eq!(until_next(midnight + 1), DAY - 1);
+        assert_eq!(format_countdown(until_next(midnight - 1)), "00:00:01");
+        assert_eq!(format_countdown(until_next(midnight)), "24:00:00");
+        assert_eq!(format_countdown(5 * 3600 + 3 * 60 + 9), "05:03:09");
+    }
+
+    #[test]
+    fn test_seeds() {
+        // seeds are part of the game: changing them changes the board of every past day
+        let day = DailyChallenge::on("2024-01-01").unwrap();
+        assert_eq!(day.seed, 0x4981_26d4_4f43_f071);
+        let days = (0..365).map(|day| DailyChallenge::of_day(19_000 + day).seed);
+        let mut seeds = days.collect::<Vec<_>>();
+        seeds.sort_unstable();
+        seeds.dedup();
+        assert_eq!(seeds.len(), 365);
+        assert_eq!(DailyChallenge::on("2024-02-30"), None);
+
+        // warm-ups never give the board away
+        let warm_up = day.warm_up(day.seed);
+        assert_ne!(warm_up.seed, day.seed);
+        assert_eq!(warm_up.difficulty.name(), day.board().difficulty.name());
+        assert_eq!(day.warm_up(7).seed, 7);
+    }
+}
@@ -0,0 +1,217 @@
+//! Line protocol of the engine mode, where an external agent plays on the board in the window.
+//! Started with `--engine "<command>"`, the agent runs as a child process and talks over its
+//! standard input and output, one message a line.
+//!
+//! Whenever the agent is to move, the game writes a [`BoardSnapshot`] as a line of JSON, see
+//! `snapshot` for its schema. The agent answers with a move:
+//!
+//! ```text
+//! uncover <x> <y>
+//! flag <x> <y>
+//! ```
+//!
+//! `x` is the column and `y` the row of `BoardSnapshot::tiles`. Once the game is over, the game
+//! writes the last snapshot of the board, whose status tells how it ended, and the first
+//! snapshot of the next board when it comes up. Blank lines and lines starting with `#` are
+//! skipped, so agents may log there; their standard error goes to the console.
+//!
+//! `examples/random_agent.rs` is a complete agent, playing random covered tiles.
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, Write},
+    process::{Child, ChildStdin, Command, Stdio},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::IVec2;
+
+use crate::{save::Action, snapshot::BoardSnapshot};
+
+/// Reasons the agent cannot play
+#[derive(Debug)]
+pub enum EngineError {
+    /// The command could not be started
+    Spawn {
+        command: String,
+        reason: String,
+    },
+    Io(String),
+    /// The agent closed its output, usually by exiting
+    Exited,
+    /// A line that is not a move
+    Move {
+        line: String,
+        reason: String,
+    },
+}
+
+impl Display for EngineError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EngineError::Spawn { command, reason } => {
+                write!(f, "Could not start the agent {command:?}: {reason}")
+            }
+            EngineError::Io(reason) => write!(f, "Lost the agent: {reason}"),
+            EngineError::Exited => write!(f, "The agent exited"),
+            EngineError::Move { line, reason } => write!(f, "Invalid move {line:?}: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for EngineError {}
+
+/// Move written on a line by an agent, `None` for lines to skip
+pub fn parse_move(line: &str) -> Result<Option<Action>, EngineError> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+    let invalid = |reason: &str| EngineError::Move {
+        line: line.to_owned(),
+        reason: reason.to_owned(),
+    };
+
+    let words = line.split_whitespace().collect::<Vec<_>>();
+    let [verb, x, y] = words[..] else {
+        return Err(invalid("expected `uncover <x> <y>` or `flag <x> <y>`"));
+    };
+    let (Ok(x), Ok(y)) = (x.parse(), y.parse()) else {
+        return Err(invalid("coordinates are whole numbers"));
+    };
+    let coord = IVec2::new(x, y);
+    match verb {
+        "uncover" => Ok(Some(Action::Uncover(coord))),
+        "flag" => Ok(Some(Action::Flag(coord))),
+        _ => Err(invalid("moves are `uncover` or `flag`")),
+    }
+}
+
+/// Line of a move, as agents write it
+pub fn format_move(action: Action) -> String {
+    match action {
+        Action::Uncover(coord) => format!("uncover {} {}", coord.x, coord.y),
+        Action::Flag(coord) => format!("flag {} {}", coord.x, coord.y),
+    }
+}
+
+/// An agent process. Its output is read on a thread of its own, so that the game never waits
+/// for a slow agent. The process is killed when dropped
+#[derive(Debug)]
+pub struct Agent {
+    child: Child,
+    stdin: ChildStdin,
+    lines: Mutex<Receiver<io::Result<String>>>,
+}
+
+impl Agent {
+    /// Start `command`, a program followed by its arguments, split on whitespace
+    pub fn spawn(command: &str) -> Result<Self, EngineError> {
+        let spawn_error = |reason: String| EngineError::Spawn {
+            command: command.to_owned(),
+            reason,
+        };
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| spawn_error("empty command".to_owned()))?;
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .spawn()
+            .map_err(|e| spawn_error(e.to_string()))?;
+
+        let (stdin, stdout) = (child.stdin.take(), child.stdout.take());
+        let (Some(stdin), Some(stdout)) = (stdin, stdout) else {
+            child.kill().ok();
+            return Err(spawn_error("no pipes to the process".to_owned()));
+        };
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(stdout).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            child,
+            stdin,
+            lines: Mutex::new(lines),
+        })
+    }
+
+    /// Ask for a move on `snapshot`, or tell the agent how the game ended
+    pub fn send(&mut self, snapshot: &BoardSnapshot) -> Result<(), EngineError> {
+        writeln!(self.stdin, "{}", snapshot.to_json())
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| EngineError::Io(e.to_string()))
+    }
+
+    /// Next move of the agent, `None` while it thinks
+    pub fn poll(&mut self) -> Result<Option<Action>, EngineError> {
+        let lines = self.lines.get_mut().unwrap_or_else(|e| e.into_inner());
+        loop {
+            match lines.try_recv() {
+                Ok(Ok(line)) => {
+                    if let Some(action) = parse_move(&line)? {
+                        return Ok(Some(action));
+                    }
+                }
+                Ok(Err(error)) => return Err(EngineError::Io(error.to_string())),
+                Err(TryRecvError::Empty) => return Ok(None),
+                Err(TryRecvError::Disconnected) => return Err(EngineError::Exited),
+            }
+        }
+    }
+}
+
+impl Drop for Agent {
+    fn drop(&mut self) {
+        self.child.kill().ok();
+        self.child.wait().ok();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{format_move, parse_move, Agent, EngineError};
+    use crate::save::Action;
+
+    #[test]
+    fn test_moves() {
+        let uncover = Action::Uncover(IVec2::new(3, 12));
+        assert_eq!(parse_move(&format_move(uncover)).unwrap(), Some(uncover));
+        assert_eq!(
+            parse_move("  flag 0 7\r").unwrap(),
+            Some(Action::Flag(IVec2::new(0, 7)))
+        );
+        assert_eq!(parse_move("").unwrap(), None);
+        assert_eq!(parse_move("# thinking").unwrap(), None);
+
+        for line in ["uncover 1", "chord 1 2", "flag a b", "uncover 1 2 3"] {
+            assert!(
+                matches!(parse_move(line), Err(EngineError::Move { .. })),
+                "{line}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_spawn() {
+        for command in ["", "  ", "minesweeper-test-no-such-agent"] {
+            assert!(matches!(
+                Agent::spawn(command),
+                Err(EngineError::Spawn { .. })
+            ));
+        }
+    }
+}
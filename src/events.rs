@@ -1,12 +1,283 @@
+//! Events of the game. Tiles are given by their storage coordinate whatever the tiling, see
+//! `topology` for their natural coordinates.
+
 use bevy::prelude::IVec2;
 
+use crate::{
+    import::ImportedBoard,
+    protocol::Emote,
+    resources::{
+        board_options::{BoardOptions, Difficulty},
+        board_state::Cover,
+        toasts::ToastKind,
+    },
+    save::{Achievement, LibrarySort},
+};
+
+/// Every safe tile is uncovered
 #[derive(Debug, Copy, Clone)]
 pub struct BoardClearEvent;
 
+/// The mine at `coord` got uncovered
 #[derive(Debug, Copy, Clone)]
-pub struct MineTriggerEvent;
+pub struct MineTriggerEvent {
+    pub coord: IVec2,
+}
 
+/// Uncover the tile at `coord`
+#[derive(Debug, Copy, Clone)]
+pub struct TileTriggerEvent {
+    pub coord: IVec2,
+}
+
+/// Toggle the flag on the tile at `coord`
 #[derive(Debug, Copy, Clone)]
 pub struct TileMarkEvent {
     pub coord: IVec2,
 }
+
+/// The tile at `coord` is a proven mine and was clicked once, a second click uncovers it anyway.
+/// See `BoardOptions::confirm_proven_mines`
+#[derive(Debug, Copy, Clone)]
+pub struct ProvenMineClickEvent {
+    pub coord: IVec2,
+}
+
+/// Despawn the board and build a new one from the current `BoardOptions`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct BoardRegenerateEvent;
+
+/// Replace the `BoardOptions` and start a new board with them
+#[derive(Debug, Clone)]
+pub struct OptionsApplyEvent {
+    pub options: BoardOptions,
+}
+
+/// Suggest the next move in the hint panel
+#[derive(Debug, Default, Copy, Clone)]
+pub struct HintRequestEvent;
+
+/// Open the insights, or close them when shown
+#[derive(Debug, Default, Copy, Clone)]
+pub struct InsightsToggleEvent;
+
+/// Take back the last move, see `UndoPlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct UndoEvent;
+
+/// Show `emote` over the tile at `coord`, from this player or another one of the session
+#[derive(Debug, Copy, Clone)]
+pub struct TilePingEvent {
+    pub coord: IVec2,
+    pub emote: Emote,
+}
+
+/// Line added to the chat, from this player or another one of the session
+#[derive(Debug, Clone)]
+pub struct ChatEvent {
+    pub from: String,
+    pub text: String,
+}
+
+/// An achievement was unlocked by the game just recorded
+#[derive(Debug, Clone)]
+pub struct AchievementEvent {
+    pub achievement: Achievement,
+}
+
+/// Open the trophies, or close them when shown
+#[derive(Debug, Default, Copy, Clone)]
+pub struct TrophiesToggleEvent;
+
+/// Open the board library, or close it when shown
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LibraryToggleEvent;
+
+/// Order the board library, see `LibraryPlugin`
+#[derive(Debug, Copy, Clone)]
+pub struct LibrarySortEvent(pub LibrarySort);
+
+/// Play the board of the library saved as `file`
+#[derive(Debug, Clone)]
+pub struct LibraryPlayEvent {
+    pub file: String,
+}
+
+/// Open the save slots, or close them when shown
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SlotsToggleEvent;
+
+/// Change to the save slots, see `SlotsPlugin`
+#[derive(Debug, Clone)]
+pub enum SlotEvent {
+    /// Put the game in progress in a new slot
+    Save,
+    /// Play the game of the slot saved as this file, closing the slots
+    Load(String),
+    /// Copy the slot saved as this file to a new one
+    Duplicate(String),
+    /// Delete the slot saved as this file, once confirmed
+    Delete(String),
+}
+
+/// Change the speed of the `GameClock` while a replay plays, or jump in the replay
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum PlaybackEvent {
+    Slower,
+    Faster,
+    /// Closest speed to this multiple of real time
+    Speed(f32),
+    TogglePause,
+    /// Take the next action and pause
+    Step,
+    /// Jump to this many seconds since the start of the replay
+    Seek(f32),
+}
+
+/// Add an annotation to the replay being watched, at the time it is at and on the tile of the
+/// last move played
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayAnnotateEvent {
+    pub text: String,
+}
+
+/// Start editing the board in the sandbox, or resume playing it, see `SandboxPlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct SandboxToggleEvent;
+
+/// Edit of the board in the sandbox, the only way to change a board outside of the rules.
+/// Ignored unless the sandbox is open
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum SandboxEvent {
+    /// Put this cover on the tile, mines included
+    Cover(IVec2, Cover),
+    /// Move the mine of the first tile to the second, a safe one
+    MoveMine(IVec2, IVec2),
+}
+
+/// Open the pause menu, or close it and resume the game
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PauseToggleEvent;
+
+/// Quick action picked in the pause menu, which closes it, see `PausePlugin`. The other new
+/// game buttons send it too, so that leaving a board asks for a confirmation the same way
+#[derive(Debug, Clone)]
+pub enum PauseActionEvent {
+    /// Play the current board again from the start
+    Restart,
+    /// Another board of the current options
+    NewBoard,
+    /// Start a board of this size and mine count, kept in the options
+    Difficulty(Difficulty),
+    /// Open the options, in builds with a window for them
+    Settings,
+    /// Leave the game, the autosave keeps the board for the next launch
+    Quit,
+}
+
+/// Destructive action waiting for the player to confirm it, see `ConfirmPlugin`
+#[derive(Debug, Clone)]
+pub enum ConfirmAction {
+    /// Leave the current board from the pause menu or a new game button
+    Pause(PauseActionEvent),
+    /// Delete the unfinished game offered on launch
+    DiscardSave,
+    /// Delete the save slot of this file
+    DeleteSlot(String),
+    /// Leave the current board for one pasted from the clipboard
+    PlayPasted(ImportedBoard),
+}
+
+/// Ask for a confirmation in a modal dialog before carrying out `action`
+#[derive(Debug, Clone)]
+pub struct ConfirmRequestEvent {
+    pub action: ConfirmAction,
+    pub title: String,
+    pub body: String,
+    /// Label of the button carrying out the action
+    pub confirm: String,
+}
+
+impl ConfirmRequestEvent {
+    pub fn new(
+        action: ConfirmAction,
+        title: impl Into<String>,
+        body: impl Into<String>,
+        confirm: impl Into<String>,
+    ) -> Self {
+        Self {
+            action,
+            title: title.into(),
+            body: body.into(),
+            confirm: confirm.into(),
+        }
+    }
+}
+
+/// Button of the confirmation dialog picked, with the mouse, the keyboard or a gamepad
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct ConfirmAnswerEvent {
+    pub confirmed: bool,
+}
+
+/// The player confirmed the action of a `ConfirmRequestEvent`, nothing is sent when they back
+/// out
+#[derive(Debug, Clone)]
+pub struct ConfirmedEvent(pub ConfirmAction);
+
+/// Play the challenge of the day, see `DailyPlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DailyPlayEvent;
+
+/// Play a practice board like the challenge of the day, see `DailyPlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct DailyWarmUpEvent;
+
+/// Attach a note to a finished daily challenge, an empty one removes it. See `DailyPlugin`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyNoteEvent {
+    /// `YYYY-MM-DD` in UTC
+    pub date: String,
+    pub note: String,
+}
+
+/// Play the puzzle of the week, see `WeeklyPlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct WeeklyPlayEvent;
+
+/// Add the mine layout of the current board to the library
+#[derive(Debug, Default, Copy, Clone)]
+pub struct LibraryExportEvent;
+
+/// Copy the result of the game just finished to the clipboard, the board file after it with
+/// `board`, see `SharePlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct ShareEvent {
+    pub board: bool,
+}
+
+/// Read a board from the clipboard and offer to play it, see `PastePlugin`
+#[derive(Debug, Default, Copy, Clone)]
+pub struct PasteBoardEvent;
+
+/// Show a toast in the corner of the screen, see `ToastPlugin`
+#[derive(Debug, Clone)]
+pub struct ToastEvent {
+    pub kind: ToastKind,
+    pub title: String,
+    pub body: String,
+}
+
+impl ToastEvent {
+    pub fn new(kind: ToastKind, title: impl Into<String>, body: impl Into<String>) -> Self {
+        Self {
+            kind,
+            title: title.into(),
+            body: body.into(),
+        }
+    }
+}
+
+/// The retry button of the `ErrorScreen` was clicked
+#[derive(Debug, Default, Copy, Clone)]
+pub struct RetryEvent;
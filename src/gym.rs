@@ -0,0 +1,207 @@
+//! Reinforcement learning environment on the rules of the game, without Bevy running: the
+//! board, its rules and its observations are the ones the window plays with. Observations are
+//! [`BoardSnapshot`]s, which never show the mines under covered tiles.
+//!
+//! Rewards follow the progress of the game. Every safe tile revealed earns its share of
+//! [`PROGRESS_REWARD`], winning adds [`WIN_REWARD`], a mine costs [`MINE_REWARD`] and a move
+//! changing nothing costs [`IDLE_REWARD`], so that agents do not stall.
+
+use crate::{
+    resources::{
+        board::{TileMap, TileState},
+        board_options::{BoardOptions, OptionsError},
+        board_scratch::BoardScratch,
+        board_state::BoardState,
+        rule_set::{Punishment, RuleSet},
+    },
+    rng::{self, Rng},
+    save::Action,
+    snapshot::BoardSnapshot,
+};
+
+/// Earned over a whole game by revealing its safe tiles
+pub const PROGRESS_REWARD: f32 = 1.0;
+pub const WIN_REWARD: f32 = 1.0;
+/// For every mine uncovered, ending the game unless the rules show the mine and go on
+pub const MINE_REWARD: f32 = -1.0;
+/// For a move on a revealed tile, out of the board or forbidden by the rules
+pub const IDLE_REWARD: f32 = -0.05;
+
+/// Games on boards of the given options, generated from a seed so that runs repeat
+pub struct MinesweeperEnv {
+    options: BoardOptions,
+    rules: RuleSet,
+    /// Seeds of the boards
+    rng: Box<dyn Rng>,
+    state: BoardState,
+    // reused by every step, agents play millions of them
+    scratch: BoardScratch,
+    done: bool,
+}
+
+impl MinesweeperEnv {
+    /// Environment playing the mode, size and tiling of `options`, with boards drawn by the
+    /// backend of the run, see `rng`, or the error of options that fail `BoardOptions::validate`.
+    /// Call [`Self::reset`] for the first observation
+    pub fn new(options: &BoardOptions, seed: u64) -> Result<Self, OptionsError> {
+        options.validate()?;
+        let mut rng = rng::seeded(seed);
+        let state = Self::board(options, rng.next_u64());
+        Ok(Self {
+            options: options.clone(),
+            rules: RuleSet::from_options(options),
+            rng,
+            state,
+            scratch: BoardScratch::default(),
+            done: false,
+        })
+    }
+
+    fn board(options: &BoardOptions, seed: u64) -> BoardState {
+        let dim = options.difficulty.dim;
+        let mut tile_map = TileMap::seeded(dim.x, dim.y, options.difficulty.n_mines, seed)
+            .expect("options validated by `new`")
+            .with_topology(options.topology());
+        BoardState::new(&mut tile_map)
+    }
+
+    /// Start a game on a new board
+    pub fn reset(&mut self) -> BoardSnapshot {
+        self.state = Self::board(&self.options, self.rng.next_u64());
+        self.done = false;
+        self.observation()
+    }
+
+    /// Play `action`, returning the observation after it, its reward and whether the game is
+    /// over. Once it is, every step returns the last observation with no reward
+    pub fn step(&mut self, action: Action) -> (BoardSnapshot, f32, bool) {
+        if self.done {
+            return (self.observation(), 0.0, true);
+        }
+
+        let n_safe = self.state.dim().x * self.state.dim().y - self.state.n_mines() as i32;
+        let reward = match action {
+            Action::Flag(coord) if self.rules.flags && self.state.toggle_flag(coord) => 0.0,
+            Action::Flag(_) => IDLE_REWARD,
+            Action::Uncover(coord) => {
+                let revealed = self
+                    .state
+                    .click_into(coord, self.rules.chords, &mut self.scratch);
+                let mines = revealed
+                    .iter()
+                    .filter(|&&(_, tile)| tile == TileState::Mine)
+                    .count();
+                let safe = revealed.len() - mines;
+                if mines > 0 && self.rules.mistakes == Punishment::Lose {
+                    self.done = true;
+                    self.state.reveal_mines();
+                }
+                if revealed.is_empty() {
+                    IDLE_REWARD
+                } else {
+                    PROGRESS_REWARD * safe as f32 / n_safe.max(1) as f32
+                        + MINE_REWARD * mines as f32
+                }
+            }
+        };
+
+        let won = !self.done && self.rules.is_won(&self.state);
+        self.done |= won;
+        let reward = if won { reward + WIN_REWARD } else { reward };
+        (self.observation(), reward, self.done)
+    }
+
+    /// What the agent sees of the current board
+    pub fn observation(&self) -> BoardSnapshot {
+        BoardSnapshot::capture(&self.state)
+    }
+
+    /// The board itself, mines included, for logging and debugging agents
+    pub fn state(&self) -> &BoardState {
+        &self.state
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.done
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{MinesweeperEnv, IDLE_REWARD, MINE_REWARD, WIN_REWARD};
+    use crate::{
+        resources::{
+            board::TileState,
+            board_options::{BoardOptions, Difficulty},
+        },
+        save::Action,
+        snapshot::SnapshotStatus,
+    };
+
+    fn easy_env() -> MinesweeperEnv {
+        let options = BoardOptions {
+            difficulty: Difficulty::EASY,
+            ..Default::default()
+        };
+        MinesweeperEnv::new(&options, 0x5eed).unwrap()
+    }
+
+    fn coords(env: &MinesweeperEnv) -> Vec<IVec2> {
+        let dim = env.state().dim();
+        (0..dim.y)
+            .flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)))
+            .collect()
+    }
+
+    #[test]
+    fn test_win() {
+        let mut env = easy_env();
+        let first = env.reset();
+        assert_eq!(first.status, SnapshotStatus::Playing);
+        // the same seed gives the same boards
+        assert_eq!(easy_env().reset(), first);
+
+        let safe = coords(&env)
+            .into_iter()
+            .filter(|&coord| env.state().tile(coord) != Some(TileState::Mine))
+            .collect::<Vec<_>>();
+        let mut total = 0.0;
+        for coord in safe {
+            let (observation, reward, done) = env.step(Action::Uncover(coord));
+            // tiles revealed by an earlier flood are idle moves
+            if reward != IDLE_REWARD {
+                total += reward;
+            }
+            if done {
+                assert_eq!(observation.status, SnapshotStatus::Won);
+                break;
+            }
+        }
+        assert!(env.is_done());
+        assert!((total - (1.0 + WIN_REWARD)).abs() < 1e-4, "{total}");
+        assert_eq!(env.step(Action::Uncover(IVec2::ZERO)).1, 0.0);
+    }
+
+    #[test]
+    fn test_loss() {
+        let mut env = easy_env();
+        env.reset();
+        let mine = coords(&env)
+            .into_iter()
+            .find(|&coord| env.state().tile(coord) == Some(TileState::Mine))
+            .unwrap();
+
+        assert_eq!(env.step(Action::Flag(mine)).1, 0.0);
+        assert_eq!(env.step(Action::Flag(IVec2::new(-1, 0))).1, IDLE_REWARD);
+        // flagged tiles do not uncover
+        assert_eq!(env.step(Action::Uncover(mine)).1, IDLE_REWARD);
+        env.step(Action::Flag(mine));
+
+        let (observation, reward, done) = env.step(Action::Uncover(mine));
+        assert_eq!((reward, done), (MINE_REWARD, true));
+        assert_eq!(observation.status, SnapshotStatus::Lost);
+        assert!(!env.reset().tiles.is_empty() && !env.is_done());
+    }
+}
@@ -0,0 +1,308 @@
+//! Hand-made puzzles in the manner of Hexcells: mines on a grid of hexagons, some tiles revealed
+//! from the start and line hints giving the mines of a row. [`Puzzle::parse`] reads them from
+//! text, [`Puzzle::graph`] maps what the player is given onto the constraints of the solver,
+//! which tells whether the puzzle can be solved without a guess.
+
+use std::fmt::Display;
+
+use bevy::prelude::IVec2;
+
+use crate::solver::{ConstraintGraph, Knowledge};
+
+/// First line of a puzzle, see [`Puzzle::parse`]
+pub const HEADER: &str = "hexcells";
+
+/// Neighbors of the tiles of even rows, odd rows being shifted half a tile right
+const EVEN_NEIGHBORS: [[i32; 2]; 6] = [[-1, 0], [1, 0], [-1, -1], [0, -1], [-1, 1], [0, 1]];
+
+/// Neighbors of the tiles of odd rows
+const ODD_NEIGHBORS: [[i32; 2]; 6] = [[-1, 0], [1, 0], [0, -1], [1, -1], [0, 1], [1, 1]];
+
+/// Reasons a puzzle cannot be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HexcellsError {
+    /// The first line is not [`HEADER`]
+    Header,
+    /// A character standing for no tile
+    Tile {
+        row: u32,
+        tile: char,
+    },
+    /// A row with another number of tiles than the first one
+    Width {
+        row: u32,
+        expected: u32,
+        found: u32,
+    },
+    /// A line hint that is no number
+    Hint {
+        row: u32,
+        hint: String,
+    },
+    /// A line hint that is not the number of mines of its row
+    WrongHint {
+        row: u32,
+        hint: u32,
+        mines: u32,
+    },
+    /// A mine revealed from the start, which the game has no way to show
+    RevealedMine {
+        row: u32,
+    },
+    NoMines,
+}
+
+impl Display for HexcellsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HexcellsError::Header => write!(f, "the first line must be `{HEADER}`"),
+            HexcellsError::Tile { row, tile } => write!(f, "unknown tile {tile:?} on row {row}"),
+            HexcellsError::Width {
+                row,
+                expected,
+                found,
+            } => write!(f, "expected {expected} tiles on row {row}, got {found}"),
+            HexcellsError::Hint { row, hint } => write!(f, "line hint {hint:?} of row {row}"),
+            HexcellsError::WrongHint { row, hint, mines } => {
+                write!(f, "row {row} has {mines} mines, its line hint says {hint}")
+            }
+            HexcellsError::RevealedMine { row } => write!(
+                f,
+                "mine revealed from the start on row {row}, which is not supported"
+            ),
+            HexcellsError::NoMines => write!(f, "no mines, write them as `x`"),
+        }
+    }
+}
+
+/// Mines of a row, as a line hint gives them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Line {
+    pub row: u32,
+    pub mines: u32,
+}
+
+/// A puzzle on hexagons, in offset coordinates: tile `[x, y]` is the `x`th of row `y`, the first
+/// row on top and odd rows shifted half a tile right
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub width: u32,
+    pub height: u32,
+    pub mines: Vec<IVec2>,
+    /// Safe tiles revealed from the start
+    pub revealed: Vec<IVec2>,
+    /// Holes in the puzzle, where there is no tile at all
+    pub gaps: Vec<IVec2>,
+    pub lines: Vec<Line>,
+}
+
+impl Puzzle {
+    /// Read a puzzle: after a first line `hexcells`, one line per row, `x` for a mine, `o` for a
+    /// safe tile to uncover, `O` for a safe tile revealed from the start and `.` for a gap.
+    /// Whitespace between tiles is ignored, so that odd rows can be indented, and lines starting
+    /// with `#` are comments. A row may end with `: n`, the line hint of its `n` mines
+    pub fn parse(source: &str) -> Result<Self, HexcellsError> {
+        let mut lines = source
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'));
+        if !(lines.next()).is_some_and(|header| header.eq_ignore_ascii_case(HEADER)) {
+            return Err(HexcellsError::Header);
+        }
+
+        let mut puzzle = Self {
+            width: 0,
+            height: 0,
+            mines: Vec::new(),
+            revealed: Vec::new(),
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        for (row, line) in (0..).zip(lines) {
+            let (tiles, hint) = match line.split_once(':') {
+                Some((tiles, hint)) => (tiles, Some(hint.trim())),
+                None => (line, None),
+            };
+            let tiles = tiles.chars().filter(|c| !c.is_whitespace());
+            let mut mines = 0;
+            let mut found = 0;
+            for (x, tile) in (0..).zip(tiles) {
+                let coord = IVec2::new(x, row as i32);
+                match tile {
+                    'x' => {
+                        puzzle.mines.push(coord);
+                        mines += 1;
+                    }
+                    'o' => {}
+                    'O' => puzzle.revealed.push(coord),
+                    '.' => puzzle.gaps.push(coord),
+                    'X' => return Err(HexcellsError::RevealedMine { row }),
+                    tile => return Err(HexcellsError::Tile { row, tile }),
+                }
+                found += 1;
+            }
+            if row == 0 {
+                puzzle.width = found;
+            }
+            if found != puzzle.width {
+                return Err(HexcellsError::Width {
+                    row,
+                    expected: puzzle.width,
+                    found,
+                });
+            }
+
+            if let Some(hint) = hint {
+                let Ok(hint) = hint.parse() else {
+                    let hint = hint.to_owned();
+                    return Err(HexcellsError::Hint { row, hint });
+                };
+                if hint != mines {
+                    return Err(HexcellsError::WrongHint { row, hint, mines });
+                }
+                puzzle.lines.push(Line { row, mines });
+            }
+            puzzle.height += 1;
+        }
+
+        if puzzle.mines.is_empty() {
+            return Err(HexcellsError::NoMines);
+        }
+        Ok(puzzle)
+    }
+
+    fn contains(&self, coord: IVec2) -> bool {
+        let dim = IVec2::new(self.width as i32, self.height as i32);
+        coord.cmpge(IVec2::ZERO).all() && coord.cmplt(dim).all() && !self.gaps.contains(&coord)
+    }
+
+    /// Tiles of the puzzle, without the gaps
+    pub fn tiles(&self) -> impl Iterator<Item = IVec2> + '_ {
+        let (width, height) = (self.width as i32, self.height as i32);
+        (0..height)
+            .flat_map(move |y| (0..width).map(move |x| IVec2::new(x, y)))
+            .filter(|&coord| self.contains(coord))
+    }
+
+    /// Tiles sharing an edge with the tile at `coord`
+    pub fn neighbors(&self, coord: IVec2) -> impl Iterator<Item = IVec2> + '_ {
+        let deltas = match coord.y.rem_euclid(2) {
+            0 => EVEN_NEIGHBORS,
+            _ => ODD_NEIGHBORS,
+        };
+        deltas
+            .into_iter()
+            .map(move |delta| coord + IVec2::from(delta))
+            .filter(|&neighbor| self.contains(neighbor))
+    }
+
+    /// Number shown by the safe tile at `coord`
+    pub fn number(&self, coord: IVec2) -> u8 {
+        let mines = self.neighbors(coord).filter(|n| self.mines.contains(n));
+        mines.count() as u8
+    }
+
+    /// What the player is given as constraints of the solver: the revealed tiles counting their
+    /// neighbors and the line hints. Gaps are revealed tiles counting nothing
+    pub fn graph(&self) -> ConstraintGraph {
+        let mut graph = ConstraintGraph::new(self.width, self.height, self.mines.len() as u32);
+        for &gap in &self.gaps {
+            graph.reveal_on(gap, [], 0);
+        }
+        for &coord in &self.revealed {
+            graph.reveal_on(coord, self.neighbors(coord), self.number(coord));
+        }
+        for line in &self.lines {
+            let row = self.tiles().filter(|coord| coord.y == line.row as i32);
+            graph.constrain(row, line.mines as u8);
+        }
+        graph
+    }
+
+    /// Whether every safe tile can be uncovered without a guess, revealing the tiles the solver
+    /// proves safe one after the other
+    pub fn is_solvable(&self) -> bool {
+        let mut graph = self.graph();
+        loop {
+            let safe = graph.analyze().safe().collect::<Vec<_>>();
+            if safe.is_empty() {
+                break;
+            }
+            for coord in safe {
+                graph.reveal_on(coord, self.neighbors(coord), self.number(coord));
+            }
+        }
+        self.tiles()
+            .filter(|coord| !self.mines.contains(coord))
+            .all(|coord| graph.knowledge(coord) != Knowledge::Covered)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{HexcellsError, Line, Puzzle};
+
+    #[test]
+    fn test_parse() {
+        let puzzle = Puzzle::parse(
+            "# a ring of mines around a revealed tile
+            hexcells
+            o x x . : 2
+             x O x o : 2
+            o x x o
+            ",
+        )
+        .unwrap();
+        assert_eq!((puzzle.width, puzzle.height), (4, 3));
+        assert_eq!(puzzle.mines.len(), 6);
+        assert_eq!(puzzle.revealed, [IVec2::new(1, 1)]);
+        assert_eq!(puzzle.gaps, [IVec2::new(3, 0)]);
+        assert_eq!(
+            puzzle.lines,
+            [Line { row: 0, mines: 2 }, Line { row: 1, mines: 2 }]
+        );
+        assert_eq!(puzzle.number(IVec2::new(1, 1)), 6);
+        // the gap is no tile, its neighbors count one less
+        assert_eq!(puzzle.neighbors(IVec2::new(3, 1)).count(), 2);
+        assert_eq!(puzzle.tiles().count(), 11);
+
+        let error = |source| Puzzle::parse(source).unwrap_err();
+        assert_eq!(error("o x\n"), HexcellsError::Header);
+        assert_eq!(
+            error("hexcells\nx o : 2\n"),
+            HexcellsError::WrongHint {
+                row: 0,
+                hint: 2,
+                mines: 1
+            }
+        );
+        assert!(matches!(
+            error("hexcells\nx o : two\n"),
+            HexcellsError::Hint { row: 0, .. }
+        ));
+        assert!(matches!(
+            error("hexcells\nx o\no\n"),
+            HexcellsError::Width { row: 1, .. }
+        ));
+        assert_eq!(
+            error("hexcells\no o\nX o\n"),
+            HexcellsError::RevealedMine { row: 1 }
+        );
+        assert_eq!(
+            error("hexcells\nx ?\n"),
+            HexcellsError::Tile { row: 0, tile: '?' }
+        );
+        assert_eq!(error("hexcells\no o\n"), HexcellsError::NoMines);
+    }
+
+    #[test]
+    fn test_solvable() {
+        // the 1 sees [0, 0] and [2, 0], the line hint then leaves [3, 0] safe
+        let with_hint = Puzzle::parse("hexcells\nx O o o : 1\n . . o o\n").unwrap();
+        assert!(with_hint.is_solvable());
+        let without = Puzzle::parse("hexcells\nx O o o\n . . o o\n").unwrap();
+        assert!(!without.is_solvable());
+    }
+}
@@ -0,0 +1,405 @@
+//! Boards read from text written by other parts of the game: save files, ASCII boards as
+//! `BoardState::to_ascii` writes them, and the results copied by `share`, whose seed gives the
+//! board back. Used by the `solve` subcommand and to play a board pasted from the clipboard.
+//!
+//! Hand-made puzzles in the manner of Hexcells are read too, see [`crate::hexcells`], from the
+//! clipboard or as `.hexcells` files of the library.
+
+use std::fmt::Display;
+
+use bevy::prelude::IVec2;
+
+use crate::{
+    hexcells::{self, HexcellsError, Puzzle},
+    resources::{
+        board_options::{Difficulty, GameMode},
+        board_seed::SeededBoard,
+    },
+    save::{self, SaveError, SaveGame},
+    topology::{Hex, Topology},
+};
+
+/// Tiles of the grids of `share`, which show no mines
+const GRID_TILES: [char; 4] = ['⬜', '🟩', '🚩', '💣'];
+
+/// Board found in a text
+#[derive(Debug, Clone)]
+pub enum ImportedBoard {
+    /// Mine layout, with the tiles revealed so far
+    Board(SaveGame),
+    Seed(SeededBoard),
+}
+
+impl ImportedBoard {
+    /// `Expert board from seed 0x…` or `16x16 board with 40 mines`
+    pub fn describe(&self) -> String {
+        match self {
+            ImportedBoard::Board(board) => format!(
+                "{}x{} board with {} mines",
+                board.width,
+                board.height,
+                board.mines.len()
+            ),
+            ImportedBoard::Seed(board) => format!(
+                "{} board from seed {:#018x}",
+                board.difficulty.name(),
+                board.seed
+            ),
+        }
+    }
+}
+
+/// Why no board could be read from a text
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    Empty,
+    /// A save file that does not load
+    File(SaveError),
+    /// An ASCII board with rows of different lengths, or without mines
+    Ascii(String),
+    /// A result whose seed or difficulty does not parse
+    Result(String),
+    /// A grid of emoji alone, which holds no mines
+    GridOnly,
+    /// A Hexcells-like puzzle with unknown tiles, rows of different lengths or wrong line hints
+    Hexcells(HexcellsError),
+    Unknown,
+}
+
+impl Display for ImportError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportError::Empty => write!(f, "There is no text to read a board from"),
+            ImportError::File(error) => write!(f, "Invalid board file: {error}"),
+            ImportError::Ascii(reason) => write!(f, "Invalid ASCII board: {reason}"),
+            ImportError::Result(reason) => write!(f, "Invalid result: {reason}"),
+            ImportError::GridOnly => write!(
+                f,
+                "A grid of emoji shows no mines, copy the result with its seed or with the board"
+            ),
+            ImportError::Hexcells(reason) => write!(f, "Invalid Hexcells puzzle: {reason}"),
+            ImportError::Unknown => write!(f, "No board file, ASCII board or result in the text"),
+        }
+    }
+}
+
+impl std::error::Error for ImportError {}
+
+/// Board of a save file, an ASCII board or a shared result. Seeded boards are played in `mode`,
+/// results do not tell theirs
+pub fn parse_board(text: &str, mode: GameMode) -> Result<ImportedBoard, ImportError> {
+    let text = text.trim();
+    if text.is_empty() {
+        return Err(ImportError::Empty);
+    }
+    // puzzles may start with comments
+    let first = text
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'));
+    if first.is_some_and(|line| line.eq_ignore_ascii_case(hexcells::HEADER)) {
+        return parse_hexcells(text).map(ImportedBoard::Board);
+    }
+
+    // a result copied with its board has the file after the grid, the file is the closer match
+    let file = text
+        .match_indices('\n')
+        .map(|(i, _)| i + 1)
+        .chain([0])
+        .filter(|&i| text[i..].starts_with("//") || text[i..].starts_with('('))
+        .min();
+    if let Some(start) = file {
+        let board = save::from_str::<SaveGame>(&text[start..]).map_err(ImportError::File)?;
+        // checked now, rather than when the board is built
+        board.restore().map_err(ImportError::File)?;
+        return Ok(ImportedBoard::Board(SaveGame {
+            flagged: Vec::new(),
+            ..board
+        }));
+    }
+
+    if let Some(seed) = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Seed "))
+    {
+        return parse_result(text, seed, mode).map(ImportedBoard::Seed);
+    }
+
+    let ascii = |c: char| c.is_whitespace() || "#F?*.12345678".contains(c);
+    if text.chars().all(ascii) {
+        return parse_ascii(text).map(ImportedBoard::Board);
+    }
+    if text.chars().any(|c| GRID_TILES.contains(&c)) {
+        return Err(ImportError::GridOnly);
+    }
+    Err(ImportError::Unknown)
+}
+
+/// Seed of a result, on the difficulty named in its headline, see `GameResult::headline`
+fn parse_result(text: &str, seed: &str, mode: GameMode) -> Result<SeededBoard, ImportError> {
+    let seed = seed.trim();
+    let parsed = match seed.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => seed.parse(),
+    };
+    let seed = parsed.map_err(|e| ImportError::Result(format!("seed {seed:?}: {e}")))?;
+
+    let name = text
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("Minesweeper "))
+        .and_then(|headline| headline.split(',').next())
+        .ok_or_else(|| ImportError::Result("no difficulty before the seed".to_owned()))?;
+    let difficulty = Difficulty::from_name(name)
+        .ok_or_else(|| ImportError::Result(format!("unknown difficulty {name:?}")))?;
+    Ok(SeededBoard {
+        seed,
+        mode,
+        difficulty,
+    })
+}
+
+/// Mines of an ASCII board, `*` where `BoardState::to_ascii` writes them with its solution. The
+/// revealed tiles are kept when some tiles are still covered, a solution alone starts from
+/// scratch
+pub fn parse_ascii(source: &str) -> Result<SaveGame, ImportError> {
+    let rows = source
+        .lines()
+        .map(|line| line.split_whitespace().collect::<String>())
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>();
+    let width = rows.first().map_or(0, |row| row.chars().count()) as u32;
+    let height = rows.len() as u32;
+
+    let tiles = |tile: char| {
+        rows.iter().enumerate().flat_map(move |(y, row)| {
+            row.chars()
+                .enumerate()
+                .filter(move |&(_, c)| c == tile)
+                .map(move |(x, _)| IVec2::new(x as i32, y as i32))
+        })
+    };
+    let mines = tiles('*').collect::<Vec<_>>();
+    if mines.is_empty() {
+        return Err(ImportError::Ascii("no mines, write them as `*`".to_owned()));
+    }
+    let revealed = parse_mask(source, width, height).map_err(ImportError::Ascii)?;
+    // a solution has no covered tiles, nothing would be left to play
+    let covered = source.contains(&['#', 'F', '?'][..]);
+
+    let board = SaveGame {
+        width,
+        height,
+        revealed: revealed
+            .into_iter()
+            .filter(|coord| covered && !mines.contains(coord))
+            .collect(),
+        mines,
+        flagged: Vec::new(),
+        topology: None,
+        gaps: Vec::new(),
+        lines: Vec::new(),
+    };
+    board.restore().map_err(ImportError::File)?;
+    Ok(board)
+}
+
+/// Board of a Hexcells-like puzzle, see [`Puzzle::parse`], on the hex tiling which lays rows
+/// out as puzzles do. The gaps and line hints are kept, the game shows the hints along the rows
+pub fn parse_hexcells(source: &str) -> Result<SaveGame, ImportError> {
+    let puzzle = Puzzle::parse(source).map_err(ImportError::Hexcells)?;
+    let board = SaveGame {
+        width: puzzle.width,
+        height: puzzle.height,
+        mines: puzzle.mines,
+        revealed: puzzle.revealed,
+        flagged: Vec::new(),
+        topology: Some(Hex.name().to_owned()),
+        gaps: puzzle.gaps,
+        lines: puzzle.lines,
+    };
+    board.restore().map_err(ImportError::File)?;
+    Ok(board)
+}
+
+/// Revealed tiles of an ASCII board, as written by `BoardState::to_ascii`: one line per row, the
+/// first row on top, with `#`, `F` or `?` for covered tiles and anything else for revealed ones.
+/// Whitespace between tiles is ignored
+pub fn parse_mask(source: &str, width: u32, height: u32) -> Result<Vec<IVec2>, String> {
+    let rows = source
+        .lines()
+        .map(|line| {
+            line.chars()
+                .filter(|c| !c.is_whitespace())
+                .collect::<Vec<_>>()
+        })
+        .filter(|row| !row.is_empty())
+        .collect::<Vec<_>>();
+    if rows.len() != height as usize {
+        return Err(format!(
+            "expected {height} rows for a {width}x{height} board, got {}",
+            rows.len()
+        ));
+    }
+
+    let mut revealed = Vec::new();
+    for (y, row) in rows.iter().enumerate() {
+        if row.len() != width as usize {
+            return Err(format!(
+                "expected {width} tiles on row {y}, got {}",
+                row.len()
+            ));
+        }
+        revealed.extend(
+            row.iter()
+                .enumerate()
+                .filter(|(_, c)| !matches!(c, '#' | 'F' | '?'))
+                .map(|(x, _)| IVec2::new(x as i32, y as i32)),
+        );
+    }
+    Ok(revealed)
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{parse_board, parse_hexcells, parse_mask, ImportError, ImportedBoard};
+    use crate::{
+        hexcells::{HexcellsError, Line},
+        resources::{
+            board::TileState,
+            board_options::{Difficulty, GameMode},
+            board_state::Cover,
+        },
+        save::{self, SaveGame},
+        share::GameResult,
+    };
+
+    fn board() -> SaveGame {
+        SaveGame {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 1)],
+            revealed: vec![IVec2::new(2, 0)],
+            flagged: vec![IVec2::new(0, 1)],
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_mask() {
+        let revealed = parse_mask("F 2 .\n\n# ? 1\n", 3, 2).unwrap();
+        assert_eq!(
+            revealed,
+            [IVec2::new(1, 0), IVec2::new(2, 0), IVec2::new(2, 1)]
+        );
+        assert!(parse_mask("# #\n", 3, 1).is_err());
+        assert!(parse_mask("# # #\n", 3, 2).is_err());
+    }
+
+    #[test]
+    fn test_parse_board() {
+        let classic = GameMode::Classic;
+        let Ok(ImportedBoard::Board(pasted)) = parse_board(&save::to_string(&board()), classic)
+        else {
+            panic!("the save file should parse");
+        };
+        assert_eq!(
+            (pasted.mines, pasted.revealed),
+            (board().mines, board().revealed)
+        );
+        assert!(pasted.flagged.is_empty());
+
+        let (_, state) = board().restore().unwrap();
+        let result = GameResult::new(&state, true, 5.0, Some(0xbeef));
+        let Ok(ImportedBoard::Seed(seeded)) = parse_board(&result.to_text(false), classic) else {
+            panic!("the result should parse");
+        };
+        assert_eq!(seeded.seed, 0xbeef);
+        assert_eq!(seeded.difficulty.name(), "3x2/1");
+        assert!(matches!(
+            parse_board(&result.to_text(true), classic),
+            Ok(ImportedBoard::Board(_))
+        ));
+        assert_eq!(
+            parse_board(&result.grid, classic).unwrap_err(),
+            ImportError::GridOnly
+        );
+
+        // a solution starts from scratch, a board in progress keeps its revealed tiles
+        let Ok(ImportedBoard::Board(ascii)) = parse_board("1 1 .\n* 1 .\n", classic) else {
+            panic!("the ASCII board should parse");
+        };
+        assert_eq!(ascii.mines, [IVec2::new(0, 1)]);
+        assert!(ascii.revealed.is_empty());
+        let Ok(ImportedBoard::Board(ascii)) = parse_board("# # .\n* # #\n", classic) else {
+            panic!("the ASCII board should parse");
+        };
+        assert_eq!(ascii.revealed, [IVec2::new(2, 0)]);
+    }
+
+    #[test]
+    fn test_parse_hexcells() {
+        let puzzle = "# a ring of mines around a revealed tile
+            hexcells
+            o x x . : 2
+             x O x o : 2
+            o x x o
+        ";
+        let board = parse_hexcells(puzzle).unwrap();
+        assert_eq!((board.width, board.height), (4, 3));
+        assert_eq!(board.topology.as_deref(), Some("hex"));
+        assert_eq!(board.mines.len(), 6);
+        assert_eq!(board.revealed, [IVec2::new(1, 1)]);
+        assert_eq!(board.gaps, [IVec2::new(3, 0)]);
+        assert_eq!(
+            board.lines,
+            [Line { row: 0, mines: 2 }, Line { row: 1, mines: 2 }]
+        );
+        // the revealed tile sees the whole ring, the gap is revealed without a number
+        let (_, state) = board.restore().unwrap();
+        assert_eq!(state.tile([1, 1]), Some(TileState::Clear(6)));
+        assert_eq!(state.cover([3, 0]), Some(Cover::Revealed));
+        assert!(state.is_gap([3, 0]));
+        let Ok(ImportedBoard::Board(pasted)) = parse_board(puzzle, GameMode::Classic) else {
+            panic!("the puzzle should parse");
+        };
+        assert_eq!(pasted, board);
+
+        assert_eq!(
+            parse_hexcells("hexcells\nx ?\n").unwrap_err(),
+            ImportError::Hexcells(HexcellsError::Tile { row: 0, tile: '?' })
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let classic = GameMode::Classic;
+        let error = |text| parse_board(text, classic).unwrap_err();
+        assert_eq!(error("  \n"), ImportError::Empty);
+        assert!(matches!(error("(version: 99)"), ImportError::File(_)));
+        assert!(matches!(error("* 1\n1\n"), ImportError::Ascii(_)));
+        assert!(matches!(error("1 1\n1 1\n"), ImportError::Ascii(_)));
+        assert!(matches!(
+            error("Minesweeper Giant, cleared in 01:00\nSeed 0x12"),
+            ImportError::Result(_)
+        ));
+        assert!(matches!(
+            error("Minesweeper Expert, lost after 00:03\nSeed 0xzz"),
+            ImportError::Result(_)
+        ));
+        assert_eq!(error("hello"), ImportError::Unknown);
+
+        assert_eq!(
+            Difficulty::from_name("expert").map(|d| d.name()),
+            Some("Expert".to_owned())
+        );
+        assert_eq!(
+            Difficulty::from_name("12x7/20").map(|d| d.name()),
+            Some("12x7/20".to_owned())
+        );
+        assert!(Difficulty::from_name("12x7").is_none());
+    }
+}
@@ -0,0 +1,237 @@
+//! Trends computed from the local session log. Everything stays on this machine.
+
+use std::collections::{BTreeMap, HashMap};
+
+use crate::save::{LossKind, SessionLog};
+
+/// Days covered by the insights, today included
+pub const WINDOW_DAYS: usize = 30;
+
+/// Seconds in a day, days are counted in UTC
+pub const DAY: u64 = 24 * 60 * 60;
+
+/// Activity of a calendar day
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DayActivity {
+    #[default]
+    Idle,
+    /// Only lost games
+    Played,
+    /// At least one win
+    Won,
+}
+
+/// Daily challenge played on a day of the calendar, warm-ups aside
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DailyResult {
+    /// Index in [`Insights::calendar`]
+    pub slot: usize,
+    /// `YYYY-MM-DD` in UTC
+    pub date: String,
+    pub won: bool,
+    /// See `Stats::daily_notes`
+    pub note: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Insights {
+    /// Games of the window
+    pub games: usize,
+    pub wins: usize,
+    /// Wins without flags
+    pub nf_wins: usize,
+    /// Average duration of the wins of the window, in seconds
+    pub average_win_time: Option<f32>,
+    /// Average efficiency of the wins of every day of the window, oldest first, `None` on days
+    /// without a win. A lost board was never fully solved, so its efficiency means little
+    pub efficiency: [Option<f32>; WINDOW_DAYS],
+    /// The most frequent way of losing, with its count
+    pub common_loss: Option<(LossKind, usize)>,
+    /// Oldest first, today last
+    pub calendar: [DayActivity; WINDOW_DAYS],
+    /// Daily challenges of the window, oldest first
+    pub dailies: Vec<DailyResult>,
+}
+
+impl Insights {
+    /// Insights of the [`WINDOW_DAYS`] days up to `now`, in seconds since the Unix epoch
+    pub fn compute(log: &SessionLog, now: u64) -> Self {
+        let today = now / DAY;
+        let slot = |finished: u64| {
+            let days_ago = today.checked_sub(finished / DAY)? as usize;
+            (days_ago < WINDOW_DAYS).then(|| WINDOW_DAYS - 1 - days_ago)
+        };
+        let games = log
+            .games
+            .iter()
+            .filter_map(|game| Some((slot(game.finished)?, game)))
+            .collect::<Vec<_>>();
+
+        let win_times = games
+            .iter()
+            .filter(|(_, game)| game.won())
+            .map(|(_, game)| game.duration)
+            .collect::<Vec<_>>();
+
+        let mut efficiency = [(0.0, 0); WINDOW_DAYS];
+        let mut calendar = [DayActivity::Idle; WINDOW_DAYS];
+        let mut losses = HashMap::<LossKind, usize>::new();
+        let mut dailies = Vec::<DailyResult>::new();
+        for &(slot, game) in &games {
+            if let (Some(date), false) = (&game.daily, game.warm_up) {
+                dailies.push(DailyResult {
+                    slot,
+                    date: date.clone(),
+                    won: game.won(),
+                    note: None,
+                });
+            }
+            if game.won() {
+                efficiency[slot].0 += game.efficiency();
+                efficiency[slot].1 += 1;
+            }
+            calendar[slot] = match (calendar[slot], game.won()) {
+                (_, true) | (DayActivity::Won, _) => DayActivity::Won,
+                _ => DayActivity::Played,
+            };
+            if let Some(loss) = game.loss {
+                *losses.entry(loss).or_default() += 1;
+            }
+        }
+
+        dailies.sort_by_key(|daily| daily.slot);
+
+        Self {
+            games: games.len(),
+            wins: win_times.len(),
+            nf_wins: games
+                .iter()
+                .filter(|(_, game)| game.won() && game.nf)
+                .count(),
+            average_win_time: (!win_times.is_empty())
+                .then(|| win_times.iter().sum::<f32>() / win_times.len() as f32),
+            efficiency: efficiency.map(|(sum, n)| (n > 0).then(|| sum / n as f32)),
+            // ties go to the least avoidable kind, in declaration order
+            common_loss: losses
+                .into_iter()
+                .max_by_key(|&(kind, count)| (count, std::cmp::Reverse(kind))),
+            calendar,
+            dailies,
+        }
+    }
+
+    /// Attach the notes of the player to the daily challenges, see `Stats::daily_notes`
+    pub fn annotate(&mut self, notes: &BTreeMap<String, String>) {
+        for daily in &mut self.dailies {
+            daily.note = notes.get(&daily.date).cloned();
+        }
+    }
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_time() -> u64 {
+    #[cfg(not(target_arch = "wasm32"))]
+    {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs())
+    }
+    // the standard clock is not available in browsers
+    #[cfg(target_arch = "wasm32")]
+    {
+        web_sys::window()
+            .and_then(|window| window.performance())
+            .map_or(0, |perf| {
+                ((perf.time_origin() + perf.now()) / 1000.0) as u64
+            })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DailyResult, DayActivity, Insights, DAY, WINDOW_DAYS};
+    use crate::save::{GameRecord, LossKind, SessionLog};
+
+    fn game(days_ago: u64, duration: f32, loss: Option<LossKind>) -> GameRecord {
+        GameRecord {
+            finished: 1000 * DAY - days_ago * DAY + 60,
+            width: 9,
+            height: 9,
+            n_mines: 10,
+            duration,
+            clicks: 20,
+            bbbv: 10,
+            loss,
+            nf: days_ago == 2,
+            splits: Vec::new(),
+            mode: Default::default(),
+            line_counts: false,
+            weekly: None,
+            daily: None,
+            warm_up: false,
+        }
+    }
+
+    #[test]
+    fn test_insights() {
+        let log = SessionLog {
+            games: vec![
+                game(0, 30.0, None),
+                game(0, 5.0, Some(LossKind::ForcedGuess)),
+                game(2, 50.0, None),
+                game(3, 5.0, Some(LossKind::AvoidableGuess)),
+                game(3, 5.0, Some(LossKind::AvoidableGuess)),
+                // out of the window
+                game(40, 10.0, None),
+                GameRecord {
+                    daily: Some("1972-09-26".to_owned()),
+                    ..game(1, 5.0, Some(LossKind::ProvenMine))
+                },
+                GameRecord {
+                    daily: Some("1972-09-26".to_owned()),
+                    warm_up: true,
+                    ..game(1, 5.0, Some(LossKind::ForcedGuess))
+                },
+            ],
+        };
+        let mut insights = Insights::compute(&log, 1000 * DAY + 3600);
+
+        assert_eq!((insights.games, insights.wins, insights.nf_wins), (7, 2, 1));
+        assert_eq!(insights.average_win_time, Some(40.0));
+        assert_eq!(insights.common_loss, Some((LossKind::AvoidableGuess, 2)));
+        assert_eq!(insights.efficiency[WINDOW_DAYS - 1], Some(0.5));
+        assert_eq!(insights.efficiency[WINDOW_DAYS - 2], None);
+        assert_eq!(insights.efficiency[WINDOW_DAYS - 4], None);
+
+        let calendar = &insights.calendar[WINDOW_DAYS - 4..];
+        assert_eq!(
+            calendar,
+            [
+                DayActivity::Played,
+                DayActivity::Won,
+                DayActivity::Played,
+                DayActivity::Won
+            ]
+        );
+
+        insights.annotate(&[("1972-09-26".to_owned(), "lost to a 50/50".to_owned())].into());
+        assert_eq!(
+            insights.dailies,
+            [DailyResult {
+                slot: WINDOW_DAYS - 2,
+                date: "1972-09-26".to_owned(),
+                won: false,
+                note: Some("lost to a 50/50".to_owned()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_empty() {
+        let insights = Insights::compute(&SessionLog::default(), 1000 * DAY);
+        assert_eq!(insights.games, 0);
+        assert_eq!(insights.average_win_time, None);
+        assert_eq!(insights.common_loss, None);
+        assert!(insights.calendar.iter().all(|&d| d == DayActivity::Idle));
+    }
+}
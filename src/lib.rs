@@ -0,0 +1,35 @@
+pub mod bug_report;
+pub mod cli;
+pub mod clipboard;
+pub mod components;
+#[cfg(feature = "debug")]
+pub mod console;
+pub mod contrast;
+pub mod daily;
+pub mod engine;
+pub mod entities;
+pub mod events;
+#[cfg(feature = "gym")]
+pub mod gym;
+pub mod hash;
+pub mod hexcells;
+pub mod import;
+pub mod insights;
+#[cfg(feature = "platform")]
+pub mod platform;
+pub mod plugins;
+pub mod protocol;
+pub mod resources;
+pub mod rng;
+pub mod save;
+pub mod session;
+pub mod share;
+pub mod snapshot;
+pub mod solver;
+pub mod storage;
+pub mod symmetry;
+pub mod sync;
+pub mod thumbnail;
+pub mod topology;
+#[cfg(feature = "voice")]
+pub mod voice;
@@ -1,18 +1,43 @@
-#![feature(rustc_attrs)]
-
 use bevy::prelude::*;
+#[cfg(feature = "debug")]
 use bevy_inspector_egui::WorldInspectorPlugin;
-use components::InspectablePlugin;
-use plugins::BoardPlugin;
-use resources::board_options::BoardOptions;
+#[cfg(feature = "egui")]
+use minesweeper::plugins::EguiUiPlugin;
+#[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+use minesweeper::plugins::TrayPlugin;
+#[cfg(feature = "voice")]
+use minesweeper::plugins::VoicePlugin;
+#[cfg(not(feature = "egui"))]
+use minesweeper::plugins::{
+    ConfirmScreenPlugin, InsightsScreenPlugin, LibraryScreenPlugin, PauseScreenPlugin,
+    ResultPanelPlugin, SlotsScreenPlugin, TrophiesScreenPlugin,
+};
+#[cfg(feature = "debug")]
+use minesweeper::plugins::{
+    ConsolePlugin, ContrastAuditPlugin, OptionsInspectorPlugin, OptionsReloadPlugin, PerfHudPlugin,
+    XrayPlugin,
+};
+use minesweeper::{
+    cli,
+    components::InspectablePlugin,
+    plugins::{
+        AchievementsPlugin, AdaptivePlugin, AnalysisPlugin, AssetWatchdogPlugin, AutosavePlugin,
+        BoardPlugin, BotPlugin, BugReportPlugin, ChatPlugin, ConfirmPlugin, DailyPlugin,
+        DemoPlugin, EngineCommand, EnginePlugin, ErrorScreen, ErrorScreenPlugin, HintPlugin,
+        HudPlugin, InsightsPlugin, LastMovePlugin, LibraryPlugin, LineCountsPlugin, LoadingPlugin,
+        PastePlugin, PausePlugin, PlaybackPlugin, ProfileSync, RulerPlugin, SandboxPlugin,
+        SessionPlugin, SharePlugin, SkinPlugin, SlotsPlugin, SmileyPlugin, SyncPlugin, ToastPlugin,
+        UndoPlugin, VersusPlugin, WatchReplay, WeeklyPlugin, WindowTitlePlugin,
+    },
+    resources::board_options::{BoardOptions, OptionOverrides, OptionsFile},
+    rng::{self, RngBackend},
+    save::{self, Profiles, Replay},
+    session::SessionArgs,
+    storage::Storage,
+    sync::{self, Remote},
+};
 use tap::Tap;
 
-mod components;
-mod entities;
-mod events;
-mod plugins;
-mod resources;
-
 fn main() {
     #[allow(clippy::assertions_on_constants)]
     {
@@ -22,6 +47,44 @@ fn main() {
         );
     }
 
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    if let Some(code) = cli::run(&args, &mut std::io::stdout()) {
+        std::process::exit(code);
+    }
+    // before anything is drawn
+    let backend = args
+        .iter()
+        .rposition(|arg| arg == "--rng")
+        .and_then(|idx| args.get(idx + 1))
+        .map(|name| name.parse::<RngBackend>());
+    if let Some(Ok(backend)) = backend {
+        rng::set_backend(backend);
+    }
+    let storage = Storage::detect();
+    // before any file is read, so that signed files are checked
+    let key = storage.as_ref().map(save::load_key).transpose();
+    if let Ok(key) = &key {
+        save::set_key(key.clone().flatten());
+    }
+    // a profile keeps files of its own
+    let profile = storage
+        .as_ref()
+        .map(|storage| Profiles::open(storage, &args))
+        .transpose()
+        .map(Option::flatten);
+    let storage = match &profile {
+        Ok(Some(profile)) => storage.map(|storage| storage.for_profile(&profile.id)),
+        _ => storage,
+    };
+    // before anything is read, so that the options come from the newest copy
+    let sync = storage
+        .as_ref()
+        .zip(Remote::from_env())
+        .map(|(storage, backend)| {
+            let on_launch = sync::sync(storage, &backend);
+            ProfileSync { backend, on_launch }
+        });
+
     App::new()
         .insert_resource(WindowDescriptor {
             title: "Minesweeper".to_owned(),
@@ -30,13 +93,150 @@ fn main() {
             ..Default::default()
         })
         .add_plugins(DefaultPlugins)
+        .add_startup_system(camera_setup)
         .tap_mut(|app| {
+            // the inspectors insert default options, replaced below
             #[cfg(feature = "debug")]
-            app.add_plugin(WorldInspectorPlugin::new());
+            app.add_plugin(WorldInspectorPlugin::new())
+                .add_plugin(OptionsInspectorPlugin)
+                .add_plugin(OptionsReloadPlugin)
+                .add_plugin(PerfHudPlugin)
+                .add_plugin(XrayPlugin)
+                .add_plugin(ContrastAuditPlugin)
+                .add_plugin(ConsolePlugin);
+
+            // saved options are the base the command line overrides
+            let saved = storage.as_ref().map(BoardOptions::from_storage);
+            match saved.transpose().map(Option::flatten) {
+                Ok(base) => match OptionOverrides::parse(args.clone()).and_then(|overrides| {
+                    let mut options = overrides.apply(base.unwrap_or_default())?;
+                    for clamped in options.clamp() {
+                        warn!("{clamped}, clamped");
+                    }
+                    Ok((options, overrides))
+                }) {
+                    // kept for the debug reload, see `OptionsReloadPlugin`
+                    Ok((options, overrides)) => {
+                        app.insert_resource(options).insert_resource(overrides)
+                    }
+                    Err(error) => app.insert_resource(ErrorScreen::new("Invalid arguments", error)),
+                },
+                Err(error) => app.insert_resource(ErrorScreen::new("Invalid saved options", error)),
+            };
+
+            let file = OptionsFile::from_args(&args)
+                .or_else(|| storage.as_ref().and_then(OptionsFile::from_storage));
+            if let Some(file) = file {
+                app.insert_resource(file);
+            }
+            let replay = args
+                .iter()
+                .rposition(|arg| arg == "--replay")
+                .and_then(|idx| args.get(idx + 1));
+            match replay.map(|path| (path, save::load::<Replay>(path))) {
+                Some((path, Ok(replay))) => {
+                    app.insert_resource(WatchReplay {
+                        replay,
+                        path: path.into(),
+                    });
+                }
+                Some((_, Err(error))) => {
+                    app.insert_resource(ErrorScreen::new("Invalid replay", error));
+                }
+                None => {}
+            }
+            let engine = args
+                .iter()
+                .rposition(|arg| arg == "--engine")
+                .and_then(|idx| args.get(idx + 1));
+            if let Some(command) = engine {
+                app.insert_resource(EngineCommand(command.clone()));
+            }
+            if let Some(storage) = storage {
+                app.insert_resource(storage);
+            }
+            if let Some(sync) = sync {
+                app.insert_resource(sync);
+            }
+            if let Some(Err(error)) = backend {
+                app.insert_resource(ErrorScreen::new("Invalid arguments", error));
+            }
+            if let Err(error) = key {
+                app.insert_resource(ErrorScreen::new("Invalid save key", error));
+            }
+            match SessionArgs::from_args(&args) {
+                Ok(Some(session)) => {
+                    app.insert_resource(session);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    app.insert_resource(ErrorScreen::new("Invalid arguments", error));
+                }
+            }
+            match profile {
+                Ok(Some(profile)) => {
+                    app.insert_resource(profile);
+                }
+                Ok(None) => {}
+                Err(error) => {
+                    app.insert_resource(ErrorScreen::new("Invalid profile list", error));
+                }
+            }
         })
-        .add_startup_system(camera_setup)
-        .insert_resource(BoardOptions::default())
+        .add_plugin(ErrorScreenPlugin)
+        .add_plugin(ToastPlugin)
+        // after the storage is inserted, which the panic hook writes to
+        .add_plugin(BugReportPlugin)
+        .add_plugin(AssetWatchdogPlugin)
+        .add_plugin(LoadingPlugin)
         .add_plugin(BoardPlugin)
+        .add_plugin(SkinPlugin)
+        .add_plugin(SmileyPlugin)
+        .add_plugin(HintPlugin)
+        .add_plugin(HudPlugin)
+        .add_plugin(WindowTitlePlugin)
+        .add_plugin(SharePlugin)
+        .add_plugin(AdaptivePlugin)
+        .add_plugin(PastePlugin)
+        .add_plugin(LineCountsPlugin)
+        .add_plugin(RulerPlugin)
+        .add_plugin(LastMovePlugin)
+        .add_plugin(AnalysisPlugin)
+        .add_plugin(AutosavePlugin)
+        .add_plugin(InsightsPlugin)
+        .add_plugin(AchievementsPlugin)
+        .add_plugin(LibraryPlugin)
+        .add_plugin(SlotsPlugin)
+        .add_plugin(WeeklyPlugin)
+        .add_plugin(DailyPlugin)
+        .add_plugin(UndoPlugin)
+        .add_plugin(SyncPlugin)
+        .add_plugin(ChatPlugin)
+        .add_plugin(VersusPlugin)
+        .add_plugin(BotPlugin)
+        .add_plugin(PlaybackPlugin)
+        .add_plugin(SandboxPlugin)
+        .add_plugin(PausePlugin)
+        .add_plugin(DemoPlugin)
+        .add_plugin(ConfirmPlugin)
+        .add_plugin(EnginePlugin)
+        .add_plugin(SessionPlugin)
+        .tap_mut(|app| {
+            #[cfg(feature = "egui")]
+            app.add_plugin(EguiUiPlugin);
+            #[cfg(not(feature = "egui"))]
+            app.add_plugin(InsightsScreenPlugin)
+                .add_plugin(TrophiesScreenPlugin)
+                .add_plugin(LibraryScreenPlugin)
+                .add_plugin(SlotsScreenPlugin)
+                .add_plugin(ResultPanelPlugin)
+                .add_plugin(PauseScreenPlugin)
+                .add_plugin(ConfirmScreenPlugin);
+            #[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+            app.add_plugin(TrayPlugin);
+            #[cfg(feature = "voice")]
+            app.add_plugin(VoicePlugin);
+        })
         .add_plugin(InspectablePlugin)
         .run();
 }
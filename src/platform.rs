@@ -0,0 +1,99 @@
+//! Integration with the desktop around the window, built with the `platform` feature.
+//!
+//! Only Windows shows progress on the taskbar button, through `ITaskbarList3`. Elsewhere
+//! [`set_taskbar_progress`] does nothing, so callers need no platform checks of their own. COM
+//! objects stay on the thread that created them: call it from a system taking a `NonSend`
+//! resource, which bevy runs on the main thread, next to the window.
+
+use raw_window_handle::HasRawWindowHandle;
+
+/// What the taskbar button of the window shows
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TaskbarProgress {
+    Hidden,
+    /// Share done, from 0 to 1
+    Normal(f32),
+    /// Share done when it failed, in red
+    Error(f32),
+}
+
+/// Show `progress` on the taskbar button of `window`
+pub fn set_taskbar_progress(window: &impl HasRawWindowHandle, progress: TaskbarProgress) {
+    #[cfg(windows)]
+    win32::set_progress(window, progress);
+    #[cfg(not(windows))]
+    let _ = (window, progress);
+}
+
+#[cfg(windows)]
+mod win32 {
+    use std::cell::RefCell;
+
+    use bevy::prelude::warn;
+    use raw_window_handle::{HasRawWindowHandle, RawWindowHandle};
+    use windows::Win32::{
+        Foundation::HWND,
+        System::Com::{
+            CoCreateInstance, CoInitializeEx, CLSCTX_INPROC_SERVER, COINIT_APARTMENTTHREADED,
+        },
+        UI::Shell::{ITaskbarList3, TaskbarList, TBPF_ERROR, TBPF_NOPROGRESS, TBPF_NORMAL},
+    };
+
+    use super::TaskbarProgress;
+
+    /// Steps of the progress value, enough for a smooth bar
+    const STEPS: u64 = 1000;
+
+    thread_local! {
+        /// Created on first use, `None` once it failed so that the warning shows once
+        static TASKBAR: RefCell<Option<Option<ITaskbarList3>>> = RefCell::new(None);
+    }
+
+    fn create() -> Option<ITaskbarList3> {
+        // the window already initialized COM on this thread, this only makes sure of it
+        let _ = unsafe { CoInitializeEx(None, COINIT_APARTMENTTHREADED) };
+        let taskbar = unsafe {
+            CoCreateInstance::<_, ITaskbarList3>(&TaskbarList, None, CLSCTX_INPROC_SERVER)
+        };
+        match taskbar.and_then(|taskbar| unsafe { taskbar.HrInit() }.map(|()| taskbar)) {
+            Ok(taskbar) => Some(taskbar),
+            Err(error) => {
+                warn!("No progress on the taskbar: {error}");
+                None
+            }
+        }
+    }
+
+    pub(super) fn set_progress(window: &impl HasRawWindowHandle, progress: TaskbarProgress) {
+        let RawWindowHandle::Win32(handle) = window.raw_window_handle() else {
+            return;
+        };
+        let hwnd = HWND(handle.hwnd as isize);
+
+        TASKBAR.with(|taskbar| {
+            let mut taskbar = taskbar.borrow_mut();
+            let Some(taskbar) = taskbar.get_or_insert_with(create) else {
+                return;
+            };
+            let (state, done) = match progress {
+                TaskbarProgress::Hidden => (TBPF_NOPROGRESS, None),
+                TaskbarProgress::Normal(done) => (TBPF_NORMAL, Some(done)),
+                TaskbarProgress::Error(done) => (TBPF_ERROR, Some(done)),
+            };
+            let result = unsafe {
+                taskbar
+                    .SetProgressState(hwnd, state)
+                    .and_then(|()| match done {
+                        Some(done) => {
+                            let completed = (done.clamp(0.0, 1.0) * STEPS as f32).round() as u64;
+                            taskbar.SetProgressValue(hwnd, completed, STEPS)
+                        }
+                        None => Ok(()),
+                    })
+            };
+            if let Err(error) = result {
+                warn!("Could not show the progress on the taskbar: {error}");
+            }
+        });
+    }
+}
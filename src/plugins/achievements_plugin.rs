@@ -0,0 +1,141 @@
+use bevy::prelude::{
+    info, warn, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin,
+    Res, ResMut, SystemSet,
+};
+
+use super::{BoardSystem, GameStatus, InsightsPlugin};
+use crate::{
+    events::{AchievementEvent, ToastEvent, TrophiesToggleEvent},
+    insights,
+    resources::{input_map::InputMap, rule_set::RuleSet, toasts::ToastKind},
+    save::{
+        self, Achievement, SessionLog, Stats, Unlocked, SESSION_FILE, STATS_FILE, UNLOCKED_FILE,
+    },
+    storage::{Location, Storage},
+};
+
+/// Achievement definitions and what the player unlocked
+#[derive(Debug, Clone)]
+pub struct Achievements {
+    pub definitions: Vec<Achievement>,
+    pub unlocked: Unlocked,
+}
+
+impl Default for Achievements {
+    fn default() -> Self {
+        Self {
+            definitions: Achievement::definitions(None).unwrap_or_default(),
+            unlocked: Unlocked::default(),
+        }
+    }
+}
+
+/// Trophies on screen, drawn by the UI backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShownTrophies(pub bool);
+
+/// Unlock achievements from the statistics after every recorded game, with a toast for each, and
+/// list the trophies on `InputMap::trophies` (`T`). The definitions come from
+/// `assets/achievements.ron`, or from a copy of it in the config directory
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<AchievementEvent>()
+            .add_event::<TrophiesToggleEvent>()
+            .init_resource::<Achievements>()
+            .init_resource::<ShownTrophies>()
+            .add_startup_system(Self::load)
+            .add_system(Self::trophies_key.before(Self::toggle))
+            .add_system(Self::toggle)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::unlock.after(InsightsPlugin::record))
+                    .with_system(Self::notify.after(Self::unlock)),
+            );
+    }
+}
+
+impl AchievementsPlugin {
+    fn load(storage: Option<Res<Storage>>, mut achievements: ResMut<Achievements>) {
+        let Some(storage) = storage else {
+            return;
+        };
+        match Achievement::definitions(Some(&storage)) {
+            Ok(definitions) => achievements.definitions = definitions,
+            Err(error) => {
+                warn!("Using the built-in achievements, the custom ones are invalid: {error}")
+            }
+        }
+        match save::read::<Unlocked>(&storage, Location::Data, UNLOCKED_FILE) {
+            Ok(unlocked) => achievements.unlocked = unlocked.unwrap_or_default(),
+            Err(error) => warn!("Could not read the achievements: {error}"),
+        }
+    }
+
+    /// Check the achievements once the game is recorded
+    fn unlock(
+        rules: Res<RuleSet>,
+        status: Res<GameStatus>,
+        storage: Option<Res<Storage>>,
+        mut achievements: ResMut<Achievements>,
+        mut unlocks: EventWriter<AchievementEvent>,
+    ) {
+        if !status.is_changed() || *status == GameStatus::Playing || !rules.recorded {
+            return;
+        }
+        let Some(storage) = storage else {
+            return;
+        };
+        let stats = save::read::<Stats>(&storage, Location::Data, STATS_FILE);
+        let log = save::read::<SessionLog>(&storage, Location::Data, SESSION_FILE);
+        let (Ok(stats), Ok(log)) = (stats, log) else {
+            return;
+        };
+        let (stats, log) = (stats.unwrap_or_default(), log.unwrap_or_default());
+
+        let Achievements {
+            definitions,
+            unlocked: shown,
+        } = &mut *achievements;
+        let mut new = Vec::new();
+        InsightsPlugin::update(&storage, UNLOCKED_FILE, |unlocked: &mut Unlocked| {
+            new = unlocked.update(definitions, &stats, &log, insights::unix_time());
+            *shown = unlocked.clone();
+        });
+        for achievement in new {
+            unlocks.send(AchievementEvent {
+                achievement: achievement.clone(),
+            });
+        }
+    }
+
+    fn notify(mut unlocks: EventReader<AchievementEvent>, mut toasts: EventWriter<ToastEvent>) {
+        for AchievementEvent { achievement } in unlocks.iter() {
+            info!("Achievement unlocked: {}", achievement.name);
+            toasts.send(ToastEvent::new(
+                ToastKind::Success,
+                format!("Achievement unlocked: {}", achievement.name),
+                &achievement.description,
+            ));
+        }
+    }
+
+    fn trophies_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut toggles: EventWriter<TrophiesToggleEvent>,
+    ) {
+        if keys.just_pressed(input.trophies) {
+            toggles.send(TrophiesToggleEvent);
+        }
+    }
+
+    pub fn toggle(mut toggles: EventReader<TrophiesToggleEvent>, mut shown: ResMut<ShownTrophies>) {
+        if !toggles.iter().count().is_multiple_of(2) {
+            shown.0 = !shown.0;
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use bevy::prelude::{info, warn, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, SystemSet};
+
+use super::{BoardSystem, GameStatus, InsightsPlugin};
+use crate::{
+    resources::board_options::BoardOptions,
+    save::{self, SkillEstimate, Trend, SKILL_FILE},
+    storage::{Location, Storage},
+};
+
+/// Skill estimate of the player, as last read or recorded
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AdaptiveSkill(pub SkillEstimate);
+
+/// Current board, when its mines follow the skill of the player. Inserted by
+/// `BoardPlugin::create_board`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveBoard {
+    /// Mines per tile
+    pub density: f32,
+    /// Where the density lies between the bounds, see `AdaptiveDensity::skill`
+    pub position: f64,
+    /// The first result counts, not the ones after an undo
+    pub recorded: bool,
+}
+
+/// Density of the adaptive board just finished and of the next one, shown with the result
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSummary {
+    pub density: f32,
+    pub next: f32,
+    pub trend: Trend,
+}
+
+impl AdaptiveSummary {
+    /// `Adaptive density 15.6%, next board 17.1% ↑`
+    pub fn describe(&self) -> String {
+        let arrow = match self.trend {
+            Trend::Rising => "↑",
+            Trend::Falling => "↓",
+            Trend::Steady => "→",
+        };
+        format!(
+            "Adaptive density {:.1}%, next board {:.1}% {arrow}",
+            self.density * 100.0,
+            self.next * 100.0
+        )
+    }
+}
+
+/// Summary of the adaptive board just finished, drawn by the UI backend with the result
+#[derive(Debug, Default, Clone, Copy)]
+pub struct ShownAdaptive(pub Option<AdaptiveSummary>);
+
+/// Adaptive difficulty: with `BoardOptions::adaptive` set, new boards get as many mines as the
+/// skill estimate of the player asks for, within the density bounds of the options. The
+/// estimate goes up after wins and down after losses, see `SkillEstimate`, and is kept in the
+/// data directory. Boards every player shares, resumed games and modes without stats keep
+/// the mines of their difficulty
+pub struct AdaptivePlugin;
+
+impl Plugin for AdaptivePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<AdaptiveSkill>()
+            .init_resource::<ShownAdaptive>()
+            .add_startup_system(Self::load)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::record.after(InsightsPlugin::track)),
+            );
+    }
+}
+
+impl AdaptivePlugin {
+    fn load(storage: Option<Res<Storage>>, mut skill: ResMut<AdaptiveSkill>) {
+        let Some(storage) = storage else {
+            return;
+        };
+        match save::read::<SkillEstimate>(&storage, Location::Data, SKILL_FILE) {
+            Ok(estimate) => skill.0 = estimate.unwrap_or_default(),
+            Err(error) => warn!("Starting from a new skill estimate: {error}"),
+        }
+    }
+
+    /// Move the estimate with the result of an adaptive board
+    pub fn record(
+        status: Res<GameStatus>,
+        options: Option<Res<BoardOptions>>,
+        storage: Option<Res<Storage>>,
+        board: Option<ResMut<AdaptiveBoard>>,
+        mut skill: ResMut<AdaptiveSkill>,
+        mut shown: ResMut<ShownAdaptive>,
+    ) {
+        if !status.is_changed() {
+            return;
+        }
+        let (Some(mut board), false) = (board, *status == GameStatus::Playing) else {
+            if shown.0.is_some() && *status == GameStatus::Playing {
+                shown.0 = None;
+            }
+            return;
+        };
+        if board.recorded {
+            return;
+        }
+        board.recorded = true;
+
+        // the file first, another machine may have moved it since the launch
+        let (won, position) = (*status == GameStatus::Won, board.position);
+        match storage {
+            Some(storage) => {
+                InsightsPlugin::update(&storage, SKILL_FILE, |estimate: &mut SkillEstimate| {
+                    estimate.record(position, won);
+                    skill.0 = *estimate;
+                })
+            }
+            None => {
+                skill.0.record(position, won);
+            }
+        }
+        info!("Skill estimate {:.2}, {:?}", skill.0.skill, skill.0.trend);
+
+        let next = options
+            .and_then(|options| options.adaptive)
+            .map_or(board.density, |adaptive| adaptive.density(skill.0.skill));
+        shown.0 = Some(AdaptiveSummary {
+            density: board.density,
+            next,
+            trend: skill.0.trend,
+        });
+    }
+}
@@ -0,0 +1,208 @@
+use bevy::{
+    prelude::{
+        Color, Input, KeyCode, MouseButton, ParallelSystemDescriptorCoercion, Parent, Plugin,
+        Query, Res, ResMut, SystemSet, Timer, With, Without,
+    },
+    sprite::Sprite,
+    text::Text,
+    time::Time,
+    window::Windows,
+};
+
+use crate::{
+    components::{BoardCoordinate, HintText, TileCover},
+    resources::{
+        board_assets::BoardAssets, board_options::DisplayParams, input_capture::InputCapture,
+        input_map::InputMap, motion_prefs::MotionPrefs, rule_set::RuleSet,
+    },
+    solver::{self, ConstraintGraph, Deduction, Proof},
+};
+
+use super::{BoardPlugin, BoardSystem};
+
+const NUMBER_COLOR: Color = Color::YELLOW;
+const SAFE_COLOR: Color = Color::GREEN;
+const MINE_COLOR: Color = Color::RED;
+
+/// Seconds each proof step stays highlighted
+const STEP_DURATION: f32 = 1.5;
+
+#[derive(Debug)]
+struct ProofPlayback {
+    proof: Proof,
+    step: usize,
+    timer: Timer,
+    // step currently highlighted on the board
+    shown: Option<usize>,
+}
+
+/// Teaching mode: click a covered tile to step through the constraints proving it safe or mined
+#[derive(Debug, Default)]
+pub struct AnalysisMode {
+    pub enabled: bool,
+    playback: Option<ProofPlayback>,
+}
+
+/// Toggle analysis mode with `InputMap::analysis` (`A` for right-handed players), unless the
+/// `RuleSet` forbids assists
+pub struct AnalysisPlugin;
+
+impl Plugin for AnalysisPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<AnalysisMode>()
+            .init_resource::<InputCapture>()
+            .add_system(Self::capture_input.before(BoardSystem::Input))
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::toggle)
+                    .with_system(Self::select_tile.after(Self::toggle))
+                    .with_system(Self::play_proof.after(Self::select_tile)),
+            );
+    }
+}
+
+impl AnalysisPlugin {
+    /// Keep the clicks from the game in analysis mode, where they select tiles to explain
+    fn capture_input(mode: Res<AnalysisMode>, mut capture: ResMut<InputCapture>) {
+        if mode.is_changed() {
+            capture.set("analysis", mode.enabled);
+        }
+    }
+
+    fn toggle(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        rules: Res<RuleSet>,
+        assets: Res<BoardAssets>,
+        mut mode: ResMut<AnalysisMode>,
+        mut tiles: Query<&mut Sprite, (With<BoardCoordinate>, Without<TileCover>)>,
+        mut covers: Query<&mut Sprite, With<TileCover>>,
+    ) {
+        // always possible to leave, e.g. after switching to a board without assists
+        if keys.just_pressed(input.analysis) && (rules.assists || mode.enabled) {
+            mode.enabled = !mode.enabled;
+            mode.playback = None;
+            tiles
+                .iter_mut()
+                .for_each(|mut sprite| sprite.color = assets.tile_color());
+            covers
+                .iter_mut()
+                .for_each(|mut sprite| sprite.color = assets.cover_color());
+        }
+    }
+
+    fn select_tile(
+        buttons: Res<Input<MouseButton>>,
+        input: Res<InputMap>,
+        windows: Res<Windows>,
+        params: Option<Res<DisplayParams>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        mut mode: ResMut<AnalysisMode>,
+        mut panel: Query<&mut Text, With<HintText>>,
+    ) {
+        if !mode.enabled || !buttons.just_pressed(input.uncover) {
+            return;
+        }
+
+        let (Some(params), Some(mut graph)) = (params, graph) else {
+            return;
+        };
+        let Some(coord) = BoardPlugin::cursor_position(&windows).and_then(|p| params.coord_at(p))
+        else {
+            return;
+        };
+
+        mode.playback = solver::explain(&mut graph, coord).map(|proof| ProofPlayback {
+            proof,
+            step: 0,
+            timer: Timer::from_seconds(STEP_DURATION, true),
+            shown: None,
+        });
+
+        if mode.playback.is_none() {
+            // the odds are all there is to go by, flagged when sampled rather than exact
+            let analysis = graph.analyze();
+            let mut message = format!(
+                "({}, {}) cannot be decided from the revealed numbers",
+                coord.x, coord.y
+            );
+            if let Some(probability) = analysis.probability(coord) {
+                message += &format!(", {:.0}% chance of a mine", probability * 100.0);
+                if let Some(confidence) = analysis.confidence() {
+                    message += &format!(
+                        " (sampled, {:.0} samples, standard error up to {:.1}%)",
+                        confidence.samples,
+                        confidence.std_error * 100.0
+                    );
+                }
+            }
+            for mut text in &mut panel {
+                text.sections[0].value = message.clone();
+            }
+        }
+    }
+
+    /// Step through the proof on a timer, or on the next step key with reduced motion
+    #[allow(clippy::too_many_arguments)]
+    fn play_proof(
+        time: Res<Time>,
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        motion: Res<MotionPrefs>,
+        assets: Res<BoardAssets>,
+        mut mode: ResMut<AnalysisMode>,
+        coords: Query<&BoardCoordinate>,
+        mut tiles: Query<(&BoardCoordinate, &mut Sprite), Without<TileCover>>,
+        mut covers: Query<(&Parent, &mut Sprite), With<TileCover>>,
+        mut panel: Query<&mut Text, (With<HintText>, Without<BoardCoordinate>)>,
+    ) {
+        let Some(playback) = &mut mode.playback else {
+            return;
+        };
+
+        let next = if motion.animate() {
+            playback.timer.tick(time.delta()).just_finished()
+        } else {
+            keys.just_pressed(input.next_step)
+        };
+        if next {
+            playback.step = (playback.step + 1) % playback.proof.steps.len();
+        }
+        if playback.shown == Some(playback.step) {
+            return;
+        }
+        playback.shown = Some(playback.step);
+
+        let step = &playback.proof.steps[playback.step];
+        let numbers = step.reason.numbers();
+        // numbers are revealed tiles, deduced tiles are still hidden under their cover
+        for (coord, mut sprite) in &mut tiles {
+            sprite.color = if numbers.contains(&coord.inner) {
+                NUMBER_COLOR
+            } else {
+                assets.tile_color()
+            };
+        }
+        for (parent, mut sprite) in &mut covers {
+            let Ok(coord) = coords.get(parent.get()) else {
+                continue;
+            };
+            sprite.color = match step.deductions.iter().find(|(c, _)| *c == coord.inner) {
+                Some((_, Deduction::Safe)) => SAFE_COLOR,
+                Some((_, Deduction::Mine)) => MINE_COLOR,
+                None => assets.cover_color(),
+            };
+        }
+
+        for mut text in &mut panel {
+            text.sections[0].value = format!(
+                "Step {}/{}: {}",
+                playback.step + 1,
+                playback.proof.steps.len(),
+                step
+            );
+        }
+    }
+}
@@ -0,0 +1,121 @@
+use bevy::{
+    asset::{HandleId, LoadState},
+    prelude::{
+        warn, AssetServer, Assets, Commands, EventReader, EventWriter, HandleUntyped, Image,
+        Plugin, Res, ResMut,
+    },
+    text::Font,
+};
+
+use super::ErrorScreen;
+use crate::{
+    events::{RetryEvent, ToastEvent},
+    resources::{
+        game_assets::{self, Fallback, REQUIRED},
+        toasts::ToastKind,
+    },
+};
+
+/// Handles of the required assets, kept so that they stay loaded, and their state
+#[derive(Debug, Default)]
+pub struct WatchedAssets {
+    pub handles: Vec<(&'static str, HandleUntyped)>,
+    /// Paths that failed to load and have no embedded copy, shown on the error screen
+    pub missing: Vec<&'static str>,
+    /// Paths replaced by their embedded copy
+    pub replaced: Vec<&'static str>,
+}
+
+/// Watch the font and sprites while they load. One that fails is replaced by its embedded copy
+/// when the `embedded-assets` feature has one, otherwise an `ErrorScreen` lists the missing
+/// paths and offers to load them again
+pub struct AssetWatchdogPlugin;
+
+impl Plugin for AssetWatchdogPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<WatchedAssets>()
+            .add_startup_system(Self::load)
+            .add_system(Self::watch)
+            .add_system(Self::retry);
+    }
+}
+
+impl AssetWatchdogPlugin {
+    fn load(asset_server: Res<AssetServer>, mut watched: ResMut<WatchedAssets>) {
+        watched.handles = REQUIRED
+            .into_iter()
+            .map(|path| (path, asset_server.load_untyped(path)))
+            .collect();
+    }
+
+    fn watch(
+        mut cmds: Commands,
+        asset_server: Res<AssetServer>,
+        mut fonts: ResMut<Assets<Font>>,
+        mut images: ResMut<Assets<Image>>,
+        mut watched: ResMut<WatchedAssets>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let failed = watched
+            .handles
+            .iter()
+            .filter(|(path, handle)| {
+                asset_server.get_load_state(handle) == LoadState::Failed
+                    && !watched.missing.contains(path)
+                    && !watched.replaced.contains(path)
+            })
+            .map(|(path, handle)| (*path, handle.id))
+            .collect::<Vec<_>>();
+        if failed.is_empty() {
+            return;
+        }
+
+        for (path, id) in failed {
+            if Self::replace(id, path, &mut fonts, &mut images) {
+                warn!("Could not load {path}, using the embedded copy");
+                toasts.send(ToastEvent::new(
+                    ToastKind::Warning,
+                    format!("Could not load {path}"),
+                    "Using the copy built into the game",
+                ));
+                watched.replaced.push(path);
+            } else {
+                watched.missing.push(path);
+            }
+        }
+        if !watched.missing.is_empty() {
+            cmds.insert_resource(
+                ErrorScreen::new("Missing assets", watched.missing.join("\n")).with_retry(),
+            );
+        }
+    }
+
+    /// Put the embedded copy of `path` under the handle that failed, so that everything using
+    /// the path gets it
+    fn replace(
+        id: HandleId,
+        path: &str,
+        fonts: &mut Assets<Font>,
+        images: &mut Assets<Image>,
+    ) -> bool {
+        match game_assets::fallback(path) {
+            Some(Fallback::Font(font)) => fonts.set_untracked(id, font),
+            Some(Fallback::Image(image)) => images.set_untracked(id, image),
+            None => return false,
+        }
+        true
+    }
+
+    fn retry(
+        mut retries: EventReader<RetryEvent>,
+        asset_server: Res<AssetServer>,
+        mut watched: ResMut<WatchedAssets>,
+    ) {
+        if retries.iter().count() == 0 {
+            return;
+        }
+        for path in std::mem::take(&mut watched.missing) {
+            asset_server.reload_asset(path);
+        }
+    }
+}
@@ -0,0 +1,263 @@
+use bevy::{
+    app::AppExit,
+    prelude::{
+        info, warn, AssetServer, Assets, BuildChildren, Color, Commands, CoreStage,
+        DespawnRecursiveExt, Entity, EventReader, EventWriter, Image, ImageBundle, Input, KeyCode,
+        Name, NodeBundle, Plugin, Query, Res, ResMut, SystemSet, TextBundle, Timer, Val, With,
+    },
+    text::TextStyle,
+    time::Time,
+    ui::{AlignItems, FlexDirection, PositionType, Size, Style, UiRect},
+};
+
+use super::{BoardSystem, GameStatus, ShownConfirm};
+use crate::{
+    components::ResumePromptText,
+    events::{BoardRegenerateEvent, ConfirmAction, ConfirmRequestEvent, ConfirmedEvent},
+    resources::{board_options::BoardOptions, board_state::BoardState, input_map::InputMap},
+    save::{self, SaveGame, SAVE_FILE},
+    storage::{Location, Storage},
+    thumbnail::Thumbnail,
+};
+
+/// Seconds between two autosaves of a changed board
+const AUTOSAVE_INTERVAL: f32 = 5.0;
+
+/// Longest side of the thumbnail of the saved game, in pixels
+const THUMBNAIL_SIZE: f32 = 120.0;
+
+/// Saved game found on launch, waiting for the player to resume or discard it
+#[derive(Debug, Clone)]
+struct ResumePrompt(SaveGame);
+
+/// Build the next board from this saved game instead of the board options
+#[derive(Debug, Clone)]
+pub struct ResumeGame(pub SaveGame);
+
+/// When the board is saved next
+#[derive(Debug)]
+pub struct Autosave {
+    timer: Timer,
+    // the board changed since the last save
+    dirty: bool,
+}
+
+impl Default for Autosave {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(AUTOSAVE_INTERVAL, true),
+            dirty: false,
+        }
+    }
+}
+
+/// Keep the game in progress saved, so that a crash loses at most a few seconds of play, and
+/// offer to resume it on the next launch
+pub struct AutosavePlugin;
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Autosave>()
+            .add_startup_system(Self::find_save)
+            .add_system(Self::answer_prompt)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::autosave),
+            )
+            .add_system_to_stage(CoreStage::Last, Self::save_on_exit);
+    }
+}
+
+impl AutosavePlugin {
+    fn write(storage: &Storage, state: &BoardState) {
+        match save::write(
+            storage,
+            Location::Data,
+            SAVE_FILE,
+            &SaveGame::capture(state),
+        ) {
+            Ok(()) => info!("Game saved"),
+            Err(error) => warn!("Could not save the game: {error}"),
+        }
+    }
+
+    fn find_save(
+        mut cmds: Commands,
+        storage: Option<Res<Storage>>,
+        board_options: Option<Res<BoardOptions>>,
+        mut images: ResMut<Assets<Image>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Some(storage) = storage else {
+            return;
+        };
+
+        let saved = match save::read::<SaveGame>(&storage, Location::Data, SAVE_FILE) {
+            Ok(Some(saved)) => saved,
+            Ok(None) => return,
+            Err(error) => {
+                warn!("Ignoring the saved game: {error}");
+                return;
+            }
+        };
+
+        let thumbnail = match Thumbnail::of_save(&saved) {
+            Ok(thumbnail) => thumbnail,
+            Err(error) => {
+                warn!("Ignoring the saved game: {error}");
+                return;
+            }
+        };
+        // at most THUMBNAIL_SIZE on its longest side, whole pixels per tile
+        let scale = (THUMBNAIL_SIZE / thumbnail.dim().max_element() as f32)
+            .floor()
+            .max(1.0);
+        let size = thumbnail.dim().as_vec2() * scale;
+
+        // the `InputMap` only follows the options from the first frame on
+        let keys =
+            board_options.map_or_else(Default::default, |options| options.input.resume.clone());
+        cmds.insert_resource(ResumePrompt(saved));
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexEnd,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Resume Prompt"))
+        .insert(ResumePromptText)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(ImageBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                        margin: UiRect {
+                            right: Val::Px(10.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    },
+                    image: images.add(thumbnail.to_image()).into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("Thumbnail"));
+            parent.spawn_bundle(TextBundle::from_section(
+                format!(
+                    "A game was left unfinished. Press {:?} to resume it, {:?} to start over",
+                    keys.resume, keys.discard
+                ),
+                TextStyle {
+                    font: asset_server.load("fonts/robotoslab.ttf"),
+                    font_size: 24.0,
+                    color: Color::YELLOW,
+                },
+            ));
+        });
+    }
+
+    /// Resume the saved game, or delete it once the player confirms
+    #[allow(clippy::too_many_arguments)]
+    fn answer_prompt(
+        mut cmds: Commands,
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        prompt: Option<Res<ResumePrompt>>,
+        storage: Option<Res<Storage>>,
+        confirm: Res<ShownConfirm>,
+        texts: Query<Entity, With<ResumePromptText>>,
+        mut confirmations: EventReader<ConfirmedEvent>,
+        mut confirms: EventWriter<ConfirmRequestEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let discarded = confirmations
+            .iter()
+            .any(|ConfirmedEvent(action)| matches!(action, ConfirmAction::DiscardSave));
+        let Some(prompt) = prompt else {
+            return;
+        };
+
+        if discarded {
+            if let Some(Err(error)) = storage.map(|s| s.remove(Location::Data, SAVE_FILE)) {
+                warn!("Could not delete the saved game: {error}");
+            }
+        } else if confirm.request.is_some() {
+            return;
+        } else if keys.just_pressed(input.resume.resume) {
+            cmds.insert_resource(ResumeGame(prompt.0.clone()));
+            regenerate.send(BoardRegenerateEvent);
+        } else if keys.just_pressed(input.resume.discard) {
+            confirms.send(ConfirmRequestEvent::new(
+                ConfirmAction::DiscardSave,
+                "Delete the unfinished game?",
+                format!(
+                    "The {}x{} board left unfinished cannot be resumed afterwards",
+                    prompt.0.width, prompt.0.height
+                ),
+                "Delete",
+            ));
+            return;
+        } else {
+            return;
+        }
+
+        cmds.remove_resource::<ResumePrompt>();
+        texts
+            .iter()
+            .for_each(|text| cmds.entity(text).despawn_recursive());
+    }
+
+    /// Save a changed board every few seconds, and drop the save once the game is over
+    fn autosave(
+        time: Res<Time>,
+        mut autosave: ResMut<Autosave>,
+        status: Res<GameStatus>,
+        prompt: Option<Res<ResumePrompt>>,
+        storage: Option<Res<Storage>>,
+        state: Option<Res<BoardState>>,
+    ) {
+        // the previous save must survive until the player decided what to do with it
+        let (Some(storage), Some(state), None) = (storage, state, prompt) else {
+            return;
+        };
+
+        if status.is_changed() && *status != GameStatus::Playing {
+            autosave.dirty = false;
+            if let Err(error) = storage.remove(Location::Data, SAVE_FILE) {
+                warn!("Could not delete the saved game: {error}");
+            }
+            return;
+        }
+
+        // a board nobody played on yet is not worth resuming
+        autosave.dirty |= state.is_changed() && !state.is_added() && *status == GameStatus::Playing;
+        if autosave.timer.tick(time.delta()).just_finished() && autosave.dirty {
+            autosave.dirty = false;
+            Self::write(&storage, &state);
+        }
+    }
+
+    pub fn save_on_exit(
+        mut exits: EventReader<AppExit>,
+        autosave: Res<Autosave>,
+        storage: Option<Res<Storage>>,
+        state: Option<Res<BoardState>>,
+    ) {
+        if exits.iter().count() == 0 || !autosave.dirty {
+            return;
+        }
+        if let (Some(storage), Some(state)) = (storage, state) {
+            Self::write(&storage, &state);
+        }
+    }
+}
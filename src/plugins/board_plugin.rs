@@ -1,75 +1,963 @@
 use bevy::{
-    prelude::{AssetServer, ChildBuilder, Handle, Image, Plugin, SpatialBundle, Vec3, Visibility},
-    text::{Font, Text, Text2dBundle, TextAlignment, TextStyle},
+    ecs::{change_detection::DetectChanges, system::EntityCommands},
+    math::uvec2,
+    prelude::{
+        ChildBuilder, CoreStage, IVec2, Input, Local, MouseButton,
+        ParallelSystemDescriptorCoercion, Plugin, SpatialBundle, StageLabel, SystemLabel,
+        SystemSet, SystemStage, Timer, Vec3, Visibility,
+    },
+    text::{Text, Text2dBundle, TextAlignment, TextStyle},
+    time::Time,
+    utils::Instant,
     window::Windows,
 };
 
 use bevy::{
-    prelude::{info, BuildChildren, Color, Commands, GlobalTransform, Name, Res, Transform, Vec2},
+    prelude::{
+        info, warn, BuildChildren, Children, Color, Commands, DespawnRecursiveExt, Entity,
+        EventReader, EventWriter, Name, Query, Res, ResMut, Transform, Vec2, With, Without,
+    },
     sprite::{Sprite, SpriteBundle},
 };
-use tap::{Pipe, Tap};
 
 use crate::{
-    components::{BoardCoordinate, Mine, MineNeighbor},
+    components::{
+        Board, BoardBackground, BoardCoordinate, Covered, FlagSprite, Flagged, Mine, MineNeighbor,
+        MineSprite, NumberSprite, NumberText, Pop, Questioned, Revealed, TileCover,
+    },
+    events::{
+        BoardClearEvent, BoardRegenerateEvent, MineTriggerEvent, OptionsApplyEvent,
+        ProvenMineClickEvent, TileMarkEvent, TileTriggerEvent,
+    },
     resources::{
-        board::TileMap,
-        board_options::{BoardOptions, DisplayParams},
+        board::{TileMap, TileState},
+        board_assets::{number_color, BoardAssets},
+        board_options::{BoardOptions, Difficulty, DisplayParams, GameMode, TileSize},
+        board_queue::{BoardKey, BoardQueue, QueuedBoard},
+        board_scratch::BoardScratch,
+        board_seed::{BoardSeed, SeededBoard},
+        board_state::{BoardState, Cover},
+        board_transition::BoardTransition,
+        game_clock::GameClock,
+        input_capture::InputCapture,
+        input_latency::{InputLatency, LatencyStage},
+        input_map::InputMap,
+        motion_prefs::MotionPrefs,
+        reveal_wave::RevealWave,
+        rule_set::{Player, Punishment, Roles, RuleSet},
+        tile_index::TileEntityIndex,
+        uncover_queue::{UncoverBudget, UncoverQueue},
     },
+    rng::{self, RngBackend},
+    save::{Action, INITIAL_SKILL},
+    solver::{ConstraintGraph, Deduction, Knowledge},
+    topology::SQUARE,
 };
 
+use super::{AdaptiveBoard, AdaptiveSkill, AppState, ErrorScreen, ResumeGame};
+
+/// Seconds for a mine revealed by the loss wave to shrink back
+const POP_DURATION: f32 = 0.2;
+
+/// Extra size of a mine as it pops out
+const POP_SCALE: f32 = 0.4;
+
+/// Size the board is laid out for without a window, as in headless tests
+const HEADLESS_WINDOW: Vec2 = Vec2::new(1280.0, 720.0);
+
+/// Outcome of the current board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GameStatus {
+    #[default]
+    Playing,
+    Won,
+    Lost,
+}
+
+/// Board systems, in the order they run every frame:
+///
+/// 1. [`BoardSystem::Input`]: clicks become [`TileTriggerEvent`]s and [`TileMarkEvent`]s
+/// 2. [`BoardSystem::Uncover`]: triggered tiles are uncovered, flood filling through empty
+///    tiles, and flags are toggled. The [`ConstraintGraph`] is updated right away. On huge boards
+///    a cascade may take several frames, see [`UncoverQueue`]
+/// 3. [`BoardSystem::Rules`]: loss and win detection, sending [`BoardClearEvent`] on a win
+/// 4. [`BoardSystem::Hud`]: panels reporting on the frame, e.g. hints
+///
+/// Uncover and rules systems only mutate the [`BoardState`]. [`BoardSystem::Render`] then applies
+/// the tiles that changed to the tile entities, in [`BoardStage::Render`] so that every rule of
+/// the frame has run and a click is fully displayed in the frame it happened.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, SystemLabel)]
+pub enum BoardSystem {
+    Input,
+    Uncover,
+    Rules,
+    Hud,
+    Render,
+}
+
+/// Stage of [`BoardSystem::Render`], between `CoreStage::Update` and `CoreStage::PostUpdate`, so
+/// that visibility and transform propagation see its changes in the same frame. In
+/// `CoreStage::PostUpdate`, covers could be hidden after the visibility check, and flags spawned
+/// with commands always were, showing one frame after the click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, StageLabel)]
+pub enum BoardStage {
+    Render,
+}
+
 pub struct BoardPlugin;
 
 impl Plugin for BoardPlugin {
     fn build(&self, app: &mut bevy::prelude::App) {
-        app.add_startup_system(Self::create_board);
+        app.add_event::<BoardRegenerateEvent>()
+            .add_event::<OptionsApplyEvent>()
+            .add_event::<TileTriggerEvent>()
+            .add_event::<TileMarkEvent>()
+            .add_event::<MineTriggerEvent>()
+            .add_event::<BoardClearEvent>()
+            .add_event::<ProvenMineClickEvent>()
+            .init_resource::<GameStatus>()
+            .init_resource::<GameMode>()
+            .init_resource::<Roles>()
+            .init_resource::<RuleSet>()
+            .init_resource::<RevealWave>()
+            .init_resource::<UncoverQueue>()
+            .init_resource::<TileEntityIndex>()
+            .init_resource::<MotionPrefs>()
+            .init_resource::<BoardTransition>()
+            .init_resource::<InputMap>()
+            .init_resource::<InputCapture>()
+            .init_resource::<BoardAssets>()
+            .init_resource::<GameClock>()
+            .init_resource::<BoardQueue>()
+            .init_resource::<BoardScratch>()
+            .add_system_to_stage(CoreStage::PreUpdate, Self::tick_clock)
+            .add_system(Self::sync_settings.before(BoardSystem::Input))
+            .add_system(Self::relayout_board.before(BoardSystem::Input))
+            .add_system_set(SystemSet::on_enter(AppState::Playing).with_system(Self::create_board))
+            .add_system(Self::apply_options.before(Self::regenerate_board))
+            .add_system(Self::regenerate_board.before(BoardSystem::Input))
+            .add_system(Self::fill_queue.after(Self::regenerate_board))
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Input)
+                    .with_system(Self::handle_input),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Uncover)
+                    .after(BoardSystem::Input)
+                    .with_system(Self::uncover_tiles)
+                    .with_system(Self::mark_tiles),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Rules)
+                    .after(BoardSystem::Uncover)
+                    .with_system(Self::detect_loss)
+                    .with_system(Self::reveal_wave.after(Self::detect_loss))
+                    .with_system(Self::detect_win.after(Self::detect_loss))
+                    .with_system(Self::deduce_mines),
+            )
+            .add_stage_after(
+                CoreStage::Update,
+                BoardStage::Render,
+                SystemStage::parallel(),
+            )
+            .add_system_set_to_stage(
+                BoardStage::Render,
+                SystemSet::new()
+                    .label(BoardSystem::Render)
+                    .with_system(Self::sync_board)
+                    .with_system(Self::pop_tiles)
+                    .with_system(Self::animate_transition),
+            );
     }
 }
 
 impl BoardPlugin {
+    /// Spawn a new board from the `BoardOptions`, a [`SeededBoard`], or the saved game of a
+    /// [`ResumeGame`]. With adaptive density, new boards get the mines of the [`AdaptiveSkill`].
+    /// Other random boards come from the [`BoardQueue`] when it has one ready
+    #[allow(clippy::too_many_arguments)]
     pub fn create_board(
         mut cmds: Commands,
         board_options: Option<Res<BoardOptions>>,
+        resume: Option<Res<ResumeGame>>,
+        seeded: Option<Res<SeededBoard>>,
+        error: Option<Res<ErrorScreen>>,
         windows: Res<Windows>,
-        asset_server: Res<AssetServer>,
+        assets: Res<BoardAssets>,
+        skill: Option<Res<AdaptiveSkill>>,
+        queue: Option<ResMut<BoardQueue>>,
     ) {
-        let font: Handle<Font> = asset_server.load("fonts/robotoslab.ttf");
-        let mine_image: Handle<Image> = asset_server.load("sprites/bomb.png");
-        let options = board_options.map(|res| res.clone()).unwrap_or_default();
+        if error.is_some() {
+            return;
+        }
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!("create_board").entered();
+
+        let mut options = board_options.map(|res| res.clone()).unwrap_or_default();
+        // boards every player shares keep their mines, and so do the games without stats
+        let rules = RuleSet::from_options(&options);
+        let adaptive = options
+            .adaptive
+            .filter(|_| seeded.is_none() && resume.is_none() && rules.recorded && !rules.versus);
+        let shared = seeded.is_some();
+        // boards every player shares come from the default backend, whatever `--rng` says
+        let (seed, backend) = match seeded {
+            Some(seeded) => {
+                cmds.remove_resource::<SeededBoard>();
+                options.mode = seeded.mode;
+                options.difficulty = seeded.difficulty.clone();
+                // the seed says nothing of the tiling, every player gets the same squares
+                options.topology = SQUARE.to_owned();
+                (seeded.seed, RngBackend::default())
+            }
+            None => (rng::entropy().next_u64(), rng::backend()),
+        };
+        if let Err(error) = options.validate() {
+            cmds.insert_resource(ErrorScreen::new("Invalid board options", error));
+            return;
+        }
+        cmds.remove_resource::<AdaptiveBoard>();
 
-        let mut tile_map = TileMap::from_options(&options);
+        let restored = resume.map(|resume| {
+            cmds.remove_resource::<ResumeGame>();
+            resume.0.restore()
+        });
+        let (mut tile_map, state) = match restored {
+            Some(Ok((tile_map, state))) => {
+                cmds.remove_resource::<BoardSeed>();
+                options.difficulty = Difficulty {
+                    dim: uvec2(tile_map.width(), tile_map.height()),
+                    n_mines: tile_map.n_mines(),
+                };
+                options.topology = tile_map.topology().name().to_owned();
+                (tile_map, state)
+            }
+            restored => {
+                if let Some(Err(error)) = restored {
+                    warn!("Could not resume the saved game: {error}");
+                }
+                if let Some(adaptive) = adaptive {
+                    let skill = skill.map_or(INITIAL_SKILL, |skill| skill.0.skill);
+                    let dim = options.difficulty.dim;
+                    let n_mines = adaptive.n_mines(skill, dim);
+                    let density = n_mines as f32 / (dim.x * dim.y) as f32;
+                    options.difficulty.n_mines = n_mines;
+                    cmds.insert_resource(AdaptiveBoard {
+                        density,
+                        position: adaptive.skill(density),
+                        recorded: false,
+                    });
+                }
+                let key = BoardKey::new(&options, backend);
+                let queued = queue
+                    .filter(|_| adaptive.is_none() && !shared)
+                    .and_then(|mut queue| queue.pop(key));
+                let board = queued.unwrap_or_else(|| QueuedBoard::generate(key, seed));
+                // kept so that bug reports can rebuild the board
+                cmds.insert_resource(BoardSeed(board.seed));
+                (board.tile_map, board.state)
+            }
+        };
+        // the `XrayPlugin` shows the mines in game, the dump is left for logs
         #[cfg(feature = "debug")]
-        info!("{:#}", tile_map);
+        bevy::log::debug!("{:#}", tile_map);
 
-        let window_dim = windows
-            .get_primary()
-            .unwrap()
-            .pipe(|window| [window.width(), window.height()].into());
+        let window_dim = Self::window_dim(&windows);
 
-        let DisplayParams {
-            board_size,
-            tile_size,
-            position,
-        } = options.display_params(window_dim);
+        cmds.insert_resource(GameStatus::Playing);
+        // later option changes wait for the next board
+        cmds.insert_resource(options.mode);
+        cmds.insert_resource(RuleSet::from_options(&options));
+        // the numbers revealed by a saved game, and what its puzzle gives
+        let graph = ConstraintGraph::new(tile_map.width(), tile_map.height(), tile_map.n_mines())
+            .rebuilt(&state);
+        cmds.insert_resource(graph);
+        let gaps = state.gaps().to_vec();
+        cmds.insert_resource(state);
+        cmds.insert_resource(UncoverQueue::new(UncoverBudget::for_board(
+            tile_map.width(),
+            tile_map.height(),
+        )));
+
+        let mut index = TileEntityIndex::new(tile_map.width(), tile_map.height())
+            .with_topology(tile_map.topology());
+        let display_params = options.display_params(window_dim);
+        cmds.insert_resource(display_params);
 
         cmds.spawn()
             .insert(Name::new("Board"))
+            .insert(Board)
             .insert_bundle(SpatialBundle {
                 visibility: Visibility::visible(),
-                transform: Transform::from_translation(position),
+                transform: Transform::from_translation(display_params.position),
                 ..Default::default()
             })
-            .with_children(Self::spawn_background(board_size))
+            .with_children(Self::spawn_background(display_params.board_size, &assets))
             .with_children(Self::spawn_tiles(
                 &mut tile_map,
-                tile_size,
+                &gaps,
+                &mut index,
+                display_params,
                 options.tile_padding,
-                mine_image,
-                font,
+                &assets,
             ));
+        cmds.insert_resource(index);
+    }
+
+    /// Keep boards of the `BoardOptions` ready in the [`BoardQueue`]
+    fn fill_queue(board_options: Option<Res<BoardOptions>>, mut queue: ResMut<BoardQueue>) {
+        let Some(options) = board_options.filter(|options| options.validate().is_ok()) else {
+            return;
+        };
+        queue.refill(BoardKey::new(&options, rng::backend()));
+    }
+
+    /// Follow the settings of the `BoardOptions`, however they were changed
+    pub fn sync_settings(
+        board_options: Option<Res<BoardOptions>>,
+        mut prefs: ResMut<MotionPrefs>,
+        mut input: ResMut<InputMap>,
+    ) {
+        let Some(options) = board_options.filter(|options| options.is_changed()) else {
+            return;
+        };
+
+        // only touch the resources on actual changes, systems may watch them
+        let new = MotionPrefs::from_options(&options);
+        if *prefs != new {
+            *prefs = new;
+        }
+        if *input != options.input {
+            *input = options.input.clone();
+        }
+    }
+
+    /// Move the tiles when the [`BoardView`] changes, the game goes on undisturbed
+    ///
+    /// [`BoardView`]: crate::resources::board_options::BoardView
+    #[allow(clippy::type_complexity)]
+    pub fn relayout_board(
+        board_options: Option<Res<BoardOptions>>,
+        params: Option<ResMut<DisplayParams>>,
+        windows: Res<Windows>,
+        mut boards: Query<&mut Transform, With<Board>>,
+        mut tiles: Query<(&BoardCoordinate, &mut Transform), Without<Board>>,
+        mut backgrounds: Query<
+            (&mut Sprite, &mut Transform),
+            (
+                With<BoardBackground>,
+                Without<Board>,
+                Without<BoardCoordinate>,
+            ),
+        >,
+    ) {
+        let (Some(options), Some(mut params)) = (board_options, params) else {
+            return;
+        };
+        if !options.is_changed() || options.view == params.view {
+            return;
+        }
+
+        let window_dim = Self::window_dim(&windows);
+        // the difficulty may have been edited without regenerating, keep the current map size.
+        // Tiles and their numbers keep their size as well until the next board
+        let options = BoardOptions {
+            tile_size: TileSize::Fixed(params.tile_size),
+            ..options.clone()
+        };
+        *params = options.layout(params.dim, window_dim);
+
+        for mut transform in &mut boards {
+            transform.translation = params.position;
+        }
+        for (coord, mut transform) in &mut tiles {
+            let center = params.tile_center(coord.inner);
+            transform.translation = center.extend(transform.translation.z);
+        }
+        for (mut sprite, mut transform) in &mut backgrounds {
+            sprite.custom_size = Some(params.board_size);
+            transform.translation = params.board_size.extend(0.0) / 2.0;
+        }
+    }
+
+    /// Take the options of the last [`OptionsApplyEvent`], if valid, and regenerate the board
+    pub fn apply_options(
+        mut events: EventReader<OptionsApplyEvent>,
+        board_options: Option<ResMut<BoardOptions>>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let (Some(event), Some(mut board_options)) = (events.iter().last(), board_options) else {
+            return;
+        };
+
+        match event.options.validate() {
+            Ok(()) => {
+                *board_options = event.options.clone();
+                regenerate.send(BoardRegenerateEvent);
+            }
+            Err(error) => warn!("Ignoring the new options: {error}"),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn regenerate_board(
+        mut cmds: Commands,
+        mut events: EventReader<BoardRegenerateEvent>,
+        boards: Query<Entity, With<Board>>,
+        mut index: ResMut<TileEntityIndex>,
+        board_options: Option<Res<BoardOptions>>,
+        resume: Option<Res<ResumeGame>>,
+        seeded: Option<Res<SeededBoard>>,
+        error: Option<Res<ErrorScreen>>,
+        windows: Res<Windows>,
+        assets: Res<BoardAssets>,
+        params: Option<Res<DisplayParams>>,
+        prefs: Res<MotionPrefs>,
+        mut transition: ResMut<BoardTransition>,
+        skill: Option<Res<AdaptiveSkill>>,
+        queue: Option<ResMut<BoardQueue>>,
+    ) {
+        if events.iter().count() == 0 {
+            return;
+        }
+
+        // options edited live may be broken, keep playing on the current board in that case
+        if let Some(Err(error)) = board_options.as_ref().map(|options| options.validate()) {
+            warn!("Not regenerating the board: {error}");
+            // a board that already left stays as it is, faded but playable
+            transition.swapped();
+            return;
+        }
+
+        // a board of another size waits for the current one to shrink away, see
+        // `animate_transition`, which asks for the board again once it is gone
+        match *transition {
+            BoardTransition::Idle => {
+                let new_dim = match (&resume, &seeded, &board_options) {
+                    (Some(resume), _, _) => uvec2(resume.0.width, resume.0.height),
+                    (None, Some(seeded), _) => seeded.difficulty.dim,
+                    (None, None, Some(options)) => options.difficulty.dim,
+                    (None, None, None) => BoardOptions::default().difficulty.dim,
+                };
+                let old_dim = params.map(|params| params.dim);
+                if BoardTransition::wanted(old_dim, new_dim, prefs.animate()) {
+                    transition.start();
+                    return;
+                }
+            }
+            BoardTransition::Leaving { .. } => return,
+            BoardTransition::Swapping | BoardTransition::Entering { .. } => {}
+        }
+        transition.swapped();
+
+        index.clear();
+        for board in &boards {
+            cmds.entity(board).despawn_recursive();
+        }
+        Self::create_board(
+            cmds,
+            board_options,
+            resume,
+            seeded,
+            error,
+            windows,
+            assets,
+            skill,
+            queue,
+        );
+    }
+
+    /// Left click uncovers a tile, right click toggles its flag, or the other way around with the
+    /// buttons of the [`InputMap`]. With
+    /// [`BoardOptions::click_on_release`], the click happens when the button is released over
+    /// the tile it was pressed on. With [`BoardOptions::confirm_proven_mines`], a proven mine
+    /// needs a second left click, the deductions coming from [`Self::deduce_mines`]. Flags and the
+    /// confirmation follow the [`RuleSet`]. Nothing happens while an overlay holds the
+    /// [`InputCapture`]
+    #[allow(clippy::too_many_arguments)]
+    pub fn handle_input(
+        buttons: Res<Input<MouseButton>>,
+        windows: Res<Windows>,
+        params: Option<Res<DisplayParams>>,
+        board_options: Option<Res<BoardOptions>>,
+        input: Res<InputMap>,
+        rules: Res<RuleSet>,
+        roles: Res<Roles>,
+        // what keeps clicks away from the board
+        (status, transition, capture): (Res<GameStatus>, Res<BoardTransition>, Res<InputCapture>),
+        graph: Option<Res<ConstraintGraph>>,
+        // button held down and the tile it was pressed on
+        mut pressed: Local<Option<(MouseButton, IVec2)>>,
+        // proven mine clicked once, waiting for the confirmation
+        mut unconfirmed: Local<Option<IVec2>>,
+        mut proven_clicks: EventWriter<ProvenMineClickEvent>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+        mut marks: EventWriter<TileMarkEvent>,
+        latency: Option<ResMut<InputLatency>>,
+    ) {
+        if *status != GameStatus::Playing || !transition.is_idle() || capture.is_captured() {
+            return;
+        }
+        let Some(params) = params else {
+            return;
+        };
+        let cursor = Self::cursor_position(&windows).and_then(|p| params.coord_at(p));
+        let buttons_pressed = [input.uncover, input.flag]
+            .into_iter()
+            .find(|&button| buttons.just_pressed(button));
+
+        let click = if board_options.as_ref().is_some_and(|o| o.click_on_release) {
+            if let Some(button) = buttons_pressed {
+                *pressed = cursor.map(|coord| (button, coord));
+            }
+            match *pressed {
+                Some((button, coord)) if buttons.just_released(button) => {
+                    *pressed = None;
+                    // dragging off the tile cancels the click
+                    (cursor == Some(coord)).then_some((button, coord))
+                }
+                _ => None,
+            }
+        } else {
+            buttons_pressed.zip(cursor)
+        };
+        let Some((button, coord)) = click else {
+            return;
+        };
+
+        if button == input.uncover {
+            if !rules.allows(roles.get(Player::Local), Action::Uncover(coord)) {
+                return;
+            }
+            let confirm =
+                rules.assists && board_options.is_some_and(|options| options.confirm_proven_mines);
+            let proven_mine = |graph: Res<ConstraintGraph>| {
+                graph.knowledge(coord) == Knowledge::Covered
+                    && graph.deduction(coord) == Some(Deduction::Mine)
+            };
+            if confirm && unconfirmed.take() != Some(coord) && graph.is_some_and(proven_mine) {
+                *unconfirmed = Some(coord);
+                proven_clicks.send(ProvenMineClickEvent { coord });
+                return;
+            }
+            triggers.send(TileTriggerEvent { coord });
+            if let Some(mut latency) = latency {
+                latency.mark(LatencyStage::Event, Some(coord), Instant::now());
+            }
+        } else if rules.allows(roles.get(Player::Local), Action::Flag(coord)) {
+            *unconfirmed = None;
+            marks.send(TileMarkEvent { coord });
+        }
+    }
+
+    /// Bring the deductions of the [`ConstraintGraph`] up to date after every change while proven
+    /// mines need a confirmation, so that [`Self::handle_input`] only has to read them
+    pub fn deduce_mines(
+        board_options: Option<Res<BoardOptions>>,
+        rules: Res<RuleSet>,
+        graph: Option<ResMut<ConstraintGraph>>,
+    ) {
+        let (Some(options), Some(mut graph)) = (board_options, graph) else {
+            return;
+        };
+        let turned_on = options.is_changed() || rules.is_changed();
+        if options.confirm_proven_mines && rules.assists && (turned_on || graph.is_changed()) {
+            graph.analyze();
+        }
+    }
+
+    /// Uncover triggered tiles, spreading to the neighbors of tiles without adjacent mines.
+    /// Triggering a revealed number chords it when the [`RuleSet`] allows it. Normal boards take
+    /// the whole cascade in the frame of the click, see [`BoardState::click_into`]. On huge ones
+    /// it stops when the frame budget of the [`UncoverQueue`] runs out, leaving the rest of the
+    /// cascade for the next frames
+    #[allow(clippy::too_many_arguments)]
+    pub fn uncover_tiles(
+        mut triggers: EventReader<TileTriggerEvent>,
+        mut mines: EventWriter<MineTriggerEvent>,
+        mut queue: ResMut<UncoverQueue>,
+        mut scratch: ResMut<BoardScratch>,
+        rules: Res<RuleSet>,
+        state: Option<ResMut<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        latency: Option<ResMut<InputLatency>>,
+    ) {
+        let (Some(mut state), Some(mut graph)) = (state, graph) else {
+            queue.extend(triggers.iter().map(|e| e.coord));
+            return;
+        };
+        let mut reveal = |coord, tile| match tile {
+            TileState::Mine => mines.send(MineTriggerEvent { coord }),
+            TileState::Clear(n) => graph.reveal(coord, n),
+        };
+
+        let mut uncovered = 0;
+        if queue.budget == UncoverBudget::Instant {
+            // clicks queued before the board was ready come first
+            let queued = std::iter::from_fn(|| queue.pop());
+            for coord in queued.chain(triggers.iter().map(|trigger| trigger.coord)) {
+                #[cfg(feature = "trace")]
+                let _span = bevy::log::info_span!("flood_fill").entered();
+
+                let revealed = state.click_into(coord, rules.chords, &mut scratch);
+                uncovered += revealed.len();
+                for &(coord, tile) in revealed {
+                    reveal(coord, tile);
+                }
+            }
+        } else {
+            for trigger in triggers.iter() {
+                queue.push(trigger.coord);
+                if rules.chords {
+                    queue.extend(state.chord_targets(trigger.coord));
+                }
+            }
+            if queue.is_empty() {
+                return;
+            }
+            #[cfg(feature = "trace")]
+            let _span = bevy::log::info_span!("flood_fill", queued = queue.len()).entered();
+
+            let start = Instant::now();
+            while !queue.budget.exhausted(uncovered, start) {
+                let Some(coord) = queue.pop() else {
+                    break;
+                };
+                let Some(tile) = state.uncover(coord) else {
+                    continue;
+                };
+
+                uncovered += 1;
+                reveal(coord, tile);
+                if tile == TileState::Clear(0) {
+                    queue.extend(state.neighbors(coord).filter(|&neighbor| {
+                        matches!(
+                            state.cover(neighbor),
+                            Some(Cover::Covered | Cover::Questioned)
+                        )
+                    }));
+                }
+            }
+        }
+        if let Some(mut latency) = latency.filter(|_| uncovered > 0) {
+            latency.mark(LatencyStage::State, None, Instant::now());
+        }
+    }
+
+    /// Cycle the mark of covered tiles, through question marks if the [`RuleSet`] has them
+    pub fn mark_tiles(
+        mut marks: EventReader<TileMarkEvent>,
+        rules: Res<RuleSet>,
+        state: Option<ResMut<BoardState>>,
+    ) {
+        let Some(mut state) = state else {
+            return;
+        };
+        for mark in marks.iter() {
+            state.cycle_mark(mark.coord, rules.question_marks);
+        }
+    }
+
+    /// Uncovering a mine ends the game and shows every mine in a [`RevealWave`], unless the
+    /// [`RuleSet`] forgives it. With reduced motion, the mines show all at once
+    pub fn detect_loss(
+        mut triggered: EventReader<MineTriggerEvent>,
+        rules: Res<RuleSet>,
+        motion: Res<MotionPrefs>,
+        mut status: ResMut<GameStatus>,
+        mut queue: ResMut<UncoverQueue>,
+        mut wave: ResMut<RevealWave>,
+        state: Option<ResMut<BoardState>>,
+    ) {
+        let Some(trigger) = triggered.iter().last() else {
+            return;
+        };
+        if *status != GameStatus::Playing {
+            return;
+        }
+        if rules.mistakes == Punishment::ShowMine {
+            info!("Mine triggered, playing on");
+            return;
+        }
+
+        info!("Mine triggered, game lost");
+        *status = GameStatus::Lost;
+        queue.clear();
+        if let Some(mut state) = state {
+            if motion.animate() {
+                *wave = RevealWave::new(trigger.coord, state.hidden_mines());
+            } else {
+                state.reveal_mines();
+            }
+        }
+    }
+
+    /// Size of the primary window, if any
+    fn window_dim(windows: &Windows) -> Vec2 {
+        windows.get_primary().map_or(HEADLESS_WINDOW, |window| {
+            Vec2::new(window.width(), window.height())
+        })
+    }
+
+    /// Advance the [`GameClock`] by the real time of the frame
+    fn tick_clock(time: Res<Time>, mut clock: ResMut<GameClock>) {
+        clock.tick(time.delta_seconds());
     }
 
-    fn spawn_background(size: Vec2) -> impl FnOnce(&mut ChildBuilder) {
+    /// Reveal the mines of the [`RevealWave`] once due, popping them out
+    pub fn reveal_wave(
+        mut cmds: Commands,
+        clock: Res<GameClock>,
+        status: Res<GameStatus>,
+        index: Res<TileEntityIndex>,
+        mut wave: ResMut<RevealWave>,
+        state: Option<ResMut<BoardState>>,
+    ) {
+        if wave.is_done() {
+            return;
+        }
+        // an undo or a new board ends the wave
+        let (GameStatus::Lost, Some(mut state)) = (*status, state) else {
+            wave.clear();
+            return;
+        };
+
+        for mine in wave.advance(clock.delta_seconds()) {
+            state.reveal_mine(mine);
+            if let Some(entity) = index.get(mine) {
+                cmds.entity(entity).insert(Pop {
+                    timer: Timer::from_seconds(POP_DURATION, false),
+                });
+            }
+        }
+    }
+
+    /// Shrink popped tiles back to their size
+    pub fn pop_tiles(
+        mut cmds: Commands,
+        clock: Res<GameClock>,
+        mut tiles: Query<(Entity, &mut Pop, &mut Transform)>,
+    ) {
+        for (entity, mut pop, mut transform) in &mut tiles {
+            let scale = if pop.timer.tick(clock.delta()).finished() {
+                cmds.entity(entity).remove::<Pop>();
+                1.0
+            } else {
+                1.0 + POP_SCALE * (1.0 - pop.timer.percent())
+            };
+            transform.scale = Vec3::new(scale, scale, 1.0);
+        }
+    }
+
+    /// Shrink and fade out the board while it leaves, then grow the new one in. Boards are scaled
+    /// around their center
+    #[allow(clippy::too_many_arguments)]
+    pub fn animate_transition(
+        time: Res<Time>,
+        params: Option<Res<DisplayParams>>,
+        mut transition: ResMut<BoardTransition>,
+        mut boards: Query<(Entity, &mut Transform), With<Board>>,
+        children: Query<&Children>,
+        mut sprites: Query<&mut Sprite>,
+        mut texts: Query<&mut Text>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        // whether the board is scaled, to restore it once the transition is over
+        mut scaled: Local<bool>,
+    ) {
+        if transition.is_idle() && !*scaled {
+            return;
+        }
+        let Some(params) = params else {
+            return;
+        };
+
+        if transition.advance(time.delta_seconds()) {
+            regenerate.send(BoardRegenerateEvent);
+        }
+        let (scale, alpha) = (transition.scale(), transition.alpha());
+        *scaled = !transition.is_idle();
+
+        let center = params.board_size.extend(0.0) / 2.0;
+        for (board, mut transform) in &mut boards {
+            transform.scale = Vec3::new(scale, scale, 1.0);
+            transform.translation = params.position + center * (1.0 - scale);
+            if alpha >= 1.0 {
+                continue;
+            }
+            // the old board is despawned after the fade, its colors need no restoring
+            let mut stack = vec![board];
+            while let Some(entity) = stack.pop() {
+                if let Ok(mut sprite) = sprites.get_mut(entity) {
+                    let faded = sprite.color.a().min(alpha);
+                    sprite.color.set_a(faded);
+                }
+                if let Ok(mut text) = texts.get_mut(entity) {
+                    for section in &mut text.sections {
+                        let faded = section.style.color.a().min(alpha);
+                        section.style.color.set_a(faded);
+                    }
+                }
+                stack.extend(children.get(entity).into_iter().flatten());
+            }
+        }
+    }
+
+    /// The game is won once the win condition of the [`RuleSet`] holds
+    pub fn detect_win(
+        rules: Res<RuleSet>,
+        state: Option<Res<BoardState>>,
+        mut status: ResMut<GameStatus>,
+        mut cleared: EventWriter<BoardClearEvent>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+        if *status != GameStatus::Playing || !state.is_changed() {
+            return;
+        }
+
+        if rules.is_won(&state) {
+            info!("Board cleared, game won");
+            *status = GameStatus::Won;
+            cleared.send(BoardClearEvent);
+        }
+    }
+
+    /// Apply the tiles changed in the [`BoardState`] to their entities: marker components, cover
+    /// visibility and flags, both ways for undos. Tiles whose entity already matches the state
+    /// are left untouched
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn sync_board(
+        mut cmds: Commands,
+        assets: Res<BoardAssets>,
+        state: Option<ResMut<BoardState>>,
+        index: Res<TileEntityIndex>,
+        tiles: Query<(
+            &Children,
+            Option<&Revealed>,
+            Option<&Flagged>,
+            Option<&Questioned>,
+        )>,
+        mut covers: Query<(Entity, &Sprite, &mut Visibility), With<TileCover>>,
+        mut scratch: ResMut<BoardScratch>,
+        mut latency: Option<ResMut<InputLatency>>,
+    ) {
+        let Some(mut state) = state else {
+            return;
+        };
+        if !state.has_changes() {
+            return;
+        }
+
+        state.swap_changes(&mut scratch.changes);
+        for &coord in &scratch.changes {
+            let Some(entity) = index.get(coord) else {
+                continue;
+            };
+            let Ok((children, revealed, flagged, questioned)) = tiles.get(entity) else {
+                continue;
+            };
+            let cover = state.cover(coord).unwrap();
+
+            if cover == Cover::Revealed {
+                if revealed.is_none() {
+                    cmds.entity(entity)
+                        .remove::<Covered>()
+                        .remove::<Flagged>()
+                        .remove::<Questioned>()
+                        .insert(Revealed);
+                    let mut covers = covers.iter_many_mut(children);
+                    while let Some((_, _, mut visibility)) = covers.fetch_next() {
+                        visibility.is_visible = false;
+                    }
+                    if let Some(latency) = &mut latency {
+                        latency.mark(LatencyStage::Sprite, Some(coord), Instant::now());
+                    }
+                }
+                continue;
+            }
+
+            // taken back by an undo, the flag of a revealed mine has to go as well
+            if revealed.is_some() {
+                cmds.entity(entity).remove::<Revealed>().insert(Covered);
+                let mut covers = covers.iter_many_mut(children);
+                while let Some((_, _, mut visibility)) = covers.fetch_next() {
+                    visibility.is_visible = true;
+                }
+            }
+            let shown = match (flagged, questioned) {
+                (Some(_), _) => Cover::Flagged,
+                (_, Some(_)) => Cover::Questioned,
+                _ => Cover::Covered,
+            };
+            if revealed.is_none() && shown == cover {
+                continue;
+            }
+
+            // the mark is the only child of a cover
+            let mut tile = cmds.entity(entity);
+            tile.remove::<Flagged>().remove::<Questioned>();
+            match cover {
+                Cover::Flagged => {
+                    tile.insert(Flagged);
+                }
+                Cover::Questioned => {
+                    tile.insert(Questioned);
+                }
+                _ => {}
+            }
+            for (cover_entity, sprite, _) in covers.iter_many(children) {
+                cmds.entity(cover_entity).despawn_descendants();
+                let size = sprite.custom_size.unwrap_or_default();
+                let mark = match cover {
+                    Cover::Flagged => cmds
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: Some(size),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(Vec3::Z),
+                            texture: assets.flag.clone(),
+                            ..Default::default()
+                        })
+                        .insert(Name::new("Flag"))
+                        .insert(FlagSprite)
+                        .id(),
+                    Cover::Questioned => cmds
+                        .spawn_bundle(Text2dBundle {
+                            text: Text::from_section(
+                                "?",
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: size.x,
+                                    color: Color::WHITE,
+                                },
+                            )
+                            .with_alignment(TextAlignment::CENTER),
+                            transform: Transform::from_translation(Vec3::Z),
+                            ..Default::default()
+                        })
+                        .insert(Name::new("Question Mark"))
+                        .id(),
+                    _ => continue,
+                };
+                cmds.entity(cover_entity).add_child(mark);
+            }
+        }
+    }
+
+    /// World position of the cursor, assuming the default 2D camera centered on the origin
+    pub fn cursor_position(windows: &Windows) -> Option<Vec2> {
+        let window = windows.get_primary()?;
+        let cursor = window.cursor_position()?;
+        Some(cursor - Vec2::new(window.width(), window.height()) / 2.0)
+    }
+
+    fn spawn_background(size: Vec2, assets: &BoardAssets) -> impl FnOnce(&mut ChildBuilder) {
+        let texture = assets.background.clone().unwrap_or_default();
         move |parent| {
             parent
                 .spawn_bundle(SpriteBundle {
@@ -79,82 +967,142 @@ impl BoardPlugin {
                         ..Default::default()
                     },
                     transform: Transform::from_translation(size.extend(0.0) / 2.0),
+                    texture,
                     ..Default::default()
                 })
-                .insert(Name::new("Background"));
+                .insert(Name::new("Background"))
+                .insert(BoardBackground);
         }
     }
 
-    fn spawn_tiles(
-        tile_map: &mut TileMap,
-        tile_size: f32,
+    fn spawn_tiles<'a>(
+        tile_map: &'a mut TileMap,
+        gaps: &'a [IVec2],
+        index: &'a mut TileEntityIndex,
+        params: DisplayParams,
         tile_padding: f32,
-        mine_image: Handle<Image>,
-        font: Handle<Font>,
-    ) -> impl FnOnce(&mut ChildBuilder) + '_ {
-        let sprite_size = Vec2::splat(tile_size - tile_padding);
+        assets: &'a BoardAssets,
+    ) -> impl FnOnce(&mut ChildBuilder) + 'a {
+        let sprite_size = Vec2::splat(params.tile_size - tile_padding);
 
         move |parent| {
+            #[cfg(feature = "trace")]
+            let _span =
+                bevy::log::info_span!("spawn_tiles", tiles = tile_map.width() * tile_map.height())
+                    .entered();
+
             tile_map.all_tiles().for_each(|tile| {
                 let mut tile_entity = parent.spawn_bundle(SpriteBundle {
                     sprite: Sprite {
-                        color: Color::GRAY,
+                        color: assets.tile_color(),
                         custom_size: sprite_size.into(),
                         ..Default::default()
                     },
-                    transform: Transform::from_translation({
-                        let coord = tile.coord().as_vec2() * tile_size + (tile_size / 2.0);
-                        coord.extend(1.0)
-                    }),
+                    transform: Transform::from_translation(
+                        params.tile_center(tile.coord()).extend(1.0),
+                    ),
+                    texture: assets.revealed.clone().unwrap_or_default(),
                     ..Default::default()
                 });
 
+                index.insert(tile.coord(), tile_entity.id());
                 tile_entity
                     .insert(Name::new(format!("Tile {:?}", tile.coord().to_array())))
                     .insert(BoardCoordinate {
                         inner: tile.coord(),
-                    });
-
-                match tile.state() {
-                    crate::resources::board::TileState::Mine => {
-                        tile_entity.insert(Mine).with_children(|parent| {
-                            parent.spawn_bundle(SpriteBundle {
+                        native: tile.native(),
+                    })
+                    .insert(Covered)
+                    .with_children(|parent| {
+                        parent
+                            .spawn_bundle(SpriteBundle {
                                 sprite: Sprite {
+                                    color: assets.cover_color(),
                                     custom_size: sprite_size.into(),
                                     ..Default::default()
                                 },
-                                transform: Transform::from_translation(Vec3::Z),
-                                texture: mine_image.clone(),
+                                // above the mine or number of the tile
+                                transform: Transform::from_translation(Vec3::Z * 2.0),
+                                texture: assets.covered.clone().unwrap_or_default(),
                                 ..Default::default()
-                            });
-                        });
-                    }
-                    crate::resources::board::TileState::Clear(n) if n > 0 => {
-                        tile_entity.insert(MineNeighbor(n)).with_children(|parent| {
-                            parent.spawn_bundle(Text2dBundle {
-                                text: Text::from_section(
-                                    n.to_string(),
-                                    TextStyle {
-                                        font: font.clone(),
-                                        font_size: sprite_size.x,
-                                        color: match n {
-                                            // 0 => Color::BLACK,
-                                            1 => Color::BLUE,
-                                            2 => Color::GREEN,
-                                            3 => Color::ORANGE,
-                                            _ => Color::RED,
-                                        },
-                                    },
-                                )
-                                .with_alignment(TextAlignment::CENTER),
-                                transform: Transform::from_translation(Vec3::Z),
-                                ..Default::default()
-                            });
-                        });
-                    }
-                    _ => {}
-                }
+                            })
+                            .insert(Name::new("Cover"))
+                            .insert(TileCover);
+                    });
+
+                // the gaps of a puzzle show as empty tiles
+                let content = match gaps.contains(&tile.coord()) {
+                    true => TileState::Clear(0),
+                    false => tile.state(),
+                };
+                Self::spawn_content(&mut tile_entity, content, sprite_size, assets);
             });
         }
     }
+
+    /// Mine or number of a tile, under its cover, also respawned by sandbox edits
+    pub fn spawn_content(
+        tile: &mut EntityCommands,
+        content: TileState,
+        sprite_size: Vec2,
+        assets: &BoardAssets,
+    ) {
+        match content {
+            TileState::Mine => {
+                tile.insert(Mine).with_children(|parent| {
+                    parent
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: sprite_size.into(),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(Vec3::Z),
+                            texture: assets.mine.clone(),
+                            ..Default::default()
+                        })
+                        .insert(MineSprite);
+                });
+            }
+            TileState::Clear(n) if n > 0 => {
+                // both, the skin picks which one shows
+                let digit = assets.digit(n);
+                tile.insert(MineNeighbor(n)).with_children(|parent| {
+                    parent
+                        .spawn_bundle(Text2dBundle {
+                            text: Text::from_section(
+                                assets.label(n),
+                                TextStyle {
+                                    font: assets.font.clone(),
+                                    font_size: sprite_size.x,
+                                    color: number_color(n),
+                                },
+                            )
+                            .with_alignment(TextAlignment::CENTER),
+                            transform: Transform::from_translation(Vec3::Z),
+                            visibility: Visibility {
+                                is_visible: digit.is_none(),
+                            },
+                            ..Default::default()
+                        })
+                        .insert(NumberText(n));
+                    parent
+                        .spawn_bundle(SpriteBundle {
+                            sprite: Sprite {
+                                custom_size: sprite_size.into(),
+                                color: assets.digit_color(n),
+                                ..Default::default()
+                            },
+                            transform: Transform::from_translation(Vec3::Z),
+                            visibility: Visibility {
+                                is_visible: digit.is_some(),
+                            },
+                            texture: digit.unwrap_or_default(),
+                            ..Default::default()
+                        })
+                        .insert(NumberSprite(n));
+                });
+            }
+            _ => {}
+        }
+    }
 }
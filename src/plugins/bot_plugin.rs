@@ -0,0 +1,101 @@
+use bevy::prelude::{
+    EventWriter, Input, Local, MouseButton, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+};
+
+use super::{BoardSystem, GameStatus, VersusPlugin};
+use crate::{
+    events::TileTriggerEvent,
+    resources::{
+        board_options::BoardOptions,
+        board_state::{BoardState, Cover},
+        game_clock::GameClock,
+        rule_set::RuleSet,
+        uncover_queue::UncoverQueue,
+        versus::Versus,
+    },
+    rng::{self, Rng},
+    solver::{self, BotSkill, ConstraintGraph},
+};
+
+/// Player the bot plays, the human moves first
+const BOT_PLAYER: usize = 1;
+
+/// Computer opponent for versus games, playing the second player with the
+/// [`BoardOptions::bot`] skill, so that single players can practice offline
+pub struct BotPlugin;
+
+impl Plugin for BotPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::block_input.before(BoardSystem::Input))
+            .add_system(
+                Self::play
+                    .after(BoardSystem::Input)
+                    .before(VersusPlugin::take_turns),
+            );
+    }
+}
+
+impl BotPlugin {
+    /// Skill of the bot to move, if any
+    fn bot_turn(
+        rules: &RuleSet,
+        options: Option<&BoardOptions>,
+        versus: &Versus,
+        status: GameStatus,
+    ) -> Option<BotSkill> {
+        let playing = rules.versus && status == GameStatus::Playing;
+        options
+            .and_then(|options| options.bot)
+            .filter(|_| playing && versus.turn() == BOT_PLAYER)
+    }
+
+    /// Keep the human from moving for the bot
+    fn block_input(
+        rules: Res<RuleSet>,
+        options: Option<Res<BoardOptions>>,
+        versus: Res<Versus>,
+        status: Res<GameStatus>,
+        mut buttons: ResMut<Input<MouseButton>>,
+    ) {
+        if Self::bot_turn(&rules, options.as_deref(), &versus, *status).is_some() {
+            buttons.clear();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        clock: Res<GameClock>,
+        rules: Res<RuleSet>,
+        options: Option<Res<BoardOptions>>,
+        versus: Res<Versus>,
+        status: Res<GameStatus>,
+        queue: Res<UncoverQueue>,
+        state: Option<Res<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        // seconds spent thinking on the current move
+        mut thinking: Local<f32>,
+        mut rng: Local<Option<Box<dyn Rng>>>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+    ) {
+        let skill = Self::bot_turn(&rules, options.as_deref(), &versus, *status);
+        let (Some(skill), Some(state), Some(mut graph)) = (skill, state, graph) else {
+            *thinking = 0.0;
+            return;
+        };
+        // the board settles before the bot looks at it
+        if !queue.is_empty() {
+            return;
+        }
+        *thinking += clock.delta_seconds();
+        if *thinking < skill.think_time() {
+            return;
+        }
+
+        *thinking = 0.0;
+        let rng = rng.get_or_insert_with(rng::entropy);
+        let covered = |coord| state.cover(coord) == Some(Cover::Covered);
+        if let Some(coord) = solver::bot_move(&mut graph, skill, &mut **rng, covered) {
+            triggers.send(TileTriggerEvent { coord });
+        }
+    }
+}
@@ -0,0 +1,210 @@
+use std::sync::{Arc, Mutex};
+
+use bevy::{
+    log::{error, warn},
+    prelude::{
+        info, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin,
+        Res, ResMut, SystemSet,
+    },
+    time::Time,
+};
+
+use super::{BoardSystem, GameStatus};
+use crate::{
+    bug_report::{BugReport, EventLog, REPORT_DIR},
+    events::{
+        BoardClearEvent, MineTriggerEvent, TileMarkEvent, TileTriggerEvent, ToastEvent, UndoEvent,
+    },
+    insights,
+    resources::{
+        board_options::BoardOptions, board_seed::BoardSeed, board_state::BoardState,
+        input_map::InputMap, toasts::ToastKind,
+    },
+    storage::{Location, Storage},
+};
+
+/// What a report is made of, as of the last change
+#[derive(Debug, Default, Clone)]
+struct Snapshot {
+    enabled: bool,
+    seed: Option<u64>,
+    options: Option<BoardOptions>,
+    log: EventLog,
+    state: Option<BoardState>,
+}
+
+impl Snapshot {
+    fn report(&self, created: u64) -> BugReport {
+        BugReport::capture(
+            created,
+            self.seed,
+            self.options.as_ref(),
+            &self.log,
+            self.state.as_ref(),
+        )
+    }
+}
+
+/// Latest snapshot, shared with the panic hook which cannot reach the world
+#[derive(Debug, Default, Clone)]
+struct SharedSnapshot(Arc<Mutex<Snapshot>>);
+
+/// Write a bug report in the `reports` directory of the data directory when the game panics,
+/// unless turned off in the `BoardOptions`, or when the report key of the `InputMap` is pressed,
+/// `F9` by default. See `bug_report`
+pub struct BugReportPlugin;
+
+impl Plugin for BugReportPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        let shared = SharedSnapshot::default();
+        if let Some(storage) = app.world.get_resource::<Storage>() {
+            Self::install_hook(storage.clone(), shared.clone());
+        }
+
+        app.init_resource::<EventLog>()
+            .insert_resource(shared)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::log_events)
+                    .with_system(Self::snapshot.after(Self::log_events)),
+            )
+            .add_system(Self::report_key.after(Self::log_events));
+    }
+}
+
+impl BugReportPlugin {
+    /// Write the latest snapshot before the default hook prints the panic
+    fn install_hook(storage: Storage, shared: SharedSnapshot) {
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |panic| {
+            // the panicking system may hold the lock, never wait for it
+            if let Ok(snapshot) = shared.0.try_lock() {
+                if snapshot.enabled {
+                    let report = BugReport {
+                        panic: Some(panic.to_string()),
+                        ..snapshot.report(insights::unix_time())
+                    };
+                    match report.write(&storage) {
+                        Ok(file) => warn!("Bug report written to {REPORT_DIR}/{file}"),
+                        Err(error) => error!("Could not write the bug report: {error}"),
+                    }
+                }
+            }
+            previous(panic);
+        }));
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn log_events(
+        time: Res<Time>,
+        status: Res<GameStatus>,
+        state: Option<Res<BoardState>>,
+        mut log: ResMut<EventLog>,
+        mut triggers: EventReader<TileTriggerEvent>,
+        mut marks: EventReader<TileMarkEvent>,
+        mut mines: EventReader<MineTriggerEvent>,
+        mut cleared: EventReader<BoardClearEvent>,
+        mut undos: EventReader<UndoEvent>,
+    ) {
+        let now = time.seconds_since_startup();
+        if let Some(state) = state.filter(|state| state.is_added()) {
+            let dim = state.dim();
+            let entry = format!("new {}x{} board, {} mines", dim.x, dim.y, state.n_mines());
+            log.push(now, entry);
+        }
+        for TileTriggerEvent { coord } in triggers.iter() {
+            log.push(now, format!("uncover ({}, {})", coord.x, coord.y));
+        }
+        for TileMarkEvent { coord } in marks.iter() {
+            log.push(now, format!("mark ({}, {})", coord.x, coord.y));
+        }
+        for MineTriggerEvent { coord } in mines.iter() {
+            log.push(now, format!("mine triggered at ({}, {})", coord.x, coord.y));
+        }
+        for _ in undos.iter() {
+            log.push(now, "undo");
+        }
+        if cleared.iter().count() > 0 {
+            log.push(now, "board cleared");
+        }
+        if status.is_changed() && *status == GameStatus::Lost {
+            log.push(now, "game lost");
+        }
+    }
+
+    /// Keep the snapshot of the panic hook up to date
+    fn snapshot(
+        shared: Res<SharedSnapshot>,
+        log: Res<EventLog>,
+        seed: Option<Res<BoardSeed>>,
+        board_options: Option<Res<BoardOptions>>,
+        state: Option<Res<BoardState>>,
+    ) {
+        let changed = log.is_changed()
+            || seed.as_ref().is_some_and(|seed| seed.is_changed())
+            || board_options.as_ref().is_some_and(|o| o.is_changed())
+            || state.as_ref().is_some_and(|state| state.is_changed());
+        if !changed {
+            return;
+        }
+
+        let Ok(mut snapshot) = shared.0.lock() else {
+            return;
+        };
+        *snapshot = Snapshot {
+            enabled: board_options.as_ref().is_none_or(|o| o.bug_reports),
+            seed: seed.map(|seed| seed.0),
+            options: board_options.map(|options| options.clone()),
+            log: log.clone(),
+            state: state.map(|state| state.clone()),
+        };
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn report_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        storage: Option<Res<Storage>>,
+        log: Res<EventLog>,
+        seed: Option<Res<BoardSeed>>,
+        board_options: Option<Res<BoardOptions>>,
+        state: Option<Res<BoardState>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if !keys.just_pressed(input.bug_report) {
+            return;
+        }
+        let Some(storage) = storage else {
+            return;
+        };
+
+        let report = BugReport::capture(
+            insights::unix_time(),
+            seed.map(|seed| seed.0),
+            board_options.as_deref(),
+            &log,
+            state.as_deref(),
+        );
+        match report.write(&storage) {
+            Ok(file) => {
+                let path = format!("{REPORT_DIR}/{file}");
+                let shown = storage
+                    .path(Location::Data, &path)
+                    .map_or(path, |path| path.display().to_string());
+                info!("Bug report written to {shown}");
+                toasts.send(ToastEvent::new(
+                    ToastKind::Success,
+                    "Bug report written",
+                    shown,
+                ));
+            }
+            Err(error) => toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Could not write the bug report",
+                error.to_string(),
+            )),
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, Color, Commands, DespawnRecursiveExt, Entity, EventReader,
+        EventWriter, Input, KeyCode, Name, ParallelSystemDescriptorCoercion, Plugin, Query, Res,
+        ResMut, SystemSet, Timer, Transform, Vec3,
+    },
+    text::{Text, Text2dBundle, TextAlignment, TextStyle},
+    time::Time,
+    window::Windows,
+};
+
+use super::{BoardPlugin, BoardSystem};
+use crate::{
+    components::PingMarker,
+    events::{ChatEvent, TilePingEvent, ToastEvent},
+    protocol::Emote,
+    resources::{
+        board_options::DisplayParams, input_map::InputMap, motion_prefs::MotionPrefs,
+        tile_index::TileEntityIndex, toasts::ToastKind,
+    },
+};
+
+/// Seconds a ping stays over its tile
+const PING_DURATION: f32 = 3.0;
+
+/// Lines kept in the chat
+const MAX_LINES: usize = 50;
+
+/// Name of this player in its own chat
+pub const LOCAL_PLAYER: &str = "You";
+
+/// Chat of the session, shown by the UI backend
+#[derive(Debug, Default, Clone)]
+pub struct Chat {
+    /// Sender and text, the last line at the back
+    pub lines: VecDeque<(String, String)>,
+    /// Emote of the pings of this player
+    pub emote: Emote,
+}
+
+/// `InputMap::ping`, `P` by default, pings the tile under the cursor with the emote chosen in the
+/// chat. Pings and chat lines are events, which a multiplayer session exchanges as
+/// `protocol::Message`s
+pub struct ChatPlugin;
+
+impl Plugin for ChatPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<TilePingEvent>()
+            .add_event::<ChatEvent>()
+            .init_resource::<Chat>()
+            .add_system(Self::ping_key.before(BoardSystem::Hud))
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::log_chat)
+                    .with_system(Self::show_pings)
+                    .with_system(Self::fade),
+            );
+    }
+}
+
+impl ChatPlugin {
+    fn ping_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        windows: Res<Windows>,
+        chat: Res<Chat>,
+        params: Option<Res<DisplayParams>>,
+        mut pings: EventWriter<TilePingEvent>,
+    ) {
+        if !keys.just_pressed(input.ping) {
+            return;
+        }
+        let cursor = BoardPlugin::cursor_position(&windows);
+        if let Some(coord) = cursor
+            .zip(params)
+            .and_then(|(p, params)| params.coord_at(p))
+        {
+            pings.send(TilePingEvent {
+                coord,
+                emote: chat.emote,
+            });
+        }
+    }
+
+    /// Keep the line, with a toast for the lines of the other players
+    fn log_chat(
+        mut lines: EventReader<ChatEvent>,
+        mut chat: ResMut<Chat>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        for ChatEvent { from, text } in lines.iter() {
+            if from != LOCAL_PLAYER {
+                toasts.send(ToastEvent::new(ToastKind::Info, from, text));
+            }
+            if chat.lines.len() == MAX_LINES {
+                chat.lines.pop_front();
+            }
+            chat.lines.push_back((from.clone(), text.clone()));
+        }
+    }
+
+    fn show_pings(
+        mut cmds: Commands,
+        asset_server: Res<AssetServer>,
+        mut pings: EventReader<TilePingEvent>,
+        params: Option<Res<DisplayParams>>,
+        index: Res<TileEntityIndex>,
+    ) {
+        let Some(params) = params else {
+            return;
+        };
+        for ping in pings.iter() {
+            let Some(tile) = index.get(ping.coord) else {
+                continue;
+            };
+            let color = match ping.emote {
+                Emote::Look => Color::YELLOW,
+                Emote::Mine => Color::RED,
+                Emote::Safe => Color::GREEN,
+            };
+            cmds.entity(tile).with_children(|parent| {
+                parent
+                    .spawn_bundle(Text2dBundle {
+                        text: Text::from_section(
+                            ping.emote.symbol(),
+                            TextStyle {
+                                font: asset_server.load("fonts/robotoslab.ttf"),
+                                font_size: params.tile_size,
+                                color,
+                            },
+                        )
+                        .with_alignment(TextAlignment::CENTER),
+                        // above the last move outline
+                        transform: Transform::from_translation(Vec3::Z * 5.0),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("Ping"))
+                    .insert(PingMarker {
+                        fade: Timer::from_seconds(PING_DURATION, false),
+                    });
+            });
+        }
+    }
+
+    /// Fade the emote out, or leave it solid until it goes with reduced motion
+    fn fade(
+        mut cmds: Commands,
+        time: Res<Time>,
+        motion: Res<MotionPrefs>,
+        mut markers: Query<(Entity, &mut PingMarker, &mut Text)>,
+    ) {
+        for (entity, mut marker, mut text) in &mut markers {
+            if marker.fade.tick(time.delta()).finished() {
+                cmds.entity(entity).despawn_recursive();
+                continue;
+            }
+            if motion.animate() {
+                text.sections[0]
+                    .style
+                    .color
+                    .set_a(1.0 - marker.fade.percent());
+            }
+        }
+    }
+}
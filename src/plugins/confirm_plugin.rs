@@ -0,0 +1,129 @@
+use bevy::{
+    input::gamepad::{GamepadButton, GamepadButtonType, Gamepads},
+    prelude::{
+        info, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin,
+        Res, ResMut,
+    },
+};
+
+use super::BoardSystem;
+use crate::{
+    events::{ConfirmAnswerEvent, ConfirmRequestEvent, ConfirmedEvent},
+    resources::input_capture::InputCapture,
+};
+
+/// Confirmation dialog on screen, drawn by the UI backend. The board takes no clicks while it is
+/// open
+#[derive(Debug, Default, Clone)]
+pub struct ShownConfirm {
+    pub request: Option<ConfirmRequestEvent>,
+    /// Button under the keyboard and gamepad focus, backing out until moved
+    pub focus_confirm: bool,
+}
+
+/// One dialog for every destructive action: a `ConfirmRequestEvent` opens it, and a
+/// `ConfirmedEvent` comes back once the player agrees. The arrows or the d-pad move between the
+/// buttons, `Return`, `Space` or the south button picks one, `Escape` or the east button backs
+/// out. A new request replaces the one on screen
+pub struct ConfirmPlugin;
+
+impl Plugin for ConfirmPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<ConfirmRequestEvent>()
+            .add_event::<ConfirmAnswerEvent>()
+            .add_event::<ConfirmedEvent>()
+            .init_resource::<ShownConfirm>()
+            .init_resource::<InputCapture>()
+            .add_system(Self::request)
+            .add_system(Self::navigate.after(Self::request).before(Self::answer))
+            .add_system(Self::answer)
+            .add_system(
+                Self::capture_input
+                    .after(Self::answer)
+                    .before(BoardSystem::Input),
+            );
+    }
+}
+
+impl ConfirmPlugin {
+    /// Keep the clicks from the board while a question is asked
+    fn capture_input(shown: Res<ShownConfirm>, mut capture: ResMut<InputCapture>) {
+        if shown.is_changed() {
+            capture.set("confirm", shown.request.is_some());
+        }
+    }
+
+    pub fn request(
+        mut requests: EventReader<ConfirmRequestEvent>,
+        mut shown: ResMut<ShownConfirm>,
+    ) {
+        let Some(request) = requests.iter().last() else {
+            return;
+        };
+        *shown = ShownConfirm {
+            request: Some(request.clone()),
+            focus_confirm: false,
+        };
+    }
+
+    fn navigate(
+        keys: Res<Input<KeyCode>>,
+        gamepads: Res<Gamepads>,
+        buttons: Res<Input<GamepadButton>>,
+        mut shown: ResMut<ShownConfirm>,
+        mut answers: EventWriter<ConfirmAnswerEvent>,
+    ) {
+        if shown.request.is_none() {
+            return;
+        }
+        let pad = |button| {
+            gamepads
+                .iter()
+                .any(|&gamepad| buttons.just_pressed(GamepadButton::new(gamepad, button)))
+        };
+
+        let moves = [
+            KeyCode::Left,
+            KeyCode::Right,
+            KeyCode::Up,
+            KeyCode::Down,
+            KeyCode::Tab,
+        ];
+        let pad_moves = [
+            GamepadButtonType::DPadLeft,
+            GamepadButtonType::DPadRight,
+            GamepadButtonType::DPadUp,
+            GamepadButtonType::DPadDown,
+        ];
+        if keys.any_just_pressed(moves) || pad_moves.into_iter().any(pad) {
+            shown.focus_confirm = !shown.focus_confirm;
+        }
+
+        if keys.any_just_pressed([KeyCode::Return, KeyCode::Space]) || pad(GamepadButtonType::South)
+        {
+            answers.send(ConfirmAnswerEvent {
+                confirmed: shown.focus_confirm,
+            });
+        } else if keys.just_pressed(KeyCode::Escape) || pad(GamepadButtonType::East) {
+            answers.send(ConfirmAnswerEvent { confirmed: false });
+        }
+    }
+
+    /// Close the dialog, carrying out the action when confirmed
+    pub fn answer(
+        mut answers: EventReader<ConfirmAnswerEvent>,
+        mut shown: ResMut<ShownConfirm>,
+        mut confirmed: EventWriter<ConfirmedEvent>,
+    ) {
+        let Some(answer) = answers.iter().last() else {
+            return;
+        };
+        let Some(request) = shown.request.take() else {
+            return;
+        };
+        if answer.confirmed {
+            info!("Confirmed: {}", request.title);
+            confirmed.send(ConfirmedEvent(request.action));
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Size, Style, UiRect,
+    },
+};
+
+use super::{screen_layout::spawn_row, ConfirmPlugin, PauseScreenPlugin, ShownConfirm};
+use crate::{
+    components::{ConfirmButton, ConfirmScreen},
+    events::ConfirmAnswerEvent,
+};
+
+/// Draw the question of the [`ShownConfirm`] with bevy_ui
+pub struct ConfirmScreenPlugin;
+
+impl Plugin for ConfirmScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // spawned after the pause menu, to cover it
+        app.add_system(
+            Self::sync_screen
+                .after(ConfirmPlugin::answer)
+                .after(PauseScreenPlugin::sync_screen),
+        )
+        .add_system(Self::click);
+    }
+}
+
+impl ConfirmScreenPlugin {
+    /// Question of the `ConfirmPlugin` over a dimmed screen, the focused button framed
+    fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownConfirm>,
+        screens: Query<Entity, With<ConfirmScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        let Some(request) = &shown.request else {
+            return;
+        };
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let button = |parent: &mut ChildBuilder, confirmed: bool, text: &str, color| {
+            let focused = shown.focus_confirm == confirmed;
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(6.0)),
+                        padding: UiRect::new(
+                            Val::Px(12.0),
+                            Val::Px(12.0),
+                            Val::Px(4.0),
+                            Val::Px(4.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: Color::rgba(1.0, 1.0, 1.0, if focused { 0.3 } else { 0.08 }).into(),
+                    ..Default::default()
+                })
+                .insert(ConfirmButton { confirmed })
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(text, style(22.0, color)));
+                });
+        };
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Confirm Screen"))
+        .insert(ConfirmScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_sections([
+                TextSection::new(format!("{}\n\n", request.title), style(32.0, Color::WHITE)),
+                TextSection::new(format!("{}\n", request.body), style(20.0, Color::GRAY)),
+            ]));
+            spawn_row(parent, 40.0, |row| {
+                button(row, false, "Cancel", Color::WHITE);
+                button(row, true, &request.confirm, Color::ORANGE_RED);
+            });
+        });
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &ConfirmButton), Changed<Interaction>>,
+        mut answers: EventWriter<ConfirmAnswerEvent>,
+    ) {
+        for (interaction, button) in &buttons {
+            if *interaction == Interaction::Clicked {
+                answers.send(ConfirmAnswerEvent {
+                    confirmed: button.confirmed,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,203 @@
+use std::collections::VecDeque;
+
+use bevy::{
+    ecs::world::{Mut, World},
+    input::InputSystem,
+    prelude::{
+        AssetServer, BuildChildren, Color, Commands, CoreStage, EventReader,
+        ExclusiveSystemDescriptorCoercion, Input, IntoExclusiveSystem, KeyCode, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, TextBundle, Val, Visibility,
+        With,
+    },
+    text::{Text, TextStyle},
+    ui::{PositionType, Size, Style, UiRect},
+    window::ReceivedCharacter,
+};
+
+use crate::{
+    components::{ConsoleRoot, ConsoleText},
+    console::CommandRegistry,
+    resources::game_assets::FONT,
+};
+
+/// Key opening and closing the console, `~` on US layouts
+const TOGGLE_KEY: KeyCode = KeyCode::Grave;
+
+/// Lines kept in the console
+const MAX_LINES: usize = 200;
+
+/// Lines shown above the prompt
+const SHOWN_LINES: usize = 12;
+
+/// Drop-down console of the debug builds, see [`ConsolePlugin`]
+#[derive(Debug, Default, Clone)]
+pub struct Console {
+    pub open: bool,
+    /// Line being typed
+    pub input: String,
+    /// Lines entered and their answers, the last line at the back
+    pub log: VecDeque<String>,
+    /// Lines entered, for `Up` and `Down`
+    history: Vec<String>,
+    // line of the history shown, `None` while typing a new one
+    recall: Option<usize>,
+    // lines entered this frame, run by `ConsolePlugin::run`
+    pending: Vec<String>,
+}
+
+impl Console {
+    fn print(&mut self, text: &str) {
+        for line in text.lines() {
+            if self.log.len() == MAX_LINES {
+                self.log.pop_front();
+            }
+            self.log.push_back(line.to_owned());
+        }
+    }
+}
+
+/// Debug only: press `~` to drop down a console running the commands of the [`CommandRegistry`],
+/// like `seed 42`, `reveal 3 4` or `exec script.txt`, see `console`. Typing goes to the console
+/// alone, the keys of the game wait until it closes
+pub struct ConsolePlugin;
+
+impl Plugin for ConsolePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Console>()
+            .init_resource::<CommandRegistry>()
+            .add_startup_system(Self::spawn_console)
+            .add_system_to_stage(CoreStage::PreUpdate, Self::read_input.after(InputSystem))
+            // ahead of the board systems, which see the events of the commands this very frame
+            .add_system(Self::run.exclusive_system().at_start())
+            .add_system(Self::update_view);
+    }
+}
+
+impl ConsolePlugin {
+    fn spawn_console(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(0.0),
+                    left: Val::Px(0.0),
+                    ..Default::default()
+                },
+                padding: UiRect::all(Val::Px(6.0)),
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Name::new("Console"))
+        .insert(ConsoleRoot)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load(FONT),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(ConsoleText);
+        });
+    }
+
+    /// Type into the console while it is open, keeping the keys from the game
+    fn read_input(
+        mut console: ResMut<Console>,
+        mut keys: ResMut<Input<KeyCode>>,
+        mut chars: EventReader<ReceivedCharacter>,
+    ) {
+        let typed = chars
+            .iter()
+            .map(|received| received.char)
+            .filter(|&c| !c.is_control() && c != '`' && c != '~')
+            .collect::<String>();
+        if keys.just_pressed(TOGGLE_KEY) {
+            console.open = !console.open;
+            keys.clear();
+            return;
+        }
+        if !console.open {
+            return;
+        }
+
+        console.input.push_str(&typed);
+        if keys.just_pressed(KeyCode::Back) {
+            console.input.pop();
+        }
+        if keys.just_pressed(KeyCode::Escape) {
+            console.open = false;
+        }
+        if keys.just_pressed(KeyCode::Up) || keys.just_pressed(KeyCode::Down) {
+            let last = console.history.len().checked_sub(1);
+            console.recall = match (console.recall, keys.just_pressed(KeyCode::Up)) {
+                (None, true) => last,
+                (Some(line), true) => Some(line.saturating_sub(1)),
+                (Some(line), false) if Some(line) != last => Some(line + 1),
+                (_, false) => None,
+            };
+            console.input = console
+                .recall
+                .map_or_else(String::new, |line| console.history[line].clone());
+        }
+        if keys.just_pressed(KeyCode::Return) {
+            let line = std::mem::take(&mut console.input);
+            console.recall = None;
+            if !line.trim().is_empty() {
+                console.print(&format!("> {line}"));
+                console.history.push(line.clone());
+                console.pending.push(line);
+            }
+        }
+        // hotkeys of the game wait while typing
+        keys.clear();
+    }
+
+    /// Run the lines entered, the commands getting the whole `World`
+    fn run(world: &mut World) {
+        if world.resource::<Console>().pending.is_empty() {
+            return;
+        }
+        let pending = std::mem::take(&mut world.resource_mut::<Console>().pending);
+        world.resource_scope(|world, registry: Mut<CommandRegistry>| {
+            for line in pending {
+                let answer = registry.run(world, &line);
+                let mut console = world.resource_mut::<Console>();
+                match answer {
+                    Ok(answer) => console.print(&answer),
+                    Err(error) => console.print(&format!("Error: {error}")),
+                }
+            }
+        });
+    }
+
+    fn update_view(
+        console: Res<Console>,
+        mut roots: Query<&mut Visibility, With<ConsoleRoot>>,
+        mut texts: Query<&mut Text, With<ConsoleText>>,
+    ) {
+        if !console.is_changed() {
+            return;
+        }
+        for mut visibility in &mut roots {
+            visibility.is_visible = console.open;
+        }
+        let skipped = console.log.len().saturating_sub(SHOWN_LINES);
+        let mut lines = console
+            .log
+            .iter()
+            .skip(skipped)
+            .cloned()
+            .collect::<Vec<_>>();
+        lines.push(format!("> {}_", console.input));
+        for mut text in &mut texts {
+            text.sections[0].value = lines.join("\n");
+        }
+    }
+}
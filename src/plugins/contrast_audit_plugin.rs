@@ -0,0 +1,201 @@
+use bevy::{
+    prelude::{
+        info, warn, AssetEvent, Assets, BuildChildren, Color, Commands, DespawnRecursiveExt,
+        Entity, EventReader, Handle, Image, Input, KeyCode, Name, Plugin, Query, Res, ResMut,
+        SpriteBundle, SystemSet, Transform, Vec2, With,
+    },
+    sprite::Sprite,
+};
+use bevy_inspector_egui::{Inspectable, InspectorPlugin};
+
+use super::BoardSystem;
+use crate::{
+    components::{BoardCoordinate, ContrastMarker, MineNeighbor},
+    contrast::{average_color, contrast_ratio, Grade},
+    resources::{
+        board_assets::{number_color, BoardAssets},
+        board_options::{BoardOptions, DisplayParams, NumberGlyphs},
+        board_state::{BoardState, Cover},
+        input_map::InputMap,
+    },
+};
+
+/// Color of the markers, unlike any number
+const MARKER_COLOR: Color = Color::FUCHSIA;
+
+/// Side of the markers, relative to the tiles
+const MARKER_SCALE: f32 = 0.25;
+
+/// Number of the skin against the opened tile it is drawn on
+#[derive(Debug, Default, Clone, Inspectable)]
+struct ContrastPair {
+    number: u8,
+    ratio: f32,
+    /// See `Grade::name`
+    grade: String,
+    /// Under the ratio of large text
+    fails: bool,
+}
+
+/// Contrast of the numbers of the active skin, shown in the inspector
+#[derive(Debug, Default, Clone, Inspectable)]
+struct ContrastAudit {
+    /// Id of the skin pack, empty for the built-in look
+    skin: String,
+    failing: usize,
+    /// Empty until the images of the skin are loaded
+    pairs: Vec<ContrastPair>,
+}
+
+/// Whether the markers are shown
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct ContrastOverlay(bool);
+
+/// Debug only: check the numbers of the active skin against its opened tiles with the contrast
+/// ratios of the WCAG, see `contrast`. The inspector lists every pair with its grade, and the
+/// audit key of the `InputMap`, `F6` out of the box, marks the numbers on the board too faint to
+/// read, to help skin authors ship readable packs
+pub struct ContrastAuditPlugin;
+
+impl Plugin for ContrastAuditPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_plugin(InspectorPlugin::<ContrastAudit>::new())
+            .init_resource::<ContrastOverlay>()
+            .add_system(Self::audit)
+            .add_system(Self::toggle)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::update_markers),
+            );
+    }
+}
+
+impl ContrastAuditPlugin {
+    /// Grade the numbers again when the skin changes or one of its images is loaded
+    fn audit(
+        assets: Res<BoardAssets>,
+        images: Res<Assets<Image>>,
+        mut image_events: EventReader<AssetEvent<Image>>,
+        mut audit: ResMut<ContrastAudit>,
+    ) {
+        let watched = |handle: &Handle<Image>| {
+            assets.revealed.as_ref() == Some(handle)
+                || assets.digits.iter().flatten().any(|digit| digit == handle)
+        };
+        let loaded = image_events.iter().any(|event| match event {
+            AssetEvent::Created { handle } | AssetEvent::Modified { handle } => watched(handle),
+            AssetEvent::Removed { .. } => false,
+        });
+        if !assets.is_changed() && !loaded {
+            return;
+        }
+
+        let background = match &assets.revealed {
+            Some(revealed) => images.get(revealed).and_then(average_color),
+            None => Some(assets.tile_color()),
+        };
+        let pairs = (1..=8)
+            .filter_map(|n| {
+                // the white dice are tinted like the font
+                let color = match (assets.digit(n), assets.glyphs) {
+                    (Some(_), NumberGlyphs::Dice) | (None, _) => number_color(n),
+                    (Some(digit), _) => images.get(&digit).and_then(average_color)?,
+                };
+                let ratio = contrast_ratio(color, background?);
+                let grade = Grade::of(ratio);
+                Some(ContrastPair {
+                    number: n,
+                    ratio,
+                    grade: grade.name().to_owned(),
+                    fails: grade == Grade::Fail,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let failing = pairs.iter().filter(|pair| pair.fails).count();
+        let skin = assets.skin.clone().unwrap_or_default();
+        if failing > 0 {
+            warn!(
+                "{failing} numbers of the skin {skin:?} are too faint on the opened tiles, see the \
+                 contrast audit in the inspector"
+            );
+        }
+        *audit = ContrastAudit {
+            skin,
+            failing,
+            pairs,
+        };
+    }
+
+    fn toggle(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut overlay: ResMut<ContrastOverlay>,
+    ) {
+        if keys.just_pressed(input.contrast_audit) {
+            overlay.0 = !overlay.0;
+            info!("Contrast markers {}", if overlay.0 { "on" } else { "off" });
+        }
+    }
+
+    /// Put a marker on every opened number failing the audit, from scratch whenever the board or
+    /// the audit changes
+    #[allow(clippy::too_many_arguments)]
+    fn update_markers(
+        mut cmds: Commands,
+        overlay: Res<ContrastOverlay>,
+        audit: Res<ContrastAudit>,
+        board_options: Option<Res<BoardOptions>>,
+        params: Option<Res<DisplayParams>>,
+        state: Option<Res<BoardState>>,
+        numbers: Query<(Entity, &BoardCoordinate, &MineNeighbor)>,
+        markers: Query<Entity, With<ContrastMarker>>,
+    ) {
+        let changed = overlay.is_changed()
+            || audit.is_changed()
+            || state.as_ref().is_some_and(|state| state.is_changed())
+            || params.as_ref().is_some_and(|params| params.is_changed());
+        if !changed {
+            return;
+        }
+
+        markers
+            .iter()
+            .for_each(|marker| cmds.entity(marker).despawn_recursive());
+        let (true, Some(state), Some(params)) = (overlay.0, state, params) else {
+            return;
+        };
+
+        let padding = board_options.map_or(0.0, |options| options.tile_padding);
+        let tile = params.tile_size - padding;
+        let size = tile * MARKER_SCALE;
+        // in the top right corner, clear of the number
+        let corner = Vec2::splat((tile - size) / 2.0);
+        for (entity, coord, MineNeighbor(n)) in &numbers {
+            let fails = audit
+                .pairs
+                .iter()
+                .any(|pair| pair.number == *n && pair.fails);
+            if !fails || state.cover(coord.inner) != Some(Cover::Revealed) {
+                continue;
+            }
+            cmds.entity(entity).with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: MARKER_COLOR,
+                            custom_size: Some(Vec2::splat(size)),
+                            ..Default::default()
+                        },
+                        // above the number, under the cover
+                        transform: Transform::from_translation(corner.extend(1.5)),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("Contrast marker"))
+                    .insert(ContrastMarker);
+            });
+        }
+    }
+}
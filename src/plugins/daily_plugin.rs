@@ -0,0 +1,259 @@
+use bevy::prelude::{
+    info, Commands, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion,
+    Plugin, Res, ResMut, SystemSet,
+};
+
+use super::{BoardPlugin, BoardSystem, GameStatus, InsightsPlugin};
+use crate::{
+    daily::{format_countdown, until_next, DailyChallenge, DAILY_DIFFICULTY, DAILY_MODE},
+    events::{BoardRegenerateEvent, DailyNoteEvent, DailyPlayEvent, DailyWarmUpEvent, ToastEvent},
+    insights,
+    resources::{
+        board_seed::BoardSeed, board_state::BoardState, input_map::InputMap, toasts::ToastKind,
+    },
+    rng,
+    save::{self, SessionLog, Stats, SESSION_FILE, STATS_FILE},
+    storage::{Location, Storage},
+};
+
+/// Board of the daily challenge on screen
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DailyBoard {
+    /// The challenge of the date
+    Attempt(String),
+    /// A practice board before the challenge of the date
+    WarmUp(String),
+}
+
+impl DailyBoard {
+    /// `YYYY-MM-DD` in UTC
+    pub fn date(&self) -> &str {
+        match self {
+            DailyBoard::Attempt(date) | DailyBoard::WarmUp(date) => date,
+        }
+    }
+
+    pub fn is_warm_up(&self) -> bool {
+        matches!(self, DailyBoard::WarmUp(_))
+    }
+}
+
+/// Daily board on screen, `None` for any other board. Games of the challenge are recorded in a
+/// category of their own, warm-ups as practice
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ActiveDaily(pub Option<DailyBoard>);
+
+/// Date of the daily challenge just finished, `None` otherwise. The UI backend offers to attach
+/// a note to it, kept with the stats and shown in the calendar of the insights
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ShownDailyNote(pub Option<String>);
+
+/// Seed of the last warm-up handed out, recognized once its board is created
+#[derive(Debug, Default, Clone, Copy)]
+pub struct WarmUpSeed(pub Option<u64>);
+
+/// Daily challenge: the same board for every player for a UTC day, see `daily`. `InputMap::daily`,
+/// `D` for right-handed players, plays it. It counts once a day: the first game recorded is the
+/// attempt, and the challenge is not dealt again until the next one. Before it, `Shift+D` deals
+/// warm-ups, as many as wanted, with the size and mines of the challenge but other seeds. Once the
+/// attempt is over, a short note can be attached to it, see `ShownDailyNote`
+pub struct DailyPlugin;
+
+impl Plugin for DailyPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<DailyPlayEvent>()
+            .add_event::<DailyWarmUpEvent>()
+            .add_event::<DailyNoteEvent>()
+            .init_resource::<ActiveDaily>()
+            .init_resource::<ShownDailyNote>()
+            .init_resource::<WarmUpSeed>()
+            .add_startup_system(Self::announce)
+            .add_system(Self::daily_key.before(Self::play).before(Self::warm_up))
+            // the board is rebuilt next frame, once the `SeededBoard` is inserted
+            .add_system(Self::play.after(BoardPlugin::regenerate_board))
+            .add_system(Self::warm_up.after(BoardPlugin::regenerate_board))
+            .add_system(Self::save_note)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::track.before(InsightsPlugin::record))
+                    .with_system(Self::offer_note.after(Self::track)),
+            );
+    }
+}
+
+impl DailyPlugin {
+    fn announce(input: Res<InputMap>, mut toasts: EventWriter<ToastEvent>) {
+        let now = insights::unix_time();
+        let daily = DailyChallenge::at(now);
+        toasts.send(ToastEvent::new(
+            ToastKind::Info,
+            "Daily challenge",
+            format!(
+                "{} {:?} of {}, press {:?} to play or Shift+{:?} to warm up. The next one comes \
+                 in {}",
+                DAILY_DIFFICULTY.name(),
+                DAILY_MODE,
+                daily.date,
+                input.daily,
+                input.daily,
+                format_countdown(until_next(now))
+            ),
+        ));
+    }
+
+    fn daily_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut plays: EventWriter<DailyPlayEvent>,
+        mut warm_ups: EventWriter<DailyWarmUpEvent>,
+    ) {
+        if !keys.just_pressed(input.daily) {
+            return;
+        }
+        if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+            warm_ups.send(DailyWarmUpEvent);
+        } else {
+            plays.send(DailyPlayEvent);
+        }
+    }
+
+    fn play(
+        mut cmds: Commands,
+        storage: Option<Res<Storage>>,
+        mut plays: EventReader<DailyPlayEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if plays.iter().count() == 0 {
+            return;
+        }
+        // read when asked, the game may have been running since yesterday
+        let now = insights::unix_time();
+        let daily = DailyChallenge::at(now);
+        let attempted = storage.is_some_and(|storage| {
+            let log = save::read::<SessionLog>(&storage, Location::Data, SESSION_FILE);
+            let games = log.ok().flatten().unwrap_or_default().games;
+            games
+                .iter()
+                .any(|game| !game.warm_up && game.daily.as_ref() == Some(&daily.date))
+        });
+        if attempted {
+            toasts.send(ToastEvent::new(
+                ToastKind::Warning,
+                "Daily challenge already played",
+                format!(
+                    "The challenge of {} counts once, the next one comes in {}. Warm-ups are \
+                     still open",
+                    daily.date,
+                    format_countdown(until_next(now))
+                ),
+            ));
+            return;
+        }
+
+        info!(
+            "Daily challenge of {}, seed {:#018x}",
+            daily.date, daily.seed
+        );
+        cmds.insert_resource(daily.board());
+        regenerate.send(BoardRegenerateEvent);
+    }
+
+    /// Deal a practice board like the challenge of the day
+    fn warm_up(
+        mut cmds: Commands,
+        mut warm_ups: EventReader<DailyWarmUpEvent>,
+        mut seed: ResMut<WarmUpSeed>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if warm_ups.iter().count() == 0 {
+            return;
+        }
+        let daily = DailyChallenge::at(insights::unix_time());
+        let board = daily.warm_up(rng::entropy().next_u64());
+        info!(
+            "Warm-up for the daily challenge of {}, seed {:#018x}",
+            daily.date, board.seed
+        );
+        seed.0 = Some(board.seed);
+        toasts.send(ToastEvent::new(
+            ToastKind::Info,
+            "Warm-up",
+            format!(
+                "Practice board for the daily challenge of {}, left out of its stats",
+                daily.date
+            ),
+        ));
+        cmds.insert_resource(board);
+        regenerate.send(BoardRegenerateEvent);
+    }
+
+    /// Tell whether the new board is the challenge of the day or a warm-up, from its seed
+    pub fn track(
+        state: Option<Res<BoardState>>,
+        seed: Option<Res<BoardSeed>>,
+        warm_up: Res<WarmUpSeed>,
+        mut active: ResMut<ActiveDaily>,
+    ) {
+        if !state.is_some_and(|state| state.is_added()) {
+            return;
+        }
+        let daily = DailyChallenge::at(insights::unix_time());
+        let board = seed.and_then(|seed| match Some(seed.0) {
+            seed if seed == Some(daily.seed) => Some(DailyBoard::Attempt(daily.date)),
+            seed if seed == warm_up.0 => Some(DailyBoard::WarmUp(daily.date)),
+            _ => None,
+        });
+        if active.0 != board {
+            active.0 = board;
+        }
+    }
+
+    /// Offer a note once the attempt at the challenge is over
+    fn offer_note(
+        status: Res<GameStatus>,
+        active: Res<ActiveDaily>,
+        mut shown: ResMut<ShownDailyNote>,
+    ) {
+        if !status.is_changed() {
+            return;
+        }
+        let date = match (&active.0, *status) {
+            (Some(DailyBoard::Attempt(date)), GameStatus::Won | GameStatus::Lost) => {
+                Some(date.clone())
+            }
+            _ => None,
+        };
+        if shown.0 != date {
+            shown.0 = date;
+        }
+    }
+
+    fn save_note(
+        mut notes: EventReader<DailyNoteEvent>,
+        storage: Option<Res<Storage>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(storage) = storage else {
+            return;
+        };
+        for DailyNoteEvent { date, note } in notes.iter() {
+            InsightsPlugin::update(&storage, STATS_FILE, |stats: &mut Stats| {
+                stats.set_note(date, note)
+            });
+            let title = if note.trim().is_empty() {
+                "Note removed"
+            } else {
+                "Note saved"
+            };
+            toasts.send(ToastEvent::new(
+                ToastKind::Success,
+                title,
+                format!("Daily challenge of {date}, see the insights"),
+            ));
+        }
+    }
+}
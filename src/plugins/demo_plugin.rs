@@ -0,0 +1,300 @@
+use bevy::{
+    input::{
+        mouse::{MouseMotion, MouseWheel},
+        InputSystem,
+    },
+    prelude::{
+        info, AssetServer, Camera2d, Color, Commands, CoreStage, EventReader, EventWriter, Input,
+        KeyCode, MouseButton, Name, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut,
+        TextBundle, Transform, Val, Visibility, With,
+    },
+    text::TextStyle,
+    time::Time,
+    ui::{PositionType, Style, UiRect},
+};
+
+use super::{BoardSystem, GameStatus, ResumeGame, ShownConfirm, ShownPause, SkinPacks};
+use crate::{
+    components::DemoBanner,
+    events::{BoardRegenerateEvent, PauseToggleEvent, TileTriggerEvent},
+    resources::{
+        board_options::{BoardOptions, Difficulty, GameMode},
+        board_seed::SeededBoard,
+        board_state::{BoardState, Cover},
+        board_transition::BoardTransition,
+        demo::DemoMode,
+        game_assets::FONT,
+        motion_prefs::MotionPrefs,
+        rule_set::RuleSet,
+        uncover_queue::UncoverQueue,
+    },
+    rng,
+    save::SaveGame,
+    solver::{self, BotSkill, ConstraintGraph},
+};
+
+/// Attract mode on screen, see [`DemoPlugin`]
+#[derive(Debug, Default, Clone)]
+pub struct Demo {
+    pub running: bool,
+    /// Boards started since the demo began
+    pub boards: usize,
+    // seconds on the pause menu without input
+    idle: f32,
+    // seconds since the demo began, driving the camera pans
+    elapsed: f32,
+    // seconds until the next move, or the next board once this one is over
+    wait: f32,
+    // a demo board was asked for and is not built yet
+    loading: bool,
+    // game and skin of the player, given back when the demo ends
+    saved: Option<SaveGame>,
+    skin: Option<String>,
+}
+
+/// Attract mode for kiosks, with a `DemoMode` in the options file: after its idle time on the
+/// pause menu, the solver plays random boards of the presets while the camera pans slowly and the
+/// skins take turns. Any key, click or mouse move brings the player's game back with the menu
+/// open. Demo games are left out of the stats
+pub struct DemoPlugin;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Demo>()
+            .add_startup_system(Self::spawn_banner)
+            // the input ending the demo is used up before the game sees it
+            .add_system_to_stage(CoreStage::PreUpdate, Self::watch.after(InputSystem))
+            .add_system(
+                Self::play
+                    .after(BoardSystem::Input)
+                    .before(BoardSystem::Uncover),
+            )
+            .add_system(Self::show_banner);
+    }
+}
+
+impl DemoPlugin {
+    fn spawn_banner(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(
+            TextBundle::from_section(
+                "Demo: press any key to play",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: 28.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(24.0),
+                    left: Val::Px(24.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .insert(Visibility { is_visible: false })
+        .insert(Name::new("Demo Banner"))
+        .insert(DemoBanner);
+    }
+
+    /// Start the demo once the pause menu stayed idle long enough, and end it on any input
+    #[allow(clippy::too_many_arguments)]
+    fn watch(
+        mut cmds: Commands,
+        time: Res<Time>,
+        board_options: Option<ResMut<BoardOptions>>,
+        (shown, confirm): (Res<ShownPause>, Res<ShownConfirm>),
+        (mut keys, mut buttons): (ResMut<Input<KeyCode>>, ResMut<Input<MouseButton>>),
+        (mut motion, mut wheel): (EventReader<MouseMotion>, EventReader<MouseWheel>),
+        (state, status, packs): (Option<Res<BoardState>>, Res<GameStatus>, Res<SkinPacks>),
+        mut demo: ResMut<Demo>,
+        mut cameras: Query<&mut Transform, With<Camera2d>>,
+        mut toggles: EventWriter<PauseToggleEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let moved = motion.iter().count() > 0;
+        let scrolled = wheel.iter().count() > 0;
+        let input = moved
+            || scrolled
+            || keys.get_pressed().next().is_some()
+            || buttons.get_pressed().next().is_some();
+        let Some(mut options) = board_options else {
+            return;
+        };
+
+        if demo.running {
+            if !input && options.demo.is_some() {
+                return;
+            }
+            info!("Leaving the demo after {} boards", demo.boards);
+            for mut transform in &mut cameras {
+                transform.translation.x = 0.0;
+                transform.translation.y = 0.0;
+            }
+            if options.skin != demo.skin {
+                options.skin = demo.skin.clone();
+            }
+            if let Some(game) = demo.saved.take() {
+                cmds.insert_resource(ResumeGame(game));
+            }
+            cmds.remove_resource::<SeededBoard>();
+            regenerate.send(BoardRegenerateEvent);
+            // back to the menu the demo started from
+            toggles.send(PauseToggleEvent);
+            keys.clear();
+            buttons.clear();
+            *demo = Demo::default();
+            return;
+        }
+
+        let Some(config) = options.demo.clone() else {
+            return;
+        };
+        if !shown.0 || confirm.request.is_some() || input {
+            demo.idle = 0.0;
+            return;
+        }
+        demo.idle += time.delta_seconds();
+        if demo.idle < config.idle {
+            return;
+        }
+
+        info!("Starting the demo");
+        let saved = state.filter(|state| *status == GameStatus::Playing && state.n_revealed() > 0);
+        *demo = Demo {
+            running: true,
+            saved: saved.map(|state| SaveGame::capture(&state)),
+            skin: options.skin.clone(),
+            ..Default::default()
+        };
+        toggles.send(PauseToggleEvent);
+        Self::next_board(
+            &mut cmds,
+            &mut demo,
+            &config,
+            &mut options,
+            &packs,
+            &mut regenerate,
+        );
+    }
+
+    /// Ask for a random board of the presets, in the next skin
+    fn next_board(
+        cmds: &mut Commands,
+        demo: &mut Demo,
+        config: &DemoMode,
+        options: &mut BoardOptions,
+        packs: &SkinPacks,
+        regenerate: &mut EventWriter<BoardRegenerateEvent>,
+    ) {
+        let found = packs
+            .0
+            .iter()
+            .map(|pack| pack.id.clone())
+            .collect::<Vec<_>>();
+        let skin = config.skin(demo.boards, &found);
+        if options.skin != skin {
+            options.skin = skin;
+        }
+
+        let mut rng = rng::entropy();
+        let presets = [Difficulty::EASY, Difficulty::MEDIUM, Difficulty::EXPERT];
+        cmds.insert_resource(SeededBoard {
+            seed: rng.next_u64(),
+            mode: GameMode::Classic,
+            difficulty: presets[rng.below(presets.len())].clone(),
+        });
+        regenerate.send(BoardRegenerateEvent);
+        demo.boards += 1;
+        demo.loading = true;
+    }
+
+    /// Pan the camera and play the board with the solver, a board after another
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        mut cmds: Commands,
+        time: Res<Time>,
+        board_options: Option<ResMut<BoardOptions>>,
+        prefs: Res<MotionPrefs>,
+        packs: Res<SkinPacks>,
+        (status, queue, transition): (Res<GameStatus>, Res<UncoverQueue>, Res<BoardTransition>),
+        (state, graph): (Option<Res<BoardState>>, Option<ResMut<ConstraintGraph>>),
+        mut rules: ResMut<RuleSet>,
+        mut demo: ResMut<Demo>,
+        mut cameras: Query<&mut Transform, With<Camera2d>>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let (Some(mut options), true) = (board_options, demo.running) else {
+            return;
+        };
+        let Some(config) = options.demo.clone() else {
+            return;
+        };
+
+        demo.elapsed += time.delta_seconds();
+        if prefs.animate() {
+            let pan = config.pan(demo.elapsed);
+            for mut transform in &mut cameras {
+                transform.translation.x = pan.x;
+                transform.translation.y = pan.y;
+            }
+        }
+
+        if demo.loading {
+            if !status.is_changed() || *status != GameStatus::Playing {
+                return;
+            }
+            demo.loading = false;
+            demo.wait = config.move_time;
+        }
+        if rules.recorded {
+            rules.recorded = false;
+        }
+        if status.is_changed() && *status != GameStatus::Playing {
+            demo.wait = config.board_time;
+        }
+        // the board settles before the solver looks at it
+        if !queue.is_empty() || !transition.is_idle() {
+            return;
+        }
+        demo.wait -= time.delta_seconds();
+        if demo.wait > 0.0 {
+            return;
+        }
+
+        if *status != GameStatus::Playing {
+            Self::next_board(
+                &mut cmds,
+                &mut demo,
+                &config,
+                &mut options,
+                &packs,
+                &mut regenerate,
+            );
+            return;
+        }
+        demo.wait = config.move_time;
+        let (Some(state), Some(mut graph)) = (state, graph) else {
+            return;
+        };
+        let covered = |coord| state.cover(coord) == Some(Cover::Covered);
+        let mut rng = rng::entropy();
+        if let Some(coord) = solver::bot_move(&mut graph, BotSkill::Perfect, &mut *rng, covered) {
+            triggers.send(TileTriggerEvent { coord });
+        }
+    }
+
+    fn show_banner(demo: Res<Demo>, mut banners: Query<&mut Visibility, With<DemoBanner>>) {
+        if !demo.is_changed() {
+            return;
+        }
+        for mut visibility in &mut banners {
+            if visibility.is_visible != demo.running {
+                visibility.is_visible = demo.running;
+            }
+        }
+    }
+}
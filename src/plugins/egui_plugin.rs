@@ -0,0 +1,1057 @@
+use bevy::{
+    input::InputSystem,
+    prelude::{
+        AssetServer, CoreStage, EventReader, EventWriter, Input, KeyCode, Local, MouseButton,
+        ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+    },
+};
+use bevy_egui::{
+    egui::{
+        self,
+        plot::{Bar, BarChart, Line, Plot, PlotPoints},
+        Align2, Color32, DragValue, Sense,
+    },
+    EguiContext, EguiPlugin,
+};
+
+use super::{
+    Achievements, AchievementsPlugin, BoardLibrary, Chat, ConfirmPlugin, InsightsPlugin,
+    LibraryPlugin, PausePlugin, ReplayViewer, Sandbox, SandboxPlugin, SaveSlots, SharePlugin,
+    ShownAdaptive, ShownConfirm, ShownDailyNote, ShownInsights, ShownLibrary, ShownPause,
+    ShownRating, ShownResult, ShownSlots, ShownTrophies, SkinPacks, SlotsPlugin, UndoHistory,
+    WeeklyPuzzles, LOCAL_PLAYER,
+};
+use crate::{
+    daily::{format_countdown, until_next, DailyChallenge},
+    events::{
+        ChatEvent, ConfirmAnswerEvent, DailyNoteEvent, DailyPlayEvent, DailyWarmUpEvent,
+        HintRequestEvent, InsightsToggleEvent, LibraryExportEvent, LibraryPlayEvent,
+        LibrarySortEvent, LibraryToggleEvent, OptionsApplyEvent, PasteBoardEvent, PauseActionEvent,
+        PauseToggleEvent, PlaybackEvent, ReplayAnnotateEvent, SandboxToggleEvent, ShareEvent,
+        SlotEvent, SlotsToggleEvent, TrophiesToggleEvent, UndoEvent, WeeklyPlayEvent,
+    },
+    insights::{unix_time, DailyResult, DayActivity, DAY},
+    protocol::{Emote, Message},
+    resources::{
+        board_assets::BoardAssets,
+        board_options::{BoardOptions, Difficulty, GameMode, NumberGlyphs, Rotation, MAX_DIM},
+        game_clock::{GameClock, SPEEDS},
+        input_map::InputMap,
+        rule_set::RuleSet,
+    },
+    save::{format_date, LibrarySort, Profile, MAX_ANNOTATION_CHARS, MAX_NOTE_CHARS},
+    solver::BotSkill,
+    topology,
+};
+
+/// Options being edited in the options window
+#[derive(Debug, Default)]
+struct OptionsDialog {
+    /// `None` while the window is closed
+    draft: Option<BoardOptions>,
+    /// Why the draft was rejected
+    error: Option<String>,
+}
+
+/// Menu, options and insights drawn with egui. Every button sends the same events as the keyboard
+/// shortcuts, so the game logic does not depend on the UI backend
+pub struct EguiUiPlugin;
+
+impl Plugin for EguiUiPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // the inspector may have added it already
+        if !app.world.contains_resource::<EguiContext>() {
+            app.add_plugin(EguiPlugin);
+        }
+
+        app.init_resource::<OptionsDialog>()
+            .add_system_to_stage(CoreStage::PreUpdate, Self::capture_input.after(InputSystem))
+            .add_system(Self::menu)
+            .add_system(Self::open_options.before(Self::options_window))
+            .add_system(Self::options_window.after(Self::menu))
+            .add_system(Self::pause_window.after(PausePlugin::toggle))
+            .add_system(Self::confirm_window.after(ConfirmPlugin::request))
+            .add_system(Self::insights_window.after(InsightsPlugin::toggle))
+            .add_system(Self::trophies_window.after(AchievementsPlugin::toggle))
+            .add_system(Self::library_window.after(LibraryPlugin::toggle))
+            .add_system(Self::slots_window.after(SlotsPlugin::toggle))
+            .add_system(Self::result_window.after(SharePlugin::sum_up))
+            .add_system(Self::chat_window)
+            .add_system(Self::replay_window)
+            .add_system(Self::sandbox_window.after(SandboxPlugin::toggle));
+    }
+}
+
+impl EguiUiPlugin {
+    /// Keep clicks and key presses meant for egui away from the board
+    fn capture_input(
+        mut egui: ResMut<EguiContext>,
+        mut buttons: ResMut<Input<MouseButton>>,
+        mut keys: ResMut<Input<KeyCode>>,
+    ) {
+        let ctx = egui.ctx_mut();
+        if ctx.wants_pointer_input() || ctx.is_pointer_over_area() {
+            buttons.clear();
+        }
+        if ctx.wants_keyboard_input() {
+            keys.clear();
+        }
+    }
+
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    fn menu(
+        mut egui: ResMut<EguiContext>,
+        board_options: Option<Res<BoardOptions>>,
+        input: Res<InputMap>,
+        rules: Res<RuleSet>,
+        history: Res<UndoHistory>,
+        profile: Option<Res<Profile>>,
+        weekly: Res<WeeklyPuzzles>,
+        mut dialog: ResMut<OptionsDialog>,
+        mut new_boards: EventWriter<PauseActionEvent>,
+        mut hints: EventWriter<HintRequestEvent>,
+        mut insights: EventWriter<InsightsToggleEvent>,
+        mut trophies: EventWriter<TrophiesToggleEvent>,
+        mut library: EventWriter<LibraryToggleEvent>,
+        mut undos: EventWriter<UndoEvent>,
+        // grouped, systems take at most 16 parameters
+        (mut weekly_plays, mut daily_plays, mut warm_ups, mut pauses, mut slots, mut pastes): (
+            EventWriter<WeeklyPlayEvent>,
+            EventWriter<DailyPlayEvent>,
+            EventWriter<DailyWarmUpEvent>,
+            EventWriter<PauseToggleEvent>,
+            EventWriter<SlotsToggleEvent>,
+            EventWriter<PasteBoardEvent>,
+        ),
+        (sandbox, mut sandboxes): (Res<Sandbox>, EventWriter<SandboxToggleEvent>),
+    ) {
+        egui::Window::new("Menu")
+            .anchor(Align2::RIGHT_TOP, [-10.0, 10.0])
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                if let Some(profile) = profile {
+                    let [r, g, b] = profile.color;
+                    ui.colored_label(Color32::from_rgb(r, g, b), &profile.name);
+                }
+                if ui.button("New game").clicked() {
+                    new_boards.send(PauseActionEvent::NewBoard);
+                }
+                if ui.button(format!("Pause ({:?})", input.menu)).clicked() {
+                    pauses.send(PauseToggleEvent);
+                }
+                let puzzle = weekly.current();
+                let button = egui::Button::new(format!("Puzzle of the week ({:?})", input.weekly));
+                let response = ui.add_enabled(puzzle.is_some(), button);
+                let response = match puzzle {
+                    Some(puzzle) => response.on_hover_text(format!(
+                        "Week of {}: {} {:?}",
+                        puzzle.week,
+                        puzzle.difficulty().name(),
+                        puzzle.mode
+                    )),
+                    None => response.on_disabled_hover_text("No puzzle this week"),
+                };
+                if response.clicked() {
+                    weekly_plays.send(WeeklyPlayEvent);
+                }
+                let now = unix_time();
+                let button = ui
+                    .button(format!("Daily challenge ({:?})", input.daily))
+                    .on_hover_text(format!(
+                        "{} UTC, the same board for everybody",
+                        DailyChallenge::at(now).date
+                    ));
+                if button.clicked() {
+                    daily_plays.send(DailyPlayEvent);
+                }
+                let button = ui
+                    .button(format!("Daily warm-up (Shift+{:?})", input.daily))
+                    .on_hover_text("A practice board like the daily, left out of its stats");
+                if button.clicked() {
+                    warm_ups.send(DailyWarmUpEvent);
+                }
+                ui.small(format!(
+                    "Next daily in {}",
+                    format_countdown(until_next(now))
+                ));
+                if ui.button("Options").clicked() {
+                    dialog.error = None;
+                    dialog.draft = match dialog.draft {
+                        Some(_) => None,
+                        None => Some(board_options.map(|o| o.clone()).unwrap_or_default()),
+                    };
+                }
+                if rules.undo
+                    && ui
+                        .add_enabled(
+                            !history.is_empty(),
+                            egui::Button::new(format!("Undo ({:?})", input.undo)),
+                        )
+                        .clicked()
+                {
+                    undos.send(UndoEvent);
+                }
+                let label = if sandbox.open { "Resume" } else { "Sandbox" };
+                if ui
+                    .button(format!("{label} ({:?})", input.sandbox))
+                    .on_hover_text("Edit the board to try lines out, it then leaves the stats")
+                    .clicked()
+                {
+                    sandboxes.send(SandboxToggleEvent);
+                }
+                if ui
+                    .add_enabled(
+                        rules.assists,
+                        egui::Button::new(format!("Hint ({:?})", input.hint)),
+                    )
+                    .clicked()
+                {
+                    hints.send(HintRequestEvent);
+                }
+                if ui
+                    .button(format!("Insights ({:?})", input.insights))
+                    .clicked()
+                {
+                    insights.send(InsightsToggleEvent);
+                }
+                if ui
+                    .button(format!("Trophies ({:?})", input.trophies))
+                    .clicked()
+                {
+                    trophies.send(TrophiesToggleEvent);
+                }
+                if ui
+                    .button(format!("Library ({:?})", input.library))
+                    .clicked()
+                {
+                    library.send(LibraryToggleEvent);
+                }
+                if ui.button(format!("Saves ({:?})", input.saves)).clicked() {
+                    slots.send(SlotsToggleEvent);
+                }
+                if ui
+                    .button("Paste board (Ctrl+V)")
+                    .on_hover_text("A board file, an ASCII board or a shared result")
+                    .clicked()
+                {
+                    pastes.send(PasteBoardEvent);
+                }
+            });
+    }
+
+    /// Open the options asked for from the pause menu
+    fn open_options(
+        mut actions: EventReader<PauseActionEvent>,
+        board_options: Option<Res<BoardOptions>>,
+        mut dialog: ResMut<OptionsDialog>,
+    ) {
+        let settings = actions
+            .iter()
+            .any(|action| matches!(action, PauseActionEvent::Settings));
+        if settings && dialog.draft.is_none() {
+            dialog.error = None;
+            dialog.draft = Some(board_options.map(|o| o.clone()).unwrap_or_default());
+        }
+    }
+
+    fn options_window(
+        mut egui: ResMut<EguiContext>,
+        asset_server: Res<AssetServer>,
+        packs: Res<SkinPacks>,
+        board_options: Option<ResMut<BoardOptions>>,
+        mut dialog: ResMut<OptionsDialog>,
+        mut apply: EventWriter<OptionsApplyEvent>,
+    ) {
+        let OptionsDialog { draft, error } = &mut *dialog;
+        let Some(options) = draft else {
+            return;
+        };
+
+        // registered before the window borrows the context
+        let preview = options
+            .skin
+            .as_deref()
+            .and_then(|id| packs.get(id))
+            .map(|pack| {
+                let assets = BoardAssets::from_pack(&asset_server, pack);
+                [
+                    assets.covered.clone(),
+                    Some(assets.flag.clone()),
+                    Some(assets.mine.clone()),
+                ]
+                .into_iter()
+                .chain((1..=3).map(|n| assets.digit(n)))
+                .flatten()
+                .map(|image| egui.add_image(image))
+                .collect::<Vec<_>>()
+            });
+
+        let mut open = true;
+        let mut submitted = false;
+        egui::Window::new("Options")
+            .open(&mut open)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.selectable_value(&mut options.mode, GameMode::Classic, "Classic");
+                    ui.selectable_value(&mut options.mode, GameMode::Zen, "Zen")
+                        .on_hover_text("No counters, unlimited undo, no stats");
+                    ui.selectable_value(&mut options.mode, GameMode::Hardcore, "Hardcore")
+                        .on_hover_text("No flags and no assists, with its own best times");
+                    ui.selectable_value(&mut options.mode, GameMode::Versus, "Versus")
+                        .on_hover_text("Two players taking turns, scoring the tiles they reveal");
+                    ui.selectable_value(&mut options.mode, GameMode::Nonogram, "Nonogram")
+                        .on_hover_text("Mines per row and column given, flag them all to win");
+                });
+                if options.mode == GameMode::Versus {
+                    ui.horizontal(|ui| {
+                        ui.label("Opponent");
+                        ui.selectable_value(&mut options.bot, None, "Hot-seat");
+                        for skill in BotSkill::ALL {
+                            ui.selectable_value(&mut options.bot, Some(skill), skill.name());
+                        }
+                    });
+                }
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for (name, preset) in [
+                        ("Easy", Difficulty::EASY),
+                        ("Medium", Difficulty::MEDIUM),
+                        ("Expert", Difficulty::EXPERT),
+                    ] {
+                        let selected = options.difficulty.dim == preset.dim
+                            && options.difficulty.n_mines == preset.n_mines;
+                        if ui.selectable_label(selected, name).clicked() {
+                            options.difficulty = preset;
+                        }
+                    }
+                });
+
+                egui::Grid::new("custom game").show(ui, |ui| {
+                    let difficulty = &mut options.difficulty;
+                    ui.label("Width");
+                    ui.add(DragValue::new(&mut difficulty.dim.x).clamp_range(1..=MAX_DIM));
+                    ui.end_row();
+                    ui.label("Height");
+                    ui.add(DragValue::new(&mut difficulty.dim.y).clamp_range(1..=MAX_DIM));
+                    ui.end_row();
+                    ui.label("Mines");
+                    ui.add(DragValue::new(&mut difficulty.n_mines));
+                    ui.end_row();
+                    ui.label("Tile padding");
+                    ui.add(DragValue::new(&mut options.tile_padding).clamp_range(0.0..=20.0));
+                    ui.end_row();
+                });
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Tiles");
+                    for name in topology::names() {
+                        let label = name.clone();
+                        ui.selectable_value(&mut options.topology, name, label);
+                    }
+                });
+                ui.checkbox(&mut options.safe_start, "Safe first click");
+                ui.checkbox(&mut options.highlight_last_move, "Highlight the last move");
+                ui.checkbox(
+                    &mut options.confirm_proven_mines,
+                    "Confirm clicks on proven mines",
+                );
+                ui.checkbox(&mut options.click_on_release, "Click on button release");
+                ui.checkbox(&mut options.question_marks, "Question marks");
+                ui.checkbox(&mut options.line_counts, "Mines left per row and column")
+                    .on_hover_text("An assist, wins with it set no best time");
+                ui.checkbox(&mut options.reduce_motion, "Reduce motion");
+                ui.checkbox(&mut options.bug_reports, "Write a bug report on crashes");
+
+                let mut left_handed = options.input.is_left_handed();
+                if ui
+                    .checkbox(&mut left_handed, "Left-handed controls")
+                    .changed()
+                {
+                    options.input = if left_handed {
+                        InputMap::LEFT_HANDED
+                    } else {
+                        InputMap::RIGHT_HANDED
+                    };
+                }
+
+                ui.separator();
+                ui.label("View, skin and numbers, applied right away");
+                ui.horizontal(|ui| {
+                    for (name, rotation) in [
+                        ("0°", Rotation::Deg0),
+                        ("90°", Rotation::Deg90),
+                        ("180°", Rotation::Deg180),
+                        ("270°", Rotation::Deg270),
+                    ] {
+                        ui.selectable_value(&mut options.view.rotation, rotation, name);
+                    }
+                    ui.checkbox(&mut options.view.mirror, "Mirror");
+                });
+                ui.horizontal(|ui| {
+                    ui.label("Skin");
+                    ui.selectable_value(&mut options.skin, None, "Built-in");
+                    for pack in &packs.0 {
+                        ui.selectable_value(&mut options.skin, Some(pack.id.clone()), pack.name())
+                            .on_hover_text(pack.manifest.author.as_deref().unwrap_or(""));
+                    }
+                });
+                if let Some(preview) = &preview {
+                    ui.horizontal(|ui| {
+                        for &image in preview {
+                            ui.image(image, [32.0, 32.0]);
+                        }
+                    });
+                }
+                ui.checkbox(&mut options.coordinates, "Row and column labels")
+                    .on_hover_text("Name tiles like C7");
+                ui.horizontal(|ui| {
+                    ui.label("Numbers");
+                    for glyphs in NumberGlyphs::ALL {
+                        let example = format!("{} ({})", glyphs.name(), glyphs.label(3));
+                        ui.selectable_value(&mut options.number_glyphs, glyphs, example);
+                    }
+                });
+
+                if let Some(error) = error.as_ref() {
+                    ui.colored_label(Color32::RED, error);
+                }
+                submitted = ui.button("Start a new game").clicked();
+            });
+
+        // turning the board or swapping its skin keeps the game going, unlike the other options
+        if let Some(mut current) = board_options.filter(|o| {
+            o.view != options.view
+                || o.skin != options.skin
+                || o.number_glyphs != options.number_glyphs
+                || o.coordinates != options.coordinates
+        }) {
+            current.view = options.view;
+            current.skin = options.skin.clone();
+            current.number_glyphs = options.number_glyphs;
+            current.coordinates = options.coordinates;
+        }
+
+        if submitted {
+            // checked here as well to keep the window open on mistakes
+            match options.validate() {
+                Ok(()) => {
+                    apply.send(OptionsApplyEvent {
+                        options: options.clone(),
+                    });
+                    open = false;
+                }
+                Err(e) => *error = Some(e.to_string()),
+            }
+        }
+        if !open {
+            *draft = None;
+        }
+    }
+
+    fn insights_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownInsights>,
+        rating: Res<ShownRating>,
+        mut toggles: EventWriter<InsightsToggleEvent>,
+    ) {
+        let Some(insights) = &shown.0 else {
+            return;
+        };
+
+        let mut open = true;
+        egui::Window::new("Insights")
+            .open(&mut open)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.label(format!(
+                    "{} games, {} won ({} without flags) in the last {} days",
+                    insights.games,
+                    insights.wins,
+                    insights.nf_wins,
+                    insights.calendar.len()
+                ));
+                if let Some(time) = insights.average_win_time {
+                    ui.label(format!("Average solve time: {time:.1}s"));
+                }
+                if let Some((kind, count)) = insights.common_loss {
+                    let kind = InsightsPlugin::describe(kind);
+                    ui.label(format!("Most common loss: {kind} ({count} times)"));
+                }
+
+                ui.separator();
+                ui.label("Efficiency of the wins");
+                let bars = insights
+                    .efficiency
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(day, e)| Some(Bar::new(day as f64, (*e)? as f64)))
+                    .collect();
+                Plot::new("efficiency")
+                    .height(120.0)
+                    .width(420.0)
+                    .include_y(1.0)
+                    .allow_drag(false)
+                    .allow_zoom(false)
+                    .show(ui, |plot| plot.bar_chart(BarChart::new(bars)));
+
+                ui.separator();
+                ui.label("Days played and won");
+                ui.horizontal(|ui| {
+                    ui.spacing_mut().item_spacing.x = 2.0;
+                    for (slot, day) in insights.calendar.into_iter().enumerate() {
+                        let color = match day {
+                            DayActivity::Idle => Color32::DARK_GRAY,
+                            DayActivity::Played => Color32::from_rgb(255, 165, 0),
+                            DayActivity::Won => Color32::GREEN,
+                        };
+                        let (rect, response) =
+                            ui.allocate_exact_size([12.0, 12.0].into(), Sense::hover());
+                        ui.painter().rect_filled(rect, 2.0, color);
+                        if let Some(daily) = insights.dailies.iter().find(|d| d.slot == slot) {
+                            response.on_hover_text(Self::describe_daily(daily));
+                        }
+                    }
+                });
+                for daily in insights.dailies.iter().filter(|daily| daily.note.is_some()) {
+                    ui.small(Self::describe_daily(daily));
+                }
+
+                if let Some(ratings) = &rating.0 {
+                    ui.separator();
+                    ui.label(format!(
+                        "Versus rating: {} after {} games",
+                        ratings.rating,
+                        ratings.history.len()
+                    ));
+                    let points = ratings
+                        .history
+                        .iter()
+                        .enumerate()
+                        .map(|(game, rated)| [game as f64 + 1.0, rated.rating as f64])
+                        .collect::<PlotPoints>();
+                    Plot::new("rating")
+                        .height(120.0)
+                        .width(420.0)
+                        .allow_drag(false)
+                        .allow_zoom(false)
+                        .show(ui, |plot| plot.line(Line::new(points)));
+                }
+            });
+
+        if !open {
+            toggles.send(InsightsToggleEvent);
+        }
+    }
+
+    /// `Daily 2024-01-02 lost: lost to a 50/50 at bottom-left`
+    fn describe_daily(daily: &DailyResult) -> String {
+        let outcome = if daily.won { "won" } else { "lost" };
+        match &daily.note {
+            Some(note) => format!("Daily {} {outcome}: {note}", daily.date),
+            None => format!("Daily {} {outcome}", daily.date),
+        }
+    }
+
+    /// Every achievement, the locked ones grayed out
+    fn trophies_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownTrophies>,
+        achievements: Res<Achievements>,
+        mut toggles: EventWriter<TrophiesToggleEvent>,
+    ) {
+        if !shown.0 {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Trophies")
+            .open(&mut open)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                let unlocked = &achievements.unlocked.0;
+                ui.label(format!(
+                    "{} of {} unlocked",
+                    achievements
+                        .definitions
+                        .iter()
+                        .filter(|achievement| unlocked.contains_key(&achievement.id))
+                        .count(),
+                    achievements.definitions.len()
+                ));
+                ui.separator();
+                for achievement in &achievements.definitions {
+                    let color = if unlocked.contains_key(&achievement.id) {
+                        Color32::GOLD
+                    } else {
+                        Color32::DARK_GRAY
+                    };
+                    ui.colored_label(color, &achievement.name);
+                    ui.label(&achievement.description);
+                }
+            });
+
+        if !open {
+            toggles.send(TrophiesToggleEvent);
+        }
+    }
+
+    /// Boards of the library, with the sort order and a button to play each
+    fn library_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownLibrary>,
+        library: Res<BoardLibrary>,
+        mut toggles: EventWriter<LibraryToggleEvent>,
+        mut sorts: EventWriter<LibrarySortEvent>,
+        mut plays: EventWriter<LibraryPlayEvent>,
+        mut exports: EventWriter<LibraryExportEvent>,
+    ) {
+        if !shown.0 {
+            return;
+        }
+
+        let mut open = true;
+        egui::Window::new("Library")
+            .open(&mut open)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Sort by");
+                    for sort in LibrarySort::ALL {
+                        if ui
+                            .selectable_label(library.sort == sort, sort.name())
+                            .clicked()
+                        {
+                            sorts.send(LibrarySortEvent(sort));
+                        }
+                    }
+                });
+                ui.separator();
+                if library.library.entries.is_empty() {
+                    ui.label("No boards yet, add the current one or copy board files here");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("library").striped(true).show(ui, |ui| {
+                            for entry in &library.library.entries {
+                                ui.label(&entry.file);
+                                ui.label(format!(
+                                    "{}x{}, {} mines",
+                                    entry.board.width,
+                                    entry.board.height,
+                                    entry.board.mines.len()
+                                ));
+                                ui.label(format!("{:.0}%", entry.density() * 100.0))
+                                    .on_hover_text("Mine density");
+                                ui.label(format!("3BV {}", entry.bbbv));
+                                if entry.solvable {
+                                    ui.colored_label(Color32::GREEN, "No guess");
+                                } else {
+                                    ui.colored_label(Color32::GRAY, "Guessing");
+                                }
+                                if ui.button("Play").clicked() {
+                                    plays.send(LibraryPlayEvent {
+                                        file: entry.file.clone(),
+                                    });
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                ui.separator();
+                if ui.button("Add the current board").clicked() {
+                    exports.send(LibraryExportEvent);
+                }
+            });
+
+        if !open {
+            toggles.send(LibraryToggleEvent);
+        }
+    }
+
+    /// Save slots with their thumbnail, the one under the keyboard focus highlighted
+    fn slots_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownSlots>,
+        input: Res<InputMap>,
+        mut slots: ResMut<SaveSlots>,
+        mut toggles: EventWriter<SlotsToggleEvent>,
+        mut changes: EventWriter<SlotEvent>,
+    ) {
+        if !shown.0 {
+            return;
+        }
+
+        // registered before the window borrows the context
+        let thumbnails = slots
+            .thumbnails
+            .iter()
+            .map(|(image, size)| (egui.add_image(image.clone()), *size))
+            .collect::<Vec<_>>();
+
+        let mut open = true;
+        let mut picked = None;
+        egui::Window::new("Saves")
+            .open(&mut open)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                if slots.slots.entries.is_empty() {
+                    ui.label("No saved games yet");
+                }
+                egui::ScrollArea::vertical()
+                    .max_height(300.0)
+                    .show(ui, |ui| {
+                        egui::Grid::new("slots").striped(true).show(ui, |ui| {
+                            let entries = slots.slots.entries.iter().zip(&thumbnails);
+                            for (i, (entry, &(texture, size))) in entries.enumerate() {
+                                ui.image(texture, [size.x, size.y]);
+                                let name = entry.difficulty().name();
+                                if ui.selectable_label(i == slots.selected, name).clicked() {
+                                    picked = Some(i);
+                                }
+                                let secs = entry.slot.elapsed as u64;
+                                ui.label(format!("{}:{:02}", secs / 60, secs % 60))
+                                    .on_hover_text("Time played");
+                                ui.label(format!("{:.0}%", entry.progress * 100.0))
+                                    .on_hover_text("Safe tiles revealed");
+                                ui.label(format_date(entry.slot.saved / DAY));
+                                if ui.button("Load").clicked() {
+                                    changes.send(SlotEvent::Load(entry.file.clone()));
+                                }
+                                if ui.button("Duplicate").clicked() {
+                                    changes.send(SlotEvent::Duplicate(entry.file.clone()));
+                                }
+                                if ui.button("Delete").clicked() {
+                                    changes.send(SlotEvent::Delete(entry.file.clone()));
+                                }
+                                ui.end_row();
+                            }
+                        });
+                    });
+                ui.separator();
+                if ui.button("Save the current game").clicked() {
+                    changes.send(SlotEvent::Save);
+                }
+                ui.small(input.slots.help());
+            });
+
+        if let Some(i) = picked {
+            slots.selected = i;
+        }
+        if !open {
+            toggles.send(SlotsToggleEvent);
+        }
+    }
+
+    /// Result of the game just finished, under the board, with the buttons copying it
+    fn result_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownResult>,
+        adaptive: Res<ShownAdaptive>,
+        daily: Res<ShownDailyNote>,
+        // note being typed
+        mut draft: Local<String>,
+        mut shares: EventWriter<ShareEvent>,
+        mut notes: EventWriter<DailyNoteEvent>,
+    ) {
+        let Some(result) = &shown.0 else {
+            return;
+        };
+
+        egui::Window::new("Result")
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -10.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.label(result.headline());
+                if let Some(adaptive) = adaptive.0 {
+                    ui.small(adaptive.describe());
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Copy result").clicked() {
+                        shares.send(ShareEvent { board: false });
+                    }
+                    if ui
+                        .button("Copy with the board")
+                        .on_hover_text("The board file after the result, to play it again")
+                        .clicked()
+                    {
+                        shares.send(ShareEvent { board: true });
+                    }
+                });
+                if let Some(date) = &daily.0 {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        let field = ui.add(
+                            egui::TextEdit::singleline(&mut *draft)
+                                .hint_text("Note, e.g. lost to a 50/50 at bottom-left"),
+                        );
+                        // egui 0.19 has no `char_limit`, the text is cut once typed
+                        let end = draft
+                            .char_indices()
+                            .nth(MAX_NOTE_CHARS)
+                            .map_or(draft.len(), |(i, _)| i);
+                        draft.truncate(end);
+                        let entered =
+                            field.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                        if ui.button("Save note").clicked() || entered {
+                            notes.send(DailyNoteEvent {
+                                date: date.clone(),
+                                note: std::mem::take(&mut *draft),
+                            });
+                        }
+                    });
+                }
+            });
+    }
+
+    /// Chat lines and the emote pinged with the key of the `InputMap`
+    fn chat_window(
+        mut egui: ResMut<EguiContext>,
+        input: Res<InputMap>,
+        mut chat: ResMut<Chat>,
+        // line being typed
+        mut draft: Local<String>,
+        mut lines: EventWriter<ChatEvent>,
+    ) {
+        egui::Window::new("Chat")
+            .anchor(Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                egui::ScrollArea::vertical()
+                    .max_height(160.0)
+                    .stick_to_bottom(true)
+                    .show(ui, |ui| {
+                        for (from, text) in &chat.lines {
+                            ui.label(format!("{from}: {text}"));
+                        }
+                    });
+
+                let field = ui.text_edit_singleline(&mut *draft);
+                if field.lost_focus() && ui.input().key_pressed(egui::Key::Enter) {
+                    if let Some(Message::Chat { from, text }) = Message::chat(LOCAL_PLAYER, &draft)
+                    {
+                        lines.send(ChatEvent { from, text });
+                    }
+                    draft.clear();
+                }
+
+                ui.horizontal(|ui| {
+                    ui.label(format!("Ping ({:?})", input.ping));
+                    for emote in Emote::ALL {
+                        ui.selectable_value(&mut chat.emote, emote, emote.symbol())
+                            .on_hover_text(emote.name());
+                    }
+                });
+            });
+    }
+
+    /// Quick actions of the pause menu, in the middle of the screen
+    fn pause_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownPause>,
+        input: Res<InputMap>,
+        board_options: Option<Res<BoardOptions>>,
+        mut toggles: EventWriter<PauseToggleEvent>,
+        mut actions: EventWriter<PauseActionEvent>,
+    ) {
+        if !shown.0 {
+            return;
+        }
+        let current = board_options.map_or_else(
+            || BoardOptions::default().difficulty,
+            |options| options.difficulty.clone(),
+        );
+
+        let mut open = true;
+        egui::Window::new("Paused")
+            .open(&mut open)
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                if ui.button(format!("Resume ({:?})", input.menu)).clicked() {
+                    toggles.send(PauseToggleEvent);
+                }
+                if ui.button("Restart this board").clicked() {
+                    actions.send(PauseActionEvent::Restart);
+                }
+                if ui
+                    .button("New board")
+                    .on_hover_text(current.name())
+                    .clicked()
+                {
+                    actions.send(PauseActionEvent::NewBoard);
+                }
+                ui.horizontal(|ui| {
+                    for preset in [Difficulty::EASY, Difficulty::MEDIUM, Difficulty::EXPERT] {
+                        let selected =
+                            current.dim == preset.dim && current.n_mines == preset.n_mines;
+                        if ui.selectable_label(selected, preset.name()).clicked() {
+                            actions.send(PauseActionEvent::Difficulty(preset));
+                        }
+                    }
+                });
+                if ui.button("Options").clicked() {
+                    actions.send(PauseActionEvent::Settings);
+                }
+                ui.separator();
+                if ui.button("Save and quit").clicked() {
+                    actions.send(PauseActionEvent::Quit);
+                }
+            });
+
+        if !open {
+            toggles.send(PauseToggleEvent);
+        }
+    }
+
+    /// Question of the `ConfirmPlugin` over everything else, the focused button highlighted
+    fn confirm_window(
+        mut egui: ResMut<EguiContext>,
+        shown: Res<ShownConfirm>,
+        mut answers: EventWriter<ConfirmAnswerEvent>,
+    ) {
+        let Some(request) = &shown.request else {
+            return;
+        };
+
+        egui::Window::new(request.title.as_str())
+            .anchor(Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.label(request.body.as_str());
+                ui.horizontal(|ui| {
+                    if ui
+                        .selectable_label(!shown.focus_confirm, "Cancel")
+                        .clicked()
+                    {
+                        answers.send(ConfirmAnswerEvent { confirmed: false });
+                    }
+                    let confirm = egui::RichText::new(&request.confirm).color(Color32::RED);
+                    if ui.selectable_label(shown.focus_confirm, confirm).clicked() {
+                        answers.send(ConfirmAnswerEvent { confirmed: true });
+                    }
+                });
+            });
+    }
+
+    /// Speed and time of the replay being watched, shown while it plays
+    fn replay_window(
+        mut egui: ResMut<EguiContext>,
+        input: Res<InputMap>,
+        clock: Res<GameClock>,
+        viewer: Option<Res<ReplayViewer>>,
+        mut events: EventWriter<PlaybackEvent>,
+        // annotation being typed
+        mut draft: Local<String>,
+        mut annotations: EventWriter<ReplayAnnotateEvent>,
+    ) {
+        let Some(viewer) = viewer else {
+            return;
+        };
+        egui::Window::new("Replay")
+            .anchor(Align2::CENTER_BOTTOM, [0.0, -10.0])
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                let moves = viewer.replay.moves.len();
+                let elapsed = viewer
+                    .started
+                    .map_or(0.0, |started| clock.elapsed_seconds() - started);
+                ui.label(format!(
+                    "Move {}/{moves}, {elapsed:.1}s",
+                    viewer.next.min(moves)
+                ));
+                // scrub bar, dragging it seeks every frame
+                let duration = viewer.replay.duration();
+                let mut time = (elapsed as f32).clamp(0.0, duration);
+                let slider = egui::Slider::new(&mut time, 0.0..=duration).show_value(false);
+                let seekable = viewer.keyframes.is_some() && duration > 0.0;
+                if ui
+                    .add_enabled(seekable, slider)
+                    .on_disabled_hover_text("This replay cannot be played ahead")
+                    .changed()
+                {
+                    events.send(PlaybackEvent::Seek(time));
+                }
+                ui.horizontal(|ui| {
+                    for speed in SPEEDS {
+                        if ui
+                            .selectable_label(clock.scale() == speed, format!("{speed}×"))
+                            .clicked()
+                        {
+                            events.send(PlaybackEvent::Speed(speed));
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    let pause = if clock.is_paused() { "Play" } else { "Pause" };
+                    if ui.button(format!("{pause} ({:?})", input.pause)).clicked() {
+                        events.send(PlaybackEvent::TogglePause);
+                    }
+                    if ui.button(format!("Step ({:?})", input.step)).clicked() {
+                        events.send(PlaybackEvent::Step);
+                    }
+                });
+                ui.separator();
+                ui.label(format!("{} annotations", viewer.replay.annotations.len()));
+                ui.horizontal(|ui| {
+                    let field = ui.add(
+                        egui::TextEdit::singleline(&mut *draft)
+                            .hint_text("Annotation, e.g. flag from the 1-2-1"),
+                    );
+                    // egui 0.19 has no `char_limit`, the text is cut once typed
+                    let end = draft
+                        .char_indices()
+                        .nth(MAX_ANNOTATION_CHARS)
+                        .map_or(draft.len(), |(i, _)| i);
+                    draft.truncate(end);
+                    let entered = field.lost_focus() && ui.input().key_pressed(egui::Key::Enter);
+                    if ui
+                        .button(format!("Annotate at {time:.1}s"))
+                        .on_hover_text("Shown over the tile of the last move, saved in the file")
+                        .clicked()
+                        || entered
+                    {
+                        annotations.send(ReplayAnnotateEvent {
+                            text: std::mem::take(&mut *draft),
+                        });
+                    }
+                });
+            });
+    }
+
+    /// Controls of the sandbox while it is open
+    fn sandbox_window(
+        mut egui: ResMut<EguiContext>,
+        input: Res<InputMap>,
+        sandbox: Res<Sandbox>,
+        mut toggles: EventWriter<SandboxToggleEvent>,
+    ) {
+        if !sandbox.open {
+            return;
+        }
+        egui::Window::new("Sandbox")
+            .anchor(Align2::LEFT_BOTTOM, [10.0, -10.0])
+            .resizable(false)
+            .show(egui.ctx_mut(), |ui| {
+                ui.label(format!("{:?} click: reveal or cover", input.uncover));
+                ui.label(format!("{:?} click: flag or unflag", input.flag));
+                ui.label(format!("Shift+{:?} click: move a mine", input.uncover));
+                match sandbox.held {
+                    Some(held) => ui.label(format!(
+                        "Mine of ({}, {}) picked up, drop it on a safe tile",
+                        held.x, held.y
+                    )),
+                    None => ui.small(format!(
+                        "{} edits, the game is left out of the stats once edited",
+                        sandbox.edits
+                    )),
+                };
+                if ui.button(format!("Resume ({:?})", input.sandbox)).clicked() {
+                    toggles.send(SandboxToggleEvent);
+                }
+            });
+    }
+}
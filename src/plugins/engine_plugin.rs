@@ -0,0 +1,153 @@
+use bevy::prelude::{
+    warn, Commands, EventWriter, Input, MouseButton, ParallelSystemDescriptorCoercion, Plugin, Res,
+    ResMut,
+};
+
+use super::{BoardSystem, GameStatus};
+use crate::{
+    engine::{format_move, Agent, EngineError},
+    events::{TileMarkEvent, TileTriggerEvent, ToastEvent},
+    resources::{board_state::BoardState, toasts::ToastKind, uncover_queue::UncoverQueue},
+    save::Action,
+    snapshot::BoardSnapshot,
+};
+
+/// Command of the agent to start, given with `--engine <command>`
+#[derive(Debug, Clone)]
+pub struct EngineCommand(pub String);
+
+/// Agent playing the board, see `engine`
+#[derive(Debug)]
+pub struct EngineAgent {
+    agent: Agent,
+    /// A snapshot is out, the agent owes a move
+    waiting: bool,
+    /// The agent got the last snapshot of the board
+    told_outcome: bool,
+}
+
+/// Let an external agent play on the board in place of the player, over the line protocol of
+/// `engine`. The player cannot click while the agent plays
+pub struct EnginePlugin;
+
+impl Plugin for EnginePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(Self::start)
+            .add_system(Self::block_input.before(BoardSystem::Input))
+            .add_system(Self::play.before(BoardSystem::Input));
+    }
+}
+
+impl EnginePlugin {
+    fn start(
+        mut cmds: Commands,
+        command: Option<Res<EngineCommand>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(command) = command else {
+            return;
+        };
+        cmds.remove_resource::<EngineCommand>();
+        match Agent::spawn(&command.0) {
+            Ok(agent) => cmds.insert_resource(EngineAgent {
+                agent,
+                waiting: false,
+                told_outcome: false,
+            }),
+            Err(error) => toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Engine",
+                error.to_string(),
+            )),
+        }
+    }
+
+    /// Keep the player from moving for the agent
+    fn block_input(engine: Option<Res<EngineAgent>>, mut buttons: ResMut<Input<MouseButton>>) {
+        if engine.is_some() {
+            buttons.clear();
+        }
+    }
+
+    /// Send a snapshot once the board settles, and the move of the agent once it comes
+    #[allow(clippy::too_many_arguments)]
+    fn play(
+        mut cmds: Commands,
+        engine: Option<ResMut<EngineAgent>>,
+        state: Option<Res<BoardState>>,
+        status: Res<GameStatus>,
+        queue: Res<UncoverQueue>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+        mut marks: EventWriter<TileMarkEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let (Some(mut engine), Some(state)) = (engine, state) else {
+            return;
+        };
+        if state.is_added() {
+            engine.waiting = false;
+            engine.told_outcome = false;
+        }
+        if !queue.is_empty() {
+            return;
+        }
+
+        let result = if engine.waiting {
+            Self::take_move(&mut engine, &state, &mut triggers, &mut marks)
+        } else if *status == GameStatus::Playing {
+            engine.waiting = true;
+            engine.agent.send(&BoardSnapshot::capture(&state))
+        } else if !engine.told_outcome {
+            engine.told_outcome = true;
+            engine.agent.send(&BoardSnapshot::capture(&state))
+        } else {
+            Ok(())
+        };
+
+        match result {
+            Ok(()) => {}
+            // the agent may try again
+            Err(error @ EngineError::Move { .. }) => {
+                warn!("{error}");
+                toasts.send(ToastEvent::new(
+                    ToastKind::Warning,
+                    "Engine",
+                    error.to_string(),
+                ));
+            }
+            Err(error) => {
+                cmds.remove_resource::<EngineAgent>();
+                toasts.send(ToastEvent::new(
+                    ToastKind::Error,
+                    "Engine",
+                    error.to_string(),
+                ));
+            }
+        }
+    }
+
+    fn take_move(
+        engine: &mut EngineAgent,
+        state: &BoardState,
+        triggers: &mut EventWriter<TileTriggerEvent>,
+        marks: &mut EventWriter<TileMarkEvent>,
+    ) -> Result<(), EngineError> {
+        let Some(action) = engine.agent.poll()? else {
+            return Ok(());
+        };
+        let (Action::Uncover(coord) | Action::Flag(coord)) = action;
+        if state.tile(coord).is_none() {
+            return Err(EngineError::Move {
+                line: format_move(action),
+                reason: "out of the board".to_owned(),
+            });
+        }
+
+        engine.waiting = false;
+        match action {
+            Action::Uncover(coord) => triggers.send(TileTriggerEvent { coord }),
+            Action::Flag(coord) => marks.send(TileMarkEvent { coord }),
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,138 @@
+use std::fmt::Display;
+
+use bevy::{
+    prelude::{
+        error, AssetServer, BuildChildren, ButtonBundle, Changed, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, Name, NodeBundle, Plugin, Query, Res, TextBundle,
+        Val, With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{AlignItems, FlexDirection, Interaction, JustifyContent, Size, Style, UiRect},
+};
+
+use crate::{
+    components::{ErrorScreenRoot, RetryButton},
+    events::RetryEvent,
+    resources::game_assets::FONT,
+};
+
+/// Fatal error shown in place of the game. Inserting it stops the board from being created
+#[derive(Debug, Clone)]
+pub struct ErrorScreen {
+    pub title: String,
+    pub message: String,
+    /// Offer a retry button, which sends a `RetryEvent` and closes the screen
+    pub retry: bool,
+}
+
+impl ErrorScreen {
+    pub fn new(title: impl Into<String>, message: impl Display) -> Self {
+        Self {
+            title: title.into(),
+            message: message.to_string(),
+            retry: false,
+        }
+    }
+
+    pub fn with_retry(self) -> Self {
+        Self {
+            retry: true,
+            ..self
+        }
+    }
+}
+
+pub struct ErrorScreenPlugin;
+
+impl Plugin for ErrorScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<RetryEvent>()
+            .add_system(Self::show_error)
+            .add_system(Self::retry);
+    }
+}
+
+impl ErrorScreenPlugin {
+    fn show_error(
+        mut cmds: Commands,
+        error: Option<Res<ErrorScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        let Some(error) = error.filter(|error| error.is_added()) else {
+            return;
+        };
+
+        error!("{}: {}", error.title, error.message);
+
+        let font = asset_server.load(FONT);
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgb(0.15, 0.05, 0.05).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Error Screen"))
+        .insert(ErrorScreenRoot)
+        .with_children(|parent| {
+            let hint = if error.retry {
+                "Check the game files, then retry"
+            } else {
+                "Fix the options and restart the game"
+            };
+            parent.spawn_bundle(TextBundle::from_sections([
+                TextSection::new(format!("{}\n\n", error.title), style(40.0, Color::RED)),
+                TextSection::new(format!("{}\n\n", error.message), style(24.0, Color::WHITE)),
+                TextSection::new(hint, style(18.0, Color::GRAY)),
+            ]));
+            if !error.retry {
+                return;
+            }
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(20.0)),
+                        padding: UiRect::all(Val::Px(10.0)),
+                        ..Default::default()
+                    },
+                    color: Color::DARK_GRAY.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("Retry Button"))
+                .insert(RetryButton)
+                .with_children(|button| {
+                    button
+                        .spawn_bundle(TextBundle::from_section("Retry", style(24.0, Color::WHITE)));
+                });
+        });
+    }
+
+    fn retry(
+        mut cmds: Commands,
+        buttons: Query<&Interaction, (Changed<Interaction>, With<RetryButton>)>,
+        screens: Query<Entity, With<ErrorScreenRoot>>,
+        mut retries: EventWriter<RetryEvent>,
+    ) {
+        if !buttons
+            .iter()
+            .any(|interaction| *interaction == Interaction::Clicked)
+        {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        cmds.remove_resource::<ErrorScreen>();
+        retries.send(RetryEvent);
+    }
+}
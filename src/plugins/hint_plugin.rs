@@ -0,0 +1,119 @@
+use bevy::{
+    prelude::{
+        AssetServer, Color, Commands, EventReader, EventWriter, Input, KeyCode, Name,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemSet, TextBundle, Val,
+        With,
+    },
+    text::{Text, TextStyle},
+    ui::{PositionType, Style, UiRect},
+};
+
+use super::BoardSystem;
+use crate::{
+    components::HintText,
+    events::{HintRequestEvent, ProvenMineClickEvent},
+    resources::{input_map::InputMap, rule_set::RuleSet},
+    solver::{self, ConstraintGraph, Hint},
+};
+
+/// Hint shown in the hint panel, if any
+#[derive(Debug, Default, Clone)]
+pub struct CurrentHint(pub Option<Hint>);
+
+/// Suggest the next move with the rule proving it on `InputMap::hint`, `H` unless rebound. Locked
+/// when the `RuleSet` forbids assists
+pub struct HintPlugin;
+
+impl Plugin for HintPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<HintRequestEvent>()
+            .init_resource::<CurrentHint>()
+            .add_startup_system(Self::spawn_panel)
+            .add_system(Self::hint_key.before(BoardSystem::Hud))
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::request_hint)
+                    .with_system(Self::update_panel.after(Self::request_hint))
+                    .with_system(Self::warn_proven_mine.after(Self::update_panel)),
+            );
+    }
+}
+
+impl HintPlugin {
+    fn spawn_panel(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/robotoslab.ttf"),
+                    font_size: 20.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        )
+        .insert(Name::new("Hint Panel"))
+        .insert(HintText);
+    }
+
+    fn hint_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut requests: EventWriter<HintRequestEvent>,
+    ) {
+        if keys.just_pressed(input.hint) {
+            requests.send(HintRequestEvent);
+        }
+    }
+
+    fn request_hint(
+        mut requests: EventReader<HintRequestEvent>,
+        rules: Res<RuleSet>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        mut current: ResMut<CurrentHint>,
+    ) {
+        let requested = requests.iter().count() > 0;
+        if let (true, true, Some(mut graph)) = (requested, rules.assists, graph) {
+            current.0 = solver::hint(&mut graph);
+        }
+    }
+
+    /// Ask for the second click a proven mine needs, see `BoardOptions::confirm_proven_mines`
+    fn warn_proven_mine(
+        mut clicks: EventReader<ProvenMineClickEvent>,
+        mut panel: Query<&mut Text, With<HintText>>,
+    ) {
+        let Some(ProvenMineClickEvent { coord }) = clicks.iter().last() else {
+            return;
+        };
+        for mut text in &mut panel {
+            text.sections[0].value = format!(
+                "({}, {}) is a mine, click again to uncover it anyway",
+                coord.x, coord.y
+            );
+        }
+    }
+
+    fn update_panel(current: Res<CurrentHint>, mut panel: Query<&mut Text, With<HintText>>) {
+        if !current.is_changed() {
+            return;
+        }
+
+        for mut text in &mut panel {
+            text.sections[0].value = match &current.0 {
+                Some(hint) => hint.to_string(),
+                None => "No hint available".to_owned(),
+            };
+        }
+    }
+}
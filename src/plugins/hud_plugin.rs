@@ -0,0 +1,366 @@
+use bevy::{
+    ecs::query::ChangeTrackers,
+    prelude::{
+        Added, AssetServer, BuildChildren, ChildBuilder, Children, Color, Commands, Component,
+        Entity, ImageBundle, Name, NodeBundle, Or, ParallelSystemDescriptorCoercion, Plugin, Query,
+        Res, SpriteBundle, SystemSet, TextBundle, Transform, Val, Vec2, Visibility, With, Without,
+    },
+    sprite::{Anchor, Sprite},
+    text::{Text, TextSection, TextStyle},
+    ui::{Display, JustifyContent, PositionType, Size, Style, UiImage, UiRect},
+};
+
+use super::{BoardSystem, GameStatus, InsightsPlugin, SessionTracker};
+use crate::{
+    components::{
+        Board, FlagCounterText, GameTimer, MineCounter, ProgressBar, ProgressTrack, SegmentDigit,
+        SegmentDisplay, SplitText,
+    },
+    resources::{
+        board_assets::{BoardAssets, SegmentGlyph},
+        board_options::DisplayParams,
+        board_state::BoardState,
+        game_clock::GameClock,
+        rule_set::RuleSet,
+    },
+    save::SPLITS,
+};
+
+/// Counter color once more flags than mines are placed
+const OVERFLAG_COLOR: Color = Color::RED;
+
+/// Split delta colors against the personal best
+const AHEAD_COLOR: Color = Color::GREEN;
+const BEHIND_COLOR: Color = Color::RED;
+
+/// Thickness of the progress bar, and its gap with the board
+const PROGRESS_HEIGHT: f32 = 6.0;
+
+/// Size of a digit of the counters drawn by the skin, in pixels
+const SEGMENT_SIZE: Vec2 = Vec2::new(16.0, 28.0);
+
+/// Digits of the mine counter and the timer, up to 999 as on the original game
+const SEGMENT_DIGITS: usize = 3;
+
+/// Counters and split times above the board, and the share of safe tiles revealed under it.
+/// Skins with counter digits, like the classic one, show the mines left and the time in
+/// [`SegmentDisplay`]s instead of the flag counter
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(Self::spawn_hud).add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(Self::hide_pressure)
+                .with_system(Self::update_flag_counter)
+                .with_system(Self::update_mine_counter)
+                .with_system(Self::update_timer.after(InsightsPlugin::track))
+                .with_system(
+                    Self::draw_segments
+                        .after(Self::update_mine_counter)
+                        .after(Self::update_timer),
+                )
+                .with_system(Self::update_splits.after(InsightsPlugin::track))
+                .with_system(Self::spawn_progress_bar)
+                .with_system(Self::update_progress_bar),
+        );
+    }
+}
+
+impl HudPlugin {
+    fn spawn_hud(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        // full width row centering the counters
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("HUD"))
+        .with_children(|parent| {
+            let style = TextStyle {
+                font: asset_server.load("fonts/robotoslab.ttf"),
+                font_size: 24.0,
+                color: Color::WHITE,
+            };
+            Self::spawn_segments(parent, "Mine Counter", MineCounter);
+            parent
+                .spawn_bundle(TextBundle::from_section("", style.clone()))
+                .insert(Name::new("Flag Counter"))
+                .insert(FlagCounterText);
+            parent
+                .spawn_bundle(
+                    TextBundle::from_sections([
+                        TextSection::new("", style.clone()),
+                        TextSection::new("", style),
+                    ])
+                    .with_style(Style {
+                        margin: UiRect {
+                            left: Val::Px(30.0),
+                            ..Default::default()
+                        },
+                        ..Default::default()
+                    }),
+                )
+                .insert(Name::new("Splits"))
+                .insert(SplitText);
+            Self::spawn_segments(parent, "Timer", GameTimer);
+        });
+    }
+
+    /// [`SegmentDisplay`] of [`SEGMENT_DIGITS`] digits, hidden until a skin brings the images
+    fn spawn_segments(parent: &mut ChildBuilder, name: &'static str, marker: impl Component) {
+        parent
+            .spawn_bundle(NodeBundle {
+                style: Style {
+                    display: Display::None,
+                    margin: UiRect {
+                        left: Val::Px(15.0),
+                        right: Val::Px(15.0),
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                color: Color::NONE.into(),
+                ..Default::default()
+            })
+            .insert(Name::new(name))
+            .insert(SegmentDisplay {
+                digits: SEGMENT_DIGITS,
+                value: 0,
+            })
+            .insert(marker)
+            .with_children(|display| {
+                for position in 0..SEGMENT_DIGITS {
+                    display
+                        .spawn_bundle(ImageBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(SEGMENT_SIZE.x), Val::Px(SEGMENT_SIZE.y)),
+                                ..Default::default()
+                            },
+                            ..Default::default()
+                        })
+                        .insert(SegmentDigit(position));
+                }
+            });
+    }
+
+    /// Counters and split times put pressure on the player, some rules go without them
+    #[allow(clippy::type_complexity)]
+    fn hide_pressure(
+        rules: Res<RuleSet>,
+        mut texts: Query<
+            &mut Visibility,
+            Or<(With<FlagCounterText>, With<SplitText>, With<SegmentDisplay>)>,
+        >,
+    ) {
+        if !rules.is_changed() {
+            return;
+        }
+        for mut visibility in &mut texts {
+            visibility.is_visible = rules.pressure;
+        }
+    }
+
+    /// Placed flags against mines, in red when some flag must be wrong
+    fn update_flag_counter(
+        state: Option<Res<BoardState>>,
+        mut counters: Query<&mut Text, With<FlagCounterText>>,
+    ) {
+        let Some(state) = state.filter(|state| state.is_changed()) else {
+            return;
+        };
+
+        for mut text in &mut counters {
+            let section = &mut text.sections[0];
+            section.value = format!("Flags: {} / {}", state.n_flags(), state.n_mines());
+            section.style.color = if state.is_overflagged() {
+                OVERFLAG_COLOR
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+
+    /// Mines left to flag, below zero once more flags than mines are placed
+    fn update_mine_counter(
+        state: Option<Res<BoardState>>,
+        mut counters: Query<&mut SegmentDisplay, With<MineCounter>>,
+    ) {
+        let Some(state) = state.filter(|state| state.is_changed()) else {
+            return;
+        };
+        let left = state.n_mines() as i32 - state.n_flags() as i32;
+        for mut counter in &mut counters {
+            if counter.value != left {
+                counter.value = left;
+            }
+        }
+    }
+
+    /// Whole seconds since the first click, stopping with the game
+    fn update_timer(
+        tracker: Res<SessionTracker>,
+        clock: Res<GameClock>,
+        status: Res<GameStatus>,
+        mut timers: Query<&mut SegmentDisplay, With<GameTimer>>,
+    ) {
+        if *status != GameStatus::Playing && !status.is_changed() {
+            return;
+        }
+        let seconds = tracker
+            .started
+            .map_or(0.0, |started| clock.elapsed_seconds() - started)
+            .max(0.0) as i32;
+        for mut timer in &mut timers {
+            if timer.value != seconds {
+                timer.value = seconds;
+            }
+        }
+    }
+
+    /// Show the [`SegmentDisplay`]s with the digits of the skin in place of the flag counter, or
+    /// the flag counter alone when the skin has no digits
+    fn draw_segments(
+        assets: Res<BoardAssets>,
+        mut displays: Query<(
+            &SegmentDisplay,
+            ChangeTrackers<SegmentDisplay>,
+            &Children,
+            &mut Style,
+        )>,
+        mut digits: Query<(&SegmentDigit, &mut UiImage)>,
+        mut counters: Query<&mut Style, (With<FlagCounterText>, Without<SegmentDisplay>)>,
+    ) {
+        if assets.is_changed() {
+            for mut style in &mut counters {
+                style.display = match assets.segments {
+                    Some(_) => Display::None,
+                    None => Display::Flex,
+                };
+            }
+        }
+
+        for (display, tracker, children, mut style) in &mut displays {
+            if !assets.is_changed() && !tracker.is_changed() {
+                continue;
+            }
+            let Some(images) = &assets.segments else {
+                if style.display != Display::None {
+                    style.display = Display::None;
+                }
+                continue;
+            };
+            // laid out again only when the skin changes
+            if style.display != Display::Flex {
+                style.display = Display::Flex;
+            }
+            let mut digits = digits.iter_many_mut(children);
+            while let Some((&SegmentDigit(position), mut image)) = digits.fetch_next() {
+                let glyph = SegmentGlyph::at(display.value, display.digits, position);
+                image.0 = images.get(glyph).clone();
+            }
+        }
+    }
+
+    /// Latest split, and how far ahead or behind the personal best it is
+    fn update_splits(tracker: Res<SessionTracker>, mut texts: Query<&mut Text, With<SplitText>>) {
+        if !tracker.is_changed() {
+            return;
+        }
+
+        let last = tracker.splits.len().checked_sub(1);
+        for mut text in &mut texts {
+            let [split, delta] = &mut text.sections[..] else {
+                continue;
+            };
+            split.value = last.map_or_else(String::new, |i| {
+                format!("{:.0}%: {:.1}s", SPLITS[i] * 100.0, tracker.splits[i])
+            });
+
+            let best = tracker.best_splits.as_ref();
+            match last.and_then(|i| Some(tracker.splits[i] - best?.get(i)?)) {
+                Some(diff) => {
+                    delta.value = format!(" ({diff:+.1}s)");
+                    delta.style.color = if diff <= 0.0 {
+                        AHEAD_COLOR
+                    } else {
+                        BEHIND_COLOR
+                    };
+                }
+                None => delta.value.clear(),
+            }
+        }
+    }
+
+    fn spawn_progress_bar(
+        mut cmds: Commands,
+        boards: Query<Entity, Added<Board>>,
+        params: Option<Res<DisplayParams>>,
+    ) {
+        let Some(params) = params else {
+            return;
+        };
+
+        let width = params.board_size.x;
+        // the board origin is its bottom left corner
+        let bar = |color, width, z| SpriteBundle {
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(width, PROGRESS_HEIGHT)),
+                anchor: Anchor::CenterLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0.0, -1.5 * PROGRESS_HEIGHT, z),
+            ..Default::default()
+        };
+        for board in &boards {
+            cmds.entity(board).with_children(|parent| {
+                parent
+                    .spawn_bundle(bar(Color::DARK_GRAY, width, 0.0))
+                    .insert(Name::new("Progress"))
+                    .insert(ProgressTrack);
+                parent
+                    .spawn_bundle(bar(Color::GREEN, 0.0, 1.0))
+                    .insert(Name::new("Progress Fill"))
+                    .insert(ProgressBar);
+            });
+        }
+    }
+
+    /// Stretch the bar as flood fills complete, several frames long on large boards, and follow
+    /// the width of the board when its view turns
+    fn update_progress_bar(
+        state: Option<Res<BoardState>>,
+        params: Option<Res<DisplayParams>>,
+        mut bars: Query<&mut Sprite, With<ProgressBar>>,
+        mut tracks: Query<&mut Sprite, (With<ProgressTrack>, Without<ProgressBar>)>,
+    ) {
+        let (Some(state), Some(params)) = (state, params) else {
+            return;
+        };
+        if !state.is_changed() && !params.is_changed() {
+            return;
+        }
+
+        for mut sprite in &mut tracks {
+            sprite.custom_size = Some(Vec2::new(params.board_size.x, PROGRESS_HEIGHT));
+        }
+        for mut sprite in &mut bars {
+            sprite.custom_size = Some(Vec2::new(
+                params.board_size.x * state.progress(),
+                PROGRESS_HEIGHT,
+            ));
+        }
+    }
+}
@@ -0,0 +1,292 @@
+use bevy::prelude::{
+    info, warn, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin,
+    Res, ResMut, SystemSet,
+};
+
+use super::{ActiveDaily, ActiveWeekly, BoardSystem, DailyBoard, GameStatus};
+use crate::{
+    events::{InsightsToggleEvent, MineTriggerEvent, TileMarkEvent, TileTriggerEvent},
+    insights::{self, Insights},
+    resources::{
+        board_options::{Difficulty, GameMode},
+        board_state::BoardState,
+        game_clock::GameClock,
+        input_capture::InputCapture,
+        input_map::InputMap,
+        rule_set::RuleSet,
+    },
+    save::{
+        self, GameRecord, LossKind, Ratings, SessionLog, Stats, Versioned, RATING_FILE,
+        SESSION_FILE, SPLITS, STATS_FILE,
+    },
+    solver::{ConstraintGraph, Deduction},
+    storage::{Location, Storage},
+};
+
+/// Insights on screen, `None` when closed
+#[derive(Debug, Default, Clone)]
+pub struct ShownInsights(pub Option<Insights>);
+
+/// Versus rating shown with the insights, `None` when closed or before the first rated game
+#[derive(Debug, Default, Clone)]
+pub struct ShownRating(pub Option<Ratings>);
+
+/// Clock and clicks of the current board
+#[derive(Debug, Default)]
+pub struct SessionTracker {
+    /// `GameClock::elapsed_seconds` of the first click
+    pub started: Option<f64>,
+    pub clicks: u32,
+    /// A flag was placed at some point
+    pub flagged: bool,
+    /// Seconds from the first click to each of the `SPLITS` reached so far
+    pub splits: Vec<f32>,
+    /// Splits of the personal best on this difficulty, if any
+    pub best_splits: Option<Vec<f32>>,
+}
+
+/// Record every finished game the `RuleSet` allows in the session log, and show the trends of the
+/// last days on `InputMap::insights`, `I` by default. Nothing leaves this machine. The trends are
+/// drawn by the UI backend, either `InsightsScreenPlugin` or `EguiUiPlugin`
+pub struct InsightsPlugin;
+
+impl Plugin for InsightsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<InsightsToggleEvent>()
+            .init_resource::<SessionTracker>()
+            .init_resource::<ShownInsights>()
+            .init_resource::<ShownRating>()
+            .init_resource::<InputCapture>()
+            .add_system(Self::insights_key.before(Self::toggle))
+            .add_system(Self::toggle)
+            .add_system(
+                Self::capture_input
+                    .after(Self::toggle)
+                    .before(BoardSystem::Input),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::track)
+                    .with_system(Self::record.after(Self::track)),
+            );
+    }
+}
+
+impl InsightsPlugin {
+    /// Keep the clicks from the board while the insights cover it
+    fn capture_input(shown: Res<ShownInsights>, mut capture: ResMut<InputCapture>) {
+        if shown.is_changed() {
+            capture.set("insights", shown.0.is_some());
+        }
+    }
+
+    pub fn track(
+        clock: Res<GameClock>,
+        mut tracker: ResMut<SessionTracker>,
+        storage: Option<Res<Storage>>,
+        state: Option<Res<BoardState>>,
+        mut triggers: EventReader<TileTriggerEvent>,
+        mut marks: EventReader<TileMarkEvent>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+
+        if state.is_added() {
+            let difficulty = Difficulty {
+                dim: state.dim().as_uvec2(),
+                n_mines: state.n_mines() as u32,
+            };
+            *tracker = SessionTracker {
+                best_splits: storage
+                    .and_then(|s| save::read::<Stats>(&s, Location::Data, STATS_FILE).ok())
+                    .flatten()
+                    .and_then(|mut stats| stats.best_splits.remove(&difficulty.name())),
+                ..Default::default()
+            };
+        }
+        // a flag removed right away still makes the game a flagging one
+        tracker.flagged |= state.n_flags() > 0;
+
+        let clicks = (triggers.iter().count() + marks.iter().count()) as u32;
+        if clicks > 0 {
+            tracker
+                .started
+                .get_or_insert_with(|| clock.elapsed_seconds());
+            tracker.clicks += clicks;
+        }
+
+        let (Some(started), true) = (tracker.started, state.is_changed()) else {
+            return;
+        };
+        let progress = state.bbbv_progress() as f32 / state.bbbv().max(1) as f32;
+        while let Some(&split) = SPLITS.get(tracker.splits.len()) {
+            if progress < split {
+                break;
+            }
+            let elapsed = (clock.elapsed_seconds() - started) as f32;
+            tracker.splits.push(elapsed);
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub fn record(
+        clock: Res<GameClock>,
+        mode: Res<GameMode>,
+        rules: Res<RuleSet>,
+        status: Res<GameStatus>,
+        tracker: Res<SessionTracker>,
+        weekly: Res<ActiveWeekly>,
+        daily: Res<ActiveDaily>,
+        storage: Option<Res<Storage>>,
+        state: Option<Res<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        mut mines: EventReader<MineTriggerEvent>,
+    ) {
+        let mine = mines.iter().last().map(|event| event.coord);
+        if !status.is_changed() || *status == GameStatus::Playing || !rules.recorded {
+            return;
+        }
+        let (Some(storage), Some(state), Some(mut graph)) = (storage, state, graph) else {
+            return;
+        };
+
+        // the graph does not know about the mine yet, so it shows what the player could see
+        let loss = match (*status, mine) {
+            (GameStatus::Won, _) => None,
+            (_, Some(coord)) => {
+                let analysis = graph.analyze();
+                Some(if analysis.deduction(coord) == Some(Deduction::Mine) {
+                    LossKind::ProvenMine
+                } else if analysis.safe().next().is_some() {
+                    LossKind::AvoidableGuess
+                } else {
+                    LossKind::ForcedGuess
+                })
+            }
+            (_, None) => Some(LossKind::ForcedGuess),
+        };
+
+        let dim = state.dim();
+        let record = GameRecord {
+            finished: insights::unix_time(),
+            width: dim.x as u32,
+            height: dim.y as u32,
+            n_mines: graph.n_mines(),
+            duration: tracker
+                .started
+                .map_or(0.0, |started| (clock.elapsed_seconds() - started) as f32),
+            clicks: tracker.clicks,
+            bbbv: state.bbbv(),
+            loss,
+            nf: !tracker.flagged,
+            splits: tracker.splits.clone(),
+            mode: *mode,
+            line_counts: rules.line_counts,
+            weekly: weekly.0.clone(),
+            daily: daily.0.as_ref().map(|board| board.date().to_owned()),
+            warm_up: daily.0.as_ref().is_some_and(DailyBoard::is_warm_up),
+        };
+
+        Self::update(&storage, STATS_FILE, |stats: &mut Stats| {
+            if stats.record(&record) {
+                info!("New best time on {}", record.difficulty());
+            }
+        });
+        Self::update(&storage, SESSION_FILE, |log: &mut SessionLog| {
+            log.games.push(record)
+        });
+    }
+
+    /// Edit a file of the data directory, leaving it alone when it cannot be read
+    pub fn update<T: Versioned + Default>(
+        storage: &Storage,
+        name: &str,
+        edit: impl FnOnce(&mut T),
+    ) {
+        let mut data = match save::read::<T>(storage, Location::Data, name) {
+            Ok(data) => data.unwrap_or_default(),
+            Err(error) => {
+                // never overwrite a file the player could still repair by hand
+                warn!(
+                    "Not recording the game, the {} is unreadable: {error}",
+                    T::KIND
+                );
+                return;
+            }
+        };
+        edit(&mut data);
+        if let Err(error) = save::write(storage, Location::Data, name, &data) {
+            warn!("Could not record the game in the {}: {error}", T::KIND);
+        }
+    }
+
+    fn insights_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut toggles: EventWriter<InsightsToggleEvent>,
+    ) {
+        if keys.just_pressed(input.insights) {
+            toggles.send(InsightsToggleEvent);
+        }
+    }
+
+    /// Compute the insights when opening them
+    pub fn toggle(
+        mut toggles: EventReader<InsightsToggleEvent>,
+        storage: Option<Res<Storage>>,
+        mut shown: ResMut<ShownInsights>,
+        mut rating: ResMut<ShownRating>,
+    ) {
+        if toggles.iter().count().is_multiple_of(2) {
+            return;
+        }
+        if shown.0.is_some() {
+            shown.0 = None;
+            rating.0 = None;
+            return;
+        }
+
+        rating.0 = match storage
+            .as_ref()
+            .map(|s| save::read::<Ratings>(s, Location::Data, RATING_FILE))
+        {
+            Some(Ok(ratings)) => ratings,
+            Some(Err(error)) => {
+                warn!("Could not read the rating: {error}");
+                None
+            }
+            None => None,
+        };
+
+        let log = match storage
+            .as_ref()
+            .map(|s| save::read::<SessionLog>(s, Location::Data, SESSION_FILE))
+        {
+            Some(Ok(log)) => log.unwrap_or_default(),
+            Some(Err(error)) => {
+                warn!("Could not read the session log: {error}");
+                return;
+            }
+            None => SessionLog::default(),
+        };
+        let mut computed = Insights::compute(&log, insights::unix_time());
+        // the notes of the daily challenges are kept with the stats
+        let stats = storage.and_then(|s| save::read::<Stats>(&s, Location::Data, STATS_FILE).ok());
+        if let Some(stats) = stats.flatten() {
+            computed.annotate(&stats.daily_notes);
+        }
+        shown.0 = Some(computed);
+    }
+
+    /// Readable explanation of a [`LossKind`]
+    pub fn describe(kind: LossKind) -> &'static str {
+        match kind {
+            LossKind::ProvenMine => "clicking a provable mine",
+            LossKind::AvoidableGuess => "guessing while a safe tile was provable",
+            LossKind::ForcedGuess => "a forced guess",
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ChildBuilder, Color, Commands, DespawnRecursiveExt, Entity,
+        Name, NodeBundle, ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val,
+        With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{AlignItems, FlexDirection, JustifyContent, PositionType, Size, Style, UiRect},
+};
+
+use super::{screen_layout::spawn_row, InsightsPlugin, ShownInsights, ShownRating};
+use crate::{
+    components::InsightsScreen,
+    insights::{DayActivity, WINDOW_DAYS},
+};
+
+/// Height of the efficiency chart, reached by a perfect game
+const CHART_HEIGHT: f32 = 120.0;
+
+/// Rated games in the rating chart, the most recent ones
+const RATING_GAMES: usize = 30;
+
+/// Draw the [`ShownInsights`] with bevy_ui, its charts made of one bar per game or day
+pub struct InsightsScreenPlugin;
+
+impl Plugin for InsightsScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::sync_screen.after(InsightsPlugin::toggle));
+    }
+}
+
+impl InsightsScreenPlugin {
+    fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownInsights>,
+        rating: Res<ShownRating>,
+        screens: Query<Entity, With<InsightsScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        let Some(insights) = &shown.0 else {
+            return;
+        };
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+
+        let mut summary = format!(
+            "{} games, {} won ({} without flags) in the last {WINDOW_DAYS} days\n",
+            insights.games, insights.wins, insights.nf_wins
+        );
+        if let Some(time) = insights.average_win_time {
+            summary += &format!("Average solve time: {time:.1}s\n");
+        }
+        if let Some((kind, count)) = insights.common_loss {
+            let kind = InsightsPlugin::describe(kind);
+            summary += &format!("Most common loss: {kind} ({count} times)\n");
+        }
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.05, 0.05, 0.1, 0.95).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Insights Screen"))
+        .insert(InsightsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_sections([
+                TextSection::new("Insights\n\n", style(40.0, Color::WHITE)),
+                TextSection::new(summary, style(22.0, Color::WHITE)),
+                TextSection::new("\nEfficiency of the wins", style(18.0, Color::GRAY)),
+            ]));
+            spawn_row(parent, CHART_HEIGHT, |row| {
+                for efficiency in insights.efficiency {
+                    let height = efficiency.map_or(0.0, |e| e.min(1.0) * CHART_HEIGHT);
+                    Self::spawn_cell(row, height.max(2.0), Color::rgb(0.3, 0.6, 0.9));
+                }
+            });
+            parent.spawn_bundle(TextBundle::from_section(
+                "\nDays played and won",
+                style(18.0, Color::GRAY),
+            ));
+            spawn_row(parent, 16.0, |row| {
+                for day in insights.calendar {
+                    let color = match day {
+                        DayActivity::Idle => Color::DARK_GRAY,
+                        DayActivity::Played => Color::ORANGE,
+                        DayActivity::Won => Color::GREEN,
+                    };
+                    Self::spawn_cell(row, 16.0, color);
+                }
+            });
+            let notes = insights
+                .dailies
+                .iter()
+                .filter_map(|daily| {
+                    let outcome = if daily.won { "won" } else { "lost" };
+                    Some(format!(
+                        "Daily {} {outcome}: {}\n",
+                        daily.date,
+                        daily.note.as_ref()?
+                    ))
+                })
+                .collect::<String>();
+            if !notes.is_empty() {
+                parent.spawn_bundle(TextBundle::from_section(notes, style(16.0, Color::GRAY)));
+            }
+            if let Some(ratings) = &rating.0 {
+                parent.spawn_bundle(TextBundle::from_section(
+                    format!(
+                        "\nVersus rating: {} after {} games",
+                        ratings.rating,
+                        ratings.history.len()
+                    ),
+                    style(18.0, Color::GRAY),
+                ));
+                // scaled between the lowest and highest rating shown
+                let recent = &ratings.history[ratings.history.len().saturating_sub(RATING_GAMES)..];
+                let low = recent.iter().map(|game| game.rating).min().unwrap_or(0);
+                let high = recent.iter().map(|game| game.rating).max().unwrap_or(0);
+                spawn_row(parent, CHART_HEIGHT, |row| {
+                    for game in recent {
+                        let share = (game.rating - low) as f32 / (high - low).max(1) as f32;
+                        Self::spawn_cell(row, 2.0 + share * (CHART_HEIGHT - 2.0), Color::GOLD);
+                    }
+                });
+            }
+            parent.spawn_bundle(TextBundle::from_section(
+                "\nPress I to close",
+                style(18.0, Color::GRAY),
+            ));
+        });
+    }
+
+    fn spawn_cell(row: &mut ChildBuilder, height: f32, color: Color) {
+        row.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(14.0), Val::Px(height)),
+                margin: UiRect::all(Val::Px(1.0)),
+                ..Default::default()
+            },
+            color: color.into(),
+            ..Default::default()
+        });
+    }
+}
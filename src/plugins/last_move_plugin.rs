@@ -0,0 +1,144 @@
+use bevy::{
+    prelude::{
+        BuildChildren, Children, Color, Commands, DespawnRecursiveExt, Entity, EventReader, Name,
+        Plugin, Query, Res, SpatialBundle, SpriteBundle, SystemSet, Timer, Transform, Vec2, Vec3,
+        With, Without,
+    },
+    sprite::Sprite,
+};
+
+use super::BoardSystem;
+use crate::{
+    components::MoveHighlight,
+    events::{TileMarkEvent, TileTriggerEvent},
+    resources::{
+        board_options::{BoardOptions, DisplayParams},
+        board_state::{BoardState, Cover},
+        game_clock::GameClock,
+        motion_prefs::MotionPrefs,
+        tile_index::TileEntityIndex,
+    },
+};
+
+/// Seconds for the outline to fade out
+const FADE_DURATION: f32 = 1.5;
+
+const OUTLINE_COLOR: Color = Color::YELLOW;
+
+/// Outline the tiles changed by the last click for a moment, see
+/// [`BoardOptions::highlight_last_move`]
+pub struct LastMovePlugin;
+
+impl Plugin for LastMovePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(Self::highlight)
+                .with_system(Self::fade),
+        );
+    }
+}
+
+impl LastMovePlugin {
+    #[allow(clippy::too_many_arguments)]
+    fn highlight(
+        mut cmds: Commands,
+        mut triggers: EventReader<TileTriggerEvent>,
+        mut marks: EventReader<TileMarkEvent>,
+        board_options: Option<Res<BoardOptions>>,
+        params: Option<Res<DisplayParams>>,
+        state: Option<Res<BoardState>>,
+        index: Res<TileEntityIndex>,
+        highlights: Query<Entity, With<MoveHighlight>>,
+    ) {
+        // clicks on revealed tiles change nothing
+        let uncovered = triggers
+            .iter()
+            .map(|event| event.coord)
+            .filter(|&coord| {
+                state
+                    .as_ref()
+                    .is_some_and(|state| state.cover(coord) == Some(Cover::Revealed))
+            })
+            .collect::<Vec<_>>();
+        let moves = uncovered
+            .into_iter()
+            .chain(marks.iter().map(|event| event.coord))
+            .filter_map(|coord| index.get(coord))
+            .collect::<Vec<_>>();
+
+        let enabled = board_options.is_some_and(|options| options.highlight_last_move);
+        let (true, false, Some(params)) = (enabled, moves.is_empty(), params) else {
+            return;
+        };
+
+        highlights
+            .iter()
+            .for_each(|highlight| cmds.entity(highlight).despawn_recursive());
+
+        let size = params.tile_size;
+        let width = (size / 10.0).max(1.0);
+        // top, bottom, left and right edges
+        let edges = [
+            (Vec2::new(0.0, (size - width) / 2.0), Vec2::new(size, width)),
+            (Vec2::new(0.0, (width - size) / 2.0), Vec2::new(size, width)),
+            (Vec2::new((width - size) / 2.0, 0.0), Vec2::new(width, size)),
+            (Vec2::new((size - width) / 2.0, 0.0), Vec2::new(width, size)),
+        ];
+        for tile in moves {
+            cmds.entity(tile).with_children(|parent| {
+                parent
+                    .spawn_bundle(SpatialBundle {
+                        // above the cover and its flag
+                        transform: Transform::from_translation(Vec3::Z * 4.0),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("Last Move"))
+                    .insert(MoveHighlight {
+                        fade: Timer::from_seconds(FADE_DURATION, false),
+                    })
+                    .with_children(|frame| {
+                        for (position, edge) in edges {
+                            frame.spawn_bundle(SpriteBundle {
+                                sprite: Sprite {
+                                    color: OUTLINE_COLOR,
+                                    custom_size: Some(edge),
+                                    ..Default::default()
+                                },
+                                transform: Transform::from_translation(position.extend(0.0)),
+                                ..Default::default()
+                            });
+                        }
+                    });
+            });
+        }
+    }
+
+    /// Fade the outline out, or leave it solid until it goes with reduced motion
+    fn fade(
+        mut cmds: Commands,
+        clock: Res<GameClock>,
+        motion: Res<MotionPrefs>,
+        mut highlights: Query<(Entity, &mut MoveHighlight, &Children)>,
+        mut edges: Query<&mut Sprite, Without<MoveHighlight>>,
+    ) {
+        for (entity, mut highlight, children) in &mut highlights {
+            if highlight.fade.tick(clock.delta()).finished() {
+                cmds.entity(entity).despawn_recursive();
+                continue;
+            }
+
+            let alpha = if motion.animate() {
+                1.0 - highlight.fade.percent()
+            } else {
+                1.0
+            };
+            let mut iter = edges.iter_many_mut(children);
+            while let Some(mut sprite) = iter.fetch_next() {
+                sprite.color.set_a(alpha);
+            }
+        }
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::{
+    prelude::{
+        info, warn, Commands, EventReader, EventWriter, Input, KeyCode, Local,
+        ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, Timer,
+    },
+    time::Time,
+};
+
+use super::{BoardPlugin, BoardSystem, ResumeGame};
+use crate::{
+    events::{
+        BoardRegenerateEvent, LibraryExportEvent, LibraryPlayEvent, LibrarySortEvent,
+        LibraryToggleEvent, ToastEvent,
+    },
+    insights,
+    resources::{
+        board_state::BoardState, input_capture::InputCapture, input_map::InputMap,
+        toasts::ToastKind,
+    },
+    save::{Library, LibrarySort, SaveGame, LIBRARY_DIR},
+    storage::{Location, Storage},
+};
+
+/// Seconds between two looks at the library directory
+const SCAN_INTERVAL: f32 = 2.0;
+
+/// Boards of the library directory, in the order chosen by the player
+#[derive(Debug, Default, Clone)]
+pub struct BoardLibrary {
+    pub library: Library,
+    pub sort: LibrarySort,
+}
+
+/// Library on screen, drawn by the UI backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShownLibrary(pub bool);
+
+/// Browse the boards saved in the `boards` directory of the data directory, opened with
+/// `InputMap::library` (`B`). New and edited files show up within a couple of seconds, and playing
+/// one starts a board from it, its revealed tiles given away
+pub struct LibraryPlugin;
+
+impl Plugin for LibraryPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<LibraryToggleEvent>()
+            .add_event::<LibrarySortEvent>()
+            .add_event::<LibraryPlayEvent>()
+            .add_event::<LibraryExportEvent>()
+            .init_resource::<BoardLibrary>()
+            .init_resource::<ShownLibrary>()
+            .init_resource::<InputCapture>()
+            .add_system(Self::scan)
+            .add_system(Self::sort.after(Self::scan))
+            .add_system(Self::library_key.before(Self::toggle))
+            .add_system(Self::toggle)
+            .add_system(
+                Self::capture_input
+                    .after(Self::toggle)
+                    .before(BoardSystem::Input),
+            )
+            // the board is rebuilt next frame, once the `ResumeGame` is inserted
+            .add_system(
+                Self::play
+                    .after(Self::toggle)
+                    .after(BoardPlugin::regenerate_board),
+            )
+            .add_system(Self::export);
+    }
+}
+
+impl LibraryPlugin {
+    /// Keep the clicks from the board while the library covers it
+    fn capture_input(shown: Res<ShownLibrary>, mut capture: ResMut<InputCapture>) {
+        if shown.is_changed() {
+            capture.set("library", shown.0);
+        }
+    }
+
+    /// Read the library again when a file is added, removed or modified
+    #[allow(clippy::type_complexity)]
+    fn scan(
+        time: Res<Time>,
+        storage: Option<Res<Storage>>,
+        mut timer: Local<Option<Timer>>,
+        mut seen: Local<Option<Vec<(String, Option<u64>)>>>,
+        mut library: ResMut<BoardLibrary>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let timer = timer.get_or_insert_with(|| Timer::from_seconds(SCAN_INTERVAL, true));
+        if !timer.tick(time.delta()).just_finished() && seen.is_some() {
+            return;
+        }
+        let Some(storage) = storage else {
+            return;
+        };
+
+        let files = storage
+            .list(Location::Data, LIBRARY_DIR)
+            .unwrap_or_default();
+        let files = files
+            .into_iter()
+            .map(|file| {
+                let modified = storage.modified(Location::Data, &format!("{LIBRARY_DIR}/{file}"));
+                (file, modified.ok().flatten())
+            })
+            .collect();
+        if seen.as_ref() == Some(&files) {
+            return;
+        }
+        *seen = Some(files);
+
+        let mut found = match Library::scan(&storage) {
+            Ok(found) => found,
+            Err(error) => {
+                warn!("Could not read the board library: {error}");
+                return;
+            }
+        };
+        // reported once, when the files change
+        for (file, error) in &found.invalid {
+            warn!("Invalid board {file} in the library: {error}");
+            toasts.send(ToastEvent::new(
+                ToastKind::Warning,
+                "Invalid board in the library",
+                format!("{file}: {error}"),
+            ));
+        }
+        info!("Found {} boards in the library", found.entries.len());
+        found.sort(library.sort);
+        library.library = found;
+    }
+
+    fn sort(mut sorts: EventReader<LibrarySortEvent>, mut library: ResMut<BoardLibrary>) {
+        let Some(LibrarySortEvent(sort)) = sorts.iter().last() else {
+            return;
+        };
+        library.sort = *sort;
+        library.library.sort(*sort);
+    }
+
+    fn library_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut toggles: EventWriter<LibraryToggleEvent>,
+    ) {
+        if keys.just_pressed(input.library) {
+            toggles.send(LibraryToggleEvent);
+        }
+    }
+
+    pub fn toggle(mut toggles: EventReader<LibraryToggleEvent>, mut shown: ResMut<ShownLibrary>) {
+        if !toggles.iter().count().is_multiple_of(2) {
+            shown.0 = !shown.0;
+        }
+    }
+
+    /// Start the chosen board, closing the library
+    fn play(
+        mut cmds: Commands,
+        mut plays: EventReader<LibraryPlayEvent>,
+        library: Res<BoardLibrary>,
+        mut shown: ResMut<ShownLibrary>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let Some(LibraryPlayEvent { file }) = plays.iter().last() else {
+            return;
+        };
+        let entries = &library.library.entries;
+        let Some(entry) = entries.iter().find(|entry| &entry.file == file) else {
+            warn!("No board {file} in the library");
+            return;
+        };
+
+        info!("Playing {file} from the library");
+        cmds.insert_resource(ResumeGame(entry.level()));
+        regenerate.send(BoardRegenerateEvent);
+        shown.0 = false;
+    }
+
+    fn export(
+        mut exports: EventReader<LibraryExportEvent>,
+        storage: Option<Res<Storage>>,
+        state: Option<Res<BoardState>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if exports.iter().count() == 0 {
+            return;
+        }
+        let (Some(storage), Some(state)) = (storage, state) else {
+            return;
+        };
+
+        let board = SaveGame::capture(&state);
+        match Library::export(&storage, &board, insights::unix_time()) {
+            Ok(file) => toasts.send(ToastEvent::new(
+                ToastKind::Success,
+                "Board added to the library",
+                file,
+            )),
+            Err(error) => toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Could not add the board to the library",
+                error.to_string(),
+            )),
+        }
+    }
+}
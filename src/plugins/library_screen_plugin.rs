@@ -0,0 +1,160 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Size, Style, UiRect,
+    },
+};
+
+use super::{screen_layout::spawn_row, BoardLibrary, LibraryPlugin, ShownLibrary};
+use crate::{
+    components::{LibraryButton, LibraryScreen},
+    events::{LibraryExportEvent, LibraryPlayEvent, LibrarySortEvent},
+    resources::input_map::InputMap,
+    save::LibrarySort,
+};
+
+/// Draw the [`ShownLibrary`] with bevy_ui, a button per board and per sort order
+pub struct LibraryScreenPlugin;
+
+impl Plugin for LibraryScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::sync_screen.after(LibraryPlugin::toggle))
+            .add_system(Self::click);
+    }
+}
+
+impl LibraryScreenPlugin {
+    /// One line per board, clicked to play it, under the sort orders
+    fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownLibrary>,
+        library: Res<BoardLibrary>,
+        input: Res<InputMap>,
+        screens: Query<Entity, With<LibraryScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() && !library.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        if !shown.0 {
+            return;
+        }
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let button = |parent: &mut ChildBuilder, action, text: String, color| {
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::new(
+                            Val::Px(8.0),
+                            Val::Px(8.0),
+                            Val::Px(2.0),
+                            Val::Px(2.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.08).into(),
+                    ..Default::default()
+                })
+                .insert(action)
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(text, style(18.0, color)));
+                });
+        };
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.05, 0.05, 0.1, 0.95).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Library Screen"))
+        .insert(LibraryScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Library\n",
+                style(40.0, Color::WHITE),
+            ));
+            spawn_row(parent, 28.0, |row| {
+                for sort in LibrarySort::ALL {
+                    let color = if library.sort == sort {
+                        Color::WHITE
+                    } else {
+                        Color::GRAY
+                    };
+                    button(
+                        row,
+                        LibraryButton::Sort(sort),
+                        sort.name().to_owned(),
+                        color,
+                    );
+                }
+            });
+            if library.library.entries.is_empty() {
+                parent.spawn_bundle(TextBundle::from_section(
+                    "\nNo boards yet",
+                    style(18.0, Color::GRAY),
+                ));
+            }
+            for entry in &library.library.entries {
+                let text = format!(
+                    "{}   {}x{}, {} mines, {:.0}%, 3BV {}{}",
+                    entry.file,
+                    entry.board.width,
+                    entry.board.height,
+                    entry.board.mines.len(),
+                    entry.density() * 100.0,
+                    entry.bbbv,
+                    if entry.solvable { ", no guess" } else { "" }
+                );
+                let play = LibraryButton::Play(entry.file.clone());
+                button(parent, play, text, Color::WHITE);
+            }
+            parent.spawn_bundle(TextBundle::from_section("\n", style(18.0, Color::GRAY)));
+            let export = "Add the current board".to_owned();
+            button(parent, LibraryButton::Export, export, Color::WHITE);
+            parent.spawn_bundle(TextBundle::from_section(
+                format!("\nPress {:?} to close", input.library),
+                style(18.0, Color::GRAY),
+            ));
+        });
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &LibraryButton), Changed<Interaction>>,
+        mut sorts: EventWriter<LibrarySortEvent>,
+        mut plays: EventWriter<LibraryPlayEvent>,
+        mut exports: EventWriter<LibraryExportEvent>,
+    ) {
+        for (interaction, button) in &buttons {
+            if *interaction != Interaction::Clicked {
+                continue;
+            }
+            match button {
+                LibraryButton::Sort(sort) => sorts.send(LibrarySortEvent(*sort)),
+                LibraryButton::Play(file) => plays.send(LibraryPlayEvent { file: file.clone() }),
+                LibraryButton::Export => exports.send(LibraryExportEvent),
+            }
+        }
+    }
+}
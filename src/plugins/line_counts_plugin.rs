@@ -0,0 +1,149 @@
+use bevy::{
+    math::ivec2,
+    prelude::{
+        Added, BuildChildren, Color, Commands, Entity, Name, Plugin, Query, Res, SystemSet,
+        Text2dBundle, Transform, Vec2, Visibility,
+    },
+    text::{Text, TextAlignment, TextStyle},
+};
+
+use super::BoardSystem;
+use crate::{
+    components::{Board, Line, LineCountText},
+    resources::{
+        board_assets::BoardAssets, board_options::DisplayParams, board_state::BoardState,
+        rule_set::RuleSet,
+    },
+};
+
+/// Count of a line holding more flags than mines
+const OVERFLAG_COLOR: Color = Color::RED;
+
+/// Mines of every row and column, written along the border of the board when the `RuleSet`
+/// allows it: the mines left with the line counts assist, the totals in nonograms. Otherwise
+/// the line hints of a puzzle
+pub struct LineCountsPlugin;
+
+impl Plugin for LineCountsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(Self::spawn_counts)
+                .with_system(Self::update_counts)
+                .with_system(Self::relayout),
+        );
+    }
+}
+
+impl LineCountsPlugin {
+    /// Rows on the left and columns on top, in the default view
+    fn spawn_counts(
+        mut cmds: Commands,
+        boards: Query<Entity, Added<Board>>,
+        params: Option<Res<DisplayParams>>,
+        assets: Res<BoardAssets>,
+    ) {
+        let Some(params) = params else {
+            return;
+        };
+
+        let style = TextStyle {
+            font: assets.font.clone(),
+            font_size: params.tile_size * 0.6,
+            color: Color::WHITE,
+        };
+        let lines = (0..params.dim.y)
+            .map(Line::Row)
+            .chain((0..params.dim.x).map(Line::Column));
+        for board in &boards {
+            cmds.entity(board).with_children(|parent| {
+                for line in lines.clone() {
+                    parent
+                        .spawn_bundle(Text2dBundle {
+                            text: Text::from_section("", style.clone())
+                                .with_alignment(TextAlignment::CENTER),
+                            transform: Transform::from_translation(
+                                Self::position(&params, line).extend(1.0),
+                            ),
+                            // until the first count
+                            visibility: Visibility { is_visible: false },
+                            ..Default::default()
+                        })
+                        .insert(Name::new(format!("{line:?} Count")))
+                        .insert(LineCountText(line));
+                }
+            });
+        }
+    }
+
+    /// Center of the tile just outside the board, next to the first tile of the line
+    fn position(params: &DisplayParams, line: Line) -> Vec2 {
+        let coord = match line {
+            Line::Row(y) => ivec2(-1, y as i32),
+            Line::Column(x) => ivec2(x as i32, params.dim.y as i32),
+        };
+        params.tile_center(coord)
+    }
+
+    /// Follow the flags, and the rules of a new board
+    fn update_counts(
+        state: Option<Res<BoardState>>,
+        rules: Res<RuleSet>,
+        added: Query<(), Added<LineCountText>>,
+        mut texts: Query<(&LineCountText, &mut Text, &mut Visibility)>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+        if !state.is_changed() && !rules.is_changed() && added.is_empty() {
+            return;
+        }
+
+        // the assist says more than the totals
+        let counts = if rules.line_counts {
+            state.line_counts()
+        } else {
+            state.line_totals()
+        };
+        let shown = rules.line_counts || rules.line_totals;
+        for (LineCountText(line), mut text, mut visibility) in &mut texts {
+            let count = match *line {
+                _ if !shown => None,
+                Line::Row(y) => counts.rows.get(y as usize).copied(),
+                Line::Column(x) => counts.columns.get(x as usize).copied(),
+            };
+            // otherwise the line hints of a puzzle, the only counts it gives
+            let count = count.or_else(|| match *line {
+                Line::Row(y) => state
+                    .lines()
+                    .iter()
+                    .find(|hint| hint.row == y)
+                    .map(|hint| hint.mines as i32),
+                Line::Column(_) => None,
+            });
+            visibility.is_visible = count.is_some();
+            let section = &mut text.sections[0];
+            section.value = count.map_or_else(String::new, |count| count.to_string());
+            section.style.color = match count {
+                Some(count) if count < 0 => OVERFLAG_COLOR,
+                _ => Color::WHITE,
+            };
+        }
+    }
+
+    /// Follow the tiles when the view of the board turns
+    fn relayout(
+        params: Option<Res<DisplayParams>>,
+        mut texts: Query<(&LineCountText, &mut Transform)>,
+    ) {
+        let Some(params) = params.filter(|params| params.is_changed()) else {
+            return;
+        };
+        for (LineCountText(line), mut transform) in &mut texts {
+            let position = Self::position(&params, *line);
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
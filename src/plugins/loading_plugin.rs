@@ -0,0 +1,131 @@
+use bevy::{
+    asset::LoadState,
+    prelude::{
+        info, AssetServer, BuildChildren, Color, Commands, DespawnRecursiveExt, Entity, Name,
+        NodeBundle, Plugin, Query, Res, ResMut, State, SystemSet, TextBundle, Val, With,
+    },
+    text::TextStyle,
+    ui::{AlignItems, FlexDirection, JustifyContent, PositionType, Size, Style},
+};
+
+use super::WatchedAssets;
+use crate::{
+    components::{LoadingBar, LoadingScreen},
+    resources::game_assets::FONT,
+};
+
+/// Width of the loading bar
+const BAR_WIDTH: f32 = 400.0;
+
+/// Stage of the app
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AppState {
+    /// Waiting for the assets watched by the `AssetWatchdogPlugin`
+    Loading,
+    /// The board is built on entering this state
+    Playing,
+}
+
+/// Start in [`AppState::Loading`] with a progress bar, until every watched asset is loaded or
+/// replaced by its embedded copy, so that the first board never shows without its font or
+/// sprites
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_state(AppState::Loading)
+            .add_system_set(SystemSet::on_enter(AppState::Loading).with_system(Self::spawn_screen))
+            .add_system_set(SystemSet::on_update(AppState::Loading).with_system(Self::progress))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Loading).with_system(Self::despawn_screen),
+            );
+    }
+}
+
+impl LoadingPlugin {
+    fn spawn_screen(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgb(0.05, 0.05, 0.1).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Loading Screen"))
+        .insert(LoadingScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Loading",
+                TextStyle {
+                    font: asset_server.load(FONT),
+                    font_size: 32.0,
+                    color: Color::WHITE,
+                },
+            ));
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(BAR_WIDTH), Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                    color: Color::DARK_GRAY.into(),
+                    ..Default::default()
+                })
+                .with_children(|track| {
+                    track
+                        .spawn_bundle(NodeBundle {
+                            style: Style {
+                                size: Size::new(Val::Px(0.0), Val::Percent(100.0)),
+                                ..Default::default()
+                            },
+                            color: Color::WHITE.into(),
+                            ..Default::default()
+                        })
+                        .insert(LoadingBar);
+                });
+        });
+    }
+
+    /// Fill the bar with the share of assets ready, and start playing once they all are
+    fn progress(
+        asset_server: Res<AssetServer>,
+        watched: Res<WatchedAssets>,
+        mut state: ResMut<State<AppState>>,
+        mut bars: Query<&mut Style, With<LoadingBar>>,
+    ) {
+        // the watchdog fills the handles in a startup system
+        if watched.handles.is_empty() {
+            return;
+        }
+        let ready = watched
+            .handles
+            .iter()
+            .filter(|(path, handle)| match asset_server.get_load_state(handle) {
+                LoadState::Loaded => true,
+                LoadState::Failed => watched.replaced.contains(path),
+                _ => false,
+            })
+            .count();
+        let share = ready as f32 / watched.handles.len() as f32;
+        for mut style in &mut bars {
+            style.size.width = Val::Px(share * BAR_WIDTH);
+        }
+
+        if ready == watched.handles.len() {
+            info!("Loaded {ready} assets");
+            // already queued if it fails
+            let _ = state.set(AppState::Playing);
+        }
+    }
+
+    fn despawn_screen(mut cmds: Commands, screens: Query<Entity, With<LoadingScreen>>) {
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+    }
+}
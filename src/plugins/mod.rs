@@ -1,3 +1,128 @@
+mod achievements_plugin;
+mod adaptive_plugin;
+mod analysis_plugin;
+mod asset_watchdog_plugin;
+mod autosave_plugin;
 mod board_plugin;
+mod bot_plugin;
+mod bug_report_plugin;
+mod chat_plugin;
+mod confirm_plugin;
+mod confirm_screen_plugin;
+#[cfg(feature = "debug")]
+mod console_plugin;
+#[cfg(feature = "debug")]
+mod contrast_audit_plugin;
+mod daily_plugin;
+mod demo_plugin;
+#[cfg(feature = "egui")]
+mod egui_plugin;
+mod engine_plugin;
+mod error_plugin;
+mod hint_plugin;
+mod hud_plugin;
+mod insights_plugin;
+mod insights_screen_plugin;
+mod last_move_plugin;
+mod library_plugin;
+mod library_screen_plugin;
+mod line_counts_plugin;
+mod loading_plugin;
+#[cfg(feature = "debug")]
+mod options_inspector_plugin;
+#[cfg(feature = "debug")]
+mod options_reload_plugin;
+mod paste_plugin;
+mod pause_plugin;
+mod pause_screen_plugin;
+#[cfg(feature = "debug")]
+mod perf_hud_plugin;
+mod playback_plugin;
+mod result_panel_plugin;
+mod ruler_plugin;
+mod sandbox_plugin;
+mod screen_layout;
+mod session_plugin;
+mod share_plugin;
+mod skin_plugin;
+mod slots_plugin;
+mod slots_screen_plugin;
+mod smiley_plugin;
+mod sync_plugin;
+mod toast_plugin;
+#[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+mod tray_plugin;
+mod trophies_screen_plugin;
+mod undo_plugin;
+mod versus_plugin;
+#[cfg(feature = "voice")]
+mod voice_plugin;
+mod weekly_plugin;
+mod window_title_plugin;
+#[cfg(feature = "debug")]
+mod xray_plugin;
 
-pub use board_plugin::BoardPlugin;
+pub use achievements_plugin::{Achievements, AchievementsPlugin, ShownTrophies};
+pub use adaptive_plugin::{
+    AdaptiveBoard, AdaptivePlugin, AdaptiveSkill, AdaptiveSummary, ShownAdaptive,
+};
+pub use analysis_plugin::{AnalysisMode, AnalysisPlugin};
+pub use asset_watchdog_plugin::{AssetWatchdogPlugin, WatchedAssets};
+pub use autosave_plugin::{AutosavePlugin, ResumeGame};
+pub use board_plugin::{BoardPlugin, BoardStage, BoardSystem, GameStatus};
+pub use bot_plugin::BotPlugin;
+pub use bug_report_plugin::BugReportPlugin;
+pub use chat_plugin::{Chat, ChatPlugin, LOCAL_PLAYER};
+pub use confirm_plugin::{ConfirmPlugin, ShownConfirm};
+pub use confirm_screen_plugin::ConfirmScreenPlugin;
+#[cfg(feature = "debug")]
+pub use console_plugin::{Console, ConsolePlugin};
+#[cfg(feature = "debug")]
+pub use contrast_audit_plugin::ContrastAuditPlugin;
+pub use daily_plugin::{ActiveDaily, DailyBoard, DailyPlugin, ShownDailyNote};
+pub use demo_plugin::{Demo, DemoPlugin};
+#[cfg(feature = "egui")]
+pub use egui_plugin::EguiUiPlugin;
+pub use engine_plugin::{EngineAgent, EngineCommand, EnginePlugin};
+pub use error_plugin::{ErrorScreen, ErrorScreenPlugin};
+pub use hint_plugin::{CurrentHint, HintPlugin};
+pub use hud_plugin::HudPlugin;
+pub use insights_plugin::{InsightsPlugin, SessionTracker, ShownInsights, ShownRating};
+pub use insights_screen_plugin::InsightsScreenPlugin;
+pub use last_move_plugin::LastMovePlugin;
+pub use library_plugin::{BoardLibrary, LibraryPlugin, ShownLibrary};
+pub use library_screen_plugin::LibraryScreenPlugin;
+pub use line_counts_plugin::LineCountsPlugin;
+pub use loading_plugin::{AppState, LoadingPlugin};
+#[cfg(feature = "debug")]
+pub use options_inspector_plugin::OptionsInspectorPlugin;
+#[cfg(feature = "debug")]
+pub use options_reload_plugin::OptionsReloadPlugin;
+pub use paste_plugin::PastePlugin;
+pub use pause_plugin::{PausePlugin, ShownPause};
+pub use pause_screen_plugin::PauseScreenPlugin;
+#[cfg(feature = "debug")]
+pub use perf_hud_plugin::PerfHudPlugin;
+pub use playback_plugin::{PlaybackPlugin, ReplayViewer, WatchReplay};
+pub use result_panel_plugin::ResultPanelPlugin;
+pub use ruler_plugin::RulerPlugin;
+pub use sandbox_plugin::{Sandbox, SandboxPlugin};
+pub use session_plugin::{LocalEvents, Session, SessionPlugin};
+pub use share_plugin::{SharePlugin, ShownResult};
+pub use skin_plugin::{SkinPacks, SkinPlugin};
+pub use slots_plugin::{SaveSlots, ShownSlots, SlotsPlugin};
+pub use slots_screen_plugin::SlotsScreenPlugin;
+pub use smiley_plugin::SmileyPlugin;
+pub use sync_plugin::{ProfileSync, SyncPlugin};
+pub use toast_plugin::ToastPlugin;
+#[cfg(all(feature = "tray", any(windows, target_os = "macos")))]
+pub use tray_plugin::TrayPlugin;
+pub use trophies_screen_plugin::TrophiesScreenPlugin;
+pub use undo_plugin::{UndoHistory, UndoPlugin};
+pub use versus_plugin::VersusPlugin;
+#[cfg(feature = "voice")]
+pub use voice_plugin::VoicePlugin;
+pub use weekly_plugin::{ActiveWeekly, WeeklyPlugin, WeeklyPuzzles};
+pub use window_title_plugin::WindowTitlePlugin;
+#[cfg(feature = "debug")]
+pub use xray_plugin::XrayPlugin;
@@ -0,0 +1,23 @@
+use bevy::prelude::Plugin;
+use bevy_inspector_egui::{widgets::InspectableButton, Inspectable, InspectorPlugin};
+
+use crate::{events::BoardRegenerateEvent, resources::board_options::BoardOptions};
+
+/// Inspector window rebuilding the board from the edited options
+#[derive(Debug, Default, Inspectable)]
+pub struct BoardControls {
+    #[inspectable(text = "Apply & regenerate")]
+    apply: InspectableButton<BoardRegenerateEvent>,
+}
+
+/// Debug only: edit the `BoardOptions` resource live from the inspector. Edits take effect once
+/// the board is regenerated
+pub struct OptionsInspectorPlugin;
+
+impl Plugin for OptionsInspectorPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // the inspector needs the resource to exist, options given on startup replace the default
+        app.add_plugin(InspectorPlugin::<BoardOptions>::new())
+            .add_plugin(InspectorPlugin::<BoardControls>::new());
+    }
+}
@@ -0,0 +1,106 @@
+use std::time::SystemTime;
+
+use bevy::{
+    prelude::{info, warn, EventWriter, Local, Plugin, Res, ResMut, Timer},
+    time::Time,
+};
+
+use crate::{
+    events::{BoardRegenerateEvent, ToastEvent},
+    resources::{
+        board_options::{BoardOptions, OptionOverrides, OptionsFile},
+        toasts::ToastKind,
+    },
+};
+
+/// Seconds between two checks of the options file
+const POLL_INTERVAL: f32 = 0.5;
+
+#[derive(Debug)]
+struct Watcher {
+    timer: Timer,
+    modified: Option<SystemTime>,
+}
+
+impl Default for Watcher {
+    fn default() -> Self {
+        Self {
+            timer: Timer::from_seconds(POLL_INTERVAL, true),
+            modified: None,
+        }
+    }
+}
+
+/// Debug only: poll the `--config` file and rebuild the board whenever it changes, so that tile
+/// size, padding and position can be tuned without restarting. Options given on the command
+/// line, e.g. `--mines`, still win over the file, see `OptionOverrides`
+pub struct OptionsReloadPlugin;
+
+impl Plugin for OptionsReloadPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::watch_options);
+    }
+}
+
+impl OptionsReloadPlugin {
+    fn watch_options(
+        time: Res<Time>,
+        file: Option<Res<OptionsFile>>,
+        overrides: Option<Res<OptionOverrides>>,
+        options: Option<ResMut<BoardOptions>>,
+        mut watcher: Local<Watcher>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let (Some(file), Some(mut options)) = (file, options) else {
+            return;
+        };
+        if !watcher.timer.tick(time.delta()).just_finished() {
+            return;
+        }
+
+        let modified = std::fs::metadata(&file.0)
+            .and_then(|meta| meta.modified())
+            .ok();
+        let previous = std::mem::replace(&mut watcher.modified, modified);
+        // the first poll only records the timestamp of the file loaded at startup
+        if previous.is_none() || previous == modified {
+            return;
+        }
+
+        // the command line overrides the file as it did at launch, and the current board is
+        // kept when the new options are broken
+        let reloaded = BoardOptions::load(&file.0)
+            .and_then(|loaded| match &overrides {
+                Some(overrides) => overrides.apply(loaded),
+                None => Ok(loaded),
+            })
+            .map(|mut reloaded| {
+                for clamped in reloaded.clamp() {
+                    warn!("{clamped}, clamped");
+                    toasts.send(ToastEvent::new(
+                        ToastKind::Warning,
+                        "Options clamped",
+                        clamped.to_string(),
+                    ));
+                }
+                reloaded
+            })
+            .and_then(BoardOptions::validated);
+        match reloaded {
+            Ok(reloaded) => {
+                info!("Reloaded board options from {}", file.0.display());
+                *options = reloaded;
+                regenerate.send(BoardRegenerateEvent);
+            }
+            Err(error) => {
+                warn!("{error}, keeping the current board");
+                toasts.send(ToastEvent::new(
+                    ToastKind::Error,
+                    "Options failed to load",
+                    format!("{error}, keeping the current board"),
+                ));
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+use bevy::prelude::{
+    info, Commands, EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion,
+    Plugin, Res,
+};
+
+use super::{BoardPlugin, ConfirmPlugin, ResumeGame, ShownConfirm};
+use crate::{
+    clipboard,
+    events::{
+        BoardRegenerateEvent, ConfirmAction, ConfirmRequestEvent, ConfirmedEvent, PasteBoardEvent,
+        ToastEvent,
+    },
+    import::{self, ImportedBoard},
+    resources::{board_options::BoardOptions, toasts::ToastKind},
+};
+
+/// Play a board pasted with `Ctrl+V`: a board file, an ASCII board, or a result copied by
+/// `SharePlugin` with its seed or its board, see `import`. The board is played once confirmed,
+/// a text without a board shows why in a toast
+pub struct PastePlugin;
+
+impl Plugin for PastePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<PasteBoardEvent>()
+            .add_system(
+                Self::paste_key
+                    .before(Self::paste)
+                    .before(ConfirmPlugin::answer),
+            )
+            .add_system(Self::paste)
+            // the board is rebuilt next frame, once the `ResumeGame` is inserted
+            .add_system(Self::play.after(BoardPlugin::regenerate_board));
+    }
+}
+
+impl PastePlugin {
+    fn paste_key(
+        keys: Res<Input<KeyCode>>,
+        confirm: Res<ShownConfirm>,
+        mut pastes: EventWriter<PasteBoardEvent>,
+    ) {
+        let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+        if ctrl && keys.just_pressed(KeyCode::V) && confirm.request.is_none() {
+            pastes.send(PasteBoardEvent);
+        }
+    }
+
+    /// Read the clipboard and ask to play the board found in it
+    fn paste(
+        mut pastes: EventReader<PasteBoardEvent>,
+        options: Option<Res<BoardOptions>>,
+        mut confirms: EventWriter<ConfirmRequestEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if pastes.iter().count() == 0 {
+            return;
+        }
+        let mode = options.map(|options| options.mode).unwrap_or_default();
+
+        let board = clipboard::paste()
+            .map_err(|error| error.to_string())
+            .and_then(|text| import::parse_board(&text, mode).map_err(|error| error.to_string()));
+        match board {
+            Ok(board) => {
+                let body = format!("{}. The current board is left", board.describe());
+                confirms.send(ConfirmRequestEvent::new(
+                    ConfirmAction::PlayPasted(board),
+                    "Play the pasted board?",
+                    body,
+                    "Play",
+                ));
+            }
+            Err(error) => toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Could not read a board",
+                error,
+            )),
+        }
+    }
+
+    fn play(
+        mut cmds: Commands,
+        mut confirmations: EventReader<ConfirmedEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let pasted = confirmations
+            .iter()
+            .filter_map(|ConfirmedEvent(action)| match action {
+                ConfirmAction::PlayPasted(board) => Some(board.clone()),
+                _ => None,
+            })
+            .next_back();
+        let Some(board) = pasted else {
+            return;
+        };
+
+        info!("Playing the pasted {}", board.describe());
+        match board {
+            ImportedBoard::Board(board) => cmds.insert_resource(ResumeGame(board)),
+            ImportedBoard::Seed(board) => cmds.insert_resource(board),
+        }
+        regenerate.send(BoardRegenerateEvent);
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::{
+    app::AppExit,
+    prelude::{
+        info, Commands, EventReader, EventWriter, Input, KeyCode, Local,
+        ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+    },
+};
+
+use super::{BoardPlugin, BoardSystem, ConfirmPlugin, GameStatus, ResumeGame, ShownConfirm};
+use crate::{
+    daily::DailyChallenge,
+    events::{
+        BoardRegenerateEvent, ConfirmAction, ConfirmRequestEvent, ConfirmedEvent,
+        OptionsApplyEvent, PauseActionEvent, PauseToggleEvent,
+    },
+    insights,
+    resources::{
+        board_options::BoardOptions, board_seed::BoardSeed, board_state::BoardState,
+        game_clock::GameClock, input_capture::InputCapture, input_map::InputMap,
+    },
+    save::SaveGame,
+};
+
+/// Share of the safe tiles revealed from which leaving a board asks for a confirmation
+const SIGNIFICANT_PROGRESS: f32 = 0.25;
+
+/// Pause menu on screen, drawn by the UI backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShownPause(pub bool);
+
+/// Pause the game with the menu key, `Escape` unless rebound. The `GameClock` stops and the board
+/// takes no clicks while the menu is open. Besides resuming, the menu restarts the board, starts
+/// another one, changes the difficulty, opens the options or quits, each closing it. Leaving a
+/// board with much progress, or the daily challenge once started, asks first
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<PauseToggleEvent>()
+            .add_event::<PauseActionEvent>()
+            .init_resource::<ShownPause>()
+            .init_resource::<InputCapture>()
+            // the key backing out of a confirmation must not open the menu as well
+            .add_system(
+                Self::pause_key
+                    .before(Self::toggle)
+                    .before(ConfirmPlugin::answer),
+            )
+            .add_system(Self::toggle)
+            .add_system(
+                Self::capture_input
+                    .after(Self::toggle)
+                    .before(BoardSystem::Input),
+            )
+            // the board is rebuilt next frame, once the `ResumeGame` is inserted
+            .add_system(
+                Self::act
+                    .before(Self::toggle)
+                    .after(BoardPlugin::regenerate_board),
+            );
+    }
+}
+
+impl PausePlugin {
+    /// Keep the clicks from the board while the menu is open
+    fn capture_input(shown: Res<ShownPause>, mut capture: ResMut<InputCapture>) {
+        if shown.is_changed() {
+            capture.set("pause", shown.0);
+        }
+    }
+
+    fn pause_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        confirm: Res<ShownConfirm>,
+        mut toggles: EventWriter<PauseToggleEvent>,
+    ) {
+        if keys.just_pressed(input.menu) && confirm.request.is_none() {
+            toggles.send(PauseToggleEvent);
+        }
+    }
+
+    /// Open or close the menu, leaving the clock as it was before, paused in a replay
+    pub fn toggle(
+        mut toggles: EventReader<PauseToggleEvent>,
+        mut shown: ResMut<ShownPause>,
+        mut clock: ResMut<GameClock>,
+        mut was_paused: Local<bool>,
+    ) {
+        if toggles.iter().count().is_multiple_of(2) {
+            return;
+        }
+        shown.0 = !shown.0;
+        if shown.0 {
+            *was_paused = clock.is_paused();
+            clock.set_paused(true);
+        } else {
+            clock.set_paused(*was_paused);
+        }
+    }
+
+    /// Carry out the actions of the menu, once confirmed when they need it. Opening the options
+    /// is left to the UI backend
+    #[allow(clippy::too_many_arguments)]
+    fn act(
+        mut cmds: Commands,
+        mut actions: EventReader<PauseActionEvent>,
+        mut confirmations: EventReader<ConfirmedEvent>,
+        shown: Res<ShownPause>,
+        board_options: Option<Res<BoardOptions>>,
+        (state, status, seed): (
+            Option<Res<BoardState>>,
+            Res<GameStatus>,
+            Option<Res<BoardSeed>>,
+        ),
+        mut toggles: EventWriter<PauseToggleEvent>,
+        mut confirms: EventWriter<ConfirmRequestEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut apply: EventWriter<OptionsApplyEvent>,
+        mut exits: EventWriter<AppExit>,
+    ) {
+        let confirmed = confirmations
+            .iter()
+            .filter_map(|ConfirmedEvent(action)| match action {
+                ConfirmAction::Pause(action) => Some(action),
+                _ => None,
+            })
+            .next_back();
+        let action = match (confirmed, actions.iter().last()) {
+            (Some(action), _) => action,
+            (None, Some(action)) => {
+                let seed = seed.map(|seed| *seed);
+                if let Some(request) = Self::confirmation(action, state.as_deref(), *status, seed) {
+                    // the menu stays open behind the question
+                    confirms.send(request);
+                    return;
+                }
+                action
+            }
+            (None, None) => return,
+        };
+        if shown.0 {
+            toggles.send(PauseToggleEvent);
+        }
+
+        match action {
+            PauseActionEvent::Restart => {
+                let Some(state) = state else {
+                    return;
+                };
+                info!("Restarting the board");
+                // the same mines from a file, so a puzzle of the week played again is practice
+                let mut board = SaveGame::capture(&state);
+                board.revealed.clear();
+                board.flagged.clear();
+                cmds.insert_resource(ResumeGame(board));
+                regenerate.send(BoardRegenerateEvent);
+            }
+            PauseActionEvent::NewBoard => regenerate.send(BoardRegenerateEvent),
+            PauseActionEvent::Difficulty(difficulty) => {
+                let mut options = board_options.map(|o| o.clone()).unwrap_or_default();
+                info!("Switching to {} boards", difficulty.name());
+                options.difficulty = difficulty.clone();
+                apply.send(OptionsApplyEvent { options });
+            }
+            PauseActionEvent::Settings => {}
+            PauseActionEvent::Quit => {
+                info!("Quitting from the pause menu");
+                exits.send(AppExit);
+            }
+        }
+    }
+
+    /// Question to ask before `action` leaves a board worth keeping: one with
+    /// [`SIGNIFICANT_PROGRESS`], or the daily challenge once started
+    fn confirmation(
+        action: &PauseActionEvent,
+        state: Option<&BoardState>,
+        status: GameStatus,
+        seed: Option<BoardSeed>,
+    ) -> Option<ConfirmRequestEvent> {
+        let confirm = match action {
+            PauseActionEvent::Restart => "Restart",
+            PauseActionEvent::NewBoard => "New board",
+            PauseActionEvent::Difficulty(_) => "Switch",
+            PauseActionEvent::Settings | PauseActionEvent::Quit => return None,
+        };
+        let state =
+            state.filter(|state| status == GameStatus::Playing && state.n_revealed() > 0)?;
+        let pending = ConfirmAction::Pause(action.clone());
+
+        let daily = DailyChallenge::at(insights::unix_time());
+        if seed == Some(BoardSeed(daily.seed)) {
+            return Some(ConfirmRequestEvent::new(
+                pending,
+                "Abandon the daily challenge?",
+                format!("Your attempt at the challenge of {} ends here", daily.date),
+                confirm,
+            ));
+        }
+        let progress = state.progress();
+        (progress >= SIGNIFICANT_PROGRESS).then(|| {
+            ConfirmRequestEvent::new(
+                pending,
+                "Leave this board?",
+                format!(
+                    "{:.0}% of the board is revealed, the progress will be lost",
+                    progress * 100.0
+                ),
+                confirm,
+            )
+        })
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Size, Style, UiRect,
+    },
+};
+
+use super::{screen_layout::spawn_row, PausePlugin, ShownPause};
+use crate::{
+    components::{PauseButton, PauseScreen},
+    events::{PauseActionEvent, PauseToggleEvent},
+    resources::{
+        board_options::{BoardOptions, Difficulty},
+        input_map::InputMap,
+    },
+};
+
+/// Draw the [`ShownPause`] menu with bevy_ui
+pub struct PauseScreenPlugin;
+
+impl Plugin for PauseScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::sync_screen.after(PausePlugin::toggle))
+            .add_system(Self::click);
+    }
+}
+
+impl PauseScreenPlugin {
+    /// Quick actions of the pause menu, one button each. The options have no screen here,
+    /// they are edited in the options file
+    pub fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownPause>,
+        input: Res<InputMap>,
+        board_options: Option<Res<BoardOptions>>,
+        screens: Query<Entity, With<PauseScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        if !shown.0 {
+            return;
+        }
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let button = |parent: &mut ChildBuilder, action, text: String, color| {
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::new(
+                            Val::Px(8.0),
+                            Val::Px(8.0),
+                            Val::Px(2.0),
+                            Val::Px(2.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.08).into(),
+                    ..Default::default()
+                })
+                .insert(PauseButton(action))
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(text, style(22.0, color)));
+                });
+        };
+        let current = board_options.map_or_else(
+            || BoardOptions::default().difficulty,
+            |options| options.difficulty.clone(),
+        );
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.05, 0.05, 0.1, 0.8).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Pause Screen"))
+        .insert(PauseScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Paused\n",
+                style(40.0, Color::WHITE),
+            ));
+            let resume = format!("Resume ({:?})", input.menu);
+            button(parent, None, resume, Color::WHITE);
+            let restart = Some(PauseActionEvent::Restart);
+            button(
+                parent,
+                restart,
+                "Restart this board".to_owned(),
+                Color::WHITE,
+            );
+            let new_board = format!("New board, {}", current.name());
+            button(
+                parent,
+                Some(PauseActionEvent::NewBoard),
+                new_board,
+                Color::WHITE,
+            );
+            spawn_row(parent, 32.0, |row| {
+                for preset in [Difficulty::EASY, Difficulty::MEDIUM, Difficulty::EXPERT] {
+                    let color = if current.dim == preset.dim && current.n_mines == preset.n_mines {
+                        Color::WHITE
+                    } else {
+                        Color::GRAY
+                    };
+                    let name = preset.name();
+                    button(row, Some(PauseActionEvent::Difficulty(preset)), name, color);
+                }
+            });
+            let quit = Some(PauseActionEvent::Quit);
+            button(parent, quit, "Save and quit".to_owned(), Color::WHITE);
+        });
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &PauseButton), Changed<Interaction>>,
+        mut toggles: EventWriter<PauseToggleEvent>,
+        mut actions: EventWriter<PauseActionEvent>,
+    ) {
+        for (interaction, PauseButton(action)) in &buttons {
+            if *interaction != Interaction::Clicked {
+                continue;
+            }
+            match action {
+                Some(action) => actions.send(action.clone()),
+                None => toggles.send(PauseToggleEvent),
+            }
+        }
+    }
+}
@@ -0,0 +1,234 @@
+use bevy::{
+    diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin},
+    input::InputSystem,
+    prelude::{
+        AssetServer, BuildChildren, Children, Color, Commands, ComputedVisibility, CoreStage,
+        Input, KeyCode, MouseButton, Name, NodeBundle, ParallelSystemDescriptorCoercion, Plugin,
+        Query, Res, ResMut, TextBundle, Val, Visibility, With, Without,
+    },
+    render::view::VisibilitySystems,
+    text::{Text, TextStyle},
+    time::Time,
+    ui::{AlignItems, FlexDirection, PositionType, Size, Style, UiColor, UiRect},
+    utils::Instant,
+};
+
+use crate::{
+    components::{FrameBar, PerfHud, PerfHudText, TileCover},
+    resources::{
+        board_options::BoardOptions,
+        frame_times::{FrameTimes, FRAME_HISTORY},
+        input_latency::{InputLatency, LatencyStage, StageTime},
+        input_map::InputMap,
+        tile_index::TileEntityIndex,
+        uncover_queue::UncoverQueue,
+    },
+};
+
+/// Height of the graph in pixels, reached by a frame of [`SLOW_FRAME`]
+const GRAPH_HEIGHT: f32 = 60.0;
+
+/// Frame time of 60 FPS, in seconds, the target of the game
+const TARGET_FRAME: f32 = 1.0 / 60.0;
+
+/// Frame time of 30 FPS, in seconds, noticeably slow
+const SLOW_FRAME: f32 = 1.0 / 30.0;
+
+/// Debug only: FPS, frame time graph, entity count, uncover queue depth and click latency in the
+/// bottom right corner, toggled with `InputMap::perf_hud` (`F3`), to check performance work on huge
+/// boards in game. The latency of a click uncovering a tile is followed through the board systems,
+/// see [`InputLatency`], and is 0 frames when the tile shows in the frame the click was read
+pub struct PerfHudPlugin;
+
+impl Plugin for PerfHudPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_plugin(EntityCountDiagnosticsPlugin)
+            .init_resource::<FrameTimes>()
+            .init_resource::<InputLatency>()
+            .add_startup_system(Self::spawn_hud)
+            .add_system_to_stage(CoreStage::PreUpdate, Self::start_probe.after(InputSystem))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                Self::probe_visible.after(VisibilitySystems::CheckVisibility),
+            )
+            .add_system(Self::record)
+            .add_system(Self::toggle)
+            .add_system(Self::update_hud.after(Self::record));
+    }
+}
+
+impl PerfHudPlugin {
+    fn spawn_hud(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                // bevy_ui columns grow upwards: the graph under the counters
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                padding: UiRect::all(Val::Px(4.0)),
+                ..Default::default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            visibility: Visibility { is_visible: false },
+            ..Default::default()
+        })
+        .insert(Name::new("Performance HUD"))
+        .insert(PerfHud)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Auto, Val::Px(GRAPH_HEIGHT)),
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::FlexStart,
+                        ..Default::default()
+                    },
+                    color: Color::NONE.into(),
+                    ..Default::default()
+                })
+                .with_children(|graph| {
+                    for i in 0..FRAME_HISTORY {
+                        graph
+                            .spawn_bundle(NodeBundle {
+                                style: Style {
+                                    size: Size::new(Val::Px(2.0), Val::Px(0.0)),
+                                    ..Default::default()
+                                },
+                                ..Default::default()
+                            })
+                            .insert(FrameBar(i));
+                    }
+                });
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/robotoslab.ttf"),
+                        font_size: 16.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(PerfHudText);
+        });
+    }
+
+    fn record(time: Res<Time>, mut times: ResMut<FrameTimes>) {
+        times.push(time.delta_seconds());
+    }
+
+    /// Follow the clicks on the uncover button, as `BoardPlugin::handle_input` reads them
+    fn start_probe(
+        buttons: Res<Input<MouseButton>>,
+        input: Res<InputMap>,
+        board_options: Option<Res<BoardOptions>>,
+        mut latency: ResMut<InputLatency>,
+    ) {
+        latency.next_frame();
+        let clicked = if board_options.is_some_and(|options| options.click_on_release) {
+            buttons.just_released(input.uncover)
+        } else {
+            buttons.just_pressed(input.uncover)
+        };
+        if clicked {
+            latency.press(Instant::now());
+        }
+    }
+
+    /// The click is on screen once the cover of its tile is hidden for the renderer
+    fn probe_visible(
+        index: Res<TileEntityIndex>,
+        tiles: Query<&Children>,
+        covers: Query<&ComputedVisibility, With<TileCover>>,
+        mut latency: ResMut<InputLatency>,
+    ) {
+        if !latency.reached(LatencyStage::Sprite) {
+            return;
+        }
+        let Some(children) = latency
+            .coord()
+            .and_then(|coord| index.get(coord))
+            .and_then(|tile| tiles.get(tile).ok())
+        else {
+            return;
+        };
+        if covers.iter_many(children).all(|cover| !cover.is_visible()) {
+            latency.mark(LatencyStage::Visible, None, Instant::now());
+        }
+    }
+
+    fn toggle(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut huds: Query<&mut Visibility, With<PerfHud>>,
+    ) {
+        if !keys.just_pressed(input.perf_hud) {
+            return;
+        }
+        for mut visibility in &mut huds {
+            visibility.is_visible = !visibility.is_visible;
+        }
+    }
+
+    fn update_hud(
+        times: Res<FrameTimes>,
+        diagnostics: Res<Diagnostics>,
+        queue: Res<UncoverQueue>,
+        latency: Res<InputLatency>,
+        huds: Query<&Visibility, With<PerfHud>>,
+        mut texts: Query<&mut Text, With<PerfHudText>>,
+        mut bars: Query<(&FrameBar, &mut Style, &mut UiColor), Without<PerfHudText>>,
+    ) {
+        if !huds.iter().any(|visibility| visibility.is_visible) {
+            return;
+        }
+
+        let entities = diagnostics
+            .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+            .and_then(|diagnostic| diagnostic.value())
+            .unwrap_or_default();
+        let ms = |seconds: Option<f32>| seconds.unwrap_or_default() * 1000.0;
+        let time = |time: StageTime| format!("{}f {:.1} ms", time.frames, time.seconds * 1000.0);
+        let click = match (latency.last(), latency.worst()) {
+            (Some(last), Some(worst)) => format!(
+                "click to screen {}, worst {}\n{}",
+                time(last.total()),
+                time(worst.total()),
+                LatencyStage::ALL[..3]
+                    .iter()
+                    .map(|&stage| format!("{} {}", stage.name(), time(last.stage(stage))))
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            ),
+            _ => "click to screen: no click yet".to_string(),
+        };
+        for mut text in &mut texts {
+            text.sections[0].value = format!(
+                "{:.0} FPS, {:.1} ms, worst {:.1} ms\n{entities} entities, {} tiles queued\n{click}",
+                times.fps().unwrap_or_default(),
+                ms(times.average()),
+                ms(times.worst()),
+                queue.len()
+            );
+        }
+
+        // the newest frame on the right
+        let frames = times.iter().collect::<Vec<_>>();
+        let offset = FRAME_HISTORY - frames.len();
+        for (FrameBar(i), mut style, mut color) in &mut bars {
+            let frame = i.checked_sub(offset).map_or(0.0, |i| frames[i]);
+            let height = (frame / SLOW_FRAME).min(1.0) * GRAPH_HEIGHT;
+            style.size.height = Val::Px(height);
+            *color = match frame {
+                frame if frame <= TARGET_FRAME * 1.1 => Color::GREEN,
+                frame if frame <= SLOW_FRAME * 1.1 => Color::ORANGE,
+                _ => Color::RED,
+            }
+            .into();
+        }
+    }
+}
@@ -0,0 +1,407 @@
+use std::path::PathBuf;
+
+use bevy::{
+    prelude::{
+        info, warn, AssetServer, BuildChildren, Color, Commands, DespawnRecursiveExt, Entity,
+        EventReader, EventWriter, Input, KeyCode, MouseButton, Name,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, Transform, Vec3, With,
+    },
+    text::{Text, Text2dBundle, TextAlignment, TextStyle},
+};
+
+use super::{BoardPlugin, BoardSystem, GameStatus, ResumeGame};
+use crate::{
+    components::{AnnotationCallout, Board},
+    events::{
+        BoardRegenerateEvent, PlaybackEvent, ReplayAnnotateEvent, TileMarkEvent, TileTriggerEvent,
+        ToastEvent,
+    },
+    resources::{
+        board_options::{DisplayParams, GameMode},
+        board_state::BoardState,
+        game_assets::FONT,
+        game_clock::GameClock,
+        input_map::InputMap,
+        rule_set::{Punishment, RuleSet},
+        tile_index::TileEntityIndex,
+        toasts::ToastKind,
+        uncover_queue::UncoverQueue,
+    },
+    save::{self, Action, Annotation, Replay, ReplayKeyframes, KEYFRAME_INTERVAL},
+    solver::ConstraintGraph,
+};
+
+/// Replay to watch once the first board is up, given with `--replay <file>`
+#[derive(Debug, Clone)]
+pub struct WatchReplay {
+    pub replay: Replay,
+    pub path: PathBuf,
+}
+
+/// Replay being played on the board
+#[derive(Debug, Clone)]
+pub struct ReplayViewer {
+    pub replay: Replay,
+    /// File of the replay, where annotations are saved
+    pub path: PathBuf,
+    /// Index of the next move
+    pub next: usize,
+    /// `GameClock::elapsed_seconds` when the board of the replay came up, moved by seeking
+    pub started: Option<f64>,
+    /// `None` when the moves could not be played back ahead, seeking is then off
+    pub keyframes: Option<ReplayKeyframes>,
+}
+
+/// Watch replays on the board, from a quarter of real time to 16 times faster, paused, or a
+/// move at a time, with the keys of the `InputMap` or the buttons of the UI. The controls scale
+/// the `GameClock`, so animations follow the moves. The UI can also seek to any time of the
+/// replay, the board being rebuilt from the closest of its `ReplayKeyframes`. The annotations of
+/// the replay show as callouts over their tiles in time, and the UI can add some for a lesson,
+/// saved in the file right away. The player cannot click while a replay is watched, which lasts
+/// until another board replaces it, and nothing is recorded
+pub struct PlaybackPlugin;
+
+impl Plugin for PlaybackPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<PlaybackEvent>()
+            .add_event::<ReplayAnnotateEvent>()
+            .add_system(Self::playback_keys.before(Self::control))
+            .add_system(Self::control)
+            // the board is rebuilt next frame, once the `ResumeGame` is inserted
+            .add_system(Self::start.after(BoardPlugin::regenerate_board))
+            .add_system(Self::follow_board.before(Self::play))
+            .add_system(Self::block_input.before(BoardSystem::Input))
+            .add_system(Self::seek.after(Self::control).before(Self::play))
+            .add_system(Self::play.after(Self::control).before(BoardSystem::Input))
+            .add_system(Self::annotate.after(Self::play))
+            .add_system(Self::show_annotation.after(Self::annotate));
+    }
+}
+
+impl PlaybackPlugin {
+    fn playback_keys(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        viewer: Option<Res<ReplayViewer>>,
+        mut events: EventWriter<PlaybackEvent>,
+    ) {
+        if viewer.is_none() {
+            return;
+        }
+        let bindings = [
+            (input.slower, PlaybackEvent::Slower),
+            (input.faster, PlaybackEvent::Faster),
+            (input.pause, PlaybackEvent::TogglePause),
+            (input.step, PlaybackEvent::Step),
+        ];
+        for (key, event) in bindings {
+            if keys.just_pressed(key) {
+                events.send(event);
+            }
+        }
+    }
+
+    /// Apply the controls, taking effect on the next tick of the clock
+    fn control(
+        mut clock: ResMut<GameClock>,
+        mut events: EventReader<PlaybackEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let before = (clock.scale(), clock.is_paused());
+        for event in events.iter() {
+            match *event {
+                PlaybackEvent::Slower => clock.slower(),
+                PlaybackEvent::Faster => clock.faster(),
+                PlaybackEvent::Speed(speed) => clock.set_scale(speed),
+                PlaybackEvent::TogglePause => {
+                    let paused = clock.is_paused();
+                    clock.set_paused(!paused);
+                }
+                PlaybackEvent::Step => clock.step(),
+                PlaybackEvent::Seek(_) => {}
+            }
+        }
+        if (clock.scale(), clock.is_paused()) != before {
+            let body = if clock.is_paused() {
+                "Paused".to_owned()
+            } else {
+                format!("{}× speed", clock.scale())
+            };
+            toasts.send(ToastEvent::new(ToastKind::Info, "Replay", body));
+        }
+    }
+
+    /// Build the board of a replay waiting to be watched, once the first board is up
+    fn start(
+        mut cmds: Commands,
+        watch: Option<Res<WatchReplay>>,
+        state: Option<Res<BoardState>>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+    ) {
+        let (Some(watch), true) = (watch, state.is_some()) else {
+            return;
+        };
+        cmds.remove_resource::<WatchReplay>();
+
+        let replay = &watch.replay;
+        info!(
+            "Watching a replay of {} moves on a {}x{} board",
+            replay.moves.len(),
+            replay.width,
+            replay.height
+        );
+        // keyframes of another game would show the wrong boards, the moves are played ahead then
+        let keyframes = replay
+            .keyframes()
+            .or_else(|error| {
+                warn!("Ignoring the keyframes of the replay: {error}");
+                replay.keyframes_every(KEYFRAME_INTERVAL)
+            })
+            .map_err(|error| warn!("Seeking is off, the replay cannot be played ahead: {error}"))
+            .ok();
+        cmds.insert_resource(ResumeGame(replay.layout()));
+        cmds.insert_resource(ReplayViewer {
+            replay: replay.clone(),
+            path: watch.path.clone(),
+            next: 0,
+            started: None,
+            keyframes,
+        });
+        regenerate.send(BoardRegenerateEvent);
+    }
+
+    /// Play the replay under the rules of its mode once its board is up, and stop watching
+    /// when another board replaces it
+    fn follow_board(
+        mut cmds: Commands,
+        state: Option<Res<BoardState>>,
+        viewer: Option<ResMut<ReplayViewer>>,
+        mut clock: ResMut<GameClock>,
+        mut rules: ResMut<RuleSet>,
+        mut mode: ResMut<GameMode>,
+    ) {
+        let added = state.is_some_and(|state| state.is_added());
+        let (Some(mut viewer), true) = (viewer, added) else {
+            return;
+        };
+        if viewer.started.is_some() {
+            cmds.remove_resource::<ReplayViewer>();
+            clock.resume_real_time();
+            return;
+        }
+
+        viewer.started = Some(clock.elapsed_seconds());
+        *mode = viewer.replay.mode;
+        *rules = RuleSet {
+            recorded: false,
+            undo: false,
+            ..RuleSet::for_mode(viewer.replay.mode)
+        };
+    }
+
+    /// Keep the player from moving for the replay
+    fn block_input(viewer: Option<Res<ReplayViewer>>, mut buttons: ResMut<Input<MouseButton>>) {
+        if viewer.is_some() {
+            buttons.clear();
+        }
+    }
+
+    /// Jump to the time of the last seek, rebuilding the board from the closest keyframe with
+    /// the moves made by then. The moves after it are played from there on
+    #[allow(clippy::too_many_arguments)]
+    fn seek(
+        clock: Res<GameClock>,
+        rules: Res<RuleSet>,
+        mut events: EventReader<PlaybackEvent>,
+        mut status: ResMut<GameStatus>,
+        mut queue: ResMut<UncoverQueue>,
+        viewer: Option<ResMut<ReplayViewer>>,
+        state: Option<ResMut<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+    ) {
+        let time = events
+            .iter()
+            .filter_map(|event| match *event {
+                PlaybackEvent::Seek(time) => Some(time),
+                _ => None,
+            })
+            .next_back();
+        let (Some(time), Some(mut viewer), Some(mut state), Some(mut graph)) =
+            (time, viewer, state, graph)
+        else {
+            return;
+        };
+        let (Some(_), Some(keyframes)) = (viewer.started, &viewer.keyframes) else {
+            return;
+        };
+
+        let time = time.clamp(0.0, viewer.replay.duration());
+        let moves = viewer.replay.moves_until(time);
+        let mut board = keyframes.seek(&viewer.replay, moves);
+        let exploded = board.hidden_mines().count() < board.n_mines();
+        let seeked = if exploded && rules.mistakes == Punishment::Lose {
+            board.reveal_mines();
+            GameStatus::Lost
+        } else if rules.is_won(&board) {
+            GameStatus::Won
+        } else {
+            GameStatus::Playing
+        };
+
+        // the graph knows the numbers revealed, from scratch as an undo cannot help here
+        let rebuilt = graph.rebuilt(&board);
+
+        // a cascade still running belongs to the moves left behind
+        queue.clear();
+        state.rewind(&board);
+        *graph = rebuilt;
+        if *status != seeked {
+            *status = seeked;
+        }
+        viewer.next = moves;
+        viewer.started = Some(clock.elapsed_seconds() - f64::from(time));
+    }
+
+    /// Send the moves that are due, one a frame at most so that every flood settles first
+    fn play(
+        mut clock: ResMut<GameClock>,
+        queue: Res<UncoverQueue>,
+        viewer: Option<ResMut<ReplayViewer>>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+        mut marks: EventWriter<TileMarkEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(mut viewer) = viewer else {
+            return;
+        };
+        let Some(started) = viewer.started else {
+            return;
+        };
+        if !queue.is_empty() {
+            return;
+        }
+        let Some(&next) = viewer.replay.moves.get(viewer.next) else {
+            return;
+        };
+
+        let time = started + next.time as f64;
+        if time > clock.elapsed_seconds() && !clock.is_stepping() {
+            return;
+        }
+        clock.advance_to(time);
+        viewer.next += 1;
+        // the viewer stays up, to seek back
+        if viewer.next == viewer.replay.moves.len() {
+            toasts.send(ToastEvent::new(
+                ToastKind::Info,
+                "Replay finished",
+                format!("{} moves played", viewer.replay.moves.len()),
+            ));
+        }
+        match next.action {
+            Action::Uncover(coord) => triggers.send(TileTriggerEvent { coord }),
+            Action::Flag(coord) => marks.send(TileMarkEvent { coord }),
+        }
+    }
+
+    /// Seconds since the start of the replay, up to its last move
+    fn replay_time(viewer: &ReplayViewer, clock: &GameClock) -> Option<f32> {
+        let elapsed = clock.elapsed_seconds() - viewer.started?;
+        Some((elapsed as f32).clamp(0.0, viewer.replay.duration()))
+    }
+
+    /// Annotate the replay where it is, and write it back to its file
+    fn annotate(
+        clock: Res<GameClock>,
+        viewer: Option<ResMut<ReplayViewer>>,
+        mut annotations: EventReader<ReplayAnnotateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(mut viewer) = viewer else {
+            return;
+        };
+        let Some(time) = Self::replay_time(&viewer, &clock) else {
+            return;
+        };
+        for ReplayAnnotateEvent { text } in annotations.iter() {
+            let last = viewer.next.checked_sub(1);
+            let tile = last.and_then(|last| viewer.replay.moves.get(last));
+            let annotation = Annotation {
+                time,
+                text: text.clone(),
+                tile: tile.map(|tile| tile.action.coord()),
+            };
+            if !viewer.replay.annotate(annotation) {
+                continue;
+            }
+            let toast = match save::save(&viewer.path, &viewer.replay) {
+                Ok(()) => ToastEvent::new(
+                    ToastKind::Success,
+                    "Annotation saved",
+                    format!("At {time:.1}s, in {}", viewer.path.display()),
+                ),
+                Err(error) => ToastEvent::new(
+                    ToastKind::Error,
+                    "Annotation not saved",
+                    format!("{}: {error}", viewer.path.display()),
+                ),
+            };
+            toasts.send(toast);
+        }
+    }
+
+    /// Keep the callout of the annotation up at the time of the replay, over its tile, or in the
+    /// middle of the board when it has none
+    #[allow(clippy::too_many_arguments)]
+    fn show_annotation(
+        mut cmds: Commands,
+        asset_server: Res<AssetServer>,
+        clock: Res<GameClock>,
+        params: Option<Res<DisplayParams>>,
+        index: Res<TileEntityIndex>,
+        viewer: Option<Res<ReplayViewer>>,
+        boards: Query<Entity, With<Board>>,
+        callouts: Query<(Entity, &AnnotationCallout)>,
+    ) {
+        let annotation = viewer.as_ref().and_then(|viewer| {
+            let time = Self::replay_time(viewer, &clock)?;
+            viewer.replay.annotation_at(time)
+        });
+        let shown = callouts.iter().next().map(|(_, callout)| &callout.0);
+        if shown == annotation {
+            return;
+        }
+        for (entity, _) in &callouts {
+            cmds.entity(entity).despawn_recursive();
+        }
+        let (Some(annotation), Some(params)) = (annotation, params) else {
+            return;
+        };
+
+        let on_tile = annotation.tile.and_then(|tile| index.get(tile));
+        let (parent, translation) = match (on_tile, boards.get_single()) {
+            // above the tile, clear of its number
+            (Some(tile), _) => (tile, Vec3::new(0.0, params.tile_size * 0.6, 6.0)),
+            (None, Ok(board)) => (board, (params.board_size / 2.0).extend(6.0)),
+            (None, Err(_)) => return,
+        };
+        cmds.entity(parent).with_children(|parent| {
+            parent
+                .spawn_bundle(Text2dBundle {
+                    text: Text::from_section(
+                        annotation.text.clone(),
+                        TextStyle {
+                            font: asset_server.load(FONT),
+                            font_size: (params.tile_size * 0.6).max(14.0),
+                            color: Color::YELLOW,
+                        },
+                    )
+                    .with_alignment(TextAlignment::BOTTOM_CENTER),
+                    transform: Transform::from_translation(translation),
+                    ..Default::default()
+                })
+                .insert(Name::new("Annotation"))
+                .insert(AnnotationCallout(annotation.clone()));
+        });
+    }
+}
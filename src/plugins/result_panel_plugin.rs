@@ -0,0 +1,133 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Size, Style, UiRect,
+    },
+};
+
+use super::{AdaptivePlugin, SharePlugin, ShownAdaptive, ShownResult};
+use crate::{
+    components::{ResultPanel, ShareButton},
+    events::ShareEvent,
+};
+
+/// Draw the [`ShownResult`], and the [`ShownAdaptive`] summary along with it, with bevy_ui
+pub struct ResultPanelPlugin;
+
+impl Plugin for ResultPanelPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(
+            Self::sync_panel
+                .after(SharePlugin::sum_up)
+                .after(AdaptivePlugin::record),
+        )
+        .add_system(Self::click);
+    }
+}
+
+impl ResultPanelPlugin {
+    /// Result of the game just finished, in a bar under the board that leaves it in sight
+    fn sync_panel(
+        mut cmds: Commands,
+        shown: Res<ShownResult>,
+        adaptive: Res<ShownAdaptive>,
+        panels: Query<Entity, With<ResultPanel>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() && !adaptive.is_changed() {
+            return;
+        }
+        panels
+            .iter()
+            .for_each(|panel| cmds.entity(panel).despawn_recursive());
+        let Some(result) = &shown.0 else {
+            return;
+        };
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let button = |parent: &mut ChildBuilder, board, text: &str| {
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::new(
+                            Val::Px(8.0),
+                            Val::Px(8.0),
+                            Val::Px(2.0),
+                            Val::Px(2.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.08).into(),
+                    ..Default::default()
+                })
+                .insert(ShareButton { board })
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(text, style(18.0, Color::WHITE)));
+                });
+        };
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                flex_direction: FlexDirection::Row,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Result Panel"))
+        .insert(ResultPanel)
+        .with_children(|parent| {
+            parent.spawn_bundle(
+                TextBundle::from_section(result.headline(), style(20.0, Color::WHITE)).with_style(
+                    Style {
+                        margin: UiRect::all(Val::Px(8.0)),
+                        ..Default::default()
+                    },
+                ),
+            );
+            if let Some(adaptive) = adaptive.0 {
+                parent.spawn_bundle(
+                    TextBundle::from_section(adaptive.describe(), style(16.0, Color::GRAY))
+                        .with_style(Style {
+                            margin: UiRect::all(Val::Px(8.0)),
+                            ..Default::default()
+                        }),
+                );
+            }
+            button(parent, false, "Copy result");
+            button(parent, true, "Copy with the board");
+        });
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &ShareButton), Changed<Interaction>>,
+        mut shares: EventWriter<ShareEvent>,
+    ) {
+        for (interaction, button) in &buttons {
+            if *interaction == Interaction::Clicked {
+                shares.send(ShareEvent {
+                    board: button.board,
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use bevy::{
+    math::ivec2,
+    prelude::{
+        Added, BuildChildren, Color, Commands, Entity, Name, Plugin, Query, Res, SystemSet,
+        Text2dBundle, Transform, Vec2, Visibility, With,
+    },
+    text::{Text, TextAlignment, TextStyle},
+};
+
+use super::BoardSystem;
+use crate::{
+    components::{Board, Line, RulerText},
+    resources::{
+        board::column_name,
+        board_assets::BoardAssets,
+        board_options::{BoardOptions, DisplayParams},
+    },
+};
+
+/// Color of the labels, quieter than the line counts
+const RULER_COLOR: Color = Color::rgb(0.7, 0.7, 0.7);
+
+/// Letters of the columns and numbers of the rows along the border of the board with
+/// `BoardOptions::coordinates`, so that players and spectators can name tiles like `C7`. The
+/// labels are children of the board, scaling and panning with it, on the sides the line counts
+/// leave free
+pub struct RulerPlugin;
+
+impl Plugin for RulerPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(Self::spawn_labels)
+                .with_system(Self::show_labels)
+                .with_system(Self::relayout),
+        );
+    }
+}
+
+impl RulerPlugin {
+    /// Rows on the right and columns at the bottom, in the default view
+    fn spawn_labels(
+        mut cmds: Commands,
+        boards: Query<Entity, Added<Board>>,
+        params: Option<Res<DisplayParams>>,
+        board_options: Option<Res<BoardOptions>>,
+        assets: Res<BoardAssets>,
+    ) {
+        let Some(params) = params else {
+            return;
+        };
+
+        let shown = board_options.is_some_and(|options| options.coordinates);
+        let style = TextStyle {
+            font: assets.font.clone(),
+            font_size: params.tile_size * 0.45,
+            color: RULER_COLOR,
+        };
+        let lines = (0..params.dim.y)
+            .map(Line::Row)
+            .chain((0..params.dim.x).map(Line::Column));
+        for board in &boards {
+            cmds.entity(board).with_children(|parent| {
+                for line in lines.clone() {
+                    let label = match line {
+                        Line::Row(y) => (y + 1).to_string(),
+                        Line::Column(x) => column_name(x),
+                    };
+                    parent
+                        .spawn_bundle(Text2dBundle {
+                            text: Text::from_section(label, style.clone())
+                                .with_alignment(TextAlignment::CENTER),
+                            transform: Transform::from_translation(
+                                Self::position(&params, line).extend(1.0),
+                            ),
+                            visibility: Visibility { is_visible: shown },
+                            ..Default::default()
+                        })
+                        .insert(Name::new(format!("{line:?} Label")))
+                        .insert(RulerText(line));
+                }
+            });
+        }
+    }
+
+    /// Center of the tile just outside the board, next to the last tile of a row or the first
+    /// tile of a column
+    fn position(params: &DisplayParams, line: Line) -> Vec2 {
+        let coord = match line {
+            Line::Row(y) => ivec2(params.dim.x as i32, y as i32),
+            Line::Column(x) => ivec2(x as i32, -1),
+        };
+        params.tile_center(coord)
+    }
+
+    /// Follow the option, on the current board as well
+    fn show_labels(
+        board_options: Option<Res<BoardOptions>>,
+        mut labels: Query<&mut Visibility, With<RulerText>>,
+    ) {
+        let Some(options) = board_options.filter(|options| options.is_changed()) else {
+            return;
+        };
+        for mut visibility in &mut labels {
+            if visibility.is_visible != options.coordinates {
+                visibility.is_visible = options.coordinates;
+            }
+        }
+    }
+
+    /// Follow the tiles when the view of the board turns
+    fn relayout(
+        params: Option<Res<DisplayParams>>,
+        mut labels: Query<(&RulerText, &mut Transform)>,
+    ) {
+        let Some(params) = params.filter(|params| params.is_changed()) else {
+            return;
+        };
+        for (RulerText(line), mut transform) in &mut labels {
+            let position = Self::position(&params, *line);
+            transform.translation = position.extend(transform.translation.z);
+        }
+    }
+}
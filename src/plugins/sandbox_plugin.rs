@@ -0,0 +1,302 @@
+use bevy::{
+    prelude::{
+        info, Children, Commands, DespawnRecursiveExt, Entity, EventReader, EventWriter, IVec2,
+        Input, KeyCode, MouseButton, Or, ParallelSystemDescriptorCoercion, Plugin, Query, Res,
+        ResMut, Vec2, With,
+    },
+    sprite::Sprite,
+    window::Windows,
+};
+
+use super::{BoardPlugin, BoardSystem, GameStatus, ReplayViewer, ShownPause, UndoHistory};
+use crate::{
+    components::{Mine, MineNeighbor, MineSprite, NumberSprite, NumberText, TileCover},
+    events::{SandboxEvent, SandboxToggleEvent, ToastEvent},
+    resources::{
+        board::TileState,
+        board_assets::BoardAssets,
+        board_options::DisplayParams,
+        board_state::{BoardState, Cover},
+        game_clock::GameClock,
+        input_map::InputMap,
+        rule_set::{Punishment, RuleSet},
+        tile_index::TileEntityIndex,
+        toasts::ToastKind,
+    },
+    solver::ConstraintGraph,
+};
+
+/// Sandbox over the board on screen, drawn by the UI backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Sandbox {
+    /// Editing the board instead of playing it
+    pub open: bool,
+    /// Mine picked up, moved to the next tile picked
+    pub held: Option<IVec2>,
+    /// Edits made to the board, which then stays out of the stats
+    pub edits: u32,
+    // the clock when the sandbox opened, as it is left on resuming
+    clock_paused: bool,
+}
+
+/// Explore "what if" lines on the current board: the sandbox key, `E` in the default `InputMap`,
+/// pauses it for editing. A click reveals a tile or covers it again, a right click flags it, and
+/// `Shift`+click picks a mine up and drops it on a safe tile, the numbers following. Press again to
+/// resume playing from there. Every change goes through a [`SandboxEvent`], which updates the
+/// `BoardState` and the tile entities together. An edited board is left out of the stats, and its
+/// moves before the edits cannot be taken back. Not in replays or versus games
+pub struct SandboxPlugin;
+
+impl Plugin for SandboxPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<SandboxToggleEvent>()
+            .add_event::<SandboxEvent>()
+            .init_resource::<Sandbox>()
+            .add_system(Self::sandbox_key.before(Self::toggle))
+            .add_system(Self::follow_board.before(Self::toggle))
+            .add_system(Self::toggle.before(BoardSystem::Input))
+            .add_system(
+                Self::edit_input
+                    .after(Self::toggle)
+                    .before(BoardSystem::Input),
+            )
+            .add_system(
+                Self::apply
+                    .after(Self::edit_input)
+                    .before(BoardSystem::Uncover),
+            );
+    }
+}
+
+impl SandboxPlugin {
+    fn sandbox_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut toggles: EventWriter<SandboxToggleEvent>,
+    ) {
+        if keys.just_pressed(input.sandbox) {
+            toggles.send(SandboxToggleEvent);
+        }
+    }
+
+    /// Close the sandbox of the board replaced, edits are not carried over
+    fn follow_board(
+        state: Option<Res<BoardState>>,
+        mut sandbox: ResMut<Sandbox>,
+        mut clock: ResMut<GameClock>,
+    ) {
+        if !state.is_some_and(|state| state.is_added()) || *sandbox == Sandbox::default() {
+            return;
+        }
+        if sandbox.open {
+            clock.set_paused(sandbox.clock_paused);
+        }
+        *sandbox = Sandbox::default();
+    }
+
+    /// Open the sandbox, pausing the clock, or resume playing the board as edited
+    #[allow(clippy::too_many_arguments)]
+    pub fn toggle(
+        mut toggles: EventReader<SandboxToggleEvent>,
+        mut sandbox: ResMut<Sandbox>,
+        mut clock: ResMut<GameClock>,
+        rules: Res<RuleSet>,
+        viewer: Option<Res<ReplayViewer>>,
+        input: Res<InputMap>,
+        mut status: ResMut<GameStatus>,
+        mut history: ResMut<UndoHistory>,
+        state: Option<ResMut<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if toggles.iter().count().is_multiple_of(2) {
+            return;
+        }
+        let (Some(mut state), Some(mut graph)) = (state, graph) else {
+            return;
+        };
+
+        if !sandbox.open {
+            if viewer.is_some() || rules.versus {
+                toasts.send(ToastEvent::new(
+                    ToastKind::Warning,
+                    "No sandbox",
+                    "Replays and versus games cannot be edited",
+                ));
+                return;
+            }
+            sandbox.open = true;
+            sandbox.clock_paused = clock.is_paused();
+            clock.set_paused(true);
+            toasts.send(ToastEvent::new(
+                ToastKind::Info,
+                "Sandbox",
+                format!(
+                    "{:?} click reveals or covers a tile, {:?} click flags it, Shift+{:?} click \
+                     moves a mine. Press {:?} to resume",
+                    input.uncover, input.flag, input.uncover, input.sandbox
+                ),
+            ));
+            return;
+        }
+
+        sandbox.open = false;
+        sandbox.held = None;
+        clock.set_paused(sandbox.clock_paused);
+        if sandbox.edits == 0 {
+            return;
+        }
+
+        // what the edits left, as seeking in a replay does
+        let exploded = state.hidden_mines().count() < state.n_mines();
+        let resumed = if exploded && rules.mistakes == Punishment::Lose {
+            state.reveal_mines();
+            GameStatus::Lost
+        } else if rules.is_won(&state) {
+            GameStatus::Won
+        } else {
+            GameStatus::Playing
+        };
+        *graph = graph.rebuilt(&state);
+        history.clear();
+        if *status != resumed {
+            *status = resumed;
+        }
+        toasts.send(ToastEvent::new(
+            ToastKind::Info,
+            "Edited board",
+            format!("{} edits, the game is left out of the stats", sandbox.edits),
+        ));
+    }
+
+    /// Turn clicks on the board into edits while the sandbox is open, keeping them from the game
+    #[allow(clippy::too_many_arguments)]
+    fn edit_input(
+        windows: Res<Windows>,
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        params: Option<Res<DisplayParams>>,
+        pause: Res<ShownPause>,
+        state: Option<Res<BoardState>>,
+        mut sandbox: ResMut<Sandbox>,
+        mut buttons: ResMut<Input<MouseButton>>,
+        mut edits: EventWriter<SandboxEvent>,
+    ) {
+        if !sandbox.open {
+            return;
+        }
+        let clicked = [input.uncover, input.flag]
+            .into_iter()
+            .find(|&button| buttons.just_pressed(button));
+        buttons.clear();
+        let (Some(params), Some(state), Some(button), false) = (params, state, clicked, pause.0)
+        else {
+            return;
+        };
+        let Some(coord) =
+            BoardPlugin::cursor_position(&windows).and_then(|position| params.coord_at(position))
+        else {
+            return;
+        };
+
+        let cover = state.cover(coord).unwrap_or_default();
+        if button == input.flag {
+            let flag = if cover == Cover::Flagged {
+                Cover::Covered
+            } else {
+                Cover::Flagged
+            };
+            edits.send(SandboxEvent::Cover(coord, flag));
+        } else if keys.any_pressed([KeyCode::LShift, KeyCode::RShift]) {
+            match sandbox.held.take() {
+                Some(held) if held != coord => edits.send(SandboxEvent::MoveMine(held, coord)),
+                // picked twice, put back
+                Some(_) => {}
+                None if state.tile(coord) == Some(TileState::Mine) => sandbox.held = Some(coord),
+                None => {}
+            }
+        } else {
+            let reveal = if cover == Cover::Revealed {
+                Cover::Covered
+            } else {
+                Cover::Revealed
+            };
+            edits.send(SandboxEvent::Cover(coord, reveal));
+        }
+    }
+
+    /// Carry out the edits on the `BoardState`, and respawn the mines and numbers they changed.
+    /// Covers follow with every other change of the state, see `BoardPlugin::sync_board`
+    #[allow(clippy::too_many_arguments, clippy::type_complexity)]
+    pub fn apply(
+        mut cmds: Commands,
+        assets: Res<BoardAssets>,
+        index: Res<TileEntityIndex>,
+        mut edits: EventReader<SandboxEvent>,
+        mut sandbox: ResMut<Sandbox>,
+        mut rules: ResMut<RuleSet>,
+        state: Option<ResMut<BoardState>>,
+        tiles: Query<&Children>,
+        covers: Query<&Sprite, With<TileCover>>,
+        contents: Query<Entity, Or<(With<MineSprite>, With<NumberText>, With<NumberSprite>)>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(mut state) = state else {
+            return;
+        };
+        for &edit in edits.iter() {
+            if !sandbox.open {
+                continue;
+            }
+            let changed = match edit {
+                SandboxEvent::Cover(coord, cover) => {
+                    if !state.edit_cover(coord, cover) {
+                        continue;
+                    }
+                    Vec::new()
+                }
+                SandboxEvent::MoveMine(from, to) => match state.move_mine(from, to) {
+                    Some(changed) => changed,
+                    None => {
+                        toasts.send(ToastEvent::new(
+                            ToastKind::Warning,
+                            "Mine not moved",
+                            format!("({}, {}) is not a safe tile", to.x, to.y),
+                        ));
+                        continue;
+                    }
+                },
+            };
+
+            for coord in changed {
+                let Some(entity) = index.get(coord) else {
+                    continue;
+                };
+                let Ok(children) = tiles.get(entity) else {
+                    continue;
+                };
+                let size = covers
+                    .iter_many(children)
+                    .next()
+                    .and_then(|sprite| sprite.custom_size)
+                    .unwrap_or(Vec2::ZERO);
+                for content in contents.iter_many(children) {
+                    cmds.entity(content).despawn_recursive();
+                }
+                let mut tile = cmds.entity(entity);
+                tile.remove::<Mine>().remove::<MineNeighbor>();
+                let content = match state.is_gap(coord) {
+                    true => TileState::Clear(0),
+                    false => state.tile(coord).unwrap(),
+                };
+                BoardPlugin::spawn_content(&mut tile, content, size, &assets);
+            }
+
+            sandbox.edits += 1;
+            if rules.recorded {
+                info!("Board edited in the sandbox, the game is not recorded");
+                rules.recorded = false;
+            }
+        }
+    }
+}
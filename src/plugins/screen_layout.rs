@@ -0,0 +1,26 @@
+//! Layout shared by the screens drawn with bevy_ui
+
+use bevy::{
+    prelude::{BuildChildren, ChildBuilder, Color, NodeBundle, Val},
+    ui::{AlignItems, FlexDirection, Size, Style},
+};
+
+/// One cell after the other, left to right, aligned on the bottom
+pub(super) fn spawn_row(
+    parent: &mut ChildBuilder,
+    height: f32,
+    cells: impl FnOnce(&mut ChildBuilder),
+) {
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Auto, Val::Px(height)),
+                flex_direction: FlexDirection::Row,
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .with_children(cells);
+}
@@ -0,0 +1,300 @@
+use bevy::{
+    ecs::event::{Event, Events, ManualEventReader},
+    prelude::{
+        info, warn, Added, Commands, EventWriter, Local, ParallelSystemDescriptorCoercion, Plugin,
+        Query, Res, ResMut, Timer,
+    },
+    time::Time,
+};
+
+use super::{BoardSystem, ResumeGame, LOCAL_PLAYER};
+use crate::{
+    components::Board,
+    events::{
+        BoardRegenerateEvent, ChatEvent, TileMarkEvent, TilePingEvent, TileTriggerEvent, ToastEvent,
+    },
+    protocol::{Handshake, Message},
+    resources::{
+        board_options::BoardOptions,
+        board_seed::SeededBoard,
+        board_state::BoardState,
+        rule_set::{Player, Role, Roles, RuleSet},
+        toasts::ToastKind,
+    },
+    save::Action,
+    session::{
+        Client, ClientEvent, Host, HostEvent, SessionArgs, SessionError, SessionRole, HOST_NAME,
+    },
+    topology::SQUARE,
+};
+
+/// Seconds between two attempts of a client to reach the host again
+const RETRY_INTERVAL: f32 = 2.0;
+
+/// Multiplayer session opened from the [`SessionArgs`]
+#[derive(Debug)]
+pub enum Session {
+    Host(Host),
+    Client(Client),
+}
+
+/// Readers of the events of this player, passed on to the session. They skip the events the
+/// session sends for the other players
+#[derive(Default)]
+pub struct LocalEvents {
+    triggers: ManualEventReader<TileTriggerEvent>,
+    marks: ManualEventReader<TileMarkEvent>,
+    pings: ManualEventReader<TilePingEvent>,
+    chat: ManualEventReader<ChatEvent>,
+}
+
+/// Client side catching up with the host
+#[derive(Default)]
+struct Resync {
+    retry: Option<Timer>,
+    /// Moves of the host received while the board it sent is being built, played once it is
+    pending: Option<Vec<Action>>,
+}
+
+/// Play on one board with other players, see `session`. `--host <addr>` waits for players at
+/// the address, `--join <addr>` plays on the board of the host there, and both give the
+/// `--seed <n>` of the board. The host may take a `--role`, the players joining get the other
+/// one. Moves, chat lines and pings go through the session as events, so the board plays the
+/// moves of the other players as it plays the clicks. A client that loses the host tries again
+/// every [`RETRY_INTERVAL`] seconds, and plays on the board the host sends
+pub struct SessionPlugin;
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<TilePingEvent>()
+            .add_event::<ChatEvent>()
+            .add_startup_system(Self::open)
+            .add_system(
+                Self::exchange
+                    .after(BoardSystem::Input)
+                    .before(BoardSystem::Uncover),
+            );
+    }
+}
+
+impl SessionPlugin {
+    fn open(
+        mut cmds: Commands,
+        args: Option<Res<SessionArgs>>,
+        options: Option<Res<BoardOptions>>,
+        mut roles: ResMut<Roles>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(args) = args else {
+            return;
+        };
+        cmds.remove_resource::<SessionArgs>();
+        roles.assign(Player::Local, args.player_role);
+
+        let mut options = options.map(|options| options.clone()).unwrap_or_default();
+        // seeded boards are squares whatever the options say, see `SeededBoard`
+        options.topology = SQUARE.to_owned();
+        cmds.insert_resource(SeededBoard {
+            seed: args.seed,
+            mode: options.mode,
+            difficulty: options.difficulty.clone(),
+        });
+
+        let handshake = Handshake::new(&options, args.seed);
+        let session = match &args.role {
+            SessionRole::Host(addr) => Host::listen(addr, handshake).map(Session::Host),
+            SessionRole::Join(addr) => Client::connect(addr, handshake).map(Session::Client),
+        };
+        match session {
+            Ok(session) => cmds.insert_resource(session),
+            Err(error) => Self::report(&mut toasts, ToastKind::Error, &error),
+        }
+    }
+
+    /// Pass the events of this player on to the session, and send those of the other players
+    #[allow(clippy::too_many_arguments)]
+    fn exchange(
+        mut cmds: Commands,
+        session: Option<ResMut<Session>>,
+        state: Option<Res<BoardState>>,
+        rules: Res<RuleSet>,
+        mut roles: ResMut<Roles>,
+        new_boards: Query<(), Added<Board>>,
+        time: Res<Time>,
+        mut local: Local<LocalEvents>,
+        mut resync: Local<Resync>,
+        (mut triggers, mut marks): (
+            ResMut<Events<TileTriggerEvent>>,
+            ResMut<Events<TileMarkEvent>>,
+        ),
+        (mut pings, mut chat): (ResMut<Events<TilePingEvent>>, ResMut<Events<ChatEvent>>),
+        (mut regenerate, mut toasts): (EventWriter<BoardRegenerateEvent>, EventWriter<ToastEvent>),
+    ) {
+        let (Some(mut session), Some(state)) = (session, state) else {
+            return;
+        };
+
+        let mut moves = (local.triggers.iter(&triggers))
+            .map(|trigger| Action::Uncover(trigger.coord))
+            .collect::<Vec<_>>();
+        moves.extend(
+            local
+                .marks
+                .iter(&marks)
+                .map(|mark| Action::Flag(mark.coord)),
+        );
+        if let Session::Client(_) = &*session {
+            // played once the host sends them back, in the order of the host
+            triggers.clear();
+            marks.clear();
+        }
+        let mut messages = (local.pings.iter(&pings))
+            .map(|ping| Message::Ping {
+                coord: ping.coord,
+                emote: ping.emote,
+            })
+            .collect::<Vec<_>>();
+        messages.extend(
+            (local.chat.iter(&chat))
+                .filter(|line| line.from == LOCAL_PLAYER)
+                .filter_map(|line| Message::chat(LOCAL_PLAYER, &line.text)),
+        );
+
+        let mut play = |action| match action {
+            Action::Uncover(coord) => triggers.send(TileTriggerEvent { coord }),
+            Action::Flag(coord) => marks.send(TileMarkEvent { coord }),
+        };
+        match &mut *session {
+            Session::Host(host) => {
+                if !new_boards.is_empty() {
+                    host.restart(&state);
+                }
+                for action in moves {
+                    host.broadcast(action);
+                }
+                for message in messages {
+                    let message = match message {
+                        Message::Chat { text, .. } => Message::Chat {
+                            from: HOST_NAME.to_owned(),
+                            text,
+                        },
+                        message => message,
+                    };
+                    host.relay(&message, None);
+                }
+
+                for event in host.poll(&state, &rules, &mut roles) {
+                    match event {
+                        HostEvent::Joined(peer) => {
+                            info!("{} joined", peer.name());
+                            toasts.send(ToastEvent::new(
+                                ToastKind::Info,
+                                "Multiplayer",
+                                format!("{} joined", peer.name()),
+                            ));
+                        }
+                        HostEvent::Left { peer, error } => {
+                            warn!("{} left: {error}", peer.name());
+                            toasts.send(ToastEvent::new(
+                                ToastKind::Warning,
+                                format!("{} left", peer.name()),
+                                error.to_string(),
+                            ));
+                        }
+                        HostEvent::Move { action, .. } => {
+                            play(action);
+                            host.broadcast(action);
+                        }
+                        HostEvent::Forbidden { peer, action } => {
+                            warn!("Dropped {action:?} of {}, its role forbids it", peer.name());
+                        }
+                        HostEvent::Chat { from, text } => chat.send(ChatEvent { from, text }),
+                        HostEvent::Ping { coord, emote } => {
+                            pings.send(TilePingEvent { coord, emote })
+                        }
+                    }
+                }
+            }
+            Session::Client(client) => {
+                if !client.is_connected() {
+                    let retry = (resync.retry)
+                        .get_or_insert_with(|| Timer::from_seconds(RETRY_INTERVAL, true));
+                    if retry.tick(time.delta()).just_finished() {
+                        if let Err(error) = client.reconnect() {
+                            warn!("{error}");
+                        }
+                    }
+                }
+                if !new_boards.is_empty() {
+                    match resync.pending.take() {
+                        Some(actions) => actions.into_iter().for_each(&mut play),
+                        // this player went on a board of its own
+                        None if client.applied() > 0 => client.resync(),
+                        None => {}
+                    }
+                }
+
+                for action in moves {
+                    client.send_move(action);
+                }
+                for message in messages {
+                    client.send(&message);
+                }
+
+                for event in client.poll() {
+                    match event {
+                        ClientEvent::Joined => info!("Joined the game"),
+                        // another version or game, trying again would not help
+                        ClientEvent::Left(
+                            error @ (SessionError::Handshake(_) | SessionError::NoHandshake),
+                        ) => {
+                            Self::report(&mut toasts, ToastKind::Error, &error);
+                            cmds.remove_resource::<Session>();
+                        }
+                        ClientEvent::Left(error) => {
+                            Self::report(&mut toasts, ToastKind::Warning, &error);
+                        }
+                        ClientEvent::AssignRole(role) => {
+                            roles.assign(Player::Local, role);
+                            if role != Role::Both {
+                                toasts.send(ToastEvent::new(
+                                    ToastKind::Info,
+                                    "Multiplayer",
+                                    format!("Your role: {}", role.name()),
+                                ));
+                            }
+                        }
+                        ClientEvent::Apply(action) => match &mut resync.pending {
+                            Some(pending) => pending.push(action),
+                            None => play(action),
+                        },
+                        ClientEvent::Replace(board) => {
+                            cmds.insert_resource(ResumeGame(board));
+                            regenerate.send(BoardRegenerateEvent);
+                            resync.pending = Some(Vec::new());
+                        }
+                        ClientEvent::Chat { from, text } => chat.send(ChatEvent { from, text }),
+                        ClientEvent::Ping { coord, emote } => {
+                            pings.send(TilePingEvent { coord, emote })
+                        }
+                    }
+                }
+            }
+        }
+
+        skip(&mut local.triggers, &triggers);
+        skip(&mut local.marks, &marks);
+        skip(&mut local.pings, &pings);
+        skip(&mut local.chat, &chat);
+    }
+
+    fn report(toasts: &mut EventWriter<ToastEvent>, kind: ToastKind, error: &SessionError) {
+        warn!("{error}");
+        toasts.send(ToastEvent::new(kind, "Multiplayer", error.to_string()));
+    }
+}
+
+/// Mark the events sent so far as read
+fn skip<E: Event>(reader: &mut ManualEventReader<E>, events: &Events<E>) {
+    reader.iter(events).for_each(|_| {});
+}
@@ -0,0 +1,101 @@
+use bevy::prelude::{
+    EventReader, EventWriter, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, SystemSet,
+};
+
+use super::{BoardSystem, GameStatus, InsightsPlugin, SessionTracker};
+use crate::{
+    clipboard,
+    events::{ShareEvent, ToastEvent},
+    resources::{
+        board_seed::BoardSeed, board_state::BoardState, game_clock::GameClock, toasts::ToastKind,
+    },
+    share::GameResult,
+    topology::SQUARE,
+};
+
+/// Result of the game just finished, drawn by the UI backend, `None` while playing
+#[derive(Debug, Default, Clone)]
+pub struct ShownResult(pub Option<GameResult>);
+
+/// Sum up a finished game under the board, with buttons copying the result to the clipboard as
+/// a grid of emoji, see `share`. The result goes away with the next board, or with the undo of
+/// the last move
+pub struct SharePlugin;
+
+impl Plugin for SharePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<ShareEvent>()
+            .init_resource::<ShownResult>()
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::sum_up.after(InsightsPlugin::track)),
+            )
+            .add_system(Self::copy);
+    }
+}
+
+impl SharePlugin {
+    pub fn sum_up(
+        clock: Res<GameClock>,
+        status: Res<GameStatus>,
+        tracker: Res<SessionTracker>,
+        seed: Option<Res<BoardSeed>>,
+        state: Option<Res<BoardState>>,
+        mut shown: ResMut<ShownResult>,
+    ) {
+        if !status.is_changed() {
+            return;
+        }
+        let (Some(state), true) = (state, *status != GameStatus::Playing) else {
+            if shown.0.is_some() {
+                shown.0 = None;
+            }
+            return;
+        };
+
+        let secs = tracker
+            .started
+            .map_or(0.0, |started| (clock.elapsed_seconds() - started) as f32);
+        // seeds give back square boards only, see `SeededBoard`
+        let seed = seed
+            .map(|seed| seed.0)
+            .filter(|_| state.topology().name() == SQUARE);
+        shown.0 = Some(GameResult::new(
+            &state,
+            *status == GameStatus::Won,
+            secs,
+            seed,
+        ));
+    }
+
+    /// Copy the result, the grid taken from the board as it is now, once every mine is shown
+    fn copy(
+        mut shares: EventReader<ShareEvent>,
+        shown: Res<ShownResult>,
+        state: Option<Res<BoardState>>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(ShareEvent { board }) = shares.iter().last() else {
+            return;
+        };
+        let (Some(result), Some(state)) = (&shown.0, state) else {
+            return;
+        };
+
+        let result = GameResult::new(&state, result.won, result.secs, result.seed);
+        match clipboard::copy(&result.to_text(*board)) {
+            Ok(()) => toasts.send(ToastEvent::new(
+                ToastKind::Success,
+                "Result copied",
+                "Paste it in a chat to share it",
+            )),
+            Err(error) => toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Could not copy the result",
+                error.to_string(),
+            )),
+        }
+    }
+}
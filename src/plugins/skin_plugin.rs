@@ -0,0 +1,176 @@
+use bevy::{
+    prelude::{
+        info, warn, AssetServer, DetectChanges, EventWriter, Handle, Image, Local, Or, Plugin,
+        Query, Res, ResMut, Timer, Visibility, With, Without,
+    },
+    sprite::Sprite,
+    text::Text,
+    time::Time,
+};
+
+use crate::{
+    components::{
+        BoardBackground, BoardCoordinate, FlagSprite, MineSprite, NumberSprite, NumberText,
+        TileCover,
+    },
+    events::ToastEvent,
+    resources::{
+        board_assets::BoardAssets,
+        board_options::BoardOptions,
+        skin::{self, SkinPack},
+        toasts::ToastKind,
+    },
+};
+
+/// Seconds between two scans of the skin directory
+const SCAN_INTERVAL: f32 = 2.0;
+
+/// Skin packs found in `assets/skins`, kept up to date while the game runs
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct SkinPacks(pub Vec<SkinPack>);
+
+impl SkinPacks {
+    pub fn get(&self, id: &str) -> Option<&SkinPack> {
+        self.0.iter().find(|pack| pack.id == id)
+    }
+}
+
+/// Find the skin packs dropped into `assets/skins`, and swap the [`BoardAssets`] for the pack
+/// of `BoardOptions::skin` whenever it changes, restyling the board without restarting the game.
+/// The `BoardOptions::number_glyphs` are swapped the same way
+pub struct SkinPlugin;
+
+impl Plugin for SkinPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<SkinPacks>()
+            .add_system(Self::scan)
+            .add_system(Self::swap_skin)
+            .add_system(Self::restyle);
+    }
+}
+
+impl SkinPlugin {
+    /// Scan right away, then every few seconds
+    fn scan(
+        time: Res<Time>,
+        mut timer: Local<Option<Timer>>,
+        mut packs: ResMut<SkinPacks>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let timer = timer.get_or_insert_with(|| Timer::from_seconds(SCAN_INTERVAL, true));
+        if !timer.tick(time.delta()).just_finished() && !packs.is_added() {
+            return;
+        }
+        let Some(assets) = skin::assets_dir() else {
+            return;
+        };
+
+        let (found, invalid) = SkinPack::enumerate(&assets);
+        if packs.0 == found {
+            return;
+        }
+        // reported once, when the packs change
+        for (path, error) in invalid {
+            warn!("Invalid skin manifest {}: {error}", path.display());
+            toasts.send(ToastEvent::new(
+                ToastKind::Warning,
+                "Invalid skin pack",
+                format!("{}: {error}", path.display()),
+            ));
+        }
+        info!("Found {} skin packs", found.len());
+        packs.0 = found;
+    }
+
+    fn swap_skin(
+        asset_server: Res<AssetServer>,
+        options: Option<Res<BoardOptions>>,
+        packs: Res<SkinPacks>,
+        mut assets: ResMut<BoardAssets>,
+    ) {
+        let Some(options) = options else {
+            return;
+        };
+        if !options.is_changed() && !packs.is_changed() {
+            return;
+        }
+
+        let pack = options.skin.as_deref().and_then(|id| packs.get(id));
+        if pack.map(|pack| &pack.id) == assets.skin.as_ref() {
+            if assets.glyphs != options.number_glyphs {
+                assets.glyphs = options.number_glyphs;
+            }
+            return;
+        }
+        if let (Some(id), None) = (&options.skin, pack) {
+            // may show up with the next scan
+            warn!("No skin pack {id:?}, using the built-in look");
+        }
+        *assets = BoardAssets {
+            glyphs: options.number_glyphs,
+            ..match pack {
+                Some(pack) => BoardAssets::from_pack(&asset_server, pack),
+                None => BoardAssets::builtin(&asset_server),
+            }
+        };
+    }
+
+    /// Apply new [`BoardAssets`] to the board in place
+    #[allow(clippy::type_complexity)]
+    fn restyle(
+        assets: Res<BoardAssets>,
+        mut sprites: Query<
+            (
+                &mut Handle<Image>,
+                &mut Sprite,
+                &mut Visibility,
+                Option<&TileCover>,
+                Option<&BoardCoordinate>,
+                Option<&MineSprite>,
+                Option<&FlagSprite>,
+                Option<&NumberSprite>,
+            ),
+            Or<(
+                With<TileCover>,
+                With<BoardCoordinate>,
+                With<MineSprite>,
+                With<FlagSprite>,
+                With<NumberSprite>,
+            )>,
+        >,
+        mut backgrounds: Query<&mut Handle<Image>, (With<BoardBackground>, Without<TileCover>)>,
+        mut numbers: Query<(&NumberText, &mut Text, &mut Visibility), Without<Sprite>>,
+    ) {
+        if !assets.is_changed() || assets.is_added() {
+            return;
+        }
+
+        for (mut texture, mut sprite, mut visibility, cover, tile, mine, flag, number) in
+            &mut sprites
+        {
+            if cover.is_some() {
+                *texture = assets.covered.clone().unwrap_or_default();
+                sprite.color = assets.cover_color();
+            } else if tile.is_some() {
+                *texture = assets.revealed.clone().unwrap_or_default();
+                sprite.color = assets.tile_color();
+            } else if mine.is_some() {
+                *texture = assets.mine.clone();
+            } else if flag.is_some() {
+                *texture = assets.flag.clone();
+            } else if let Some(NumberSprite(n)) = number {
+                let digit = assets.digit(*n);
+                visibility.is_visible = digit.is_some();
+                *texture = digit.unwrap_or_default();
+                sprite.color = assets.digit_color(*n);
+            }
+        }
+        for mut texture in &mut backgrounds {
+            *texture = assets.background.clone().unwrap_or_default();
+        }
+        for (&NumberText(n), mut text, mut visibility) in &mut numbers {
+            visibility.is_visible = assets.digit(n).is_none();
+            text.sections[0].value = assets.label(n);
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use bevy::{
+    math::Vec2,
+    prelude::{
+        info, warn, Assets, Commands, EventReader, EventWriter, Handle, Image, Input, KeyCode,
+        ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+    },
+};
+
+use super::{
+    BoardPlugin, BoardSystem, ConfirmPlugin, GameStatus, ResumeGame, SessionTracker, ShownConfirm,
+};
+use crate::{
+    events::{
+        BoardRegenerateEvent, ConfirmAction, ConfirmRequestEvent, ConfirmedEvent, SlotEvent,
+        SlotsToggleEvent, ToastEvent,
+    },
+    insights,
+    resources::{
+        board_state::BoardState, game_clock::GameClock, input_capture::InputCapture,
+        input_map::InputMap, toasts::ToastKind,
+    },
+    save::{SaveGame, SavedSlot, Slots},
+    storage::Storage,
+    thumbnail::Thumbnail,
+};
+
+/// Longest side of the thumbnail of a slot, in pixels
+const THUMBNAIL_SIZE: f32 = 48.0;
+
+/// Save slots of the data directory, as last read
+#[derive(Debug, Default, Clone)]
+pub struct SaveSlots {
+    pub slots: Slots,
+    /// Thumbnail of every entry, in the same order, and its size on screen
+    pub thumbnails: Vec<(Handle<Image>, Vec2)>,
+    /// Entry under the keyboard focus
+    pub selected: usize,
+}
+
+/// Save slots on screen, drawn by the UI backend
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ShownSlots(pub bool);
+
+/// Put games aside in save slots, next to the autosave, and pick them up later. `InputMap::saves`,
+/// `S` by default, lists them with their difficulty, time played, progress and a thumbnail. `Up`
+/// and `Down` move between them, `Return` loads one, `C` duplicates it, `Delete` deletes it once
+/// confirmed, and `Insert` saves the game in progress, each rebound in `InputMap::slots`
+pub struct SlotsPlugin;
+
+impl Plugin for SlotsPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<SlotsToggleEvent>()
+            .add_event::<SlotEvent>()
+            .init_resource::<SaveSlots>()
+            .init_resource::<ShownSlots>()
+            .init_resource::<InputCapture>()
+            .add_system(Self::slots_key.before(Self::toggle))
+            .add_system(Self::toggle)
+            .add_system(
+                Self::capture_input
+                    .after(Self::toggle)
+                    .before(BoardSystem::Input),
+            )
+            // the key answering a confirmation must not act on the slots as well
+            .add_system(
+                Self::navigate
+                    .after(Self::toggle)
+                    .before(Self::change)
+                    .before(ConfirmPlugin::answer),
+            )
+            // the board is rebuilt next frame, once the `ResumeGame` is inserted
+            .add_system(
+                Self::change
+                    .after(Self::toggle)
+                    .after(BoardPlugin::regenerate_board),
+            );
+    }
+}
+
+impl SlotsPlugin {
+    /// Keep the clicks from the board while the slots cover it
+    fn capture_input(shown: Res<ShownSlots>, mut capture: ResMut<InputCapture>) {
+        if shown.is_changed() {
+            capture.set("slots", shown.0);
+        }
+    }
+
+    /// Read the slots again, keeping the selection on the same line
+    fn rescan(storage: &Storage, slots: &mut SaveSlots, images: &mut Assets<Image>) {
+        let found = match Slots::scan(storage) {
+            Ok(found) => found,
+            Err(error) => {
+                warn!("Could not read the save slots: {error}");
+                return;
+            }
+        };
+        for (file, error) in &found.invalid {
+            warn!("Ignoring the save slot {file}: {error}");
+        }
+
+        // the handles of the previous scan are dropped with it
+        slots.thumbnails = found
+            .entries
+            .iter()
+            .map(|entry| match Thumbnail::of_save(&entry.slot.game) {
+                Ok(thumbnail) => {
+                    // whole pixels per tile, as for the autosave
+                    let scale = (THUMBNAIL_SIZE / thumbnail.dim().max_element() as f32)
+                        .floor()
+                        .max(1.0);
+                    let size = thumbnail.dim().as_vec2() * scale;
+                    (images.add(thumbnail.to_image()), size)
+                }
+                Err(_) => (Handle::default(), Vec2::ZERO),
+            })
+            .collect();
+        slots.selected = slots.selected.min(found.entries.len().saturating_sub(1));
+        slots.slots = found;
+    }
+
+    fn slots_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        confirm: Res<ShownConfirm>,
+        mut toggles: EventWriter<SlotsToggleEvent>,
+    ) {
+        if keys.just_pressed(input.saves) && confirm.request.is_none() {
+            toggles.send(SlotsToggleEvent);
+        }
+    }
+
+    /// Open or close the slots, read again on opening
+    pub fn toggle(
+        mut toggles: EventReader<SlotsToggleEvent>,
+        storage: Option<Res<Storage>>,
+        mut shown: ResMut<ShownSlots>,
+        mut slots: ResMut<SaveSlots>,
+        mut images: ResMut<Assets<Image>>,
+    ) {
+        if toggles.iter().count().is_multiple_of(2) {
+            return;
+        }
+        shown.0 = !shown.0;
+        if let (true, Some(storage)) = (shown.0, storage) {
+            Self::rescan(&storage, &mut slots, &mut images);
+        }
+    }
+
+    fn navigate(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        shown: Res<ShownSlots>,
+        confirm: Res<ShownConfirm>,
+        mut slots: ResMut<SaveSlots>,
+        mut changes: EventWriter<SlotEvent>,
+    ) {
+        if !shown.0 || confirm.request.is_some() {
+            return;
+        }
+
+        let bindings = &input.slots;
+        let count = slots.slots.entries.len();
+        if keys.just_pressed(bindings.up) && slots.selected > 0 {
+            slots.selected -= 1;
+        }
+        if keys.just_pressed(bindings.down) && slots.selected + 1 < count {
+            slots.selected += 1;
+        }
+        if keys.just_pressed(bindings.save) {
+            changes.send(SlotEvent::Save);
+        }
+
+        let Some(entry) = slots.slots.entries.get(slots.selected) else {
+            return;
+        };
+        let file = entry.file.clone();
+        if keys.just_pressed(bindings.load) {
+            changes.send(SlotEvent::Load(file));
+        } else if keys.just_pressed(bindings.duplicate) {
+            changes.send(SlotEvent::Duplicate(file));
+        } else if keys.just_pressed(bindings.delete) {
+            changes.send(SlotEvent::Delete(file));
+        }
+    }
+
+    /// Carry out the changes asked for, deleting only once confirmed, and read the slots again
+    #[allow(clippy::too_many_arguments)]
+    fn change(
+        mut cmds: Commands,
+        mut changes: EventReader<SlotEvent>,
+        mut confirmations: EventReader<ConfirmedEvent>,
+        storage: Option<Res<Storage>>,
+        (state, status, tracker, clock): (
+            Option<Res<BoardState>>,
+            Res<GameStatus>,
+            Res<SessionTracker>,
+            Res<GameClock>,
+        ),
+        mut shown: ResMut<ShownSlots>,
+        mut slots: ResMut<SaveSlots>,
+        mut images: ResMut<Assets<Image>>,
+        mut confirms: EventWriter<ConfirmRequestEvent>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let deletions = confirmations
+            .iter()
+            .filter_map(|ConfirmedEvent(action)| match action {
+                ConfirmAction::DeleteSlot(file) => Some(file.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let changes = changes.iter().cloned().collect::<Vec<_>>();
+        if deletions.is_empty() && changes.is_empty() {
+            return;
+        }
+        let Some(storage) = storage else {
+            return;
+        };
+
+        let mut changed = false;
+        for file in deletions {
+            match Slots::delete(&storage, &file) {
+                Ok(()) => {
+                    info!("Deleted the save slot {file}");
+                    changed = true;
+                }
+                Err(error) => toasts.send(ToastEvent::new(
+                    ToastKind::Error,
+                    "Could not delete the save slot",
+                    error.to_string(),
+                )),
+            }
+        }
+        for change in changes {
+            match change {
+                SlotEvent::Save => {
+                    let state = state.as_ref().filter(|_| *status == GameStatus::Playing);
+                    let Some(state) = state else {
+                        toasts.send(ToastEvent::new(
+                            ToastKind::Warning,
+                            "Nothing to save",
+                            "Only a game in progress goes in a slot",
+                        ));
+                        continue;
+                    };
+                    let elapsed = tracker
+                        .started
+                        .map_or(0.0, |started| (clock.elapsed_seconds() - started) as f32);
+                    let slot = SavedSlot {
+                        game: SaveGame::capture(state),
+                        elapsed,
+                        saved: insights::unix_time(),
+                    };
+                    match Slots::write(&storage, &slot) {
+                        Ok(file) => {
+                            toasts.send(ToastEvent::new(ToastKind::Success, "Game saved", file));
+                            changed = true;
+                        }
+                        Err(error) => toasts.send(ToastEvent::new(
+                            ToastKind::Error,
+                            "Could not save the game",
+                            error.to_string(),
+                        )),
+                    }
+                }
+                SlotEvent::Load(file) => {
+                    let entries = &slots.slots.entries;
+                    let Some(entry) = entries.iter().find(|entry| entry.file == file) else {
+                        warn!("No save slot {file}");
+                        continue;
+                    };
+                    info!("Loading the save slot {file}");
+                    cmds.insert_resource(ResumeGame(entry.slot.game.clone()));
+                    regenerate.send(BoardRegenerateEvent);
+                    shown.0 = false;
+                }
+                SlotEvent::Duplicate(file) => match Slots::duplicate(&storage, &file) {
+                    Ok(copy) => {
+                        info!("Copied the save slot {file} to {copy}");
+                        changed = true;
+                    }
+                    Err(error) => toasts.send(ToastEvent::new(
+                        ToastKind::Error,
+                        "Could not duplicate the save slot",
+                        error.to_string(),
+                    )),
+                },
+                SlotEvent::Delete(file) => {
+                    let entries = &slots.slots.entries;
+                    let Some(entry) = entries.iter().find(|entry| entry.file == file) else {
+                        continue;
+                    };
+                    confirms.send(ConfirmRequestEvent::new(
+                        ConfirmAction::DeleteSlot(file.clone()),
+                        "Delete this save slot?",
+                        format!(
+                            "The {} game, {:.0}% revealed, cannot be loaded afterwards",
+                            entry.difficulty().name(),
+                            entry.progress * 100.0
+                        ),
+                        "Delete",
+                    ));
+                }
+            }
+        }
+        if changed {
+            Self::rescan(&storage, &mut slots, &mut images);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, ChildBuilder, Color, Commands,
+        DespawnRecursiveExt, Entity, EventWriter, ImageBundle, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::TextStyle,
+    ui::{
+        AlignItems, FlexDirection, Interaction, JustifyContent, PositionType, Size, Style, UiRect,
+    },
+};
+
+use super::{screen_layout::spawn_row, SaveSlots, ShownSlots, SlotsPlugin};
+use crate::{
+    components::{SlotButton, SlotsScreen},
+    events::SlotEvent,
+    insights::DAY,
+    resources::input_map::InputMap,
+    save::format_date,
+};
+
+/// Height of a line of the save slots, room for the thumbnail
+const THUMBNAIL_ROW: f32 = 56.0;
+
+/// Draw the [`ShownSlots`] with bevy_ui, with their thumbnails and buttons
+pub struct SlotsScreenPlugin;
+
+impl Plugin for SlotsScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::sync_screen.after(SlotsPlugin::toggle))
+            .add_system(Self::click);
+    }
+}
+
+impl SlotsScreenPlugin {
+    /// One line per slot, its thumbnail on the left and its buttons on the right, the selected
+    /// one in white
+    fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownSlots>,
+        slots: Res<SaveSlots>,
+        input: Res<InputMap>,
+        screens: Query<Entity, With<SlotsScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() && !slots.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        if !shown.0 {
+            return;
+        }
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let button = |parent: &mut ChildBuilder, change, text: &str| {
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        margin: UiRect::all(Val::Px(2.0)),
+                        padding: UiRect::new(
+                            Val::Px(8.0),
+                            Val::Px(8.0),
+                            Val::Px(2.0),
+                            Val::Px(2.0),
+                        ),
+                        ..Default::default()
+                    },
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.08).into(),
+                    ..Default::default()
+                })
+                .insert(SlotButton(change))
+                .with_children(|button| {
+                    button.spawn_bundle(TextBundle::from_section(text, style(18.0, Color::WHITE)));
+                });
+        };
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.05, 0.05, 0.1, 0.95).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Slots Screen"))
+        .insert(SlotsScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Saves\n",
+                style(40.0, Color::WHITE),
+            ));
+            if slots.slots.entries.is_empty() {
+                parent.spawn_bundle(TextBundle::from_section(
+                    "No saved games yet\n",
+                    style(18.0, Color::GRAY),
+                ));
+            }
+            let entries = slots.slots.entries.iter().zip(&slots.thumbnails);
+            for (i, (entry, (image, size))) in entries.enumerate() {
+                spawn_row(parent, THUMBNAIL_ROW, |row| {
+                    row.spawn_bundle(ImageBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+                            margin: UiRect::all(Val::Px(4.0)),
+                            ..Default::default()
+                        },
+                        image: image.clone().into(),
+                        ..Default::default()
+                    });
+                    let secs = entry.slot.elapsed as u64;
+                    let text = format!(
+                        "{}   {}:{:02}   {:.0}%   {}",
+                        entry.difficulty().name(),
+                        secs / 60,
+                        secs % 60,
+                        entry.progress * 100.0,
+                        format_date(entry.slot.saved / DAY)
+                    );
+                    let color = if i == slots.selected {
+                        Color::WHITE
+                    } else {
+                        Color::GRAY
+                    };
+                    row.spawn_bundle(TextBundle::from_section(text, style(18.0, color)));
+                    let file = &entry.file;
+                    button(row, SlotEvent::Load(file.clone()), "Load");
+                    button(row, SlotEvent::Duplicate(file.clone()), "Duplicate");
+                    button(row, SlotEvent::Delete(file.clone()), "Delete");
+                });
+            }
+            parent.spawn_bundle(TextBundle::from_section("\n", style(18.0, Color::GRAY)));
+            button(parent, SlotEvent::Save, "Save the current game");
+            parent.spawn_bundle(TextBundle::from_section(
+                format!("\n{}\nPress {:?} to close", input.slots.help(), input.saves),
+                style(18.0, Color::GRAY),
+            ));
+        });
+    }
+
+    fn click(
+        buttons: Query<(&Interaction, &SlotButton), Changed<Interaction>>,
+        mut changes: EventWriter<SlotEvent>,
+    ) {
+        for (interaction, SlotButton(change)) in &buttons {
+            if *interaction == Interaction::Clicked {
+                changes.send(change.clone());
+            }
+        }
+    }
+}
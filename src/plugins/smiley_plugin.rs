@@ -0,0 +1,134 @@
+use bevy::{
+    prelude::{
+        BuildChildren, ButtonBundle, Changed, Color, Commands, EventWriter, Input, MouseButton,
+        Name, NodeBundle, ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemSet,
+        Val, With,
+    },
+    ui::{Interaction, JustifyContent, PositionType, Size, Style, UiImage, UiRect},
+    window::Windows,
+};
+
+use super::{BoardPlugin, BoardSystem, GameStatus};
+use crate::{
+    components::SmileyButton,
+    events::PauseActionEvent,
+    resources::{
+        board_assets::BoardAssets, board_options::DisplayParams, input_map::InputMap,
+        smiley::Smiley,
+    },
+};
+
+/// Side of the button, in pixels
+const SMILEY_SIZE: f32 = 40.0;
+
+/// Reset button centered above the board. Its [`Smiley`] face follows the game: worried while a
+/// tile is held down, sunglasses on a win and dead on a loss. Skins may bring their own faces.
+/// A click on it leaves the board like the new board of the pause menu, see `PausePlugin`
+pub struct SmileyPlugin;
+
+impl Plugin for SmileyPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Smiley>()
+            .add_startup_system(Self::spawn_button)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::track)
+                    .with_system(Self::update_face.after(Self::track))
+                    .with_system(Self::reset),
+            );
+    }
+}
+
+impl SmileyPlugin {
+    fn spawn_button(mut cmds: Commands) {
+        // full width row under the counters of the HUD
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(44.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Smiley"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(ButtonBundle {
+                    style: Style {
+                        size: Size::new(Val::Px(SMILEY_SIZE), Val::Px(SMILEY_SIZE)),
+                        ..Default::default()
+                    },
+                    color: Color::WHITE.into(),
+                    ..Default::default()
+                })
+                .insert(Name::new("Smiley Button"))
+                .insert(SmileyButton);
+        });
+    }
+
+    /// Follow the game and the tile held down, the faces are shown by [`Self::update_face`]
+    fn track(
+        status: Res<GameStatus>,
+        buttons: Res<Input<MouseButton>>,
+        input: Res<InputMap>,
+        windows: Res<Windows>,
+        params: Option<Res<DisplayParams>>,
+        mut smiley: ResMut<Smiley>,
+    ) {
+        let mut next = *smiley;
+        if status.is_changed() {
+            match *status {
+                GameStatus::Playing => next.reset(),
+                GameStatus::Won => next.finish(true),
+                GameStatus::Lost => next.finish(false),
+            }
+        }
+        let cursor = BoardPlugin::cursor_position(&windows);
+        let on_tile = params
+            .zip(cursor)
+            .is_some_and(|(params, p)| params.coord_at(p).is_some());
+        if buttons.just_pressed(input.uncover) && on_tile {
+            next.press();
+        }
+        if buttons.just_released(input.uncover) {
+            next.release();
+        }
+        // untouched unless the face changes
+        if next != *smiley {
+            *smiley = next;
+        }
+    }
+
+    fn update_face(
+        smiley: Res<Smiley>,
+        assets: Res<BoardAssets>,
+        mut images: Query<&mut UiImage, With<SmileyButton>>,
+    ) {
+        if !smiley.is_changed() && !assets.is_changed() {
+            return;
+        }
+        for mut image in &mut images {
+            image.0 = assets.faces.get(*smiley).clone();
+        }
+    }
+
+    fn reset(
+        buttons: Query<&Interaction, (Changed<Interaction>, With<SmileyButton>)>,
+        mut new_boards: EventWriter<PauseActionEvent>,
+    ) {
+        if buttons
+            .iter()
+            .any(|interaction| *interaction == Interaction::Clicked)
+        {
+            new_boards.send(PauseActionEvent::NewBoard);
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use bevy::{
+    app::AppExit,
+    prelude::{
+        info, warn, CoreStage, EventReader, EventWriter, ParallelSystemDescriptorCoercion, Plugin,
+        Res,
+    },
+};
+
+use super::AutosavePlugin;
+use crate::{
+    events::ToastEvent,
+    resources::toasts::ToastKind,
+    save::SaveError,
+    storage::Storage,
+    sync::{self, Remote, SyncReport},
+};
+
+/// Remote copy of the profile files, and how syncing them went on launch. Must be inserted
+/// for the [`SyncPlugin`] to do anything
+#[derive(Debug, Clone)]
+pub struct ProfileSync {
+    pub backend: Remote,
+    /// Sync done before the options were loaded
+    pub on_launch: Result<SyncReport, SaveError>,
+}
+
+/// Sync the profile files once more when the game closes, after the last autosave. The
+/// launch sync happens before the app is built, see `sync::sync`
+pub struct SyncPlugin;
+
+impl Plugin for SyncPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(Self::report_launch)
+            .add_system_to_stage(
+                CoreStage::Last,
+                Self::sync_on_exit.after(AutosavePlugin::save_on_exit),
+            );
+    }
+}
+
+impl SyncPlugin {
+    fn log(result: &Result<SyncReport, SaveError>) {
+        match result {
+            Ok(report) => info!("Profile synced: {report}"),
+            Err(error) => warn!("Could not sync the profile: {error}"),
+        }
+    }
+
+    // the log is not set up yet when the launch sync runs
+    fn report_launch(sync: Option<Res<ProfileSync>>, mut toasts: EventWriter<ToastEvent>) {
+        let Some(sync) = sync else {
+            return;
+        };
+        Self::log(&sync.on_launch);
+        if let Err(error) = &sync.on_launch {
+            toasts.send(ToastEvent::new(
+                ToastKind::Error,
+                "Could not sync the profile",
+                error.to_string(),
+            ));
+        }
+    }
+
+    fn sync_on_exit(
+        mut exits: EventReader<AppExit>,
+        sync: Option<Res<ProfileSync>>,
+        storage: Option<Res<Storage>>,
+    ) {
+        if exits.iter().count() == 0 {
+            return;
+        }
+        if let (Some(sync), Some(storage)) = (sync, storage) {
+            Self::log(&sync::sync(&storage, &sync.backend));
+        }
+    }
+}
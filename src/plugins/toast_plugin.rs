@@ -0,0 +1,143 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, ButtonBundle, Changed, Color, Commands, DespawnRecursiveExt,
+        Entity, EventReader, Local, Name, NodeBundle, ParallelSystemDescriptorCoercion, Plugin,
+        Query, Res, ResMut, TextBundle, Val, With,
+    },
+    text::{TextSection, TextStyle},
+    time::Time,
+    ui::{AlignItems, FlexDirection, Interaction, PositionType, Size, Style, UiRect},
+};
+
+use crate::{
+    components::{ToastNode, ToastStack},
+    events::ToastEvent,
+    resources::toasts::{ToastKind, Toasts},
+};
+
+/// Width of a toast
+const TOAST_WIDTH: f32 = 320.0;
+
+/// Queued, timed toasts stacked in the bottom left corner, the oldest at the bottom. Any plugin
+/// sends a `ToastEvent` to show one, and a click dismisses it
+pub struct ToastPlugin;
+
+impl Plugin for ToastPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<ToastEvent>()
+            .init_resource::<Toasts>()
+            .add_system(Self::queue)
+            .add_system(Self::tick.after(Self::queue))
+            .add_system(Self::dismiss.after(Self::tick))
+            .add_system(Self::draw.after(Self::dismiss));
+    }
+}
+
+impl ToastPlugin {
+    fn queue(mut events: EventReader<ToastEvent>, mut toasts: ResMut<Toasts>) {
+        for ToastEvent { kind, title, body } in events.iter() {
+            toasts.push(*kind, title.clone(), body.clone());
+        }
+    }
+
+    fn tick(time: Res<Time>, mut toasts: ResMut<Toasts>) {
+        toasts.tick(time.delta_seconds());
+    }
+
+    fn dismiss(
+        nodes: Query<(&ToastNode, &Interaction), Changed<Interaction>>,
+        mut toasts: ResMut<Toasts>,
+    ) {
+        // one at a time, the indices of the others shift
+        let clicked = nodes
+            .iter()
+            .find(|(_, interaction)| **interaction == Interaction::Clicked);
+        if let Some((ToastNode(idx), _)) = clicked {
+            toasts.dismiss(*idx);
+        }
+    }
+
+    fn color(kind: ToastKind) -> Color {
+        match kind {
+            ToastKind::Info => Color::rgb(0.3, 0.6, 0.9),
+            ToastKind::Success => Color::GOLD,
+            ToastKind::Warning => Color::ORANGE,
+            ToastKind::Error => Color::RED,
+        }
+    }
+
+    /// Rebuild the stack whenever the toasts on screen change
+    fn draw(
+        mut cmds: Commands,
+        toasts: Res<Toasts>,
+        asset_server: Res<AssetServer>,
+        stacks: Query<Entity, With<ToastStack>>,
+        mut drawn: Local<Option<u64>>,
+    ) {
+        if *drawn == Some(toasts.revision()) {
+            return;
+        }
+        *drawn = Some(toasts.revision());
+        stacks
+            .iter()
+            .for_each(|stack| cmds.entity(stack).despawn_recursive());
+        if toasts.shown().is_empty() {
+            return;
+        }
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.0),
+                    bottom: Val::Px(10.0),
+                    ..Default::default()
+                },
+                // bevy_ui columns grow upwards
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::FlexStart,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Toasts"))
+        .insert(ToastStack)
+        .with_children(|parent| {
+            for (idx, toast) in toasts.shown().iter().enumerate() {
+                let mut sections = vec![TextSection::new(
+                    &toast.title,
+                    style(20.0, Self::color(toast.kind)),
+                )];
+                if !toast.body.is_empty() {
+                    sections.push(TextSection::new(
+                        format!("\n{}", toast.body),
+                        style(16.0, Color::WHITE),
+                    ));
+                }
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            size: Size::new(Val::Px(TOAST_WIDTH), Val::Auto),
+                            margin: UiRect::all(Val::Px(4.0)),
+                            padding: UiRect::all(Val::Px(8.0)),
+                            ..Default::default()
+                        },
+                        color: Color::rgba(0.05, 0.05, 0.1, 0.9).into(),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("Toast"))
+                    .insert(ToastNode(idx))
+                    .with_children(|toast| {
+                        toast.spawn_bundle(TextBundle::from_sections(sections));
+                    });
+            }
+        });
+    }
+}
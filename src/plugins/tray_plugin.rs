@@ -0,0 +1,118 @@
+use std::sync::{
+    mpsc::{self, Receiver},
+    Mutex,
+};
+
+use bevy::{
+    prelude::{info, warn, EventWriter, IntoExclusiveSystem, NonSend, Plugin, Res, World},
+    window::Windows,
+    winit::WinitWindows,
+};
+use tray_item::{IconSource, TrayItem};
+
+use super::ShownInsights;
+use crate::{
+    events::{InsightsToggleEvent, PauseActionEvent},
+    resources::board_options::Difficulty,
+};
+
+/// Icon of the tray, a resource of the executable
+const TRAY_ICON: &str = "minesweeper";
+
+/// Entry of the tray menu, picked on the thread of the tray
+#[derive(Debug, Clone)]
+enum TrayAction {
+    NewGame(Difficulty),
+    ShowStats,
+}
+
+/// Entries picked in the tray menu since the last frame
+#[derive(Debug)]
+struct TrayMenu {
+    actions: Mutex<Receiver<TrayAction>>,
+}
+
+/// Icon in the system tray, for players who keep the game running in the background. Its menu
+/// starts a new game of a preset or shows the insights, raising the window first. New games ask
+/// for a confirmation like the other new game buttons
+pub struct TrayPlugin;
+
+impl Plugin for TrayPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        // the icon is a non-send resource, inserted with the world at hand
+        app.add_startup_system(Self::start.exclusive_system())
+            .add_system(Self::act);
+    }
+}
+
+impl TrayPlugin {
+    /// Create the icon, kept on the main thread, where some platforms need it
+    fn start(world: &mut World) {
+        let (sender, actions) = mpsc::channel();
+        let entries = [
+            ("New Easy game", TrayAction::NewGame(Difficulty::EASY)),
+            ("New Medium game", TrayAction::NewGame(Difficulty::MEDIUM)),
+            ("New Expert game", TrayAction::NewGame(Difficulty::EXPERT)),
+            ("Show stats", TrayAction::ShowStats),
+        ];
+
+        let tray =
+            TrayItem::new("Minesweeper", IconSource::Resource(TRAY_ICON)).and_then(|mut tray| {
+                for (label, action) in entries {
+                    let sender = Mutex::new(sender.clone());
+                    tray.add_menu_item(label, move || {
+                        // the game may be gone, nothing left to do then
+                        let _ = sender.lock().unwrap().send(action.clone());
+                    })?;
+                }
+                Ok(tray)
+            });
+        match tray {
+            Ok(tray) => {
+                info!("Tray icon added");
+                world.insert_non_send_resource(tray);
+                world.insert_resource(TrayMenu {
+                    actions: Mutex::new(actions),
+                });
+            }
+            Err(error) => warn!("No tray icon: {error}"),
+        }
+    }
+
+    /// Raise the window and send the events of the entries picked
+    fn act(
+        menu: Option<Res<TrayMenu>>,
+        insights: Res<ShownInsights>,
+        windows: Res<Windows>,
+        winit_windows: NonSend<WinitWindows>,
+        mut actions: EventWriter<PauseActionEvent>,
+        mut toggles: EventWriter<InsightsToggleEvent>,
+    ) {
+        let Some(menu) = menu else {
+            return;
+        };
+        let picked = menu.actions.lock().unwrap().try_iter().collect::<Vec<_>>();
+        if picked.is_empty() {
+            return;
+        }
+
+        let window = windows
+            .get_primary()
+            .and_then(|window| winit_windows.get_window(window.id()));
+        if let Some(window) = window {
+            window.set_visible(true);
+            window.set_minimized(false);
+            window.focus_window();
+        }
+        for action in picked {
+            match action {
+                TrayAction::NewGame(difficulty) => {
+                    actions.send(PauseActionEvent::Difficulty(difficulty));
+                }
+                // already shown, a toggle would close them
+                TrayAction::ShowStats if insights.0.is_some() => {}
+                TrayAction::ShowStats => toggles.send(InsightsToggleEvent),
+            }
+        }
+    }
+}
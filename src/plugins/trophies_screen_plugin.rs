@@ -0,0 +1,85 @@
+use bevy::{
+    prelude::{
+        AssetServer, BuildChildren, Color, Commands, DespawnRecursiveExt, Entity, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, TextBundle, Val, With,
+    },
+    text::{TextSection, TextStyle},
+    ui::{AlignItems, JustifyContent, PositionType, Size, Style},
+};
+
+use super::{Achievements, AchievementsPlugin, ShownTrophies};
+use crate::components::TrophiesScreen;
+
+/// Draw the [`ShownTrophies`] with bevy_ui, the unlocked ones in gold
+pub struct TrophiesScreenPlugin;
+
+impl Plugin for TrophiesScreenPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system(Self::sync_screen.after(AchievementsPlugin::toggle));
+    }
+}
+
+impl TrophiesScreenPlugin {
+    fn sync_screen(
+        mut cmds: Commands,
+        shown: Res<ShownTrophies>,
+        achievements: Res<Achievements>,
+        screens: Query<Entity, With<TrophiesScreen>>,
+        asset_server: Res<AssetServer>,
+    ) {
+        if !shown.is_changed() && !achievements.is_changed() {
+            return;
+        }
+        screens
+            .iter()
+            .for_each(|screen| cmds.entity(screen).despawn_recursive());
+        if !shown.0 {
+            return;
+        }
+
+        let font = asset_server.load("fonts/robotoslab.ttf");
+        let style = |font_size, color| TextStyle {
+            font: font.clone(),
+            font_size,
+            color,
+        };
+        let unlocked = &achievements.unlocked.0;
+        let mut sections = vec![TextSection::new("Trophies\n\n", style(40.0, Color::WHITE))];
+        for achievement in &achievements.definitions {
+            let color = if unlocked.contains_key(&achievement.id) {
+                Color::GOLD
+            } else {
+                Color::DARK_GRAY
+            };
+            sections.push(TextSection::new(
+                format!("{}\n", achievement.name),
+                style(22.0, color),
+            ));
+            sections.push(TextSection::new(
+                format!("{}\n\n", achievement.description),
+                style(16.0, Color::GRAY),
+            ));
+        }
+        sections.push(TextSection::new(
+            "Press T to close",
+            style(18.0, Color::GRAY),
+        ));
+
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..Default::default()
+            },
+            color: Color::rgba(0.05, 0.05, 0.1, 0.95).into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Trophies Screen"))
+        .insert(TrophiesScreen)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_sections(sections));
+        });
+    }
+}
@@ -0,0 +1,156 @@
+use bevy::prelude::{
+    EventReader, EventWriter, Input, KeyCode, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut,
+};
+
+use super::{BoardSystem, GameStatus};
+use crate::{
+    events::{TileMarkEvent, TileTriggerEvent, UndoEvent},
+    resources::{
+        board_state::{BoardState, Cover},
+        input_map::InputMap,
+        rule_set::RuleSet,
+        uncover_queue::UncoverQueue,
+    },
+    solver::ConstraintGraph,
+};
+
+/// A move to take back: the tiles it covered differently, with their covers before it
+#[derive(Debug, Clone)]
+struct Move {
+    covers: Vec<(usize, Cover)>,
+    status: GameStatus,
+}
+
+/// Moves of the current game, the last one on top. Each keeps only the covers it changed, found
+/// by comparing the board before it with the board before the next one or before the undo
+#[derive(Debug, Default)]
+pub struct UndoHistory {
+    moves: Vec<Move>,
+    // covers before the last move, not yet compared with the board after it
+    before: Option<Vec<Cover>>,
+}
+
+impl UndoHistory {
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Forget the moves, for boards changed in ways a move cannot take back
+    pub fn clear(&mut self) {
+        self.moves.clear();
+        self.before = None;
+    }
+
+    /// Record a move about to be applied to `state`
+    fn push(&mut self, state: &BoardState, status: GameStatus) {
+        self.settle(state);
+        self.before = Some(state.covers().to_vec());
+        self.moves.push(Move {
+            covers: Vec::new(),
+            status,
+        });
+    }
+
+    /// The last move, applied to `state` by now
+    fn pop(&mut self, state: &BoardState) -> Option<Move> {
+        self.settle(state);
+        self.moves.pop()
+    }
+
+    /// Keep the covers the last move changed on `state`, and drop the full copy taken before it
+    fn settle(&mut self, state: &BoardState) {
+        let (Some(before), Some(last)) = (self.before.take(), self.moves.last_mut()) else {
+            return;
+        };
+        last.covers = before
+            .into_iter()
+            .zip(state.covers())
+            .enumerate()
+            .filter(|(_, (before, now))| before != *now)
+            .map(|(idx, (before, _))| (idx, before))
+            .collect();
+    }
+}
+
+/// Take back moves one by one with `InputMap::undo`, `Backspace` out of the box, lost games
+/// included. Only when the `RuleSet` allows it, as in Zen mode, where the history has no limit
+pub struct UndoPlugin;
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<UndoEvent>()
+            .init_resource::<UndoHistory>()
+            .add_system(Self::undo_key.before(Self::undo))
+            .add_system(Self::undo.before(BoardSystem::Input))
+            .add_system(
+                Self::snapshot
+                    .after(BoardSystem::Input)
+                    .before(BoardSystem::Uncover),
+            );
+    }
+}
+
+impl UndoPlugin {
+    fn undo_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut undos: EventWriter<UndoEvent>,
+    ) {
+        if keys.just_pressed(input.undo) {
+            undos.send(UndoEvent);
+        }
+    }
+
+    /// Note the covers of the frame a move is made, before it is applied
+    fn snapshot(
+        rules: Res<RuleSet>,
+        status: Res<GameStatus>,
+        state: Option<Res<BoardState>>,
+        mut history: ResMut<UndoHistory>,
+        mut triggers: EventReader<TileTriggerEvent>,
+        mut marks: EventReader<TileMarkEvent>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+        if state.is_added() {
+            history.clear();
+        }
+
+        let moves = triggers.iter().count() + marks.iter().count();
+        if moves == 0 || !rules.undo || *status != GameStatus::Playing {
+            return;
+        }
+        history.push(&state, *status);
+    }
+
+    fn undo(
+        mut undos: EventReader<UndoEvent>,
+        mut history: ResMut<UndoHistory>,
+        mut status: ResMut<GameStatus>,
+        mut queue: ResMut<UncoverQueue>,
+        state: Option<ResMut<BoardState>>,
+        graph: Option<ResMut<ConstraintGraph>>,
+    ) {
+        let count = undos.iter().count();
+        let (Some(mut state), Some(mut graph)) = (state, graph) else {
+            return;
+        };
+
+        let mut undone = false;
+        for _ in 0..count {
+            let Some(last) = history.pop(&state) else {
+                break;
+            };
+            // a cascade still running belongs to the move taken back
+            queue.clear();
+            state.restore_covers(&last.covers);
+            *status = last.status;
+            undone = true;
+        }
+        if undone {
+            let rebuilt = graph.rebuilt(&state);
+            *graph = rebuilt;
+        }
+    }
+}
@@ -0,0 +1,182 @@
+use bevy::{
+    prelude::{
+        info, AssetServer, BuildChildren, Color, Commands, EventReader, Local, Name, NodeBundle,
+        ParallelSystemDescriptorCoercion, Plugin, Query, Res, ResMut, SystemSet, TextBundle, Val,
+        Visibility, With,
+    },
+    text::{Text, TextStyle},
+    ui::{JustifyContent, PositionType, Size, Style, UiRect},
+};
+
+use super::{BoardSystem, GameStatus, InsightsPlugin};
+use crate::{
+    components::VersusText,
+    events::{MineTriggerEvent, TileTriggerEvent},
+    insights,
+    resources::{
+        board_options::BoardOptions,
+        board_state::{BoardState, Cover},
+        rule_set::RuleSet,
+        versus::Versus,
+    },
+    save::{Outcome, Ratings, INITIAL_RATING, RATING_FILE},
+    storage::Storage,
+};
+
+/// Hot-seat versus: players alternate single moves, score the safe tiles they reveal and lose
+/// points on mines, when the `RuleSet` says so. The best score wins once the board is cleared,
+/// and the game changes the rating of the first player
+pub struct VersusPlugin;
+
+impl Plugin for VersusPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Versus>()
+            .add_startup_system(Self::spawn_panel)
+            .add_system(
+                Self::take_turns
+                    .after(BoardSystem::Input)
+                    .before(BoardSystem::Uncover),
+            )
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::score)
+                    .with_system(Self::update_panel.after(Self::score))
+                    .with_system(Self::rate.after(Self::score)),
+            );
+    }
+}
+
+impl VersusPlugin {
+    fn spawn_panel(mut cmds: Commands, asset_server: Res<AssetServer>) {
+        cmds.spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    ..Default::default()
+                },
+                justify_content: JustifyContent::Center,
+                ..Default::default()
+            },
+            color: Color::NONE.into(),
+            ..Default::default()
+        })
+        .insert(Name::new("Versus"))
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/robotoslab.ttf"),
+                        font_size: 24.0,
+                        color: Color::WHITE,
+                    },
+                ))
+                .insert(VersusText);
+        });
+    }
+
+    /// Every click on a covered tile is the move of the player whose turn it is
+    pub fn take_turns(
+        rules: Res<RuleSet>,
+        state: Option<Res<BoardState>>,
+        mut versus: ResMut<Versus>,
+        mut triggers: EventReader<TileTriggerEvent>,
+    ) {
+        let (true, Some(state)) = (rules.versus, state) else {
+            return;
+        };
+        for trigger in triggers.iter() {
+            if matches!(
+                state.cover(trigger.coord),
+                Some(Cover::Covered | Cover::Questioned)
+            ) {
+                versus.take_turn();
+            }
+        }
+    }
+
+    fn score(
+        rules: Res<RuleSet>,
+        state: Option<Res<BoardState>>,
+        mut versus: ResMut<Versus>,
+        // safe tiles revealed at the last frame
+        mut revealed: Local<usize>,
+        mut mines: EventReader<MineTriggerEvent>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+        if state.is_added() {
+            *versus = Versus::default();
+            *revealed = state.n_revealed();
+        }
+        if !rules.versus {
+            return;
+        }
+
+        versus.reveal(state.n_revealed() - *revealed);
+        *revealed = state.n_revealed();
+        mines.iter().for_each(|_| versus.hit_mine());
+    }
+
+    fn update_panel(
+        rules: Res<RuleSet>,
+        versus: Res<Versus>,
+        status: Res<GameStatus>,
+        mut panel: Query<(&mut Text, &mut Visibility), With<VersusText>>,
+    ) {
+        if !rules.is_changed() && !versus.is_changed() && !status.is_changed() {
+            return;
+        }
+
+        let scores = versus
+            .scores()
+            .iter()
+            .enumerate()
+            .map(|(player, score)| format!("Player {}: {score}", player + 1))
+            .collect::<Vec<_>>()
+            .join("    ");
+        let outcome = match (*status, versus.leader()) {
+            (GameStatus::Playing, _) => format!("Player {} to move", versus.turn() + 1),
+            (_, Some(leader)) => format!("Player {} wins", leader + 1),
+            (_, None) => "Draw".to_owned(),
+        };
+        for (mut text, mut visibility) in &mut panel {
+            visibility.is_visible = rules.versus;
+            text.sections[0].value = format!("{scores}    {outcome}");
+        }
+    }
+
+    /// Rate the finished game for the first player, against the bot or an unrated human
+    fn rate(
+        rules: Res<RuleSet>,
+        status: Res<GameStatus>,
+        versus: Res<Versus>,
+        options: Option<Res<BoardOptions>>,
+        storage: Option<Res<Storage>>,
+    ) {
+        if !status.is_changed() || *status == GameStatus::Playing || !rules.versus {
+            return;
+        }
+        let Some(storage) = storage else {
+            return;
+        };
+
+        let opponent = options
+            .and_then(|options| options.bot)
+            .map_or(INITIAL_RATING, Ratings::bot_rating);
+        let outcome = match versus.leader() {
+            Some(0) => Outcome::Win,
+            Some(_) => Outcome::Loss,
+            None => Outcome::Draw,
+        };
+        InsightsPlugin::update(&storage, RATING_FILE, |ratings: &mut Ratings| {
+            let change = ratings.record(opponent, outcome, insights::unix_time());
+            info!("Rating {} ({change:+})", ratings.rating);
+        });
+    }
+}
@@ -0,0 +1,232 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+    thread,
+};
+
+use bevy::prelude::{
+    info, warn, Commands, EventWriter, ParallelSystemDescriptorCoercion, Plugin, Res,
+};
+use cpal::{
+    traits::{DeviceTrait, HostTrait, StreamTrait},
+    InputCallbackInfo, SampleFormat, Stream, StreamError,
+};
+use vosk::{DecodingState, Model, Recognizer};
+
+use super::{BoardSystem, GameStatus};
+use crate::{
+    events::{TileMarkEvent, TileTriggerEvent, ToastEvent},
+    resources::{
+        board::coord_name,
+        board_state::BoardState,
+        board_transition::BoardTransition,
+        input_capture::InputCapture,
+        rule_set::{Player, Roles, RuleSet},
+        toasts::ToastKind,
+    },
+    save::Action,
+    storage::{Location, Storage},
+    voice::{self, VoiceCommand},
+};
+
+/// Environment variable pointing at the directory of a Vosk model, in place of [`MODEL_DIR`]
+pub const MODEL_VAR: &str = "MINESWEEPER_VOICE_MODEL";
+
+/// Directory of the Vosk model, in the data directory shared by the profiles
+const MODEL_DIR: &str = "voice-model";
+
+/// What the thread of the recognizer heard, or why it stopped
+type Heard = Result<String, String>;
+
+/// Phrases recognized since the last frame
+#[derive(Debug)]
+struct VoiceInput {
+    heard: Mutex<Receiver<Heard>>,
+}
+
+/// Experimental: reveal and flag tiles by speaking their names, like `reveal C7` or
+/// `flag delta two`, for players who cannot use a mouse and for streams. Speech is recognized
+/// offline by a Vosk model, from [`MODEL_VAR`] or else `voice-model` in the data directory, on a
+/// thread of its own. Commands send the events of the clicks, kept away from the board as the
+/// clicks are
+pub struct VoicePlugin;
+
+impl Plugin for VoicePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_startup_system(Self::start).add_system(
+            Self::act
+                .after(BoardSystem::Input)
+                .before(BoardSystem::Uncover),
+        );
+    }
+}
+
+impl VoicePlugin {
+    /// Listen to the default microphone, on a thread keeping the audio stream
+    fn start(mut cmds: Commands, storage: Option<Res<Storage>>) {
+        let from_env = std::env::var_os(MODEL_VAR).filter(|dir| !dir.is_empty());
+        let model = from_env.map(PathBuf::from).or_else(|| {
+            let data = storage?.shared().dir(Location::Data)?;
+            Some(data.join(MODEL_DIR))
+        });
+        let Some(model) = model else {
+            warn!("No voice commands: no directory for the speech model");
+            return;
+        };
+
+        let (sender, heard) = mpsc::channel();
+        cmds.insert_resource(VoiceInput {
+            heard: Mutex::new(heard),
+        });
+        thread::spawn(move || match Self::listen(&model, sender.clone()) {
+            Ok(_stream) => {
+                info!("Listening for voice commands");
+                // the stream stops when dropped
+                loop {
+                    thread::park();
+                }
+            }
+            Err(error) => {
+                let _ = sender.send(Err(error));
+            }
+        });
+    }
+
+    /// Feed the microphone to a recognizer knowing the words of the commands alone
+    fn listen(model: &Path, sender: Sender<Heard>) -> Result<Stream, String> {
+        let model = Model::new(model.to_string_lossy())
+            .ok_or_else(|| format!("no speech model in {}", model.display()))?;
+        let device = cpal::default_host()
+            .default_input_device()
+            .ok_or("no microphone")?;
+        let config = device
+            .default_input_config()
+            .map_err(|error| error.to_string())?;
+        let channels = usize::from(config.channels());
+        let mut recognizer = Recognizer::new_with_grammar(
+            &model,
+            config.sample_rate().0 as f32,
+            &voice::vocabulary(),
+        )
+        .ok_or("the speech recognizer failed to start")?;
+
+        let mut hear = move |samples: Vec<i16>| {
+            if !matches!(
+                recognizer.accept_waveform(&samples),
+                DecodingState::Finalized
+            ) {
+                return;
+            }
+            let text = recognizer
+                .result()
+                .single()
+                .map(|result| result.text.to_owned());
+            if let Some(text) = text.filter(|text| !text.is_empty()) {
+                // the game may be gone, nothing left to do then
+                let _ = sender.send(Ok(text));
+            }
+        };
+        let failed = |error: StreamError| warn!("Voice input failed: {error}");
+        let stream_config = config.config();
+        let stream = match config.sample_format() {
+            SampleFormat::I16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[i16], _: &InputCallbackInfo| hear(mono(data, channels, |s| s)),
+                failed,
+            ),
+            SampleFormat::U16 => device.build_input_stream(
+                &stream_config,
+                move |data: &[u16], _: &InputCallbackInfo| {
+                    hear(mono(data, channels, |s| (i32::from(s) - 32768) as i16))
+                },
+                failed,
+            ),
+            SampleFormat::F32 => device.build_input_stream(
+                &stream_config,
+                move |data: &[f32], _: &InputCallbackInfo| {
+                    hear(mono(data, channels, |s| (s * f32::from(i16::MAX)) as i16))
+                },
+                failed,
+            ),
+        }
+        .map_err(|error| error.to_string())?;
+        stream.play().map_err(|error| error.to_string())?;
+        Ok(stream)
+    }
+
+    /// Send the events of the commands heard, when a click would be taken
+    #[allow(clippy::too_many_arguments)]
+    fn act(
+        input: Option<Res<VoiceInput>>,
+        rules: Res<RuleSet>,
+        roles: Res<Roles>,
+        (status, transition, capture): (Res<GameStatus>, Res<BoardTransition>, Res<InputCapture>),
+        state: Option<Res<BoardState>>,
+        mut triggers: EventWriter<TileTriggerEvent>,
+        mut marks: EventWriter<TileMarkEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let Some(input) = input else {
+            return;
+        };
+        // heard while the board is away, they are dropped rather than played later
+        let heard = input.heard.lock().unwrap().try_iter().collect::<Vec<_>>();
+        let blocked =
+            *status != GameStatus::Playing || !transition.is_idle() || capture.is_captured();
+        let role = roles.get(Player::Local);
+
+        for heard in heard {
+            let phrase = match heard {
+                Ok(phrase) => phrase,
+                Err(error) => {
+                    warn!("No voice commands: {error}");
+                    toasts.send(ToastEvent::new(
+                        ToastKind::Warning,
+                        "Voice commands off",
+                        error,
+                    ));
+                    continue;
+                }
+            };
+            let Some(command) = voice::parse(&phrase) else {
+                info!("Not a voice command: {phrase}");
+                continue;
+            };
+            if blocked {
+                continue;
+            }
+            let coord = command.coord();
+            if !state
+                .as_ref()
+                .is_some_and(|state| state.cover(coord).is_some())
+            {
+                toasts.send(ToastEvent::new(
+                    ToastKind::Info,
+                    "Voice command",
+                    format!("No tile {} on this board", coord_name(coord)),
+                ));
+                continue;
+            }
+            info!("Voice command: {phrase}");
+            match command {
+                VoiceCommand::Reveal(coord) if rules.allows(role, Action::Uncover(coord)) => {
+                    triggers.send(TileTriggerEvent { coord });
+                }
+                VoiceCommand::Flag(coord) if rules.allows(role, Action::Flag(coord)) => {
+                    marks.send(TileMarkEvent { coord });
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// First channel of interleaved `data`, as the 16-bit samples Vosk takes
+fn mono<T: Copy>(data: &[T], channels: usize, sample: impl Fn(T) -> i16) -> Vec<i16> {
+    data.chunks(channels)
+        .map(|frame| sample(frame[0]))
+        .collect()
+}
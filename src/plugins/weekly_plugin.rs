@@ -0,0 +1,147 @@
+use bevy::prelude::{
+    info, warn, Commands, EventReader, EventWriter, Input, KeyCode,
+    ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, SystemSet,
+};
+
+use super::{BoardPlugin, BoardSystem, InsightsPlugin};
+use crate::{
+    events::{BoardRegenerateEvent, ToastEvent, WeeklyPlayEvent},
+    insights::{self, DAY},
+    resources::{
+        board_seed::{BoardSeed, SeededBoard},
+        board_state::BoardState,
+        input_map::InputMap,
+        toasts::ToastKind,
+    },
+    save::{PuzzleRotation, WeeklyPuzzle},
+    storage::Storage,
+};
+
+/// Curated puzzles, one per week
+#[derive(Debug, Default, Clone)]
+pub struct WeeklyPuzzles {
+    pub rotation: PuzzleRotation,
+}
+
+impl WeeklyPuzzles {
+    /// Puzzle of the current week, `None` once the rotation has run out
+    pub fn current(&self) -> Option<&WeeklyPuzzle> {
+        self.rotation.current(insights::unix_time() / DAY)
+    }
+}
+
+/// Week of the puzzle on screen, `None` for any other board. Games of the puzzle of the week
+/// are recorded in a category of their own
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ActiveWeekly(pub Option<String>);
+
+/// Puzzle of the week: the same board for every player for a week, from the rotation of
+/// `assets/weekly.ron` or a newer one downloaded in the cache directory. Press `W`, or the key
+/// of the `InputMap`, to play it
+pub struct WeeklyPlugin;
+
+impl Plugin for WeeklyPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_event::<WeeklyPlayEvent>()
+            .init_resource::<WeeklyPuzzles>()
+            .init_resource::<ActiveWeekly>()
+            .add_startup_system(Self::load)
+            .add_system(Self::weekly_key.before(Self::play))
+            // the board is rebuilt next frame, once the `SeededBoard` is inserted
+            .add_system(Self::play.after(BoardPlugin::regenerate_board))
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::track.before(InsightsPlugin::record)),
+            );
+    }
+}
+
+impl WeeklyPlugin {
+    fn load(
+        storage: Option<Res<Storage>>,
+        input: Res<InputMap>,
+        mut weekly: ResMut<WeeklyPuzzles>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        let (rotation, error) = PuzzleRotation::load(storage.as_deref());
+        if let Some(error) = error {
+            warn!(
+                "Using the bundled puzzles of the week, the downloaded ones are invalid: {error}"
+            );
+        }
+        weekly.rotation = rotation;
+
+        if let Some(puzzle) = weekly.current() {
+            info!("Puzzle of the week {}", puzzle.week);
+            toasts.send(ToastEvent::new(
+                ToastKind::Info,
+                "Puzzle of the week",
+                format!(
+                    "{} {:?}, press {:?} to play",
+                    puzzle.difficulty().name(),
+                    puzzle.mode,
+                    input.weekly
+                ),
+            ));
+        }
+    }
+
+    fn weekly_key(
+        keys: Res<Input<KeyCode>>,
+        input: Res<InputMap>,
+        mut plays: EventWriter<WeeklyPlayEvent>,
+    ) {
+        if keys.just_pressed(input.weekly) {
+            plays.send(WeeklyPlayEvent);
+        }
+    }
+
+    fn play(
+        mut cmds: Commands,
+        mut plays: EventReader<WeeklyPlayEvent>,
+        weekly: Res<WeeklyPuzzles>,
+        mut regenerate: EventWriter<BoardRegenerateEvent>,
+        mut toasts: EventWriter<ToastEvent>,
+    ) {
+        if plays.iter().count() == 0 {
+            return;
+        }
+        let Some(puzzle) = weekly.current() else {
+            toasts.send(ToastEvent::new(
+                ToastKind::Warning,
+                "No puzzle this week",
+                "The puzzles of the week have run out, update the game for new ones",
+            ));
+            return;
+        };
+
+        cmds.insert_resource(SeededBoard {
+            seed: puzzle.seed,
+            mode: puzzle.mode,
+            difficulty: puzzle.difficulty(),
+        });
+        regenerate.send(BoardRegenerateEvent);
+    }
+
+    /// Tell whether the new board is a puzzle of the week, from its seed
+    fn track(
+        state: Option<Res<BoardState>>,
+        seed: Option<Res<BoardSeed>>,
+        weekly: Res<WeeklyPuzzles>,
+        mut active: ResMut<ActiveWeekly>,
+    ) {
+        if !state.is_some_and(|state| state.is_added()) {
+            return;
+        }
+        let week = seed.and_then(|seed| {
+            let puzzles = &weekly.rotation.puzzles;
+            let puzzle = puzzles.iter().find(|puzzle| puzzle.seed == seed.0)?;
+            Some(puzzle.week.clone())
+        });
+        if active.0 != week {
+            active.0 = week;
+        }
+    }
+}
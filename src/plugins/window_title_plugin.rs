@@ -0,0 +1,122 @@
+use bevy::{
+    prelude::{Local, ParallelSystemDescriptorCoercion, Plugin, Res, ResMut, SystemSet},
+    window::Windows,
+};
+
+use super::{ActiveDaily, BoardSystem, DailyPlugin, GameStatus, InsightsPlugin, SessionTracker};
+use crate::resources::{board_options::Difficulty, board_state::BoardState, game_clock::GameClock};
+
+/// Name of the game, first in the title
+const GAME_TITLE: &str = "Minesweeper";
+
+/// Keep the difficulty, the time played and the mines left in the window title, so that they
+/// show in the taskbar and the window switcher. With the `platform` feature, the taskbar button
+/// fills up with the safe tiles revealed as well, on Windows
+pub struct WindowTitlePlugin;
+
+impl Plugin for WindowTitlePlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(
+                    Self::update_title
+                        .after(InsightsPlugin::track)
+                        .after(DailyPlugin::track),
+                ),
+        );
+        #[cfg(feature = "platform")]
+        app.add_system_set(
+            SystemSet::new()
+                .label(BoardSystem::Hud)
+                .after(BoardSystem::Rules)
+                .with_system(Self::update_taskbar),
+        );
+    }
+}
+
+impl WindowTitlePlugin {
+    /// `Minesweeper — Expert — 03:12 — 34 mines left`, the clock stopped once the game is over.
+    /// Warm-ups for the daily challenge say so after the difficulty
+    fn update_title(
+        clock: Res<GameClock>,
+        status: Res<GameStatus>,
+        tracker: Res<SessionTracker>,
+        daily: Res<ActiveDaily>,
+        state: Option<Res<BoardState>>,
+        mut windows: ResMut<Windows>,
+        // seconds played when the game ended
+        mut ended: Local<Option<u64>>,
+    ) {
+        let Some(state) = state else {
+            return;
+        };
+        let Some(window) = windows.get_primary_mut() else {
+            return;
+        };
+
+        let played = |started: f64| (clock.elapsed_seconds() - started).max(0.0) as u64;
+        let secs = match *status {
+            GameStatus::Playing => {
+                *ended = None;
+                tracker.started.map_or(0, played)
+            }
+            _ => *ended.get_or_insert_with(|| tracker.started.map_or(0, played)),
+        };
+        let difficulty = Difficulty {
+            dim: state.dim().as_uvec2(),
+            n_mines: state.n_mines() as u32,
+        };
+        let outcome = match *status {
+            GameStatus::Playing => {
+                let left = state.n_mines() as i64 - state.n_flags() as i64;
+                format!("{left} mines left")
+            }
+            GameStatus::Won => "Cleared".to_owned(),
+            GameStatus::Lost => "Lost".to_owned(),
+        };
+        let practice = match &daily.0 {
+            Some(board) if board.is_warm_up() => " (warm-up)",
+            _ => "",
+        };
+        let title = format!(
+            "{GAME_TITLE} — {}{practice} — {:02}:{:02} — {outcome}",
+            difficulty.name(),
+            secs / 60,
+            secs % 60
+        );
+        // setting it queues a change for the window, even to the same title
+        if window.title() != title {
+            window.set_title(title);
+        }
+    }
+
+    /// Safe tiles revealed on the taskbar button, in red once a mine went off
+    #[cfg(feature = "platform")]
+    fn update_taskbar(
+        status: Res<GameStatus>,
+        state: Option<Res<BoardState>>,
+        windows: Res<Windows>,
+        winit_windows: bevy::prelude::NonSend<bevy::winit::WinitWindows>,
+        mut shown: Local<Option<crate::platform::TaskbarProgress>>,
+    ) {
+        use crate::platform::{self, TaskbarProgress};
+
+        let progress = match (state, *status) {
+            (None, _) | (Some(_), GameStatus::Won) => TaskbarProgress::Hidden,
+            (Some(state), GameStatus::Playing) => TaskbarProgress::Normal(state.progress()),
+            (Some(state), GameStatus::Lost) => TaskbarProgress::Error(state.progress()),
+        };
+        if *shown == Some(progress) {
+            return;
+        }
+        let window = windows
+            .get_primary()
+            .and_then(|window| winit_windows.get_window(window.id()));
+        if let Some(window) = window {
+            platform::set_taskbar_progress(window, progress);
+            *shown = Some(progress);
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use bevy::{
+    prelude::{
+        info, BuildChildren, Color, Commands, DespawnRecursiveExt, Entity, Input, KeyCode, Name,
+        Plugin, Query, Res, ResMut, SpriteBundle, SystemSet, Transform, Vec2, Vec3, With,
+    },
+    sprite::Sprite,
+};
+
+use super::BoardSystem;
+use crate::{
+    components::{BoardCoordinate, Mine, XrayMarker},
+    resources::{
+        board_assets::BoardAssets,
+        board_options::{BoardOptions, DisplayParams},
+        board_state::{BoardState, Cover},
+        input_map::InputMap,
+    },
+};
+
+/// Tint of the markers, faint enough to read flags and question marks through
+const MARKER_COLOR: Color = Color::rgba(1.0, 0.2, 0.2, 0.45);
+
+/// Whether the markers are shown
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Xray(bool);
+
+/// Debug only: faint mines over the covered mines of the board, toggled with `InputMap::xray`
+/// (`F4`), to see the solution while working on interactions. Drawn on this screen alone, nothing
+/// leaves the game
+pub struct XrayPlugin;
+
+impl Plugin for XrayPlugin {
+    fn build(&self, app: &mut bevy::prelude::App) {
+        app.init_resource::<Xray>()
+            .add_system(Self::toggle)
+            .add_system_set(
+                SystemSet::new()
+                    .label(BoardSystem::Hud)
+                    .after(BoardSystem::Rules)
+                    .with_system(Self::update_markers),
+            );
+    }
+}
+
+impl XrayPlugin {
+    fn toggle(keys: Res<Input<KeyCode>>, input: Res<InputMap>, mut xray: ResMut<Xray>) {
+        if keys.just_pressed(input.xray) {
+            xray.0 = !xray.0;
+            info!("X-ray {}", if xray.0 { "on" } else { "off" });
+        }
+    }
+
+    /// Put a marker on every mine still covered, from scratch whenever the board changes
+    #[allow(clippy::too_many_arguments)]
+    fn update_markers(
+        mut cmds: Commands,
+        xray: Res<Xray>,
+        assets: Res<BoardAssets>,
+        board_options: Option<Res<BoardOptions>>,
+        params: Option<Res<DisplayParams>>,
+        state: Option<Res<BoardState>>,
+        mines: Query<(Entity, &BoardCoordinate), With<Mine>>,
+        markers: Query<Entity, With<XrayMarker>>,
+    ) {
+        let changed = xray.is_changed()
+            || state.as_ref().is_some_and(|state| state.is_changed())
+            || params.as_ref().is_some_and(|params| params.is_changed());
+        if !changed {
+            return;
+        }
+
+        markers
+            .iter()
+            .for_each(|marker| cmds.entity(marker).despawn_recursive());
+        let (true, Some(state), Some(params)) = (xray.0, state, params) else {
+            return;
+        };
+
+        let padding = board_options.map_or(0.0, |options| options.tile_padding);
+        let size = Vec2::splat(params.tile_size - padding);
+        for (tile, coord) in &mines {
+            if state.cover(coord.inner) == Some(Cover::Revealed) {
+                continue;
+            }
+            cmds.entity(tile).with_children(|parent| {
+                parent
+                    .spawn_bundle(SpriteBundle {
+                        sprite: Sprite {
+                            color: MARKER_COLOR,
+                            custom_size: Some(size),
+                            ..Default::default()
+                        },
+                        // above the cover and its flag, under the last move outline
+                        transform: Transform::from_translation(Vec3::Z * 3.5),
+                        texture: assets.mine.clone(),
+                        ..Default::default()
+                    })
+                    .insert(Name::new("X-ray"))
+                    .insert(XrayMarker);
+            });
+        }
+    }
+}
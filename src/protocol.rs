@@ -0,0 +1,552 @@
+//! Messages exchanged by multiplayer peers, independent of the transport carrying them.
+//!
+//! A connection starts with both peers sending a [`Handshake`]. Each one checks the other with
+//! [`Handshake::accept`] and closes the connection with the error on a mismatch, instead of
+//! playing on diverging boards.
+//!
+//! The host then numbers every move of the shared board in a [`MoveJournal`]. A client that
+//! lost its connection sends [`Message::Resume`] with the moves it has applied, and gets the ones
+//! it missed, or a snapshot of the whole board when they are no longer kept. [`ClientSync`]
+//! keeps track of the numbering on the client side.
+//!
+//! `session` carries these messages over TCP.
+
+use std::{collections::VecDeque, fmt::Display};
+
+use bevy::prelude::IVec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    hash::StableHasher,
+    resources::{
+        board_options::{BoardOptions, GameMode},
+        board_state::BoardState,
+        rule_set::Role,
+    },
+    save::{Action, SaveGame},
+    topology::SQUARE,
+};
+
+/// Bump whenever a message changes, peers on different versions refuse each other
+pub const PROTOCOL_VERSION: u32 = 3;
+
+/// First message of a connection
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Handshake {
+    pub protocol: u32,
+    /// Crate version of the peer, to tell the player what to update
+    pub game_version: String,
+    /// [`options_hash`] of the board options
+    pub options_hash: u64,
+    /// Readable options, for error messages only
+    pub options: String,
+    /// Seed of the board, see `TileMap::seeded`
+    pub seed: u64,
+}
+
+/// Reasons a peer is refused
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HandshakeError {
+    Protocol {
+        ours: u32,
+        theirs: u32,
+        their_game: String,
+    },
+    Options {
+        ours: String,
+        theirs: String,
+    },
+    Seed {
+        ours: u64,
+        theirs: u64,
+    },
+}
+
+/// Stable hash of the options deciding the board and its rules. Display and control options
+/// are left out, players may differ there. Coordinates in messages are storage coordinates, so
+/// peers agreeing on the tiling agree on every tile
+pub fn options_hash(options: &BoardOptions) -> u64 {
+    let mut hash = StableHasher::new();
+    hash.write(b"minesweeper options");
+    hash.write_u32(options.mode as u32);
+    hash.write_u32(options.difficulty.dim.x);
+    hash.write_u32(options.difficulty.dim.y);
+    hash.write_u32(options.difficulty.n_mines);
+    hash.write(&[options.safe_start as u8, options.question_marks as u8]);
+    // square boards hash as they did before tilings
+    if options.topology != SQUARE {
+        hash.write(options.topology.as_bytes());
+    }
+    hash.finish()
+}
+
+impl Handshake {
+    pub fn new(options: &BoardOptions, seed: u64) -> Self {
+        let mode = match options.mode {
+            GameMode::Classic => "classic",
+            GameMode::Zen => "zen",
+            GameMode::Hardcore => "hardcore",
+            GameMode::Versus => "versus",
+            GameMode::Nonogram => "nonogram",
+        };
+        Self {
+            protocol: PROTOCOL_VERSION,
+            game_version: env!("CARGO_PKG_VERSION").to_owned(),
+            options_hash: options_hash(options),
+            options: match options.topology.as_str() {
+                SQUARE => format!("{} {mode}", options.difficulty.name()),
+                topology => format!("{} {mode} on {topology}", options.difficulty.name()),
+            },
+            seed,
+        }
+    }
+
+    /// Check the handshake of a peer against ours
+    pub fn accept(&self, peer: &Handshake) -> Result<(), HandshakeError> {
+        if peer.protocol != self.protocol {
+            return Err(HandshakeError::Protocol {
+                ours: self.protocol,
+                theirs: peer.protocol,
+                their_game: peer.game_version.clone(),
+            });
+        }
+        if peer.options_hash != self.options_hash {
+            return Err(HandshakeError::Options {
+                ours: self.options.clone(),
+                theirs: peer.options.clone(),
+            });
+        }
+        if peer.seed != self.seed {
+            return Err(HandshakeError::Seed {
+                ours: self.seed,
+                theirs: peer.seed,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl Display for HandshakeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandshakeError::Protocol {
+                ours,
+                theirs,
+                their_game,
+            } => write!(
+                f,
+                "The other player runs version {their_game} with protocol {theirs}, this game \
+                 speaks protocol {ours}. Both players need the same version"
+            ),
+            HandshakeError::Options { ours, theirs } => write!(
+                f,
+                "The other player set up a different game ({theirs}, here {ours})"
+            ),
+            HandshakeError::Seed { ours, theirs } => write!(
+                f,
+                "The other player is on another board (seed {theirs:#x}, here {ours:#x})"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for HandshakeError {}
+
+/// Everything peers send each other
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Message {
+    Handshake(Handshake),
+    /// Move on the shared board, numbered from 0
+    Move {
+        seq: u64,
+        action: Action,
+    },
+    /// The whole board after its first `next_seq` moves, later moves follow
+    Snapshot {
+        board: SaveGame,
+        next_seq: u64,
+    },
+    /// From a reconnecting client, which applied the first `applied` moves
+    Resume {
+        applied: u64,
+    },
+    /// Role of the receiving player, from the host when it joins. The host drops the moves the
+    /// role forbids, see [`RuleSet::allows`](crate::resources::rule_set::RuleSet::allows)
+    AssignRole(Role),
+    /// Line of the chat, see [`Message::chat`]
+    Chat {
+        from: String,
+        text: String,
+    },
+    /// Draw the attention of the other players to a tile
+    Ping {
+        coord: IVec2,
+        emote: Emote,
+    },
+}
+
+/// Longest chat line, in characters
+pub const MAX_CHAT_LEN: usize = 200;
+
+impl Message {
+    /// Chat line with the text trimmed and cut to [`MAX_CHAT_LEN`], `None` when blank
+    pub fn chat(from: &str, text: &str) -> Option<Self> {
+        let text = text.trim();
+        (!text.is_empty()).then(|| Message::Chat {
+            from: from.to_owned(),
+            text: text.chars().take(MAX_CHAT_LEN).collect(),
+        })
+    }
+}
+
+/// Reaction shown over a pinged tile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Emote {
+    /// Look here
+    #[default]
+    Look,
+    /// This is a mine
+    Mine,
+    /// This is safe
+    Safe,
+}
+
+impl Emote {
+    pub const ALL: [Emote; 3] = [Emote::Look, Emote::Mine, Emote::Safe];
+
+    /// Glyph drawn over the tile
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Emote::Look => "!",
+            Emote::Mine => "×",
+            Emote::Safe => "•",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Emote::Look => "Look here",
+            Emote::Mine => "Mine",
+            Emote::Safe => "Safe",
+        }
+    }
+}
+
+/// Host side record of the moves of the shared board, keeping the last ones for clients that
+/// reconnect
+#[derive(Debug, Clone)]
+pub struct MoveJournal {
+    // the most recent moves, the first one numbered `first`
+    moves: VecDeque<Action>,
+    first: u64,
+    capacity: usize,
+}
+
+impl MoveJournal {
+    /// Keep up to `capacity` moves, older ones are only sent as part of a snapshot
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            moves: VecDeque::with_capacity(capacity),
+            first: 0,
+            capacity,
+        }
+    }
+
+    /// Number of the next move
+    pub fn next_seq(&self) -> u64 {
+        self.first + self.moves.len() as u64
+    }
+
+    /// Number a move, returning the message broadcasting it
+    pub fn record(&mut self, action: Action) -> Message {
+        let seq = self.next_seq();
+        if self.moves.len() == self.capacity {
+            self.moves.pop_front();
+            self.first += 1;
+        }
+        if self.capacity > 0 {
+            self.moves.push_back(action);
+        } else {
+            self.first += 1;
+        }
+        Message::Move { seq, action }
+    }
+
+    /// Start over on a new board. Numbering goes on, skipping a number, so that clients still
+    /// on the old board are behind the first kept move and get the new board whole
+    pub fn restart(&mut self) {
+        self.first = self.next_seq() + 1;
+        self.moves.clear();
+    }
+
+    /// Bring a client that applied `applied` moves up to date: the moves it missed if they are
+    /// all kept, a snapshot of `state` otherwise
+    pub fn resume(&self, applied: u64, state: &BoardState) -> Vec<Message> {
+        let next_seq = self.next_seq();
+        if applied < self.first || applied > next_seq {
+            return vec![Message::Snapshot {
+                board: SaveGame::capture(state),
+                next_seq,
+            }];
+        }
+
+        let missed = (applied - self.first) as usize;
+        (self.moves.iter().enumerate().skip(missed))
+            .map(|(i, &action)| Message::Move {
+                seq: self.first + i as u64,
+                action,
+            })
+            .collect()
+    }
+}
+
+/// What a client does with a message of the host
+#[derive(Debug, Clone, PartialEq)]
+pub enum SyncStep {
+    Apply(Action),
+    /// Rebuild the board from the snapshot
+    Replace(SaveGame),
+    /// Already applied, e.g. resent after a reconnection
+    Skip,
+    /// Moves were lost in between, send [`Message::Resume`] with `applied`
+    Resume {
+        applied: u64,
+    },
+}
+
+/// Client side numbering of the moves of the host
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ClientSync {
+    applied: u64,
+}
+
+impl ClientSync {
+    /// Moves applied so far, to send in [`Message::Resume`] when reconnecting
+    pub fn applied(&self) -> u64 {
+        self.applied
+    }
+
+    pub fn receive(&mut self, message: &Message) -> SyncStep {
+        match message {
+            Message::Move { seq, .. } if *seq < self.applied => SyncStep::Skip,
+            Message::Move { seq, action } if *seq == self.applied => {
+                self.applied += 1;
+                SyncStep::Apply(*action)
+            }
+            Message::Move { .. } => SyncStep::Resume {
+                applied: self.applied,
+            },
+            Message::Snapshot { board, next_seq } => {
+                self.applied = *next_seq;
+                SyncStep::Replace(board.clone())
+            }
+            Message::Handshake(_)
+            | Message::Resume { .. }
+            | Message::AssignRole(_)
+            | Message::Chat { .. }
+            | Message::Ping { .. } => SyncStep::Skip,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{
+        options_hash, ClientSync, Emote, Handshake, HandshakeError, Message, MoveJournal, SyncStep,
+        MAX_CHAT_LEN,
+    };
+    use crate::{
+        resources::{
+            board::TileMap,
+            board_options::{BoardOptions, BoardView, Difficulty, Rotation},
+            board_state::BoardState,
+        },
+        save::{Action, SaveGame},
+        topology::{Hex, TileCoord},
+    };
+
+    #[test]
+    fn test_handshake() {
+        let options = BoardOptions::default();
+        let ours = Handshake::new(&options, 42);
+        assert_eq!(ours.accept(&ours.clone()), Ok(()));
+
+        // the view is personal
+        let turned = BoardOptions {
+            view: BoardView {
+                rotation: Rotation::Deg90,
+                mirror: true,
+            },
+            ..options.clone()
+        };
+        assert_eq!(options_hash(&turned), options_hash(&options));
+
+        let hex = BoardOptions {
+            topology: "hex".to_owned(),
+            ..options.clone()
+        };
+        assert_eq!(
+            ours.accept(&Handshake::new(&hex, 42))
+                .unwrap_err()
+                .to_string(),
+            "The other player set up a different game (Medium classic on hex, here Medium classic)"
+        );
+
+        let expert = BoardOptions {
+            difficulty: Difficulty::EXPERT,
+            ..options
+        };
+        let theirs = Handshake::new(&expert, 42);
+        assert!(matches!(
+            ours.accept(&theirs),
+            Err(HandshakeError::Options { .. })
+        ));
+        assert_eq!(
+            ours.accept(&theirs).unwrap_err().to_string(),
+            "The other player set up a different game (Expert classic, here Medium classic)"
+        );
+
+        let newer = Handshake {
+            protocol: ours.protocol + 1,
+            ..ours.clone()
+        };
+        assert!(matches!(
+            ours.accept(&newer),
+            Err(HandshakeError::Protocol { .. })
+        ));
+        let elsewhere = Handshake {
+            seed: 7,
+            ..ours.clone()
+        };
+        assert!(matches!(
+            ours.accept(&elsewhere),
+            Err(HandshakeError::Seed { .. })
+        ));
+
+        // stable across platforms and releases
+        assert_eq!(ours.options_hash, 0x32f5_7032_7402_d152);
+    }
+
+    #[test]
+    fn test_resume() {
+        let mut tiles = TileMap::with_mines(4, 4, [[0, 0]]).unwrap();
+        let mut state = BoardState::new(&mut tiles);
+        let mut journal = MoveJournal::new(3);
+        let mut client = ClientSync::default();
+
+        let flags = (0..5)
+            .map(|x| Action::Flag(IVec2::new(x % 4, 1)))
+            .collect::<Vec<_>>();
+        for (i, &action) in flags.iter().enumerate() {
+            let message = journal.record(action);
+            if let Action::Flag(coord) = action {
+                state.toggle_flag(coord);
+            }
+            // the client drops after two moves
+            if i < 2 {
+                assert_eq!(client.receive(&message), SyncStep::Apply(action));
+            }
+        }
+        assert_eq!(journal.next_seq(), 5);
+
+        // moves 2 to 4 are still kept
+        let missed = journal.resume(client.applied(), &state);
+        let steps = missed.iter().map(|m| client.receive(m)).collect::<Vec<_>>();
+        assert_eq!(
+            steps,
+            flags[2..]
+                .iter()
+                .map(|&a| SyncStep::Apply(a))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(client.applied(), 5);
+        assert!(journal.resume(5, &state).is_empty());
+
+        // a late duplicate is skipped, a gap asks for a resume
+        assert_eq!(client.receive(&missed[0]), SyncStep::Skip);
+        let gap = Message::Move {
+            seq: 7,
+            action: flags[0],
+        };
+        assert_eq!(client.receive(&gap), SyncStep::Resume { applied: 5 });
+
+        // too far behind, the board comes whole, and survives serialization
+        let [snapshot] = &journal.resume(1, &state)[..] else {
+            panic!("expected a single snapshot");
+        };
+        let snapshot: Message = ron::from_str(&ron::to_string(snapshot).unwrap()).unwrap();
+        let mut late = ClientSync::default();
+        let SyncStep::Replace(board) = late.receive(&snapshot) else {
+            panic!("expected the board to be replaced");
+        };
+        assert_eq!(late.applied(), 5);
+        let (_, restored) = board.restore().unwrap();
+        assert_eq!(restored.n_flags(), state.n_flags());
+
+        // on a new board, clients of the old one get it whole, even when they missed nothing
+        journal.restart();
+        let restarted = journal.record(flags[0]);
+        assert_eq!(
+            restarted,
+            Message::Move {
+                seq: 6,
+                action: flags[0]
+            }
+        );
+        assert!(matches!(
+            &journal.resume(client.applied(), &state)[..],
+            [Message::Snapshot { next_seq: 7, .. }]
+        ));
+        assert_eq!(journal.resume(6, &state), [restarted]);
+    }
+
+    #[test]
+    fn test_topology() {
+        let mut tiles = TileMap::with_mines(5, 4, [[0, 0]])
+            .unwrap()
+            .with_topology(&Hex);
+        let mut state = BoardState::new(&mut tiles);
+        state.toggle_flag([0, 0]);
+
+        // the hexagon pinged here is the one the peer sees
+        let hexagon = TileCoord::Axial { q: 1, r: 3 };
+        let ping = Message::Ping {
+            coord: state.from_native(hexagon).unwrap(),
+            emote: Emote::Mine,
+        };
+        let snapshot = Message::Snapshot {
+            board: SaveGame::capture(&state),
+            next_seq: 1,
+        };
+        let send = |message: &Message| -> Message {
+            ron::from_str(&ron::to_string(message).unwrap()).unwrap()
+        };
+        let (Message::Ping { coord, .. }, Message::Snapshot { board, .. }) =
+            (send(&ping), send(&snapshot))
+        else {
+            panic!("messages changed in transit");
+        };
+        let (_, peer) = board.restore().unwrap();
+        assert_eq!(peer.topology().name(), "hex");
+        assert_eq!(peer.native(coord), hexagon);
+        assert_eq!(peer.n_flags(), 1);
+    }
+
+    #[test]
+    fn test_chat() {
+        assert_eq!(Message::chat("host", "  \n"), None);
+        assert_eq!(
+            Message::chat("host", " here? "),
+            Some(Message::Chat {
+                from: "host".to_owned(),
+                text: "here?".to_owned()
+            })
+        );
+        let Some(Message::Chat { text, .. }) = Message::chat("host", &"é".repeat(300)) else {
+            panic!("expected a chat line");
+        };
+        assert_eq!(text.chars().count(), MAX_CHAT_LEN);
+    }
+}
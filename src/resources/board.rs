@@ -4,12 +4,18 @@ use std::{
     ops::DerefMut,
 };
 
-use bevy::prelude::{IVec2, UVec2};
+use bevy::{
+    math::uvec2,
+    prelude::{IVec2, UVec2},
+};
 use colored::Colorize;
 use itertools::Itertools;
-use nanorand::{tls_rng, Rng};
 
-use super::board_options::{BoardOptions, Difficulty};
+use super::board_options::{BoardOptions, Difficulty, OptionsError};
+use crate::{
+    rng::{self, Rng},
+    topology::{TileCoord, Topology},
+};
 
 #[must_use]
 fn bound_check(coord: IVec2, dim: IVec2) -> bool {
@@ -34,41 +40,111 @@ pub struct TileMap {
 
     // number of adjacent mines, negative if the tile itself is a mine
     tiles: Box<[i8]>,
+
+    topology: &'static dyn Topology,
 }
 
 impl TileMap {
-    pub fn empty(width: u32, height: u32) -> Self {
-        Self {
+    /// Board without mines, the size is checked like in [`Self::random_with`]
+    pub fn empty(width: u32, height: u32) -> Result<Self, OptionsError> {
+        let dim = uvec2(width, height);
+        let n_tiles = width
+            .checked_mul(height)
+            .filter(|_| i32::try_from(width.max(height)).is_ok())
+            .ok_or(OptionsError::BoardTooLarge { dim })?;
+
+        Ok(Self {
             n_mines: 0,
-            dim: IVec2::new(width.try_into().unwrap(), height.try_into().unwrap()),
-            tiles: vec![0; (width * height) as usize].into_boxed_slice(),
-        }
+            dim: dim.as_ivec2(),
+            tiles: vec![0; n_tiles as usize].into_boxed_slice(),
+            topology: Default::default(),
+        })
+    }
+
+    /// The same mines on another tiling, with the numbers counted again
+    pub fn with_topology(mut self, topology: &'static dyn Topology) -> Self {
+        self.topology = topology;
+        self.count_adjacent_mines();
+        self
+    }
+
+    pub fn random(width: u32, height: u32, n_mines: u32) -> Result<Self, OptionsError> {
+        Self::random_with(width, height, n_mines, &mut *rng::entropy())
+    }
+
+    /// Deterministic board generation, the same seed always yields the same board with the same
+    /// backend, see `rng`
+    pub fn seeded(width: u32, height: u32, n_mines: u32, seed: u64) -> Result<Self, OptionsError> {
+        Self::random_with(width, height, n_mines, &mut *rng::seeded(seed))
     }
 
-    pub fn random(width: u32, height: u32, n_mines: u32) -> Self {
-        let mut board = Self::empty(width, height);
-        let mut rng = tls_rng();
+    /// Board with `n_mines` mines placed by `rng`, at most one less than the tiles so that a
+    /// tile stays safe: callers skipping `BoardOptions::validate` get its error rather than a
+    /// panic
+    pub fn random_with(
+        width: u32,
+        height: u32,
+        n_mines: u32,
+        rng: &mut dyn Rng,
+    ) -> Result<Self, OptionsError> {
+        let mut board = Self::empty(width, height)?;
+        let n_tiles = board.tiles.len() as u32;
+        if n_mines > n_tiles.saturating_sub(1) {
+            return Err(OptionsError::TooManyMines { n_mines, n_tiles });
+        }
+
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!("generate_tiles", width, height, n_mines).entered();
+
+        board.n_mines = n_mines;
         board.tiles[..n_mines as usize].fill(-1);
-        rng.shuffle(&mut board.tiles);
+        rng::shuffle(rng, &mut board.tiles);
+        board.count_adjacent_mines();
+
+        Ok(board)
+    }
 
-        board
-            .all_tiles()
-            .filter(|tile| !tile.is_mine())
-            .for_each(|tile| {
-                let adj_mines = tile.neighbors().filter(|tile| tile.is_mine()).count();
-                tile.tile_state().set(adj_mines as i8);
-            });
+    /// Board with mines exactly at `mines`, duplicates are ignored
+    pub fn with_mines<T: Into<IVec2>>(
+        width: u32,
+        height: u32,
+        mines: impl IntoIterator<Item = T>,
+    ) -> Result<Self, OptionsError> {
+        let mut board = Self::empty(width, height)?;
+        for coord in mines {
+            let tile = board.tile(coord);
+            if !tile.is_mine() {
+                tile.set_state(TileState::Mine);
+                board.n_mines += 1;
+            }
+        }
+        board.count_adjacent_mines();
+
+        Ok(board)
+    }
 
-        board
+    fn count_adjacent_mines(&mut self) {
+        for idx in 0..self.tiles.len() {
+            if self.tiles[idx] < 0 {
+                continue;
+            }
+            let adj_mines = self
+                .topology
+                .neighbors(self.coord_of(idx), self.dim)
+                .filter_map(|coord| self.topology.index(coord, self.dim))
+                .filter(|&neighbor| self.tiles[neighbor] < 0)
+                .count();
+            self.tiles[idx] = adj_mines as i8;
+        }
     }
 
-    pub fn from_options(options: &BoardOptions) -> Self {
+    pub fn from_options(options: &BoardOptions) -> Result<Self, OptionsError> {
         let Difficulty {
             dim: UVec2 { x, y },
             n_mines,
         } = options.difficulty;
 
-        Self::random(x, y, n_mines)
+        Ok(Self::random(x, y, n_mines)?.with_topology(options.topology()))
     }
 
     pub fn width(&self) -> u32 {
@@ -87,29 +163,25 @@ impl TileMap {
         self.n_mines
     }
 
-    fn tile_state(&self, coord: IVec2) -> i8 {
-        let width = self.dim.x;
-
-        bound_check_assert(coord, self.dim);
-
-        let idx = coord.y * width + coord.x;
-        self.tiles[idx as usize]
+    pub fn topology(&self) -> &'static dyn Topology {
+        self.topology
     }
 
-    pub fn get_tile<T: Into<IVec2>>(&mut self, coord: T) -> Option<TileView> {
-        fn get_tile(inner: &mut TileMap, coord: IVec2) -> Option<TileView> {
+    pub fn get_tile<T: Into<IVec2>>(&mut self, coord: T) -> Option<TileView<'_>> {
+        fn get_tile(inner: &mut TileMap, coord: IVec2) -> Option<TileView<'_>> {
             bound_check(coord, inner.dim).then(|| TileView {
                 coord,
                 n_mines: inner.n_mines,
                 dim: inner.dim,
                 tiles: Cell::from_mut(inner.tiles.deref_mut()).as_slice_of_cells(),
+                topology: inner.topology,
             })
         }
 
         get_tile(self, coord.into())
     }
 
-    pub fn tile<T: Into<IVec2>>(&mut self, coord: T) -> TileView {
+    pub fn tile<T: Into<IVec2>>(&mut self, coord: T) -> TileView<'_> {
         self.get_tile(coord).unwrap()
     }
 
@@ -124,15 +196,37 @@ impl TileMap {
                 n_mines: self.n_mines,
                 dim: self.dim,
                 tiles,
+                topology: self.topology,
             })
         })
     }
 
+    /// Number of tiles, the indices of [`Self::state_at`] and [`Self::coord_of`] run up to it
+    pub fn len(&self) -> usize {
+        self.tiles.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+    }
+
+    /// Coordinate of the tile stored at `idx`
+    pub fn coord_of(&self, idx: usize) -> IVec2 {
+        self.topology.coord(idx, self.dim)
+    }
+
+    /// Content of the tile stored at `idx`, without a [`TileView`]
+    pub fn state_at(&self, idx: usize) -> TileState {
+        match self.tiles[idx] {
+            n if n < 0 => TileState::Mine,
+            n => TileState::Clear(n as u8),
+        }
+    }
+
+    /// Coordinates of every tile, in storage order
     pub fn coords(&self) -> impl Iterator<Item = IVec2> {
-        let [width, height] = self.dim.to_array();
-        (0..height)
-            .cartesian_product(0..width)
-            .map(|(y, x)| (x, y).into())
+        let (topology, dim) = (self.topology, self.dim);
+        (0..self.tiles.len()).map(move |idx| topology.coord(idx, dim))
     }
 
     pub fn all_tiles(&mut self) -> impl Iterator<Item = TileView<'_>> {
@@ -151,6 +245,8 @@ pub struct TileView<'a> {
 
     // number of adjacent mines, negative if the tile itself is a mine
     tiles: &'a [Cell<i8>],
+
+    topology: &'static dyn Topology,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -161,10 +257,8 @@ pub enum TileState {
 
 impl<'a> TileView<'a> {
     fn tile_state(&self) -> &Cell<i8> {
-        let width = self.dim.x;
-
-        let idx = self.coord.y * width + self.coord.x;
-        &self.tiles[idx as usize]
+        let idx = self.topology.index(self.coord, self.dim).unwrap();
+        &self.tiles[idx]
     }
 
     pub fn state(&self) -> TileState {
@@ -191,10 +285,19 @@ impl<'a> TileView<'a> {
         self.coord
     }
 
+    /// Coordinate in the natural system of the tiling
+    pub fn native(&self) -> TileCoord {
+        self.topology.native(self.coord, self.dim)
+    }
+
     pub fn dim(&self) -> IVec2 {
         self.dim
     }
 
+    pub fn n_mines(&self) -> u32 {
+        self.n_mines
+    }
+
     pub fn with_coordinate<T: Into<IVec2>>(self, coord: T) -> Self {
         fn with_coordinate(mut this: TileView, coord: IVec2) -> TileView {
             bound_check_assert(coord, this.dim);
@@ -221,23 +324,11 @@ impl<'a> TileView<'a> {
         self.try_with_coordinate(coord.into() + self.coord)
     }
 
+    /// Tiles touching this one, as the topology of the map has it
     pub fn neighbors(self) -> impl Iterator<Item = TileView<'a>> {
-        /// Delta coordinates for all 8 square neighbors
-        const NEIGHBORS: [[i32; 2]; 8] = [
-            [-1, -1],
-            [0, -1],
-            [1, -1],
-            [-1, 0],
-            [1, 0],
-            [-1, 1],
-            [0, 1],
-            [1, 1],
-        ];
-
-        NEIGHBORS.into_iter().filter_map(move |delta| {
-            let coord = self.coord + IVec2::from(delta);
-            bound_check(coord, self.dim).then(|| self.with_coordinate(coord))
-        })
+        self.topology
+            .neighbors(self.coord, self.dim)
+            .map(move |coord| self.with_coordinate(coord))
     }
 }
 
@@ -283,15 +374,68 @@ impl Display for TileMap {
     }
 }
 
+/// Letters of the column `x` along the board edges: A to Z, then AA, AB and so on like the
+/// columns of a spreadsheet
+pub fn column_name(x: u32) -> String {
+    let mut name = String::new();
+    let mut left = u64::from(x) + 1;
+    while left > 0 {
+        left -= 1;
+        name.insert(0, char::from(b'A' + (left % 26) as u8));
+        left /= 26;
+    }
+    name
+}
+
+/// Name a player can call a tile by, its column letters then its row from 1, e.g. `C7`
+pub fn coord_name(coord: IVec2) -> String {
+    format!("{}{}", column_name(coord.x as u32), coord.y + 1)
+}
+
+/// Tile called by its name, the inverse of [`coord_name`], in any case
+pub fn coord_from_name(name: &str) -> Option<IVec2> {
+    let split = name.find(|c: char| !c.is_ascii_alphabetic())?;
+    let (letters, row) = name.split_at(split);
+    if letters.is_empty() || !row.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let column = letters.bytes().try_fold(0u64, |column, letter| {
+        let digit = u64::from(letter.to_ascii_uppercase() - b'A') + 1;
+        column.checked_mul(26)?.checked_add(digit)
+    })?;
+    let x = i32::try_from(column - 1).ok()?;
+    let y = i32::try_from(row.parse::<u64>().ok()?.checked_sub(1)?).ok()?;
+    Some(IVec2::new(x, y))
+}
+
 #[cfg(test)]
 mod test {
+    use bevy::{math::uvec2, prelude::IVec2};
     use itertools::Itertools;
+    use nanorand::{Rng, WyRand};
 
-    use super::TileMap;
+    use super::{column_name, coord_from_name, coord_name, TileMap, TileState};
+    use crate::{resources::board_options::OptionsError, rng::RngBackend, topology::Torus};
+
+    #[test]
+    fn test_names() {
+        let names = [0, 1, 25, 26, 27, 51, 701, 702].map(column_name);
+        assert_eq!(names, ["A", "B", "Z", "AA", "AB", "AZ", "ZZ", "AAA"]);
+        assert_eq!(coord_name(IVec2::new(2, 6)), "C7");
+        assert_eq!(coord_name(IVec2::new(29, 15)), "AD16");
+
+        for coord in [IVec2::new(2, 6), IVec2::new(29, 15), IVec2::new(702, 0)] {
+            assert_eq!(coord_from_name(&coord_name(coord)), Some(coord));
+        }
+        assert_eq!(coord_from_name("c7"), Some(IVec2::new(2, 6)));
+        for name in ["C", "7", "C0", "C-1", "C7D", ""] {
+            assert_eq!(coord_from_name(name), None, "{name}");
+        }
+    }
 
     #[test]
     fn test_neighbors() {
-        let mut tiles = TileMap::empty(8, 8);
+        let mut tiles = TileMap::empty(8, 8).unwrap();
 
         let tile = tiles.tile([1, 1]);
         let actual = tile
@@ -317,9 +461,79 @@ mod test {
         assert_eq!(actual, expected);
     }
 
+    #[test]
+    fn test_with_mines() {
+        let mut board = TileMap::with_mines(3, 2, [[0, 0], [2, 1], [0, 0]]).unwrap();
+        assert_eq!(board.n_mines(), 2);
+        assert_eq!(board.tile([1, 0]).state(), TileState::Clear(2));
+        assert_eq!(board.tile([2, 0]).state(), TileState::Clear(1));
+        assert!(board.tile([2, 1]).is_mine());
+    }
+
     #[test]
     fn test_random() {
-        let board = TileMap::random(30, 16, 99);
+        let board = TileMap::random(30, 16, 99).unwrap();
         println!("{:#}", board);
     }
+
+    #[test]
+    fn test_invalid_sizes() {
+        let board = TileMap::seeded(3, 3, 8, 1).unwrap();
+        assert_eq!(board.tiles.iter().filter(|&&tile| tile < 0).count(), 8);
+        assert_eq!(
+            TileMap::seeded(3, 3, 9, 1).unwrap_err(),
+            OptionsError::TooManyMines {
+                n_mines: 9,
+                n_tiles: 9
+            }
+        );
+        assert_eq!(TileMap::seeded(0, 4, 0, 1).unwrap().n_mines(), 0);
+        assert!(TileMap::seeded(0, 4, 1, 1).is_err());
+        // the tile count overflows before any allocation
+        assert_eq!(
+            TileMap::seeded(1 << 16, 1 << 16, 1, 1).unwrap_err(),
+            OptionsError::BoardTooLarge {
+                dim: uvec2(1 << 16, 1 << 16)
+            }
+        );
+        assert!(TileMap::empty(1 << 16, 1 << 16).is_err());
+        assert!(TileMap::with_mines(u32::MAX, 1, [[0, 0]]).is_err());
+    }
+
+    #[test]
+    fn test_seeded_compat() {
+        // the boards of the releases before `rng`, shuffled by nanorand itself
+        for (seed, width, height, n_mines) in [(0, 9, 9, 10), (42, 16, 16, 40), (7, 30, 16, 99)] {
+            let mut expected = vec![false; (width * height) as usize];
+            expected[..n_mines as usize].fill(true);
+            WyRand::new_seed(seed).shuffle(&mut expected);
+
+            let mut rng = RngBackend::Nanorand.seeded(seed);
+            let board = TileMap::random_with(width, height, n_mines, &mut *rng).unwrap();
+            let mines = board.tiles.iter().map(|&tile| tile < 0).collect_vec();
+            assert_eq!(mines, expected, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_indices() {
+        let mut tiles = TileMap::with_mines(4, 3, [[1, 2], [3, 0]]).unwrap();
+        assert_eq!(tiles.len(), 12);
+        let coords = tiles.coords().collect_vec();
+        assert_eq!(coords[5].to_array(), [1, 1]);
+        for (idx, coord) in coords.into_iter().enumerate() {
+            assert_eq!(tiles.coord_of(idx), coord);
+            assert_eq!(tiles.state_at(idx), tiles.tile(coord).state());
+        }
+    }
+
+    #[test]
+    fn test_topology() {
+        let mut tiles = TileMap::with_mines(4, 4, [[0, 0]]).unwrap();
+        assert_eq!(tiles.tile([3, 3]).state(), TileState::Clear(0));
+
+        let mut tiles = tiles.with_topology(&Torus);
+        assert_eq!(tiles.tile([3, 3]).state(), TileState::Clear(1));
+        assert_eq!(tiles.tile([0, 0]).neighbors().count(), 8);
+    }
 }
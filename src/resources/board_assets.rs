@@ -0,0 +1,244 @@
+use bevy::{
+    prelude::{AssetServer, Color, FromWorld, Handle, Image, World},
+    text::Font,
+};
+
+use super::{
+    board_options::NumberGlyphs,
+    game_assets::{
+        DICE, FACE_IDLE, FACE_LOST, FACE_PRESSED, FACE_WON, FLAG_SPRITE, FONT, MINE_SPRITE,
+    },
+    skin::SkinPack,
+    smiley::Smiley,
+};
+
+/// Color of the sprite hiding covered tiles, without a skin image
+pub const COVER_COLOR: Color = Color::DARK_GRAY;
+
+/// Color of opened tiles, without a skin image
+pub const TILE_COLOR: Color = Color::GRAY;
+
+/// Color of the number `n` written with the font
+pub fn number_color(n: u8) -> Color {
+    match n {
+        1 => Color::BLUE,
+        2 => Color::GREEN,
+        3 => Color::ORANGE,
+        _ => Color::RED,
+    }
+}
+
+/// Images and font the board is drawn with, from the active skin pack. Changing it restyles the
+/// board in place, see `SkinPlugin`
+#[derive(Debug, Clone)]
+pub struct BoardAssets {
+    /// Id of the skin pack, `None` for the built-in look
+    pub skin: Option<String>,
+    pub font: Handle<Font>,
+    pub flag: Handle<Image>,
+    pub mine: Handle<Image>,
+    /// Covered tile, a plain [`COVER_COLOR`] square when `None`
+    pub covered: Option<Handle<Image>>,
+    /// Opened tile, a plain [`TILE_COLOR`] square when `None`
+    pub revealed: Option<Handle<Image>>,
+    /// Numbers 1 to 8, written with the font when `None`
+    pub digits: Option<Vec<Handle<Image>>>,
+    /// Dots of the numbers 1 to 8, the same for every skin
+    pub dice: Vec<Handle<Image>>,
+    /// Glyphs the numbers are drawn with, from `BoardOptions::number_glyphs`
+    pub glyphs: NumberGlyphs,
+    pub background: Option<Handle<Image>>,
+    /// Reset button
+    pub faces: FaceImages,
+    /// Mine counter and timer, written with the font when `None`
+    pub segments: Option<SegmentImages>,
+}
+
+/// Images of the reset button, see [`SmileyFaces`](super::skin::SmileyFaces)
+#[derive(Debug, Clone)]
+pub struct FaceImages {
+    pub idle: Handle<Image>,
+    pub pressed: Handle<Image>,
+    pub won: Handle<Image>,
+    pub lost: Handle<Image>,
+}
+
+impl FaceImages {
+    pub fn get(&self, smiley: Smiley) -> &Handle<Image> {
+        match smiley {
+            Smiley::Idle => &self.idle,
+            Smiley::Pressed => &self.pressed,
+            Smiley::Won => &self.won,
+            Smiley::Lost => &self.lost,
+        }
+    }
+}
+
+/// Character of a digit of a counter
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SegmentGlyph {
+    Digit(u8),
+    Minus,
+}
+
+impl SegmentGlyph {
+    /// Character at `position`, from the left, of `value` written on `digits` digits. Values out
+    /// of the display stop at its largest or smallest number, as on the original counters
+    pub fn at(value: i32, digits: usize, position: usize) -> Self {
+        let pow = |n: usize| 10i64.saturating_pow(n as u32);
+        let (value, digits, position) = if value < 0 {
+            if position == 0 {
+                return Self::Minus;
+            }
+            let max = pow(digits - 1) - 1;
+            (i64::from(value).abs().min(max), digits - 1, position - 1)
+        } else {
+            (i64::from(value).min(pow(digits) - 1), digits, position)
+        };
+        Self::Digit((value / pow(digits - 1 - position) % 10) as u8)
+    }
+}
+
+/// Images of the counters, see [`SegmentDigits`](super::skin::SegmentDigits)
+#[derive(Debug, Clone)]
+pub struct SegmentImages {
+    /// Digits 0 to 9
+    pub digits: Vec<Handle<Image>>,
+    pub minus: Handle<Image>,
+}
+
+impl SegmentImages {
+    pub fn get(&self, glyph: SegmentGlyph) -> &Handle<Image> {
+        match glyph {
+            SegmentGlyph::Digit(n) => &self.digits[usize::from(n)],
+            SegmentGlyph::Minus => &self.minus,
+        }
+    }
+}
+
+impl BoardAssets {
+    pub fn builtin(asset_server: &AssetServer) -> Self {
+        Self {
+            skin: None,
+            font: asset_server.load(FONT),
+            flag: asset_server.load(FLAG_SPRITE),
+            mine: asset_server.load(MINE_SPRITE),
+            covered: None,
+            revealed: None,
+            digits: None,
+            dice: DICE.iter().map(|path| asset_server.load(*path)).collect(),
+            glyphs: NumberGlyphs::Digits,
+            background: None,
+            faces: FaceImages {
+                idle: asset_server.load(FACE_IDLE),
+                pressed: asset_server.load(FACE_PRESSED),
+                won: asset_server.load(FACE_WON),
+                lost: asset_server.load(FACE_LOST),
+            },
+            segments: None,
+        }
+    }
+
+    /// Images of `pack`, the built-in ones where it has none
+    pub fn from_pack(asset_server: &AssetServer, pack: &SkinPack) -> Self {
+        let builtin = Self::builtin(asset_server);
+        let load = |file: &String| asset_server.load(&pack.asset_path(file));
+        let manifest = &pack.manifest;
+        Self {
+            skin: Some(pack.id.clone()),
+            font: builtin.font,
+            flag: manifest.flag.as_ref().map_or(builtin.flag, load),
+            mine: manifest.mine.as_ref().map_or(builtin.mine, load),
+            covered: manifest.covered.as_ref().map(load),
+            revealed: manifest.revealed.as_ref().map(load),
+            digits: manifest
+                .digits
+                .as_ref()
+                .map(|digits| digits.iter().map(load).collect()),
+            dice: builtin.dice,
+            glyphs: builtin.glyphs,
+            background: manifest.background.as_ref().map(load),
+            faces: manifest
+                .faces
+                .as_ref()
+                .map_or(builtin.faces, |faces| FaceImages {
+                    idle: load(&faces.idle),
+                    pressed: load(&faces.pressed),
+                    won: load(&faces.won),
+                    lost: load(&faces.lost),
+                }),
+            segments: manifest.segments.as_ref().map(|segments| SegmentImages {
+                digits: segments.digits.iter().map(load).collect(),
+                minus: load(&segments.minus),
+            }),
+        }
+    }
+
+    /// Untinted color of the covers: an image shows its own colors
+    pub fn cover_color(&self) -> Color {
+        match self.covered {
+            Some(_) => Color::WHITE,
+            None => COVER_COLOR,
+        }
+    }
+
+    /// Untinted color of the opened tiles
+    pub fn tile_color(&self) -> Color {
+        match self.revealed {
+            Some(_) => Color::WHITE,
+            None => TILE_COLOR,
+        }
+    }
+
+    /// Image of the number `n`, from 1 to 8, in the current glyphs. `None` when written with the
+    /// font
+    pub fn digit(&self, n: u8) -> Option<Handle<Image>> {
+        let images = match self.glyphs {
+            NumberGlyphs::Digits => self.digits.as_ref()?,
+            NumberGlyphs::Dice => &self.dice,
+            NumberGlyphs::Roman => return None,
+        };
+        images.get(usize::from(n).checked_sub(1)?).cloned()
+    }
+
+    /// Tint of the image of the number `n`: the white dice take the colors of the font, the
+    /// digits of a skin show their own
+    pub fn digit_color(&self, n: u8) -> Color {
+        match self.glyphs {
+            NumberGlyphs::Dice => number_color(n),
+            NumberGlyphs::Digits | NumberGlyphs::Roman => Color::WHITE,
+        }
+    }
+
+    /// Text of the number `n` when written with the font
+    pub fn label(&self, n: u8) -> String {
+        self.glyphs.label(n)
+    }
+}
+
+impl FromWorld for BoardAssets {
+    fn from_world(world: &mut World) -> Self {
+        Self::builtin(world.resource::<AssetServer>())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SegmentGlyph::{self, Digit, Minus};
+
+    fn glyphs(value: i32, digits: usize) -> Vec<SegmentGlyph> {
+        (0..digits)
+            .map(|position| SegmentGlyph::at(value, digits, position))
+            .collect()
+    }
+
+    #[test]
+    fn test_segments() {
+        assert_eq!(glyphs(7, 3), [Digit(0), Digit(0), Digit(7)]);
+        assert_eq!(glyphs(99, 3), [Digit(0), Digit(9), Digit(9)]);
+        assert_eq!(glyphs(1234, 3), [Digit(9), Digit(9), Digit(9)]);
+        assert_eq!(glyphs(-5, 3), [Minus, Digit(0), Digit(5)]);
+        assert_eq!(glyphs(-120, 3), [Minus, Digit(9), Digit(9)]);
+        assert_eq!(glyphs(0, 1), [Digit(0)]);
+    }
+}
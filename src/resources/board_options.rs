@@ -1,19 +1,125 @@
+use std::{
+    fmt::Display,
+    path::{Path, PathBuf},
+};
+
 use bevy::{
-    math::uvec2,
-    prelude::{UVec2, Vec2, Vec3},
+    math::{ivec2, uvec2},
+    prelude::{IVec2, UVec2, Vec2, Vec3},
 };
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
 use serde::{Deserialize, Serialize};
 
+use super::{demo::DemoMode, input_map::InputMap, rule_set::RuleSet};
+use crate::{
+    solver::BotSkill,
+    storage::{Location, Storage},
+    topology::{self, Topology, SQUARE},
+};
+
+/// Options file in the config directory, used when no `--config` is given
+pub const OPTIONS_FILE: &str = "options.ron";
+
+/// Largest supported board side, keeps tile indices and world coordinates well within range
+pub const MAX_DIM: u32 = 1024;
+
+/// File the board options were loaded from with `--config`, if any
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OptionsFile(pub PathBuf);
+
+impl OptionsFile {
+    pub fn from_args(args: &[String]) -> Option<Self> {
+        args.iter()
+            .rposition(|arg| arg == "--config")
+            .and_then(|idx| args.get(idx + 1))
+            .map(|path| Self(path.into()))
+    }
+
+    /// The options file of the config directory, if it exists
+    pub fn from_storage(storage: &Storage) -> Option<Self> {
+        storage
+            .path(Location::Config, OPTIONS_FILE)
+            .filter(|path| path.is_file())
+            .map(Self)
+    }
+}
+
+/// Reasons board options cannot be used
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionsError {
+    /// The board has no tiles
+    EmptyBoard {
+        dim: UVec2,
+    },
+    /// The board is bigger than [`MAX_DIM`] on a side
+    BoardTooLarge {
+        dim: UVec2,
+    },
+    /// There must be at least one safe tile
+    TooManyMines {
+        n_mines: u32,
+        n_tiles: u32,
+    },
+    NegativePadding(f32),
+    NonPositiveTileSize(f32),
+    /// Adaptive tile size with `min > max`
+    InvertedTileSize {
+        min: f32,
+        max: f32,
+    },
+    /// Adaptive density bounds outside of `0..1`, or with `min > max`
+    InvalidDensity {
+        min: f32,
+        max: f32,
+    },
+    /// Demo mode with a negative time, or no idle time before it starts
+    InvalidDemo {
+        idle: f32,
+        move_time: f32,
+        board_time: f32,
+    },
+    /// The config file could not be read or parsed
+    Config {
+        path: String,
+        reason: String,
+    },
+    /// Malformed command line
+    Argument {
+        arg: String,
+        reason: String,
+    },
+    /// No tiling is registered under this name
+    UnknownTopology(String),
+    /// The tiling does not fit the board
+    Topology {
+        name: String,
+        reason: String,
+    },
+    /// Two actions of the `InputMap` share a button or key, so that one of them cannot be used
+    DuplicateBinding {
+        first: &'static str,
+        second: &'static str,
+    },
+}
+
 /// Tile size options
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum TileSize {
     /// Fixed tile size
-    Fixed(f32),
+    Fixed(#[cfg_attr(feature = "debug", inspectable(default = 30.0))] f32),
     /// Window adaptative tile size
-    Adaptive { min: f32, max: f32 },
+    Adaptive {
+        #[cfg_attr(feature = "debug", inspectable(default = 10.0))]
+        min: f32,
+        #[cfg_attr(feature = "debug", inspectable(default = 50.0))]
+        max: f32,
+    },
 }
 
 /// Board position customization options
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BoardPosition {
     /// Centered board
@@ -22,7 +128,142 @@ pub enum BoardPosition {
     Custom(Vec3),
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Bounds of the mine density of adaptive boards, mines per tile. The density of the next board
+/// follows the skill estimate of the player between them, see `SkillEstimate`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveDensity {
+    pub min: f32,
+    pub max: f32,
+}
+
+impl Default for AdaptiveDensity {
+    /// Around the density of the easy preset up to a bit more than the expert one
+    fn default() -> Self {
+        Self {
+            min: 0.12,
+            max: 0.24,
+        }
+    }
+}
+
+impl AdaptiveDensity {
+    /// Density of a board at `skill`, from 0 at the lower bound to 1 at the upper one
+    pub fn density(&self, skill: f64) -> f32 {
+        self.min + (self.max - self.min) * skill.clamp(0.0, 1.0) as f32
+    }
+
+    /// Where `density` lies between the bounds, inverse of [`Self::density`]
+    pub fn skill(&self, density: f32) -> f64 {
+        if self.max <= self.min {
+            return 0.5;
+        }
+        f64::from((density - self.min) / (self.max - self.min)).clamp(0.0, 1.0)
+    }
+
+    /// Mines of a `dim` sized board at `skill`, leaving a mine and a safe tile at least
+    pub fn n_mines(&self, skill: f64, dim: UVec2) -> u32 {
+        let n_tiles = dim.x * dim.y;
+        let n_mines = (self.density(skill) * n_tiles as f32).round() as u32;
+        n_mines.clamp(1, n_tiles.saturating_sub(1).max(1))
+    }
+}
+
+/// Rules of the game, also a resource holding the mode of the current board. Replay
+/// fingerprints hash the variant index, new modes go last
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// No counters or split times, unlimited undo, and nothing recorded in the stats
+    Zen,
+    /// No flags and no assists, with best times of its own
+    Hardcore,
+    /// Two players alternating moves on the same board, see `Versus`
+    Versus,
+    /// Mines of every row and column written along the border, like a nonogram. Won by flagging
+    /// every mine and nothing else
+    Nonogram,
+}
+
+/// Counterclockwise rotation of the board on screen
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Rotation {
+    #[default]
+    Deg0,
+    Deg90,
+    Deg180,
+    Deg270,
+}
+
+/// How the numbers of the opened tiles are drawn, some players reading patterns faster than
+/// digits
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum NumberGlyphs {
+    /// The digit images of the skin, or the font when it has none
+    #[default]
+    Digits,
+    /// Dots of a die, 7 and 8 filling the middle column and row
+    Dice,
+    /// Roman numerals written with the font
+    Roman,
+}
+
+impl NumberGlyphs {
+    pub const ALL: [NumberGlyphs; 3] = [
+        NumberGlyphs::Digits,
+        NumberGlyphs::Dice,
+        NumberGlyphs::Roman,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            NumberGlyphs::Digits => "Digits",
+            NumberGlyphs::Dice => "Dice",
+            NumberGlyphs::Roman => "Roman",
+        }
+    }
+
+    /// Text of the number `n` when written with the font
+    pub fn label(self, n: u8) -> String {
+        if self != NumberGlyphs::Roman {
+            return n.to_string();
+        }
+        const NUMERALS: [(u8, &str); 6] = [
+            (50, "L"),
+            (40, "XL"),
+            (10, "X"),
+            (9, "IX"),
+            (5, "V"),
+            (4, "IV"),
+        ];
+        let mut left = n;
+        let mut label = String::new();
+        for (value, numeral) in NUMERALS {
+            while left >= value {
+                label.push_str(numeral);
+                left -= value;
+            }
+        }
+        label + &"I".repeat(usize::from(left))
+    }
+}
+
+/// How the board is shown. The tile map is never touched, tiles are only placed differently
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoardView {
+    pub rotation: Rotation,
+    /// Mirror left and right, before rotating
+    pub mirror: bool,
+}
+
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Difficulty {
     /// Tile map size
     pub dim: UVec2,
@@ -46,12 +287,47 @@ impl Difficulty {
         dim: uvec2(30, 16),
         n_mines: 99,
     };
+
+    /// Name of the preset, or `WxH/mines` for custom boards
+    pub fn name(&self) -> String {
+        [
+            ("Easy", Self::EASY),
+            ("Medium", Self::MEDIUM),
+            ("Expert", Self::EXPERT),
+        ]
+        .into_iter()
+        .find(|(_, preset)| preset.dim == self.dim && preset.n_mines == self.n_mines)
+        .map_or_else(
+            || format!("{}x{}/{}", self.dim.x, self.dim.y, self.n_mines),
+            |(name, _)| name.to_owned(),
+        )
+    }
+
+    /// Difficulty of a name given by [`Self::name`], the preset names in any case
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "easy" => return Some(Self::EASY),
+            "medium" => return Some(Self::MEDIUM),
+            "expert" => return Some(Self::EXPERT),
+            _ => {}
+        }
+        let (dim, n_mines) = name.split_once('/')?;
+        let (width, height) = dim.split_once('x')?;
+        Some(Self {
+            dim: uvec2(width.parse().ok()?, height.parse().ok()?),
+            n_mines: n_mines.parse().ok()?,
+        })
+    }
 }
 
 /// Board generation options. Must be used as a resource
-// We use serde to allow saving option presets and loading them at runtime
+// We use serde to allow saving option presets and loading them at runtime. Options missing
+// from older files take their default value
+#[cfg_attr(feature = "debug", derive(Inspectable))]
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BoardOptions {
+    pub mode: GameMode,
     pub difficulty: Difficulty,
     /// Board world position
     pub position: BoardPosition,
@@ -61,27 +337,91 @@ pub struct BoardOptions {
     pub tile_padding: f32,
     /// Does the board generate a safe place to start
     pub safe_start: bool,
+    /// Outline the last uncovered or flagged tile for a moment
+    pub highlight_last_move: bool,
+    /// Ask for a second click before uncovering a tile the solver proved to be a mine. An
+    /// assist, off by default so that competitive games are played without it
+    pub confirm_proven_mines: bool,
+    /// Click tiles on button release, so that dragging off the tile cancels a misclick
+    pub click_on_release: bool,
+    /// Flags turn into question marks before going away, in modes with flags
+    pub question_marks: bool,
+    /// Show the mines left on every row and column along the border, an assist for beginners.
+    /// Wins with it set no best time
+    pub line_counts: bool,
+    /// Label the columns with letters and the rows with numbers along the border, so that tiles
+    /// can be named out loud like `C7`. Shown without regenerating the board
+    pub coordinates: bool,
+    /// Turn animations off, see `MotionPrefs`
+    pub reduce_motion: bool,
+    /// Write a bug report with the seed, options, last events and board when the game crashes,
+    /// see `BugReportPlugin`
+    pub bug_reports: bool,
+    /// Rotation and mirroring of the board, applied without regenerating it
+    pub view: BoardView,
+    /// Tiling of the board, a name of the topology registry, see `topology::get`
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub topology: String,
+    /// Mine density of new boards following the skill of the player between these bounds, the
+    /// mines of the difficulty when `None`. Boards every player shares keep their own
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub adaptive: Option<AdaptiveDensity>,
+    /// Computer opponent playing the second player in versus mode, hot-seat when `None`
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub bot: Option<BotSkill>,
+    /// Directory of the skin pack in `assets/skins`, the built-in look when `None`. Swapped
+    /// without regenerating the board
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub skin: Option<String>,
+    /// Digits, dice or Roman numerals on the opened tiles, over the digits of the skin. Swapped
+    /// without regenerating the board
+    pub number_glyphs: NumberGlyphs,
+    /// Controls
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub input: InputMap,
+    /// Attract mode starting after a while on the pause menu, off when `None`
+    #[cfg_attr(feature = "debug", inspectable(ignore))]
+    pub demo: Option<DemoMode>,
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct DisplayParams {
+    /// Size of the board on screen, rotation included
     pub board_size: Vec2,
     pub tile_size: f32,
     pub position: Vec3,
+    /// Tile map size
+    pub dim: UVec2,
+    pub view: BoardView,
+    pub topology: &'static dyn Topology,
 }
 
 impl BoardOptions {
     pub fn display_params(&self, window_dim: Vec2) -> DisplayParams {
+        self.layout(self.difficulty.dim, window_dim)
+    }
+
+    /// Display parameters of a `dim` sized tile map, which may differ from the difficulty
+    /// while options are being edited
+    pub fn layout(&self, dim: UVec2, window_dim: Vec2) -> DisplayParams {
+        let topology = self.topology();
+        let display_dim = self.view.display_extent(topology.extent(dim.as_ivec2()));
         let tile_size = match self.tile_size {
             TileSize::Fixed(size) => size,
             TileSize::Adaptive { min, max } => {
-                let [max_width, max_height] =
-                    (window_dim / self.difficulty.dim.as_vec2()).to_array();
+                // a line of tiles on every side for the counts along the border
+                let rules = RuleSet::from_options(self);
+                let margin = if rules.line_counts || rules.line_totals {
+                    2.0
+                } else {
+                    0.0
+                };
+                let [max_width, max_height] = (window_dim / (display_dim + margin)).to_array();
                 max_width.min(max_height).clamp(min, max)
             }
         };
 
-        let board_size = self.difficulty.dim.as_vec2() * tile_size;
+        let board_size = display_dim * tile_size;
         let position = match self.position {
             BoardPosition::Centered { offset } => -board_size.extend(0.0) / 2.0 + offset,
             BoardPosition::Custom(p) => p,
@@ -91,8 +431,549 @@ impl BoardOptions {
             board_size,
             tile_size,
             position,
+            dim,
+            view: self.view,
+            topology,
         }
     }
+
+    /// Tiling of the `topology` option, squares if it names none
+    pub fn topology(&self) -> &'static dyn Topology {
+        topology::get(&self.topology).unwrap_or_default()
+    }
+}
+
+impl BoardView {
+    /// Size of a `dim` sized tile map on screen, in tiles
+    pub fn display_dim(&self, dim: UVec2) -> UVec2 {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => dim,
+            Rotation::Deg90 | Rotation::Deg270 => uvec2(dim.y, dim.x),
+        }
+    }
+
+    /// Screen coordinate of a tile, in tiles from the bottom left corner
+    pub fn to_display(&self, coord: IVec2, dim: UVec2) -> IVec2 {
+        let max = dim.as_ivec2() - 1;
+        let coord = self.mirrored(coord, max);
+        match self.rotation {
+            Rotation::Deg0 => coord,
+            Rotation::Deg90 => ivec2(max.y - coord.y, coord.x),
+            Rotation::Deg180 => max - coord,
+            Rotation::Deg270 => ivec2(coord.y, max.x - coord.x),
+        }
+    }
+
+    /// Tile shown at a screen coordinate, inverse of [`Self::to_display`]
+    pub fn to_board(&self, display: IVec2, dim: UVec2) -> IVec2 {
+        let max = dim.as_ivec2() - 1;
+        let coord = match self.rotation {
+            Rotation::Deg0 => display,
+            Rotation::Deg90 => ivec2(display.y, max.y - display.x),
+            Rotation::Deg180 => max - display,
+            Rotation::Deg270 => ivec2(max.x - display.y, display.x),
+        };
+        self.mirrored(coord, max)
+    }
+
+    fn mirrored(&self, coord: IVec2, max: IVec2) -> IVec2 {
+        if self.mirror {
+            ivec2(max.x - coord.x, coord.y)
+        } else {
+            coord
+        }
+    }
+
+    /// Size on screen of a board `extent` large, see [`Topology::extent`]
+    pub fn display_extent(&self, extent: Vec2) -> Vec2 {
+        match self.rotation {
+            Rotation::Deg0 | Rotation::Deg180 => extent,
+            Rotation::Deg90 | Rotation::Deg270 => Vec2::new(extent.y, extent.x),
+        }
+    }
+
+    /// Screen position of a point of an `extent` large board, both from the bottom left corner.
+    /// [`Self::to_display`] for any tiling
+    pub fn place(&self, point: Vec2, extent: Vec2) -> Vec2 {
+        let point = if self.mirror {
+            Vec2::new(extent.x - point.x, point.y)
+        } else {
+            point
+        };
+        match self.rotation {
+            Rotation::Deg0 => point,
+            Rotation::Deg90 => Vec2::new(extent.y - point.y, point.x),
+            Rotation::Deg180 => extent - point,
+            Rotation::Deg270 => Vec2::new(point.y, extent.x - point.x),
+        }
+    }
+
+    /// Point of the board at a screen position, inverse of [`Self::place`]
+    pub fn unplace(&self, display: Vec2, extent: Vec2) -> Vec2 {
+        let point = match self.rotation {
+            Rotation::Deg0 => display,
+            Rotation::Deg90 => Vec2::new(display.y, extent.y - display.x),
+            Rotation::Deg180 => extent - display,
+            Rotation::Deg270 => Vec2::new(extent.x - display.y, display.x),
+        };
+        if self.mirror {
+            Vec2::new(extent.x - point.x, point.y)
+        } else {
+            point
+        }
+    }
+}
+
+impl BoardOptions {
+    /// Repair the options with an obvious fix, and return what was wrong with them so that it can
+    /// be reported:
+    ///
+    /// - more mines than the board holds are cut down to leave one safe tile
+    /// - a negative padding becomes 0
+    /// - inverted bounds of the adaptive tile size or mine density are swapped
+    ///
+    /// Everything else is left to [`Self::validate`]: an empty or oversized board, NaN sizes,
+    /// densities out of `0..1`, demo times, duplicate bindings and unknown tilings have no value
+    /// the player could have meant, so guessing one would hide the mistake.
+    pub fn clamp(&mut self) -> Vec<OptionsError> {
+        let mut clamped = Vec::new();
+
+        let dim = self.difficulty.dim;
+        if (1..=MAX_DIM).contains(&dim.x) && (1..=MAX_DIM).contains(&dim.y) {
+            let n_tiles = dim.x * dim.y;
+            if self.difficulty.n_mines >= n_tiles {
+                clamped.push(OptionsError::TooManyMines {
+                    n_mines: self.difficulty.n_mines,
+                    n_tiles,
+                });
+                self.difficulty.n_mines = n_tiles - 1;
+            }
+        }
+
+        if self.tile_padding < 0.0 {
+            clamped.push(OptionsError::NegativePadding(self.tile_padding));
+            self.tile_padding = 0.0;
+        }
+
+        if let TileSize::Adaptive { min, max } = &mut self.tile_size {
+            if *min > *max {
+                clamped.push(OptionsError::InvertedTileSize {
+                    min: *min,
+                    max: *max,
+                });
+                std::mem::swap(min, max);
+            }
+        }
+
+        if let Some(AdaptiveDensity { min, max }) = &mut self.adaptive {
+            if *min > *max {
+                clamped.push(OptionsError::InvalidDensity {
+                    min: *min,
+                    max: *max,
+                });
+                std::mem::swap(min, max);
+            }
+        }
+
+        clamped
+    }
+
+    /// Reject options that would produce a broken or unplayable board
+    pub fn validate(&self) -> Result<(), OptionsError> {
+        let dim = self.difficulty.dim;
+        if dim.x == 0 || dim.y == 0 {
+            return Err(OptionsError::EmptyBoard { dim });
+        }
+        if dim.x > MAX_DIM || dim.y > MAX_DIM {
+            return Err(OptionsError::BoardTooLarge { dim });
+        }
+
+        let n_tiles = dim.x * dim.y;
+        if self.difficulty.n_mines >= n_tiles {
+            return Err(OptionsError::TooManyMines {
+                n_mines: self.difficulty.n_mines,
+                n_tiles,
+            });
+        }
+
+        if self.tile_padding.is_nan() || self.tile_padding < 0.0 {
+            return Err(OptionsError::NegativePadding(self.tile_padding));
+        }
+
+        match self.tile_size {
+            TileSize::Fixed(size) | TileSize::Adaptive { min: size, .. }
+                if size.is_nan() || size <= 0.0 =>
+            {
+                Err(OptionsError::NonPositiveTileSize(size))
+            }
+            TileSize::Adaptive { min, max } if max.is_nan() || min > max => {
+                Err(OptionsError::InvertedTileSize { min, max })
+            }
+            _ => Ok(()),
+        }?;
+
+        if let Some(AdaptiveDensity { min, max }) = self.adaptive {
+            if !(0.0 < min && min <= max && max < 1.0) {
+                return Err(OptionsError::InvalidDensity { min, max });
+            }
+        }
+
+        if let Some(demo) = &self.demo {
+            let times = [demo.move_time, demo.board_time];
+            if !(demo.idle > 0.0 && times.iter().all(|&time| time >= 0.0)) {
+                return Err(OptionsError::InvalidDemo {
+                    idle: demo.idle,
+                    move_time: demo.move_time,
+                    board_time: demo.board_time,
+                });
+            }
+        }
+
+        if let Some((first, second)) = self.input.conflict() {
+            return Err(OptionsError::DuplicateBinding { first, second });
+        }
+
+        let Some(topology) = topology::get(&self.topology) else {
+            return Err(OptionsError::UnknownTopology(self.topology.clone()));
+        };
+        topology
+            .validate(dim.as_ivec2())
+            .map_err(|reason| OptionsError::Topology {
+                name: self.topology.clone(),
+                reason,
+            })
+    }
+
+    pub fn validated(self) -> Result<Self, OptionsError> {
+        self.validate().map(|_| self)
+    }
+
+    /// Read options from a RON file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, OptionsError> {
+        let path = path.as_ref();
+        let config_error = |reason: String| OptionsError::Config {
+            path: path.display().to_string(),
+            reason,
+        };
+
+        let source = std::fs::read_to_string(path).map_err(|e| config_error(e.to_string()))?;
+        ron::from_str(&source).map_err(|e| config_error(e.to_string()))
+    }
+
+    /// Options saved in the config directory, `None` if there are none
+    pub fn from_storage(storage: &Storage) -> Result<Option<Self>, OptionsError> {
+        let config_error = |reason: String| OptionsError::Config {
+            path: OPTIONS_FILE.to_owned(),
+            reason,
+        };
+
+        storage
+            .read(Location::Config, OPTIONS_FILE)
+            .map_err(|e| config_error(e.to_string()))?
+            .map(|source| ron::from_str(&source).map_err(|e| config_error(e.to_string())))
+            .transpose()
+    }
+
+    /// Build options from command line arguments (program name excluded):
+    ///
+    /// - `--config <path>` load a RON options file, later arguments override it
+    /// - `--mode <classic|zen|hardcore|versus|nonogram>`
+    /// - `--difficulty <easy|medium|expert>`
+    /// - `--width <n>`, `--height <n>`, `--mines <n>`
+    /// - `--padding <px>`
+    /// - `--hand <left|right>` controls preset
+    /// - `--rotate <0|90|180|270>` counterclockwise, `--mirror <true|false>`
+    /// - `--topology <square|torus|hex|triangle|layered>`, or any registered tiling
+    /// - `--bot <none|perfect|probabilistic|noisy>` opponent of the versus mode
+    /// - `--adaptive <off|min-max>` bounds of the adaptive mine density, in percent
+    /// - `--skin <name|default>` skin pack in `assets/skins`, `default` for the built-in look
+    /// - `--numbers <digits|dice|roman>` glyphs of the numbers
+    ///
+    /// `--profile`, `--replay`, `--engine`, `--rng`, `--host`, `--join`, `--seed` and `--role`
+    /// take a value too, but are read elsewhere and skipped here.
+    pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<Self, OptionsError> {
+        Self::from_args_over(Self::default(), args)
+    }
+
+    /// Same as [`Self::from_args`], starting from `options` instead of the defaults
+    pub fn from_args_over(
+        options: Self,
+        args: impl IntoIterator<Item = String>,
+    ) -> Result<Self, OptionsError> {
+        OptionOverrides::parse(args)?.apply(options)
+    }
+}
+
+/// One option of the command line, see [`BoardOptions::from_args`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionOverride {
+    Config(PathBuf),
+    Mode(GameMode),
+    Difficulty(Difficulty),
+    Width(u32),
+    Height(u32),
+    Mines(u32),
+    Padding(f32),
+    Hand(InputMap),
+    Rotate(Rotation),
+    Mirror(bool),
+    Topology(String),
+    Bot(Option<BotSkill>),
+    Adaptive(Option<AdaptiveDensity>),
+    Skin(Option<String>),
+    Numbers(NumberGlyphs),
+}
+
+/// Options of the command line in order, parsed once at startup so that the debug reload applies
+/// them again over the new file
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OptionOverrides(pub Vec<OptionOverride>);
+
+impl OptionOverrides {
+    /// Parse command line arguments (program name excluded), see [`BoardOptions::from_args`]
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Self, OptionsError> {
+        fn parse<T: std::str::FromStr>(arg: &str, value: &str) -> Result<T, OptionsError>
+        where
+            T::Err: Display,
+        {
+            value.parse().map_err(|e: T::Err| OptionsError::Argument {
+                arg: arg.to_owned(),
+                reason: format!("{value:?}: {e}"),
+            })
+        }
+
+        let mut overrides = Vec::new();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            let value = args.next().ok_or_else(|| OptionsError::Argument {
+                arg: arg.clone(),
+                reason: "missing value".to_owned(),
+            })?;
+
+            let option = match arg.as_str() {
+                "--config" => OptionOverride::Config(value.into()),
+                // picks the storage, see `Profiles::open`
+                "--profile" => continue,
+                // watched once the game is up, see `WatchReplay`
+                "--replay" => continue,
+                // started with the game, see `EngineCommand`
+                "--engine" => continue,
+                // set before anything is drawn, see `rng::set_backend`
+                "--rng" => continue,
+                // opens the multiplayer session, see `SessionArgs`
+                "--host" | "--join" | "--seed" | "--role" => continue,
+                "--mode" => OptionOverride::Mode(match value.to_lowercase().as_str() {
+                    "classic" => GameMode::Classic,
+                    "zen" => GameMode::Zen,
+                    "hardcore" => GameMode::Hardcore,
+                    "versus" => GameMode::Versus,
+                    "nonogram" => GameMode::Nonogram,
+                    _ => {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("unknown mode {value:?}"),
+                        })
+                    }
+                }),
+                "--difficulty" => OptionOverride::Difficulty(match value.to_lowercase().as_str() {
+                    "easy" => Difficulty::EASY,
+                    "medium" => Difficulty::MEDIUM,
+                    "expert" => Difficulty::EXPERT,
+                    _ => {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("unknown difficulty {value:?}"),
+                        })
+                    }
+                }),
+                "--width" => OptionOverride::Width(parse(&arg, &value)?),
+                "--height" => OptionOverride::Height(parse(&arg, &value)?),
+                "--mines" => OptionOverride::Mines(parse(&arg, &value)?),
+                "--padding" => OptionOverride::Padding(parse(&arg, &value)?),
+                "--hand" => OptionOverride::Hand(match value.to_lowercase().as_str() {
+                    "left" => InputMap::LEFT_HANDED,
+                    "right" => InputMap::RIGHT_HANDED,
+                    _ => {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("expected left or right, got {value:?}"),
+                        })
+                    }
+                }),
+                "--rotate" => OptionOverride::Rotate(match value.as_str() {
+                    "0" => Rotation::Deg0,
+                    "90" => Rotation::Deg90,
+                    "180" => Rotation::Deg180,
+                    "270" => Rotation::Deg270,
+                    _ => {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("expected 0, 90, 180 or 270, got {value:?}"),
+                        })
+                    }
+                }),
+                "--mirror" => OptionOverride::Mirror(parse(&arg, &value)?),
+                // checked with the rest of the options, tilings may be registered later
+                "--topology" => OptionOverride::Topology(value.to_lowercase()),
+                "--bot" => OptionOverride::Bot(match value.to_lowercase().as_str() {
+                    "none" => None,
+                    "perfect" => Some(BotSkill::Perfect),
+                    "probabilistic" => Some(BotSkill::Probabilistic),
+                    "noisy" => Some(BotSkill::Noisy),
+                    _ => {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("unknown bot {value:?}"),
+                        })
+                    }
+                }),
+                "--adaptive" => OptionOverride::Adaptive(match value.to_lowercase().as_str() {
+                    "off" => None,
+                    bounds => {
+                        let Some((min, max)) = bounds.split_once('-') else {
+                            return Err(OptionsError::Argument {
+                                arg,
+                                reason: format!("expected off or min-max, got {value:?}"),
+                            });
+                        };
+                        let min: f32 = parse(&arg, min)?;
+                        let max: f32 = parse(&arg, max)?;
+                        Some(AdaptiveDensity {
+                            min: min / 100.0,
+                            max: max / 100.0,
+                        })
+                    }
+                }),
+                "--skin" => OptionOverride::Skin(Some(value).filter(|skin| skin != "default")),
+                "--numbers" => {
+                    let glyphs = NumberGlyphs::ALL
+                        .into_iter()
+                        .find(|glyphs| glyphs.name().eq_ignore_ascii_case(&value));
+                    let Some(glyphs) = glyphs else {
+                        return Err(OptionsError::Argument {
+                            arg,
+                            reason: format!("unknown numbers {value:?}"),
+                        });
+                    };
+                    OptionOverride::Numbers(glyphs)
+                }
+                _ => {
+                    return Err(OptionsError::Argument {
+                        arg,
+                        reason: "unknown option".to_owned(),
+                    })
+                }
+            };
+            overrides.push(option);
+        }
+
+        Ok(Self(overrides))
+    }
+
+    /// Options over `options`, in the order of the command line
+    pub fn apply(&self, mut options: BoardOptions) -> Result<BoardOptions, OptionsError> {
+        for option in &self.0 {
+            match option {
+                OptionOverride::Config(path) => options = BoardOptions::load(path)?,
+                OptionOverride::Mode(mode) => options.mode = *mode,
+                OptionOverride::Difficulty(difficulty) => options.difficulty = difficulty.clone(),
+                OptionOverride::Width(width) => options.difficulty.dim.x = *width,
+                OptionOverride::Height(height) => options.difficulty.dim.y = *height,
+                OptionOverride::Mines(n_mines) => options.difficulty.n_mines = *n_mines,
+                OptionOverride::Padding(padding) => options.tile_padding = *padding,
+                OptionOverride::Hand(input) => options.input = input.clone(),
+                OptionOverride::Rotate(rotation) => options.view.rotation = *rotation,
+                OptionOverride::Mirror(mirror) => options.view.mirror = *mirror,
+                OptionOverride::Topology(topology) => options.topology = topology.clone(),
+                OptionOverride::Bot(bot) => options.bot = *bot,
+                OptionOverride::Adaptive(adaptive) => options.adaptive = *adaptive,
+                OptionOverride::Skin(skin) => options.skin = skin.clone(),
+                OptionOverride::Numbers(glyphs) => options.number_glyphs = *glyphs,
+            }
+        }
+        Ok(options)
+    }
+}
+
+impl Display for OptionsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OptionsError::EmptyBoard { dim } => {
+                write!(f, "The board must not be empty, got {}x{}", dim.x, dim.y)
+            }
+            OptionsError::BoardTooLarge { dim } => write!(
+                f,
+                "The board can be at most {MAX_DIM}x{MAX_DIM}, got {}x{}",
+                dim.x, dim.y
+            ),
+            OptionsError::TooManyMines { n_mines, n_tiles } => write!(
+                f,
+                "{n_mines} mines do not fit on {n_tiles} tiles, at least one tile must be safe"
+            ),
+            OptionsError::NegativePadding(padding) => {
+                write!(f, "Tile padding must not be negative, got {padding}")
+            }
+            OptionsError::NonPositiveTileSize(size) => {
+                write!(f, "Tile size must be positive, got {size}")
+            }
+            OptionsError::InvertedTileSize { min, max } => write!(
+                f,
+                "Adaptive tile size minimum {min} is larger than its maximum {max}"
+            ),
+            OptionsError::InvalidDensity { min, max } => write!(
+                f,
+                "Adaptive mine density must go from more than 0 to less than 1, got {min} to {max}"
+            ),
+            OptionsError::InvalidDemo {
+                idle,
+                move_time,
+                board_time,
+            } => write!(
+                f,
+                "Demo times must not be negative and the idle time must be positive, got {idle}, \
+                 {move_time} and {board_time}"
+            ),
+            OptionsError::Config { path, reason } => {
+                write!(f, "Could not load options from {path}: {reason}")
+            }
+            OptionsError::Argument { arg, reason } => write!(f, "Invalid argument {arg}: {reason}"),
+            OptionsError::UnknownTopology(name) => write!(
+                f,
+                "Unknown topology {name:?}, expected one of {}",
+                topology::names().join(", ")
+            ),
+            OptionsError::Topology { name, reason } => {
+                write!(f, "The {name} topology does not fit the board: {reason}")
+            }
+            OptionsError::DuplicateBinding { first, second } => {
+                write!(f, "{first} and {second} are bound to the same input")
+            }
+        }
+    }
+}
+
+impl std::error::Error for OptionsError {}
+
+impl DisplayParams {
+    /// Coordinate of the tile under a world position, if any
+    pub fn coord_at(&self, world: Vec2) -> Option<IVec2> {
+        let local = world - self.position.truncate();
+        if !(local.cmpge(Vec2::ZERO).all() && local.cmplt(self.board_size).all()) {
+            return None;
+        }
+        let dim = self.dim.as_ivec2();
+        let point = self
+            .view
+            .unplace(local / self.tile_size, self.topology.extent(dim));
+        self.topology.coord_at(point, dim)
+    }
+
+    /// Center of a tile relative to the board origin
+    pub fn tile_center(&self, coord: IVec2) -> Vec2 {
+        let dim = self.dim.as_ivec2();
+        let point = self.topology.position(coord, dim);
+        self.view.place(point, self.topology.extent(dim)) * self.tile_size
+    }
 }
 
 impl Default for TileSize {
@@ -115,11 +996,377 @@ impl Default for BoardPosition {
 impl Default for BoardOptions {
     fn default() -> Self {
         Self {
+            mode: GameMode::Classic,
             difficulty: Difficulty::MEDIUM,
             position: Default::default(),
             tile_size: Default::default(),
             tile_padding: 3.0,
             safe_start: true,
+            highlight_last_move: true,
+            confirm_proven_mines: false,
+            click_on_release: false,
+            question_marks: false,
+            line_counts: false,
+            coordinates: false,
+            reduce_motion: false,
+            bug_reports: true,
+            view: Default::default(),
+            topology: SQUARE.to_owned(),
+            adaptive: None,
+            bot: None,
+            skin: None,
+            number_glyphs: NumberGlyphs::Digits,
+            input: InputMap::default(),
+            demo: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        math::{ivec2, uvec2, vec2},
+        prelude::MouseButton,
+    };
+
+    use super::{
+        AdaptiveDensity, BoardOptions, BoardView, Difficulty, GameMode, NumberGlyphs,
+        OptionOverride, OptionOverrides, OptionsError, Rotation, TileSize,
+    };
+    use crate::{resources::demo::DemoMode, solver::BotSkill};
+
+    fn with_difficulty(dim: [u32; 2], n_mines: u32) -> BoardOptions {
+        BoardOptions {
+            difficulty: Difficulty {
+                dim: dim.into(),
+                n_mines,
+            },
+            ..Default::default()
         }
     }
+
+    #[test]
+    fn test_validate() {
+        assert_eq!(BoardOptions::default().validate(), Ok(()));
+        assert_eq!(
+            with_difficulty([0, 9], 0).validate(),
+            Err(OptionsError::EmptyBoard { dim: uvec2(0, 9) })
+        );
+        assert_eq!(
+            with_difficulty([3, 3], 9).validate(),
+            Err(OptionsError::TooManyMines {
+                n_mines: 9,
+                n_tiles: 9
+            })
+        );
+
+        let options = BoardOptions {
+            tile_padding: -1.0,
+            ..Default::default()
+        };
+        assert_eq!(options.validate(), Err(OptionsError::NegativePadding(-1.0)));
+
+        let options = BoardOptions {
+            tile_size: TileSize::Adaptive {
+                min: 50.0,
+                max: 10.0,
+            },
+            ..Default::default()
+        };
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::InvertedTileSize {
+                min: 50.0,
+                max: 10.0
+            })
+        );
+
+        let options = BoardOptions {
+            adaptive: Some(AdaptiveDensity { min: 0.3, max: 0.2 }),
+            ..Default::default()
+        };
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::InvalidDensity { min: 0.3, max: 0.2 })
+        );
+
+        let options = BoardOptions {
+            demo: Some(DemoMode {
+                idle: 0.0,
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            options.validate(),
+            Err(OptionsError::InvalidDemo { idle, .. }) if idle == 0.0
+        ));
+        let options = BoardOptions {
+            demo: Some(DemoMode::default()),
+            ..Default::default()
+        };
+        assert_eq!(options.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_clamp() {
+        let mut options = BoardOptions {
+            tile_padding: -1.0,
+            tile_size: TileSize::Adaptive {
+                min: 50.0,
+                max: 10.0,
+            },
+            adaptive: Some(AdaptiveDensity { min: 0.3, max: 0.2 }),
+            ..with_difficulty([3, 3], 12)
+        };
+        assert_eq!(
+            options.clamp(),
+            vec![
+                OptionsError::TooManyMines {
+                    n_mines: 12,
+                    n_tiles: 9
+                },
+                OptionsError::NegativePadding(-1.0),
+                OptionsError::InvertedTileSize {
+                    min: 50.0,
+                    max: 10.0
+                },
+                OptionsError::InvalidDensity { min: 0.3, max: 0.2 },
+            ]
+        );
+        assert_eq!(options.difficulty.n_mines, 8);
+        assert_eq!(options.tile_padding, 0.0);
+        assert!(matches!(
+            options.tile_size,
+            TileSize::Adaptive { min, max } if min == 10.0 && max == 50.0
+        ));
+        assert_eq!(
+            options.adaptive,
+            Some(AdaptiveDensity { min: 0.2, max: 0.3 })
+        );
+        assert_eq!(options.validate(), Ok(()));
+        assert_eq!(options.clamp(), vec![]);
+
+        // no safe value to pick, left to `validate`
+        let mut options = with_difficulty([0, 9], 3);
+        assert_eq!(options.clamp(), vec![]);
+        assert!(options.validate().is_err());
+    }
+
+    #[test]
+    fn test_adaptive() {
+        let args = ["--adaptive", "10-20"].map(String::from);
+        let adaptive = BoardOptions::from_args(args).unwrap().adaptive.unwrap();
+        assert_eq!(adaptive, AdaptiveDensity { min: 0.1, max: 0.2 });
+        assert_eq!(adaptive.n_mines(0.0, uvec2(10, 10)), 10);
+        assert_eq!(adaptive.n_mines(1.0, uvec2(10, 10)), 20);
+        assert_eq!(adaptive.n_mines(0.5, uvec2(10, 10)), 15);
+        assert!((adaptive.skill(0.15) - 0.5).abs() < 1e-6);
+        // a tiny board keeps a mine and a safe tile
+        assert_eq!(adaptive.n_mines(0.0, uvec2(2, 1)), 1);
+
+        let args = ["--adaptive", "10-20", "--adaptive", "off"].map(String::from);
+        assert_eq!(BoardOptions::from_args(args).unwrap().adaptive, None);
+        let args = ["--adaptive", "lots"].map(String::from);
+        assert!(BoardOptions::from_args(args).is_err());
+    }
+
+    #[test]
+    fn test_number_glyphs() {
+        let labels = |glyphs: NumberGlyphs| (1..=8).map(|n| glyphs.label(n)).collect::<Vec<_>>();
+        assert_eq!(
+            labels(NumberGlyphs::Digits),
+            ["1", "2", "3", "4", "5", "6", "7", "8"]
+        );
+        assert_eq!(labels(NumberGlyphs::Dice), labels(NumberGlyphs::Digits));
+        assert_eq!(
+            labels(NumberGlyphs::Roman),
+            ["I", "II", "III", "IV", "V", "VI", "VII", "VIII"]
+        );
+        assert_eq!(NumberGlyphs::Roman.label(49), "XLIX");
+        assert_eq!(NumberGlyphs::Roman.label(0), "");
+
+        let options: BoardOptions = ron::from_str("(number_glyphs: Dice)").unwrap();
+        assert_eq!(options.number_glyphs, NumberGlyphs::Dice);
+        let args = ["--numbers", "roman"].map(String::from);
+        let options = BoardOptions::from_args(args).unwrap();
+        assert_eq!(options.number_glyphs, NumberGlyphs::Roman);
+        assert!(BoardOptions::from_args(["--numbers", "tally"].map(String::from)).is_err());
+    }
+
+    #[test]
+    fn test_from_args() {
+        let args = ["--difficulty", "expert", "--mines", "120"].map(String::from);
+        let options = BoardOptions::from_args(args).unwrap();
+        assert_eq!(options.difficulty.dim, uvec2(30, 16));
+        assert_eq!(options.difficulty.n_mines, 120);
+
+        let args = ["--hand", "left"].map(String::from);
+        assert!(BoardOptions::from_args(args)
+            .unwrap()
+            .input
+            .is_left_handed());
+
+        let args = ["--mode", "zen"].map(String::from);
+        assert_eq!(BoardOptions::from_args(args).unwrap().mode, GameMode::Zen);
+
+        let args = ["--mode", "versus", "--bot", "noisy"].map(String::from);
+        let options = BoardOptions::from_args(args).unwrap();
+        assert_eq!(options.bot, Some(BotSkill::Noisy));
+
+        let args = ["--skin", "retro", "--skin", "default"].map(String::from);
+        assert_eq!(BoardOptions::from_args(args).unwrap().skin, None);
+        let args = ["--skin", "retro"].map(String::from);
+        assert_eq!(
+            BoardOptions::from_args(args).unwrap().skin.as_deref(),
+            Some("retro")
+        );
+
+        let args = ["--rotate", "90", "--mirror", "true"].map(String::from);
+        let view = BoardOptions::from_args(args).unwrap().view;
+        assert_eq!(view.rotation, Rotation::Deg90);
+        assert!(view.mirror);
+
+        let args = ["--width", "wide"].map(String::from);
+        assert!(matches!(
+            BoardOptions::from_args(args),
+            Err(OptionsError::Argument { .. })
+        ));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let args = ["--rng", "xorshift", "--width", "12", "--mode", "zen"].map(String::from);
+        let overrides = OptionOverrides::parse(args).unwrap();
+        assert_eq!(
+            overrides.0,
+            [
+                OptionOverride::Width(12),
+                OptionOverride::Mode(GameMode::Zen)
+            ]
+        );
+
+        // applied again over other options, as the debug reload does
+        let options = BoardOptions {
+            tile_padding: 3.0,
+            ..Default::default()
+        };
+        let options = overrides.apply(options).unwrap();
+        assert_eq!(options.difficulty.dim.x, 12);
+        assert_eq!((options.mode, options.tile_padding), (GameMode::Zen, 3.0));
+
+        let args = ["--mines"].map(String::from);
+        assert!(OptionOverrides::parse(args).is_err());
+    }
+
+    #[test]
+    fn test_missing_fields() {
+        let options: BoardOptions =
+            ron::from_str("(difficulty: (dim: (5, 4), n_mines: 3), tile_padding: 1.0)").unwrap();
+        assert_eq!(options.difficulty.dim, uvec2(5, 4));
+        assert_eq!(options.tile_padding, 1.0);
+        assert!(options.safe_start && options.highlight_last_move);
+        assert!(!options.confirm_proven_mines);
+
+        let options: BoardOptions = ron::from_str("(input: (uncover: Right))").unwrap();
+        assert_eq!(options.input.uncover, MouseButton::Right);
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::DuplicateBinding {
+                first: "uncover",
+                second: "flag"
+            })
+        );
+
+        let options: BoardOptions =
+            ron::from_str("(input: (uncover: Right, flag: Left, slots: (delete: C)))").unwrap();
+        assert_eq!(
+            options.validate(),
+            Err(OptionsError::DuplicateBinding {
+                first: "slots.duplicate",
+                second: "slots.delete"
+            })
+        );
+    }
+
+    #[test]
+    fn test_view() {
+        let dim = uvec2(4, 3);
+        for rotation in [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ] {
+            for mirror in [false, true] {
+                let view = BoardView { rotation, mirror };
+                let display_dim = view.display_dim(dim).as_ivec2();
+                for coord in (0..3).flat_map(|y| (0..4).map(move |x| ivec2(x, y))) {
+                    let display = view.to_display(coord, dim);
+                    assert!(display.cmpge(ivec2(0, 0)).all() && display.cmplt(display_dim).all());
+                    assert_eq!(view.to_board(display, dim), coord);
+                }
+            }
+        }
+
+        let view = BoardView {
+            rotation: Rotation::Deg90,
+            mirror: false,
+        };
+        // the bottom right corner turns to the top right
+        assert_eq!(view.to_display(ivec2(3, 0), dim), ivec2(2, 3));
+
+        let options = BoardOptions {
+            difficulty: Difficulty { dim, n_mines: 1 },
+            tile_size: TileSize::Fixed(10.0),
+            view,
+            ..Default::default()
+        };
+        let params = options.display_params(vec2(800.0, 600.0));
+        assert_eq!(params.board_size, vec2(30.0, 40.0));
+        // picking undoes the rotation
+        let world = params.position.truncate() + vec2(25.0, 35.0);
+        assert_eq!(params.coord_at(world), Some(ivec2(3, 0)));
+        assert_eq!(params.tile_center(ivec2(3, 0)), vec2(25.0, 35.0));
+    }
+
+    #[test]
+    fn test_topology() {
+        let options = BoardOptions {
+            difficulty: Difficulty {
+                dim: uvec2(5, 4),
+                n_mines: 1,
+            },
+            tile_size: TileSize::Fixed(10.0),
+            topology: "hex".to_owned(),
+            view: BoardView {
+                rotation: Rotation::Deg90,
+                mirror: true,
+            },
+            ..Default::default()
+        };
+        assert_eq!(options.validate(), Ok(()));
+        let params = options.display_params(vec2(800.0, 600.0));
+        for coord in (0..4).flat_map(|y| (0..5).map(move |x| ivec2(x, y))) {
+            let world = params.position.truncate() + params.tile_center(coord);
+            assert_eq!(params.coord_at(world), Some(coord));
+        }
+
+        let unknown = BoardOptions {
+            topology: "moebius".to_owned(),
+            ..Default::default()
+        };
+        assert!(matches!(
+            unknown.validate(),
+            Err(OptionsError::UnknownTopology(_))
+        ));
+        let layered = BoardOptions {
+            topology: "layered".to_owned(),
+            ..with_difficulty([4, 4], 2)
+        };
+        assert!(matches!(
+            layered.validate(),
+            Err(OptionsError::Topology { .. })
+        ));
+    }
 }
@@ -0,0 +1,194 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Mutex,
+    },
+};
+
+use bevy::{prelude::UVec2, tasks::AsyncComputeTaskPool};
+
+use super::{board::TileMap, board_options::BoardOptions, board_state::BoardState};
+use crate::{
+    rng::{self, RngBackend},
+    topology,
+};
+
+/// Boards kept ready ahead of the next games
+pub const QUEUE_LEN: usize = 3;
+
+/// What a board is generated from, boards of another key are thrown away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BoardKey {
+    pub dim: UVec2,
+    pub n_mines: u32,
+    /// Name of the tiling
+    pub topology: &'static str,
+    pub backend: RngBackend,
+}
+
+impl BoardKey {
+    pub fn new(options: &BoardOptions, backend: RngBackend) -> Self {
+        Self {
+            dim: options.difficulty.dim,
+            n_mines: options.difficulty.n_mines,
+            topology: options.topology().name(),
+            backend,
+        }
+    }
+}
+
+/// Board generated ahead, with the seed of its mines
+#[derive(Debug, Clone)]
+pub struct QueuedBoard {
+    pub seed: u64,
+    pub tile_map: TileMap,
+    pub state: BoardState,
+}
+
+impl QueuedBoard {
+    /// The board `BoardPlugin::create_board` would make of `seed`, for a key of validated options
+    pub fn generate(key: BoardKey, seed: u64) -> Self {
+        let mut tile_map = TileMap::random_with(
+            key.dim.x,
+            key.dim.y,
+            key.n_mines,
+            &mut *key.backend.seeded(seed),
+        )
+        .expect("keys come from validated options")
+        .with_topology(topology::get(key.topology).unwrap_or_default());
+        let state = BoardState::new(&mut tile_map);
+        Self {
+            seed,
+            tile_map,
+            state,
+        }
+    }
+}
+
+/// Upcoming boards of the options on screen, generated on the async compute task pool so that a
+/// new game starts at once however long its board takes. The queue is emptied when the options
+/// change, and the boards still being generated for the old ones are dropped as they arrive
+#[derive(Debug)]
+pub struct BoardQueue {
+    key: Option<BoardKey>,
+    ready: VecDeque<QueuedBoard>,
+    // boards asked for and not received yet, of any key
+    pending: usize,
+    sender: Sender<(BoardKey, QueuedBoard)>,
+    receiver: Mutex<Receiver<(BoardKey, QueuedBoard)>>,
+}
+
+impl Default for BoardQueue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            key: None,
+            ready: VecDeque::new(),
+            pending: 0,
+            sender,
+            receiver: Mutex::new(receiver),
+        }
+    }
+}
+
+impl BoardQueue {
+    pub fn len(&self) -> usize {
+        self.ready.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ready.is_empty()
+    }
+
+    /// Next board ready for `key`, if any
+    pub fn pop(&mut self, key: BoardKey) -> Option<QueuedBoard> {
+        self.follow(key);
+        self.ready.pop_front()
+    }
+
+    /// Collect the boards generated since the last call, and start generating more for `key`
+    /// until the queue is full
+    pub fn refill(&mut self, key: BoardKey) {
+        self.follow(key);
+        let received = self.receiver.get_mut().unwrap_or_else(|e| e.into_inner());
+        for (generated, board) in received.try_iter() {
+            self.pending -= 1;
+            if generated == key {
+                self.ready.push_back(board);
+            }
+        }
+
+        // boards of the old options still count, so that switching back and forth stays cheap
+        let missing = QUEUE_LEN.saturating_sub(self.ready.len() + self.pending);
+        for _ in 0..missing {
+            let sender = self.sender.clone();
+            let seed = rng::entropy().next_u64();
+            self.pending += 1;
+            AsyncComputeTaskPool::get()
+                .spawn(async move {
+                    // the queue may be gone with the app
+                    let _ = sender.send((key, QueuedBoard::generate(key, seed)));
+                })
+                .detach();
+        }
+    }
+
+    /// Forget the boards of other options
+    fn follow(&mut self, key: BoardKey) {
+        if self.key != Some(key) {
+            self.key = Some(key);
+            self.ready.clear();
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{math::uvec2, tasks::TaskPool};
+
+    use super::{BoardKey, BoardQueue, QueuedBoard, QUEUE_LEN};
+    use crate::{
+        resources::{board::TileMap, board_options::BoardOptions, board_state::BoardState},
+        rng::RngBackend,
+    };
+
+    #[test]
+    fn test_queue() {
+        bevy::tasks::AsyncComputeTaskPool::init(TaskPool::new);
+        let mut options = BoardOptions::default();
+        let key = BoardKey::new(&options, RngBackend::Nanorand);
+
+        let board = QueuedBoard::generate(key, 7);
+        let mut expected = TileMap::random_with(
+            key.dim.x,
+            key.dim.y,
+            key.n_mines,
+            &mut *RngBackend::Nanorand.seeded(7),
+        )
+        .unwrap();
+        assert_eq!(board.tile_map.to_string(), expected.to_string());
+        assert_eq!(board.state.bbbv(), BoardState::new(&mut expected).bbbv());
+
+        let mut queue = BoardQueue::default();
+        assert!(queue.pop(key).is_none());
+        while queue.len() < QUEUE_LEN {
+            queue.refill(key);
+            std::thread::yield_now();
+        }
+        let board = queue.pop(key).unwrap();
+        assert_eq!(
+            board.tile_map.dim().to_array(),
+            [key.dim.x as i32, key.dim.y as i32]
+        );
+        assert_eq!(queue.len(), QUEUE_LEN - 1);
+
+        // new options empty the queue
+        options.difficulty.dim = uvec2(12, 7);
+        options.difficulty.n_mines = 11;
+        let other = BoardKey::new(&options, RngBackend::Nanorand);
+        assert_ne!(other, key);
+        assert!(queue.pop(other).is_none());
+        assert!(queue.is_empty());
+    }
+}
@@ -0,0 +1,16 @@
+use bevy::prelude::IVec2;
+
+use super::board::TileState;
+
+/// Buffers reused by the flood fill and the board sync, cleared between uses but never freed, so
+/// that cascades allocate nothing once the buffers have grown to the size of the board
+#[derive(Debug, Default, Clone)]
+pub struct BoardScratch {
+    /// Tiles left to uncover in [`BoardState::click_into`](super::board_state::BoardState::click_into)
+    pub stack: Vec<IVec2>,
+    /// Tiles revealed by the last click, in the order they were
+    pub revealed: Vec<(IVec2, TileState)>,
+    /// Tiles changed since the last sync, swapped with the ones the `BoardState` records, see
+    /// [`BoardState::swap_changes`](super::board_state::BoardState::swap_changes)
+    pub changes: Vec<IVec2>,
+}
@@ -0,0 +1,17 @@
+use super::board_options::{Difficulty, GameMode};
+
+/// Seed the current board was generated from, see `TileMap::seeded`. Missing while the board
+/// comes from a file, as resumed games and library boards do
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct BoardSeed(pub u64);
+
+/// Board to generate instead of a random one of the `BoardOptions`, taken by the next board
+/// created. Always on squares, whatever the topology of the options, so that the players
+/// sharing a seed share the board. The options are left alone, so the board after it is a usual
+/// one again
+#[derive(Debug, Clone)]
+pub struct SeededBoard {
+    pub seed: u64,
+    pub mode: GameMode,
+    pub difficulty: Difficulty,
+}
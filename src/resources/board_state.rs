@@ -0,0 +1,881 @@
+use bevy::prelude::IVec2;
+
+use super::{
+    board::{TileMap, TileState},
+    board_scratch::BoardScratch,
+};
+use crate::{
+    hexcells::Line,
+    topology::{Neighbors, TileCoord, Topology},
+};
+
+/// What the player sees of a tile
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cover {
+    #[default]
+    Covered,
+    Flagged,
+    /// Marked as uncertain, see `RuleSet::question_marks`
+    Questioned,
+    Revealed,
+}
+
+/// Mines of every row and column not accounted for yet, see [`BoardState::line_counts`]
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct LineCounts {
+    /// From the bottom row up
+    pub rows: Vec<i32>,
+    /// From the left column
+    pub columns: Vec<i32>,
+}
+
+/// Rules side of the board: the content and cover of every tile, free of entities.
+///
+/// Game logic mutates the state, and the board plugin applies the tiles changed since the last
+/// frame to the tile entities, so that rules never have to care about sprites.
+#[derive(Debug, Clone, Default)]
+pub struct BoardState {
+    dim: IVec2,
+    tiles: Vec<TileState>,
+    covers: Vec<Cover>,
+    // safe tiles not revealed yet
+    hidden_safe: usize,
+    n_mines: usize,
+    n_flags: usize,
+    // see `bbbv_clicks`
+    bbbv_clicks: Vec<Option<u32>>,
+    bbbv_done: Vec<bool>,
+    bbbv_progress: u32,
+    // coordinates changed since the last call to `take_changes`, may contain duplicates
+    changed: Vec<IVec2>,
+    topology: &'static dyn Topology,
+    // holes and line hints of a puzzle, see `set_puzzle`
+    gaps: Vec<IVec2>,
+    lines: Vec<Line>,
+}
+
+impl BoardState {
+    pub fn new(tile_map: &mut TileMap) -> Self {
+        let tiles = (0..tile_map.len())
+            .map(|idx| tile_map.state_at(idx))
+            .collect::<Vec<_>>();
+        let hidden_safe = tiles.iter().filter(|&&t| t != TileState::Mine).count();
+        let n_mines = tiles.len() - hidden_safe;
+
+        let mut state = Self {
+            dim: tile_map.dim(),
+            covers: vec![Cover::Covered; tiles.len()],
+            tiles,
+            hidden_safe,
+            n_mines,
+            topology: tile_map.topology(),
+            ..Default::default()
+        };
+        let (clicks, bbbv) = state.bbbv_clicks();
+        state.bbbv_clicks = clicks;
+        state.bbbv_done = vec![false; bbbv as usize];
+        state
+    }
+
+    pub fn dim(&self) -> IVec2 {
+        self.dim
+    }
+
+    pub fn topology(&self) -> &'static dyn Topology {
+        self.topology
+    }
+
+    /// Make the board a puzzle: `gaps` are revealed and show no number, and `lines` give the
+    /// mines of their rows, see [`crate::hexcells`]
+    pub fn set_puzzle(&mut self, gaps: Vec<IVec2>, lines: Vec<Line>) {
+        for &gap in &gaps {
+            self.uncover(gap);
+        }
+        self.gaps = gaps;
+        self.lines = lines;
+    }
+
+    /// Holes of the puzzle, revealed from the start
+    pub fn gaps(&self) -> &[IVec2] {
+        &self.gaps
+    }
+
+    /// Whether the tile at `coord` is a hole of the puzzle, which shows no number
+    pub fn is_gap<T: Into<IVec2>>(&self, coord: T) -> bool {
+        self.gaps.contains(&coord.into())
+    }
+
+    /// Line hints of the puzzle
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Tiles of a line hint: its row, without the gaps
+    pub fn line_tiles(&self, line: Line) -> impl Iterator<Item = IVec2> + '_ {
+        (0..self.dim.x)
+            .map(move |x| IVec2::new(x, line.row as i32))
+            .filter(|&coord| !self.is_gap(coord))
+    }
+
+    fn index(&self, coord: IVec2) -> Option<usize> {
+        self.topology.index(coord, self.dim)
+    }
+
+    fn coord(&self, idx: usize) -> IVec2 {
+        self.topology.coord(idx, self.dim)
+    }
+
+    /// `coord` in the coordinates of the tiling
+    pub fn native<T: Into<IVec2>>(&self, coord: T) -> TileCoord {
+        self.topology.native(coord.into(), self.dim)
+    }
+
+    /// Storage coordinate of a tile given in the coordinates of the tiling, `None` if out of
+    /// the board or written for another tiling
+    pub fn from_native(&self, native: TileCoord) -> Option<IVec2> {
+        self.topology.coord_of_native(native, self.dim)
+    }
+
+    /// Content of the tile at `coord`, `None` if out of the board
+    pub fn tile<T: Into<IVec2>>(&self, coord: T) -> Option<TileState> {
+        self.index(coord.into()).map(|idx| self.tiles[idx])
+    }
+
+    /// Cover of the tile at `coord`, `None` if out of the board
+    pub fn cover<T: Into<IVec2>>(&self, coord: T) -> Option<Cover> {
+        self.index(coord.into()).map(|idx| self.covers[idx])
+    }
+
+    /// Cover of every tile by flat index, row by row from the bottom
+    pub fn covers(&self) -> &[Cover] {
+        &self.covers
+    }
+
+    fn set_cover(&mut self, idx: usize, cover: Cover) {
+        let coord = self.coord(idx);
+        match (self.covers[idx], cover) {
+            (Cover::Flagged, Cover::Flagged) => {}
+            (Cover::Flagged, _) => self.n_flags -= 1,
+            (_, Cover::Flagged) => self.n_flags += 1,
+            _ => {}
+        }
+        self.covers[idx] = cover;
+        self.changed.push(coord);
+    }
+
+    /// Reveal the tile at `coord` if it is covered and not flagged, returning its content
+    pub fn uncover<T: Into<IVec2>>(&mut self, coord: T) -> Option<TileState> {
+        let idx = self.index(coord.into())?;
+        if !matches!(self.covers[idx], Cover::Covered | Cover::Questioned) {
+            return None;
+        }
+
+        self.set_cover(idx, Cover::Revealed);
+        let tile = self.tiles[idx];
+        if tile != TileState::Mine {
+            self.hidden_safe -= 1;
+        }
+        if let Some(click) = self.bbbv_clicks[idx] {
+            let done = std::mem::replace(&mut self.bbbv_done[click as usize], true);
+            self.bbbv_progress += u32::from(!done);
+        }
+        Some(tile)
+    }
+
+    /// Flag or unflag the covered tile at `coord`, returning whether anything changed
+    pub fn toggle_flag<T: Into<IVec2>>(&mut self, coord: T) -> bool {
+        self.cycle_mark(coord, false)
+    }
+
+    /// Move the covered tile at `coord` to its next mark: a flag, then a question mark if
+    /// enabled, then none. Returns whether anything changed
+    pub fn cycle_mark<T: Into<IVec2>>(&mut self, coord: T, question_marks: bool) -> bool {
+        let Some(idx) = self.index(coord.into()) else {
+            return false;
+        };
+
+        let next = match self.covers[idx] {
+            Cover::Covered => Cover::Flagged,
+            Cover::Flagged if question_marks => Cover::Questioned,
+            Cover::Flagged | Cover::Questioned => Cover::Covered,
+            Cover::Revealed => return false,
+        };
+        self.set_cover(idx, next);
+        true
+    }
+
+    /// Tiles a chord on `coord` uncovers: the unflagged covered neighbors of a revealed number
+    /// with as many flags around it. Empty for any other tile
+    pub fn chord_targets<T: Into<IVec2>>(&self, coord: T) -> Neighbors {
+        let coord = coord.into();
+        let mut targets = Neighbors::new();
+        let (Some(Cover::Revealed), Some(TileState::Clear(n))) =
+            (self.cover(coord), self.tile(coord))
+        else {
+            return targets;
+        };
+
+        let flags = self
+            .neighbors(coord)
+            .filter(|&neighbor| self.cover(neighbor) == Some(Cover::Flagged))
+            .count();
+        if n == 0 || flags != n as usize {
+            return targets;
+        }
+        for neighbor in self.neighbors(coord) {
+            if matches!(
+                self.cover(neighbor),
+                Some(Cover::Covered | Cover::Questioned)
+            ) {
+                targets.push(neighbor);
+            }
+        }
+        targets
+    }
+
+    /// Uncover `coord` the way a click does, at once: flooding through empty tiles and, with
+    /// `chords`, uncovering the [`Self::chord_targets`] as well. Returns the tiles revealed
+    pub fn click<T: Into<IVec2>>(&mut self, coord: T, chords: bool) -> Vec<(IVec2, TileState)> {
+        let mut scratch = BoardScratch::default();
+        self.click_into(coord, chords, &mut scratch);
+        scratch.revealed
+    }
+
+    /// [`Self::click`] into the buffers of `scratch`, allocating nothing once they have grown.
+    /// Returns the tiles revealed, kept in `scratch` until the next click
+    pub fn click_into<'s, T: Into<IVec2>>(
+        &mut self,
+        coord: T,
+        chords: bool,
+        scratch: &'s mut BoardScratch,
+    ) -> &'s [(IVec2, TileState)] {
+        let coord = coord.into();
+        let BoardScratch {
+            stack, revealed, ..
+        } = scratch;
+        stack.clear();
+        revealed.clear();
+        stack.push(coord);
+        if chords {
+            stack.extend(self.chord_targets(coord));
+        }
+        while let Some(coord) = stack.pop() {
+            let Some(tile) = self.uncover(coord) else {
+                continue;
+            };
+            // question marks are no flags, the cascade goes through them
+            if tile == TileState::Clear(0) {
+                stack.extend(self.neighbors(coord).filter(|&n| {
+                    matches!(self.cover(n), Some(Cover::Covered | Cover::Questioned))
+                }));
+            }
+            revealed.push((coord, tile));
+        }
+        revealed
+    }
+
+    /// Reveal every mine, flagged or not
+    pub fn reveal_mines(&mut self) {
+        for idx in 0..self.tiles.len() {
+            if self.tiles[idx] == TileState::Mine && self.covers[idx] != Cover::Revealed {
+                self.set_cover(idx, Cover::Revealed);
+            }
+        }
+    }
+
+    /// Reveal the mine at `coord`, flagged or not. Other tiles are left alone
+    pub fn reveal_mine<T: Into<IVec2>>(&mut self, coord: T) {
+        if let Some(idx) = self.index(coord.into()) {
+            if self.tiles[idx] == TileState::Mine && self.covers[idx] != Cover::Revealed {
+                self.set_cover(idx, Cover::Revealed);
+            }
+        }
+    }
+
+    /// Coordinates of the mines not revealed yet
+    pub fn hidden_mines(&self) -> impl Iterator<Item = IVec2> + '_ {
+        (0..self.tiles.len())
+            .filter(|&idx| {
+                self.tiles[idx] == TileState::Mine && self.covers[idx] != Cover::Revealed
+            })
+            .map(|idx| self.coord(idx))
+    }
+
+    pub fn n_mines(&self) -> usize {
+        self.n_mines
+    }
+
+    /// Flags currently placed, right or wrong
+    pub fn n_flags(&self) -> usize {
+        self.n_flags
+    }
+
+    /// More flags than mines, so at least one flag is wrong
+    pub fn is_overflagged(&self) -> bool {
+        self.n_flags > self.n_mines
+    }
+
+    /// Share of the safe tiles revealed, from 0 to 1
+    pub fn progress(&self) -> f32 {
+        let n_safe = self.tiles.len() - self.n_mines;
+        1.0 - self.hidden_safe as f32 / n_safe.max(1) as f32
+    }
+
+    /// Safe tiles revealed so far
+    pub fn n_revealed(&self) -> usize {
+        self.tiles.len() - self.n_mines - self.hidden_safe
+    }
+
+    /// Whether every safe tile is revealed
+    pub fn is_cleared(&self) -> bool {
+        self.hidden_safe == 0
+    }
+
+    /// Whether every mine, and nothing else, is flagged
+    pub fn is_fully_flagged(&self) -> bool {
+        self.n_flags == self.n_mines
+            && self
+                .tiles
+                .iter()
+                .zip(&self.covers)
+                .all(|(&tile, &cover)| (tile == TileState::Mine) == (cover == Cover::Flagged))
+    }
+
+    /// Mines of every row and column, less the flags and revealed mines on it. Negative when a
+    /// line holds more flags than mines
+    pub fn line_counts(&self) -> LineCounts {
+        self.count_lines(|mine, cover| match (mine, cover) {
+            (true, Cover::Covered | Cover::Questioned) => 1,
+            (false, Cover::Flagged) => -1,
+            _ => 0,
+        })
+    }
+
+    /// Mines of every row and column, whatever the player did
+    pub fn line_totals(&self) -> LineCounts {
+        self.count_lines(|mine, _| i32::from(mine))
+    }
+
+    /// Sum of `count(is_mine, cover)` over the tiles of every row and column
+    fn count_lines(&self, count: impl Fn(bool, Cover) -> i32) -> LineCounts {
+        let mut counts = LineCounts {
+            rows: vec![0; self.dim.y as usize],
+            columns: vec![0; self.dim.x as usize],
+        };
+        for (idx, (&tile, &cover)) in self.tiles.iter().zip(&self.covers).enumerate() {
+            let n = count(tile == TileState::Mine, cover);
+            let coord = self.coord(idx);
+            counts.rows[coord.y as usize] += n;
+            counts.columns[coord.x as usize] += n;
+        }
+        counts
+    }
+
+    /// In-board coordinates of the tiles touching `coord`, up to 8 on squares
+    pub fn neighbors<T: Into<IVec2>>(&self, coord: T) -> Neighbors {
+        self.topology.neighbors(coord.into(), self.dim)
+    }
+
+    /// Bechtel's Board Benchmark Value: the least number of clicks clearing the board, one per
+    /// opening plus one per number not bordering an opening
+    pub fn bbbv(&self) -> u32 {
+        self.bbbv_done.len() as u32
+    }
+
+    /// Clicks of the [`Self::bbbv`] already made, in whatever order the player made them
+    pub fn bbbv_progress(&self) -> u32 {
+        self.bbbv_progress
+    }
+
+    /// For every tile, the 3BV click revealing it: the opening of zeros, or the lone number.
+    /// Mines and numbers bordering an opening are `None`, revealing them clicks nothing
+    fn bbbv_clicks(&self) -> (Vec<Option<u32>>, u32) {
+        let zero = |coord: IVec2| self.tile(coord) == Some(TileState::Clear(0));
+        let mut clicks = vec![None; self.tiles.len()];
+        let mut seen = vec![false; self.tiles.len()];
+        let mut count = 0;
+
+        for idx in 0..self.tiles.len() {
+            let coord = self.coord(idx);
+            if seen[idx] || !zero(coord) {
+                continue;
+            }
+
+            // flood the opening, marking its border numbers as cleared by the same click
+            seen[idx] = true;
+            clicks[idx] = Some(count);
+            let mut stack = vec![coord];
+            while let Some(coord) = stack.pop() {
+                for neighbor in self.neighbors(coord) {
+                    let idx = self.index(neighbor).unwrap();
+                    if !seen[idx] {
+                        seen[idx] = true;
+                        if zero(neighbor) {
+                            clicks[idx] = Some(count);
+                            stack.push(neighbor);
+                        }
+                    }
+                }
+            }
+            count += 1;
+        }
+
+        for idx in 0..self.tiles.len() {
+            if !seen[idx] && self.tiles[idx] != TileState::Mine {
+                clicks[idx] = Some(count);
+                count += 1;
+            }
+        }
+        (clicks, count)
+    }
+
+    /// Go back to an earlier state of the same board. Tiles covered differently count as changed
+    pub fn rewind(&mut self, earlier: &BoardState) {
+        let mut changed = std::mem::take(&mut self.changed);
+        changed.extend(
+            (0..self.covers.len())
+                .filter(|&idx| self.covers[idx] != earlier.covers[idx])
+                .map(|idx| self.coord(idx)),
+        );
+        *self = Self {
+            changed,
+            ..earlier.clone()
+        };
+    }
+
+    /// Put back the covers of the tiles at the flat indices of `covers`, as recorded from
+    /// [`Self::covers`] before a move, to take the move back. Tiles covered differently count as
+    /// changed
+    pub fn restore_covers(&mut self, covers: &[(usize, Cover)]) {
+        for &(idx, cover) in covers {
+            self.set_cover(idx, cover);
+        }
+        self.recount();
+    }
+
+    /// Sandbox edit: put any cover on the tile at `coord`, revealing a mine as well without
+    /// ending anything. Returns whether anything changed
+    pub fn edit_cover<T: Into<IVec2>>(&mut self, coord: T, cover: Cover) -> bool {
+        let Some(idx) = self.index(coord.into()) else {
+            return false;
+        };
+        if self.covers[idx] == cover {
+            return false;
+        }
+        self.set_cover(idx, cover);
+        self.recount();
+        true
+    }
+
+    /// Sandbox edit: move the mine at `from` to the safe tile at `to`, numbering the tiles
+    /// around both again. Returns the tiles whose content changed, `None` if `from` is no mine
+    /// or `to` no safe tile
+    pub fn move_mine<T: Into<IVec2>>(&mut self, from: T, to: T) -> Option<Vec<IVec2>> {
+        let (from, to) = (from.into(), to.into());
+        let (from_idx, to_idx) = (self.index(from)?, self.index(to)?);
+        if self.tiles[from_idx] != TileState::Mine || self.tiles[to_idx] == TileState::Mine {
+            return None;
+        }
+        self.tiles[from_idx] = TileState::Clear(0);
+        self.tiles[to_idx] = TileState::Mine;
+
+        let mut changed = vec![from, to];
+        changed.extend(self.neighbors(from).chain(self.neighbors(to)));
+        changed.sort_by_key(|coord| (coord.y, coord.x));
+        changed.dedup();
+        for &coord in &changed {
+            let idx = self.index(coord).unwrap();
+            if self.tiles[idx] != TileState::Mine {
+                let mines = self
+                    .neighbors(coord)
+                    .filter(|&neighbor| self.tile(neighbor) == Some(TileState::Mine))
+                    .count();
+                self.tiles[idx] = TileState::Clear(mines as u8);
+            }
+        }
+        self.changed.extend(&changed);
+        self.recount();
+        Some(changed)
+    }
+
+    /// Counts kept up by the moves, from scratch after a sandbox edit
+    fn recount(&mut self) {
+        let revealed = |idx: usize| self.covers[idx] == Cover::Revealed;
+        self.hidden_safe = (0..self.tiles.len())
+            .filter(|&idx| self.tiles[idx] != TileState::Mine && !revealed(idx))
+            .count();
+
+        let (clicks, bbbv) = self.bbbv_clicks();
+        let mut done = vec![false; bbbv as usize];
+        for (idx, click) in clicks.iter().enumerate() {
+            if let (Some(click), true) = (click, revealed(idx)) {
+                done[*click as usize] = true;
+            }
+        }
+        self.bbbv_progress = done.iter().filter(|&&done| done).count() as u32;
+        self.bbbv_clicks = clicks;
+        self.bbbv_done = done;
+    }
+
+    /// One line per row, the first row on top, for logs and bug reports. Revealed tiles show
+    /// their number, `.` for none and `*` for a mine, covered ones `#`, `F` when flagged and `?`
+    /// when questioned. With `solution`, covered tiles show what is under them instead
+    pub fn to_ascii(&self, solution: bool) -> String {
+        let mut ascii = String::with_capacity(self.tiles.len() * 2);
+        let coords = (0..self.dim.y).flat_map(|y| (0..self.dim.x).map(move |x| IVec2::new(x, y)));
+        for coord in coords {
+            let idx = self.index(coord).unwrap();
+            let c = match (self.tiles[idx], self.covers[idx]) {
+                (_, Cover::Flagged) if !solution => 'F',
+                (_, Cover::Questioned) if !solution => '?',
+                (_, Cover::Covered) if !solution => '#',
+                (TileState::Mine, _) => '*',
+                (TileState::Clear(0), _) => '.',
+                (TileState::Clear(n), _) => char::from_digit(n as u32, 10).unwrap_or('+'),
+            };
+            ascii.push(c);
+            let end_of_row = coord.x == self.dim.x - 1;
+            ascii.push(if end_of_row { '\n' } else { ' ' });
+        }
+        ascii
+    }
+
+    pub fn has_changes(&self) -> bool {
+        !self.changed.is_empty()
+    }
+
+    /// Coordinates of the tiles changed since the previous call
+    pub fn take_changes(&mut self) -> Vec<IVec2> {
+        std::mem::take(&mut self.changed)
+    }
+
+    /// Move the tiles changed since the previous call into `buffer`, the state recording the next
+    /// ones in its old allocation: swapping with the same buffer every frame allocates nothing
+    /// once both have grown
+    pub fn swap_changes(&mut self, buffer: &mut Vec<IVec2>) {
+        buffer.clear();
+        std::mem::swap(&mut self.changed, buffer);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{BoardState, Cover, LineCounts};
+    use crate::{
+        resources::{
+            board::{TileMap, TileState},
+            board_scratch::BoardScratch,
+        },
+        topology::{Neighbors, Square, Topology},
+    };
+
+    fn state() -> BoardState {
+        // * 1 .
+        // 1 1 .
+        let mut map = TileMap::empty(3, 2).unwrap();
+        map.tile([0, 0]).set_state(TileState::Mine);
+        for coord in [[1, 0], [0, 1], [1, 1]] {
+            map.tile(coord).set_state(TileState::Clear(1));
+        }
+        BoardState::new(&mut map)
+    }
+
+    #[test]
+    fn test_uncover() {
+        let mut state = state();
+        assert_eq!(state.uncover([2, 0]), Some(TileState::Clear(0)));
+        assert_eq!(state.uncover([2, 0]), None);
+        assert_eq!(state.cover([2, 0]), Some(Cover::Revealed));
+
+        assert!(state.toggle_flag([1, 1]));
+        assert_eq!(state.uncover([1, 1]), None);
+        assert!(state.toggle_flag([1, 1]));
+        assert!(!state.toggle_flag([2, 0]));
+
+        for coord in [[1, 0], [0, 1], [1, 1]] {
+            assert!(!state.is_cleared());
+            state.uncover(coord);
+        }
+        assert!(!state.is_cleared());
+        assert_eq!(state.progress(), 0.8);
+        state.uncover([2, 1]);
+        assert!(state.is_cleared());
+        assert_eq!(state.progress(), 1.0);
+    }
+
+    #[test]
+    fn test_bbbv() {
+        // * 1 .
+        // 1 1 .   one opening, plus the number out of its reach
+        assert_eq!(state().bbbv(), 2);
+
+        // * 2 *   no opening, every number takes a click
+        // 1 2 1
+        let mut map = TileMap::with_mines(3, 2, [[0, 0], [2, 0]]).unwrap();
+        assert_eq!(BoardState::new(&mut map).bbbv(), 4);
+
+        let mut state = state();
+        // a number bordering the opening does not open it
+        state.uncover([1, 0]);
+        assert_eq!(state.bbbv_progress(), 0);
+        state.uncover([2, 1]);
+        assert_eq!(state.bbbv_progress(), 1);
+        state.uncover([0, 1]);
+        assert_eq!(state.bbbv_progress(), 2);
+    }
+
+    #[test]
+    fn test_flags() {
+        let mut state = state();
+        assert_eq!((state.n_flags(), state.n_mines()), (0, 1));
+
+        state.toggle_flag([0, 0]);
+        assert!(!state.is_overflagged());
+        state.toggle_flag([2, 0]);
+        assert_eq!(state.n_flags(), 2);
+        assert!(state.is_overflagged());
+
+        state.toggle_flag([2, 0]);
+        assert_eq!(state.n_flags(), 1);
+        assert_eq!(state.hidden_mines().collect::<Vec<_>>(), [IVec2::new(0, 0)]);
+        // revealing the flagged mine takes its flag away
+        state.reveal_mines();
+        assert_eq!(state.n_flags(), 0);
+    }
+
+    #[test]
+    fn test_changes() {
+        let mut state = state();
+        assert!(!state.has_changes());
+
+        state.toggle_flag([0, 0]);
+        state.uncover([1, 0]);
+        state.reveal_mines();
+        assert_eq!(
+            state.take_changes(),
+            [IVec2::new(0, 0), IVec2::new(1, 0), IVec2::new(0, 0)]
+        );
+        assert_eq!(state.cover([0, 0]), Some(Cover::Revealed));
+        assert!(!state.has_changes());
+
+        let mut neighbors = state.neighbors([0, 0]).collect::<Vec<_>>();
+        neighbors.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(
+            neighbors,
+            [IVec2::new(1, 0), IVec2::new(0, 1), IVec2::new(1, 1)]
+        );
+    }
+
+    #[test]
+    fn test_rewind() {
+        let mut state = state();
+        state.toggle_flag([0, 0]);
+        state.take_changes();
+        let earlier = state.clone();
+
+        state.uncover([2, 0]);
+        state.reveal_mines();
+        state.take_changes();
+        state.rewind(&earlier);
+
+        assert_eq!(state.cover([0, 0]), Some(Cover::Flagged));
+        assert_eq!(state.cover([2, 0]), Some(Cover::Covered));
+        assert_eq!((state.n_flags(), state.progress()), (1, 0.0));
+        let mut changes = state.take_changes();
+        changes.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(changes, [IVec2::new(0, 0), IVec2::new(2, 0)]);
+    }
+
+    #[test]
+    fn test_restore_covers() {
+        let mut state = state();
+        state.toggle_flag([0, 0]);
+        state.take_changes();
+        let before = state.covers().to_vec();
+
+        state.uncover([2, 0]);
+        state.toggle_flag([0, 0]);
+        state.take_changes();
+        let covers = before
+            .iter()
+            .zip(state.covers())
+            .enumerate()
+            .filter(|(_, (before, now))| before != now)
+            .map(|(idx, (&before, _))| (idx, before))
+            .collect::<Vec<_>>();
+        state.restore_covers(&covers);
+
+        assert_eq!(state.covers(), before);
+        assert_eq!((state.n_flags(), state.progress()), (1, 0.0));
+        let mut changes = state.take_changes();
+        changes.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(changes, [IVec2::new(0, 0), IVec2::new(2, 0)]);
+    }
+
+    #[test]
+    fn test_marks() {
+        let mut state = state();
+        assert!(state.cycle_mark([0, 0], true));
+        assert!(state.is_fully_flagged());
+        state.cycle_mark([0, 0], true);
+        assert_eq!(state.cover([0, 0]), Some(Cover::Questioned));
+        assert!(!state.is_fully_flagged());
+        state.cycle_mark([0, 0], true);
+        assert_eq!(state.cover([0, 0]), Some(Cover::Covered));
+
+        // question marks do not protect a tile
+        state.cycle_mark([2, 1], true);
+        state.cycle_mark([2, 1], true);
+        assert_eq!(state.uncover([2, 1]), Some(TileState::Clear(0)));
+
+        // nor do they stop a cascade
+        let mut state = self::state();
+        state.cycle_mark([1, 1], true);
+        state.cycle_mark([1, 1], true);
+        let mut scratch = BoardScratch::default();
+        let revealed = state.click_into([2, 0], false, &mut scratch);
+        assert!(revealed.contains(&(IVec2::new(1, 1), TileState::Clear(1))));
+    }
+
+    #[test]
+    fn test_chord() {
+        let mut state = state();
+        state.uncover([1, 1]);
+        // not enough flags yet
+        assert!(state.chord_targets([1, 1]).is_empty());
+
+        state.toggle_flag([0, 0]);
+        let mut targets = state.chord_targets([1, 1]).collect::<Vec<_>>();
+        targets.sort_by_key(|c| (c.y, c.x));
+        assert_eq!(
+            targets,
+            [
+                IVec2::new(1, 0),
+                IVec2::new(2, 0),
+                IVec2::new(0, 1),
+                IVec2::new(2, 1)
+            ]
+        );
+        assert!(state.chord_targets([0, 0]).is_empty());
+    }
+
+    #[test]
+    fn test_ascii() {
+        let mut state = state();
+        state.uncover([2, 0]);
+        state.toggle_flag([0, 0]);
+        state.cycle_mark([1, 1], true);
+        state.cycle_mark([1, 1], true);
+        assert_eq!(state.to_ascii(false), "F # .\n# ? #\n");
+        assert_eq!(state.to_ascii(true), "* 1 .\n1 1 .\n");
+    }
+
+    #[test]
+    fn test_line_counts() {
+        let mut state = state();
+        let counts = |rows: &[i32], columns: &[i32]| LineCounts {
+            rows: rows.to_vec(),
+            columns: columns.to_vec(),
+        };
+        assert_eq!(state.line_counts(), counts(&[1, 0], &[1, 0, 0]));
+
+        // a wrong flag is a mine too many
+        state.toggle_flag([1, 1]);
+        assert_eq!(state.line_counts(), counts(&[1, -1], &[1, -1, 0]));
+        state.toggle_flag([0, 0]);
+        assert_eq!(state.line_counts(), counts(&[0, -1], &[0, -1, 0]));
+        state.toggle_flag([0, 0]);
+        state.reveal_mine([0, 0]);
+        assert_eq!(state.line_counts(), counts(&[0, -1], &[0, -1, 0]));
+        assert_eq!(state.line_totals(), counts(&[1, 0], &[1, 0, 0]));
+    }
+
+    /// Square tiles stored column by column, to catch row-major index arithmetic
+    #[derive(Debug)]
+    struct ColumnMajor;
+
+    impl Topology for ColumnMajor {
+        fn name(&self) -> &str {
+            "column-major"
+        }
+
+        fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+            Square.neighbors(coord, dim)
+        }
+
+        fn index(&self, coord: IVec2, dim: IVec2) -> Option<usize> {
+            Square
+                .index(coord, dim)
+                .map(|_| (coord.x * dim.y + coord.y) as usize)
+        }
+
+        fn coord(&self, index: usize, dim: IVec2) -> IVec2 {
+            IVec2::new(index as i32 / dim.y, index as i32 % dim.y)
+        }
+    }
+
+    #[test]
+    fn test_storage_order() {
+        // 1 1 .
+        // * 1 .
+        let mut map = TileMap::empty(3, 2).unwrap().with_topology(&ColumnMajor);
+        map.tile([0, 1]).set_state(TileState::Mine);
+        for coord in [[0, 0], [1, 0], [1, 1]] {
+            map.tile(coord).set_state(TileState::Clear(1));
+        }
+        let mut state = BoardState::new(&mut map);
+        let earlier = state.clone();
+        assert_eq!(state.bbbv(), 2);
+        assert_eq!(state.hidden_mines().collect::<Vec<_>>(), [IVec2::new(0, 1)]);
+        assert_eq!(
+            state.line_totals(),
+            LineCounts {
+                rows: vec![0, 1],
+                columns: vec![1, 0, 0],
+            }
+        );
+        assert_eq!(state.to_ascii(true), "1 1 .\n* 1 .\n");
+
+        state.take_changes();
+        state.toggle_flag([1, 0]);
+        assert_eq!(state.take_changes(), [IVec2::new(1, 0)]);
+        state.rewind(&earlier);
+        assert_eq!(state.take_changes(), [IVec2::new(1, 0)]);
+    }
+
+    #[test]
+    fn test_sandbox_edits() {
+        let mut state = state();
+        state.take_changes();
+        assert!(state.edit_cover([2, 0], Cover::Revealed));
+        assert!(!state.edit_cover([2, 0], Cover::Revealed));
+        assert_eq!(state.bbbv_progress(), 1);
+        // a mine revealed in the sandbox ends nothing, and a tile covered again counts again
+        assert!(state.edit_cover([0, 0], Cover::Revealed));
+        assert!(state.edit_cover([2, 0], Cover::Flagged));
+        assert_eq!((state.n_flags(), state.bbbv_progress()), (1, 0));
+        assert!(!state.is_cleared());
+
+        // * 1 .    1 1 .
+        // 1 1 . -> * 1 .
+        assert_eq!(state.move_mine([1, 0], [2, 0]), None);
+        let changed = state.move_mine([0, 0], [0, 1]).unwrap();
+        assert_eq!(changed.len(), 4);
+        assert_eq!(state.tile([0, 0]), Some(TileState::Clear(1)));
+        assert_eq!(state.tile([0, 1]), Some(TileState::Mine));
+        assert_eq!(state.tile([1, 0]), Some(TileState::Clear(1)));
+        assert_eq!(state.n_mines(), 1);
+        // the mine left its cover behind
+        assert_eq!(state.cover([0, 0]), Some(Cover::Revealed));
+        assert!(state.take_changes().contains(&IVec2::new(0, 1)));
+
+        for coord in [[1, 0], [1, 1], [2, 1]] {
+            state.edit_cover(coord, Cover::Revealed);
+        }
+        state.edit_cover([2, 0], Cover::Revealed);
+        assert!(state.is_cleared());
+        assert_eq!(state.bbbv_progress(), state.bbbv());
+    }
+}
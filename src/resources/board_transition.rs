@@ -0,0 +1,164 @@
+use bevy::prelude::UVec2;
+
+/// Seconds for the old board to shrink away, and as many for the new one to grow in
+pub const TRANSITION_HALF: f32 = 0.25;
+
+/// Smallest scale of a board, reached right before the swap
+const MIN_SCALE: f32 = 0.2;
+
+/// Swap of the board for one of another difficulty, in phases:
+///
+/// 1. [`BoardTransition::Leaving`]: the old board shrinks and fades out, ignoring clicks
+/// 2. [`BoardTransition::Swapping`]: the old board is despawned and the new one generated and
+///    spawned, within a single frame
+/// 3. [`BoardTransition::Entering`]: the new board grows in, ignoring clicks
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub enum BoardTransition {
+    #[default]
+    Idle,
+    Leaving {
+        elapsed: f32,
+    },
+    Swapping,
+    Entering {
+        elapsed: f32,
+    },
+}
+
+impl BoardTransition {
+    /// Whether swapping a board of `old` tiles for one of `new` tiles deserves the animation.
+    /// New boards of the same size appear in place, as they always did
+    pub fn wanted(old: Option<UVec2>, new: UVec2, animate: bool) -> bool {
+        animate && old.is_some_and(|old| old != new)
+    }
+
+    /// The old board starts leaving, unless a transition is already under way
+    pub fn start(&mut self) {
+        if *self == Self::Idle {
+            *self = Self::Leaving { elapsed: 0.0 };
+        }
+    }
+
+    /// Let `delta` seconds pass, returning whether the old board is gone and the new one is due
+    pub fn advance(&mut self, delta: f32) -> bool {
+        match self {
+            Self::Idle | Self::Swapping => false,
+            Self::Leaving { elapsed } => {
+                *elapsed += delta;
+                if *elapsed >= TRANSITION_HALF {
+                    *self = Self::Swapping;
+                    return true;
+                }
+                false
+            }
+            Self::Entering { elapsed } => {
+                *elapsed += delta;
+                if *elapsed >= TRANSITION_HALF {
+                    *self = Self::Idle;
+                }
+                false
+            }
+        }
+    }
+
+    /// The new board is spawned, and starts growing in. Any other board swap ends the transition
+    pub fn swapped(&mut self) {
+        *self = match self {
+            Self::Swapping => Self::Entering { elapsed: 0.0 },
+            _ => Self::Idle,
+        };
+    }
+
+    /// Whether the board on screen takes clicks
+    pub fn is_idle(&self) -> bool {
+        *self == Self::Idle
+    }
+
+    /// Progress of the current phase, from 0 to 1
+    fn progress(&self) -> f32 {
+        match self {
+            Self::Idle => 1.0,
+            Self::Swapping => 0.0,
+            Self::Leaving { elapsed } | Self::Entering { elapsed } => {
+                (elapsed / TRANSITION_HALF).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Scale of the board on screen, easing out when growing and in when shrinking
+    pub fn scale(&self) -> f32 {
+        let size = match self {
+            Self::Idle => 1.0,
+            Self::Swapping => 0.0,
+            Self::Leaving { .. } => 1.0 - self.progress().powi(2),
+            Self::Entering { .. } => 1.0 - (1.0 - self.progress()).powi(2),
+        };
+        MIN_SCALE + (1.0 - MIN_SCALE) * size
+    }
+
+    /// Opacity of the old board as it fades out, the new board is opaque right away
+    pub fn alpha(&self) -> f32 {
+        match self {
+            Self::Leaving { .. } => 1.0 - self.progress(),
+            Self::Swapping => 0.0,
+            Self::Idle | Self::Entering { .. } => 1.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::math::uvec2;
+
+    use super::{BoardTransition, MIN_SCALE, TRANSITION_HALF};
+
+    #[test]
+    fn test_phases() {
+        assert!(BoardTransition::wanted(
+            Some(uvec2(9, 9)),
+            uvec2(16, 16),
+            true
+        ));
+        assert!(!BoardTransition::wanted(
+            Some(uvec2(9, 9)),
+            uvec2(9, 9),
+            true
+        ));
+        assert!(!BoardTransition::wanted(
+            Some(uvec2(9, 9)),
+            uvec2(16, 16),
+            false
+        ));
+        // the first board
+        assert!(!BoardTransition::wanted(None, uvec2(9, 9), true));
+
+        let mut transition = BoardTransition::default();
+        assert!(!transition.advance(1.0));
+        assert_eq!(transition.scale(), 1.0);
+        transition.start();
+        assert!(!transition.is_idle());
+        assert_eq!((transition.scale(), transition.alpha()), (1.0, 1.0));
+
+        assert!(!transition.advance(TRANSITION_HALF / 2.0));
+        assert!(transition.scale() < 1.0 && transition.scale() > MIN_SCALE);
+        assert_eq!(transition.alpha(), 0.5);
+        // a second switch while leaving does not start over
+        transition.start();
+        assert!(transition.advance(TRANSITION_HALF / 2.0));
+        assert_eq!(transition, BoardTransition::Swapping);
+        assert_eq!(transition.scale(), MIN_SCALE);
+        assert!(!transition.advance(1.0));
+
+        transition.swapped();
+        assert_eq!(transition, BoardTransition::Entering { elapsed: 0.0 });
+        assert_eq!((transition.scale(), transition.alpha()), (MIN_SCALE, 1.0));
+        assert!(!transition.advance(TRANSITION_HALF));
+        assert!(transition.is_idle());
+        assert_eq!(transition.scale(), 1.0);
+
+        // a board swapped without the animation interrupts it
+        transition.start();
+        transition.swapped();
+        assert!(transition.is_idle());
+    }
+}
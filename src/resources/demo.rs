@@ -0,0 +1,104 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::Vec2;
+use serde::{Deserialize, Serialize};
+
+/// Seconds of a full sweep of the camera across the board, slow enough to read the numbers
+const PAN_PERIOD: f32 = 40.0;
+
+/// Attract mode for kiosks and exhibitions: after a while on the pause menu without input, the
+/// solver plays random boards while the camera pans and the skins take turns, until any input
+/// brings the menu back. Off unless set in the options file, see `DemoPlugin`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DemoMode {
+    /// Seconds on the pause menu without input before the demo starts
+    pub idle: f32,
+    /// Seconds between two moves of the solver
+    pub move_time: f32,
+    /// Seconds a finished board stays on screen before the next one
+    pub board_time: f32,
+    /// How far the camera pans from the center, in pixels. Still with reduced motion
+    pub pan: f32,
+    /// Skin packs taking turns after the built-in look, one per board. Every pack found in
+    /// `assets/skins` when empty
+    pub skins: Vec<String>,
+}
+
+impl Default for DemoMode {
+    fn default() -> Self {
+        Self {
+            idle: 60.0,
+            move_time: 0.4,
+            board_time: 3.0,
+            pan: 60.0,
+            skins: Vec::new(),
+        }
+    }
+}
+
+impl DemoMode {
+    /// Offset of the camera `elapsed` seconds into the demo, a slow figure eight around the
+    /// center
+    pub fn pan(&self, elapsed: f32) -> Vec2 {
+        let phase = elapsed * TAU / PAN_PERIOD;
+        Vec2::new(phase.sin(), (2.0 * phase).sin() / 2.0) * self.pan
+    }
+
+    /// Skin of the `board`th board of the demo, `None` for the built-in look, among the packs
+    /// listed or else the ones `found`
+    pub fn skin(&self, board: usize, found: &[String]) -> Option<String> {
+        let skins = if self.skins.is_empty() {
+            found
+        } else {
+            &self.skins
+        };
+        match board % (skins.len() + 1) {
+            0 => None,
+            idx => Some(skins[idx - 1].clone()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::Vec2;
+
+    use super::{DemoMode, PAN_PERIOD};
+
+    #[test]
+    fn test_demo() {
+        let demo = DemoMode::default();
+        assert_eq!(demo.pan(0.0), Vec2::ZERO);
+        assert!(
+            demo.pan(PAN_PERIOD / 4.0)
+                .distance(Vec2::new(demo.pan, 0.0))
+                < 1e-3
+        );
+        assert!(demo.pan(PAN_PERIOD).length() < 1e-3);
+        for t in 0..100 {
+            assert!(demo.pan(t as f32).length() <= demo.pan * 1.2);
+        }
+
+        let found = vec!["classic".to_owned(), "neon".to_owned()];
+        let skins = (0..4)
+            .map(|board| demo.skin(board, &found))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            skins,
+            [
+                None,
+                Some("classic".to_owned()),
+                Some("neon".to_owned()),
+                None
+            ]
+        );
+        let listed = DemoMode {
+            skins: vec!["neon".to_owned()],
+            ..Default::default()
+        };
+        assert_eq!(listed.skin(1, &found), Some("neon".to_owned()));
+        assert_eq!(listed.skin(2, &found), None);
+        assert_eq!(demo.skin(7, &[]), None);
+    }
+}
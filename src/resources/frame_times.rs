@@ -0,0 +1,68 @@
+use std::collections::VecDeque;
+
+/// Frames in the graph of the performance HUD, the most recent ones
+pub const FRAME_HISTORY: usize = 120;
+
+/// Duration of the last frames, in seconds, oldest first
+#[derive(Debug, Default, Clone)]
+pub struct FrameTimes {
+    times: VecDeque<f32>,
+}
+
+impl FrameTimes {
+    pub fn push(&mut self, seconds: f32) {
+        if self.times.len() == FRAME_HISTORY {
+            self.times.pop_front();
+        }
+        self.times.push_back(seconds);
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = f32> + '_ {
+        self.times.iter().copied()
+    }
+
+    /// Average frame time, `None` before the first frame
+    pub fn average(&self) -> Option<f32> {
+        (!self.times.is_empty()).then(|| self.iter().sum::<f32>() / self.times.len() as f32)
+    }
+
+    /// Frames per second over the history
+    pub fn fps(&self) -> Option<f32> {
+        self.average()
+            .filter(|&average| average > 0.0)
+            .map(|average| 1.0 / average)
+    }
+
+    /// Longest frame of the history, the stutter an average hides
+    pub fn worst(&self) -> Option<f32> {
+        self.iter().reduce(f32::max)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{FrameTimes, FRAME_HISTORY};
+
+    #[test]
+    fn test_frame_times() {
+        let mut times = FrameTimes::default();
+        assert_eq!(
+            (times.average(), times.fps(), times.worst()),
+            (None, None, None)
+        );
+
+        times.push(0.02);
+        times.push(0.03);
+        times.push(0.01);
+        assert!((times.average().unwrap() - 0.02).abs() < 1e-6);
+        assert!((times.fps().unwrap() - 50.0).abs() < 1e-3);
+        assert_eq!(times.worst(), Some(0.03));
+
+        // the spike leaves the history
+        for _ in 0..FRAME_HISTORY {
+            times.push(0.01);
+        }
+        assert_eq!(times.iter().count(), FRAME_HISTORY);
+        assert_eq!(times.worst(), Some(0.01));
+    }
+}
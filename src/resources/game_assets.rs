@@ -0,0 +1,97 @@
+//! Paths of the assets the game cannot do without, and copies of them compiled in with the
+//! `embedded-assets` feature.
+
+use bevy::{
+    prelude::Image,
+    render::texture::{CompressedImageFormats, ImageType},
+    text::Font,
+};
+
+pub const FONT: &str = "fonts/robotoslab.ttf";
+pub const MINE_SPRITE: &str = "sprites/bomb.png";
+pub const FLAG_SPRITE: &str = "sprites/flag.png";
+
+/// Faces of the reset button, when the skin has none
+pub const FACE_IDLE: &str = "sprites/faces/idle.png";
+pub const FACE_PRESSED: &str = "sprites/faces/pressed.png";
+pub const FACE_WON: &str = "sprites/faces/won.png";
+pub const FACE_LOST: &str = "sprites/faces/lost.png";
+
+/// Dots of the numbers 1 to 8, see `NumberGlyphs::Dice`
+pub const DICE: [&str; 8] = [
+    "sprites/dice/1.png",
+    "sprites/dice/2.png",
+    "sprites/dice/3.png",
+    "sprites/dice/4.png",
+    "sprites/dice/5.png",
+    "sprites/dice/6.png",
+    "sprites/dice/7.png",
+    "sprites/dice/8.png",
+];
+
+/// Every asset watched by the `AssetWatchdogPlugin`
+pub const REQUIRED: [&str; 3] = [FONT, MINE_SPRITE, FLAG_SPRITE];
+
+/// A decoded asset, ready to replace one that failed to load
+pub enum Fallback {
+    Font(Font),
+    Image(Image),
+}
+
+/// Compiled in copy of the asset at `path`, `None` without the `embedded-assets` feature
+#[cfg_attr(not(feature = "embedded-assets"), allow(unused_variables))]
+pub fn embedded(path: &str) -> Option<&'static [u8]> {
+    #[cfg(feature = "embedded-assets")]
+    match path {
+        FONT => return Some(include_bytes!("../../assets/fonts/robotoslab.ttf")),
+        MINE_SPRITE => return Some(include_bytes!("../../assets/sprites/bomb.png")),
+        FLAG_SPRITE => return Some(include_bytes!("../../assets/sprites/flag.png")),
+        _ => {}
+    }
+    None
+}
+
+/// Compiled in copy of the asset at `path`, decoded
+pub fn fallback(path: &str) -> Option<Fallback> {
+    let bytes = embedded(path)?;
+    if path.ends_with(".ttf") {
+        Font::try_from_bytes(bytes.to_vec())
+            .ok()
+            .map(Fallback::Font)
+    } else {
+        let extension = path.rsplit('.').next()?;
+        Image::from_buffer(
+            bytes,
+            ImageType::Extension(extension),
+            CompressedImageFormats::NONE,
+            true,
+        )
+        .ok()
+        .map(Fallback::Image)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{fallback, Fallback, REQUIRED};
+
+    #[test]
+    fn test_fallback() {
+        for path in REQUIRED {
+            let fallback = fallback(path);
+            if !cfg!(feature = "embedded-assets") {
+                assert!(fallback.is_none());
+                continue;
+            }
+            match fallback {
+                Some(Fallback::Font(_)) => assert!(path.starts_with("fonts/")),
+                Some(Fallback::Image(image)) => {
+                    assert!(path.starts_with("sprites/"));
+                    assert!(image.size().x > 0.0);
+                }
+                None => panic!("no embedded copy of {path}"),
+            }
+        }
+        assert!(fallback("sprites/missing.png").is_none());
+    }
+}
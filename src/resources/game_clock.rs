@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+/// Speeds offered by the playback controls, slowest first
+pub const SPEEDS: [f32; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+
+/// Time of the game itself: game timers, split times, gameplay animations, the bot and replays
+/// run on it instead of the real `Time`, so that it can be paused, scaled and moved one action
+/// at a time. Menus, toasts and file watchers stay on real time. Must be used as a resource,
+/// ticked once a frame by `BoardPlugin`, or by hand in tests
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameClock {
+    scale: f32,
+    paused: bool,
+    /// A step was asked for, taken on the next tick
+    step_asked: bool,
+    stepping: bool,
+    elapsed: f64,
+    delta: f32,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        Self {
+            scale: 1.0,
+            paused: false,
+            step_asked: false,
+            stepping: false,
+            elapsed: 0.0,
+            delta: 0.0,
+        }
+    }
+}
+
+impl GameClock {
+    /// Advance by `real` seconds of real time
+    pub fn tick(&mut self, real: f32) {
+        self.stepping = std::mem::take(&mut self.step_asked);
+        self.delta = if self.paused { 0.0 } else { real * self.scale };
+        self.elapsed += self.delta as f64;
+    }
+
+    /// Seconds of game time since the last tick, 0 while paused
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta
+    }
+
+    /// Game time since the last tick, for `Timer::tick`
+    pub fn delta(&self) -> Duration {
+        Duration::from_secs_f32(self.delta)
+    }
+
+    /// Seconds of game time since startup
+    pub fn elapsed_seconds(&self) -> f64 {
+        self.elapsed
+    }
+
+    /// Jump ahead to `elapsed`, once a step took the next action
+    pub fn advance_to(&mut self, elapsed: f64) {
+        self.elapsed = self.elapsed.max(elapsed);
+    }
+
+    /// Multiple of real time
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Clamped to the range of [`SPEEDS`]
+    pub fn set_scale(&mut self, scale: f32) {
+        self.scale = scale.clamp(SPEEDS[0], SPEEDS[SPEEDS.len() - 1]);
+    }
+
+    /// Next speed of [`SPEEDS`] up
+    pub fn faster(&mut self) {
+        let next = SPEEDS.into_iter().find(|&speed| speed > self.scale);
+        self.set_scale(next.unwrap_or(self.scale));
+    }
+
+    /// Next speed of [`SPEEDS`] down
+    pub fn slower(&mut self) {
+        let next = SPEEDS.into_iter().rev().find(|&speed| speed < self.scale);
+        self.set_scale(next.unwrap_or(self.scale));
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Real time, running
+    pub fn resume_real_time(&mut self) {
+        self.scale = 1.0;
+        self.paused = false;
+    }
+
+    /// Take a single action on the next tick, pausing after it
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.step_asked = true;
+    }
+
+    /// Whether the single action asked with [`GameClock::step`] is due this frame. Whoever
+    /// plays takes its next action now, whatever its time, then moves the clock to it with
+    /// [`GameClock::advance_to`]
+    pub fn is_stepping(&self) -> bool {
+        self.stepping
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{GameClock, SPEEDS};
+
+    #[test]
+    fn test_clock() {
+        let mut clock = GameClock::default();
+        clock.tick(0.5);
+        assert_eq!((clock.delta_seconds(), clock.elapsed_seconds()), (0.5, 0.5));
+
+        clock.faster();
+        clock.faster();
+        clock.tick(0.5);
+        assert_eq!((clock.scale(), clock.elapsed_seconds()), (4.0, 2.5));
+        for _ in 0..10 {
+            clock.faster();
+        }
+        assert_eq!(clock.scale(), SPEEDS[SPEEDS.len() - 1]);
+
+        clock.set_scale(0.3);
+        clock.slower();
+        assert_eq!(clock.scale(), 0.25);
+        clock.set_paused(true);
+        clock.tick(1.0);
+        assert_eq!((clock.delta_seconds(), clock.elapsed_seconds()), (0.0, 2.5));
+
+        // a step lasts a single tick
+        clock.step();
+        assert!(!clock.is_stepping());
+        clock.tick(1.0);
+        assert!(clock.is_stepping() && clock.is_paused());
+        clock.advance_to(4.0);
+        clock.tick(1.0);
+        assert!(!clock.is_stepping());
+        assert_eq!(clock.elapsed_seconds(), 4.0);
+
+        clock.resume_real_time();
+        clock.tick(1.0);
+        assert_eq!(clock.elapsed_seconds(), 5.0);
+    }
+}
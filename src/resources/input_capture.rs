@@ -0,0 +1,23 @@
+use std::collections::HashSet;
+
+/// Overlays keeping the clicks and keys from the board, e.g. the pause menu or an open screen.
+/// Each overlay keeps its own entry up to date, so that the board checks this alone rather than
+/// knowing every overlay
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct InputCapture(HashSet<&'static str>);
+
+impl InputCapture {
+    /// Record whether the overlay `holder` takes the input
+    pub fn set(&mut self, holder: &'static str, captured: bool) {
+        if captured {
+            self.0.insert(holder);
+        } else {
+            self.0.remove(holder);
+        }
+    }
+
+    /// Whether any overlay takes the input
+    pub fn is_captured(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
@@ -0,0 +1,228 @@
+use std::collections::VecDeque;
+
+use bevy::{prelude::IVec2, utils::Instant};
+
+/// Clicks kept by the performance HUD, the most recent ones
+pub const LATENCY_HISTORY: usize = 20;
+
+/// Frames a click may take to show before its probe is dropped, e.g. a click on a revealed tile
+pub const PROBE_TIMEOUT: u64 = 30;
+
+/// Step of a click on its way to the screen, in the order they happen
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LatencyStage {
+    /// `BoardPlugin::handle_input` sent the `TileTriggerEvent`
+    Event,
+    /// The `BoardState` has the tile uncovered
+    State,
+    /// `BoardPlugin::sync_board` hid the cover of the tile
+    Sprite,
+    /// The cover is hidden for the renderer, after visibility propagation
+    Visible,
+}
+
+impl LatencyStage {
+    pub const ALL: [LatencyStage; 4] = [
+        LatencyStage::Event,
+        LatencyStage::State,
+        LatencyStage::Sprite,
+        LatencyStage::Visible,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LatencyStage::Event => "event",
+            LatencyStage::State => "state",
+            LatencyStage::Sprite => "sprite",
+            LatencyStage::Visible => "visible",
+        }
+    }
+}
+
+/// Time from the click to a stage
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct StageTime {
+    /// Frames after the one the click was read in, 0 when in the same frame
+    pub frames: u32,
+    pub seconds: f32,
+}
+
+/// Every stage of a click that got to the screen
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LatencySample {
+    pub stages: [StageTime; 4],
+}
+
+impl LatencySample {
+    pub fn stage(&self, stage: LatencyStage) -> StageTime {
+        self.stages[stage as usize]
+    }
+
+    /// From the click to the screen
+    pub fn total(&self) -> StageTime {
+        self.stage(LatencyStage::Visible)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Probe {
+    frame: u64,
+    start: Instant,
+    /// Tile whose cover the probe waits for
+    coord: Option<IVec2>,
+    stages: [Option<StageTime>; 4],
+}
+
+/// Debug only: latency of the clicks uncovering tiles, from the frame the button was read to the
+/// cover hidden for the renderer, see `PerfHudPlugin`. Board systems mark the stages they reach
+/// when the resource exists, one click at a time
+#[derive(Debug, Default, Clone)]
+pub struct InputLatency {
+    frame: u64,
+    probe: Option<Probe>,
+    samples: VecDeque<LatencySample>,
+}
+
+impl InputLatency {
+    /// Count a frame, dropping the probe of a click that never showed
+    pub fn next_frame(&mut self) {
+        self.frame += 1;
+        if let Some(probe) = &self.probe {
+            if self.frame - probe.frame > PROBE_TIMEOUT {
+                self.probe = None;
+            }
+        }
+    }
+
+    /// Follow a click read at `now`, in place of the previous one
+    pub fn press(&mut self, now: Instant) {
+        self.probe = Some(Probe {
+            frame: self.frame,
+            start: now,
+            coord: None,
+            stages: [None; 4],
+        });
+    }
+
+    /// Tile the click is waiting for, to check its cover
+    pub fn coord(&self) -> Option<IVec2> {
+        self.probe.as_ref().and_then(|probe| probe.coord)
+    }
+
+    /// Whether the click reached `stage`
+    pub fn reached(&self, stage: LatencyStage) -> bool {
+        self.probe
+            .as_ref()
+            .is_some_and(|probe| probe.stages[stage as usize].is_some())
+    }
+
+    /// The click reached `stage` at `now`, on the tile at `coord` if given. Stages reached
+    /// before are kept, and the last one completes the sample
+    pub fn mark(&mut self, stage: LatencyStage, coord: Option<IVec2>, now: Instant) {
+        let frame = self.frame;
+        let Some(probe) = &mut self.probe else {
+            return;
+        };
+        let time = &mut probe.stages[stage as usize];
+        if time.is_some() {
+            return;
+        }
+        *time = Some(StageTime {
+            frames: (frame - probe.frame) as u32,
+            seconds: now.saturating_duration_since(probe.start).as_secs_f32(),
+        });
+        if coord.is_some() {
+            probe.coord = coord;
+        }
+        if stage != LatencyStage::Visible {
+            return;
+        }
+
+        // a stage skipped happened by the next one
+        let mut stages = [StageTime::default(); 4];
+        for idx in (0..stages.len()).rev() {
+            stages[idx] = probe.stages[idx].unwrap_or(stages[(idx + 1).min(3)]);
+        }
+        self.probe = None;
+        if self.samples.len() == LATENCY_HISTORY {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(LatencySample { stages });
+    }
+
+    /// Clicks that got to the screen, oldest first
+    pub fn samples(&self) -> impl Iterator<Item = &LatencySample> {
+        self.samples.iter()
+    }
+
+    pub fn last(&self) -> Option<&LatencySample> {
+        self.samples.back()
+    }
+
+    /// Slowest click of the history, by frames and then by time
+    pub fn worst(&self) -> Option<&LatencySample> {
+        self.samples().max_by(|a, b| {
+            let (a, b) = (a.total(), b.total());
+            (a.frames, a.seconds)
+                .partial_cmp(&(b.frames, b.seconds))
+                .unwrap()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use bevy::{prelude::IVec2, utils::Instant};
+
+    use super::{InputLatency, LatencyStage, LATENCY_HISTORY, PROBE_TIMEOUT};
+
+    #[test]
+    fn test_latency() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut latency = InputLatency::default();
+        latency.mark(LatencyStage::Event, None, at(0));
+        assert!(latency.last().is_none());
+
+        latency.next_frame();
+        latency.press(at(0));
+        latency.mark(LatencyStage::Event, Some(IVec2::new(3, 4)), at(1));
+        assert_eq!(latency.coord(), Some(IVec2::new(3, 4)));
+        latency.mark(LatencyStage::Sprite, Some(IVec2::new(3, 5)), at(3));
+        // the first time counts
+        latency.mark(LatencyStage::Event, None, at(2));
+        assert!(latency.reached(LatencyStage::Sprite) && !latency.reached(LatencyStage::State));
+        assert_eq!(latency.coord(), Some(IVec2::new(3, 5)));
+        latency.next_frame();
+        latency.mark(LatencyStage::Visible, None, at(20));
+
+        let sample = *latency.last().unwrap();
+        assert_eq!(sample.stage(LatencyStage::Event).frames, 0);
+        assert!((sample.stage(LatencyStage::Event).seconds - 0.001).abs() < 1e-6);
+        // skipped, it happened by the sprite update
+        assert_eq!(
+            sample.stage(LatencyStage::State),
+            sample.stage(LatencyStage::Sprite)
+        );
+        assert_eq!(sample.total().frames, 1);
+        assert!(!latency.reached(LatencyStage::Event));
+
+        // a click that never shows is dropped
+        latency.press(at(100));
+        latency.mark(LatencyStage::Event, None, at(101));
+        for _ in 0..=PROBE_TIMEOUT {
+            latency.next_frame();
+        }
+        assert!(!latency.reached(LatencyStage::Event));
+
+        for ms in 0..LATENCY_HISTORY as u64 {
+            latency.press(at(200));
+            latency.mark(LatencyStage::Visible, None, at(200 + ms));
+        }
+        assert_eq!(latency.samples().count(), LATENCY_HISTORY);
+        assert_eq!(latency.worst().unwrap().total().frames, 0);
+        assert!(latency.worst().unwrap().total().seconds >= 0.018);
+    }
+}
@@ -0,0 +1,239 @@
+use bevy::prelude::{KeyCode, MouseButton};
+use serde::{Deserialize, Serialize};
+
+/// Button or key of every action. Part of the board options, so that any of them can be rebound
+/// in the options file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputMap {
+    pub uncover: MouseButton,
+    pub flag: MouseButton,
+    pub hint: KeyCode,
+    pub insights: KeyCode,
+    pub analysis: KeyCode,
+    /// Next proof step in analysis mode, with reduced motion
+    pub next_step: KeyCode,
+    /// Take back the last move, in modes allowing it
+    pub undo: KeyCode,
+    /// Ping the tile under the cursor with the emote chosen in the chat
+    pub ping: KeyCode,
+    pub trophies: KeyCode,
+    pub library: KeyCode,
+    /// Open the save slots
+    pub saves: KeyCode,
+    /// Play the puzzle of the week
+    pub weekly: KeyCode,
+    /// Play the daily challenge
+    pub daily: KeyCode,
+    /// Speed of replays, see `GameClock`
+    pub slower: KeyCode,
+    pub faster: KeyCode,
+    pub pause: KeyCode,
+    /// Next move of a paused replay
+    pub step: KeyCode,
+    /// Open the pause menu, or close it
+    pub menu: KeyCode,
+    /// Edit the board in the sandbox, or resume playing it
+    pub sandbox: KeyCode,
+    pub console: KeyCode,
+    /// Debug only: frame times and board statistics
+    pub perf_hud: KeyCode,
+    /// Debug only: the covered mines
+    pub xray: KeyCode,
+    /// Contrast grades of the theme over the board
+    pub contrast_audit: KeyCode,
+    /// Write a bug report with the board and the log
+    pub bug_report: KeyCode,
+    /// Keys of the save slots, while they are open
+    pub slots: SlotKeys,
+    /// Answers to the prompt resuming an unfinished game
+    pub resume: ResumeKeys,
+}
+
+/// Keys of the save slots, bound apart from the rest as they only work while the slots are open
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SlotKeys {
+    pub up: KeyCode,
+    pub down: KeyCode,
+    /// Save the game in progress in a new slot
+    pub save: KeyCode,
+    pub load: KeyCode,
+    pub duplicate: KeyCode,
+    pub delete: KeyCode,
+}
+
+impl SlotKeys {
+    pub const DEFAULT: Self = Self {
+        up: KeyCode::Up,
+        down: KeyCode::Down,
+        save: KeyCode::Insert,
+        load: KeyCode::Return,
+        duplicate: KeyCode::C,
+        delete: KeyCode::Delete,
+    };
+
+    /// One line reminding the keys, under the slots
+    pub fn help(&self) -> String {
+        format!(
+            "{:?} and {:?} to pick, {:?} to load, {:?} to copy, {:?} to delete, {:?} to save",
+            self.up, self.down, self.load, self.duplicate, self.delete, self.save
+        )
+    }
+}
+
+impl Default for SlotKeys {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+/// Answers to the prompt shown at startup when a game was left unfinished
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResumeKeys {
+    pub resume: KeyCode,
+    /// Delete the saved game, once confirmed
+    pub discard: KeyCode,
+}
+
+impl ResumeKeys {
+    pub const DEFAULT: Self = Self {
+        resume: KeyCode::R,
+        discard: KeyCode::N,
+    };
+}
+
+impl Default for ResumeKeys {
+    fn default() -> Self {
+        Self::DEFAULT
+    }
+}
+
+impl InputMap {
+    pub const RIGHT_HANDED: Self = Self {
+        uncover: MouseButton::Left,
+        flag: MouseButton::Right,
+        hint: KeyCode::H,
+        insights: KeyCode::I,
+        analysis: KeyCode::A,
+        next_step: KeyCode::Space,
+        undo: KeyCode::Back,
+        ping: KeyCode::P,
+        trophies: KeyCode::T,
+        library: KeyCode::B,
+        saves: KeyCode::S,
+        weekly: KeyCode::W,
+        daily: KeyCode::D,
+        slower: KeyCode::Comma,
+        faster: KeyCode::Period,
+        pause: KeyCode::Return,
+        step: KeyCode::Slash,
+        menu: KeyCode::Escape,
+        sandbox: KeyCode::E,
+        console: KeyCode::Grave,
+        perf_hud: KeyCode::F3,
+        xray: KeyCode::F4,
+        contrast_audit: KeyCode::F6,
+        bug_report: KeyCode::F9,
+        slots: SlotKeys::DEFAULT,
+        resume: ResumeKeys::DEFAULT,
+    };
+
+    /// Buttons swapped, and keys moved under the right hand while the left one holds the mouse
+    pub const LEFT_HANDED: Self = Self {
+        uncover: MouseButton::Right,
+        flag: MouseButton::Left,
+        hint: KeyCode::L,
+        insights: KeyCode::I,
+        analysis: KeyCode::Semicolon,
+        next_step: KeyCode::Space,
+        undo: KeyCode::Back,
+        ping: KeyCode::O,
+        trophies: KeyCode::T,
+        library: KeyCode::K,
+        saves: KeyCode::Y,
+        weekly: KeyCode::U,
+        daily: KeyCode::J,
+        slower: KeyCode::Comma,
+        faster: KeyCode::Period,
+        pause: KeyCode::Return,
+        step: KeyCode::Slash,
+        menu: KeyCode::Escape,
+        sandbox: KeyCode::M,
+        console: KeyCode::Grave,
+        perf_hud: KeyCode::F3,
+        xray: KeyCode::F4,
+        contrast_audit: KeyCode::F6,
+        bug_report: KeyCode::F9,
+        slots: SlotKeys::DEFAULT,
+        resume: ResumeKeys::DEFAULT,
+    };
+
+    pub fn is_left_handed(&self) -> bool {
+        *self == Self::LEFT_HANDED
+    }
+
+    /// Names of the first two actions bound to the same button or key, `None` if every action
+    /// can be told apart. The keys of the slots and of the resume prompt are only compared among
+    /// themselves, as they work while the others do not
+    pub fn conflict(&self) -> Option<(&'static str, &'static str)> {
+        let keys = [
+            ("hint", self.hint),
+            ("insights", self.insights),
+            ("analysis", self.analysis),
+            ("next_step", self.next_step),
+            ("undo", self.undo),
+            ("ping", self.ping),
+            ("trophies", self.trophies),
+            ("library", self.library),
+            ("saves", self.saves),
+            ("weekly", self.weekly),
+            ("daily", self.daily),
+            ("slower", self.slower),
+            ("faster", self.faster),
+            ("pause", self.pause),
+            ("step", self.step),
+            ("menu", self.menu),
+            ("sandbox", self.sandbox),
+            ("console", self.console),
+            ("perf_hud", self.perf_hud),
+            ("xray", self.xray),
+            ("contrast_audit", self.contrast_audit),
+            ("bug_report", self.bug_report),
+        ];
+        let slots = &self.slots;
+        let slot_keys = [
+            ("slots.up", slots.up),
+            ("slots.down", slots.down),
+            ("slots.save", slots.save),
+            ("slots.load", slots.load),
+            ("slots.duplicate", slots.duplicate),
+            ("slots.delete", slots.delete),
+        ];
+        let resume_keys = [
+            ("resume.resume", self.resume.resume),
+            ("resume.discard", self.resume.discard),
+        ];
+
+        duplicate(&[("uncover", self.uncover), ("flag", self.flag)])
+            .or_else(|| duplicate(&keys))
+            .or_else(|| duplicate(&slot_keys))
+            .or_else(|| duplicate(&resume_keys))
+    }
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        Self::RIGHT_HANDED
+    }
+}
+
+fn duplicate<T: PartialEq>(bindings: &[(&'static str, T)]) -> Option<(&'static str, &'static str)> {
+    bindings.iter().enumerate().find_map(|(i, (first, input))| {
+        bindings[i + 1..]
+            .iter()
+            .find(|(_, other)| other == input)
+            .map(|(second, _)| (*first, *second))
+    })
+}
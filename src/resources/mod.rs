@@ -1,2 +1,24 @@
 pub mod board;
+pub mod board_assets;
 pub mod board_options;
+pub mod board_queue;
+pub mod board_scratch;
+pub mod board_seed;
+pub mod board_state;
+pub mod board_transition;
+pub mod demo;
+pub mod frame_times;
+pub mod game_assets;
+pub mod game_clock;
+pub mod input_capture;
+pub mod input_latency;
+pub mod input_map;
+pub mod motion_prefs;
+pub mod reveal_wave;
+pub mod rule_set;
+pub mod skin;
+pub mod smiley;
+pub mod tile_index;
+pub mod toasts;
+pub mod uncover_queue;
+pub mod versus;
@@ -0,0 +1,22 @@
+use super::board_options::BoardOptions;
+
+/// How much the game may move on its own. Every animation checks it, so that a single setting
+/// calms the whole game down for motion-sensitive players
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MotionPrefs {
+    /// Skip fades, shakes and delays, showing their end state right away
+    pub reduce_motion: bool,
+}
+
+impl MotionPrefs {
+    pub fn from_options(options: &BoardOptions) -> Self {
+        Self {
+            reduce_motion: options.reduce_motion,
+        }
+    }
+
+    /// Whether animations should play at all
+    pub fn animate(&self) -> bool {
+        !self.reduce_motion
+    }
+}
@@ -0,0 +1,81 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::IVec2;
+
+/// Seconds between mines one tile further away from the triggered one
+pub const WAVE_STEP: f32 = 0.05;
+
+/// Longest wave in seconds, the step shrinks on boards too large for it
+pub const MAX_WAVE: f32 = 1.5;
+
+/// Mines left to reveal after a loss, in a wave spreading from the triggered mine
+#[derive(Debug, Default, Clone)]
+pub struct RevealWave {
+    elapsed: f32,
+    // mines with the delay after which they show, soonest first
+    pending: VecDeque<(f32, IVec2)>,
+}
+
+impl RevealWave {
+    pub fn new(origin: IVec2, mines: impl IntoIterator<Item = IVec2>) -> Self {
+        let distance = |mine: IVec2| origin.as_vec2().distance(mine.as_vec2());
+        let mut pending = mines
+            .into_iter()
+            .map(|mine| (distance(mine), mine))
+            .collect::<Vec<_>>();
+        pending.sort_by(|(a, _), (b, _)| a.total_cmp(b));
+
+        let furthest = pending.last().map_or(0.0, |(d, _)| *d);
+        let step = WAVE_STEP.min(MAX_WAVE / furthest.max(1.0));
+        Self {
+            elapsed: 0.0,
+            pending: pending
+                .into_iter()
+                .map(|(distance, mine)| (distance * step, mine))
+                .collect(),
+        }
+    }
+
+    /// Let `delta` seconds pass, returning the mines due to show
+    pub fn advance(&mut self, delta: f32) -> Vec<IVec2> {
+        self.elapsed += delta;
+        let due = self
+            .pending
+            .iter()
+            .take_while(|(delay, _)| *delay <= self.elapsed)
+            .count();
+        self.pending.drain(..due).map(|(_, mine)| mine).collect()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Stop the wave, e.g. when the loss is undone
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{RevealWave, MAX_WAVE, WAVE_STEP};
+
+    #[test]
+    fn test_wave() {
+        let mines = [IVec2::new(5, 0), IVec2::new(1, 0), IVec2::new(0, 3)];
+        let mut wave = RevealWave::new(IVec2::ZERO, mines);
+        assert!(wave.advance(0.0).is_empty());
+        assert_eq!(wave.advance(WAVE_STEP), [IVec2::new(1, 0)]);
+        assert_eq!(wave.advance(2.0 * WAVE_STEP), [IVec2::new(0, 3)]);
+        assert!(!wave.is_done());
+        assert_eq!(wave.advance(2.0 * WAVE_STEP), [IVec2::new(5, 0)]);
+        assert!(wave.is_done());
+
+        // huge boards keep the wave short
+        let mut wave = RevealWave::new(IVec2::ZERO, [IVec2::new(1000, 1000)]);
+        assert_eq!(wave.advance(MAX_WAVE + 0.01), [IVec2::new(1000, 1000)]);
+    }
+}
@@ -0,0 +1,269 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    board_options::{BoardOptions, GameMode},
+    board_state::BoardState,
+};
+use crate::{save::Action, session::PeerId};
+
+/// When a board is won
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum WinCondition {
+    /// Every safe tile is revealed
+    #[default]
+    Cleared,
+    /// Every mine is flagged and nothing else. Needs flags
+    Flagged,
+}
+
+/// What uncovering a mine costs
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Punishment {
+    /// The game is lost
+    #[default]
+    Lose,
+    /// The mine stays revealed and the game goes on
+    ShowMine,
+}
+
+/// Moves a player may make in a co-op session. Handed out when a player joins, so that one
+/// player uncovers while the other flags and they have to talk. Every player has one, see
+/// [`Roles`]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Role {
+    #[default]
+    Both,
+    /// Only uncovers
+    Sweeper,
+    /// Only flags
+    Flagger,
+}
+
+impl Role {
+    /// Role making up for this one, handed out to the players joining
+    pub fn partner(self) -> Self {
+        match self {
+            Role::Both => Role::Both,
+            Role::Sweeper => Role::Flagger,
+            Role::Flagger => Role::Sweeper,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Role::Both => "Sweeper and flagger",
+            Role::Sweeper => "Sweeper",
+            Role::Flagger => "Flagger",
+        }
+    }
+}
+
+/// Player of a session, as the host and the local systems know it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Player {
+    /// The player of this game
+    Local,
+    /// A player connected to this host
+    Peer(PeerId),
+}
+
+/// Role of every player, checked against their own moves. Players without one, as everyone
+/// outside of sessions, may make every move
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Roles(HashMap<Player, Role>);
+
+impl Roles {
+    pub fn get(&self, player: Player) -> Role {
+        self.0.get(&player).copied().unwrap_or_default()
+    }
+
+    pub fn assign(&mut self, player: Player, role: Role) {
+        self.0.insert(player, role);
+    }
+
+    /// Forget a player that left
+    pub fn remove(&mut self, player: Player) {
+        self.0.remove(&player);
+    }
+}
+
+/// What the player may do on the current board, how it is won and how mistakes are punished.
+/// Derived from the `BoardOptions` when the board is created. Gameplay, assist and HUD systems
+/// ask the rules rather than the [`GameMode`], so that a new mode is a new set of rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RuleSet {
+    /// Placing flags
+    pub flags: bool,
+    /// Clicking a number with as many flags around uncovers its other neighbors
+    pub chords: bool,
+    /// Flags turn into question marks before going away
+    pub question_marks: bool,
+    /// Hints, analysis mode and proven mine confirmation
+    pub assists: bool,
+    /// Mines left on every row and column shown along the border, an assist
+    pub line_counts: bool,
+    /// Mines of every row and column shown along the border, flags or not. Part of the puzzle
+    /// rather than an assist
+    pub line_totals: bool,
+    /// Taking moves back
+    pub undo: bool,
+    /// Flag counter and split times on screen
+    pub pressure: bool,
+    /// Finished games go to the stats and session log
+    pub recorded: bool,
+    /// Players take turns and score the safe tiles they reveal, the best score wins
+    pub versus: bool,
+    pub win: WinCondition,
+    pub mistakes: Punishment,
+}
+
+impl RuleSet {
+    pub fn for_mode(mode: GameMode) -> Self {
+        let classic = Self {
+            flags: true,
+            chords: true,
+            question_marks: false,
+            assists: true,
+            line_counts: false,
+            line_totals: false,
+            undo: false,
+            pressure: true,
+            recorded: true,
+            versus: false,
+            win: WinCondition::Cleared,
+            mistakes: Punishment::Lose,
+        };
+
+        match mode {
+            GameMode::Classic => classic,
+            GameMode::Zen => Self {
+                undo: true,
+                pressure: false,
+                recorded: false,
+                ..classic
+            },
+            GameMode::Hardcore => Self {
+                flags: false,
+                chords: false,
+                assists: false,
+                ..classic
+            },
+            // mines cost points instead of the game
+            GameMode::Versus => Self {
+                flags: false,
+                chords: false,
+                assists: false,
+                pressure: false,
+                recorded: false,
+                versus: true,
+                mistakes: Punishment::ShowMine,
+                ..classic
+            },
+            // numbers and totals together, so that every mine can be found and flagged
+            GameMode::Nonogram => Self {
+                line_totals: true,
+                win: WinCondition::Flagged,
+                ..classic
+            },
+        }
+    }
+
+    /// Rules of the mode, with the preferences of the options it allows
+    pub fn from_options(options: &BoardOptions) -> Self {
+        let rules = Self::for_mode(options.mode);
+        Self {
+            question_marks: rules.flags && options.question_marks,
+            line_counts: rules.assists && options.line_counts,
+            ..rules
+        }
+    }
+}
+
+impl RuleSet {
+    /// Whether the [`WinCondition`] holds on `state`
+    pub fn is_won(&self, state: &BoardState) -> bool {
+        match self.win {
+            WinCondition::Cleared => state.is_cleared(),
+            WinCondition::Flagged => state.is_fully_flagged(),
+        }
+    }
+
+    /// Whether a player with `role` may make the move, checked on local clicks and on the moves
+    /// the host receives from the other players
+    pub fn allows(&self, role: Role, action: Action) -> bool {
+        match action {
+            Action::Uncover(_) => role != Role::Flagger,
+            Action::Flag(_) => self.flags && role != Role::Sweeper,
+        }
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        Self::for_mode(GameMode::default())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{Player, Role, Roles, RuleSet, WinCondition};
+    use crate::{
+        resources::board_options::{BoardOptions, GameMode},
+        save::Action,
+        session::PeerId,
+    };
+
+    #[test]
+    fn test_from_options() {
+        let options = BoardOptions {
+            question_marks: true,
+            line_counts: true,
+            ..Default::default()
+        };
+        let rules = RuleSet::from_options(&options);
+        assert!(rules.question_marks && rules.line_counts);
+
+        // no question marks without flags
+        let options = BoardOptions {
+            mode: GameMode::Hardcore,
+            ..options
+        };
+        let rules = RuleSet::from_options(&options);
+        assert!(!rules.flags && !rules.question_marks && !rules.chords);
+        assert!(!rules.line_counts);
+    }
+
+    #[test]
+    fn test_roles() {
+        let uncover = Action::Uncover(IVec2::ZERO);
+        let flag = Action::Flag(IVec2::ZERO);
+        let classic = RuleSet::default();
+        assert!(classic.allows(Role::Both, uncover) && classic.allows(Role::Both, flag));
+        assert!(classic.allows(Role::Sweeper, uncover) && !classic.allows(Role::Sweeper, flag));
+        assert!(!classic.allows(Role::Flagger, uncover) && classic.allows(Role::Flagger, flag));
+
+        // the totals are the puzzle, flagging is the only way to win
+        let nonogram = RuleSet::for_mode(GameMode::Nonogram);
+        assert!(nonogram.line_totals && nonogram.allows(Role::Both, flag));
+        assert_eq!(nonogram.win, WinCondition::Flagged);
+
+        // roles never grant what the mode forbids
+        let hardcore = RuleSet::for_mode(GameMode::Hardcore);
+        assert!(!hardcore.allows(Role::Flagger, flag));
+
+        // every player has a role of its own
+        let mut roles = Roles::default();
+        let peer = Player::Peer(PeerId(2));
+        roles.assign(Player::Local, Role::Sweeper);
+        roles.assign(peer, Role::Sweeper.partner());
+        assert!(classic.allows(roles.get(peer), flag) && !classic.allows(roles.get(peer), uncover));
+        assert_eq!(roles.get(Player::Local), Role::Sweeper);
+        roles.remove(peer);
+        assert_eq!(roles.get(peer), Role::Both);
+        assert_eq!(roles.get(Player::Peer(PeerId(3))), Role::Both);
+    }
+}
@@ -0,0 +1,199 @@
+//! Skin packs: a directory in `assets/skins` holding a `skin.ron` manifest and the images it
+//! names. Every image is optional, the built-in look fills the gaps.
+//!
+//! ```ron
+//! (
+//!     name: "Retro",
+//!     author: Some("Jane"),
+//!     covered: Some("covered.png"),
+//!     revealed: Some("revealed.png"),
+//!     flag: Some("flag.png"),
+//!     mine: Some("mine.png"),
+//!     digits: Some(("1.png", "2.png", "3.png", "4.png", "5.png", "6.png", "7.png", "8.png")),
+//!     background: Some("background.png"),
+//!     faces: Some((
+//!         idle: "face_idle.png",
+//!         pressed: "face_pressed.png",
+//!         won: "face_won.png",
+//!         lost: "face_lost.png",
+//!     )),
+//!     segments: Some((
+//!         digits: (
+//!             "0.png", "1.png", "2.png", "3.png", "4.png",
+//!             "5.png", "6.png", "7.png", "8.png", "9.png",
+//!         ),
+//!         minus: "minus.png",
+//!     )),
+//! )
+//! ```
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Directory of the skin packs, in the assets directory
+pub const SKIN_DIR: &str = "skins";
+
+/// Manifest of a skin pack
+pub const MANIFEST_FILE: &str = "skin.ron";
+
+/// Images of a skin pack, relative to its directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SkinManifest {
+    /// Shown in the settings, the directory name when empty
+    pub name: String,
+    pub author: Option<String>,
+    /// Covered tile
+    pub covered: Option<String>,
+    /// Opened tile, under its number or mine
+    pub revealed: Option<String>,
+    pub flag: Option<String>,
+    pub mine: Option<String>,
+    /// Numbers 1 to 8
+    pub digits: Option<[String; 8]>,
+    /// Drawn under the tiles, stretched to the board
+    pub background: Option<String>,
+    /// Reset button above the board
+    pub faces: Option<SmileyFaces>,
+    /// Mine counter and timer of the HUD, written with the font when `None`
+    pub segments: Option<SegmentDigits>,
+}
+
+/// Expressions of the reset button
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SmileyFaces {
+    pub idle: String,
+    /// While a tile is held down
+    pub pressed: String,
+    pub won: String,
+    pub lost: String,
+}
+
+/// Digits of the counters, usually seven segment displays
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SegmentDigits {
+    /// Digits 0 to 9
+    pub digits: [String; 10],
+    /// Sign of the mine counter once more flags than mines are placed
+    pub minus: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SkinPack {
+    /// Name of the directory, as stored in `BoardOptions::skin`
+    pub id: String,
+    pub manifest: SkinManifest,
+}
+
+impl SkinPack {
+    pub fn name(&self) -> &str {
+        match self.manifest.name.trim() {
+            "" => &self.id,
+            name => name,
+        }
+    }
+
+    /// Asset path of `file`, an image of the manifest
+    pub fn asset_path(&self, file: &str) -> String {
+        format!("{SKIN_DIR}/{}/{file}", self.id)
+    }
+
+    /// Every pack in the skin directory of `assets`, sorted by id, with the packs whose
+    /// manifest is invalid apart
+    pub fn enumerate(assets: &Path) -> (Vec<SkinPack>, Vec<(PathBuf, String)>) {
+        let (mut packs, mut invalid) = (Vec::new(), Vec::new());
+        let Ok(entries) = std::fs::read_dir(assets.join(SKIN_DIR)) else {
+            return (packs, invalid);
+        };
+        for entry in entries.flatten() {
+            let path = entry.path().join(MANIFEST_FILE);
+            let Some(id) = entry.file_name().to_str().map(str::to_owned) else {
+                continue;
+            };
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            match ron::from_str(&content) {
+                Ok(manifest) => packs.push(SkinPack { id, manifest }),
+                Err(error) => invalid.push((path, error.to_string())),
+            }
+        }
+        packs.sort_by(|a, b| a.id.cmp(&b.id));
+        (packs, invalid)
+    }
+}
+
+/// Assets directory of the game, `None` where it is not a directory, on the web
+pub fn assets_dir() -> Option<PathBuf> {
+    #[cfg(not(target_arch = "wasm32"))]
+    return Some(bevy::asset::FileAssetIo::get_base_path().join("assets"));
+    #[cfg(target_arch = "wasm32")]
+    None
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SkinPack, MANIFEST_FILE, SKIN_DIR};
+
+    #[test]
+    fn test_enumerate() {
+        let root = std::env::temp_dir().join(format!("minesweeper-skins-{}", std::process::id()));
+        let skins = root.join(SKIN_DIR);
+        for (id, manifest) in [
+            ("retro", r#"(name: "Retro", flag: Some("flag.png"))"#),
+            ("plain", "()"),
+            ("broken", "(name: 3)"),
+        ] {
+            std::fs::create_dir_all(skins.join(id)).unwrap();
+            std::fs::write(skins.join(id).join(MANIFEST_FILE), manifest).unwrap();
+        }
+        // not a pack
+        std::fs::create_dir_all(skins.join("empty")).unwrap();
+
+        let (packs, invalid) = SkinPack::enumerate(&root);
+        let ids = packs
+            .iter()
+            .map(|pack| pack.id.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(ids, ["plain", "retro"]);
+        assert_eq!(invalid.len(), 1);
+        assert!(invalid[0].0.starts_with(skins.join("broken")));
+
+        assert_eq!(packs[0].name(), "plain");
+        assert_eq!(packs[1].name(), "Retro");
+        assert_eq!(packs[1].manifest.mine, None);
+        assert_eq!(packs[1].asset_path("flag.png"), "skins/retro/flag.png");
+
+        assert_eq!(SkinPack::enumerate(&root.join("missing")).0, []);
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_classic() {
+        let assets = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("assets");
+        let (packs, invalid) = SkinPack::enumerate(&assets);
+        assert_eq!(invalid, []);
+        let classic = packs.iter().find(|pack| pack.id == "classic").unwrap();
+        assert_eq!(classic.name(), "Classic");
+
+        let manifest = &classic.manifest;
+        let faces = manifest.faces.as_ref().unwrap();
+        let segments = manifest.segments.as_ref().unwrap();
+        let files = [
+            &manifest.covered,
+            &manifest.revealed,
+            &manifest.flag,
+            &manifest.mine,
+        ]
+        .into_iter()
+        .map(|file| file.as_ref().unwrap())
+        .chain(manifest.digits.as_ref().unwrap())
+        .chain([&faces.idle, &faces.pressed, &faces.won, &faces.lost])
+        .chain(&segments.digits)
+        .chain([&segments.minus]);
+        for file in files {
+            assert!(assets.join(classic.asset_path(file)).is_file(), "{file}");
+        }
+    }
+}
@@ -0,0 +1,70 @@
+/// Expression of the reset button above the board
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Smiley {
+    #[default]
+    Idle,
+    /// Worried, while a tile is held down
+    Pressed,
+    /// Sunglasses, until the next board
+    Won,
+    /// Dead, until the next board
+    Lost,
+}
+
+impl Smiley {
+    /// A tile is held down, ignored once the game is over
+    pub fn press(&mut self) {
+        if *self == Smiley::Idle {
+            *self = Smiley::Pressed;
+        }
+    }
+
+    /// The held tile is let go, wherever the cursor went
+    pub fn release(&mut self) {
+        if *self == Smiley::Pressed {
+            *self = Smiley::Idle;
+        }
+    }
+
+    /// The game is over, even with a tile still held
+    pub fn finish(&mut self, won: bool) {
+        *self = if won { Smiley::Won } else { Smiley::Lost };
+    }
+
+    /// A new board starts
+    pub fn reset(&mut self) {
+        *self = Smiley::Idle;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Smiley;
+
+    #[test]
+    fn test_transitions() {
+        let mut smiley = Smiley::default();
+        smiley.release();
+        assert_eq!(smiley, Smiley::Idle);
+        smiley.press();
+        smiley.press();
+        assert_eq!(smiley, Smiley::Pressed);
+        smiley.release();
+        assert_eq!(smiley, Smiley::Idle);
+
+        // the tile uncovered on release is a mine
+        smiley.press();
+        smiley.finish(false);
+        assert_eq!(smiley, Smiley::Lost);
+        smiley.release();
+        smiley.press();
+        assert_eq!(smiley, Smiley::Lost);
+
+        smiley.reset();
+        smiley.finish(true);
+        smiley.press();
+        assert_eq!(smiley, Smiley::Won);
+        smiley.reset();
+        assert_eq!(smiley, Smiley::Idle);
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::{Entity, IVec2};
+
+use crate::topology::Topology;
+
+/// Tile entities laid out like the board, so that the entity at a coordinate is found without
+/// going through every tile. Rebuilt whenever the board is spawned
+#[derive(Debug, Clone, Default)]
+pub struct TileEntityIndex {
+    dim: IVec2,
+    entities: Vec<Option<Entity>>,
+    topology: &'static dyn Topology,
+}
+
+impl TileEntityIndex {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            dim: IVec2::new(width.try_into().unwrap(), height.try_into().unwrap()),
+            entities: vec![None; (width * height) as usize],
+            topology: Default::default(),
+        }
+    }
+
+    /// Neighbors as another tiling than squares has them
+    pub fn with_topology(mut self, topology: &'static dyn Topology) -> Self {
+        self.topology = topology;
+        self
+    }
+
+    pub fn dim(&self) -> IVec2 {
+        self.dim
+    }
+
+    fn index(&self, coord: IVec2) -> Option<usize> {
+        self.topology.index(coord, self.dim)
+    }
+
+    /// Entity of the tile at `coord`, `None` if out of the board or despawned
+    pub fn get<T: Into<IVec2>>(&self, coord: T) -> Option<Entity> {
+        self.index(coord.into()).and_then(|idx| self.entities[idx])
+    }
+
+    /// Record the entity spawned for the tile at `coord`
+    pub fn insert<T: Into<IVec2>>(&mut self, coord: T, entity: Entity) {
+        let coord = coord.into();
+        let idx = self.index(coord).unwrap_or_else(|| {
+            panic!(
+                "Coordinate {:?} must be bound between [0, 0] and {:?}",
+                coord.to_array(),
+                self.dim.to_array()
+            )
+        });
+        self.entities[idx] = Some(entity);
+    }
+
+    /// Forget the tile at `coord`, returning its entity
+    pub fn remove<T: Into<IVec2>>(&mut self, coord: T) -> Option<Entity> {
+        self.index(coord.into())
+            .and_then(|idx| self.entities[idx].take())
+    }
+
+    /// Forget every tile, e.g. when the board gets despawned
+    pub fn clear(&mut self) {
+        self.entities.fill(None);
+    }
+
+    /// Coordinates and entities of the tiles touching `coord`, up to 8 on squares
+    pub fn neighbors<T: Into<IVec2>>(
+        &self,
+        coord: T,
+    ) -> impl Iterator<Item = (IVec2, Entity)> + '_ {
+        self.topology
+            .neighbors(coord.into(), self.dim)
+            .filter_map(|neighbor| Some((neighbor, self.get(neighbor)?)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::{Entity, IVec2};
+
+    use super::TileEntityIndex;
+
+    #[test]
+    fn test_index() {
+        let mut index = TileEntityIndex::new(3, 2);
+        for (i, coord) in [[0, 0], [2, 0], [1, 1]].into_iter().enumerate() {
+            index.insert(coord, Entity::from_raw(i as u32));
+        }
+
+        assert_eq!(index.get([2, 0]), Some(Entity::from_raw(1)));
+        assert_eq!(index.get([1, 0]), None);
+        assert_eq!(index.get([3, 0]), None);
+        assert_eq!(index.get([-1, 1]), None);
+
+        let mut neighbors = index.neighbors([1, 0]).collect::<Vec<_>>();
+        neighbors.sort_by_key(|(_, entity)| entity.id());
+        assert_eq!(
+            neighbors,
+            [
+                (IVec2::new(0, 0), Entity::from_raw(0)),
+                (IVec2::new(2, 0), Entity::from_raw(1)),
+                (IVec2::new(1, 1), Entity::from_raw(2)),
+            ]
+        );
+
+        assert_eq!(index.remove([0, 0]), Some(Entity::from_raw(0)));
+        assert_eq!(index.get([0, 0]), None);
+        index.clear();
+        assert_eq!(index.get([1, 1]), None);
+    }
+}
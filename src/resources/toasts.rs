@@ -0,0 +1,136 @@
+use std::collections::VecDeque;
+
+/// Toasts on screen at once, the next ones wait for a free slot
+pub const MAX_SHOWN: usize = 3;
+
+/// What a toast reports, setting its color and how long it stays
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ToastKind {
+    #[default]
+    Info,
+    Success,
+    Warning,
+    Error,
+}
+
+impl ToastKind {
+    /// Seconds on screen, longer for what the player should not miss
+    pub fn duration(self) -> f32 {
+        match self {
+            ToastKind::Info => 4.0,
+            ToastKind::Success => 5.0,
+            ToastKind::Warning => 6.0,
+            ToastKind::Error => 8.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Toast {
+    pub kind: ToastKind,
+    pub title: String,
+    pub body: String,
+    /// Seconds left on screen
+    pub remaining: f32,
+}
+
+/// Toasts on screen, the oldest first, and the ones waiting for a slot
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Toasts {
+    shown: Vec<Toast>,
+    queued: VecDeque<Toast>,
+    /// Bumped whenever the toasts on screen change, so that they are only redrawn then
+    revision: u64,
+}
+
+impl Toasts {
+    pub fn shown(&self) -> &[Toast] {
+        &self.shown
+    }
+
+    pub fn n_queued(&self) -> usize {
+        self.queued.len()
+    }
+
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Show a toast, or queue it while [`MAX_SHOWN`] are on screen
+    pub fn push(&mut self, kind: ToastKind, title: impl Into<String>, body: impl Into<String>) {
+        let toast = Toast {
+            kind,
+            title: title.into(),
+            body: body.into(),
+            remaining: kind.duration(),
+        };
+        if self.shown.len() < MAX_SHOWN {
+            self.shown.push(toast);
+            self.revision += 1;
+        } else {
+            self.queued.push_back(toast);
+        }
+    }
+
+    /// Advance the clocks of the toasts on screen by `delta` seconds, replacing the expired ones
+    /// with queued ones. Returns whether the toasts on screen changed
+    pub fn tick(&mut self, delta: f32) -> bool {
+        let before = self.shown.len();
+        self.shown
+            .iter_mut()
+            .for_each(|toast| toast.remaining -= delta);
+        self.shown.retain(|toast| toast.remaining > 0.0);
+        let mut changed = self.shown.len() != before;
+        while self.shown.len() < MAX_SHOWN {
+            let Some(toast) = self.queued.pop_front() else {
+                break;
+            };
+            self.shown.push(toast);
+            changed = true;
+        }
+        if changed {
+            self.revision += 1;
+        }
+        changed
+    }
+
+    /// Close the toast at `idx` before its time
+    pub fn dismiss(&mut self, idx: usize) {
+        if idx < self.shown.len() {
+            self.shown.remove(idx);
+            self.revision += 1;
+            self.tick(0.0);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ToastKind, Toasts, MAX_SHOWN};
+
+    #[test]
+    fn test_queue() {
+        let mut toasts = Toasts::default();
+        for n in 0..5 {
+            toasts.push(ToastKind::Info, format!("Toast {n}"), "");
+        }
+        assert_eq!(toasts.shown().len(), MAX_SHOWN);
+        assert_eq!(toasts.n_queued(), 2);
+        assert!(!toasts.tick(1.0));
+
+        // the queued ones take the free slots in order, with their full duration
+        let error = ToastKind::Error;
+        toasts.push(error, "Broken", "");
+        assert!(toasts.tick(ToastKind::Info.duration()));
+        let titles = toasts.shown().iter().map(|t| &t.title).collect::<Vec<_>>();
+        assert_eq!(titles, ["Toast 3", "Toast 4", "Broken"]);
+        assert_eq!(toasts.shown()[2].remaining, error.duration());
+
+        toasts.dismiss(0);
+        assert_eq!(toasts.shown().len(), 2);
+        toasts.dismiss(5);
+        assert!(toasts.tick(error.duration()));
+        assert_eq!(toasts.shown(), []);
+        assert!(!toasts.tick(1.0));
+    }
+}
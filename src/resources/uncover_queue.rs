@@ -0,0 +1,118 @@
+use std::{collections::VecDeque, time::Duration};
+
+use bevy::{prelude::IVec2, utils::Instant};
+
+/// Boards with more tiles than this spread big cascades over several frames
+pub const BUDGET_THRESHOLD: u32 = 256 * 256;
+
+/// How much flood fill work a single frame may do
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum UncoverBudget {
+    /// Uncover the whole cascade in the frame of the click
+    #[default]
+    Instant,
+    /// Stop after `tiles` tiles or `time`, whichever comes first, and go on next frame
+    Budgeted { tiles: usize, time: Duration },
+}
+
+impl UncoverBudget {
+    pub const DEFAULT_BUDGETED: Self = Self::Budgeted {
+        tiles: 4096,
+        time: Duration::from_millis(4),
+    };
+
+    /// Instant for normal boards, budgeted once the board has more than [`BUDGET_THRESHOLD`]
+    /// tiles
+    pub fn for_board(width: u32, height: u32) -> Self {
+        if width * height > BUDGET_THRESHOLD {
+            Self::DEFAULT_BUDGETED
+        } else {
+            Self::Instant
+        }
+    }
+
+    /// Whether a frame that started at `start` and uncovered `uncovered` tiles must stop
+    pub fn exhausted(&self, uncovered: usize, start: Instant) -> bool {
+        match *self {
+            Self::Instant => false,
+            Self::Budgeted { tiles, time } => uncovered >= tiles || start.elapsed() >= time,
+        }
+    }
+}
+
+/// Tiles waiting to be uncovered, fed by clicks and by the flood fill itself
+#[derive(Debug, Clone, Default)]
+pub struct UncoverQueue {
+    pub budget: UncoverBudget,
+    pending: VecDeque<IVec2>,
+}
+
+impl UncoverQueue {
+    pub fn new(budget: UncoverBudget) -> Self {
+        Self {
+            budget,
+            pending: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, coord: IVec2) {
+        self.pending.push_back(coord);
+    }
+
+    pub fn pop(&mut self) -> Option<IVec2> {
+        self.pending.pop_front()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty()
+    }
+
+    /// Tiles still waiting, some of them may be uncovered already
+    pub fn len(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl Extend<IVec2> for UncoverQueue {
+    fn extend<T: IntoIterator<Item = IVec2>>(&mut self, iter: T) {
+        self.pending.extend(iter);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use bevy::utils::Instant;
+
+    use super::UncoverBudget;
+
+    #[test]
+    fn test_budget() {
+        assert_eq!(UncoverBudget::for_board(30, 16), UncoverBudget::Instant);
+        assert_eq!(
+            UncoverBudget::for_board(1024, 1024),
+            UncoverBudget::DEFAULT_BUDGETED
+        );
+
+        let start = Instant::now();
+        assert!(!UncoverBudget::Instant.exhausted(usize::MAX, start));
+
+        let budget = UncoverBudget::Budgeted {
+            tiles: 10,
+            time: Duration::from_secs(60),
+        };
+        assert!(!budget.exhausted(9, start));
+        assert!(budget.exhausted(10, start));
+
+        let budget = UncoverBudget::Budgeted {
+            tiles: usize::MAX,
+            time: Duration::ZERO,
+        };
+        assert!(budget.exhausted(0, start));
+    }
+}
@@ -0,0 +1,85 @@
+/// Players taking turns in versus mode
+pub const PLAYERS: usize = 2;
+
+/// Points lost for uncovering a mine
+pub const MINE_PENALTY: i32 = 5;
+
+/// Turns and scores of a versus game, where players alternate single moves on the same board
+/// and score the safe tiles they reveal. Reset with every board
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Versus {
+    turn: usize,
+    scores: [i32; PLAYERS],
+    // player of the last move, scoring the tiles its cascade reveals
+    mover: Option<usize>,
+}
+
+impl Versus {
+    /// Player to move, from 0
+    pub fn turn(&self) -> usize {
+        self.turn
+    }
+
+    pub fn scores(&self) -> &[i32] {
+        &self.scores
+    }
+
+    /// The player to move made a move, the turn passes
+    pub fn take_turn(&mut self) {
+        self.mover = Some(self.turn);
+        self.turn = (self.turn + 1) % PLAYERS;
+    }
+
+    /// Safe tiles revealed by the last move, a cascade may reveal them over several frames
+    pub fn reveal(&mut self, n: usize) {
+        if let Some(mover) = self.mover {
+            self.scores[mover] += n as i32;
+        }
+    }
+
+    /// The last move uncovered a mine
+    pub fn hit_mine(&mut self) {
+        if let Some(mover) = self.mover {
+            self.scores[mover] -= MINE_PENALTY;
+        }
+    }
+
+    /// Player with the most points, `None` on a tie
+    pub fn leader(&self) -> Option<usize> {
+        let best = *self.scores.iter().max()?;
+        let mut leaders = (0..PLAYERS).filter(|&p| self.scores[p] == best);
+        leaders.next().filter(|_| leaders.next().is_none())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Versus, MINE_PENALTY};
+
+    #[test]
+    fn test_turns() {
+        let mut versus = Versus::default();
+        assert_eq!(versus.leader(), None);
+        // nothing scores before the first move
+        versus.reveal(3);
+        assert_eq!(versus.scores(), [0, 0]);
+
+        versus.take_turn();
+        assert_eq!(versus.turn(), 1);
+        // a cascade over two frames
+        versus.reveal(4);
+        versus.reveal(2);
+        assert_eq!(versus.leader(), Some(0));
+
+        versus.take_turn();
+        versus.reveal(1);
+        versus.hit_mine();
+        assert_eq!(versus.scores(), [6, 1 - MINE_PENALTY]);
+        assert_eq!(versus.turn(), 0);
+
+        versus.take_turn();
+        versus.take_turn();
+        versus.reveal(11);
+        assert_eq!(versus.leader(), Some(1));
+    }
+}
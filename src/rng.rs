@@ -0,0 +1,308 @@
+//! Randomness of the game behind the [`Rng`] trait object, so that the backend can be picked
+//! with `--rng <backend>` instead of being wired into board generation and the solver.
+//!
+//! Seeded backends give the same draws for the same seed on every platform: boards generated
+//! with [`seeded`] and solver runs given such an [`Rng`] repeat exactly. `nanorand` is the
+//! default and gives the boards of earlier releases back, shuffling with nanorand's own
+//! `Rng::shuffle` as they did. `std` is `rand::rngs::StdRng`, built
+//! with the `rand` feature. `os` draws every number from the entropy of the system and ignores
+//! seeds, for players who want boards nobody can predict.
+
+use std::{fmt::Display, str::FromStr, sync::RwLock};
+
+use nanorand::{Rng as _, WyRand};
+
+/// Source of random numbers. Every other draw derives from [`Rng::next_u64`], backends may
+/// override the others to keep the sequences they always produced
+pub trait Rng: Send + Sync {
+    fn backend(&self) -> RngBackend;
+
+    fn next_u64(&mut self) -> u64;
+
+    /// Uniform in `0..n`, `n` must not be 0
+    fn below(&mut self, n: usize) -> usize {
+        ((self.next_u64() as u128 * n as u128) >> 64) as usize
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.next_u64() >> 63 == 1
+    }
+
+    /// Uniform in `0.0..=1.0`
+    fn unit(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / ((1u64 << 53) - 1) as f64
+    }
+
+    /// Put `indices` in a random order, by a Fisher-Yates shuffle unless the backend has a
+    /// shuffle of its own
+    fn shuffle_indices(&mut self, indices: &mut [usize]) {
+        for i in (1..indices.len()).rev() {
+            indices.swap(i, self.below(i + 1));
+        }
+    }
+
+    /// Copy drawing the same numbers from now on, for backends with a state
+    fn clone_box(&self) -> Box<dyn Rng>;
+}
+
+impl Clone for Box<dyn Rng> {
+    fn clone(&self) -> Self {
+        self.clone_box()
+    }
+}
+
+impl std::fmt::Debug for dyn Rng {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Rng({})", self.backend())
+    }
+}
+
+/// Shuffle of the backend, see [`Rng::shuffle_indices`]
+pub fn shuffle<T: Clone>(rng: &mut dyn Rng, slice: &mut [T]) {
+    let mut order = (0..slice.len()).collect::<Vec<_>>();
+    rng.shuffle_indices(&mut order);
+    let shuffled = order
+        .iter()
+        .map(|&idx| slice[idx].clone())
+        .collect::<Vec<_>>();
+    slice.clone_from_slice(&shuffled);
+}
+
+/// Implementations of [`Rng`] this build offers
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RngBackend {
+    /// WyRand, from `nanorand`
+    #[default]
+    Nanorand,
+    /// `rand::rngs::StdRng`
+    #[cfg(feature = "rand")]
+    Std,
+    /// Entropy of the system, never reproducible
+    Os,
+}
+
+impl RngBackend {
+    pub const ALL: &'static [RngBackend] = &[
+        RngBackend::Nanorand,
+        #[cfg(feature = "rand")]
+        RngBackend::Std,
+        RngBackend::Os,
+    ];
+
+    /// Draws that are the same for every run given `seed`, except for [`RngBackend::Os`]
+    pub fn seeded(self, seed: u64) -> Box<dyn Rng> {
+        match self {
+            RngBackend::Nanorand => Box::new(NanorandRng(WyRand::new_seed(seed))),
+            #[cfg(feature = "rand")]
+            RngBackend::Std => Box::new(StdRng(rand::SeedableRng::seed_from_u64(seed))),
+            RngBackend::Os => Box::new(OsRng::new()),
+        }
+    }
+
+    /// Draws seeded from the entropy of the system
+    pub fn entropy(self) -> Box<dyn Rng> {
+        match self {
+            RngBackend::Nanorand => Box::new(NanorandRng(WyRand::new())),
+            #[cfg(feature = "rand")]
+            RngBackend::Std => Box::new(StdRng(rand::SeedableRng::from_entropy())),
+            RngBackend::Os => Box::new(OsRng::new()),
+        }
+    }
+}
+
+impl Display for RngBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RngBackend::Nanorand => write!(f, "nanorand"),
+            #[cfg(feature = "rand")]
+            RngBackend::Std => write!(f, "std"),
+            RngBackend::Os => write!(f, "os"),
+        }
+    }
+}
+
+impl FromStr for RngBackend {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        Self::ALL
+            .iter()
+            .find(|backend| backend.to_string() == name.to_lowercase())
+            .copied()
+            .ok_or_else(|| {
+                let names = Self::ALL
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>();
+                format!(
+                    "unknown random number generator {name:?}, expected one of {}",
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// Backend of this run, see [`set_backend`]
+static BACKEND: RwLock<RngBackend> = RwLock::new(RngBackend::Nanorand);
+
+/// Pick the backend of every [`Rng`] created from now on by [`seeded`] and [`entropy`]
+pub fn set_backend(backend: RngBackend) {
+    *BACKEND.write().unwrap_or_else(|e| e.into_inner()) = backend;
+}
+
+pub fn backend() -> RngBackend {
+    *BACKEND.read().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Reproducible draws of the backend of this run
+pub fn seeded(seed: u64) -> Box<dyn Rng> {
+    backend().seeded(seed)
+}
+
+/// Unpredictable draws of the backend of this run
+pub fn entropy() -> Box<dyn Rng> {
+    backend().entropy()
+}
+
+#[derive(Clone)]
+struct NanorandRng(WyRand);
+
+impl Rng for NanorandRng {
+    fn backend(&self) -> RngBackend {
+        RngBackend::Nanorand
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.generate()
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        self.0.generate_range(0..n)
+    }
+
+    fn next_bool(&mut self) -> bool {
+        self.0.generate()
+    }
+
+    fn unit(&mut self) -> f64 {
+        self.0.generate_range(0..=u32::MAX) as f64 / u32::MAX as f64
+    }
+
+    fn shuffle_indices(&mut self, indices: &mut [usize]) {
+        self.0.shuffle(indices);
+    }
+
+    fn clone_box(&self) -> Box<dyn Rng> {
+        Box::new(self.clone())
+    }
+}
+
+#[cfg(feature = "rand")]
+#[derive(Clone)]
+struct StdRng(rand::rngs::StdRng);
+
+#[cfg(feature = "rand")]
+impl Rng for StdRng {
+    fn backend(&self) -> RngBackend {
+        RngBackend::Std
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand::RngCore::next_u64(&mut self.0)
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        rand::Rng::gen_range(&mut self.0, 0..n)
+    }
+
+    fn clone_box(&self) -> Box<dyn Rng> {
+        Box::new(self.clone())
+    }
+}
+
+/// Numbers read from `/dev/urandom`, or drawn from a generator seeded anew by the system every
+/// [`OsRng::RESEED`] draws where there is no such device
+struct OsRng {
+    #[cfg(unix)]
+    device: Option<std::fs::File>,
+    fallback: nanorand::ChaCha20,
+    draws: u32,
+}
+
+impl OsRng {
+    const RESEED: u32 = 1024;
+
+    fn new() -> Self {
+        Self {
+            #[cfg(unix)]
+            device: std::fs::File::open("/dev/urandom").ok(),
+            fallback: nanorand::ChaCha20::new(),
+            draws: 0,
+        }
+    }
+}
+
+impl Rng for OsRng {
+    fn backend(&self) -> RngBackend {
+        RngBackend::Os
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        #[cfg(unix)]
+        if let Some(device) = &mut self.device {
+            let mut bytes = [0; 8];
+            if std::io::Read::read_exact(device, &mut bytes).is_ok() {
+                return u64::from_le_bytes(bytes);
+            }
+        }
+
+        self.draws += 1;
+        if self.draws >= Self::RESEED {
+            *self = Self::new();
+        }
+        self.fallback.generate()
+    }
+
+    fn clone_box(&self) -> Box<dyn Rng> {
+        // nothing to share, the copy draws numbers of its own
+        Box::new(Self::new())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{shuffle, RngBackend};
+
+    #[test]
+    fn test_seeded() {
+        for &backend in RngBackend::ALL {
+            assert_eq!(backend.to_string().parse(), Ok(backend));
+
+            let draws = |seed| {
+                let mut rng = backend.seeded(seed);
+                let mut order = (0..20).collect::<Vec<_>>();
+                shuffle(&mut *rng, &mut order);
+                (order, rng.next_u64(), rng.below(6), rng.unit())
+            };
+            let (order, _, below, unit) = draws(7);
+            let mut sorted = order.clone();
+            sorted.sort_unstable();
+            assert_eq!(sorted, (0..20).collect::<Vec<_>>());
+            assert!(below < 6 && (0.0..=1.0).contains(&unit));
+            if backend != RngBackend::Os {
+                assert_eq!(draws(7), draws(7), "{backend}");
+                assert_ne!(draws(7).0, draws(8).0, "{backend}");
+            }
+        }
+        assert!("dice".parse::<RngBackend>().is_err());
+    }
+
+    #[test]
+    fn test_clone() {
+        let mut rng = RngBackend::Nanorand.seeded(3);
+        rng.next_u64();
+        let mut copy = rng.clone();
+        assert_eq!(rng.next_u64(), copy.next_u64());
+        assert_eq!(format!("{copy:?}"), "Rng(nanorand)");
+    }
+}
@@ -0,0 +1,211 @@
+//! Achievements, defined as data in `assets/achievements.ron` and checked against the statistics
+//! and the session log after every recorded game.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{parse_date, SaveError, SessionLog, Stats, Versioned, ACHIEVEMENTS_FILE};
+use crate::storage::{Location, Storage};
+
+/// Built-in definitions
+const DEFINITIONS: &str = include_str!("../../assets/achievements.ron");
+
+/// What unlocks an achievement
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Condition {
+    Wins(u32),
+    NfWins(u32),
+    HardcoreWins(u32),
+    Played(u32),
+    /// Daily challenges won on consecutive dates, warm-ups aside
+    WinStreak(u32),
+    /// Best time on a difficulty, see `Difficulty::name`
+    BestTime {
+        difficulty: String,
+        under: f32,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Achievement {
+    /// Key of the achievement in the unlocked file, never to be changed once released
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub condition: Condition,
+}
+
+impl Achievement {
+    /// Definitions of the config directory if any, the built-in ones otherwise
+    pub fn definitions(storage: Option<&Storage>) -> Result<Vec<Achievement>, SaveError> {
+        let custom = storage
+            .map(|storage| storage.read(Location::Config, ACHIEVEMENTS_FILE))
+            .transpose()
+            .map_err(|e| SaveError::Io(e.to_string()))?
+            .flatten();
+        ron::from_str(custom.as_deref().unwrap_or(DEFINITIONS))
+            .map_err(|e| SaveError::Parse(e.to_string()))
+    }
+
+    pub fn is_met(&self, stats: &Stats, log: &SessionLog) -> bool {
+        match &self.condition {
+            Condition::Wins(n) => stats.won >= *n,
+            Condition::NfWins(n) => stats.nf_won >= *n,
+            Condition::HardcoreWins(n) => stats.hardcore_won >= *n,
+            Condition::Played(n) => stats.played >= *n,
+            Condition::WinStreak(days) => longest_streak(log) >= *days,
+            Condition::BestTime { difficulty, under } => stats
+                .best_times
+                .get(difficulty)
+                .is_some_and(|best| best < under),
+        }
+    }
+}
+
+/// Most daily challenges in a row won, by their date rather than the day they were played on
+fn longest_streak(log: &SessionLog) -> u32 {
+    let mut days = log
+        .games
+        .iter()
+        .filter(|game| game.won() && !game.warm_up)
+        .filter_map(|game| parse_date(game.daily.as_deref()?))
+        .collect::<Vec<_>>();
+    days.sort_unstable();
+    days.dedup();
+
+    let (mut longest, mut current) = (0, 0);
+    for (i, &day) in days.iter().enumerate() {
+        current = match i.checked_sub(1).map(|prev| days[prev]) {
+            Some(prev) if prev + 1 == day => current + 1,
+            _ => 1,
+        };
+        longest = longest.max(current);
+    }
+    longest
+}
+
+/// Achievements of the player, with the time they were unlocked in seconds since the Unix
+/// epoch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Unlocked(pub BTreeMap<String, u64>);
+
+impl Unlocked {
+    /// Unlock the achievements newly met at `now`, returning them
+    pub fn update<'a>(
+        &mut self,
+        definitions: &'a [Achievement],
+        stats: &Stats,
+        log: &SessionLog,
+        now: u64,
+    ) -> Vec<&'a Achievement> {
+        let new = definitions
+            .iter()
+            .filter(|achievement| !self.0.contains_key(&achievement.id))
+            .filter(|achievement| achievement.is_met(stats, log))
+            .collect::<Vec<_>>();
+        for achievement in &new {
+            self.0.insert(achievement.id.clone(), now);
+        }
+        new
+    }
+}
+
+impl Versioned for Unlocked {
+    const KIND: &'static str = "achievement file";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{longest_streak, Achievement, Unlocked};
+    use crate::{
+        insights::DAY,
+        resources::board_options::GameMode,
+        save::{format_date, GameRecord, LossKind, SessionLog, Stats},
+    };
+
+    fn game(day: u64, won: bool) -> GameRecord {
+        GameRecord {
+            finished: day * DAY + 100,
+            width: 9,
+            height: 9,
+            n_mines: 10,
+            duration: 60.0,
+            clicks: 20,
+            bbbv: 15,
+            loss: (!won).then_some(LossKind::ForcedGuess),
+            nf: false,
+            splits: Vec::new(),
+            mode: GameMode::Classic,
+            line_counts: false,
+            weekly: None,
+            daily: Some(format_date(day)),
+            warm_up: false,
+        }
+    }
+
+    #[test]
+    fn test_streak() {
+        let days = [1, 2, 2, 3, 5, 6, 7, 8, 10];
+        let mut log = SessionLog {
+            games: days.into_iter().map(|day| game(day, true)).collect(),
+        };
+        assert_eq!(longest_streak(&log), 4);
+        // a day with only losses breaks the streak
+        log.games[4] = game(5, false);
+        assert_eq!(longest_streak(&log), 3);
+        // so do wins of other boards and warm-ups on the day
+        log.games.push(GameRecord {
+            daily: None,
+            ..game(5, true)
+        });
+        log.games.push(GameRecord {
+            warm_up: true,
+            ..game(5, true)
+        });
+        assert_eq!(longest_streak(&log), 3);
+        // a challenge counts for its own date when finished past midnight
+        log.games.push(GameRecord {
+            finished: 10 * DAY + 100,
+            ..game(9, true)
+        });
+        assert_eq!(longest_streak(&log), 5);
+        assert_eq!(longest_streak(&SessionLog::default()), 0);
+    }
+
+    #[test]
+    fn test_update() {
+        let definitions = Achievement::definitions(None).unwrap();
+        assert_eq!(definitions.len(), 5);
+
+        let log = SessionLog {
+            games: vec![game(1, true)],
+        };
+        let stats = Stats {
+            played: 1,
+            won: 1,
+            best_times: [("Expert".to_owned(), 99.0)].into(),
+            ..Default::default()
+        };
+        let mut unlocked = Unlocked::default();
+        let ids = |achievements: Vec<&Achievement>| {
+            achievements
+                .iter()
+                .map(|a| a.id.clone())
+                .collect::<Vec<_>>()
+        };
+
+        let new = unlocked.update(&definitions, &stats, &log, 42);
+        assert_eq!(ids(new), ["first_win", "expert_100"]);
+        assert_eq!(unlocked.0["first_win"], 42);
+        // unlocked once only
+        assert!(unlocked.update(&definitions, &stats, &log, 43).is_empty());
+
+        let stats = Stats { nf_won: 1, ..stats };
+        assert_eq!(
+            ids(unlocked.update(&definitions, &stats, &log, 44)),
+            ["nf_win"]
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use bevy::prelude::IVec2;
+use serde::{Deserialize, Serialize};
+
+use super::{packed::PackedGame, parse_data, SaveError, Versioned};
+use crate::{
+    hexcells::Line,
+    resources::{
+        board::{TileMap, TileState},
+        board_options::MAX_DIM,
+        board_state::{BoardState, Cover},
+    },
+    topology::{self, SQUARE},
+};
+
+/// A game in progress. Written as the bytes of [`super::pack`] since version 2
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(into = "PackedGame", try_from = "PackedGame")]
+pub struct SaveGame {
+    pub width: u32,
+    pub height: u32,
+    pub mines: Vec<IVec2>,
+    pub revealed: Vec<IVec2>,
+    pub flagged: Vec<IVec2>,
+    /// Name of the tiling, squares when `None`
+    pub topology: Option<String>,
+    /// Holes of a puzzle, revealed without a number, see [`crate::hexcells`]
+    pub gaps: Vec<IVec2>,
+    /// Line hints of a puzzle
+    pub lines: Vec<Line>,
+}
+
+impl Versioned for SaveGame {
+    const KIND: &'static str = "saved game";
+    const VERSION: u32 = 2;
+
+    fn migrate(version: u32, source: &str) -> Result<Self, SaveError> {
+        match version {
+            1 => parse_data::<SaveGameV1>(source).map(Into::into),
+            _ => Err(SaveError::Unsupported {
+                kind: Self::KIND,
+                version,
+            }),
+        }
+    }
+}
+
+/// Layout of version 1, the tiles as lists of coordinates
+#[derive(Deserialize)]
+struct SaveGameV1 {
+    width: u32,
+    height: u32,
+    mines: Vec<IVec2>,
+    revealed: Vec<IVec2>,
+    flagged: Vec<IVec2>,
+    #[serde(default)]
+    topology: Option<String>,
+}
+
+impl From<SaveGameV1> for SaveGame {
+    fn from(old: SaveGameV1) -> Self {
+        Self {
+            width: old.width,
+            height: old.height,
+            mines: old.mines,
+            revealed: old.revealed,
+            flagged: old.flagged,
+            topology: old.topology,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+}
+
+impl SaveGame {
+    pub fn capture(state: &BoardState) -> Self {
+        let dim = state.dim();
+        let coords = (0..dim.y).flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)));
+        let with_cover = |cover| {
+            coords
+                .clone()
+                .filter(|&coord| state.cover(coord) == Some(cover))
+                .collect()
+        };
+
+        Self {
+            width: dim.x as u32,
+            height: dim.y as u32,
+            mines: coords
+                .clone()
+                .filter(|&coord| state.tile(coord) == Some(TileState::Mine))
+                .collect(),
+            revealed: with_cover(Cover::Revealed),
+            flagged: with_cover(Cover::Flagged),
+            topology: Some(state.topology().name())
+                .filter(|&name| name != SQUARE)
+                .map(str::to_owned),
+            gaps: state.gaps().to_vec(),
+            lines: state.lines().to_vec(),
+        }
+    }
+
+    /// Rebuild the board. Tiles revealed or flagged by the save are reported as changes of the
+    /// returned state, so they get applied to freshly spawned tiles like any move
+    pub fn restore(&self) -> Result<(TileMap, BoardState), SaveError> {
+        let dim = IVec2::new(self.width as i32, self.height as i32);
+        if self.width == 0 || self.height == 0 || self.width > MAX_DIM || self.height > MAX_DIM {
+            return Err(SaveError::Invalid(format!(
+                "unsupported board size {}x{}",
+                self.width, self.height
+            )));
+        }
+
+        let tiles = self
+            .mines
+            .iter()
+            .chain(&self.revealed)
+            .chain(&self.flagged)
+            .chain(&self.gaps);
+        if let Some(coord) = tiles
+            .into_iter()
+            .find(|coord| !(coord.cmpge(IVec2::ZERO).all() && coord.cmplt(dim).all()))
+        {
+            return Err(SaveError::Invalid(format!(
+                "tile {:?} is outside of the board",
+                coord.to_array()
+            )));
+        }
+
+        let topology = match &self.topology {
+            Some(name) => topology::get(name)
+                .ok_or_else(|| SaveError::Invalid(format!("unknown topology {name:?}")))?,
+            None => Default::default(),
+        };
+        topology.validate(dim).map_err(SaveError::Invalid)?;
+        if let Some(coord) = self.gaps.iter().find(|gap| self.mines.contains(gap)) {
+            return Err(SaveError::Invalid(format!(
+                "the gap {:?} is a mine",
+                coord.to_array()
+            )));
+        }
+        if let Some(line) = self.lines.iter().find(|line| line.row >= self.height) {
+            return Err(SaveError::Invalid(format!(
+                "line hint of row {}, outside of the board",
+                line.row
+            )));
+        }
+
+        let mut tile_map = TileMap::with_mines(self.width, self.height, self.mines.clone())
+            .map_err(|error| SaveError::Invalid(error.to_string()))?
+            .with_topology(topology);
+        let mut state = BoardState::new(&mut tile_map);
+        state.set_puzzle(self.gaps.clone(), self.lines.clone());
+        for &coord in &self.revealed {
+            state.uncover(coord);
+        }
+        for &coord in &self.flagged {
+            state.toggle_flag(coord);
+        }
+
+        Ok((tile_map, state))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SaveGame;
+    use crate::{
+        resources::{
+            board::TileMap,
+            board_state::{BoardState, Cover},
+        },
+        topology,
+    };
+
+    #[test]
+    fn test_capture_restore() {
+        let mut tile_map = TileMap::with_mines(4, 4, [[0, 0], [3, 3]]).unwrap();
+        let mut state = BoardState::new(&mut tile_map);
+        state.uncover([2, 0]);
+        state.toggle_flag([3, 3]);
+
+        let save = SaveGame::capture(&state);
+        let (_, restored) = save.restore().unwrap();
+        assert_eq!(SaveGame::capture(&restored), save);
+        assert_eq!(restored.cover([3, 3]), Some(Cover::Flagged));
+
+        let mut broken = save;
+        broken.mines.push([4, 0].into());
+        assert!(broken.restore().is_err());
+
+        let torus = topology::get("torus").unwrap();
+        let mut tile_map = TileMap::with_mines(4, 4, [[0, 0]])
+            .unwrap()
+            .with_topology(torus);
+        let save = SaveGame::capture(&BoardState::new(&mut tile_map));
+        assert_eq!(save.topology.as_deref(), Some("torus"));
+        let (restored, _) = save.restore().unwrap();
+        assert_eq!(restored.topology().name(), "torus");
+    }
+}
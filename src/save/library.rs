@@ -0,0 +1,269 @@
+use super::{SaveError, SaveGame, LIBRARY_DIR};
+use crate::{
+    import, solver,
+    storage::{Location, Storage},
+    symmetry::CanonicalBoard,
+};
+
+/// Order of the library
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LibrarySort {
+    /// Densest boards first
+    #[default]
+    Difficulty,
+    /// Boards needing no guess first
+    Solvability,
+    /// Largest boards first
+    Size,
+}
+
+impl LibrarySort {
+    pub const ALL: [Self; 3] = [Self::Difficulty, Self::Solvability, Self::Size];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LibrarySort::Difficulty => "Difficulty",
+            LibrarySort::Solvability => "Solvability",
+            LibrarySort::Size => "Size",
+        }
+    }
+}
+
+/// A board file of the library, a saved game whose revealed tiles are given away when played
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryEntry {
+    /// Name of the file in [`LIBRARY_DIR`]
+    pub file: String,
+    pub board: SaveGame,
+    /// Least number of clicks the board needs, see `BoardState::bbbv`
+    pub bbbv: u32,
+    /// Clearable without guessing, see [`solver::is_solvable`]
+    pub solvable: bool,
+    /// Same for rotated and mirrored copies, apart for other tilings and other givens, see
+    /// [`CanonicalBoard`]
+    pub hash: u64,
+}
+
+impl LibraryEntry {
+    pub fn new(file: impl Into<String>, board: SaveGame) -> Result<Self, SaveError> {
+        let (_, state) = board.restore()?;
+        let hash = CanonicalBoard::of_game(&board).hash();
+        Ok(Self {
+            file: file.into(),
+            bbbv: state.bbbv(),
+            solvable: solver::is_solvable(&state),
+            hash,
+            board,
+        })
+    }
+
+    pub fn n_tiles(&self) -> u32 {
+        self.board.width * self.board.height
+    }
+
+    /// Share of the tiles hiding a mine, the difficulty rating of the library
+    pub fn density(&self) -> f32 {
+        self.board.mines.len() as f32 / self.n_tiles() as f32
+    }
+
+    /// Board to play: the revealed tiles only, flags are left to the player
+    pub fn level(&self) -> SaveGame {
+        SaveGame {
+            flagged: Vec::new(),
+            ..self.board.clone()
+        }
+    }
+}
+
+/// Boards of [`LIBRARY_DIR`] in the data directory
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Library {
+    /// In the order of the files, copies up to symmetry left out
+    pub entries: Vec<LibraryEntry>,
+    /// Files that are not boards, and why
+    pub invalid: Vec<(String, SaveError)>,
+}
+
+impl Library {
+    /// Read every `.ron` file of the library directory, and the Hexcells-like puzzles of the
+    /// `.hexcells` files, see `import::parse_hexcells`
+    pub fn scan(storage: &Storage) -> Result<Self, SaveError> {
+        let files = storage
+            .list(Location::Data, LIBRARY_DIR)
+            .map_err(|e| SaveError::Io(e.to_string()))?;
+
+        let mut library = Self::default();
+        let boards = |file: &String| file.ends_with(".ron") || file.ends_with(".hexcells");
+        for file in files.into_iter().filter(boards) {
+            let path = format!("{LIBRARY_DIR}/{file}");
+            let board = if file.ends_with(".hexcells") {
+                Self::read_hexcells(storage, &path)
+            } else {
+                super::read::<SaveGame>(storage, Location::Data, &path)
+            };
+            let entry = board
+                .and_then(|board| board.ok_or_else(|| SaveError::Io("file removed".to_owned())))
+                .and_then(|board| LibraryEntry::new(&file, board));
+            match entry {
+                Ok(entry) => {
+                    library.add(entry);
+                }
+                Err(error) => library.invalid.push((file, error)),
+            }
+        }
+        Ok(library)
+    }
+
+    /// Board of a `.hexcells` file, `None` if it is gone
+    fn read_hexcells(storage: &Storage, path: &str) -> Result<Option<SaveGame>, SaveError> {
+        let source = storage
+            .read(Location::Data, path)
+            .map_err(|e| SaveError::Io(e.to_string()))?;
+        source
+            .map(|source| {
+                import::parse_hexcells(&source).map_err(|e| SaveError::Parse(e.to_string()))
+            })
+            .transpose()
+    }
+
+    /// Add a board, unless a copy of it is already in, returning whether it was added
+    pub fn add(&mut self, entry: LibraryEntry) -> bool {
+        let copy = self.entries.iter().any(|other| other.hash == entry.hash);
+        if !copy {
+            self.entries.push(entry);
+        }
+        !copy
+    }
+
+    /// Order the boards, ties broken by file name
+    pub fn sort(&mut self, sort: LibrarySort) {
+        self.entries.sort_by(|a, b| {
+            let order = match sort {
+                LibrarySort::Difficulty => b.density().total_cmp(&a.density()),
+                LibrarySort::Solvability => b.solvable.cmp(&a.solvable),
+                LibrarySort::Size => b.n_tiles().cmp(&a.n_tiles()),
+            };
+            order.then_with(|| a.file.cmp(&b.file))
+        });
+    }
+
+    /// Save the mine layout of `board` in the library, returning the name of the new file
+    pub fn export(storage: &Storage, board: &SaveGame, now: u64) -> Result<String, SaveError> {
+        let file = format!("board-{now}.ron");
+        let layout = SaveGame {
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            ..board.clone()
+        };
+        let path = format!("{LIBRARY_DIR}/{file}");
+        super::write(storage, Location::Data, &path, &layout)?;
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{Library, LibrarySort, LIBRARY_DIR};
+    use crate::{
+        save::SaveGame,
+        storage::{Location, Storage},
+    };
+
+    fn board(width: u32, height: u32, mines: &[[i32; 2]]) -> SaveGame {
+        SaveGame {
+            width,
+            height,
+            mines: mines.iter().map(|&mine| IVec2::from(mine)).collect(),
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_scan() {
+        let root = std::env::temp_dir().join(format!("minesweeper-library-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        assert_eq!(Library::scan(&storage), Ok(Library::default()));
+
+        // a coin flip in the corner, and a board with an opening leading everywhere
+        let coin_flip = board(3, 2, &[[0, 0]]);
+        let open = board(4, 3, &[[1, 0]]);
+        let flipped = board(3, 2, &[[2, 1]]);
+        let mut given = flipped.clone();
+        given.revealed.push(IVec2::new(0, 0));
+        assert_eq!(
+            Library::export(&storage, &coin_flip, 2).unwrap(),
+            "board-2.ron"
+        );
+        Library::export(&storage, &open, 1).unwrap();
+        let path = format!("{LIBRARY_DIR}/copy.ron");
+        crate::save::write(&storage, Location::Data, &path, &flipped).unwrap();
+        // kept as written, revealed tiles included, which make it another level
+        let path = format!("{LIBRARY_DIR}/given.ron");
+        crate::save::write(&storage, Location::Data, &path, &given).unwrap();
+        let path = format!("{LIBRARY_DIR}/notes.ron");
+        storage.write(Location::Data, &path, "(notes)").unwrap();
+        let path = format!("{LIBRARY_DIR}/readme.txt");
+        storage.write(Location::Data, &path, "boards").unwrap();
+
+        let mut library = Library::scan(&storage).unwrap();
+        let files = |library: &Library| {
+            library
+                .entries
+                .iter()
+                .map(|entry| entry.file.clone())
+                .collect::<Vec<_>>()
+        };
+        // the copy of the coin flip is left out
+        assert_eq!(files(&library), ["board-1.ron", "board-2.ron", "given.ron"]);
+        assert_eq!(library.entries[2].board, given);
+        assert_eq!(library.invalid.len(), 1);
+        assert_eq!(library.invalid[0].0, "notes.ron");
+
+        let (open_entry, coin_flip_entry) = (&library.entries[0], &library.entries[1]);
+        assert!(open_entry.solvable && !coin_flip_entry.solvable);
+        assert_eq!(coin_flip_entry.board, coin_flip);
+        assert_eq!(coin_flip_entry.bbbv, 2);
+
+        library.sort(LibrarySort::Difficulty);
+        assert_eq!(files(&library), ["board-2.ron", "given.ron", "board-1.ron"]);
+        library.sort(LibrarySort::Solvability);
+        assert_eq!(files(&library), ["board-1.ron", "board-2.ron", "given.ron"]);
+        library.sort(LibrarySort::Size);
+        assert_eq!(files(&library), ["board-1.ron", "board-2.ron", "given.ron"]);
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_scan_hexcells() {
+        let root = std::env::temp_dir().join(format!(
+            "minesweeper-library-hexcells-{}",
+            std::process::id()
+        ));
+        let storage = Storage::portable(&root);
+        let path = format!("{LIBRARY_DIR}/pair.hexcells");
+        storage
+            .write(Location::Data, &path, "hexcells\no x\nx O\n")
+            .unwrap();
+        let path = format!("{LIBRARY_DIR}/broken.hexcells");
+        storage
+            .write(Location::Data, &path, "hexcells\nx ?\n")
+            .unwrap();
+
+        let library = Library::scan(&storage).unwrap();
+        assert_eq!(library.entries.len(), 1);
+        assert_eq!(library.entries[0].file, "pair.hexcells");
+        assert_eq!(library.entries[0].board.topology.as_deref(), Some("hex"));
+        assert_eq!(library.entries[0].level().revealed, [IVec2::new(1, 1)]);
+        assert_eq!(library.invalid.len(), 1);
+        assert_eq!(library.invalid[0].0, "broken.hexcells");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
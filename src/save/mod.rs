@@ -0,0 +1,634 @@
+//! On-disk formats for saved games, replays and statistics.
+//!
+//! Every file is a RON envelope `(version: n, data: ...)`. Loading reads the version first: the
+//! current version is parsed directly, older versions go through [`Versioned::migrate`], and
+//! newer ones are refused instead of being misread. Bump the version whenever the layout of
+//! `data` changes, add a migration from the previous layout, and keep a fixture of the old
+//! layout in `fixtures/` so that the tests prove old files still load.
+//!
+//! Written files start with a comment holding a checksum of the rest, `// checksum: fnv1a
+//! <hex>`, so that damaged files are refused instead of producing a broken game. Files without
+//! it predate checksums, or were written by hand, and load as they are. Once the player creates
+//! a key with `minesweeper sign-saves`, files are signed with an HMAC of the key instead, and
+//! the progress of the player must be signed, which catches hand edits too. [`read`] moves the
+//! files failing their check aside, see [`QUARANTINE_SUFFIX`]. Machines syncing a profile
+//! need the same [`KEY_FILE`].
+
+mod achievements;
+mod game;
+mod library;
+mod packed;
+mod profiles;
+mod rating;
+mod replay;
+mod session;
+mod skill;
+mod slots;
+mod stats;
+mod weekly;
+
+use std::{fmt::Display, path::Path, sync::RwLock};
+
+use bevy::prelude::warn;
+use nanorand::{ChaCha20, Rng};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{
+    hash::{from_hex, hmac_sha256, sha256, to_hex, verify_hmac_sha256, StableHasher},
+    storage::{Location, Storage},
+};
+
+pub use achievements::{Achievement, Condition, Unlocked};
+pub use game::SaveGame;
+pub use library::{Library, LibraryEntry, LibrarySort};
+pub use packed::{pack, unpack, PackedGame, PACKED_FORMAT};
+pub use profiles::{profile_id, Profile, Profiles};
+pub use rating::{Outcome, RatedGame, Ratings, INITIAL_RATING};
+pub use replay::{
+    state_hash, Action, Annotation, Keyframe, Replay, ReplayKeyframes, ReplayMove, ReplayOutcome,
+    KEYFRAME_INTERVAL, MAX_ANNOTATION_CHARS,
+};
+pub use session::{GameRecord, LossKind, SessionLog, SPLITS};
+pub use skill::{SkillEstimate, Trend, INITIAL_SKILL};
+pub use slots::{SavedSlot, SlotEntry, Slots};
+pub use stats::{Stats, MAX_NOTE_CHARS};
+pub use weekly::{format_date, parse_date, PuzzleRotation, WeeklyPuzzle, WEEK_DAYS};
+
+/// Game in progress, in the data directory
+pub const SAVE_FILE: &str = "save.ron";
+/// Statistics, in the data directory
+pub const STATS_FILE: &str = "stats.ron";
+/// Log of every finished game, in the data directory
+pub const SESSION_FILE: &str = "sessions.ron";
+
+/// List of the profiles, in the config directory shared by every profile
+pub const PROFILES_FILE: &str = "profiles.ron";
+
+/// Versus rating, in the data directory
+pub const RATING_FILE: &str = "rating.ron";
+/// Skill estimate of adaptive boards, in the data directory
+pub const SKILL_FILE: &str = "skill.ron";
+/// Unlocked achievements, in the data directory
+pub const UNLOCKED_FILE: &str = "achievements.ron";
+/// Achievement definitions replacing the built-in ones, in the config directory
+pub const ACHIEVEMENTS_FILE: &str = "achievements.ron";
+/// Directory of the replays, in the data directory
+pub const REPLAY_DIR: &str = "replays";
+/// Directory of the shared boards, in the data directory
+pub const LIBRARY_DIR: &str = "boards";
+/// Directory of the save slots, in the data directory
+pub const SLOTS_DIR: &str = "saves";
+/// Downloaded puzzle rotation replacing the bundled one, in the cache directory
+pub const WEEKLY_FILE: &str = "weekly.ron";
+
+/// Key signing the saved files, in the config directory shared by every profile
+pub const KEY_FILE: &str = "save.key";
+/// Appended to the name of files failing their checksum, kept for the player to look into
+pub const QUARANTINE_SUFFIX: &str = ".corrupt";
+
+const CHECKSUM_PREFIX: &str = "// checksum: ";
+/// Bytes of the keys created by [`create_key`]
+const KEY_LEN: usize = 32;
+
+/// Key of this run, see [`set_key`]
+static KEY: RwLock<Option<Vec<u8>>> = RwLock::new(None);
+
+/// Reasons a file cannot be loaded or saved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SaveError {
+    Io(String),
+    /// Not RON, or not the layout its version says
+    Parse(String),
+    /// Written by a newer version of the game
+    Newer {
+        kind: &'static str,
+        version: u32,
+        supported: u32,
+    },
+    /// Too old to be migrated
+    Unsupported {
+        kind: &'static str,
+        version: u32,
+    },
+    /// Well formed but inconsistent, e.g. a tile outside of the board
+    Invalid(String),
+    /// Changed since the game wrote it, or not signed with the key of the player
+    Checksum(String),
+}
+
+impl Display for SaveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SaveError::Io(reason) => write!(f, "Could not access the file: {reason}"),
+            SaveError::Parse(reason) => write!(f, "Malformed file: {reason}"),
+            SaveError::Newer {
+                kind,
+                version,
+                supported,
+            } => write!(
+                f,
+                "This {kind} is version {version}, this game only reads up to version {supported}"
+            ),
+            SaveError::Unsupported { kind, version } => {
+                write!(
+                    f,
+                    "This {kind} is version {version}, which can no longer be read"
+                )
+            }
+            SaveError::Invalid(reason) => write!(f, "Inconsistent file: {reason}"),
+            SaveError::Checksum(reason) => write!(
+                f,
+                "The file was changed outside of the game or is damaged: {reason}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+/// A file format with an explicit version
+pub trait Versioned: Serialize + DeserializeOwned {
+    /// Name of the format in error messages
+    const KIND: &'static str;
+    /// Version written by this build
+    const VERSION: u32;
+
+    /// Convert a file written with an older `version`, `source` is the whole file
+    fn migrate(version: u32, source: &str) -> Result<Self, SaveError> {
+        let _ = source;
+        Err(SaveError::Unsupported {
+            kind: Self::KIND,
+            version,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct Header {
+    version: u32,
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    version: u32,
+    data: &'a T,
+}
+
+#[derive(Deserialize)]
+struct OwnedEnvelope<T> {
+    data: T,
+}
+
+/// Parse the `data` of an envelope as `T`, whatever its version. Meant for migrations reading
+/// the layout of an older version
+pub fn parse_data<T: DeserializeOwned>(source: &str) -> Result<T, SaveError> {
+    ron::from_str::<OwnedEnvelope<T>>(source)
+        .map(|envelope| envelope.data)
+        .map_err(|e| SaveError::Parse(e.to_string()))
+}
+
+/// Sign the files written from now on with `key`, and check the signed files against it.
+/// `None` goes back to plain checksums
+pub fn set_key(key: Option<Vec<u8>>) {
+    *KEY.write().unwrap_or_else(|e| e.into_inner()) = key;
+}
+
+fn key() -> Option<Vec<u8>> {
+    KEY.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Key of the player, kept in hexadecimal in the shared config directory. `None` until
+/// [`create_key`]
+pub fn load_key(storage: &Storage) -> Result<Option<Vec<u8>>, SaveError> {
+    let source = storage
+        .shared()
+        .read(Location::Config, KEY_FILE)
+        .map_err(|e| SaveError::Io(e.to_string()))?;
+    source
+        .map(|source| {
+            from_hex(source.trim())
+                .filter(|key| !key.is_empty())
+                .ok_or_else(|| SaveError::Parse(format!("{KEY_FILE} is not a hexadecimal key")))
+        })
+        .transpose()
+}
+
+/// Create a random key for the player, replacing any previous one
+pub fn create_key(storage: &Storage) -> Result<Vec<u8>, SaveError> {
+    let mut rng = ChaCha20::new();
+    let key = (0..KEY_LEN)
+        .map(|_| rng.generate::<u8>())
+        .collect::<Vec<_>>();
+    storage
+        .shared()
+        .write(Location::Config, KEY_FILE, &to_hex(&key))
+        .map_err(|e| SaveError::Io(e.to_string()))?;
+    Ok(key)
+}
+
+/// Short id of a key, telling apart signatures of other keys from forged ones
+fn key_id(key: &[u8]) -> String {
+    to_hex(&sha256(key)[..4])
+}
+
+fn checksum(body: &str, key: Option<&[u8]>) -> String {
+    match key {
+        Some(key) => format!(
+            "hmac-sha256 {} {}",
+            key_id(key),
+            to_hex(&hmac_sha256(key, body.as_bytes()))
+        ),
+        None => {
+            let mut hasher = StableHasher::new();
+            hasher.write(body.as_bytes());
+            format!("fnv1a {:016x}", hasher.finish())
+        }
+    }
+}
+
+/// `body` behind a line holding its checksum, or its HMAC with `key`
+pub fn seal(body: &str, key: Option<&[u8]>) -> String {
+    format!("{CHECKSUM_PREFIX}{}\n{body}", checksum(body, key))
+}
+
+/// Body of a file written by [`seal`] once its checksum is checked, and whether it is signed
+/// with `key`. Files without a checksum, and files signed with another key, are returned as
+/// they are: whether they may be used is up to the caller
+pub fn unseal<'a>(source: &'a str, key: Option<&[u8]>) -> Result<(&'a str, bool), SaveError> {
+    let Some(rest) = source.strip_prefix(CHECKSUM_PREFIX) else {
+        return Ok((source, false));
+    };
+    let (line, body) = rest.split_once('\n').unwrap_or((rest, ""));
+    let line = line.trim_end();
+
+    let tag = match line.split(' ').collect::<Vec<_>>()[..] {
+        ["fnv1a", _] => None,
+        // only the owner of the key can check it
+        ["hmac-sha256", id, _] if key.map(key_id).as_deref() != Some(id) => {
+            return Ok((body, false))
+        }
+        ["hmac-sha256", _, tag] => Some(tag),
+        _ => return Err(SaveError::Checksum(format!("unknown checksum {line:?}"))),
+    };
+    let valid = match tag.zip(key) {
+        Some((tag, key)) => {
+            from_hex(tag).is_some_and(|tag| verify_hmac_sha256(key, body.as_bytes(), &tag))
+        }
+        None => line == checksum(body, None),
+    };
+    if !valid {
+        return Err(SaveError::Checksum(
+            "the checksum does not match".to_owned(),
+        ));
+    }
+    Ok((body, tag.is_some()))
+}
+
+/// Parse a whole file, checking its checksum when it has one
+pub fn from_str<T: Versioned>(source: &str) -> Result<T, SaveError> {
+    let (source, _) = unseal(source, key().as_deref())?;
+    parse_body(source)
+}
+
+fn parse_body<T: Versioned>(source: &str) -> Result<T, SaveError> {
+    let Header { version } = ron::from_str(source).map_err(|e| SaveError::Parse(e.to_string()))?;
+
+    match version {
+        v if v == T::VERSION => parse_data(source),
+        v if v < T::VERSION => T::migrate(v, source),
+        v => Err(SaveError::Newer {
+            kind: T::KIND,
+            version: v,
+            supported: T::VERSION,
+        }),
+    }
+}
+
+/// Write a whole file, sealed with the key of the player if any
+pub fn to_string<T: Versioned>(value: &T) -> String {
+    let envelope = Envelope {
+        version: T::VERSION,
+        data: value,
+    };
+    let body = ron::ser::to_string_pretty(&envelope, Default::default()).unwrap();
+    seal(&body, key().as_deref())
+}
+
+pub fn load<T: Versioned>(path: impl AsRef<Path>) -> Result<T, SaveError> {
+    let source = std::fs::read_to_string(path).map_err(|e| SaveError::Io(e.to_string()))?;
+    from_str(&source)
+}
+
+pub fn save<T: Versioned>(path: impl AsRef<Path>, value: &T) -> Result<(), SaveError> {
+    std::fs::write(path, to_string(value)).map_err(|e| SaveError::Io(e.to_string()))
+}
+
+/// Whether the file `name` must be signed once the player has a key: the progress of the
+/// player, not the cache nor the boards shared by others
+fn must_sign(location: Location, name: &str) -> bool {
+    location == Location::Data && !name.starts_with(&format!("{LIBRARY_DIR}/"))
+}
+
+/// Load the file `name` from the platform storage, `None` if it does not exist. A file failing
+/// its checksum is renamed with [`QUARANTINE_SUFFIX`], so that the game starts over without it
+pub fn read<T: Versioned>(
+    storage: &Storage,
+    location: Location,
+    name: &str,
+) -> Result<Option<T>, SaveError> {
+    read_with(storage, location, name, key().as_deref())
+}
+
+fn read_with<T: Versioned>(
+    storage: &Storage,
+    location: Location,
+    name: &str,
+    key: Option<&[u8]>,
+) -> Result<Option<T>, SaveError> {
+    let Some(source) = storage
+        .read(location, name)
+        .map_err(|e| SaveError::Io(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let checked = unseal(&source, key).and_then(|(body, signed)| match signed {
+        false if key.is_some() && must_sign(location, name) => Err(SaveError::Checksum(
+            "not signed with the key of the player".to_owned(),
+        )),
+        _ => Ok(body),
+    });
+    match checked {
+        Ok(body) => parse_body(body).map(Some),
+        Err(error) => {
+            let quarantine = format!("{name}{QUARANTINE_SUFFIX}");
+            warn!("Moving {name} aside to {quarantine}: {error}");
+            let moved = storage
+                .write(location, &quarantine, &source)
+                .and_then(|_| storage.remove(location, name));
+            if let Err(e) = moved {
+                warn!("Could not move {name} aside: {e}");
+            }
+            Err(error)
+        }
+    }
+}
+
+/// Seal the file `name` again with `key`, as [`write`] would. Returns `false`, leaving it
+/// alone, when it is not a file of the game
+pub fn resign(
+    storage: &Storage,
+    location: Location,
+    name: &str,
+    key: &[u8],
+) -> Result<bool, SaveError> {
+    let Some(source) = storage
+        .read(location, name)
+        .map_err(|e| SaveError::Io(e.to_string()))?
+    else {
+        return Ok(false);
+    };
+    let (body, _) = unseal(&source, Some(key))?;
+    if ron::from_str::<Header>(body).is_err() {
+        return Ok(false);
+    }
+    storage
+        .write(location, name, &seal(body, Some(key)))
+        .map_err(|e| SaveError::Io(e.to_string()))?;
+    Ok(true)
+}
+
+/// Save `value` as the file `name` of the platform storage
+pub fn write<T: Versioned>(
+    storage: &Storage,
+    location: Location,
+    name: &str,
+    value: &T,
+) -> Result<(), SaveError> {
+    storage
+        .write(location, name, &to_string(value))
+        .map_err(|e| SaveError::Io(e.to_string()))
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+    use serde::{Deserialize, Serialize};
+
+    use super::{
+        from_str, parse_data, read, read_with, resign, seal, to_string, unseal, write, LossKind,
+        Replay, SaveError, SaveGame, SessionLog, Stats, Versioned, LIBRARY_DIR, QUARANTINE_SUFFIX,
+        STATS_FILE,
+    };
+    use crate::storage::{Location, Storage};
+
+    const GAME_V1: &str = include_str!("fixtures/game_v1.ron");
+    const REPLAY_V1: &str = include_str!("fixtures/replay_v1.ron");
+    const STATS_V1: &str = include_str!("fixtures/stats_v1.ron");
+    const SESSION_V1: &str = include_str!("fixtures/session_v1.ron");
+
+    #[test]
+    fn test_fixtures() {
+        let game = from_str::<SaveGame>(GAME_V1).unwrap();
+        assert_eq!((game.width, game.height), (3, 2));
+        assert_eq!(game.flagged, [IVec2::new(0, 0)]);
+        game.restore().unwrap();
+
+        let replay = from_str::<Replay>(REPLAY_V1).unwrap();
+        assert_eq!(replay.moves.len(), 3);
+
+        let stats = from_str::<Stats>(STATS_V1).unwrap();
+        assert_eq!((stats.played, stats.won), (12, 5));
+        // files older than the NF category have none
+        assert_eq!(stats.nf_won, 0);
+
+        let log = from_str::<SessionLog>(SESSION_V1).unwrap();
+        assert!(log.games[0].won());
+        assert_eq!(log.games[1].loss, Some(LossKind::AvoidableGuess));
+        assert!(!log.games[0].nf);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game = from_str::<SaveGame>(GAME_V1).unwrap();
+        // migrated games are written packed
+        let packed = to_string(&game);
+        assert!(packed.contains("version: 2") && !packed.contains("mines"));
+        assert_eq!(from_str::<SaveGame>(&packed), Ok(game));
+
+        let replay = from_str::<Replay>(REPLAY_V1).unwrap();
+        assert_eq!(from_str::<Replay>(&to_string(&replay)), Ok(replay));
+    }
+
+    #[test]
+    fn test_versions() {
+        let newer = GAME_V1.replacen("version: 1", "version: 7", 1);
+        assert!(matches!(
+            from_str::<SaveGame>(&newer),
+            Err(SaveError::Newer { version: 7, .. })
+        ));
+
+        let unversioned = "(data: (played: 1, won: 0, best_times: {}))";
+        assert!(matches!(
+            from_str::<Stats>(unversioned),
+            Err(SaveError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn test_storage() {
+        let root = std::env::temp_dir().join(format!("minesweeper-save-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+
+        assert_eq!(
+            read::<Stats>(&storage, Location::Data, STATS_FILE),
+            Ok(None)
+        );
+        let stats = from_str::<Stats>(STATS_V1).unwrap();
+        write(&storage, Location::Data, STATS_FILE, &stats).unwrap();
+        assert_eq!(
+            read::<Stats>(&storage, Location::Data, STATS_FILE),
+            Ok(Some(stats))
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_checksum() {
+        let stats = from_str::<Stats>(STATS_V1).unwrap();
+        let written = to_string(&stats);
+        assert!(written.starts_with("// checksum: fnv1a "));
+        assert_eq!(from_str::<Stats>(&written), Ok(stats));
+
+        let edited = written.replacen("played: 12", "played: 99", 1);
+        assert!(matches!(
+            from_str::<Stats>(&edited),
+            Err(SaveError::Checksum(_))
+        ));
+        let unknown = written.replacen("fnv1a", "crc32", 1);
+        assert!(matches!(
+            from_str::<Stats>(&unknown),
+            Err(SaveError::Checksum(_))
+        ));
+    }
+
+    #[test]
+    fn test_signed() {
+        let (key, other) = (b"player key".as_slice(), b"another key".as_slice());
+        let body = "(version: 1, data: ())";
+        let signed = seal(body, Some(key));
+        assert_eq!(unseal(&signed, Some(key)), Ok((body, true)));
+        // nobody else can check it
+        assert_eq!(unseal(&signed, Some(other)), Ok((body, false)));
+        assert_eq!(unseal(&signed, None), Ok((body, false)));
+        assert_eq!(unseal(body, Some(key)), Ok((body, false)));
+
+        let forged = signed.replacen("data: ()", "data: (won: 1)", 1);
+        assert!(matches!(
+            unseal(&forged, Some(key)),
+            Err(SaveError::Checksum(_))
+        ));
+        // plain checksums are easy to recompute, once there is a key they do not do
+        let recomputed = seal("(version: 1, data: (played: 1, won: 1))", None);
+        assert_eq!(
+            unseal(&recomputed, Some(key)).map(|(_, signed)| signed),
+            Ok(false)
+        );
+    }
+
+    #[test]
+    fn test_quarantine() {
+        let root =
+            std::env::temp_dir().join(format!("minesweeper-quarantine-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        let key = b"player key".as_slice();
+        let quarantined = format!("{STATS_FILE}{QUARANTINE_SUFFIX}");
+
+        // files of older builds are accepted until the player has a key
+        storage.write(Location::Data, STATS_FILE, STATS_V1).unwrap();
+        assert!(
+            read_with::<Stats>(&storage, Location::Data, STATS_FILE, None)
+                .unwrap()
+                .is_some()
+        );
+        assert!(matches!(
+            read_with::<Stats>(&storage, Location::Data, STATS_FILE, Some(key)),
+            Err(SaveError::Checksum(_))
+        ));
+        assert_eq!(storage.read(Location::Data, STATS_FILE).unwrap(), None);
+        assert_eq!(
+            storage
+                .read(Location::Data, &quarantined)
+                .unwrap()
+                .as_deref(),
+            Some(STATS_V1)
+        );
+
+        // re-signed, they load again
+        storage.write(Location::Data, STATS_FILE, STATS_V1).unwrap();
+        assert_eq!(resign(&storage, Location::Data, STATS_FILE, key), Ok(true));
+        assert_eq!(
+            read_with::<Stats>(&storage, Location::Data, STATS_FILE, Some(key)),
+            Ok(Some(from_str::<Stats>(STATS_V1).unwrap()))
+        );
+        storage
+            .write(Location::Data, "notes.txt", "not a save")
+            .unwrap();
+        assert_eq!(
+            resign(&storage, Location::Data, "notes.txt", key),
+            Ok(false)
+        );
+
+        // boards shared by others are not signed
+        let board = format!("{LIBRARY_DIR}/shared.ron");
+        storage.write(Location::Data, &board, GAME_V1).unwrap();
+        assert!(read_with::<SaveGame>(&storage, Location::Data, &board, Some(key)).is_ok());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    /// Stand-in for a future stats layout, checks that old files go through `migrate`
+    #[derive(Debug, PartialEq, Serialize, Deserialize)]
+    struct StatsV2 {
+        played: u32,
+        lost: u32,
+    }
+
+    impl Versioned for StatsV2 {
+        const KIND: &'static str = "test stats";
+        const VERSION: u32 = 2;
+
+        fn migrate(version: u32, source: &str) -> Result<Self, SaveError> {
+            match version {
+                1 => {
+                    let old = parse_data::<Stats>(source)?;
+                    Ok(Self {
+                        played: old.played,
+                        lost: old.played - old.won,
+                    })
+                }
+                _ => Err(SaveError::Unsupported {
+                    kind: Self::KIND,
+                    version,
+                }),
+            }
+        }
+    }
+
+    #[test]
+    fn test_migration() {
+        assert_eq!(
+            from_str::<StatsV2>(STATS_V1),
+            Ok(StatsV2 {
+                played: 12,
+                lost: 7
+            })
+        );
+
+        let ancient = STATS_V1.replacen("version: 1", "version: 0", 1);
+        assert!(matches!(
+            from_str::<StatsV2>(&ancient),
+            Err(SaveError::Unsupported { version: 0, .. })
+        ));
+    }
+}
@@ -0,0 +1,368 @@
+//! Compact binary layout of a board and the progress on it: saved games since version 2,
+//! library boards and the snapshots resyncing multiplayer clients all carry it.
+//!
+//! ```text
+//! format    u8, PACKED_FORMAT
+//! width     varint
+//! height    varint
+//! topology  varint length then UTF-8 name, empty for squares
+//! mines     a bit per tile, row after row, the low bit of every byte first
+//! revealed  same
+//! flagged   same
+//! ```
+//!
+//! Puzzles, with gaps or line hints, are written in [`PUZZLE_FORMAT`] and carry two more fields:
+//!
+//! ```text
+//! gaps      a bit per tile, as the layers above
+//! lines     varint count, then the varint row and mines of every line hint
+//! ```
+//!
+//! Varints are LEB128: 7 bits a byte, low bits first, the high bit set on every byte but the
+//! last. An expert board packs into less than 200 bytes.
+
+use bevy::prelude::IVec2;
+use serde::{de::Visitor, Deserialize, Serialize};
+
+use super::{SaveError, SaveGame};
+use crate::{hexcells::Line, resources::board_options::MAX_DIM};
+
+/// First byte of a packed board, bump whenever the layout changes
+pub const PACKED_FORMAT: u8 = 1;
+
+/// First byte of a packed puzzle, see [`crate::hexcells`]
+pub const PUZZLE_FORMAT: u8 = 2;
+
+/// Longest varint of a `u64`
+const MAX_VARINT_LEN: usize = 10;
+
+/// Encode a game. Tiles outside of the board are dropped
+pub fn pack(game: &SaveGame) -> Vec<u8> {
+    let (width, height) = (game.width as usize, game.height as usize);
+    let layer_len = (width * height).div_ceil(8);
+    let topology = game.topology.as_deref().unwrap_or_default();
+
+    // boards without gaps or line hints keep the first format
+    let puzzle = !game.gaps.is_empty() || !game.lines.is_empty();
+    let layers = [&game.mines, &game.revealed, &game.flagged, &game.gaps];
+    let layers = &layers[..if puzzle { 4 } else { 3 }];
+
+    let mut bytes = Vec::with_capacity(8 + topology.len() + layers.len() * layer_len);
+    bytes.push(if puzzle { PUZZLE_FORMAT } else { PACKED_FORMAT });
+    write_varint(&mut bytes, game.width as u64);
+    write_varint(&mut bytes, game.height as u64);
+    write_varint(&mut bytes, topology.len() as u64);
+    bytes.extend_from_slice(topology.as_bytes());
+    for layer in layers {
+        let start = bytes.len();
+        bytes.resize(start + layer_len, 0);
+        for coord in *layer {
+            if (0..game.width as i32).contains(&coord.x)
+                && (0..game.height as i32).contains(&coord.y)
+            {
+                let bit = coord.y as usize * width + coord.x as usize;
+                bytes[start + bit / 8] |= 1 << (bit % 8);
+            }
+        }
+    }
+    if puzzle {
+        write_varint(&mut bytes, game.lines.len() as u64);
+        for line in &game.lines {
+            write_varint(&mut bytes, line.row as u64);
+            write_varint(&mut bytes, line.mines as u64);
+        }
+    }
+    bytes
+}
+
+/// Decode a game written by [`pack`]. The board is not checked beyond its size, see
+/// `SaveGame::restore`
+pub fn unpack(mut bytes: &[u8]) -> Result<SaveGame, SaveError> {
+    let invalid = |reason: &str| SaveError::Invalid(format!("packed board: {reason}"));
+    let bytes = &mut bytes;
+
+    let puzzle = match take(bytes, 1)? {
+        [PACKED_FORMAT] => false,
+        [PUZZLE_FORMAT] => true,
+        [format] => return Err(invalid(&format!("unknown format {format}"))),
+        _ => unreachable!(),
+    };
+    let width = read_varint(bytes)?;
+    let height = read_varint(bytes)?;
+    if width > MAX_DIM as u64 || height > MAX_DIM as u64 {
+        return Err(invalid(&format!("unsupported size {width}x{height}")));
+    }
+    let (width, height) = (width as u32, height as u32);
+
+    let name_len = read_varint(bytes)?;
+    let name = take(bytes, usize::try_from(name_len).unwrap_or(usize::MAX))?;
+    let name = std::str::from_utf8(name).map_err(|_| invalid("topology is not UTF-8"))?;
+    let topology = (!name.is_empty()).then(|| name.to_owned());
+
+    let n_tiles = width as usize * height as usize;
+    let mut layer = || -> Result<Vec<IVec2>, SaveError> {
+        let bits = take(bytes, n_tiles.div_ceil(8))?;
+        Ok((0..n_tiles)
+            .filter(|&bit| bits[bit / 8] & (1 << (bit % 8)) != 0)
+            .map(|bit| IVec2::new((bit % width as usize) as i32, (bit / width as usize) as i32))
+            .collect())
+    };
+    let (mines, revealed, flagged) = (layer()?, layer()?, layer()?);
+    let gaps = if puzzle { layer()? } else { Vec::new() };
+    let mut lines = Vec::new();
+    if puzzle {
+        let count = read_varint(bytes)?;
+        for _ in 0..count {
+            let (row, mines) = (read_varint(bytes)?, read_varint(bytes)?);
+            let (Ok(row), Ok(mines)) = (u32::try_from(row), u32::try_from(mines)) else {
+                return Err(invalid("line hint out of range"));
+            };
+            lines.push(Line { row, mines });
+        }
+    }
+    if !bytes.is_empty() {
+        return Err(invalid("trailing bytes"));
+    }
+
+    Ok(SaveGame {
+        width,
+        height,
+        mines,
+        revealed,
+        flagged,
+        topology,
+        gaps,
+        lines,
+    })
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], SaveError> {
+    if bytes.len() < len {
+        return Err(SaveError::Invalid("packed board: truncated".to_owned()));
+    }
+    let (head, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(head)
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(bytes: &mut &[u8]) -> Result<u64, SaveError> {
+    let mut value = 0;
+    for i in 0..MAX_VARINT_LEN {
+        let [byte] = take(bytes, 1)? else {
+            unreachable!()
+        };
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(SaveError::Invalid(
+        "packed board: varint too long".to_owned(),
+    ))
+}
+
+/// A [`pack`]ed game, serialized as bytes: a base64 string in RON, a list of numbers in JSON
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackedGame(pub Vec<u8>);
+
+impl From<SaveGame> for PackedGame {
+    fn from(game: SaveGame) -> Self {
+        Self(pack(&game))
+    }
+}
+
+impl TryFrom<PackedGame> for SaveGame {
+    type Error = SaveError;
+
+    fn try_from(packed: PackedGame) -> Result<Self, SaveError> {
+        unpack(&packed.0)
+    }
+}
+
+impl Serialize for PackedGame {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.0)
+    }
+}
+
+impl<'de> Deserialize<'de> for PackedGame {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct BytesVisitor;
+
+        impl<'de> Visitor<'de> for BytesVisitor {
+            type Value = PackedGame;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "the bytes of a packed board")
+            }
+
+            fn visit_bytes<E: serde::de::Error>(self, bytes: &[u8]) -> Result<PackedGame, E> {
+                Ok(PackedGame(bytes.to_vec()))
+            }
+
+            fn visit_byte_buf<E: serde::de::Error>(self, bytes: Vec<u8>) -> Result<PackedGame, E> {
+                Ok(PackedGame(bytes))
+            }
+
+            fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                self,
+                mut seq: A,
+            ) -> Result<PackedGame, A::Error> {
+                let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or_default());
+                while let Some(byte) = seq.next_element()? {
+                    bytes.push(byte);
+                }
+                Ok(PackedGame(bytes))
+            }
+        }
+
+        deserializer.deserialize_bytes(BytesVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+    use serde::Serialize;
+
+    use super::{pack, read_varint, unpack, write_varint, PACKED_FORMAT, PUZZLE_FORMAT};
+    use crate::{
+        hexcells::Line,
+        resources::{
+            board::{TileMap, TileState},
+            board_state::BoardState,
+        },
+        save::{SaveError, SaveGame},
+    };
+
+    /// Expert board with the top rows played
+    fn expert() -> SaveGame {
+        let mut tile_map = TileMap::seeded(30, 16, 99, 7).unwrap();
+        let mut state = BoardState::new(&mut tile_map);
+        for y in 0..4 {
+            for x in 0..30 {
+                if state.tile([x, y]) == Some(TileState::Mine) {
+                    state.toggle_flag([x, y]);
+                } else {
+                    state.uncover([x, y]);
+                }
+            }
+        }
+        SaveGame::capture(&state)
+    }
+
+    #[test]
+    fn test_varint() {
+        for value in [0, 1, 127, 128, 300, u32::MAX as u64, u64::MAX] {
+            let mut bytes = Vec::new();
+            write_varint(&mut bytes, value);
+            assert_eq!(read_varint(&mut &bytes[..]), Ok(value));
+        }
+        let mut bytes = Vec::new();
+        write_varint(&mut bytes, 300);
+        assert_eq!(bytes, [0xac, 0x02]);
+        assert!(read_varint(&mut &[0xff; 11][..]).is_err());
+        assert!(read_varint(&mut &[0x80][..]).is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let game = expert();
+        assert_eq!(unpack(&pack(&game)), Ok(game.clone()));
+
+        let hex = SaveGame {
+            topology: Some("hex".to_owned()),
+            ..game.clone()
+        };
+        assert_eq!(unpack(&pack(&hex)), Ok(hex.clone()));
+
+        let puzzle = SaveGame {
+            gaps: vec![IVec2::new(29, 15)],
+            lines: vec![Line { row: 3, mines: 4 }],
+            ..hex
+        };
+        let bytes = pack(&puzzle);
+        assert_eq!(bytes[0], PUZZLE_FORMAT);
+        assert_eq!(unpack(&bytes), Ok(puzzle));
+
+        let empty = SaveGame {
+            width: 0,
+            height: 0,
+            mines: Vec::new(),
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        assert_eq!(pack(&empty), [PACKED_FORMAT, 0, 0, 0]);
+        assert_eq!(unpack(&pack(&empty)), Ok(empty));
+    }
+
+    #[test]
+    fn test_malformed() {
+        let bytes = pack(&expert());
+        let invalid = |bytes: &[u8]| matches!(unpack(bytes), Err(SaveError::Invalid(_)));
+        assert!(invalid(&[]));
+        assert!(invalid(&bytes[..bytes.len() - 1]));
+        assert!(invalid(&[&bytes[..], &[0]].concat()));
+        assert!(invalid(&[&[PUZZLE_FORMAT + 1], &bytes[1..]].concat()));
+        // a puzzle without its gaps and lines
+        assert!(invalid(&[&[PUZZLE_FORMAT], &bytes[1..]].concat()));
+        // a huge size is refused before anything is allocated
+        assert!(invalid(&[
+            PACKED_FORMAT,
+            0xff,
+            0xff,
+            0xff,
+            0xff,
+            0x0f,
+            1,
+            0
+        ]));
+    }
+
+    #[test]
+    fn test_size() {
+        /// Layout of saved games before packing
+        #[derive(Serialize)]
+        struct Naive<'a> {
+            width: u32,
+            height: u32,
+            mines: &'a [IVec2],
+            revealed: &'a [IVec2],
+            flagged: &'a [IVec2],
+        }
+
+        let game = expert();
+        let naive = Naive {
+            width: game.width,
+            height: game.height,
+            mines: &game.mines,
+            revealed: &game.revealed,
+            flagged: &game.flagged,
+        };
+        let packed = pack(&game).len();
+        let ron = ron::to_string(&naive).unwrap().len();
+        let json = serde_json::to_string(&naive).unwrap().len();
+        // 3 layers of 60 bytes and a header
+        assert_eq!(packed, 184);
+        assert!(
+            packed * 5 < ron && packed * 5 < json,
+            "{packed} {ron} {json}"
+        );
+        // in RON files, as base64
+        let packed_ron = ron::to_string(&super::PackedGame(pack(&game)))
+            .unwrap()
+            .len();
+        assert!(packed_ron * 4 < ron, "{packed_ron} {ron}");
+    }
+}
@@ -0,0 +1,154 @@
+use serde::{Deserialize, Serialize};
+
+use super::{SaveError, Versioned, PROFILES_FILE};
+use crate::storage::{Location, Storage};
+
+/// Avatar colors, handed out in turn to new profiles
+const AVATAR_COLORS: [[u8; 3]; 6] = [
+    [66, 135, 245],
+    [235, 87, 87],
+    [76, 175, 80],
+    [255, 167, 38],
+    [171, 71, 188],
+    [38, 198, 218],
+];
+
+/// A player sharing the machine, with settings, saves and statistics of its own. A resource
+/// when a profile is in use
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profile {
+    /// Directory of the profile, see [`profile_id`]
+    pub id: String,
+    pub name: String,
+    /// Avatar color, in sRGB
+    pub color: [u8; 3],
+}
+
+/// Every profile of the machine, in the shared config directory
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Profiles {
+    pub profiles: Vec<Profile>,
+    /// Id of the profile used last, picked at startup when none is asked for
+    pub last: Option<String>,
+}
+
+/// Directory name of the profile `name`: its letters and digits in lowercase, other characters
+/// turned into dashes
+pub fn profile_id(name: &str) -> String {
+    let id = name
+        .trim()
+        .chars()
+        .map(|c| match c {
+            c if c.is_alphanumeric() => c.to_lowercase().next().unwrap_or(c),
+            _ => '-',
+        })
+        .collect::<String>();
+    match id.trim_matches('-') {
+        "" => "player".to_owned(),
+        id => id.to_owned(),
+    }
+}
+
+impl Profiles {
+    /// Profile named `name`, created with the next avatar color when new, and used last
+    pub fn select(&mut self, name: &str) -> Profile {
+        let id = profile_id(name);
+        let profile = match self.profiles.iter().find(|profile| profile.id == id) {
+            Some(profile) => profile.clone(),
+            None => {
+                let profile = Profile {
+                    id: id.clone(),
+                    name: name.trim().to_owned(),
+                    color: AVATAR_COLORS[self.profiles.len() % AVATAR_COLORS.len()],
+                };
+                self.profiles.push(profile.clone());
+                profile
+            }
+        };
+        self.last = Some(id);
+        profile
+    }
+
+    /// Profile used last, if it still exists
+    pub fn last(&self) -> Option<&Profile> {
+        let last = self.last.as_ref()?;
+        self.profiles.iter().find(|profile| &profile.id == last)
+    }
+
+    /// Profile of this run: the one given with `--profile`, or the one used last. `None`
+    /// without profiles, the files of the storage are then used directly
+    pub fn open(storage: &Storage, args: &[String]) -> Result<Option<Profile>, SaveError> {
+        let shared = storage.shared();
+        let mut profiles =
+            super::read::<Profiles>(&shared, Location::Config, PROFILES_FILE)?.unwrap_or_default();
+
+        let asked = args
+            .iter()
+            .rposition(|arg| arg == "--profile")
+            .and_then(|idx| args.get(idx + 1));
+        let Some(name) = asked else {
+            return Ok(profiles.last().cloned());
+        };
+        let profile = profiles.select(name);
+        super::write(&shared, Location::Config, PROFILES_FILE, &profiles)?;
+        Ok(Some(profile))
+    }
+}
+
+impl Versioned for Profiles {
+    const KIND: &'static str = "profile list";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{profile_id, Profiles, AVATAR_COLORS};
+    use crate::storage::Storage;
+
+    #[test]
+    fn test_profile_id() {
+        assert_eq!(profile_id("Alice"), "alice");
+        assert_eq!(profile_id(" Jean Luc! "), "jean-luc");
+        assert_eq!(profile_id("../.."), "player");
+    }
+
+    #[test]
+    fn test_select() {
+        let mut profiles = Profiles::default();
+        assert_eq!(profiles.last(), None);
+
+        let alice = profiles.select("Alice");
+        let bob = profiles.select("Bob");
+        assert_eq!(alice.color, AVATAR_COLORS[0]);
+        assert_eq!(bob.color, AVATAR_COLORS[1]);
+        assert_eq!(profiles.last(), Some(&bob));
+
+        // same directory, same profile
+        assert_eq!(profiles.select("alice"), alice);
+        assert_eq!(profiles.profiles.len(), 2);
+        assert_eq!(profiles.last(), Some(&alice));
+    }
+
+    #[test]
+    fn test_open() {
+        let root =
+            std::env::temp_dir().join(format!("minesweeper-profiles-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        let args = |args: &[&str]| args.iter().map(|&arg| arg.to_owned()).collect::<Vec<_>>();
+
+        assert_eq!(Profiles::open(&storage, &args(&[])), Ok(None));
+        let carol = Profiles::open(&storage, &args(&["--profile", "Carol"]))
+            .unwrap()
+            .unwrap();
+        assert_eq!(carol.name, "Carol");
+        // remembered for the next run, whatever storage it is opened from
+        let profile_storage = storage.for_profile(&carol.id);
+        assert_eq!(
+            Profiles::open(&profile_storage, &args(&["--mode", "zen"])),
+            Ok(Some(carol))
+        );
+        assert!(root.join("config").join("profiles.ron").exists());
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+
+use super::Versioned;
+use crate::solver::BotSkill;
+
+/// Rating of a new player, and of human opponents, who have no rating on this machine
+pub const INITIAL_RATING: i32 = 1200;
+
+/// Largest change of a single game
+const K_FACTOR: f64 = 32.0;
+
+/// Result of a versus game for the local player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Outcome {
+    Win,
+    Draw,
+    Loss,
+}
+
+impl Outcome {
+    pub fn score(self) -> f64 {
+        match self {
+            Outcome::Win => 1.0,
+            Outcome::Draw => 0.5,
+            Outcome::Loss => 0.0,
+        }
+    }
+}
+
+/// A rated versus game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatedGame {
+    /// Unix time in seconds
+    pub finished: u64,
+    pub opponent: i32,
+    pub outcome: Outcome,
+    /// Rating after the game
+    pub rating: i32,
+}
+
+/// Elo rating of the player in versus games, against bots and hot-seat opponents
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ratings {
+    pub rating: i32,
+    /// Every rated game, the last one at the end
+    pub history: Vec<RatedGame>,
+}
+
+impl Default for Ratings {
+    fn default() -> Self {
+        Self {
+            rating: INITIAL_RATING,
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Ratings {
+    /// Expected score of `rating` against `opponent`, from 0 to 1
+    pub fn expected(rating: i32, opponent: i32) -> f64 {
+        1.0 / (1.0 + 10f64.powf((opponent - rating) as f64 / 400.0))
+    }
+
+    /// Fixed rating of a bot, calibrated by hand against each other
+    pub fn bot_rating(skill: BotSkill) -> i32 {
+        match skill {
+            BotSkill::Perfect => 1800,
+            BotSkill::Probabilistic => 1400,
+            BotSkill::Noisy => 1000,
+        }
+    }
+
+    /// Rate a game, returning the rating change. Rounded to whole points, so that the same
+    /// games always give the same rating
+    pub fn record(&mut self, opponent: i32, outcome: Outcome, finished: u64) -> i32 {
+        let expected = Self::expected(self.rating, opponent);
+        let change = (K_FACTOR * (outcome.score() - expected)).round() as i32;
+        self.rating += change;
+        self.history.push(RatedGame {
+            finished,
+            opponent,
+            outcome,
+            rating: self.rating,
+        });
+        change
+    }
+}
+
+impl Versioned for Ratings {
+    const KIND: &'static str = "rating file";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Outcome, Ratings, INITIAL_RATING};
+    use crate::save;
+
+    #[test]
+    fn test_record() {
+        assert_eq!(Ratings::expected(1500, 1500), 0.5);
+        let favorite = Ratings::expected(1600, 1200);
+        assert!((favorite + Ratings::expected(1200, 1600) - 1.0).abs() < 1e-12);
+        assert!((favorite - 10.0 / 11.0).abs() < 1e-12);
+
+        let mut ratings = Ratings::default();
+        assert_eq!(ratings.record(INITIAL_RATING, Outcome::Win, 1), 16);
+        assert_eq!(ratings.record(INITIAL_RATING, Outcome::Draw, 2), -1);
+        // an upset pays more than beating an equal
+        assert_eq!(ratings.record(1800, Outcome::Win, 3), 31);
+        assert_eq!(ratings.record(1000, Outcome::Loss, 4), -26);
+        assert_eq!(ratings.rating, 1220);
+        assert_eq!(ratings.history.len(), 4);
+        assert_eq!(ratings.history[2].rating, 1246);
+
+        let loaded: Ratings = save::from_str(&save::to_string(&ratings)).unwrap();
+        assert_eq!(loaded, ratings);
+    }
+}
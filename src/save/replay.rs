@@ -0,0 +1,649 @@
+use bevy::prelude::IVec2;
+use serde::{Deserialize, Serialize};
+
+use super::{SaveError, SaveGame, Versioned};
+use crate::{
+    hash::StableHasher,
+    resources::{
+        board::TileState,
+        board_options::GameMode,
+        board_state::{BoardState, Cover},
+        rule_set::RuleSet,
+    },
+    symmetry::CanonicalBoard,
+    topology::SQUARE,
+};
+
+/// What the player did
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Action {
+    Uncover(IVec2),
+    Flag(IVec2),
+}
+
+impl Action {
+    pub fn coord(self) -> IVec2 {
+        match self {
+            Action::Uncover(coord) | Action::Flag(coord) => coord,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayMove {
+    /// Seconds since the start of the game
+    pub time: f32,
+    pub action: Action,
+}
+
+/// How a game ended, kept with its replay so that playing it back can be checked against it
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayOutcome {
+    pub won: bool,
+    /// Seconds from the start of the game to its last move
+    pub time: f32,
+    /// [`state_hash`] of the board after the last move
+    pub board: u64,
+}
+
+/// Seconds of the replay an annotation stays up, unless the next one replaces it
+pub const ANNOTATION_SECONDS: f32 = 4.0;
+
+/// Longest text of an annotation, in characters
+pub const MAX_ANNOTATION_CHARS: usize = 200;
+
+/// Text shown as a callout while the replay plays, to explain a move in a lesson
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Annotation {
+    /// Seconds since the start of the game
+    pub time: f32,
+    pub text: String,
+    /// Tile the callout points at, above the board when `None`
+    #[serde(default)]
+    pub tile: Option<IVec2>,
+}
+
+/// Moves between two boards of the [`ReplayKeyframes`]
+pub const KEYFRAME_INTERVAL: usize = 32;
+
+/// Board of a replay after some of its moves, written in the replay file by
+/// [`Replay::add_keyframes`] so that seeking needs no play back from the start
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Keyframe {
+    /// Moves played on the board
+    pub moves: u32,
+    pub board: SaveGame,
+}
+
+/// Boards of a replay along its moves, so that seeking plays back the moves since the closest
+/// one rather than the whole game
+#[derive(Debug, Clone)]
+pub struct ReplayKeyframes {
+    /// Moves played on each board, the empty board first
+    moves: Vec<usize>,
+    states: Vec<BoardState>,
+    chords: bool,
+}
+
+impl ReplayKeyframes {
+    /// Board of `replay` after its first `moves` moves, all of them past its end
+    pub fn seek(&self, replay: &Replay, moves: usize) -> BoardState {
+        let moves = moves.min(replay.moves.len());
+        // the empty board is first, at 0 moves
+        let keyframe = self.moves.partition_point(|&at| at <= moves) - 1;
+        let mut state = self.states[keyframe].clone();
+        for ReplayMove { action, .. } in &replay.moves[self.moves[keyframe]..moves] {
+            apply(&mut state, *action, self.chords);
+        }
+        state
+    }
+
+    /// Boards kept besides the empty one
+    pub fn len(&self) -> usize {
+        self.states.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+/// Play a move on the board the way the game does, returning whether it set off a mine
+fn apply(state: &mut BoardState, action: Action, chords: bool) -> bool {
+    match action {
+        Action::Flag(coord) => {
+            state.cycle_mark(coord, false);
+            false
+        }
+        Action::Uncover(coord) => state
+            .click(coord, chords)
+            .iter()
+            .any(|&(_, tile)| tile == TileState::Mine),
+    }
+}
+
+/// Every move of a game, enough to play it back on the same board
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Replay {
+    pub width: u32,
+    pub height: u32,
+    pub mines: Vec<IVec2>,
+    pub moves: Vec<ReplayMove>,
+    /// Name of the tiling, squares when `None` as in replays older than tilings
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topology: Option<String>,
+    /// Replays older than game modes are classic games
+    #[serde(default)]
+    pub mode: GameMode,
+    /// Outcome recorded by the game, missing from older replays
+    #[serde(default)]
+    pub outcome: Option<ReplayOutcome>,
+    /// Boards along the moves, to seek fast in long replays. Trusted as written, the checks
+    /// play the moves back without them
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keyframes: Vec<Keyframe>,
+    /// Callouts in order of time, left out of the fingerprint as they change nothing of the game
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<Annotation>,
+}
+
+impl Versioned for Replay {
+    const KIND: &'static str = "replay";
+    const VERSION: u32 = 1;
+}
+
+impl Replay {
+    /// Stable hash of the board, the mode and every move with its time. Identical submissions
+    /// share it, whatever order the mines are listed in
+    pub fn fingerprint(&self) -> u64 {
+        let mut hash = StableHasher::new();
+        hash.write(b"minesweeper replay");
+        hash.write_u32(self.width);
+        hash.write_u32(self.height);
+        hash.write_u32(self.mode as u32);
+        // left out for squares, their replays keep the fingerprint they had before tilings
+        if let Some(topology) = self.topology.as_deref().filter(|&name| name != SQUARE) {
+            hash.write(b"topology");
+            hash.write_u32(topology.len() as u32);
+            hash.write(topology.as_bytes());
+        }
+
+        let mut mines = self.mines.clone();
+        mines.sort_by_key(|mine| (mine.y, mine.x));
+        mines.dedup();
+        hash.write_u32(mines.len() as u32);
+        mines.into_iter().for_each(|mine| hash.write_coord(mine));
+
+        hash.write_u32(self.moves.len() as u32);
+        for ReplayMove { time, action } in &self.moves {
+            hash.write_u32(time.to_bits());
+            match *action {
+                Action::Uncover(coord) => {
+                    hash.write(&[0]);
+                    hash.write_coord(coord);
+                }
+                Action::Flag(coord) => {
+                    hash.write(&[1]);
+                    hash.write_coord(coord);
+                }
+            }
+        }
+        hash.finish()
+    }
+
+    /// Stable hash of the board alone, the same for its rotated and mirrored copies
+    pub fn board_hash(&self) -> u64 {
+        CanonicalBoard::of_game(&self.layout()).hash()
+    }
+
+    /// Play the moves back under the rules of the mode, returning the time of the win. Fails if
+    /// a move is out of order or against the rules, or if the game is not won by the last move
+    pub fn verify(&self) -> Result<f32, SaveError> {
+        match self.simulate()? {
+            ReplayOutcome {
+                won: true, time, ..
+            } => Ok(time),
+            _ => Err(SaveError::Invalid(
+                "the board is not won by the last move".to_owned(),
+            )),
+        }
+    }
+
+    /// Play the moves back under the rules of the mode, won or lost. Fails if a move is out of
+    /// order, against the rules, or comes after the end of the game
+    pub fn simulate(&self) -> Result<ReplayOutcome, SaveError> {
+        let (_, mut state) = self.layout().restore()?;
+        let rules = RuleSet::for_mode(self.mode);
+        let invalid = |reason: String| Err(SaveError::Invalid(reason));
+
+        let mut last = 0.0;
+        let mut lost = false;
+        for (i, &ReplayMove { time, action }) in self.moves.iter().enumerate() {
+            if !time.is_finite() || time < last {
+                return invalid(format!(
+                    "move {i} happens at {time}s, before the previous one"
+                ));
+            }
+            if rules.is_won(&state) {
+                return invalid(format!("move {i} comes after the win"));
+            }
+            if lost {
+                return invalid(format!("move {i} comes after the loss"));
+            }
+            last = time;
+
+            match action {
+                Action::Flag(_) if !rules.flags => {
+                    return invalid(format!("move {i} places a flag, which the mode forbids"))
+                }
+                action => lost |= apply(&mut state, action, rules.chords),
+            }
+        }
+
+        Ok(ReplayOutcome {
+            won: !lost && rules.is_won(&state),
+            time: last,
+            board: state_hash(&state),
+        })
+    }
+
+    /// Add an annotation after the ones of the same time or earlier, its text trimmed and cut
+    /// at [`MAX_ANNOTATION_CHARS`]. Returns `false` for an empty text
+    pub fn annotate(&mut self, annotation: Annotation) -> bool {
+        let text = annotation.text.trim().chars().take(MAX_ANNOTATION_CHARS);
+        let text = text.collect::<String>().trim_end().to_owned();
+        if text.is_empty() {
+            return false;
+        }
+        let at = self
+            .annotations
+            .partition_point(|other| other.time <= annotation.time);
+        self.annotations
+            .insert(at, Annotation { text, ..annotation });
+        true
+    }
+
+    /// Annotation up at `time`, in seconds since the start of the game
+    pub fn annotation_at(&self, time: f32) -> Option<&Annotation> {
+        let at = self.annotations.partition_point(|other| other.time <= time);
+        let last = self.annotations[..at].last()?;
+        (time < last.time + ANNOTATION_SECONDS).then_some(last)
+    }
+
+    /// Seconds from the start of the game to its last move
+    pub fn duration(&self) -> f32 {
+        self.moves.last().map_or(0.0, |last| last.time)
+    }
+
+    /// Moves made by `time`, in seconds since the start of the game
+    pub fn moves_until(&self, time: f32) -> usize {
+        self.moves.partition_point(|move_| move_.time <= time)
+    }
+
+    /// Boards to seek from: the keyframes of the file when it has some, else the moves played
+    /// back once with a board every [`KEYFRAME_INTERVAL`] moves. Fails on keyframes that do not
+    /// belong to the replay
+    pub fn keyframes(&self) -> Result<ReplayKeyframes, SaveError> {
+        if self.keyframes.is_empty() {
+            return self.keyframes_every(KEYFRAME_INTERVAL);
+        }
+
+        let (_, empty) = self.layout().restore()?;
+        let mut keyframes = ReplayKeyframes {
+            moves: vec![0],
+            states: vec![empty],
+            chords: RuleSet::for_mode(self.mode).chords,
+        };
+        let mut mines = self.mines.clone();
+        mines.sort_by_key(|mine| (mine.y, mine.x));
+        for (i, Keyframe { moves, board }) in self.keyframes.iter().enumerate() {
+            let moves = *moves as usize;
+            let mut board_mines = board.mines.clone();
+            board_mines.sort_by_key(|mine| (mine.y, mine.x));
+            let invalid = |reason: &str| SaveError::Invalid(format!("keyframe {i} {reason}"));
+            if moves <= keyframes.moves[i] || moves > self.moves.len() {
+                return Err(invalid("is out of order"));
+            }
+            if (board.width, board.height, &board_mines) != (self.width, self.height, &mines) {
+                return Err(invalid("is another board"));
+            }
+            keyframes.moves.push(moves);
+            keyframes.states.push(board.restore()?.1);
+        }
+        Ok(keyframes)
+    }
+
+    /// Play the moves back once, keeping the board every `interval` moves. Moves are taken as
+    /// they come, [`Self::simulate`] is the one checking them
+    pub fn keyframes_every(&self, interval: usize) -> Result<ReplayKeyframes, SaveError> {
+        let (_, mut state) = self.layout().restore()?;
+        let mut keyframes = ReplayKeyframes {
+            moves: vec![0],
+            states: vec![state.clone()],
+            chords: RuleSet::for_mode(self.mode).chords,
+        };
+        for (i, ReplayMove { action, .. }) in self.moves.iter().enumerate() {
+            apply(&mut state, *action, keyframes.chords);
+            if (i + 1) % interval.max(1) == 0 {
+                keyframes.moves.push(i + 1);
+                keyframes.states.push(state.clone());
+            }
+        }
+        Ok(keyframes)
+    }
+
+    /// Write a keyframe every [`KEYFRAME_INTERVAL`] moves in the replay, replacing any
+    pub fn add_keyframes(&mut self) -> Result<(), SaveError> {
+        let keyframes = self.keyframes_every(KEYFRAME_INTERVAL)?;
+        self.keyframes = keyframes
+            .moves
+            .iter()
+            .zip(&keyframes.states)
+            .skip(1)
+            .map(|(&moves, state)| Keyframe {
+                moves: moves as u32,
+                board: SaveGame::capture(state),
+            })
+            .collect();
+        Ok(())
+    }
+
+    /// Empty board of the replay
+    pub fn layout(&self) -> SaveGame {
+        SaveGame {
+            width: self.width,
+            height: self.height,
+            mines: self.mines.clone(),
+            revealed: Vec::new(),
+            flagged: Vec::new(),
+            topology: self.topology.clone(),
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Play the moves back and compare the result with the recorded [`ReplayOutcome`]
+    pub fn check(&self) -> Result<ReplayOutcome, SaveError> {
+        let Some(recorded) = self.outcome else {
+            return Err(SaveError::Invalid(
+                "the replay has no recorded outcome".to_owned(),
+            ));
+        };
+        let replayed = self.simulate()?;
+        if replayed != recorded {
+            let describe = |outcome: ReplayOutcome| {
+                format!(
+                    "{} at {}s with board {:#018x}",
+                    if outcome.won { "won" } else { "not won" },
+                    outcome.time,
+                    outcome.board
+                )
+            };
+            return Err(SaveError::Invalid(format!(
+                "recorded as {}, plays back as {}",
+                describe(recorded),
+                describe(replayed)
+            )));
+        }
+        Ok(replayed)
+    }
+}
+
+/// Stable hash of what the player sees of a board: its size and the cover of every tile
+pub fn state_hash(state: &BoardState) -> u64 {
+    let dim = state.dim();
+    let mut hash = StableHasher::new();
+    hash.write(b"minesweeper board state");
+    hash.write_coord(dim);
+    for y in 0..dim.y {
+        for x in 0..dim.x {
+            let cover = match state.cover([x, y]) {
+                Some(Cover::Covered) | None => 0,
+                Some(Cover::Flagged) => 1,
+                Some(Cover::Questioned) => 2,
+                Some(Cover::Revealed) => 3,
+            };
+            hash.write(&[cover]);
+        }
+    }
+    hash.finish()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{
+        state_hash, Action, Annotation, Replay, ReplayMove, ReplayOutcome, KEYFRAME_INTERVAL,
+        MAX_ANNOTATION_CHARS,
+    };
+    use crate::{
+        resources::board_options::GameMode,
+        save::{self, SaveError},
+    };
+
+    // * 1 .
+    // 1 1 .
+    fn replay(moves: &[(f32, Action)]) -> Replay {
+        Replay {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 0)],
+            moves: moves
+                .iter()
+                .map(|&(time, action)| ReplayMove { time, action })
+                .collect(),
+            topology: None,
+            mode: GameMode::Classic,
+            outcome: None,
+            keyframes: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        let moves = [
+            (0.0, Action::Uncover(IVec2::new(2, 0))),
+            (1.25, Action::Flag(IVec2::new(0, 0))),
+            (2.5, Action::Uncover(IVec2::new(1, 1))),
+        ];
+        let replay = replay(&moves);
+        // the same on every platform and release, old submissions are compared against it
+        assert_eq!(replay.fingerprint(), 0x7dcf_5d7d_60ea_3c63);
+
+        let mut reordered = replay.clone();
+        reordered.mines.push(IVec2::new(0, 0));
+        assert_eq!(reordered.fingerprint(), replay.fingerprint());
+
+        let mut slower = replay.clone();
+        slower.moves[2].time = 2.75;
+        assert_ne!(slower.fingerprint(), replay.fingerprint());
+
+        // the same board upside down, played the same way
+        let flipped = Replay {
+            mines: vec![IVec2::new(0, 1)],
+            ..replay.clone()
+        };
+        assert_ne!(flipped.fingerprint(), replay.fingerprint());
+        assert_eq!(flipped.board_hash(), replay.board_hash());
+
+        let hardcore = Replay {
+            mode: GameMode::Hardcore,
+            ..replay.clone()
+        };
+        assert_ne!(hardcore.fingerprint(), slower.fingerprint());
+
+        // the same mines on hexagons are another board
+        let square = Replay {
+            topology: Some("square".to_owned()),
+            ..replay.clone()
+        };
+        assert_eq!(square.fingerprint(), replay.fingerprint());
+        let hex = Replay {
+            topology: Some("hex".to_owned()),
+            ..replay
+        };
+        assert_ne!(hex.fingerprint(), square.fingerprint());
+        assert_ne!(hex.board_hash(), square.board_hash());
+    }
+
+    #[test]
+    fn test_verify() {
+        let uncover = |x, y| Action::Uncover(IVec2::new(x, y));
+        // the chord on (1, 1) opens the last safe tile
+        let moves = [
+            (0.0, uncover(2, 0)),
+            (1.25, Action::Flag(IVec2::new(0, 0))),
+            (2.5, uncover(1, 1)),
+        ];
+        assert_eq!(replay(&moves).verify(), Ok(2.5));
+
+        // hardcore has neither flags nor chords
+        let hardcore = Replay {
+            mode: GameMode::Hardcore,
+            ..replay(&moves)
+        };
+        assert!(hardcore.verify().is_err());
+        let hardcore = Replay {
+            mode: GameMode::Hardcore,
+            ..replay(&[(0.0, uncover(2, 0)), (1.0, uncover(0, 1))])
+        };
+        assert_eq!(hardcore.verify(), Ok(1.0));
+
+        // nonograms are won by the flags alone
+        let flag = [(0.5, Action::Flag(IVec2::new(0, 0)))];
+        assert!(replay(&flag).verify().is_err());
+        let nonogram = Replay {
+            mode: GameMode::Nonogram,
+            ..replay(&flag)
+        };
+        assert_eq!(nonogram.verify(), Ok(0.5));
+
+        assert!(replay(&[(0.0, uncover(2, 0))]).verify().is_err());
+        assert!(replay(&[(0.0, uncover(0, 0))]).verify().is_err());
+        assert!(replay(&[(1.0, uncover(2, 0)), (0.5, uncover(0, 1))])
+            .verify()
+            .is_err());
+    }
+
+    #[test]
+    fn test_check() {
+        let uncover = |x, y| Action::Uncover(IVec2::new(x, y));
+        let mut won = replay(&[(0.0, uncover(2, 0)), (1.5, uncover(0, 1))]);
+        assert!(won.check().is_err());
+        let outcome = won.simulate().unwrap();
+        assert!(outcome.won);
+        assert_eq!(outcome.time, 1.5);
+        won.outcome = Some(outcome);
+        assert_eq!(won.check(), Ok(outcome));
+
+        // claimed faster than the moves were made
+        won.outcome = Some(ReplayOutcome {
+            time: 1.0,
+            ..outcome
+        });
+        assert!(matches!(won.check(), Err(SaveError::Invalid(_))));
+
+        // the same moves on another board
+        let moved = Replay {
+            mines: vec![IVec2::new(1, 1)],
+            outcome: Some(outcome),
+            ..won
+        };
+        assert!(moved.check().is_err());
+
+        let mut lost = replay(&[(0.0, uncover(0, 0))]);
+        let outcome = lost.simulate().unwrap();
+        assert!(!outcome.won);
+        lost.outcome = Some(outcome);
+        assert_eq!(lost.check(), Ok(outcome));
+        assert!(replay(&[(0.0, uncover(0, 0)), (1.0, uncover(2, 0))])
+            .simulate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_seek() {
+        let flag = Action::Flag(IVec2::new(0, 0));
+        // flags on and off, past a couple of keyframes, then the win
+        let mut moves = (0..2 * KEYFRAME_INTERVAL + 5)
+            .map(|i| (i as f32, flag))
+            .collect::<Vec<_>>();
+        moves.push((100.0, Action::Uncover(IVec2::new(2, 0))));
+        moves.push((101.0, Action::Uncover(IVec2::new(0, 1))));
+        let replay = replay(&moves);
+        let keyframes = replay.keyframes().unwrap();
+        assert_eq!(keyframes.len(), 2);
+
+        // the keyframes of the file, once written and read back
+        let mut indexed = replay.clone();
+        indexed.add_keyframes().unwrap();
+        assert_eq!(indexed.keyframes.len(), 2);
+        assert_eq!(indexed.keyframes[1].moves as usize, 2 * KEYFRAME_INTERVAL);
+        let indexed: Replay = save::from_str(&save::to_string(&indexed)).unwrap();
+        let stored = indexed.keyframes().unwrap();
+        assert_eq!(indexed.fingerprint(), replay.fingerprint());
+
+        for n in 0..=moves.len() {
+            let played = Replay {
+                moves: replay.moves[..n].to_vec(),
+                ..replay.clone()
+            };
+            let board = played.simulate().unwrap().board;
+            assert_eq!(state_hash(&keyframes.seek(&replay, n)), board, "{n} moves");
+            assert_eq!(state_hash(&stored.seek(&indexed, n)), board, "{n} moves");
+        }
+        let end = keyframes.seek(&replay, usize::MAX);
+        assert_eq!(state_hash(&end), replay.simulate().unwrap().board);
+
+        // keyframes of another game are refused
+        let mut swapped = indexed.clone();
+        swapped.keyframes.swap(0, 1);
+        assert!(swapped.keyframes().is_err());
+        let mut moved = indexed;
+        moved.keyframes[0].board.mines = vec![IVec2::new(2, 1)];
+        assert!(moved.keyframes().is_err());
+
+        assert_eq!(replay.duration(), 101.0);
+        assert_eq!(replay.moves_until(-1.0), 0);
+        assert_eq!(replay.moves_until(1.5), 2);
+        assert_eq!(replay.moves_until(100.0), moves.len() - 1);
+    }
+
+    #[test]
+    fn test_annotations() {
+        let mut replay = replay(&[(0.0, Action::Uncover(IVec2::new(2, 0)))]);
+        let note = |time: f32, text: &str| Annotation {
+            time,
+            text: text.to_owned(),
+            tile: None,
+        };
+        assert!(replay.annotate(note(6.0, "then this")));
+        assert!(replay.annotate(note(1.0, " the 1-2-1 \n")));
+        assert!(replay.annotate(note(6.0, "and that")));
+        assert!(!replay.annotate(note(2.0, "  ")));
+        assert!(replay.annotate(note(9.0, &"x".repeat(300))));
+        let texts = replay
+            .annotations
+            .iter()
+            .map(|annotation| annotation.text.len());
+        assert_eq!(texts.collect::<Vec<_>>(), [9, 9, 8, MAX_ANNOTATION_CHARS]);
+
+        assert_eq!(replay.annotation_at(0.5), None);
+        assert_eq!(replay.annotation_at(1.0).unwrap().text, "the 1-2-1");
+        assert_eq!(replay.annotation_at(5.5), None);
+        // the latest of the same time
+        assert_eq!(replay.annotation_at(7.0).unwrap().text, "and that");
+        assert_eq!(replay.annotation_at(9.5).unwrap().time, 9.0);
+
+        // lessons keep the fingerprint of the game
+        let plain = Replay {
+            annotations: Vec::new(),
+            ..replay.clone()
+        };
+        assert_eq!(replay.fingerprint(), plain.fingerprint());
+        let read: Replay = save::from_str(&save::to_string(&replay)).unwrap();
+        assert_eq!(read, replay);
+    }
+}
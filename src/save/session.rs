@@ -0,0 +1,107 @@
+use bevy::math::uvec2;
+use serde::{Deserialize, Serialize};
+
+use super::Versioned;
+use crate::resources::board_options::{Difficulty, GameMode};
+
+/// How a lost game was lost, judged from what the player could see before the last click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum LossKind {
+    /// The clicked tile was provably a mine
+    ProvenMine,
+    /// A provably safe tile was available elsewhere
+    AvoidableGuess,
+    /// Nothing was provably safe, the player had to guess
+    ForcedGuess,
+}
+
+/// Shares of the 3BV at which split times are taken
+pub const SPLITS: [f32; 3] = [0.25, 0.5, 0.75];
+
+/// One finished game
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameRecord {
+    /// Seconds since the Unix epoch when the game ended
+    pub finished: u64,
+    pub width: u32,
+    pub height: u32,
+    pub n_mines: u32,
+    /// Seconds from the first click to the end
+    pub duration: f32,
+    /// Uncover and flag clicks
+    pub clicks: u32,
+    /// Least number of clicks the board needs, see `BoardState::bbbv`
+    pub bbbv: u32,
+    /// `None` for a win
+    pub loss: Option<LossKind>,
+    /// Played without placing a single flag, "NF" style
+    #[serde(default)]
+    pub nf: bool,
+    /// Seconds from the first click to each of the [`SPLITS`] reached
+    #[serde(default)]
+    pub splits: Vec<f32>,
+    /// Zen games are never recorded
+    #[serde(default)]
+    pub mode: GameMode,
+    /// Played with the mines left per row and column shown, see `RuleSet::line_counts`. Such
+    /// wins and nonogram ones set no best time
+    #[serde(default)]
+    pub line_counts: bool,
+    /// Week of the puzzle of the week played, see `WeeklyPuzzle::week`. Such games have stats
+    /// of their own
+    #[serde(default)]
+    pub weekly: Option<String>,
+    /// Date of the daily challenge played, see `DailyChallenge::date`. Such games have stats of
+    /// their own
+    #[serde(default)]
+    pub daily: Option<String>,
+    /// Practice board before the daily challenge, see `DailyPlugin`. Such wins set no best
+    /// time and count nowhere in the daily stats
+    #[serde(default)]
+    pub warm_up: bool,
+}
+
+impl GameRecord {
+    pub fn won(&self) -> bool {
+        self.loss.is_none()
+    }
+
+    /// Preset name or size of the board, keying best times
+    pub fn difficulty(&self) -> String {
+        Difficulty {
+            dim: uvec2(self.width, self.height),
+            n_mines: self.n_mines,
+        }
+        .name()
+    }
+
+    /// Share of the clicks that were necessary, 1 is a perfect game
+    pub fn efficiency(&self) -> f32 {
+        self.bbbv as f32 / self.clicks.max(1) as f32
+    }
+}
+
+/// Every game played on this machine, never sent anywhere
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionLog {
+    pub games: Vec<GameRecord>,
+}
+
+impl SessionLog {
+    /// Games of both logs, each once, in the order they finished
+    pub fn merge(&self, other: &SessionLog) -> SessionLog {
+        let mut games = self.games.clone();
+        for game in &other.games {
+            if !self.games.contains(game) {
+                games.push(game.clone());
+            }
+        }
+        games.sort_by_key(|game| game.finished);
+        SessionLog { games }
+    }
+}
+
+impl Versioned for SessionLog {
+    const KIND: &'static str = "session log";
+    const VERSION: u32 = 1;
+}
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use super::Versioned;
+
+/// Estimate of a new player, halfway between the density bounds
+pub const INITIAL_SKILL: f64 = 0.5;
+
+/// Change of the first game, which says the most about a new player
+const FIRST_STEP: f64 = 0.2;
+
+/// Smallest change, so that the density keeps following the player as they improve
+const MIN_STEP: f64 = 0.04;
+
+/// Steepness of the chance to win against the gap between the skill and the board. At 6, a
+/// player a quarter of the bounds above a board wins it four times out of five
+const SPREAD: f64 = 6.0;
+
+/// Direction of the last change of the estimate
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Trend {
+    #[default]
+    Steady,
+    Rising,
+    Falling,
+}
+
+/// Skill of the player on adaptive boards, from 0 at the lower density bound to 1 at the upper
+/// one. Each game moves it like an Elo rating against the board: up after a win, down after a
+/// loss, more for the unexpected results. Boards are then played at the density of the
+/// estimate, where the player wins about half of the time
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SkillEstimate {
+    pub skill: f64,
+    /// Games recorded, the changes shrink as they add up
+    pub games: u32,
+    pub trend: Trend,
+}
+
+impl Default for SkillEstimate {
+    fn default() -> Self {
+        Self {
+            skill: INITIAL_SKILL,
+            games: 0,
+            trend: Trend::Steady,
+        }
+    }
+}
+
+impl SkillEstimate {
+    /// Chance of `skill` to win a board at `board`, both between the bounds
+    pub fn expected(skill: f64, board: f64) -> f64 {
+        1.0 / (1.0 + (-SPREAD * (skill - board)).exp())
+    }
+
+    /// Record a game on a board at `board` between the bounds, returning the change
+    pub fn record(&mut self, board: f64, won: bool) -> f64 {
+        let step = (FIRST_STEP / f64::from(self.games + 1).sqrt()).max(MIN_STEP);
+        let score = if won { 1.0 } else { 0.0 };
+        let skill = self.skill + step * 2.0 * (score - Self::expected(self.skill, board));
+        let change = skill.clamp(0.0, 1.0) - self.skill;
+
+        self.skill += change;
+        self.games += 1;
+        self.trend = match change {
+            change if change > 0.0 => Trend::Rising,
+            change if change < 0.0 => Trend::Falling,
+            _ => Trend::Steady,
+        };
+        change
+    }
+}
+
+impl Versioned for SkillEstimate {
+    const KIND: &'static str = "skill file";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{SkillEstimate, Trend, INITIAL_SKILL};
+    use crate::save;
+
+    #[test]
+    fn test_record() {
+        assert_eq!(SkillEstimate::expected(0.3, 0.3), 0.5);
+
+        let mut estimate = SkillEstimate::default();
+        // a win at the estimate moves it by the first step
+        assert!((estimate.record(INITIAL_SKILL, true) - 0.2).abs() < 1e-12);
+        assert_eq!(estimate.trend, Trend::Rising);
+        let change = estimate.record(estimate.skill, false);
+        assert!(change < 0.0 && change > -0.2);
+        assert_eq!(estimate.trend, Trend::Falling);
+
+        // an upset pays more than beating an equal board
+        let (mut even, mut upset) = (estimate, estimate);
+        assert!(upset.record(estimate.skill + 0.3, true) > even.record(estimate.skill, true));
+
+        // the estimate stays within the bounds
+        let mut estimate = SkillEstimate::default();
+        for _ in 0..50 {
+            estimate.record(estimate.skill, true);
+        }
+        assert_eq!(estimate.skill, 1.0);
+        assert_eq!(estimate.record(1.0, true), 0.0);
+        assert_eq!(estimate.trend, Trend::Steady);
+
+        let loaded: SkillEstimate = save::from_str(&save::to_string(&estimate)).unwrap();
+        assert_eq!(loaded, estimate);
+    }
+}
@@ -0,0 +1,207 @@
+use bevy::math::uvec2;
+use serde::{Deserialize, Serialize};
+
+use super::{SaveError, SaveGame, Versioned, SLOTS_DIR};
+use crate::{
+    resources::board_options::Difficulty,
+    storage::{Location, Storage},
+};
+
+/// A game put aside by the player, next to the autosave, to be picked up later
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSlot {
+    pub game: SaveGame,
+    /// Seconds played before the game was saved
+    pub elapsed: f32,
+    /// Unix time of the save
+    pub saved: u64,
+}
+
+impl Versioned for SavedSlot {
+    const KIND: &'static str = "save slot";
+    const VERSION: u32 = 1;
+}
+
+/// A file of [`SLOTS_DIR`], read back
+#[derive(Debug, Clone, PartialEq)]
+pub struct SlotEntry {
+    /// Name of the file in [`SLOTS_DIR`]
+    pub file: String,
+    pub slot: SavedSlot,
+    /// Share of the safe tiles revealed, see `BoardState::progress`
+    pub progress: f32,
+}
+
+impl SlotEntry {
+    pub fn new(file: impl Into<String>, slot: SavedSlot) -> Result<Self, SaveError> {
+        let (_, state) = slot.game.restore()?;
+        Ok(Self {
+            file: file.into(),
+            progress: state.progress(),
+            slot,
+        })
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        let game = &self.slot.game;
+        Difficulty {
+            dim: uvec2(game.width, game.height),
+            n_mines: game.mines.len() as u32,
+        }
+    }
+}
+
+/// Save slots of [`SLOTS_DIR`] in the data directory
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Slots {
+    /// Newest first, ties broken by file name
+    pub entries: Vec<SlotEntry>,
+    /// Files that are not save slots, and why
+    pub invalid: Vec<(String, SaveError)>,
+}
+
+impl Slots {
+    /// Read every `.ron` file of the slots directory
+    pub fn scan(storage: &Storage) -> Result<Self, SaveError> {
+        let files = storage
+            .list(Location::Data, SLOTS_DIR)
+            .map_err(|e| SaveError::Io(e.to_string()))?;
+
+        let mut slots = Self::default();
+        for file in files.into_iter().filter(|file| file.ends_with(".ron")) {
+            let entry = Self::read(storage, &file).and_then(|slot| SlotEntry::new(&file, slot));
+            match entry {
+                Ok(entry) => slots.entries.push(entry),
+                Err(error) => slots.invalid.push((file, error)),
+            }
+        }
+        slots.entries.sort_by(|a, b| {
+            b.slot
+                .saved
+                .cmp(&a.slot.saved)
+                .then_with(|| a.file.cmp(&b.file))
+        });
+        Ok(slots)
+    }
+
+    fn read(storage: &Storage, file: &str) -> Result<SavedSlot, SaveError> {
+        let path = format!("{SLOTS_DIR}/{file}");
+        super::read::<SavedSlot>(storage, Location::Data, &path)?
+            .ok_or_else(|| SaveError::Io("file removed".to_owned()))
+    }
+
+    /// Save `slot` in a new file, returning its name
+    pub fn write(storage: &Storage, slot: &SavedSlot) -> Result<String, SaveError> {
+        let file = Self::free_name(storage, slot.saved)?;
+        super::write(
+            storage,
+            Location::Data,
+            &format!("{SLOTS_DIR}/{file}"),
+            slot,
+        )?;
+        Ok(file)
+    }
+
+    /// Copy the slot saved as `file` to a new file, returning its name
+    pub fn duplicate(storage: &Storage, file: &str) -> Result<String, SaveError> {
+        Self::write(storage, &Self::read(storage, file)?)
+    }
+
+    pub fn delete(storage: &Storage, file: &str) -> Result<(), SaveError> {
+        storage
+            .remove(Location::Data, &format!("{SLOTS_DIR}/{file}"))
+            .map_err(|e| SaveError::Io(e.to_string()))
+    }
+
+    /// `slot-<saved>.ron`, numbered from 2 when taken. Looked up file by file, as the web cannot
+    /// list directories
+    fn free_name(storage: &Storage, saved: u64) -> Result<String, SaveError> {
+        let mut n = 1;
+        loop {
+            let file = match n {
+                1 => format!("slot-{saved}.ron"),
+                n => format!("slot-{saved}-{n}.ron"),
+            };
+            let taken = storage
+                .read(Location::Data, &format!("{SLOTS_DIR}/{file}"))
+                .map_err(|e| SaveError::Io(e.to_string()))?;
+            if taken.is_none() {
+                return Ok(file);
+            }
+            n += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{SavedSlot, Slots, SLOTS_DIR};
+    use crate::{
+        save::SaveGame,
+        storage::{Location, Storage},
+    };
+
+    fn slot(saved: u64, revealed: &[[i32; 2]]) -> SavedSlot {
+        SavedSlot {
+            game: SaveGame {
+                width: 4,
+                height: 3,
+                mines: vec![IVec2::new(3, 2)],
+                revealed: revealed.iter().map(|&tile| IVec2::from(tile)).collect(),
+                flagged: Vec::new(),
+                topology: None,
+                gaps: Vec::new(),
+                lines: Vec::new(),
+            },
+            elapsed: 12.5,
+            saved,
+        }
+    }
+
+    #[test]
+    fn test_slots() {
+        let root = std::env::temp_dir().join(format!("minesweeper-slots-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+        assert_eq!(Slots::scan(&storage), Ok(Slots::default()));
+
+        let old = slot(100, &[[0, 0], [1, 0]]);
+        let new = slot(200, &[]);
+        assert_eq!(Slots::write(&storage, &old).unwrap(), "slot-100.ron");
+        assert_eq!(Slots::write(&storage, &new).unwrap(), "slot-200.ron");
+        assert_eq!(
+            Slots::duplicate(&storage, "slot-100.ron").unwrap(),
+            "slot-100-2.ron"
+        );
+        let path = format!("{SLOTS_DIR}/notes.ron");
+        storage.write(Location::Data, &path, "(notes)").unwrap();
+
+        let slots = Slots::scan(&storage).unwrap();
+        let files = |slots: &Slots| {
+            slots
+                .entries
+                .iter()
+                .map(|entry| entry.file.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            files(&slots),
+            ["slot-200.ron", "slot-100-2.ron", "slot-100.ron"]
+        );
+        assert_eq!(slots.invalid.len(), 1);
+        assert_eq!(slots.entries[1].slot, old);
+        // 2 of the 11 safe tiles revealed
+        assert!((slots.entries[2].progress - 2.0 / 11.0).abs() < 1e-6);
+        assert_eq!(slots.entries[0].progress, 0.0);
+        assert_eq!(slots.entries[0].difficulty().name(), "4x3/1");
+
+        Slots::delete(&storage, "slot-100.ron").unwrap();
+        let slots = Slots::scan(&storage).unwrap();
+        assert_eq!(files(&slots), ["slot-200.ron", "slot-100-2.ron"]);
+        // taken again by the next save of the same second
+        assert_eq!(Slots::write(&storage, &old).unwrap(), "slot-100.ron");
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
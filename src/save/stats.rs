@@ -0,0 +1,343 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{GameRecord, Versioned};
+use crate::resources::board_options::GameMode;
+
+/// Longest note of a daily challenge, in characters
+pub const MAX_NOTE_CHARS: usize = 80;
+
+/// Lifetime statistics of the player
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Stats {
+    pub played: u32,
+    pub won: u32,
+    /// Fastest win in seconds, keyed by difficulty name
+    pub best_times: BTreeMap<String, f32>,
+    /// Wins without flags, a category of their own
+    #[serde(default)]
+    pub nf_won: u32,
+    /// Fastest win without flags in seconds, keyed by difficulty name
+    #[serde(default)]
+    pub nf_best_times: BTreeMap<String, f32>,
+    /// Split times of the best time, keyed by difficulty name
+    #[serde(default)]
+    pub best_splits: BTreeMap<String, Vec<f32>>,
+    /// Wins in hardcore mode, a category of their own
+    #[serde(default)]
+    pub hardcore_won: u32,
+    /// Fastest hardcore win in seconds, keyed by difficulty name
+    #[serde(default)]
+    pub hardcore_best_times: BTreeMap<String, f32>,
+    /// Wins of the puzzle of the week, a category of their own
+    #[serde(default)]
+    pub weekly_won: u32,
+    /// Fastest win of each puzzle of the week in seconds, keyed by week
+    #[serde(default)]
+    pub weekly_best_times: BTreeMap<String, f32>,
+    /// Daily challenges played, warm-ups left out
+    #[serde(default)]
+    pub daily_played: u32,
+    /// Wins of the daily challenge, a category of their own
+    #[serde(default)]
+    pub daily_won: u32,
+    /// Fastest win of each daily challenge in seconds, keyed by date
+    #[serde(default)]
+    pub daily_best_times: BTreeMap<String, f32>,
+    /// Note of the player on their daily challenge, keyed by date
+    #[serde(default)]
+    pub daily_notes: BTreeMap<String, String>,
+}
+
+impl Stats {
+    /// Count a finished game, returning whether it set a best time
+    pub fn record(&mut self, game: &GameRecord) -> bool {
+        self.played += 1;
+        let daily = game.daily.as_ref().filter(|_| !game.warm_up);
+        self.daily_played += u32::from(daily.is_some());
+        if !game.won() {
+            return false;
+        }
+
+        fn best(times: &mut BTreeMap<String, f32>, key: String, time: f32) -> bool {
+            let best = times.entry(key).or_insert(f32::INFINITY);
+            let improved = time < *best;
+            *best = best.min(time);
+            improved
+        }
+
+        self.won += 1;
+        let hardcore = game.mode == GameMode::Hardcore;
+        self.nf_won += u32::from(game.nf);
+        self.hardcore_won += u32::from(hardcore);
+        // the same board all week long, its times only compare with each other
+        if let Some(week) = &game.weekly {
+            self.weekly_won += 1;
+            return best(&mut self.weekly_best_times, week.clone(), game.duration);
+        }
+        if let Some(date) = daily {
+            self.daily_won += 1;
+            return best(&mut self.daily_best_times, date.clone(), game.duration);
+        }
+        // the counts along the border make any board easier, warm-ups are practice
+        if game.line_counts || game.mode == GameMode::Nonogram || game.warm_up {
+            return false;
+        }
+
+        let mut improved = best(&mut self.best_times, game.difficulty(), game.duration);
+        if improved {
+            self.best_splits
+                .insert(game.difficulty(), game.splits.clone());
+        }
+        if game.nf {
+            improved |= best(&mut self.nf_best_times, game.difficulty(), game.duration);
+        }
+        if hardcore {
+            improved |= best(
+                &mut self.hardcore_best_times,
+                game.difficulty(),
+                game.duration,
+            );
+        }
+        improved
+    }
+
+    /// Attach a note to the daily challenge of `date`, trimmed and cut at [`MAX_NOTE_CHARS`].
+    /// An empty note removes it
+    pub fn set_note(&mut self, date: &str, note: &str) {
+        let note = note.trim().chars().take(MAX_NOTE_CHARS).collect::<String>();
+        let note = note.trim_end();
+        if note.is_empty() {
+            self.daily_notes.remove(date);
+        } else {
+            self.daily_notes.insert(date.to_owned(), note.to_owned());
+        }
+    }
+
+    /// Combine the stats of two machines that both started from `base`, the last version they
+    /// shared. Games counted on either side are counted once, and the best times are the best
+    /// of both
+    pub fn merge(&self, base: &Stats, other: &Stats) -> Stats {
+        let count = |ours: u32, base: u32, theirs: u32| {
+            (ours + theirs).saturating_sub(base).max(ours).max(theirs)
+        };
+        fn best(
+            ours: &BTreeMap<String, f32>,
+            theirs: &BTreeMap<String, f32>,
+        ) -> BTreeMap<String, f32> {
+            let mut times = ours.clone();
+            for (key, &time) in theirs {
+                let best = times.entry(key.clone()).or_insert(time);
+                *best = best.min(time);
+            }
+            times
+        }
+
+        let best_times = best(&self.best_times, &other.best_times);
+        // splits of whichever side holds the best time
+        let best_splits = best_times
+            .keys()
+            .filter_map(|key| {
+                let splits = match (self.best_times.get(key), other.best_times.get(key)) {
+                    (Some(ours), Some(theirs)) if theirs < ours => other.best_splits.get(key),
+                    (Some(_), _) => self.best_splits.get(key),
+                    (None, _) => other.best_splits.get(key),
+                };
+                Some((key.clone(), splits?.clone()))
+            })
+            .collect();
+
+        // the note edited since the base, removals included, ours when both were
+        let dates = self
+            .daily_notes
+            .keys()
+            .chain(base.daily_notes.keys())
+            .chain(other.daily_notes.keys());
+        let daily_notes = dates
+            .filter_map(|date| {
+                let ours = self.daily_notes.get(date);
+                let note = if ours != base.daily_notes.get(date) {
+                    ours
+                } else {
+                    other.daily_notes.get(date)
+                };
+                Some((date.clone(), note?.clone()))
+            })
+            .collect();
+
+        Stats {
+            played: count(self.played, base.played, other.played),
+            won: count(self.won, base.won, other.won),
+            best_times,
+            nf_won: count(self.nf_won, base.nf_won, other.nf_won),
+            nf_best_times: best(&self.nf_best_times, &other.nf_best_times),
+            best_splits,
+            hardcore_won: count(self.hardcore_won, base.hardcore_won, other.hardcore_won),
+            hardcore_best_times: best(&self.hardcore_best_times, &other.hardcore_best_times),
+            weekly_won: count(self.weekly_won, base.weekly_won, other.weekly_won),
+            weekly_best_times: best(&self.weekly_best_times, &other.weekly_best_times),
+            daily_played: count(self.daily_played, base.daily_played, other.daily_played),
+            daily_won: count(self.daily_won, base.daily_won, other.daily_won),
+            daily_best_times: best(&self.daily_best_times, &other.daily_best_times),
+            daily_notes,
+        }
+    }
+}
+
+impl Versioned for Stats {
+    const KIND: &'static str = "statistics file";
+    const VERSION: u32 = 1;
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Stats, MAX_NOTE_CHARS};
+    use crate::{resources::board_options::GameMode, save::GameRecord};
+
+    #[test]
+    fn test_record() {
+        let game = |duration: f32, nf| GameRecord {
+            finished: 0,
+            width: 9,
+            height: 9,
+            n_mines: 10,
+            duration,
+            clicks: 20,
+            bbbv: 15,
+            loss: None,
+            nf,
+            splits: vec![duration / 4.0, duration / 2.0],
+            mode: GameMode::Classic,
+            line_counts: false,
+            weekly: None,
+            daily: None,
+            warm_up: false,
+        };
+
+        let mut stats = Stats::default();
+        assert!(stats.record(&game(30.0, false)));
+        assert!(stats.record(&game(40.0, true)));
+        assert!(!stats.record(&game(50.0, true)));
+        assert!(!stats.record(&GameRecord {
+            loss: Some(crate::save::LossKind::ForcedGuess),
+            ..game(5.0, true)
+        }));
+
+        assert_eq!((stats.played, stats.won, stats.nf_won), (4, 3, 2));
+        assert_eq!(stats.best_times["Easy"], 30.0);
+        assert_eq!(stats.nf_best_times["Easy"], 40.0);
+        assert_eq!(stats.best_splits["Easy"], [7.5, 15.0]);
+
+        // hardcore times improve their own board, not necessarily the overall one
+        assert!(stats.record(&GameRecord {
+            mode: GameMode::Hardcore,
+            ..game(35.0, true)
+        }));
+        assert_eq!(stats.hardcore_won, 1);
+        assert_eq!(stats.hardcore_best_times["Easy"], 35.0);
+        assert_eq!(stats.best_times["Easy"], 30.0);
+
+        // a win, but not a time
+        assert!(!stats.record(&GameRecord {
+            line_counts: true,
+            ..game(10.0, true)
+        }));
+        assert_eq!((stats.won, stats.nf_won), (5, 4));
+        assert_eq!(stats.best_times["Easy"], 30.0);
+        assert_eq!(stats.nf_best_times["Easy"], 35.0);
+
+        // the puzzle of the week keeps its times apart
+        assert!(stats.record(&GameRecord {
+            weekly: Some("2024-01-01".to_owned()),
+            ..game(5.0, true)
+        }));
+        assert_eq!((stats.won, stats.weekly_won), (6, 1));
+        assert_eq!(stats.weekly_best_times["2024-01-01"], 5.0);
+        assert_eq!(stats.best_times["Easy"], 30.0);
+
+        // so does the daily challenge, its warm-ups are practice
+        let daily = Some("2024-01-02".to_owned());
+        assert!(!stats.record(&GameRecord {
+            daily: daily.clone(),
+            warm_up: true,
+            ..game(4.0, false)
+        }));
+        assert_eq!((stats.won, stats.daily_played, stats.daily_won), (7, 0, 0));
+        assert_eq!(stats.best_times["Easy"], 30.0);
+        assert!(stats.record(&GameRecord {
+            daily,
+            ..game(20.0, false)
+        }));
+        assert_eq!(
+            (stats.played, stats.daily_played, stats.daily_won),
+            (9, 1, 1)
+        );
+        assert_eq!(stats.daily_best_times["2024-01-02"], 20.0);
+        assert_eq!(stats.best_times["Easy"], 30.0);
+    }
+
+    #[test]
+    fn test_notes() {
+        let mut stats = Stats::default();
+        stats.set_note("2024-01-02", "  lost to a 50/50 at bottom-left \n");
+        assert_eq!(
+            stats.daily_notes["2024-01-02"],
+            "lost to a 50/50 at bottom-left"
+        );
+        stats.set_note("2024-01-03", &"x".repeat(200));
+        assert_eq!(stats.daily_notes["2024-01-03"].len(), MAX_NOTE_CHARS);
+        stats.set_note("2024-01-03", " ");
+        assert!(!stats.daily_notes.contains_key("2024-01-03"));
+
+        // each side keeps its own edits, removals included
+        let base = stats.clone();
+        let mut ours = base.clone();
+        ours.set_note("2024-01-02", "");
+        ours.set_note("2024-01-04", "ours");
+        let mut theirs = base.clone();
+        theirs.set_note("2024-01-05", "theirs");
+        let merged = ours.merge(&base, &theirs);
+        assert_eq!(
+            merged.daily_notes.into_iter().collect::<Vec<_>>(),
+            [
+                ("2024-01-04".to_owned(), "ours".to_owned()),
+                ("2024-01-05".to_owned(), "theirs".to_owned())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_merge() {
+        let base = Stats {
+            played: 10,
+            won: 4,
+            best_times: [("Easy".to_owned(), 30.0)].into(),
+            best_splits: [("Easy".to_owned(), vec![10.0])].into(),
+            ..Default::default()
+        };
+        // 3 games on this machine, 2 on the other, which beat the best time
+        let ours = Stats {
+            played: 13,
+            won: 5,
+            ..base.clone()
+        };
+        let theirs = Stats {
+            played: 12,
+            won: 6,
+            best_times: [("Easy".to_owned(), 25.0), ("Expert".to_owned(), 200.0)].into(),
+            best_splits: [("Easy".to_owned(), vec![8.0])].into(),
+            ..base.clone()
+        };
+
+        let merged = ours.merge(&base, &theirs);
+        assert_eq!((merged.played, merged.won), (15, 7));
+        assert_eq!(merged.best_times["Easy"], 25.0);
+        assert_eq!(merged.best_times["Expert"], 200.0);
+        assert_eq!(merged.best_splits["Easy"], [8.0]);
+        assert_eq!(merged, theirs.merge(&base, &ours));
+
+        // merging twice counts nothing twice
+        assert_eq!(merged.merge(&merged, &merged), merged);
+    }
+}
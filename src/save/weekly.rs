@@ -0,0 +1,265 @@
+//! Puzzle of the week: a curated list of boards, one per week, bundled in `assets/weekly.ron`
+//! and replaced by a newer list downloaded in the cache directory. Unlike the boards of the
+//! library every player gets the same board for the week, so the list is signed, see
+//! [`PuzzleRotation::sign`].
+
+use serde::{Deserialize, Serialize};
+
+use super::{SaveError, Versioned, WEEKLY_FILE};
+use crate::{
+    hash::StableHasher,
+    resources::board_options::{Difficulty, GameMode},
+    storage::{Location, Storage},
+};
+
+/// Built-in rotation
+const BUNDLED: &str = include_str!("../../assets/weekly.ron");
+
+/// Mixed into the signature. It ships with the game, so the signature catches truncated
+/// downloads and hand edits, not someone determined to forge a list
+const SIGNING_KEY: &[u8] = b"minesweeper puzzle of the week";
+
+/// Days a puzzle stays current
+pub const WEEK_DAYS: u64 = 7;
+
+/// Board of one week
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WeeklyPuzzle {
+    /// First day of the week, `YYYY-MM-DD` in UTC. Also keys the best times of the puzzle
+    pub week: String,
+    /// Seed of the mine layout, see `TileMap::seeded`
+    pub seed: u64,
+    pub mode: GameMode,
+    pub width: u32,
+    pub height: u32,
+    pub n_mines: u32,
+}
+
+impl WeeklyPuzzle {
+    pub fn difficulty(&self) -> Difficulty {
+        Difficulty {
+            dim: [self.width, self.height].into(),
+            n_mines: self.n_mines,
+        }
+    }
+
+    /// Day the week starts, in days since the Unix epoch
+    pub fn first_day(&self) -> Option<u64> {
+        parse_date(&self.week)
+    }
+}
+
+/// Puzzles in the order of their weeks
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PuzzleRotation {
+    pub puzzles: Vec<WeeklyPuzzle>,
+    /// See [`PuzzleRotation::sign`]
+    pub signature: u64,
+}
+
+impl PuzzleRotation {
+    /// Hash of the key and of every puzzle, written by whoever curates the list
+    pub fn sign(puzzles: &[WeeklyPuzzle]) -> u64 {
+        let mut hash = StableHasher::new();
+        hash.write(SIGNING_KEY);
+        hash.write_u32(puzzles.len() as u32);
+        for puzzle in puzzles {
+            hash.write_u32(puzzle.week.len() as u32);
+            hash.write(puzzle.week.as_bytes());
+            hash.write(&puzzle.seed.to_le_bytes());
+            hash.write_u32(puzzle.mode as u32);
+            hash.write_u32(puzzle.width);
+            hash.write_u32(puzzle.height);
+            hash.write_u32(puzzle.n_mines);
+        }
+        hash.finish()
+    }
+
+    /// Check the signature, the dates and the boards
+    pub fn validate(&self) -> Result<(), SaveError> {
+        if self.signature != Self::sign(&self.puzzles) {
+            return Err(SaveError::Invalid(
+                "the signature does not match".to_owned(),
+            ));
+        }
+        for puzzle in &self.puzzles {
+            let week = &puzzle.week;
+            if puzzle.first_day().is_none() {
+                return Err(SaveError::Invalid(format!("{week} is not a date")));
+            }
+            let n_tiles = puzzle.width * puzzle.height;
+            if n_tiles == 0 || puzzle.n_mines >= n_tiles {
+                return Err(SaveError::Invalid(format!(
+                    "the board of {week} holds {} mines on {n_tiles} tiles",
+                    puzzle.n_mines
+                )));
+            }
+            // neither is recorded in the stats
+            if matches!(puzzle.mode, GameMode::Zen | GameMode::Versus) {
+                return Err(SaveError::Invalid(format!(
+                    "the board of {week} is a {:?} game",
+                    puzzle.mode
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// The downloaded rotation of the cache directory if there is a valid one, the bundled one
+    /// otherwise. The error tells why the download was ignored
+    pub fn load(storage: Option<&Storage>) -> (Self, Option<SaveError>) {
+        let downloaded = storage
+            .map(|storage| super::read::<Self>(storage, Location::Cache, WEEKLY_FILE))
+            .transpose()
+            .map(Option::flatten)
+            .and_then(|rotation| rotation.map(|r| r.validate().map(|_| r)).transpose());
+        match downloaded {
+            Ok(Some(rotation)) => (rotation, None),
+            Ok(None) => (Self::bundled(), None),
+            Err(error) => (Self::bundled(), Some(error)),
+        }
+    }
+
+    pub fn bundled() -> Self {
+        let rotation = super::from_str::<Self>(BUNDLED).expect("invalid bundled rotation");
+        debug_assert_eq!(rotation.validate(), Ok(()));
+        rotation
+    }
+
+    /// Puzzle of the week holding `day`, in days since the Unix epoch
+    pub fn current(&self, day: u64) -> Option<&WeeklyPuzzle> {
+        self.puzzles
+            .iter()
+            .filter_map(|puzzle| Some((puzzle.first_day()?, puzzle)))
+            .filter(|&(first, _)| first <= day && day < first + WEEK_DAYS)
+            .max_by_key(|&(first, _)| first)
+            .map(|(_, puzzle)| puzzle)
+    }
+}
+
+impl Versioned for PuzzleRotation {
+    const KIND: &'static str = "puzzle rotation";
+    const VERSION: u32 = 1;
+}
+
+/// Days since the Unix epoch of a `YYYY-MM-DD` date
+pub fn parse_date(date: &str) -> Option<u64> {
+    let mut parts = date.splitn(3, '-').map(|part| part.parse::<u64>().ok());
+    let (year, month, day) = (parts.next()??, parts.next()??, parts.next()??);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let month_days = match month {
+        2 => 28 + u64::from(leap),
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    };
+    if year < 1970 || !(1..=12).contains(&month) || day == 0 || day > month_days {
+        return None;
+    }
+
+    // days from civil, with years starting in March so that leap days come last
+    let year = if month <= 2 { year - 1 } else { year };
+    let (era, year_of_era) = (year / 400, year % 400);
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Some(era * 146_097 + day_of_era - 719_468)
+}
+
+/// `YYYY-MM-DD` date of a day since the Unix epoch, the inverse of [`parse_date`]
+pub fn format_date(day: u64) -> String {
+    // civil from days, with years starting in March as above
+    let days = day + 719_468;
+    let (era, day_of_era) = (days / 146_097, days % 146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_from_march = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_from_march + 2) / 5 + 1;
+    let month = (month_from_march + 2) % 12 + 1;
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+#[cfg(test)]
+mod test {
+    use super::{format_date, parse_date, PuzzleRotation, WeeklyPuzzle};
+    use crate::{
+        resources::board_options::GameMode,
+        save::{from_str, to_string, SaveError},
+    };
+
+    fn puzzle(week: &str, seed: u64) -> WeeklyPuzzle {
+        WeeklyPuzzle {
+            week: week.to_owned(),
+            seed,
+            mode: GameMode::Classic,
+            width: 9,
+            height: 9,
+            n_mines: 10,
+        }
+    }
+
+    #[test]
+    fn test_parse_date() {
+        assert_eq!(parse_date("1970-01-01"), Some(0));
+        assert_eq!(parse_date("2000-03-01"), Some(11_017));
+        assert_eq!(parse_date("2024-02-29"), Some(19_782));
+        assert_eq!(parse_date("2023-02-29"), None);
+        assert_eq!(parse_date("2024-13-01"), None);
+        assert_eq!(parse_date("yesterday"), None);
+    }
+
+    #[test]
+    fn test_format_date() {
+        for date in [
+            "1970-01-01",
+            "2000-02-29",
+            "2000-03-01",
+            "2023-12-31",
+            "2024-02-29",
+        ] {
+            assert_eq!(format_date(parse_date(date).unwrap()), date);
+        }
+        for day in 0..20_000 {
+            assert_eq!(parse_date(&format_date(day)), Some(day));
+        }
+    }
+
+    #[test]
+    fn test_rotation() {
+        let puzzles = vec![puzzle("2024-01-01", 1), puzzle("2024-01-08", 2)];
+        let rotation = PuzzleRotation {
+            signature: PuzzleRotation::sign(&puzzles),
+            puzzles,
+        };
+        assert_eq!(rotation.validate(), Ok(()));
+        assert_eq!(from_str(&to_string(&rotation)), Ok(rotation.clone()));
+
+        let monday = parse_date("2024-01-01").unwrap();
+        assert_eq!(rotation.current(monday).map(|p| p.seed), Some(1));
+        assert_eq!(rotation.current(monday + 6).map(|p| p.seed), Some(1));
+        assert_eq!(rotation.current(monday + 7).map(|p| p.seed), Some(2));
+        assert_eq!(rotation.current(monday + 14), None);
+        assert_eq!(rotation.current(monday - 1), None);
+
+        let mut edited = rotation.clone();
+        edited.puzzles[1].seed = 3;
+        assert!(matches!(edited.validate(), Err(SaveError::Invalid(_))));
+
+        let zen = vec![WeeklyPuzzle {
+            mode: GameMode::Zen,
+            ..puzzle("2024-01-01", 1)
+        }];
+        let zen = PuzzleRotation {
+            signature: PuzzleRotation::sign(&zen),
+            puzzles: zen,
+        };
+        assert!(zen.validate().is_err());
+    }
+
+    #[test]
+    fn test_bundled() {
+        let rotation = PuzzleRotation::bundled();
+        assert_eq!(rotation.validate(), Ok(()));
+        assert!(!rotation.puzzles.is_empty());
+    }
+}
@@ -0,0 +1,983 @@
+//! Multiplayer sessions over TCP, carrying the `protocol` messages one a line as RON.
+//!
+//! The [`Host`] listens for players and keeps the shared board: it numbers every move in a
+//! [`MoveJournal`] and sends it to every player, its own moves and theirs alike. A [`Client`]
+//! sends its moves to the host and only plays the ones the host sends back, so that every board
+//! sees the moves in the same order. Both sides start a connection with a [`Handshake`], and
+//! close it with a [`HandshakeError`] when the other side runs another version, or set up
+//! another game.
+//!
+//! Once in, a client sends [`Message::Resume`] with the moves it applied, and the host replays
+//! the ones it missed, or sends the whole board. A client that lost the host does the same after
+//! [`Client::reconnect`], so that it picks up the game where it left it.
+
+use std::{
+    fmt::Display,
+    io::{self, BufRead, BufReader, ErrorKind, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use bevy::prelude::IVec2;
+
+use crate::{
+    protocol::{ClientSync, Emote, Handshake, HandshakeError, Message, MoveJournal, SyncStep},
+    resources::{
+        board_state::BoardState,
+        rule_set::{Player, Role, Roles, RuleSet},
+    },
+    save::{Action, SaveGame},
+};
+
+/// Moves the host keeps for players that come back, older ones are sent as a snapshot
+pub const JOURNAL_CAPACITY: usize = 1024;
+
+/// Longest wait for the host to answer a connection
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Name of the host in the chat of the other players
+pub const HOST_NAME: &str = "Player 1";
+
+/// Reasons a session cannot go on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// Malformed command line
+    Argument {
+        arg: String,
+        reason: String,
+    },
+    /// The address could not be bound or reached
+    Connect {
+        addr: String,
+        reason: String,
+    },
+    Io(String),
+    /// The other side closed the connection
+    Closed,
+    /// A line that is not a message
+    Message {
+        line: String,
+        reason: String,
+    },
+    /// The first message of the other side was not a handshake
+    NoHandshake,
+    Handshake(HandshakeError),
+}
+
+impl Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SessionError::Argument { arg, reason } => write!(f, "Invalid {arg}: {reason}"),
+            SessionError::Connect { addr, reason } => {
+                write!(f, "Could not connect to {addr}: {reason}")
+            }
+            SessionError::Io(reason) => write!(f, "Lost the connection: {reason}"),
+            SessionError::Closed => write!(f, "The other player left"),
+            SessionError::Message { line, reason } => {
+                write!(f, "Invalid message {line:?}: {reason}")
+            }
+            SessionError::NoHandshake => write!(f, "The other player did not introduce itself"),
+            SessionError::Handshake(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for SessionError {}
+
+impl From<HandshakeError> for SessionError {
+    fn from(error: HandshakeError) -> Self {
+        SessionError::Handshake(error)
+    }
+}
+
+/// Side of the session this game plays, from the command line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionRole {
+    /// Listen on the address
+    Host(String),
+    /// Connect to the host at the address
+    Join(String),
+}
+
+/// Session asked for on the command line: `--host <addr>` or `--join <addr>`, with the
+/// `--seed <n>` of the board both players agreed on. The host may pick its
+/// `--role <both|sweeper|flagger>`, the players joining get the other one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionArgs {
+    pub role: SessionRole,
+    pub seed: u64,
+    /// Role of this player, see [`Role::partner`]
+    pub player_role: Role,
+}
+
+impl SessionArgs {
+    /// `None` without `--host` nor `--join`
+    pub fn from_args(args: &[String]) -> Result<Option<Self>, SessionError> {
+        let value = |name: &str| {
+            args.iter()
+                .rposition(|arg| arg == name)
+                .map(|idx| args.get(idx + 1).cloned())
+        };
+        let missing = |arg: &str| SessionError::Argument {
+            arg: arg.to_owned(),
+            reason: "missing value".to_owned(),
+        };
+
+        let role = match (value("--host"), value("--join")) {
+            (None, None) => return Ok(None),
+            (Some(_), Some(_)) => {
+                return Err(SessionError::Argument {
+                    arg: "--join".to_owned(),
+                    reason: "a game either hosts or joins".to_owned(),
+                })
+            }
+            (Some(addr), None) => SessionRole::Host(addr.ok_or_else(|| missing("--host"))?),
+            (None, Some(addr)) => SessionRole::Join(addr.ok_or_else(|| missing("--join"))?),
+        };
+        let seed = value("--seed")
+            .ok_or_else(|| SessionError::Argument {
+                arg: "--seed".to_owned(),
+                reason: "both players give the seed of the board".to_owned(),
+            })?
+            .ok_or_else(|| missing("--seed"))?;
+        let seed = seed.parse().map_err(|_| SessionError::Argument {
+            arg: "--seed".to_owned(),
+            reason: format!("expected a whole number, got {seed:?}"),
+        })?;
+        let player_role = match (&role, value("--role")) {
+            (_, None) => Role::Both,
+            (SessionRole::Join(_), Some(_)) => {
+                return Err(SessionError::Argument {
+                    arg: "--role".to_owned(),
+                    reason: "the host hands out the roles".to_owned(),
+                })
+            }
+            (SessionRole::Host(_), Some(value)) => {
+                let value = value.ok_or_else(|| missing("--role"))?;
+                match value.to_lowercase().as_str() {
+                    "both" => Role::Both,
+                    "sweeper" => Role::Sweeper,
+                    "flagger" => Role::Flagger,
+                    _ => {
+                        return Err(SessionError::Argument {
+                            arg: "--role".to_owned(),
+                            reason: format!("unknown role {value:?}"),
+                        })
+                    }
+                }
+            }
+        };
+        Ok(Some(Self {
+            role,
+            seed,
+            player_role,
+        }))
+    }
+}
+
+/// A connection to another player. Lines are read on a thread of its own, so that the game never
+/// waits for the network. The connection is shut down when dropped
+#[derive(Debug)]
+pub struct Link {
+    stream: TcpStream,
+    lines: Mutex<Receiver<io::Result<String>>>,
+}
+
+impl Link {
+    pub fn new(stream: TcpStream) -> Result<Self, SessionError> {
+        let io_error = |e: io::Error| SessionError::Io(e.to_string());
+        // accepted streams may inherit the mode of the listener
+        stream.set_nonblocking(false).map_err(io_error)?;
+        stream.set_nodelay(true).map_err(io_error)?;
+        let reader = stream.try_clone().map_err(io_error)?;
+
+        let (sender, lines) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(reader).lines() {
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            stream,
+            lines: Mutex::new(lines),
+        })
+    }
+
+    /// Connect to the host listening at `addr`
+    pub fn connect(addr: &str) -> Result<Self, SessionError> {
+        let connect_error = |reason: String| SessionError::Connect {
+            addr: addr.to_owned(),
+            reason,
+        };
+        let socket = addr
+            .to_socket_addrs()
+            .map_err(|e| connect_error(e.to_string()))?
+            .next()
+            .ok_or_else(|| connect_error("no such address".to_owned()))?;
+        let stream = TcpStream::connect_timeout(&socket, CONNECT_TIMEOUT)
+            .map_err(|e| connect_error(e.to_string()))?;
+        Self::new(stream)
+    }
+
+    pub fn send(&mut self, message: &Message) -> Result<(), SessionError> {
+        let line = ron::to_string(message).map_err(|e| SessionError::Io(e.to_string()))?;
+        writeln!(self.stream, "{line}")
+            .and_then(|_| self.stream.flush())
+            .map_err(|e| SessionError::Io(e.to_string()))
+    }
+
+    /// Next message of the other side, `None` until one arrives
+    pub fn poll(&mut self) -> Result<Option<Message>, SessionError> {
+        let lines = self.lines.get_mut().unwrap_or_else(|e| e.into_inner());
+        match lines.try_recv() {
+            Ok(Ok(line)) => ron::from_str(&line)
+                .map(Some)
+                .map_err(|e| SessionError::Message {
+                    line,
+                    reason: e.to_string(),
+                }),
+            Ok(Err(error)) => Err(SessionError::Io(error.to_string())),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(SessionError::Closed),
+        }
+    }
+}
+
+impl Drop for Link {
+    fn drop(&mut self) {
+        // also ends the reading thread
+        self.stream.shutdown(Shutdown::Both).ok();
+    }
+}
+
+/// Player connected to the host, numbered from 2 as the host is the first player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct PeerId(pub u32);
+
+impl PeerId {
+    /// Name of the player in the chat
+    pub fn name(self) -> String {
+        format!("Player {}", self.0)
+    }
+}
+
+/// What the host learned from its players
+#[derive(Debug, Clone, PartialEq)]
+pub enum HostEvent {
+    /// A player introduced itself with a matching handshake, and got the partner of the role
+    /// of the host
+    Joined(PeerId),
+    /// A player left, or was refused
+    Left { peer: PeerId, error: SessionError },
+    /// Move of a player, to play and then [`Host::broadcast`]
+    Move { peer: PeerId, action: Action },
+    /// Move the role of the player forbids, dropped
+    Forbidden { peer: PeerId, action: Action },
+    /// Chat line of a player, already passed on to the others
+    Chat { from: String, text: String },
+    /// Ping of a player, already passed on to the others
+    Ping { coord: IVec2, emote: Emote },
+}
+
+#[derive(Debug)]
+struct Peer {
+    id: PeerId,
+    link: Link,
+    /// The handshake of the player was accepted
+    joined: bool,
+    /// Why the link broke, reported at the next poll
+    error: Option<SessionError>,
+}
+
+impl Peer {
+    fn send(&mut self, message: &Message) {
+        if self.error.is_none() {
+            self.error = self.link.send(message).err();
+        }
+    }
+}
+
+/// Host side of a session
+#[derive(Debug)]
+pub struct Host {
+    listener: TcpListener,
+    handshake: Handshake,
+    journal: MoveJournal,
+    peers: Vec<Peer>,
+    next_id: u32,
+}
+
+impl Host {
+    /// Listen for players at `addr`, e.g. `0.0.0.0:7878`
+    pub fn listen(addr: &str, handshake: Handshake) -> Result<Self, SessionError> {
+        let connect_error = |e: io::Error| SessionError::Connect {
+            addr: addr.to_owned(),
+            reason: e.to_string(),
+        };
+        let listener = TcpListener::bind(addr).map_err(connect_error)?;
+        listener.set_nonblocking(true).map_err(connect_error)?;
+        Ok(Self {
+            listener,
+            handshake,
+            journal: MoveJournal::new(JOURNAL_CAPACITY),
+            peers: Vec::new(),
+            next_id: 2,
+        })
+    }
+
+    /// Address the players connect to, with the port picked by the system for port 0
+    pub fn local_addr(&self) -> Option<SocketAddr> {
+        self.listener.local_addr().ok()
+    }
+
+    /// Players that introduced themselves
+    pub fn peers(&self) -> impl Iterator<Item = PeerId> + '_ {
+        self.peers
+            .iter()
+            .filter(|peer| peer.joined)
+            .map(|peer| peer.id)
+    }
+
+    /// Accept new players and read what the others sent. `state` and `rules` are those of the
+    /// shared board, moves outside of it or that the rules forbid to the [`Roles`] of their player
+    /// are dropped. Players joining are given a role there, and leaving ones lose theirs
+    pub fn poll(
+        &mut self,
+        state: &BoardState,
+        rules: &RuleSet,
+        roles: &mut Roles,
+    ) -> Vec<HostEvent> {
+        let mut events = Vec::new();
+        self.accept(&mut events);
+
+        let mut relayed = Vec::new();
+        for peer in &mut self.peers {
+            while peer.error.is_none() {
+                let message = match peer.link.poll() {
+                    Ok(Some(message)) => message,
+                    Ok(None) => break,
+                    Err(error) => {
+                        peer.error = Some(error);
+                        break;
+                    }
+                };
+                if !peer.joined {
+                    peer.error = match message {
+                        Message::Handshake(theirs) => {
+                            self.handshake.accept(&theirs).err().map(SessionError::from)
+                        }
+                        _ => Some(SessionError::NoHandshake),
+                    };
+                    peer.joined = peer.error.is_none();
+                    if peer.joined {
+                        let role = roles.get(Player::Local).partner();
+                        roles.assign(Player::Peer(peer.id), role);
+                        peer.send(&Message::AssignRole(role));
+                        events.push(HostEvent::Joined(peer.id));
+                    }
+                    continue;
+                }
+
+                match message {
+                    Message::Move { action, .. } => {
+                        let (Action::Uncover(coord) | Action::Flag(coord)) = action;
+                        if state.tile(coord).is_none() {
+                            continue;
+                        }
+                        let peer = peer.id;
+                        if rules.allows(roles.get(Player::Peer(peer)), action) {
+                            events.push(HostEvent::Move { peer, action });
+                        } else {
+                            events.push(HostEvent::Forbidden { peer, action });
+                        }
+                    }
+                    Message::Chat { text, .. } => {
+                        let from = peer.id.name();
+                        relayed.push((peer.id, Message::Chat { from, text }));
+                    }
+                    Message::Ping { coord, emote } => {
+                        relayed.push((peer.id, Message::Ping { coord, emote }));
+                    }
+                    // a player that applied nothing may be on another board, it gets this one
+                    Message::Resume { applied: 0 } => peer.send(&Message::Snapshot {
+                        board: SaveGame::capture(state),
+                        next_seq: self.journal.next_seq(),
+                    }),
+                    Message::Resume { applied } => {
+                        for message in self.journal.resume(applied, state) {
+                            peer.send(&message);
+                        }
+                    }
+                    // only the host sends these
+                    Message::Handshake(_) | Message::Snapshot { .. } | Message::AssignRole(_) => {}
+                }
+            }
+        }
+
+        for (from, message) in relayed {
+            self.relay(&message, Some(from));
+            match message {
+                Message::Chat { from, text } => events.push(HostEvent::Chat { from, text }),
+                Message::Ping { coord, emote } => events.push(HostEvent::Ping { coord, emote }),
+                _ => {}
+            }
+        }
+
+        let (broken, peers) = std::mem::take(&mut self.peers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|peer| peer.error.is_some());
+        self.peers = peers;
+        events.extend(broken.into_iter().map(|peer| {
+            roles.remove(Player::Peer(peer.id));
+            HostEvent::Left {
+                peer: peer.id,
+                error: peer.error.unwrap_or(SessionError::Closed),
+            }
+        }));
+        events
+    }
+
+    fn accept(&mut self, events: &mut Vec<HostEvent>) {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(error) if error.kind() == ErrorKind::WouldBlock => return,
+                // the next player may fare better
+                Err(_) => return,
+            };
+            let id = PeerId(self.next_id);
+            self.next_id += 1;
+            match Link::new(stream) {
+                Ok(link) => {
+                    let mut peer = Peer {
+                        id,
+                        link,
+                        joined: false,
+                        error: None,
+                    };
+                    peer.send(&Message::Handshake(self.handshake.clone()));
+                    self.peers.push(peer);
+                }
+                Err(error) => events.push(HostEvent::Left { peer: id, error }),
+            }
+        }
+    }
+
+    /// Start over on the new shared board `state`, sending it whole to every player
+    pub fn restart(&mut self, state: &BoardState) {
+        self.journal.restart();
+        let snapshot = Message::Snapshot {
+            board: SaveGame::capture(state),
+            next_seq: self.journal.next_seq(),
+        };
+        self.relay(&snapshot, None);
+    }
+
+    /// Number a move played on the shared board and send it to every player
+    pub fn broadcast(&mut self, action: Action) {
+        let message = self.journal.record(action);
+        self.relay(&message, None);
+    }
+
+    /// Send a message to every player but `except`
+    pub fn relay(&mut self, message: &Message, except: Option<PeerId>) {
+        for peer in &mut self.peers {
+            if peer.joined && Some(peer.id) != except {
+                peer.send(message);
+            }
+        }
+    }
+
+    /// Send a message to a single player
+    pub fn send_to(&mut self, id: PeerId, message: &Message) {
+        if let Some(peer) = self.peers.iter_mut().find(|peer| peer.id == id) {
+            peer.send(message);
+        }
+    }
+}
+
+/// What the client learned from the host
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClientEvent {
+    /// The host accepted the handshake
+    Joined,
+    /// The connection is gone
+    Left(SessionError),
+    /// Move of the shared board, to play
+    Apply(Action),
+    /// The shared board as a whole, to play on instead of the current one
+    Replace(SaveGame),
+    /// Role of this player, the host drops the moves it forbids
+    AssignRole(Role),
+    Chat {
+        from: String,
+        text: String,
+    },
+    Ping {
+        coord: IVec2,
+        emote: Emote,
+    },
+}
+
+/// Client side of a session
+#[derive(Debug)]
+pub struct Client {
+    /// Address of the host, to reconnect to
+    addr: String,
+    handshake: Handshake,
+    /// `None` once the connection is lost
+    link: Option<Link>,
+    joined: bool,
+    sync: ClientSync,
+    /// Why the link broke, reported at the next poll
+    error: Option<SessionError>,
+}
+
+impl Client {
+    /// Connect to the host at `addr` and introduce this player
+    pub fn connect(addr: &str, handshake: Handshake) -> Result<Self, SessionError> {
+        let mut client = Self {
+            addr: addr.to_owned(),
+            handshake,
+            link: None,
+            joined: false,
+            sync: ClientSync::default(),
+            error: None,
+        };
+        client.reconnect()?;
+        Ok(client)
+    }
+
+    /// Connect again after the connection was lost. Once the host accepts the handshake, the
+    /// moves missed in between are played as usual. Blocks for up to [`CONNECT_TIMEOUT`]
+    pub fn reconnect(&mut self) -> Result<(), SessionError> {
+        if self.link.is_some() {
+            return Ok(());
+        }
+        let mut link = Link::connect(&self.addr)?;
+        link.send(&Message::Handshake(self.handshake.clone()))?;
+        self.link = Some(link);
+        self.joined = false;
+        Ok(())
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.link.is_some()
+    }
+
+    /// Moves of the host played so far
+    pub fn applied(&self) -> u64 {
+        self.sync.applied()
+    }
+
+    /// Ask for the shared board again, when this player went on another one
+    pub fn resync(&mut self) {
+        self.sync = ClientSync::default();
+        self.send(&Message::Resume { applied: 0 });
+    }
+
+    /// Send a move, chat line or ping to the host. Moves are only played once the host sends
+    /// them back
+    pub fn send(&mut self, message: &Message) {
+        if let (Some(link), None) = (&mut self.link, &self.error) {
+            self.error = link.send(message).err();
+        }
+    }
+
+    /// Ask the host to play `action`
+    pub fn send_move(&mut self, action: Action) {
+        let seq = self.sync.applied();
+        self.send(&Message::Move { seq, action });
+    }
+
+    /// Read what the host sent
+    pub fn poll(&mut self) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+        while let (Some(link), None) = (&mut self.link, &self.error) {
+            let message = match link.poll() {
+                Ok(Some(message)) => message,
+                Ok(None) => break,
+                Err(error) => {
+                    self.error = Some(error);
+                    break;
+                }
+            };
+            if !self.joined {
+                self.error = match message {
+                    Message::Handshake(theirs) => {
+                        self.handshake.accept(&theirs).err().map(SessionError::from)
+                    }
+                    _ => Some(SessionError::NoHandshake),
+                };
+                self.joined = self.error.is_none();
+                if self.joined {
+                    events.push(ClientEvent::Joined);
+                    let applied = self.sync.applied();
+                    self.send(&Message::Resume { applied });
+                }
+                continue;
+            }
+
+            match message {
+                Message::Chat { from, text } => events.push(ClientEvent::Chat { from, text }),
+                Message::Ping { coord, emote } => events.push(ClientEvent::Ping { coord, emote }),
+                Message::AssignRole(role) => events.push(ClientEvent::AssignRole(role)),
+                message => match self.sync.receive(&message) {
+                    SyncStep::Apply(action) => events.push(ClientEvent::Apply(action)),
+                    SyncStep::Replace(board) => events.push(ClientEvent::Replace(board)),
+                    SyncStep::Resume { applied } => self.send(&Message::Resume { applied }),
+                    SyncStep::Skip => {}
+                },
+            }
+        }
+
+        if let Some(error) = self.error.take() {
+            self.link = None;
+            self.joined = false;
+            events.push(ClientEvent::Left(error));
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        thread,
+        time::{Duration, Instant},
+    };
+
+    use bevy::prelude::IVec2;
+
+    use super::{
+        Client, ClientEvent, Host, HostEvent, PeerId, SessionArgs, SessionError, SessionRole,
+    };
+    use crate::{
+        protocol::{Handshake, HandshakeError, Message},
+        resources::{
+            board::TileMap,
+            board_options::BoardOptions,
+            board_state::BoardState,
+            rule_set::{Player, Role, Roles, RuleSet},
+        },
+        save::Action,
+    };
+
+    /// Poll until `done` holds, the messages taking their time over the loopback
+    fn wait_for(mut done: impl FnMut() -> bool) {
+        let start = Instant::now();
+        while !done() {
+            assert!(start.elapsed() < Duration::from_secs(5), "timed out");
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    fn board() -> (BoardState, RuleSet, Roles) {
+        let mut tiles = TileMap::with_mines(4, 4, [[0, 0]]).unwrap();
+        let rules = RuleSet::from_options(&BoardOptions::default());
+        (BoardState::new(&mut tiles), rules, Roles::default())
+    }
+
+    fn host(seed: u64) -> (Host, String) {
+        let handshake = Handshake::new(&BoardOptions::default(), seed);
+        let host = Host::listen("127.0.0.1:0", handshake).unwrap();
+        let addr = host.local_addr().unwrap().to_string();
+        (host, addr)
+    }
+
+    fn client(addr: &str, seed: u64) -> Client {
+        Client::connect(addr, Handshake::new(&BoardOptions::default(), seed)).unwrap()
+    }
+
+    /// Connect a player, and wait for the host to send it the board
+    fn join(
+        host: &mut Host,
+        addr: &str,
+        state: &BoardState,
+        rules: &RuleSet,
+        roles: &mut Roles,
+    ) -> Client {
+        let mut client = client(addr, 1);
+        let mut events = Vec::new();
+        wait_for(|| {
+            host.poll(state, rules, roles);
+            events.extend(client.poll());
+            events.len() == 3
+        });
+        assert!(matches!(
+            &events[..],
+            [
+                ClientEvent::Joined,
+                ClientEvent::AssignRole(_),
+                ClientEvent::Replace(_)
+            ]
+        ));
+        client
+    }
+
+    /// Poll a player until it got `n` events
+    fn receive(
+        host: &mut Host,
+        client: &mut Client,
+        state: &BoardState,
+        roles: &mut Roles,
+        n: usize,
+    ) -> Vec<ClientEvent> {
+        let rules = RuleSet::from_options(&BoardOptions::default());
+        let mut events = Vec::new();
+        wait_for(|| {
+            host.poll(state, &rules, roles);
+            events.extend(client.poll());
+            events.len() >= n
+        });
+        events
+    }
+
+    #[test]
+    fn test_args() {
+        let args = |args: &[&str]| {
+            SessionArgs::from_args(&args.iter().map(|&arg| arg.to_owned()).collect::<Vec<_>>())
+        };
+        assert_eq!(args(&["--mines", "10"]), Ok(None));
+        assert_eq!(
+            args(&["--host", "0.0.0.0:7878", "--seed", "42"]),
+            Ok(Some(SessionArgs {
+                role: SessionRole::Host("0.0.0.0:7878".to_owned()),
+                seed: 42,
+                player_role: Role::Both
+            }))
+        );
+        assert!(matches!(
+            args(&[
+                "--host",
+                "0.0.0.0:7878",
+                "--seed",
+                "42",
+                "--role",
+                "Flagger"
+            ]),
+            Ok(Some(SessionArgs {
+                player_role: Role::Flagger,
+                ..
+            }))
+        ));
+        assert!(args(&["--join", "a:1", "--seed", "1", "--role", "sweeper"]).is_err());
+        assert!(args(&["--host", "a:1", "--seed", "1", "--role", "miner"]).is_err());
+        assert!(matches!(
+            args(&["--join", "localhost:7878"]),
+            Err(SessionError::Argument { arg, .. }) if arg == "--seed"
+        ));
+        assert!(args(&["--join", "a:1", "--host", "b:1", "--seed", "1"]).is_err());
+        assert!(args(&["--seed", "many", "--join", "a:1"]).is_err());
+    }
+
+    #[test]
+    fn test_handshake() {
+        let (mut host, addr) = host(1);
+        let (state, rules, mut roles) = board();
+
+        let mut elsewhere = client(&addr, 2);
+        let (mut host_events, mut client_events) = (Vec::new(), Vec::new());
+        wait_for(|| {
+            host_events.extend(host.poll(&state, &rules, &mut roles));
+            client_events.extend(elsewhere.poll());
+            !host_events.is_empty() && !client_events.is_empty()
+        });
+        assert_eq!(
+            host_events,
+            [HostEvent::Left {
+                peer: PeerId(2),
+                error: SessionError::Handshake(HandshakeError::Seed { ours: 1, theirs: 2 })
+            }]
+        );
+        assert_eq!(
+            client_events,
+            [ClientEvent::Left(SessionError::Handshake(
+                HandshakeError::Seed { ours: 2, theirs: 1 }
+            ))]
+        );
+        assert!(!elsewhere.is_connected());
+        assert_eq!(host.peers().count(), 0);
+
+        let mut joined = client(&addr, 1);
+        let (mut host_events, mut client_events) = (Vec::new(), Vec::new());
+        wait_for(|| {
+            host_events.extend(host.poll(&state, &rules, &mut roles));
+            client_events.extend(joined.poll());
+            !host_events.is_empty() && client_events.len() >= 2
+        });
+        assert_eq!(host_events, [HostEvent::Joined(PeerId(3))]);
+        assert_eq!(
+            client_events[..2],
+            [ClientEvent::Joined, ClientEvent::AssignRole(Role::Both)]
+        );
+    }
+
+    #[test]
+    fn test_moves() {
+        let (mut host, addr) = host(1);
+        let (state, rules, mut roles) = board();
+        let mut first = join(&mut host, &addr, &state, &rules, &mut roles);
+        let mut second = join(&mut host, &addr, &state, &rules, &mut roles);
+
+        // moves go through the host, and come back numbered to every player
+        let flag = Action::Flag(IVec2::new(1, 1));
+        first.send_move(flag);
+        first.send_move(Action::Uncover(IVec2::new(9, 9)));
+        let mut host_events = Vec::new();
+        wait_for(|| {
+            host_events.extend(host.poll(&state, &rules, &mut roles));
+            !host_events.is_empty()
+        });
+        assert_eq!(
+            host_events,
+            [HostEvent::Move {
+                peer: PeerId(2),
+                action: flag
+            }]
+        );
+        host.broadcast(flag);
+        for client in [&mut first, &mut second] {
+            assert_eq!(
+                receive(&mut host, client, &state, &mut roles, 1),
+                [ClientEvent::Apply(flag)]
+            );
+        }
+
+        // chat lines carry the name the host knows the player by
+        second.send(&Message::chat("me", "mine at B2").unwrap());
+        let mut host_events = Vec::new();
+        let mut first_events = Vec::new();
+        wait_for(|| {
+            host_events.extend(host.poll(&state, &rules, &mut roles));
+            first_events.extend(first.poll());
+            !first_events.is_empty()
+        });
+        let (from, text) = ("Player 3".to_owned(), "mine at B2".to_owned());
+        assert_eq!(
+            host_events,
+            [HostEvent::Chat {
+                from: from.clone(),
+                text: text.clone()
+            }]
+        );
+        assert_eq!(first_events, [ClientEvent::Chat { from, text }]);
+    }
+
+    #[test]
+    fn test_resume() {
+        let (mut host, addr) = host(1);
+        let (mut state, rules, mut roles) = board();
+        let mut client = join(&mut host, &addr, &state, &rules, &mut roles);
+        let flags = (0..4)
+            .map(|x| Action::Flag(IVec2::new(x, 2)))
+            .collect::<Vec<_>>();
+
+        host.broadcast(flags[0]);
+        assert_eq!(
+            receive(&mut host, &mut client, &state, &mut roles, 1),
+            [ClientEvent::Apply(flags[0])]
+        );
+
+        // moves played while the player is away come once it is back
+        client.link = None;
+        wait_for(|| {
+            host.poll(&state, &rules, &mut roles);
+            host.peers().count() == 0
+        });
+        host.broadcast(flags[1]);
+        host.broadcast(flags[2]);
+        client.reconnect().unwrap();
+        assert_eq!(
+            receive(&mut host, &mut client, &state, &mut roles, 4),
+            [
+                ClientEvent::Joined,
+                ClientEvent::AssignRole(Role::Both),
+                ClientEvent::Apply(flags[1]),
+                ClientEvent::Apply(flags[2])
+            ]
+        );
+        assert_eq!(client.applied(), 3);
+
+        // a new board comes whole, and so it does to a player that missed it
+        client.link = None;
+        state.toggle_flag([3, 3]);
+        host.restart(&state);
+        host.broadcast(flags[3]);
+        state.toggle_flag([3, 2]);
+        client.reconnect().unwrap();
+        let events = receive(&mut host, &mut client, &state, &mut roles, 3);
+        let [ClientEvent::Joined, ClientEvent::AssignRole(_), ClientEvent::Replace(board)] =
+            &events[..]
+        else {
+            panic!("expected the new board, got {events:?}");
+        };
+        assert_eq!(board.restore().unwrap().1.n_flags(), 2);
+        assert_eq!(client.applied(), 5);
+
+        // so does a player that went on another board
+        client.resync();
+        let events = receive(&mut host, &mut client, &state, &mut roles, 1);
+        assert!(matches!(&events[..], [ClientEvent::Replace(_)]));
+        assert_eq!(client.applied(), 5);
+    }
+
+    #[test]
+    fn test_roles() {
+        let (mut host, addr) = host(1);
+        let (state, rules, mut roles) = board();
+        roles.assign(Player::Local, Role::Sweeper);
+        let mut client = client(&addr, 1);
+        let mut events = Vec::new();
+        wait_for(|| {
+            host.poll(&state, &rules, &mut roles);
+            events.extend(client.poll());
+            events.len() >= 2
+        });
+        // the player joining flags for the host
+        assert_eq!(events[1], ClientEvent::AssignRole(Role::Flagger));
+        let peer = Player::Peer(PeerId(2));
+        assert_eq!(roles.get(peer), Role::Flagger);
+
+        // its moves are checked against its own role, not the one of the host
+        let (uncover, flag) = (
+            Action::Uncover(IVec2::new(2, 2)),
+            Action::Flag(IVec2::new(1, 1)),
+        );
+        client.send_move(uncover);
+        client.send_move(flag);
+        let mut host_events = Vec::new();
+        wait_for(|| {
+            host_events.extend(host.poll(&state, &rules, &mut roles));
+            host_events.len() == 2
+        });
+        assert_eq!(
+            host_events,
+            [
+                HostEvent::Forbidden {
+                    peer: PeerId(2),
+                    action: uncover
+                },
+                HostEvent::Move {
+                    peer: PeerId(2),
+                    action: flag
+                }
+            ]
+        );
+
+        // and its role goes with it
+        drop(client);
+        wait_for(|| {
+            host.poll(&state, &rules, &mut roles);
+            host.peers().count() == 0
+        });
+        assert_eq!(roles.get(peer), Role::Both);
+    }
+}
@@ -0,0 +1,145 @@
+//! Results to paste in a chat: a grid of emoji in the spirit of Wordle, one per tile, under a
+//! line with the difficulty, the outcome and the time, and the seed to play the same board.
+//!
+//! The grid shows what the player uncovered and flagged without giving away the numbers, so that
+//! a result of the daily challenge spoils nothing for the players still at it.
+
+use bevy::prelude::IVec2;
+
+use crate::{
+    resources::{
+        board::TileState,
+        board_options::Difficulty,
+        board_state::{BoardState, Cover},
+    },
+    save::{self, SaveGame},
+};
+
+const COVERED: char = '⬜';
+const REVEALED: char = '🟩';
+const FLAGGED: &str = "🚩";
+const MINE: &str = "💣";
+
+/// Outcome of a finished game, ready to be copied
+#[derive(Debug, Clone, PartialEq)]
+pub struct GameResult {
+    pub difficulty: Difficulty,
+    pub won: bool,
+    /// Seconds from the first click to the last one
+    pub secs: f32,
+    /// Seed of the board, `None` for boards from a file
+    pub seed: Option<u64>,
+    /// See [`emoji_grid`]
+    pub grid: String,
+    /// The board as a save file, with the mines and the tiles uncovered and flagged
+    pub board: String,
+}
+
+impl GameResult {
+    pub fn new(state: &BoardState, won: bool, secs: f32, seed: Option<u64>) -> Self {
+        Self {
+            difficulty: Difficulty {
+                dim: state.dim().as_uvec2(),
+                n_mines: state.n_mines() as u32,
+            },
+            won,
+            secs,
+            seed,
+            grid: emoji_grid(state),
+            board: save::to_string(&SaveGame::capture(state)),
+        }
+    }
+
+    /// `Minesweeper Expert, cleared in 03:12`
+    pub fn headline(&self) -> String {
+        let secs = self.secs as u64;
+        let outcome = if self.won { "cleared in" } else { "lost after" };
+        format!(
+            "Minesweeper {}, {outcome} {:02}:{:02}",
+            self.difficulty.name(),
+            secs / 60,
+            secs % 60
+        )
+    }
+
+    /// Headline, seed and grid, and the board file after them with `board`
+    pub fn to_text(&self, board: bool) -> String {
+        let mut text = self.headline() + "\n";
+        if let Some(seed) = self.seed {
+            text += &format!("Seed {seed:#018x}\n");
+        }
+        text += &self.grid;
+        if board {
+            text += "\n";
+            text += &self.board;
+        }
+        text
+    }
+}
+
+/// One emoji per tile, the top row first as on screen: green for uncovered tiles, white for
+/// covered ones, a flag or a bomb where the player put them or hit them
+pub fn emoji_grid(state: &BoardState) -> String {
+    let dim = state.dim();
+    let mut grid = String::new();
+    for y in (0..dim.y).rev() {
+        for x in 0..dim.x {
+            let coord = IVec2::new(x, y);
+            match (state.cover(coord), state.tile(coord)) {
+                (Some(Cover::Flagged), _) => grid += FLAGGED,
+                (Some(Cover::Covered | Cover::Questioned), _) => grid.push(COVERED),
+                (_, Some(TileState::Mine)) => grid += MINE,
+                _ => grid.push(REVEALED),
+            }
+        }
+        grid.push('\n');
+    }
+    grid
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{emoji_grid, GameResult};
+    use crate::save::{self, SaveGame};
+
+    #[test]
+    fn test_result() {
+        // * 1 .
+        // 1 1 .   the mine flagged and the bottom right tile uncovered
+        let save = SaveGame {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 1)],
+            revealed: vec![IVec2::new(2, 0)],
+            flagged: vec![IVec2::new(0, 1)],
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        let (_, mut state) = save.restore().unwrap();
+        assert_eq!(emoji_grid(&state), "🚩⬜⬜\n⬜⬜🟩\n");
+        state.reveal_mines();
+        state.uncover([0, 0]);
+
+        let result = GameResult::new(&state, false, 72.9, Some(0xbeef));
+        assert_eq!(result.headline(), "Minesweeper 3x2/1, lost after 01:12");
+        let text = result.to_text(false);
+        assert_eq!(
+            text,
+            format!(
+                "{}\nSeed 0x000000000000beef\n{}",
+                result.headline(),
+                result.grid
+            )
+        );
+        assert_eq!(result.grid, "💣⬜⬜\n🟩⬜🟩\n");
+
+        let with_board = result.to_text(true);
+        let board = with_board.strip_prefix(&(text + "\n")).unwrap();
+        let pasted = save::from_str::<SaveGame>(board).unwrap();
+        assert_eq!(pasted.mines, save.mines);
+        assert!(pasted.revealed.contains(&IVec2::new(0, 0)));
+    }
+}
@@ -0,0 +1,209 @@
+//! Read-only view of a game for external solvers, bots and research tools. A snapshot holds
+//! what the player sees and nothing more: covers, marks and revealed numbers, never the mines
+//! under covered tiles, so a tool fed snapshots plays by the rules.
+//!
+//! Snapshots travel as JSON. [`SCHEMA`] describes them for tools written in other languages,
+//! the tiles are nested lists of small integers that load straight into an array.
+
+use bevy::prelude::IVec2;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    resources::{
+        board::TileState,
+        board_state::{BoardState, Cover},
+    },
+    topology::SQUARE,
+};
+
+/// Bump whenever a field changes, tools should check it
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// JSON Schema of a serialized [`BoardSnapshot`]
+pub const SCHEMA: &str = include_str!("snapshot.schema.json");
+
+/// Tile not revealed yet
+pub const COVERED: i8 = -1;
+/// Covered tile marked as a mine
+pub const FLAGGED: i8 = -2;
+/// Covered tile marked as uncertain
+pub const QUESTIONED: i8 = -3;
+/// Revealed mine, once the game is lost
+pub const MINE: i8 = -4;
+
+/// Whether the game goes on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnapshotStatus {
+    Playing,
+    Won,
+    Lost,
+}
+
+/// What the player knows of a board
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BoardSnapshot {
+    /// [`SNAPSHOT_VERSION`] of the writer
+    pub version: u32,
+    pub width: u32,
+    pub height: u32,
+    /// Name of the tiling, see `topology`
+    pub topology: String,
+    pub mines: u32,
+    /// Flags placed, right or wrong
+    pub flags: u32,
+    pub status: SnapshotStatus,
+    /// Rows of the board, `tiles[y][x]`: the number of a revealed tile, or [`COVERED`],
+    /// [`FLAGGED`], [`QUESTIONED`] or [`MINE`]
+    pub tiles: Vec<Vec<i8>>,
+    /// Flat indices `y * width + x` of the tiles touching every tile, in the same order.
+    /// Left out on squares, where they are the 8 tiles around
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub neighbors: Vec<Vec<u32>>,
+}
+
+impl BoardSnapshot {
+    pub fn capture(state: &BoardState) -> Self {
+        let dim = state.dim();
+        let topology = state.topology();
+        let rows = (0..dim.y).map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)));
+
+        let mut lost = false;
+        let tiles = rows
+            .clone()
+            .map(|row| {
+                row.map(|coord| match (state.cover(coord), state.tile(coord)) {
+                    (Some(Cover::Revealed), Some(TileState::Clear(n))) => n as i8,
+                    (Some(Cover::Revealed), _) => {
+                        lost = true;
+                        MINE
+                    }
+                    (Some(Cover::Flagged), _) => FLAGGED,
+                    (Some(Cover::Questioned), _) => QUESTIONED,
+                    _ => COVERED,
+                })
+                .collect()
+            })
+            .collect();
+
+        let neighbors = if topology.name() == SQUARE {
+            Vec::new()
+        } else {
+            rows.flatten()
+                .map(|coord| {
+                    state
+                        .neighbors(coord)
+                        .filter_map(|neighbor| topology.index(neighbor, dim))
+                        .map(|idx| idx as u32)
+                        .collect()
+                })
+                .collect()
+        };
+
+        Self {
+            version: SNAPSHOT_VERSION,
+            width: dim.x as u32,
+            height: dim.y as u32,
+            topology: topology.name().to_owned(),
+            mines: state.n_mines() as u32,
+            flags: state.n_flags() as u32,
+            status: if lost {
+                SnapshotStatus::Lost
+            } else if state.is_cleared() {
+                SnapshotStatus::Won
+            } else {
+                SnapshotStatus::Playing
+            },
+            tiles,
+            neighbors,
+        }
+    }
+
+    /// Tile at `coord` as written in [`BoardSnapshot::tiles`], `None` if out of the board
+    pub fn tile<T: Into<IVec2>>(&self, coord: T) -> Option<i8> {
+        let coord = coord.into();
+        let row = self.tiles.get(usize::try_from(coord.y).ok()?)?;
+        row.get(usize::try_from(coord.x).ok()?).copied()
+    }
+
+    /// On a single line, so that tools can read snapshots line by line
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("snapshots only hold plain data")
+    }
+
+    pub fn from_json(json: &str) -> Result<Self, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{BoardSnapshot, SnapshotStatus, COVERED, FLAGGED, MINE, SCHEMA};
+    use crate::{
+        resources::{board::TileMap, board_state::BoardState},
+        topology::Hex,
+    };
+
+    #[test]
+    fn test_capture() {
+        let mut tiles = TileMap::with_mines(3, 2, [[0, 0], [2, 1]]).unwrap();
+        let mut state = BoardState::new(&mut tiles);
+        state.uncover([1, 0]);
+        state.toggle_flag([0, 0]);
+
+        let snapshot = BoardSnapshot::capture(&state);
+        assert_eq!(snapshot.tiles, [[FLAGGED, 2, COVERED], [COVERED; 3]]);
+        assert_eq!(snapshot.tile([1, 0]), Some(2));
+        assert_eq!(snapshot.tile([3, 0]), None);
+        assert_eq!(snapshot.tile(IVec2::new(-1, 0)), None);
+        assert_eq!(snapshot.status, SnapshotStatus::Playing);
+        assert!(snapshot.neighbors.is_empty());
+
+        // a line of JSON, without neighbors on squares
+        let json = snapshot.to_json();
+        assert!(!json.contains('\n') && !json.contains("neighbors"));
+        assert_eq!(BoardSnapshot::from_json(&json).unwrap(), snapshot);
+
+        state.uncover([2, 1]);
+        state.reveal_mines();
+        let snapshot = BoardSnapshot::capture(&state);
+        assert_eq!(snapshot.status, SnapshotStatus::Lost);
+        assert_eq!(snapshot.tile([2, 1]), Some(MINE));
+    }
+
+    #[test]
+    fn test_topology() {
+        let mut tiles = TileMap::with_mines(3, 3, [[0, 0]])
+            .unwrap()
+            .with_topology(&Hex);
+        let state = BoardState::new(&mut tiles);
+        let snapshot = BoardSnapshot::capture(&state);
+        assert_eq!(snapshot.topology, "hex");
+        assert_eq!(snapshot.neighbors.len(), 9);
+        // the center hexagon touches all but two corners
+        assert_eq!(snapshot.neighbors[4].len(), 6);
+    }
+
+    #[test]
+    fn test_schema() {
+        let schema: serde_json::Value = serde_json::from_str(SCHEMA).unwrap();
+        let properties = schema["properties"].as_object().unwrap();
+
+        let mut tiles = TileMap::with_mines(3, 3, [[0, 0]])
+            .unwrap()
+            .with_topology(&Hex);
+        let snapshot = BoardSnapshot::capture(&BoardState::new(&mut tiles));
+        let json: serde_json::Value = serde_json::from_str(&snapshot.to_json()).unwrap();
+        let fields = json.as_object().unwrap();
+        assert!(fields.keys().all(|field| properties.contains_key(field)));
+        assert!(properties
+            .keys()
+            .all(|property| fields.contains_key(property)));
+        assert_eq!(
+            schema["properties"]["version"]["const"],
+            serde_json::json!(super::SNAPSHOT_VERSION)
+        );
+    }
+}
@@ -0,0 +1,133 @@
+//! Computer opponent of the versus mode, playing from what a player can see of the board.
+
+use bevy::prelude::IVec2;
+use serde::{Deserialize, Serialize};
+
+use super::ConstraintGraph;
+use crate::rng::Rng;
+
+/// Chance for a noisy bot to click a random covered tile instead of its best move
+const NOISE: f64 = 0.25;
+
+/// How well the bot plays
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BotSkill {
+    /// Proven safe tiles first, then the least likely mine
+    #[default]
+    Perfect,
+    /// Any covered tile, the likely safe ones more often, ignoring how sure they are
+    Probabilistic,
+    /// Slow to think, and sometimes clicks anywhere
+    Noisy,
+}
+
+impl BotSkill {
+    pub const ALL: [BotSkill; 3] = [BotSkill::Perfect, BotSkill::Probabilistic, BotSkill::Noisy];
+
+    /// Seconds the bot waits before each move, so that the other player can follow
+    pub fn think_time(self) -> f32 {
+        match self {
+            BotSkill::Perfect => 0.5,
+            BotSkill::Probabilistic => 0.8,
+            BotSkill::Noisy => 1.5,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            BotSkill::Perfect => "Perfect",
+            BotSkill::Probabilistic => "Probabilistic",
+            BotSkill::Noisy => "Noisy",
+        }
+    }
+}
+
+/// Tile the bot uncovers next, among the ones `clickable` accepts. `None` once none is left
+pub fn bot_move(
+    graph: &mut ConstraintGraph,
+    skill: BotSkill,
+    rng: &mut dyn Rng,
+    clickable: impl Fn(IVec2) -> bool,
+) -> Option<IVec2> {
+    let analysis = graph.analyze();
+    let dim = analysis.dim();
+    // covered tiles and their mine probability
+    let candidates = (0..dim.y)
+        .flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)))
+        .filter(|&coord| clickable(coord))
+        .filter_map(|coord| Some((coord, analysis.probability(coord)?)))
+        .collect::<Vec<_>>();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let best = || {
+        analysis.safe().find(|&coord| clickable(coord)).or_else(|| {
+            candidates
+                .iter()
+                .min_by(|(_, a), (_, b)| a.total_cmp(b))
+                .map(|&(coord, _)| coord)
+        })
+    };
+
+    match skill {
+        BotSkill::Perfect => best(),
+        BotSkill::Probabilistic => {
+            let weights = candidates
+                .iter()
+                .map(|(_, p)| (1.0 - p).powi(2))
+                .collect::<Vec<_>>();
+            let mut pick = rng.unit() * weights.iter().sum::<f64>();
+            let chosen = weights.iter().position(|&w| {
+                pick -= w;
+                pick <= 0.0
+            });
+            chosen
+                .or_else(|| weights.iter().rposition(|&w| w > 0.0))
+                .map(|idx| candidates[idx].0)
+        }
+        BotSkill::Noisy if rng.unit() < NOISE => Some(candidates[rng.below(candidates.len())].0),
+        BotSkill::Noisy => best(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{bot_move, BotSkill};
+    use crate::{rng::RngBackend, solver::ConstraintGraph};
+    use bevy::prelude::IVec2;
+
+    #[test]
+    fn test_bot_move() {
+        // . . *
+        // 0 1 1   the top left is proven safe, the top middle too
+        let mut graph = ConstraintGraph::new(3, 2, 1);
+        for (x, n) in [0, 1, 1].into_iter().enumerate() {
+            graph.reveal([x as i32, 1], n);
+        }
+        let covered = |coord: IVec2| coord.y == 0;
+        let rng = &mut *RngBackend::Nanorand.seeded(7);
+
+        let safe = [IVec2::new(0, 0), IVec2::new(1, 0)];
+        let perfect = bot_move(&mut graph, BotSkill::Perfect, rng, covered).unwrap();
+        assert!(safe.contains(&perfect));
+
+        // the mine has no weight left
+        for _ in 0..50 {
+            let coord = bot_move(&mut graph, BotSkill::Probabilistic, rng, covered).unwrap();
+            assert!(safe.contains(&coord));
+        }
+
+        // noise picks any covered tile, mines included
+        let picks = (0..200)
+            .filter_map(|_| bot_move(&mut graph, BotSkill::Noisy, rng, covered))
+            .collect::<Vec<_>>();
+        assert!(picks.iter().all(|&coord| covered(coord)));
+        assert!(picks.contains(&IVec2::new(2, 0)));
+
+        assert_eq!(
+            bot_move(&mut graph, BotSkill::Perfect, rng, |_| false),
+            None
+        );
+    }
+}
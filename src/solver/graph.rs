@@ -0,0 +1,824 @@
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+use bevy::prelude::IVec2;
+
+use super::{
+    monte_carlo,
+    probability::{self, ComponentSolution, LocalConstraint},
+    Grid, SolverConfig,
+};
+use crate::{
+    resources::{
+        board::TileState,
+        board_state::{BoardState, Cover},
+    },
+    rng::{self, Rng},
+    topology::Topology,
+};
+
+/// What the player knows about a single tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Knowledge {
+    Covered,
+    /// Revealed with the given number of adjacent mines
+    Revealed(u8),
+}
+
+/// A fact proven about a covered tile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Deduction {
+    Safe,
+    Mine,
+}
+
+/// A revealed number and the covered tiles around it, or a line of tiles given with its mines
+#[derive(Debug, Clone)]
+struct Constraint {
+    /// Covered neighbors, or covered tiles of the line
+    cells: Vec<usize>,
+    /// Adjacent mine count, or the mines of the line
+    mines: u8,
+}
+
+fn residual(constraint: &Constraint, deductions: &[Option<Deduction>]) -> (Vec<usize>, u8) {
+    let mut unknown = Vec::with_capacity(constraint.cells.len());
+    let mut mines = constraint.mines;
+
+    for &cell in &constraint.cells {
+        match deductions[cell] {
+            None => unknown.push(cell),
+            Some(Deduction::Mine) => mines = mines.saturating_sub(1),
+            Some(Deduction::Safe) => {}
+        }
+    }
+
+    (unknown, mines)
+}
+
+/// Revealed numbers as constraints over the covered tiles, maintained incrementally.
+///
+/// Each reveal marks the constraints around the revealed tile as dirty. [`Self::analyze`] only
+/// re-runs deductions starting from dirty constraints, and only re-enumerates the frontier
+/// components containing a constraint changed since the previous analysis.
+#[derive(Debug, Clone)]
+pub struct ConstraintGraph {
+    grid: Grid,
+    n_mines: u32,
+    cells: Box<[Knowledge]>,
+    deductions: Box<[Option<Deduction>]>,
+    // keyed by the flat index of the revealed tile, lines past the last tile
+    constraints: HashMap<usize, Constraint>,
+    // constraints added by `constrain`, their origins follow the last tile
+    n_lines: usize,
+    // constraints that must be re-checked by the deduction pass
+    dirty: BTreeSet<usize>,
+    // constraints whose component solution must be recomputed
+    stale: BTreeSet<usize>,
+    // component solutions, keyed by the sorted constraint origins of the component
+    cache: HashMap<Vec<usize>, ComponentSolution>,
+    config: SolverConfig,
+    /// Draws of the Monte Carlo sampling
+    rng: Box<dyn Rng>,
+}
+
+/// How far sampled probabilities can be trusted
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Confidence {
+    /// Smallest effective sample size behind any estimated tile
+    pub samples: f64,
+    /// Largest standard error among the estimated probabilities
+    pub std_error: f64,
+}
+
+/// Result of a solver pass
+#[derive(Debug, Clone)]
+pub struct Analysis {
+    grid: Grid,
+    deductions: Box<[Option<Deduction>]>,
+    probabilities: Box<[Option<f64>]>,
+    confidence: Option<Confidence>,
+}
+
+impl ConstraintGraph {
+    pub fn new(width: u32, height: u32, n_mines: u32) -> Self {
+        let grid = Grid {
+            dim: IVec2::new(width.try_into().unwrap(), height.try_into().unwrap()),
+            topology: Default::default(),
+        };
+
+        Self {
+            grid,
+            n_mines,
+            cells: vec![Knowledge::Covered; grid.len()].into_boxed_slice(),
+            deductions: vec![None; grid.len()].into_boxed_slice(),
+            constraints: HashMap::new(),
+            n_lines: 0,
+            dirty: BTreeSet::new(),
+            stale: BTreeSet::new(),
+            cache: HashMap::new(),
+            config: Default::default(),
+            rng: rng::entropy(),
+        }
+    }
+
+    pub fn with_config(mut self, config: SolverConfig) -> Self {
+        self.set_config(config);
+        self
+    }
+
+    /// Sample large components with `rng`, e.g. a seeded one so that runs repeat
+    pub fn with_rng(mut self, rng: Box<dyn Rng>) -> Self {
+        self.rng = rng;
+        self.cache.clear();
+        self
+    }
+
+    /// Constraints over the neighbors of another tiling than squares, set before any reveal
+    pub fn with_topology(mut self, topology: &'static dyn Topology) -> Self {
+        self.grid.topology = topology;
+        self
+    }
+
+    /// A graph of the same config for `state`, from scratch with the numbers revealed on it. For
+    /// boards changed in ways no reveal explains, like seeking in a replay
+    pub fn rebuilt(&self, state: &BoardState) -> Self {
+        let dim = state.dim();
+        let mut graph = ConstraintGraph::new(dim.x as u32, dim.y as u32, state.n_mines() as u32)
+            .with_config(self.config)
+            .with_topology(state.topology());
+        graph.reveal_puzzle(state);
+        for coord in (0..dim.y).flat_map(|y| (0..dim.x).map(move |x| [x, y])) {
+            if let (Some(Cover::Revealed), Some(TileState::Clear(n))) =
+                (state.cover(coord), state.tile(coord))
+            {
+                graph.reveal(coord, n);
+            }
+        }
+        graph
+    }
+
+    /// Record what the puzzle of `state` gives besides its numbers: the gaps, revealed tiles
+    /// counting nothing, and the line hints. Before any reveal, or the gaps would count
+    pub fn reveal_puzzle(&mut self, state: &BoardState) {
+        for &gap in state.gaps() {
+            self.reveal_on(gap, [], 0);
+        }
+        for &line in state.lines() {
+            self.constrain(state.line_tiles(line), line.mines as u8);
+        }
+    }
+
+    pub fn config(&self) -> SolverConfig {
+        self.config
+    }
+
+    pub fn set_config(&mut self, config: SolverConfig) {
+        if self.config != config {
+            self.config = config;
+            self.cache.clear();
+        }
+    }
+
+    pub fn dim(&self) -> IVec2 {
+        self.grid.dim
+    }
+
+    pub fn n_mines(&self) -> u32 {
+        self.n_mines
+    }
+
+    pub fn knowledge<T: Into<IVec2>>(&self, coord: T) -> Knowledge {
+        self.cells[self.grid.index(coord.into())]
+    }
+
+    pub fn deduction<T: Into<IVec2>>(&self, coord: T) -> Option<Deduction> {
+        self.deductions[self.grid.index(coord.into())]
+    }
+
+    fn mark(&mut self, origin: usize) {
+        self.dirty.insert(origin);
+        self.stale.insert(origin);
+    }
+
+    /// Record a revealed tile showing `n` adjacent mines
+    pub fn reveal<T: Into<IVec2>>(&mut self, coord: T, n: u8) {
+        let idx = self.grid.index(coord.into());
+        let grid = self.grid;
+        self.open(idx, grid.neighbors(idx), n);
+    }
+
+    /// Record a revealed tile showing `n` mines among `counted`, for the tiles of puzzles that
+    /// count less than their neighbors. The tiles counted must be among the neighbors of the
+    /// topology, as every update looks for the numbers around a tile there
+    pub fn reveal_on<T: Into<IVec2>>(
+        &mut self,
+        coord: T,
+        counted: impl IntoIterator<Item = IVec2>,
+        n: u8,
+    ) {
+        let idx = self.grid.index(coord.into());
+        let grid = self.grid;
+        let counted = counted.into_iter().map(|coord| grid.index(coord));
+        self.open(idx, counted.collect::<Vec<_>>().into_iter(), n);
+    }
+
+    fn open(&mut self, idx: usize, counted: impl Iterator<Item = usize>, n: u8) {
+        if self.cells[idx] != Knowledge::Covered {
+            return;
+        }
+
+        self.cells[idx] = Knowledge::Revealed(n);
+        self.deductions[idx] = None;
+
+        for origin in self.constraints_of(idx).collect::<Vec<_>>() {
+            if let Some(constraint) = self.constraints.get_mut(&origin) {
+                constraint.cells.retain(|&cell| cell != idx);
+            }
+            self.mark(origin);
+        }
+
+        let cells = counted
+            .filter(|&cell| self.cells[cell] == Knowledge::Covered)
+            .collect();
+        self.constraints.insert(idx, Constraint { cells, mines: n });
+        self.mark(idx);
+    }
+
+    /// Record that `mines` of the tiles of `line` are mines, a constraint the puzzle gives
+    /// rather than a number, like the line hints of Hexcells
+    pub fn constrain(&mut self, line: impl IntoIterator<Item = IVec2>, mines: u8) {
+        let origin = self.grid.len() + self.n_lines;
+        let cells = line
+            .into_iter()
+            .map(|coord| self.grid.index(coord))
+            .filter(|&cell| self.cells[cell] == Knowledge::Covered)
+            .collect();
+        self.constraints.insert(origin, Constraint { cells, mines });
+        self.n_lines += 1;
+        self.mark(origin);
+    }
+
+    /// Origins of the constraints of [`Self::constrain`], past the flat indices of the tiles
+    fn lines(&self) -> impl Iterator<Item = usize> {
+        let len = self.grid.len();
+        len..len + self.n_lines
+    }
+
+    /// Covered cells without a deduction, and the number of mines left among them
+    fn residual(&self, constraint: &Constraint) -> (Vec<usize>, u8) {
+        residual(constraint, &self.deductions)
+    }
+
+    /// Residual of the constraint at `origin` against an arbitrary set of deductions
+    pub(super) fn residual_with(
+        &self,
+        origin: usize,
+        deductions: &[Option<Deduction>],
+    ) -> Option<(Vec<usize>, u8)> {
+        self.constraints
+            .get(&origin)
+            .map(|constraint| residual(constraint, deductions))
+    }
+
+    /// Flat indices of every revealed tile, in ascending order, without the lines
+    pub(super) fn origins(&self) -> Vec<usize> {
+        let len = self.grid.len();
+        let numbers = self
+            .constraints
+            .keys()
+            .copied()
+            .filter(|&origin| origin < len);
+        let mut origins = numbers.collect::<Vec<_>>();
+        origins.sort_unstable();
+        origins
+    }
+
+    pub(super) fn grid(&self) -> Grid {
+        self.grid
+    }
+
+    /// Constraints covering `cell`
+    fn constraints_of(&self, cell: usize) -> impl Iterator<Item = usize> + '_ {
+        let lines = self.lines().filter(move |line| {
+            let constraint = &self.constraints[line];
+            constraint.cells.contains(&cell)
+        });
+        self.grid
+            .neighbors(cell)
+            .filter(|neighbor| self.constraints.contains_key(neighbor))
+            .chain(lines)
+    }
+
+    /// Constraints that may share a covered cell with the constraint at `origin`: the
+    /// constraints around its neighbors, across the wrap of the topology as well
+    pub(super) fn overlapping(&self, origin: usize) -> impl Iterator<Item = usize> + '_ {
+        let mut overlapping = BTreeSet::new();
+        if origin < self.grid.len() {
+            let around = self
+                .grid
+                .neighbors(origin)
+                .flat_map(|cell| self.constraints_of(cell));
+            overlapping.extend(around);
+        }
+        // lines reach across the board, they overlap whatever shares one of their cells
+        for &cell in &self.constraints[&origin].cells {
+            overlapping.extend(self.constraints_of(cell));
+        }
+        overlapping.remove(&origin);
+        overlapping.into_iter()
+    }
+
+    fn deduce(&self, origin: usize) -> Vec<(usize, Deduction)> {
+        let (unknown, mines) = self.residual(&self.constraints[&origin]);
+        if unknown.is_empty() {
+            return Vec::new();
+        }
+
+        let all = |deduction| unknown.iter().map(move |&cell| (cell, deduction));
+        if mines == 0 {
+            return all(Deduction::Safe).collect();
+        }
+        if mines as usize == unknown.len() {
+            return all(Deduction::Mine).collect();
+        }
+
+        // subset rule: if every unknown cell of A is shared with B, B \ A holds B - A mines
+        let mut found = Vec::new();
+        for other in self.overlapping(origin) {
+            let (other_unknown, other_mines) = self.residual(&self.constraints[&other]);
+
+            for (small, small_mines, large, large_mines) in [
+                (&unknown, mines, &other_unknown, other_mines),
+                (&other_unknown, other_mines, &unknown, mines),
+            ] {
+                if small.is_empty() || !small.iter().all(|cell| large.contains(cell)) {
+                    continue;
+                }
+
+                let rest = large.iter().filter(|cell| !small.contains(cell));
+                let rest_len = large.len() - small.len();
+                match large_mines.checked_sub(small_mines) {
+                    Some(0) => found.extend(rest.map(|&cell| (cell, Deduction::Safe))),
+                    Some(n) if n as usize == rest_len => {
+                        found.extend(rest.map(|&cell| (cell, Deduction::Mine)))
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        found
+    }
+
+    /// Propagate deductions from the dirty constraints until nothing new can be proven
+    fn propagate(&mut self) {
+        let mut queue = std::mem::take(&mut self.dirty)
+            .into_iter()
+            .collect::<VecDeque<_>>();
+
+        while let Some(origin) = queue.pop_front() {
+            for (cell, deduction) in self.deduce(origin) {
+                if self.deductions[cell].is_some() || self.cells[cell] != Knowledge::Covered {
+                    continue;
+                }
+
+                self.deductions[cell] = Some(deduction);
+                let affected = self.constraints_of(cell).collect::<Vec<_>>();
+                for constraint in affected {
+                    self.stale.insert(constraint);
+                    queue.push_back(constraint);
+                    // neighbors of the affected constraint may now be subsets of it
+                    let overlapping = self.overlapping(constraint).collect::<Vec<_>>();
+                    queue.extend(overlapping);
+                }
+            }
+        }
+    }
+
+    /// Split the undecided frontier into independent groups of constraints sharing cells
+    fn components(&self) -> Vec<(Vec<usize>, Vec<usize>)> {
+        let mut by_cell: HashMap<usize, Vec<usize>> = HashMap::new();
+        let mut residuals = HashMap::new();
+        for &origin in self.constraints.keys() {
+            let (unknown, mines) = self.residual(&self.constraints[&origin]);
+            if unknown.is_empty() {
+                continue;
+            }
+            unknown
+                .iter()
+                .for_each(|&cell| by_cell.entry(cell).or_default().push(origin));
+            residuals.insert(origin, (unknown, mines));
+        }
+
+        let mut seen = BTreeSet::new();
+        let mut components = Vec::new();
+        let mut roots = residuals.keys().copied().collect::<Vec<_>>();
+        roots.sort_unstable();
+
+        for root in roots {
+            if !seen.insert(root) {
+                continue;
+            }
+
+            let mut origins = Vec::new();
+            let mut cells = Vec::new();
+            let mut queue = VecDeque::from([root]);
+            while let Some(origin) = queue.pop_front() {
+                origins.push(origin);
+                for &cell in &residuals[&origin].0 {
+                    if cells.contains(&cell) {
+                        continue;
+                    }
+                    cells.push(cell);
+                    by_cell[&cell]
+                        .iter()
+                        .filter(|&&other| seen.insert(other))
+                        .for_each(|&other| queue.push_back(other));
+                }
+            }
+
+            origins.sort_unstable();
+            components.push((origins, cells));
+        }
+
+        components
+    }
+
+    fn solve_component(&mut self, origins: &[usize], cells: Vec<usize>) -> ComponentSolution {
+        let local = origins
+            .iter()
+            .map(|origin| {
+                let (unknown, mines) = self.residual(&self.constraints[origin]);
+                LocalConstraint {
+                    cells: unknown
+                        .iter()
+                        .map(|cell| cells.iter().position(|c| c == cell).unwrap())
+                        .collect(),
+                    mines,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        if cells.len() <= self.config.exact_threshold {
+            probability::enumerate(cells, &local)
+        } else {
+            monte_carlo::sample(cells, &local, self.config.samples, &mut *self.rng)
+        }
+    }
+
+    /// Numbers whose enumeration decides `coord`, as of the last analysis: those of its frontier
+    /// component when its arrangements alone decide the tile, every number otherwise, as the
+    /// mines left tie the components together. `None` when any component was sampled, a sample
+    /// may miss the rare arrangements and proves nothing
+    pub(super) fn enumeration(&self, coord: IVec2) -> Option<Vec<IVec2>> {
+        if self
+            .cache
+            .values()
+            .any(|solution| solution.effective_samples.is_some())
+        {
+            return None;
+        }
+
+        let cell = self.grid.index(coord);
+        let alone = self.cache.iter().find_map(|(origins, solution)| {
+            let i = solution.cells.iter().position(|&c| c == cell)?;
+            let mined = &solution.cell_weights[i];
+            let decided = mined.iter().all(|&w| w == 0.0) || *mined == solution.weights;
+            decided.then_some(origins)
+        });
+        let mut origins = match alone {
+            Some(origins) => origins.clone(),
+            None => self.cache.keys().flatten().copied().collect(),
+        };
+        origins.sort_unstable();
+        origins.dedup();
+        // the lines of puzzles are no number to point at
+        let numbers = origins
+            .into_iter()
+            .filter(|&origin| origin < self.grid.len());
+        Some(numbers.map(|origin| self.grid.coord(origin)).collect())
+    }
+
+    /// Bring deductions and probabilities up to date, reusing everything not affected by the
+    /// reveals since the previous call
+    pub fn analyze(&mut self) -> Analysis {
+        #[cfg(feature = "trace")]
+        let _span = bevy::log::info_span!("solver_analyze", stale = self.stale.len()).entered();
+
+        self.propagate();
+
+        let components = self.components();
+        let mut cache = HashMap::with_capacity(components.len());
+        for (origins, cells) in components {
+            let solution = match self.cache.remove(&origins) {
+                Some(solution) if !origins.iter().any(|o| self.stale.contains(o)) => solution,
+                _ => self.solve_component(&origins, cells),
+            };
+            cache.insert(origins, solution);
+        }
+        self.cache = cache;
+        self.stale.clear();
+
+        self.build_analysis()
+    }
+
+    /// Discard every incremental state and analyze from scratch
+    pub fn analyze_full(&mut self) -> Analysis {
+        self.deductions.fill(None);
+        self.cache.clear();
+        let origins = self.constraints.keys().copied().collect::<Vec<_>>();
+        origins.into_iter().for_each(|origin| self.mark(origin));
+
+        self.analyze()
+    }
+
+    fn build_analysis(&self) -> Analysis {
+        let known_mines = self
+            .deductions
+            .iter()
+            .filter(|&&d| d == Some(Deduction::Mine))
+            .count();
+        let remaining = (self.n_mines as usize).saturating_sub(known_mines);
+
+        let mut confidence: Option<Confidence> = None;
+        let mut probabilities = self
+            .deductions
+            .iter()
+            .zip(self.cells.iter())
+            .map(|(deduction, cell)| match (cell, deduction) {
+                (Knowledge::Revealed(_), _) => None,
+                (_, Some(Deduction::Mine)) => Some(1.0),
+                (_, Some(Deduction::Safe)) => Some(0.0),
+                (_, None) => None,
+            })
+            .collect::<Box<[_]>>();
+
+        let solutions = self.cache.values().collect::<Vec<_>>();
+        let frontier = solutions.iter().map(|s| s.cells.len()).sum::<usize>();
+        let undecided = self
+            .cells
+            .iter()
+            .zip(self.deductions.iter())
+            .filter(|(&cell, deduction)| cell == Knowledge::Covered && deduction.is_none())
+            .count();
+
+        if let Some(combined) = probability::combine(&solutions, undecided - frontier, remaining) {
+            for (solution, cells) in solutions.iter().zip(&combined.components) {
+                for (&cell, &p) in solution.cells.iter().zip(cells) {
+                    probabilities[cell] = Some(p);
+                }
+
+                if let Some(samples) = solution.effective_samples {
+                    let std_error = cells
+                        .iter()
+                        .map(|p| (p * (1.0 - p) / samples.max(1.0)).sqrt())
+                        .fold(0.0, f64::max);
+                    let entry = confidence.get_or_insert(Confidence { samples, std_error });
+                    entry.samples = entry.samples.min(samples);
+                    entry.std_error = entry.std_error.max(std_error);
+                }
+            }
+
+            if let Some(interior) = combined.interior {
+                probabilities
+                    .iter_mut()
+                    .zip(self.cells.iter())
+                    .filter(|(p, &cell)| p.is_none() && cell == Knowledge::Covered)
+                    .for_each(|(p, _)| *p = Some(interior));
+            }
+        }
+
+        Analysis {
+            grid: self.grid,
+            deductions: self.deductions.clone(),
+            probabilities,
+            confidence,
+        }
+    }
+}
+
+impl Analysis {
+    pub fn dim(&self) -> IVec2 {
+        self.grid.dim
+    }
+
+    pub fn deduction<T: Into<IVec2>>(&self, coord: T) -> Option<Deduction> {
+        self.deductions[self.grid.index(coord.into())]
+    }
+
+    /// Mine probability of a covered tile, `None` for revealed tiles
+    pub fn probability<T: Into<IVec2>>(&self, coord: T) -> Option<f64> {
+        self.probabilities[self.grid.index(coord.into())]
+    }
+
+    fn with_deduction(&self, deduction: Deduction) -> impl Iterator<Item = IVec2> + '_ {
+        self.deductions
+            .iter()
+            .enumerate()
+            .filter(move |(_, &d)| d == Some(deduction))
+            .map(|(idx, _)| self.grid.coord(idx))
+    }
+
+    /// Confidence of the sampled probabilities, `None` if every probability is exact
+    pub fn confidence(&self) -> Option<Confidence> {
+        self.confidence
+    }
+
+    /// Covered tiles proven to be safe
+    pub fn safe(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.with_deduction(Deduction::Safe)
+    }
+
+    /// Covered tiles proven to be mines
+    pub fn mines(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.with_deduction(Deduction::Mine)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+    use itertools::Itertools;
+
+    use super::{ConstraintGraph, Deduction};
+    use crate::{rng::RngBackend, solver::SolverConfig, topology::Torus};
+
+    #[test]
+    fn test_trivial_deductions() {
+        // . 1 .
+        // 1 1 .
+        // [0, 1] only touches the top left corner, which then satisfies [1, 0]
+        let mut graph = ConstraintGraph::new(3, 2, 1);
+        graph.reveal([1, 0], 1);
+        graph.reveal([0, 1], 1);
+        graph.reveal([1, 1], 1);
+
+        let analysis = graph.analyze();
+        assert_eq!(
+            analysis.mines().map(|c| c.to_array()).collect_vec(),
+            [[0, 0]]
+        );
+        assert_eq!(
+            analysis.safe().map(|c| c.to_array()).collect_vec(),
+            [[2, 0], [2, 1]]
+        );
+    }
+
+    #[test]
+    fn test_subset_rule() {
+        // row 0 covered, row 1 reads 1 2 1: the 1-2-1 pattern puts mines on both sides
+        let mut graph = ConstraintGraph::new(3, 2, 2);
+        graph.reveal([0, 1], 1);
+        graph.reveal([1, 1], 2);
+        graph.reveal([2, 1], 1);
+
+        let analysis = graph.analyze();
+        assert_eq!(analysis.deduction([0, 0]), Some(Deduction::Mine));
+        assert_eq!(analysis.deduction([1, 0]), Some(Deduction::Safe));
+        assert_eq!(analysis.deduction([2, 0]), Some(Deduction::Mine));
+    }
+
+    #[test]
+    fn test_lines() {
+        // 1 . . .
+        // . . . .
+        // the line holds the single mine of the 1 and one more tile, which is then safe
+        let mut graph = ConstraintGraph::new(4, 2, 3);
+        graph.reveal([0, 0], 1);
+        graph.constrain([[0, 0], [1, 0], [2, 0], [0, 1], [1, 1]].map(IVec2::from), 1);
+
+        let analysis = graph.analyze();
+        assert_eq!(
+            analysis.safe().map(|c| c.to_array()).collect_vec(),
+            [[2, 0]]
+        );
+
+        // revealing a tile of the line leaves the line with the tiles still covered
+        graph.reveal([2, 0], 1);
+        graph.constrain([[3, 0], [3, 1]].map(IVec2::from), 0);
+        let analysis = graph.analyze();
+        assert_eq!(
+            analysis.safe().map(|c| c.to_array()).collect_vec(),
+            [[3, 0], [3, 1]]
+        );
+    }
+
+    #[test]
+    fn test_subset_rule_across_wrap() {
+        // on a 6 wide torus, the 1 at [0, 1] is next to the 1 at [5, 1]: its covered tiles in
+        // columns 5 and 0 are all around [5, 1] too, which leaves column 4 safe
+        let mut graph = ConstraintGraph::new(6, 3, 1).with_topology(&Torus);
+        graph.reveal([0, 1], 1);
+        graph.reveal([5, 1], 1);
+        for y in 0..3 {
+            graph.reveal([1, y], 1);
+        }
+
+        let origin = graph.grid.index([0, 1].into());
+        let wrapped = graph.grid.index([5, 1].into());
+        assert!(graph.overlapping(origin).contains(&wrapped));
+        let safe = graph
+            .deduce(origin)
+            .into_iter()
+            .filter(|&(_, deduction)| deduction == Deduction::Safe)
+            .map(|(cell, _)| graph.grid.coord(cell).to_array())
+            .sorted()
+            .dedup()
+            .collect_vec();
+        assert_eq!(safe, [[4, 0], [4, 1], [4, 2]]);
+    }
+
+    #[test]
+    fn test_probabilities() {
+        // a single 1 in the corner of a 2x2 board with one mine: uniform over the others
+        let mut graph = ConstraintGraph::new(2, 2, 1);
+        graph.reveal([0, 0], 1);
+
+        let analysis = graph.analyze();
+        assert_eq!(analysis.probability([0, 0]), None);
+        for coord in [[1, 0], [0, 1], [1, 1]] {
+            let p = analysis.probability(coord).unwrap();
+            assert!((p - 1.0 / 3.0).abs() < 1e-9, "{coord:?}: {p}");
+        }
+    }
+
+    #[test]
+    fn test_monte_carlo_fallback() {
+        // the only mine is one of the 8 tiles around a lone 1
+        let exact = {
+            let mut graph = ConstraintGraph::new(6, 6, 1);
+            graph.reveal([1, 1], 1);
+            graph.analyze()
+        };
+        let sampled = {
+            let config = SolverConfig {
+                exact_threshold: 0,
+                samples: 20000,
+            };
+            let mut graph = ConstraintGraph::new(6, 6, 1).with_config(config);
+            graph.reveal([1, 1], 1);
+            graph.analyze()
+        };
+
+        assert!(exact.confidence().is_none());
+        let confidence = sampled.confidence().unwrap();
+        assert!(confidence.std_error < 0.01, "{confidence:?}");
+
+        for coord in (0..6).cartesian_product(0..6) {
+            match (exact.probability(coord), sampled.probability(coord)) {
+                (Some(l), Some(r)) => assert!((l - r).abs() < 0.05, "{coord:?}: {l} vs {r}"),
+                (l, r) => assert_eq!(l, r),
+            }
+        }
+    }
+
+    #[test]
+    fn test_seeded_sampling() {
+        let sampled = |seed| {
+            let config = SolverConfig {
+                exact_threshold: 0,
+                samples: 500,
+            };
+            let mut graph = ConstraintGraph::new(6, 6, 3)
+                .with_config(config)
+                .with_rng(RngBackend::Nanorand.seeded(seed));
+            graph.reveal([1, 1], 2);
+            graph.analyze().probability([0, 0])
+        };
+        assert_eq!(sampled(1), sampled(1));
+        assert_ne!(sampled(1), sampled(2));
+    }
+
+    #[test]
+    fn test_incremental_matches_full() {
+        let mut tiles = crate::resources::board::TileMap::seeded(16, 16, 40, 7).unwrap();
+        // sampling is random, compare exact enumeration only
+        let mut incremental = ConstraintGraph::new(16, 16, 40).with_config(SolverConfig {
+            exact_threshold: usize::MAX,
+            ..Default::default()
+        });
+
+        let safe = tiles
+            .all_tiles()
+            .filter(|tile| !tile.is_mine())
+            .map(|tile| (tile.coord(), tile.state()))
+            .collect_vec();
+
+        for (coord, state) in safe.into_iter().step_by(3).take(40) {
+            let crate::resources::board::TileState::Clear(n) = state else {
+                unreachable!()
+            };
+            incremental.reveal(coord, n);
+            let lhs = incremental.analyze();
+            let rhs = incremental.clone().analyze_full();
+
+            for coord in tiles.coords() {
+                assert_eq!(lhs.deduction(coord), rhs.deduction(coord));
+                match (lhs.probability(coord), rhs.probability(coord)) {
+                    (Some(l), Some(r)) => assert!((l - r).abs() < 1e-9),
+                    (l, r) => assert_eq!(l, r),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,374 @@
+//! Next move suggestions, explained with the rule a human would use to find them.
+
+use std::fmt::Display;
+
+use bevy::prelude::IVec2;
+
+use super::{
+    pattern::{self, PatternMatch},
+    ConstraintGraph, Deduction, Knowledge,
+};
+
+/// Why a tile is proven safe or mined
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The number already touches all of its mines
+    Satisfied { number: IVec2 },
+    /// The number has as many mines left as undecided neighbors
+    Saturated { number: IVec2 },
+    /// A named pattern from the pattern library
+    Pattern(PatternMatch),
+    /// Every undecided neighbor of `inner` also touches `outer`
+    Subset { inner: IVec2, outer: IVec2 },
+    /// No consistent arrangement of the remaining mines says otherwise, enumerated around these
+    /// numbers
+    Exhaustive { numbers: Vec<IVec2> },
+}
+
+/// One application of a rule
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Step {
+    pub reason: Reason,
+    pub deductions: Vec<(IVec2, Deduction)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Hint {
+    /// A tile proven safe or mined, and the rule proving it
+    Proven {
+        coord: IVec2,
+        deduction: Deduction,
+        reason: Reason,
+    },
+    /// Nothing can be proven, this covered tile is the least likely to be a mine
+    Guess { coord: IVec2, probability: f64 },
+}
+
+/// Find the simplest rule proving something new, trying cheap rules on every number first
+pub(super) fn next_step(graph: &ConstraintGraph, deductions: &[Option<Deduction>]) -> Option<Step> {
+    let grid = graph.grid();
+    let origins = graph.origins();
+    let residual = |origin| graph.residual_with(origin, deductions).unwrap();
+    let step = |reason, cells: &[usize], deduction| Step {
+        reason,
+        deductions: cells.iter().map(|&c| (grid.coord(c), deduction)).collect(),
+    };
+
+    let trivial = origins.iter().find_map(|&origin| {
+        let (unknown, mines) = residual(origin);
+        let number = grid.coord(origin);
+        match mines {
+            _ if unknown.is_empty() => None,
+            0 => Some(step(
+                Reason::Satisfied { number },
+                &unknown,
+                Deduction::Safe,
+            )),
+            n if n as usize == unknown.len() => Some(step(
+                Reason::Saturated { number },
+                &unknown,
+                Deduction::Mine,
+            )),
+            _ => None,
+        }
+    });
+
+    let patterns = || {
+        origins.iter().find_map(|&origin| {
+            let found = pattern::find(graph, deductions, origin)?;
+            let deductions = found
+                .deductions()
+                .filter(|&(coord, _)| deductions[grid.index(coord)].is_none())
+                .collect::<Vec<_>>();
+            (!deductions.is_empty()).then_some(Step {
+                reason: Reason::Pattern(found),
+                deductions,
+            })
+        })
+    };
+
+    let subsets = || {
+        origins.iter().find_map(|&inner| {
+            let (small, small_mines) = residual(inner);
+            if small.is_empty() {
+                return None;
+            }
+
+            // the lines of puzzles are no number to point at
+            let mut numbers = graph.overlapping(inner).filter(|&outer| outer < grid.len());
+            numbers.find_map(|outer| {
+                let (large, large_mines) = residual(outer);
+                if !small.iter().all(|cell| large.contains(cell)) || large.len() == small.len() {
+                    return None;
+                }
+
+                let rest = large
+                    .iter()
+                    .copied()
+                    .filter(|cell| !small.contains(cell))
+                    .collect::<Vec<_>>();
+                let reason = Reason::Subset {
+                    inner: grid.coord(inner),
+                    outer: grid.coord(outer),
+                };
+                match large_mines.checked_sub(small_mines)? {
+                    0 => Some(step(reason, &rest, Deduction::Safe)),
+                    n if n as usize == rest.len() => Some(step(reason, &rest, Deduction::Mine)),
+                    _ => None,
+                }
+            })
+        })
+    };
+
+    trivial.or_else(patterns).or_else(subsets)
+}
+
+/// Suggest the next move: the first tile that can be proven safe, otherwise a proven mine,
+/// otherwise the best guess
+pub fn hint(graph: &mut ConstraintGraph) -> Option<Hint> {
+    let grid = graph.grid();
+    let mut deductions = vec![None; grid.len()];
+    let mut first_mine = None;
+
+    while let Some(step) = next_step(graph, &deductions) {
+        let safe = step
+            .deductions
+            .iter()
+            .find(|(_, deduction)| *deduction == Deduction::Safe);
+        if let Some(&(coord, deduction)) = safe {
+            return Some(Hint::Proven {
+                coord,
+                deduction,
+                reason: step.reason,
+            });
+        }
+
+        if first_mine.is_none() {
+            first_mine = Some(step.clone());
+        }
+        for (coord, deduction) in step.deductions {
+            deductions[grid.index(coord)] = Some(deduction);
+        }
+    }
+
+    let analysis = graph.analyze();
+    let covered = |coord: &IVec2| graph.knowledge(*coord) == Knowledge::Covered;
+    let candidates = (0..grid.len())
+        .map(|idx| grid.coord(idx))
+        .filter(covered)
+        .filter_map(|coord| Some((coord, analysis.probability(coord)?)));
+
+    // a sampled probability of 0 may only have missed the arrangements with a mine
+    let enumerated = candidates
+        .clone()
+        .filter(|&(_, p)| p == 0.0)
+        .find_map(|(coord, _)| Some((coord, graph.enumeration(coord)?)));
+    if let Some((coord, numbers)) = enumerated {
+        return Some(Hint::Proven {
+            coord,
+            deduction: Deduction::Safe,
+            reason: Reason::Exhaustive { numbers },
+        });
+    }
+
+    if let Some(Step { reason, deductions }) = first_mine {
+        let (coord, deduction) = deductions[0];
+        return Some(Hint::Proven {
+            coord,
+            deduction,
+            reason,
+        });
+    }
+
+    candidates
+        .min_by(|(_, lhs), (_, rhs)| lhs.total_cmp(rhs))
+        .map(|(coord, probability)| Hint::Guess { coord, probability })
+}
+
+struct Tile(IVec2);
+
+impl Display for Tile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "({}, {})", self.0.x, self.0.y)
+    }
+}
+
+/// Sentence explaining why `coord` is proven safe or mined
+struct Explanation<'a> {
+    coord: IVec2,
+    deduction: Deduction,
+    reason: &'a Reason,
+}
+
+impl Display for Explanation<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let coord = Tile(self.coord);
+        let verdict = match self.deduction {
+            Deduction::Safe => "safe",
+            Deduction::Mine => "a mine",
+        };
+
+        match self.reason {
+            Reason::Satisfied { number } => write!(
+                f,
+                "The number at {} already touches all of its mines, so {coord} is {verdict}",
+                Tile(*number)
+            ),
+            Reason::Saturated { number } => write!(
+                f,
+                "The number at {} has as many mines left as covered neighbors, so {coord} is {verdict}",
+                Tile(*number)
+            ),
+            Reason::Pattern(found) => write!(
+                f,
+                "This is a {} pattern starting at {}, so {coord} is {verdict}",
+                found.pattern.name,
+                Tile(found.numbers[0])
+            ),
+            Reason::Subset { inner, outer } => write!(
+                f,
+                "Every open neighbor of {} also touches {}, so the rest of {} holds the difference: {coord} is {verdict}",
+                Tile(*inner),
+                Tile(*outer),
+                Tile(*outer)
+            ),
+            Reason::Exhaustive { numbers } if numbers.is_empty() => {
+                write!(f, "Every arrangement of the mines left makes {coord} {verdict}")
+            }
+            Reason::Exhaustive { numbers } => {
+                let shown = numbers.iter().take(3).map(|&number| Tile(number).to_string());
+                let shown = shown.collect::<Vec<_>>().join(", ");
+                let more = match numbers.len() {
+                    n if n > 3 => format!(" and {} more", n - 3),
+                    _ => String::new(),
+                };
+                write!(
+                    f,
+                    "Every arrangement of mines fitting the numbers at {shown}{more} makes {coord} {verdict}"
+                )
+            }
+        }
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Hint::Guess { coord, probability } => write!(
+                f,
+                "Nothing can be proven, {} is the safest guess with a {:.0}% chance of a mine",
+                Tile(*coord),
+                probability * 100.0
+            ),
+            Hint::Proven {
+                coord,
+                deduction,
+                reason,
+            } => Explanation {
+                coord: *coord,
+                deduction: *deduction,
+                reason,
+            }
+            .fmt(f),
+        }
+    }
+}
+
+impl Display for Step {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (coord, deduction) = self.deductions[0];
+        Explanation {
+            coord,
+            deduction,
+            reason: &self.reason,
+        }
+        .fmt(f)
+    }
+}
+
+impl Reason {
+    /// Revealed numbers the rule relies on
+    pub fn numbers(&self) -> Vec<IVec2> {
+        match self {
+            Reason::Satisfied { number } | Reason::Saturated { number } => vec![*number],
+            Reason::Pattern(found) => found.numbers.clone(),
+            Reason::Subset { inner, outer } => vec![*inner, *outer],
+            Reason::Exhaustive { numbers } => numbers.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{hint, Hint, Reason};
+    use crate::solver::{ConstraintGraph, Deduction, SolverConfig};
+
+    #[test]
+    fn test_pattern_hint() {
+        // . . *
+        // 0 1 1
+        let mut graph = ConstraintGraph::new(3, 2, 1);
+        for (x, n) in [0, 1, 1].into_iter().enumerate() {
+            graph.reveal([x as i32, 1], n);
+        }
+
+        let Some(Hint::Proven {
+            deduction, reason, ..
+        }) = hint(&mut graph)
+        else {
+            panic!("expected a proven hint");
+        };
+
+        assert_eq!(deduction, Deduction::Safe);
+        assert!(matches!(reason, Reason::Satisfied { .. }));
+
+        // . . .
+        // 1 1 1   the two 1s against the left wall share their mine, the top right is free
+        let mut graph = ConstraintGraph::new(3, 2, 1);
+        for (x, n) in [1, 1, 1].into_iter().enumerate() {
+            graph.reveal([x as i32, 1], n);
+        }
+
+        let hint = hint(&mut graph).unwrap();
+        let Hint::Proven {
+            coord,
+            deduction,
+            reason: Reason::Pattern(found),
+        } = &hint
+        else {
+            panic!("expected a pattern, got {hint:?}");
+        };
+        assert_eq!(found.pattern.name, "1-1 corner");
+        assert_eq!(coord.to_array(), [2, 0]);
+        assert_eq!(*deduction, Deduction::Safe);
+        assert!(hint.to_string().contains("1-1 corner"));
+    }
+
+    #[test]
+    fn test_exhaustive_hint() {
+        // the only mine is around the lone 1, every tile away from it is safe
+        let lone = |config| {
+            let mut graph = ConstraintGraph::new(6, 6, 1).with_config(config);
+            graph.reveal([1, 1], 1);
+            hint(&mut graph).unwrap()
+        };
+        let hint = lone(SolverConfig::default());
+        assert!(
+            matches!(
+                hint,
+                Hint::Proven {
+                    deduction: Deduction::Safe,
+                    reason: Reason::Exhaustive { .. },
+                    ..
+                }
+            ),
+            "{hint:?}"
+        );
+        // a sample proves nothing, however likely
+        let sampled = SolverConfig {
+            exact_threshold: 0,
+            samples: 1000,
+        };
+        assert!(matches!(lone(sampled), Hint::Guess { .. }));
+    }
+}
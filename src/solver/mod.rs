@@ -0,0 +1,82 @@
+//! Constraint based solver working only with the information visible to the player.
+//!
+//! Every revealed number is a constraint over its covered neighbors. The [`ConstraintGraph`]
+//! keeps these constraints up to date as tiles get revealed, so that re-analyzing the board after
+//! a move only touches the part of the frontier that actually changed.
+
+mod bot;
+mod graph;
+mod hint;
+mod monte_carlo;
+mod pattern;
+mod probability;
+mod proof;
+mod solvable;
+
+use bevy::prelude::IVec2;
+
+use crate::topology::Topology;
+
+pub use bot::{bot_move, BotSkill};
+pub use graph::{Analysis, Confidence, ConstraintGraph, Deduction, Knowledge};
+pub use hint::{hint, Hint, Reason, Step};
+pub use pattern::{Pattern, PatternMatch, PATTERNS};
+pub use proof::{explain, Proof};
+pub use solvable::is_solvable;
+
+/// Tuning of the probability pass
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverConfig {
+    /// Largest frontier component, in tiles, whose solutions are still enumerated exactly.
+    /// Bigger components fall back to Monte Carlo sampling
+    pub exact_threshold: usize,
+    /// Samples drawn for every component above the threshold
+    pub samples: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            exact_threshold: 32,
+            samples: 4096,
+        }
+    }
+}
+
+/// Flat index based addressing of a `width * height` grid, tiles touching as the topology
+/// has it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Grid {
+    dim: IVec2,
+    topology: &'static dyn Topology,
+}
+
+impl Grid {
+    fn len(self) -> usize {
+        (self.dim.x * self.dim.y) as usize
+    }
+
+    fn contains(self, coord: IVec2) -> bool {
+        coord.cmpge(IVec2::ZERO).all() && coord.cmplt(self.dim).all()
+    }
+
+    fn index(self, coord: IVec2) -> usize {
+        self.topology.index(coord, self.dim).unwrap_or_else(|| {
+            panic!(
+                "Coordinate {:?} must be bound between [0, 0] and {:?}",
+                coord.to_array(),
+                self.dim.to_array()
+            )
+        })
+    }
+
+    fn coord(self, idx: usize) -> IVec2 {
+        self.topology.coord(idx, self.dim)
+    }
+
+    fn neighbors(self, idx: usize) -> impl Iterator<Item = usize> {
+        self.topology
+            .neighbors(self.coord(idx), self.dim)
+            .map(move |neighbor| self.index(neighbor))
+    }
+}
@@ -0,0 +1,69 @@
+//! Sampled mine probabilities for frontier components too large to enumerate exactly.
+//!
+//! Each sample walks the cells in order, picking uniformly among the values that keep every
+//! constraint satisfiable, and is weighted by the product of the number of choices along the way
+//! (Knuth's estimator). Weighted sample counts are unbiased estimates of the exact solution
+//! counts, which lets sampled components be combined with exact ones unchanged.
+
+use super::probability::{ComponentSolution, LocalConstraint, PartialAssignment};
+use crate::rng::Rng;
+
+/// Draw one assignment, returning its importance weight or `None` on a dead end
+fn draw(assignment: &mut PartialAssignment, rng: &mut dyn Rng) -> Option<f64> {
+    assignment.reset();
+
+    let mut weight = 1.0;
+    for cell in 0..assignment.cells.len() {
+        let mut options = [false; 2];
+        for mine in [false, true] {
+            options[mine as usize] = assignment.assign(cell, mine);
+            assignment.unassign(cell, mine);
+        }
+
+        let mine = match options {
+            [false, false] => return None,
+            [true, true] => {
+                weight *= 2.0;
+                rng.next_bool()
+            }
+            [_, mine] => mine,
+        };
+        assignment.assign(cell, mine);
+    }
+
+    Some(weight)
+}
+
+/// Estimate the solution distribution of a component from `samples` weighted random walks
+pub(super) fn sample(
+    cells: Vec<usize>,
+    constraints: &[LocalConstraint],
+    samples: usize,
+    rng: &mut dyn Rng,
+) -> ComponentSolution {
+    let mut assignment = PartialAssignment::new(cells.len(), constraints);
+    let mut solution = ComponentSolution {
+        cell_weights: vec![Vec::new(); cells.len()],
+        cells,
+        weights: Vec::new(),
+        effective_samples: None,
+    };
+
+    let (mut sum, mut sum_sq) = (0.0, 0.0);
+    for _ in 0..samples {
+        if let Some(weight) = draw(&mut assignment, rng) {
+            let mines = assignment.cells.iter().filter(|&&mine| mine).count();
+            solution.record(&assignment.cells, mines, weight);
+            sum += weight;
+            sum_sq += weight * weight;
+        }
+    }
+
+    // Kish's effective sample size of the weighted samples
+    solution.effective_samples = Some(if sum_sq > 0.0 {
+        sum * sum / sum_sq
+    } else {
+        0.0
+    });
+    solution
+}
@@ -0,0 +1,156 @@
+//! Library of well known number patterns along a straight wall of covered tiles.
+//!
+//! A pattern is a run of adjacent revealed numbers, each touching exactly three consecutive
+//! undecided tiles of the row next to it (two for the first number of a pattern starting
+//! against a wall). Numbers are residual counts: mines already proven around a number are
+//! subtracted, the same way players "reduce" numbers in their head.
+
+use bevy::prelude::IVec2;
+
+use super::{ConstraintGraph, Deduction};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pattern {
+    pub name: &'static str,
+    /// Residual mine counts of the run of numbers
+    numbers: &'static [u8],
+    /// The first number does not touch a tile before the start of the row
+    bounded: bool,
+    /// Row offsets of the tiles proven to be mines, relative to the first number
+    mines: &'static [i32],
+    /// Row offsets of the tiles proven to be safe, relative to the first number
+    safe: &'static [i32],
+}
+
+/// Known patterns, longest first so that the most specific name gets cited
+pub const PATTERNS: &[Pattern] = &[
+    Pattern {
+        name: "1-2-2-1",
+        numbers: &[1, 2, 2, 1],
+        bounded: false,
+        mines: &[1, 2],
+        safe: &[-1, 0, 3, 4],
+    },
+    Pattern {
+        name: "1-2-1",
+        numbers: &[1, 2, 1],
+        bounded: false,
+        mines: &[0, 2],
+        safe: &[-1, 1, 3],
+    },
+    Pattern {
+        name: "1-1 corner",
+        numbers: &[1, 1],
+        bounded: true,
+        mines: &[],
+        safe: &[2],
+    },
+    Pattern {
+        name: "1-2 corner",
+        numbers: &[1, 2],
+        bounded: true,
+        mines: &[2],
+        safe: &[],
+    },
+    Pattern {
+        name: "1-2",
+        numbers: &[1, 2],
+        bounded: false,
+        mines: &[2],
+        safe: &[-1],
+    },
+];
+
+/// A pattern found on the board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PatternMatch {
+    pub pattern: &'static Pattern,
+    /// The revealed numbers forming the pattern, in order
+    pub numbers: Vec<IVec2>,
+    pub mines: Vec<IVec2>,
+    pub safe: Vec<IVec2>,
+}
+
+impl PatternMatch {
+    pub fn deductions(&self) -> impl Iterator<Item = (IVec2, Deduction)> + '_ {
+        let mines = self.mines.iter().map(|&c| (c, Deduction::Mine));
+        let safe = self.safe.iter().map(|&c| (c, Deduction::Safe));
+        mines.chain(safe)
+    }
+}
+
+/// Row directions paired with the side of the row the covered tiles are on
+const ORIENTATIONS: [([i32; 2], [i32; 2]); 8] = [
+    ([1, 0], [0, -1]),
+    ([1, 0], [0, 1]),
+    ([-1, 0], [0, -1]),
+    ([-1, 0], [0, 1]),
+    ([0, 1], [-1, 0]),
+    ([0, 1], [1, 0]),
+    ([0, -1], [-1, 0]),
+    ([0, -1], [1, 0]),
+];
+
+fn matches(
+    graph: &ConstraintGraph,
+    deductions: &[Option<Deduction>],
+    pattern: &Pattern,
+    start: IVec2,
+    step: IVec2,
+    side: IVec2,
+) -> bool {
+    let grid = graph.grid();
+    let row = |offset: i32| start + side + step * offset;
+
+    pattern.numbers.iter().enumerate().all(|(k, &number)| {
+        let k = k as i32;
+        let origin = start + step * k;
+        if !grid.contains(origin) {
+            return false;
+        }
+
+        let Some((mut unknown, mines)) = graph.residual_with(grid.index(origin), deductions) else {
+            return false;
+        };
+
+        let first = if pattern.bounded && k == 0 { 0 } else { -1 };
+        let mut expected = (k + first..=k + 1)
+            .map(row)
+            .filter(|&coord| grid.contains(coord))
+            .map(|coord| grid.index(coord))
+            .collect::<Vec<_>>();
+
+        // every tile of the window must be on the board, otherwise it is a different pattern
+        let complete = expected.len() as i32 == 2 - first;
+        unknown.sort_unstable();
+        expected.sort_unstable();
+        complete && mines == number && unknown == expected
+    })
+}
+
+/// Find a pattern starting at the revealed tile `origin`
+pub(super) fn find(
+    graph: &ConstraintGraph,
+    deductions: &[Option<Deduction>],
+    origin: usize,
+) -> Option<PatternMatch> {
+    let grid = graph.grid();
+    let start = grid.coord(origin);
+
+    PATTERNS.iter().find_map(|pattern| {
+        ORIENTATIONS.into_iter().find_map(|(step, side)| {
+            let (step, side) = (IVec2::from(step), IVec2::from(side));
+            matches(graph, deductions, pattern, start, step, side).then(|| {
+                let row = |&offset: &i32| start + side + step * offset;
+                PatternMatch {
+                    pattern,
+                    numbers: (0..pattern.numbers.len() as i32)
+                        .map(|k| start + step * k)
+                        .collect(),
+                    mines: pattern.mines.iter().map(row).collect(),
+                    safe: pattern.safe.iter().map(row).collect(),
+                }
+            })
+        })
+    })
+}
@@ -0,0 +1,270 @@
+//! Exact mine probabilities by enumerating every consistent assignment of a frontier component.
+
+/// Solution counts of one connected frontier component, bucketed by the number of mines used
+#[derive(Debug, Clone, Default)]
+pub(super) struct ComponentSolution {
+    /// Flat cell indices, in enumeration order
+    pub cells: Vec<usize>,
+    /// `weights[k]` is the number of solutions placing exactly `k` mines
+    pub weights: Vec<f64>,
+    /// `cell_weights[i][k]` is the number of solutions placing `k` mines with `cells[i]` a mine
+    pub cell_weights: Vec<Vec<f64>>,
+    /// Effective sample size if the counts are estimated, `None` if they are exact
+    pub effective_samples: Option<f64>,
+}
+
+impl ComponentSolution {
+    /// Count one assignment using `mines` mines
+    pub fn record(&mut self, assignment: &[bool], mines: usize, weight: f64) {
+        if self.weights.len() <= mines {
+            self.weights.resize(mines + 1, 0.0);
+            self.cell_weights
+                .iter_mut()
+                .for_each(|weights| weights.resize(mines + 1, 0.0));
+        }
+
+        self.weights[mines] += weight;
+        assignment
+            .iter()
+            .zip(&mut self.cell_weights)
+            .filter(|(&mine, _)| mine)
+            .for_each(|(_, weights)| weights[mines] += weight);
+    }
+}
+
+/// A constraint restricted to one component, `cells` are indices into the component cell list
+#[derive(Debug, Clone)]
+pub(super) struct LocalConstraint {
+    pub cells: Vec<usize>,
+    pub mines: u8,
+}
+
+/// Cells of a component assigned one by one, keeping track of how far each constraint is from
+/// being broken. Shared by the exact enumeration and the sampler of `monte_carlo`
+pub(super) struct PartialAssignment<'a> {
+    constraints: &'a [LocalConstraint],
+    /// constraints touching each cell
+    by_cell: Vec<Vec<usize>>,
+    /// mines placed so far per constraint
+    placed: Vec<u8>,
+    /// cells not yet assigned per constraint
+    open: Vec<u8>,
+    /// mine or not per cell, meaningful for the assigned cells only
+    pub cells: Vec<bool>,
+}
+
+impl<'a> PartialAssignment<'a> {
+    /// Nothing assigned yet among `cells` cells
+    pub fn new(cells: usize, constraints: &'a [LocalConstraint]) -> Self {
+        let mut by_cell = vec![Vec::new(); cells];
+        constraints
+            .iter()
+            .enumerate()
+            .for_each(|(idx, constraint)| {
+                constraint
+                    .cells
+                    .iter()
+                    .for_each(|&cell| by_cell[cell].push(idx))
+            });
+
+        Self {
+            constraints,
+            by_cell,
+            placed: vec![0; constraints.len()],
+            open: constraints.iter().map(|c| c.cells.len() as u8).collect(),
+            cells: vec![false; cells],
+        }
+    }
+
+    fn feasible(&self, constraint: usize) -> bool {
+        let target = self.constraints[constraint].mines;
+        let placed = self.placed[constraint];
+        placed <= target && placed + self.open[constraint] >= target
+    }
+
+    /// Assign `cell`, returning whether every constraint can still be satisfied. Undo it with
+    /// [`Self::unassign`] either way
+    pub fn assign(&mut self, cell: usize, mine: bool) -> bool {
+        self.cells[cell] = mine;
+        let mut ok = true;
+        for idx in 0..self.by_cell[cell].len() {
+            let constraint = self.by_cell[cell][idx];
+            self.open[constraint] -= 1;
+            self.placed[constraint] += mine as u8;
+            ok &= self.feasible(constraint);
+        }
+        ok
+    }
+
+    pub fn unassign(&mut self, cell: usize, mine: bool) {
+        for &constraint in &self.by_cell[cell] {
+            self.open[constraint] += 1;
+            self.placed[constraint] -= mine as u8;
+        }
+    }
+
+    /// Forget every assignment
+    pub fn reset(&mut self) {
+        self.placed.fill(0);
+        self.open
+            .iter_mut()
+            .zip(self.constraints)
+            .for_each(|(open, constraint)| *open = constraint.cells.len() as u8);
+    }
+}
+
+struct Search<'a> {
+    assignment: PartialAssignment<'a>,
+    solution: ComponentSolution,
+}
+
+impl Search<'_> {
+    fn run(&mut self, cell: usize, mines: usize) {
+        if cell == self.assignment.cells.len() {
+            self.solution.record(&self.assignment.cells, mines, 1.0);
+            return;
+        }
+
+        for mine in [false, true] {
+            if self.assignment.assign(cell, mine) {
+                self.run(cell + 1, mines + mine as usize);
+            }
+            self.assignment.unassign(cell, mine);
+        }
+    }
+}
+
+/// Count every assignment of `cells` satisfying all `constraints`.
+///
+/// Runs in time exponential to the component size, cells should be ordered so that constraints
+/// close early (e.g. breadth first along the frontier) to keep the pruning effective.
+pub(super) fn enumerate(cells: Vec<usize>, constraints: &[LocalConstraint]) -> ComponentSolution {
+    let mut search = Search {
+        assignment: PartialAssignment::new(cells.len(), constraints),
+        solution: ComponentSolution {
+            cell_weights: vec![Vec::new(); cells.len()],
+            cells,
+            weights: Vec::new(),
+            effective_samples: None,
+        },
+    };
+
+    search.run(0, 0);
+    search.solution
+}
+
+fn convolve(lhs: &[f64], rhs: &[f64]) -> Vec<f64> {
+    if lhs.is_empty() || rhs.is_empty() {
+        return Vec::new();
+    }
+
+    let mut out = vec![0.0; lhs.len() + rhs.len() - 1];
+    for (i, &l) in lhs.iter().enumerate() {
+        for (j, &r) in rhs.iter().enumerate() {
+            out[i + j] += l * r;
+        }
+    }
+    out
+}
+
+/// Mine probabilities of every component cell and of the unconstrained interior
+#[derive(Debug, Clone, Default)]
+pub(super) struct Combined {
+    /// Per component, per cell probability, parallel to `ComponentSolution::cells`
+    pub components: Vec<Vec<f64>>,
+    /// Probability of any covered tile not touching a revealed number
+    pub interior: Option<f64>,
+}
+
+/// Weigh the independent component solutions against each other given that exactly `remaining`
+/// mines are left among the component cells and the `interior` unconstrained cells.
+///
+/// Returns `None` if no global assignment is consistent.
+pub(super) fn combine(
+    components: &[&ComponentSolution],
+    interior: usize,
+    remaining: usize,
+) -> Option<Combined> {
+    // ln(n!) for every n up to the interior size, binomials get huge on big boards
+    let ln_fact = (1..=interior).fold(vec![0.0f64], |mut acc, n| {
+        acc.push(acc[n - 1] + (n as f64).ln());
+        acc
+    });
+    let ln_binomial = |k: usize| -> Option<f64> {
+        (k <= interior).then(|| ln_fact[interior] - ln_fact[k] - ln_fact[interior - k])
+    };
+
+    // weight of leaving `remaining - used` mines for the interior, scaled to avoid overflows
+    let max = (0..=remaining)
+        .filter_map(|used| ln_binomial(remaining - used))
+        .fold(f64::NEG_INFINITY, f64::max);
+    let interior_weight = |used: usize| -> f64 {
+        remaining
+            .checked_sub(used)
+            .and_then(ln_binomial)
+            .map_or(0.0, |ln| (ln - max).exp())
+    };
+
+    let mut prefix = vec![vec![1.0]];
+    components.iter().for_each(|c| {
+        let next = convolve(prefix.last().unwrap(), &c.weights);
+        prefix.push(next);
+    });
+    let mut suffix = vec![vec![1.0]];
+    components.iter().rev().for_each(|c| {
+        let next = convolve(suffix.last().unwrap(), &c.weights);
+        suffix.push(next);
+    });
+    suffix.reverse();
+
+    let total = prefix.last().unwrap();
+    let weight: f64 = total
+        .iter()
+        .enumerate()
+        .map(|(used, &w)| w * interior_weight(used))
+        .sum();
+
+    if weight <= 0.0 || !weight.is_finite() {
+        return None;
+    }
+
+    let probabilities = components
+        .iter()
+        .enumerate()
+        .map(|(idx, component)| {
+            let others = convolve(&prefix[idx], &suffix[idx + 1]);
+            let scale = (0..component.weights.len())
+                .map(|k| {
+                    others
+                        .iter()
+                        .enumerate()
+                        .map(|(used, &w)| w * interior_weight(k + used))
+                        .sum::<f64>()
+                })
+                .collect::<Vec<_>>();
+
+            component
+                .cell_weights
+                .iter()
+                .map(|weights| weights.iter().zip(&scale).map(|(w, s)| w * s).sum::<f64>() / weight)
+                .collect()
+        })
+        .collect();
+
+    let interior = (interior > 0).then(|| {
+        total
+            .iter()
+            .enumerate()
+            .map(|(used, &w)| {
+                let left = remaining.saturating_sub(used) as f64;
+                w * interior_weight(used) * left / interior as f64
+            })
+            .sum::<f64>()
+            / weight
+    });
+
+    Some(Combined {
+        components: probabilities,
+        interior,
+    })
+}
@@ -0,0 +1,173 @@
+//! Proof traces: the chain of rule applications establishing a single tile.
+
+use std::collections::BTreeSet;
+
+use bevy::prelude::IVec2;
+
+use super::{
+    hint::{self, Reason, Step},
+    ConstraintGraph, Deduction, Knowledge,
+};
+
+/// Steps proving that `coord` is safe or mined, in the order they have to be applied
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Proof {
+    pub coord: IVec2,
+    pub deduction: Deduction,
+    pub steps: Vec<Step>,
+}
+
+/// Prove the covered tile at `coord` safe or mined, `None` if it cannot be decided
+pub fn explain<T: Into<IVec2>>(graph: &mut ConstraintGraph, coord: T) -> Option<Proof> {
+    let coord = coord.into();
+    if graph.knowledge(coord) != Knowledge::Covered {
+        return None;
+    }
+
+    let grid = graph.grid();
+    let target = grid.index(coord);
+    let mut deductions = vec![None; grid.len()];
+    // step that first decided each tile
+    let mut decided_by = vec![None; grid.len()];
+    let mut steps = Vec::new();
+
+    while deductions[target].is_none() {
+        let Some(step) = hint::next_step(graph, &deductions) else {
+            break;
+        };
+
+        for &(cell, deduction) in &step.deductions {
+            let idx = grid.index(cell);
+            deductions[idx] = Some(deduction);
+            decided_by[idx] = Some(steps.len());
+        }
+        steps.push(step);
+    }
+
+    let Some(deduction) = deductions[target] else {
+        // the rules are stuck, but enumerating every arrangement may still settle it
+        let deduction = match graph.analyze().probability(coord)? {
+            0.0 => Deduction::Safe,
+            1.0 => Deduction::Mine,
+            _ => return None,
+        };
+        // a sample says nothing of the arrangements it missed
+        let numbers = graph.enumeration(coord)?;
+
+        return Some(Proof {
+            coord,
+            deduction,
+            steps: vec![Step {
+                reason: Reason::Exhaustive { numbers },
+                deductions: vec![(coord, deduction)],
+            }],
+        });
+    };
+
+    // walk back from the final step, keeping the steps that decided a tile next to a number
+    // relied upon by a kept step
+    let mut needed = BTreeSet::from([steps.len() - 1]);
+    let mut queue = vec![steps.len() - 1];
+    while let Some(idx) = queue.pop() {
+        for number in steps[idx].reason.numbers() {
+            let dependencies = grid
+                .neighbors(grid.index(number))
+                .filter_map(|cell| decided_by[cell])
+                .filter(|&step| step < idx);
+            for step in dependencies {
+                if needed.insert(step) {
+                    queue.push(step);
+                }
+            }
+        }
+    }
+
+    let steps = steps
+        .into_iter()
+        .enumerate()
+        .filter(|(idx, _)| needed.contains(idx))
+        .map(|(_, step)| step)
+        .collect();
+
+    Some(Proof {
+        coord,
+        deduction,
+        steps,
+    })
+}
+
+impl Proof {
+    /// Every revealed number the proof relies on
+    pub fn numbers(&self) -> impl Iterator<Item = IVec2> + '_ {
+        self.steps.iter().flat_map(|step| step.reason.numbers())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::explain;
+    use crate::solver::{hint::Reason, ConstraintGraph, Deduction, SolverConfig};
+
+    #[test]
+    fn test_proof_chain() {
+        // . . . . . . .
+        // 1 1 2 1 1 0 0   the zeros clear the right end, which unwinds the row from the right
+        let mut graph = ConstraintGraph::new(7, 2, 2);
+        for (x, n) in [1, 1, 2, 1, 1, 0, 0].into_iter().enumerate() {
+            graph.reveal([x as i32, 1], n);
+        }
+
+        let proof = explain(&mut graph, [1, 0]).unwrap();
+        assert_eq!(proof.deduction, Deduction::Mine);
+        assert!(proof.steps.len() > 1);
+        assert_eq!(
+            proof.steps.last().unwrap().deductions[0].0.to_array(),
+            [1, 0]
+        );
+    }
+
+    #[test]
+    fn test_pruned_trace() {
+        // 0 . . . .
+        // . . . . .
+        // . . . . .   the 0 in the corner is decided first, but has nothing to do with
+        // . * . . .   the 1-1 corner proving [2, 3] safe
+        // 1 1 1 . .
+        let mut graph = ConstraintGraph::new(5, 5, 1);
+        graph.reveal([0, 0], 0);
+        graph.reveal([0, 4], 1);
+        graph.reveal([1, 4], 1);
+        graph.reveal([2, 4], 1);
+
+        let proof = explain(&mut graph, [2, 3]).unwrap();
+        assert_eq!(proof.deduction, Deduction::Safe);
+        assert_eq!(proof.steps.len(), 1);
+        assert!(proof.numbers().all(|number| number.y == 4));
+    }
+
+    #[test]
+    fn test_exhaustive_proof() {
+        // the only mine is around the lone 1, [4, 4] is safe whatever the arrangement
+        let lone = |config| {
+            let mut graph = ConstraintGraph::new(6, 6, 1).with_config(config);
+            graph.reveal([1, 1], 1);
+            explain(&mut graph, [4, 4])
+        };
+        let proof = lone(SolverConfig::default()).unwrap();
+        assert_eq!(proof.deduction, Deduction::Safe);
+        assert_eq!(
+            proof.steps[0].reason,
+            Reason::Exhaustive {
+                numbers: vec![IVec2::new(1, 1)]
+            }
+        );
+        // sampled probabilities prove nothing
+        let sampled = SolverConfig {
+            exact_threshold: 0,
+            samples: 1000,
+        };
+        assert_eq!(lone(sampled), None);
+    }
+}
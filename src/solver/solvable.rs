@@ -0,0 +1,118 @@
+use bevy::prelude::IVec2;
+
+use super::ConstraintGraph;
+use crate::resources::{
+    board::TileState,
+    board_state::{BoardState, Cover},
+};
+
+/// Whether the board can be cleared without a single guess, going on from its revealed tiles,
+/// or from its first opening in row order when none is revealed. Plays on a copy of `state`
+pub fn is_solvable(state: &BoardState) -> bool {
+    #[cfg(feature = "trace")]
+    let _span = bevy::log::info_span!("solver_is_solvable").entered();
+
+    let mut state = state.clone();
+    let dim = state.dim();
+    let mut graph = ConstraintGraph::new(dim.x as u32, dim.y as u32, state.n_mines() as u32)
+        .with_topology(state.topology());
+    graph.reveal_puzzle(&state);
+
+    let coords = (0..dim.y).flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)));
+    let mut queue = Vec::new();
+    for coord in coords.clone() {
+        match (state.cover(coord), state.tile(coord)) {
+            (Some(Cover::Revealed), Some(TileState::Clear(n))) => graph.reveal(coord, n),
+            (Some(Cover::Revealed), _) => {}
+            // a level gives no flag away
+            (Some(Cover::Flagged | Cover::Questioned), _) => {
+                state.toggle_flag(coord);
+            }
+            _ => {}
+        }
+    }
+    // the gaps of a puzzle are no opening
+    if state.n_revealed() == state.gaps().len() {
+        let opening = coords
+            .clone()
+            .find(|&c| state.tile(c) == Some(TileState::Clear(0)));
+        queue.extend(opening.or_else(|| {
+            coords
+                .clone()
+                .find(|&c| state.tile(c) != Some(TileState::Mine))
+        }));
+    }
+
+    loop {
+        while let Some(coord) = queue.pop() {
+            match state.uncover(coord) {
+                Some(TileState::Clear(n)) => {
+                    graph.reveal(coord, n);
+                    if n == 0 {
+                        queue.extend(
+                            state
+                                .neighbors(coord)
+                                .filter(|&n| state.cover(n) == Some(Cover::Covered)),
+                        );
+                    }
+                }
+                Some(TileState::Mine) => return false,
+                None => {}
+            }
+        }
+        if state.is_cleared() {
+            return true;
+        }
+        queue.extend(graph.analyze().safe());
+        if queue.is_empty() {
+            return false;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_solvable;
+    use crate::{
+        import,
+        resources::{board::TileMap, board_state::BoardState},
+    };
+
+    #[test]
+    fn test_solvable() {
+        // . . . .
+        // 1 1 1 .
+        // 1 * 1 .
+        let mut map = TileMap::with_mines(4, 3, [[1, 0]]).unwrap();
+        assert!(is_solvable(&BoardState::new(&mut map)));
+
+        // * 1 .
+        // 1 1 .
+        // a coin flip between the two corner tiles
+        let mut map = TileMap::with_mines(3, 2, [[0, 0]]).unwrap();
+        let mut state = BoardState::new(&mut map);
+        assert!(!is_solvable(&state));
+        // unless the level reveals the safe one
+        state.uncover([2, 0]);
+        assert!(!is_solvable(&state));
+        state.uncover([0, 1]);
+        assert!(is_solvable(&state));
+        assert_eq!(state.n_revealed(), 2);
+
+        // no opening to start from, and no deduction from the first tile
+        let mut map = TileMap::with_mines(2, 2, [[0, 0], [1, 1]]).unwrap();
+        assert!(!is_solvable(&BoardState::new(&mut map)));
+    }
+
+    #[test]
+    fn test_puzzle() {
+        // the 1 sees the mine and [2, 0], the line hint leaves [3, 0] safe, the gaps count nothing
+        let puzzle = |hint| {
+            let source = format!("hexcells\nx O o o {hint}\n . . o o\n");
+            let (_, state) = import::parse_hexcells(&source).unwrap().restore().unwrap();
+            state
+        };
+        assert!(is_solvable(&puzzle(": 1")));
+        assert!(!is_solvable(&puzzle("")));
+    }
+}
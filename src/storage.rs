@@ -0,0 +1,458 @@
+//! Where the game keeps its files: settings in the config directory, saves, replays and
+//! statistics in the data directory, anything that can be rebuilt in the cache directory.
+//!
+//! Directories follow the conventions of each platform. Setting [`HOME_VAR`] to a directory
+//! keeps everything under it instead, for portable installs. On the web, files are entries of
+//! the browser local storage.
+//!
+//! Each player profile keeps its config and data apart, under [`PROFILE_DIR`], see
+//! [`Storage::for_profile`]. The cache is shared.
+
+use std::{
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Environment variable overriding every directory, for portable installs
+pub const HOME_VAR: &str = "MINESWEEPER_HOME";
+
+const APP_NAME: &str = "minesweeper";
+
+/// Directory of the profiles, in the config and data directories
+pub const PROFILE_DIR: &str = "profiles";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Location {
+    Config,
+    Data,
+    Cache,
+}
+
+impl Location {
+    fn name(self) -> &'static str {
+        match self {
+            Location::Config => "config",
+            Location::Data => "data",
+            Location::Cache => "cache",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Backend {
+    Directories {
+        config: PathBuf,
+        data: PathBuf,
+        cache: PathBuf,
+    },
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused))]
+    LocalStorage,
+}
+
+/// Platform storage of the game files. Must be used as a resource
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Storage {
+    backend: Backend,
+    /// Id of the profile owning the config and data files, `None` for the files of players
+    /// without profiles
+    profile: Option<String>,
+}
+
+impl Storage {
+    /// Storage of the current platform, `None` if no home directory can be found
+    pub fn detect() -> Option<Self> {
+        #[cfg(target_arch = "wasm32")]
+        return Some(Self {
+            backend: Backend::LocalStorage,
+            profile: None,
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        Self::resolve(std::env::consts::OS, |var| {
+            std::env::var_os(var).map(PathBuf::from)
+        })
+    }
+
+    /// Every file under `root`, split by location
+    pub fn portable(root: impl AsRef<Path>) -> Self {
+        let root = root.as_ref();
+        Self {
+            backend: Backend::Directories {
+                config: root.join(Location::Config.name()),
+                data: root.join(Location::Data.name()),
+                cache: root.join(Location::Cache.name()),
+            },
+            profile: None,
+        }
+    }
+
+    /// Same storage, with the config and data files of the profile `id` instead. `id` must be
+    /// a valid directory name, see `save::profile_id`
+    pub fn for_profile(&self, id: &str) -> Self {
+        Self {
+            profile: Some(id.to_owned()),
+            ..self.clone()
+        }
+    }
+
+    /// Storage shared by every profile, where the list of profiles is kept
+    pub fn shared(&self) -> Self {
+        Self {
+            profile: None,
+            ..self.clone()
+        }
+    }
+
+    /// Directories of `os` (as in [`std::env::consts::OS`]) given its environment
+    fn resolve(os: &str, env: impl Fn(&str) -> Option<PathBuf>) -> Option<Self> {
+        if let Some(root) = env(HOME_VAR).filter(|root| !root.as_os_str().is_empty()) {
+            return Some(Self::portable(root));
+        }
+
+        let home = env("HOME");
+        let (config, data, cache) = match os {
+            "windows" => {
+                let roaming = env("APPDATA")?;
+                let local = env("LOCALAPPDATA").unwrap_or_else(|| roaming.clone());
+                (
+                    roaming.join(APP_NAME),
+                    roaming.join(APP_NAME),
+                    local.join(APP_NAME).join(Location::Cache.name()),
+                )
+            }
+            "macos" => {
+                let library = home?.join("Library");
+                let support = library.join("Application Support").join(APP_NAME);
+                (
+                    support.clone(),
+                    support,
+                    library.join("Caches").join(APP_NAME),
+                )
+            }
+            _ => {
+                let xdg = |var, fallback: &str| {
+                    env(var)
+                        .filter(|dir| dir.is_absolute())
+                        .or_else(|| home.as_ref().map(|home| home.join(fallback)))
+                        .map(|dir| dir.join(APP_NAME))
+                };
+                (
+                    xdg("XDG_CONFIG_HOME", ".config")?,
+                    xdg("XDG_DATA_HOME", ".local/share")?,
+                    xdg("XDG_CACHE_HOME", ".cache")?,
+                )
+            }
+        };
+
+        Some(Self {
+            backend: Backend::Directories {
+                config,
+                data,
+                cache,
+            },
+            profile: None,
+        })
+    }
+
+    /// Directory of `location`, `None` when files are not kept on a file system
+    pub fn dir(&self, location: Location) -> Option<PathBuf> {
+        let Backend::Directories {
+            config,
+            data,
+            cache,
+        } = &self.backend
+        else {
+            return None;
+        };
+        Some(match (location, &self.profile) {
+            (Location::Config, Some(id)) => config.join(PROFILE_DIR).join(id),
+            (Location::Data, Some(id)) => data.join(PROFILE_DIR).join(id),
+            (Location::Config, None) => config.clone(),
+            (Location::Data, None) => data.clone(),
+            (Location::Cache, _) => cache.clone(),
+        })
+    }
+
+    /// Path of the file `name`, `None` when files are not kept on a file system
+    pub fn path(&self, location: Location, name: &str) -> Option<PathBuf> {
+        self.dir(location).map(|dir| dir.join(name))
+    }
+
+    /// Content of the file `name`, `None` if it does not exist
+    pub fn read(&self, location: Location, name: &str) -> io::Result<Option<String>> {
+        match &self.backend {
+            Backend::Directories { .. } => {
+                match std::fs::read_to_string(self.path(location, name).unwrap()) {
+                    Ok(content) => Ok(Some(content)),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e),
+                }
+            }
+            Backend::LocalStorage => local_storage::read(&self.key(location, name)),
+        }
+    }
+
+    /// Last modification of the file `name`, in seconds since the Unix epoch. `None` if it does
+    /// not exist, or when the storage does not keep the time
+    pub fn modified(&self, location: Location, name: &str) -> io::Result<Option<u64>> {
+        match &self.backend {
+            Backend::Directories { .. } => {
+                match std::fs::metadata(self.path(location, name).unwrap()) {
+                    Ok(metadata) => Ok(Some(unix_secs(metadata.modified()?))),
+                    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+                    Err(e) => Err(e),
+                }
+            }
+            Backend::LocalStorage => Ok(None),
+        }
+    }
+
+    /// Replace the content of the file `name`, creating its directory if needed, see
+    /// [`write_file`]
+    pub fn write(&self, location: Location, name: &str, content: &str) -> io::Result<()> {
+        match &self.backend {
+            Backend::Directories { .. } => write_file(&self.path(location, name).unwrap(), content),
+            Backend::LocalStorage => local_storage::write(&self.key(location, name), content),
+        }
+    }
+
+    /// Names of the files in the directory `dir`, sorted. Empty if it does not exist, and on the
+    /// web where files cannot be listed
+    pub fn list(&self, location: Location, dir: &str) -> io::Result<Vec<String>> {
+        let Some(path) = self.path(location, dir) else {
+            return Ok(Vec::new());
+        };
+        let entries = match std::fs::read_dir(path) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut names = Vec::new();
+        for entry in entries {
+            let entry = entry?;
+            if let (true, Some(name)) = (entry.file_type()?.is_file(), entry.file_name().to_str()) {
+                names.push(name.to_owned());
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    /// Delete the file `name`, doing nothing if it does not exist
+    pub fn remove(&self, location: Location, name: &str) -> io::Result<()> {
+        match &self.backend {
+            Backend::Directories { .. } => {
+                match std::fs::remove_file(self.path(location, name).unwrap()) {
+                    Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+                    _ => Ok(()),
+                }
+            }
+            Backend::LocalStorage => local_storage::remove(&self.key(location, name)),
+        }
+    }
+
+    /// Local storage key of a file
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused))]
+    fn key(&self, location: Location, name: &str) -> String {
+        match (&self.profile, location) {
+            (Some(id), Location::Config | Location::Data) => {
+                format!("{APP_NAME}/{PROFILE_DIR}/{id}/{}/{name}", location.name())
+            }
+            _ => format!("{APP_NAME}/{}/{name}", location.name()),
+        }
+    }
+}
+
+/// Replace the content of the file at `path`, creating its directory if needed.
+///
+/// The content is written to a temporary file renamed over `path` once complete, so that a crash
+/// in the middle of a write leaves the previous version intact.
+pub fn write_file(path: &Path, content: &str) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut tmp = path.as_os_str().to_owned();
+    tmp.push(".tmp");
+    let mut file = std::fs::File::create(&tmp)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    std::fs::rename(tmp, path)
+}
+
+/// Seconds since the Unix epoch, 0 for earlier times
+pub fn unix_secs(time: SystemTime) -> u64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+#[cfg(target_arch = "wasm32")]
+mod local_storage {
+    use std::io;
+
+    fn storage() -> io::Result<web_sys::Storage> {
+        web_sys::window()
+            .and_then(|window| window.local_storage().ok().flatten())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::Unsupported, "no local storage"))
+    }
+
+    fn js_error(error: wasm_bindgen::JsValue) -> io::Error {
+        io::Error::new(io::ErrorKind::Other, format!("{error:?}"))
+    }
+
+    pub fn read(key: &str) -> io::Result<Option<String>> {
+        storage()?.get_item(key).map_err(js_error)
+    }
+
+    pub fn write(key: &str, content: &str) -> io::Result<()> {
+        storage()?.set_item(key, content).map_err(js_error)
+    }
+
+    pub fn remove(key: &str) -> io::Result<()> {
+        storage()?.remove_item(key).map_err(js_error)
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod local_storage {
+    use std::io;
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "local storage only exists on the web",
+        )
+    }
+
+    pub fn read(_: &str) -> io::Result<Option<String>> {
+        Err(unsupported())
+    }
+
+    pub fn write(_: &str, _: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn remove(_: &str) -> io::Result<()> {
+        Err(unsupported())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::path::{Path, PathBuf};
+
+    use super::{Location, Storage, HOME_VAR};
+
+    fn env<'a>(vars: &'a [(&str, &str)]) -> impl Fn(&str) -> Option<PathBuf> + 'a {
+        move |var| {
+            vars.iter()
+                .find(|(name, _)| *name == var)
+                .map(|(_, value)| PathBuf::from(value))
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let linux = Storage::resolve("linux", env(&[("HOME", "/home/me")])).unwrap();
+        assert_eq!(
+            linux.dir(Location::Config).as_deref(),
+            Some(Path::new("/home/me/.config/minesweeper"))
+        );
+        assert_eq!(
+            linux.dir(Location::Data).as_deref(),
+            Some(Path::new("/home/me/.local/share/minesweeper"))
+        );
+
+        let vars = [("HOME", "/home/me"), ("XDG_CACHE_HOME", "/tmp/cache")];
+        let xdg = Storage::resolve("linux", env(&vars)).unwrap();
+        assert_eq!(
+            xdg.dir(Location::Cache).as_deref(),
+            Some(Path::new("/tmp/cache/minesweeper"))
+        );
+
+        let macos = Storage::resolve("macos", env(&[("HOME", "/Users/me")])).unwrap();
+        assert_eq!(
+            macos.dir(Location::Data).as_deref(),
+            Some(Path::new(
+                "/Users/me/Library/Application Support/minesweeper"
+            ))
+        );
+
+        let windows = Storage::resolve("windows", env(&[("APPDATA", "C:/Roaming")])).unwrap();
+        assert_eq!(
+            windows.dir(Location::Config).as_deref(),
+            Some(PathBuf::from("C:/Roaming").join("minesweeper").as_path())
+        );
+
+        assert_eq!(Storage::resolve("linux", env(&[])), None);
+
+        let vars = [("HOME", "/home/me"), (HOME_VAR, "/media/usb/minesweeper")];
+        let portable = Storage::resolve("linux", env(&vars)).unwrap();
+        assert_eq!(portable, Storage::portable("/media/usb/minesweeper"));
+        assert_eq!(
+            portable.path(Location::Data, "stats.ron"),
+            Some(PathBuf::from("/media/usb/minesweeper/data/stats.ron"))
+        );
+    }
+
+    #[test]
+    fn test_profiles() {
+        let shared = Storage::portable("/media/usb/minesweeper");
+        let alice = shared.for_profile("alice");
+        assert_eq!(
+            alice.path(Location::Data, "stats.ron"),
+            Some(PathBuf::from(
+                "/media/usb/minesweeper/data/profiles/alice/stats.ron"
+            ))
+        );
+        assert_eq!(
+            alice.dir(Location::Config),
+            Some(PathBuf::from(
+                "/media/usb/minesweeper/config/profiles/alice"
+            ))
+        );
+        assert_eq!(alice.dir(Location::Cache), shared.dir(Location::Cache));
+        assert_eq!(alice.shared(), shared);
+        assert_eq!(
+            alice.key(Location::Config, "options.ron"),
+            "minesweeper/profiles/alice/config/options.ron"
+        );
+    }
+
+    #[test]
+    fn test_files() {
+        let root = std::env::temp_dir().join(format!("minesweeper-storage-{}", std::process::id()));
+        let storage = Storage::portable(&root);
+
+        assert_eq!(storage.read(Location::Data, "stats.ron").unwrap(), None);
+        storage.write(Location::Data, "stats.ron", "()").unwrap();
+        assert_eq!(
+            storage
+                .read(Location::Data, "stats.ron")
+                .unwrap()
+                .as_deref(),
+            Some("()")
+        );
+        storage.remove(Location::Data, "stats.ron").unwrap();
+        storage.remove(Location::Data, "stats.ron").unwrap();
+        assert_eq!(storage.read(Location::Data, "stats.ron").unwrap(), None);
+
+        assert_eq!(
+            storage.list(Location::Data, "boards").unwrap(),
+            [] as [String; 0]
+        );
+        storage.write(Location::Data, "boards/b.ron", "()").unwrap();
+        storage.write(Location::Data, "boards/a.ron", "()").unwrap();
+        storage
+            .write(Location::Data, "boards/nested/c.ron", "()")
+            .unwrap();
+        assert_eq!(
+            storage.list(Location::Data, "boards").unwrap(),
+            ["a.ron", "b.ron"]
+        );
+
+        std::fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,270 @@
+//! Boards identical up to rotation and reflection. Each of the 8 symmetric copies of a board
+//! has the same canonical form, so that shared boards can be told apart from mere turned copies.
+
+use bevy::prelude::{IVec2, UVec2};
+
+use crate::{
+    hash::StableHasher,
+    hexcells::Line,
+    resources::board_options::{BoardView, Rotation},
+    save::SaveGame,
+    topology::SQUARE,
+};
+
+/// Mine layout in the form shared by all its rotations and reflections: the copy with the fewest
+/// rows, then the mines earliest in row order
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CanonicalBoard {
+    pub dim: UVec2,
+    /// Sorted by row, then column, without duplicates
+    pub mines: Vec<IVec2>,
+    /// Tiles given away revealed, sorted like the mines
+    pub revealed: Vec<IVec2>,
+    /// Holes of a puzzle, sorted like the mines
+    pub gaps: Vec<IVec2>,
+    /// Line hints of a puzzle, by row
+    pub lines: Vec<Line>,
+    /// Name of the tiling
+    pub topology: String,
+    /// Turns the original board into this one, see [`BoardView::to_display`]. The first one in
+    /// rotation order for symmetric boards, so a board already canonical keeps the default view
+    pub view: BoardView,
+}
+
+impl CanonicalBoard {
+    /// Mines alone on squares
+    pub fn new(dim: UVec2, mines: &[IVec2]) -> Self {
+        Self::turned(dim, [mines, &[], &[]], Self::views())
+    }
+
+    /// Board to play as saved, with its tiling and the tiles it gives away. Only squares without
+    /// line hints are turned, other tilings do not match themselves under a quarter turn
+    pub fn of_game(board: &SaveGame) -> Self {
+        let dim = UVec2::new(board.width, board.height);
+        let topology = board.topology.as_deref().unwrap_or(SQUARE);
+        let tiles = [&board.mines[..], &board.revealed, &board.gaps];
+        let turns = topology == SQUARE && board.lines.is_empty();
+        let views = Self::views().take(if turns { 8 } else { 1 });
+        let mut canonical = Self::turned(dim, tiles, views);
+        canonical.lines = board.lines.clone();
+        canonical.lines.sort_by_key(|line| line.row);
+        canonical.topology = topology.to_owned();
+        canonical
+    }
+
+    /// Every rotation, then every rotation of the mirrored board
+    fn views() -> impl Iterator<Item = BoardView> {
+        [false, true].into_iter().flat_map(|mirror| {
+            [
+                Rotation::Deg0,
+                Rotation::Deg90,
+                Rotation::Deg180,
+                Rotation::Deg270,
+            ]
+            .map(|rotation| BoardView { rotation, mirror })
+        })
+    }
+
+    /// Least of the `views` of the mines, revealed tiles and gaps
+    fn turned(
+        dim: UVec2,
+        tiles: [&[IVec2]; 3],
+        views: impl IntoIterator<Item = BoardView>,
+    ) -> Self {
+        views
+            .into_iter()
+            .map(|view| {
+                let [mines, revealed, gaps] = tiles.map(|tiles| {
+                    let mut tiles = tiles
+                        .iter()
+                        .map(|&tile| view.to_display(tile, dim))
+                        .collect::<Vec<_>>();
+                    tiles.sort_by_key(|tile| (tile.y, tile.x));
+                    tiles.dedup();
+                    tiles
+                });
+                Self {
+                    dim: view.display_dim(dim),
+                    mines,
+                    revealed,
+                    gaps,
+                    lines: Vec::new(),
+                    topology: SQUARE.to_owned(),
+                    view,
+                }
+            })
+            .min_by(|a, b| a.key().cmp(&b.key()))
+            .expect("there is a view")
+    }
+
+    fn key(&self) -> impl Ord {
+        let rows = |tiles: &[IVec2]| {
+            tiles
+                .iter()
+                .map(|tile| (tile.y, tile.x))
+                .collect::<Vec<_>>()
+        };
+        (
+            (self.dim.y, self.dim.x),
+            rows(&self.mines),
+            rows(&self.revealed),
+            rows(&self.gaps),
+        )
+    }
+
+    /// Stable hash, the same for every symmetric copy of the board
+    pub fn hash(&self) -> u64 {
+        let mut hash = StableHasher::new();
+        hash.write(b"minesweeper board");
+        hash.write_u32(self.dim.x);
+        hash.write_u32(self.dim.y);
+        hash.write_u32(self.mines.len() as u32);
+        self.mines.iter().for_each(|&mine| hash.write_coord(mine));
+        // left out when missing, plain boards keep the hash they had before tilings
+        if self.topology != SQUARE {
+            hash.write(b"topology");
+            hash.write_u32(self.topology.len() as u32);
+            hash.write(self.topology.as_bytes());
+        }
+        for (tag, tiles) in [(&b"revealed"[..], &self.revealed), (b"gaps", &self.gaps)] {
+            if !tiles.is_empty() {
+                hash.write(tag);
+                hash.write_u32(tiles.len() as u32);
+                tiles.iter().for_each(|&tile| hash.write_coord(tile));
+            }
+        }
+        if !self.lines.is_empty() {
+            hash.write(b"lines");
+            hash.write_u32(self.lines.len() as u32);
+            for line in &self.lines {
+                hash.write_u32(line.row);
+                hash.write_u32(line.mines);
+            }
+        }
+        hash.finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::{
+        math::{ivec2, uvec2},
+        prelude::IVec2,
+    };
+
+    use super::CanonicalBoard;
+    use crate::{
+        hexcells::Line,
+        resources::board_options::{BoardView, Rotation},
+        save::SaveGame,
+    };
+
+    #[test]
+    fn test_canonical() {
+        // * . . .
+        // * * . .
+        // . . . *
+        let dim = uvec2(4, 3);
+        let mines = [ivec2(0, 2), ivec2(0, 1), ivec2(1, 1), ivec2(3, 0)];
+        let canonical = CanonicalBoard::new(dim, &mines);
+        assert_eq!(canonical.dim, dim);
+        // upside down
+        assert_eq!(
+            canonical.mines,
+            [ivec2(0, 0), ivec2(0, 1), ivec2(1, 1), ivec2(3, 2)]
+        );
+        assert_eq!(
+            canonical.view,
+            BoardView {
+                rotation: Rotation::Deg180,
+                mirror: true
+            }
+        );
+
+        // every copy, with its mines in any order, has the same form
+        for rotation in [
+            Rotation::Deg0,
+            Rotation::Deg90,
+            Rotation::Deg180,
+            Rotation::Deg270,
+        ] {
+            for mirror in [false, true] {
+                let view = BoardView { rotation, mirror };
+                let mut copy = mines.map(|mine| view.to_display(mine, dim));
+                copy.reverse();
+                let other = CanonicalBoard::new(view.display_dim(dim), &copy);
+                assert_eq!((other.dim, &other.mines), (canonical.dim, &canonical.mines));
+                assert_eq!(other.hash(), canonical.hash());
+
+                // the view turns the copy into the canonical board
+                let mut turned =
+                    copy.map(|mine| other.view.to_display(mine, view.display_dim(dim)));
+                turned.sort_by_key(|mine| (mine.y, mine.x));
+                assert_eq!(turned[..], canonical.mines[..]);
+            }
+        }
+
+        let moved = CanonicalBoard::new(dim, &[ivec2(0, 2), ivec2(0, 1), ivec2(2, 1), ivec2(3, 0)]);
+        assert_ne!(moved.hash(), canonical.hash());
+        let wider = CanonicalBoard::new(uvec2(5, 3), &mines);
+        assert_ne!(wider.hash(), canonical.hash());
+    }
+
+    #[test]
+    fn test_symmetric() {
+        // a board already canonical, and the same under a half turn
+        let mines = [ivec2(0, 0), ivec2(2, 1)];
+        let canonical = CanonicalBoard::new(uvec2(3, 2), &mines);
+        assert_eq!(canonical.view, BoardView::default());
+        assert_eq!(canonical.mines, mines);
+        assert_eq!(
+            CanonicalBoard::new(uvec2(3, 2), &[]).mines,
+            Vec::<IVec2>::new()
+        );
+    }
+
+    #[test]
+    fn test_of_game() {
+        let board = |mines: &[[i32; 2]], revealed: &[[i32; 2]], topology: Option<&str>| SaveGame {
+            width: 3,
+            height: 2,
+            mines: mines.iter().map(|&mine| mine.into()).collect(),
+            revealed: revealed.iter().map(|&tile| tile.into()).collect(),
+            flagged: Vec::new(),
+            topology: topology.map(str::to_owned),
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        let hash = |board: &SaveGame| CanonicalBoard::of_game(board).hash();
+
+        // plain squares hash as before
+        let square = board(&[[0, 0]], &[], None);
+        let plain = CanonicalBoard::new(uvec2(3, 2), &[ivec2(0, 0)]).hash();
+        assert_eq!(hash(&square), plain);
+        assert_eq!(hash(&board(&[[0, 0]], &[], Some("square"))), plain);
+
+        // the givens are part of the level, and turn with the mines
+        let given = board(&[[0, 0]], &[[2, 1]], None);
+        assert_ne!(hash(&given), plain);
+        assert_eq!(hash(&board(&[[2, 1]], &[[0, 0]], None)), hash(&given));
+        assert_ne!(hash(&board(&[[0, 0]], &[[2, 0]], None)), hash(&given));
+
+        // hexagons are neither squares nor turned
+        let hex = board(&[[0, 0]], &[], Some("hex"));
+        assert_ne!(hash(&hex), plain);
+        assert_ne!(hash(&board(&[[2, 1]], &[], Some("hex"))), hash(&hex));
+
+        // nor are line hints, they follow the rows
+        let lines = SaveGame {
+            lines: vec![Line { row: 0, mines: 1 }],
+            ..square.clone()
+        };
+        assert_ne!(hash(&lines), plain);
+        let flipped = SaveGame {
+            mines: vec![ivec2(2, 1)],
+            lines: vec![Line { row: 1, mines: 1 }],
+            ..square
+        };
+        assert_ne!(hash(&flipped), hash(&lines));
+    }
+}
@@ -0,0 +1,182 @@
+//! Miniatures of boards, to recognize saved games, replays and levels at a glance.
+
+use bevy::{
+    prelude::{IVec2, Image},
+    render::{
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        texture::ImageSampler,
+    },
+};
+
+use crate::{
+    resources::{
+        board::TileState,
+        board_state::{BoardState, Cover},
+    },
+    save::{Replay, SaveError, SaveGame},
+};
+
+const COVERED: [u8; 4] = [70, 70, 70, 255];
+const FLAGGED: [u8; 4] = [220, 40, 40, 255];
+const MINE: [u8; 4] = [0, 0, 0, 255];
+const OPENING: [u8; 4] = [210, 210, 210, 255];
+
+/// A board drawn with one pixel per tile, computed from the board state alone so that no
+/// board needs to be spawned
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Thumbnail {
+    dim: IVec2,
+    pixels: Vec<[u8; 4]>,
+}
+
+impl Thumbnail {
+    /// The board as the player sees it
+    pub fn of_state(state: &BoardState) -> Self {
+        let dim = state.dim();
+        let pixels = (0..dim.y)
+            .flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)))
+            .map(|coord| match (state.cover(coord), state.tile(coord)) {
+                (Some(Cover::Covered | Cover::Questioned), _) => COVERED,
+                (Some(Cover::Flagged), _) => FLAGGED,
+                (_, Some(TileState::Mine)) => MINE,
+                (_, Some(TileState::Clear(0))) => OPENING,
+                // darker with more adjacent mines
+                (_, Some(TileState::Clear(n))) => {
+                    let shade = 190u8.saturating_sub(15u8.saturating_mul(n));
+                    [shade, shade, 230, 255]
+                }
+                _ => unreachable!("coordinates are within the board"),
+            })
+            .collect();
+
+        Self { dim, pixels }
+    }
+
+    /// The game in progress of a save
+    pub fn of_save(save: &SaveGame) -> Result<Self, SaveError> {
+        save.restore().map(|(_, state)| Self::of_state(&state))
+    }
+
+    /// The fully revealed board a replay was played on
+    pub fn of_replay(replay: &Replay) -> Result<Self, SaveError> {
+        let layout = replay.layout();
+        let (_, mut state) = layout.restore()?;
+        for y in 0..layout.height as i32 {
+            for x in 0..layout.width as i32 {
+                state.uncover([x, y]);
+            }
+        }
+        Ok(Self::of_state(&state))
+    }
+
+    pub fn dim(&self) -> IVec2 {
+        self.dim
+    }
+
+    /// RGBA color of the tile at `coord`, `None` if out of the board
+    pub fn pixel<T: Into<IVec2>>(&self, coord: T) -> Option<[u8; 4]> {
+        let coord = coord.into();
+        (coord.cmpge(IVec2::ZERO).all() && coord.cmplt(self.dim).all())
+            .then(|| self.pixels[(coord.y * self.dim.x + coord.x) as usize])
+    }
+
+    /// Texture of the thumbnail, sampled without blur when scaled up
+    pub fn to_image(&self) -> Image {
+        // images go top to bottom, the board bottom to top
+        let data = self
+            .pixels
+            .chunks(self.dim.x as usize)
+            .rev()
+            .flatten()
+            .flatten()
+            .copied()
+            .collect();
+
+        let mut image = Image::new(
+            Extent3d {
+                width: self.dim.x as u32,
+                height: self.dim.y as u32,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        );
+        image.sampler_descriptor = ImageSampler::nearest();
+        image
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{Thumbnail, COVERED, FLAGGED, MINE, OPENING};
+    use crate::save::{Replay, SaveGame};
+
+    #[test]
+    fn test_thumbnail() {
+        // * 1 .
+        // 1 1 .
+        let save = SaveGame {
+            width: 3,
+            height: 2,
+            mines: vec![IVec2::new(0, 0)],
+            revealed: vec![IVec2::new(2, 1)],
+            flagged: vec![IVec2::new(0, 0)],
+            topology: None,
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        let thumbnail = Thumbnail::of_save(&save).unwrap();
+        assert_eq!(thumbnail.dim(), IVec2::new(3, 2));
+        assert_eq!(thumbnail.pixel([0, 0]), Some(FLAGGED));
+        assert_eq!(thumbnail.pixel([1, 1]), Some(COVERED));
+        assert_eq!(thumbnail.pixel([2, 1]), Some(OPENING));
+        assert_eq!(thumbnail.pixel([3, 0]), None);
+
+        let replay = Replay {
+            width: 3,
+            height: 2,
+            mines: save.mines.clone(),
+            moves: Vec::new(),
+            topology: None,
+            mode: Default::default(),
+            outcome: None,
+            keyframes: Vec::new(),
+            annotations: Vec::new(),
+        };
+        let thumbnail = Thumbnail::of_replay(&replay).unwrap();
+        assert_eq!(thumbnail.pixel([0, 0]), Some(MINE));
+        assert_ne!(thumbnail.pixel([1, 1]), Some(COVERED));
+
+        let image = thumbnail.to_image();
+        assert_eq!(image.data.len(), 3 * 2 * 4);
+        // the first row of the image is the top of the board
+        assert_eq!(image.data[4 * 3..4 * 3 + 4], MINE);
+
+        let broken = SaveGame { width: 0, ..save };
+        assert!(Thumbnail::of_save(&broken).is_err());
+    }
+
+    #[test]
+    fn test_thumbnail_layered() {
+        // the middle of a 3x3x3 cube, surrounded by 26 mines
+        let center = IVec2::new(1, 4);
+        let save = SaveGame {
+            width: 3,
+            height: 9,
+            mines: (0..9)
+                .flat_map(|y| (0..3).map(move |x| IVec2::new(x, y)))
+                .filter(|&coord| coord != center)
+                .collect(),
+            revealed: vec![center],
+            flagged: Vec::new(),
+            topology: Some("layered".to_owned()),
+            gaps: Vec::new(),
+            lines: Vec::new(),
+        };
+        let thumbnail = Thumbnail::of_save(&save).unwrap();
+        assert_eq!(thumbnail.pixel(center), Some([0, 0, 230, 255]));
+    }
+}
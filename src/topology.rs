@@ -0,0 +1,673 @@
+//! How tiles are laid out and which tiles touch. The rules only ever see coordinates, their
+//! neighbors and flat indices, so a new tiling is a [`Topology`] registered under a name, then
+//! picked with `BoardOptions::topology`. Coordinates always range over `dim`, the tiling
+//! decides what they mean. Events, saves and messages carry these storage coordinates, which
+//! every tiling understands; [`TileCoord`] is the natural coordinate shown to players and tools.
+
+use std::{
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
+    sync::RwLock,
+};
+
+use bevy::prelude::{IVec2, Vec2};
+use serde::{Deserialize, Serialize};
+
+#[cfg(feature = "debug")]
+use bevy_inspector_egui::Inspectable;
+
+/// Most neighbors a tile may have, those of a cube in a 3D grid
+pub const MAX_NEIGHBORS: usize = 26;
+
+/// Key of the default topology
+pub const SQUARE: &str = "square";
+
+const SQRT_3: f32 = 1.732_050_8;
+
+/// A tiling of the board: neighbors, flat indices and the layout on screen.
+///
+/// Layout works in tile units: a tile of the square grid is 1 by 1, the board plugin scales
+/// it by the tile size and applies the `BoardView` around it
+pub trait Topology: Debug + Send + Sync + 'static {
+    /// Key in the registry, and the value of `BoardOptions::topology`
+    fn name(&self) -> &str;
+
+    /// Reason a `dim` sized board cannot use this tiling, if any
+    fn validate(&self, _dim: IVec2) -> Result<(), String> {
+        Ok(())
+    }
+
+    /// Tiles touching `coord`, without duplicates nor `coord` itself
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors;
+
+    /// Flat index of `coord`, `None` if out of the board
+    fn index(&self, coord: IVec2, dim: IVec2) -> Option<usize> {
+        in_bounds(coord, dim).then_some((coord.y * dim.x + coord.x) as usize)
+    }
+
+    /// Coordinate of a flat index, inverse of [`Topology::index`]
+    fn coord(&self, index: usize, dim: IVec2) -> IVec2 {
+        IVec2::new(index as i32 % dim.x, index as i32 / dim.x)
+    }
+
+    /// Size of the board, in tile units
+    fn extent(&self, dim: IVec2) -> Vec2 {
+        dim.as_vec2()
+    }
+
+    /// Center of the tile at `coord` from the bottom left corner, in tile units
+    fn position(&self, coord: IVec2, _dim: IVec2) -> Vec2 {
+        coord.as_vec2() + 0.5
+    }
+
+    /// Tile under a position from the bottom left corner, inverse of [`Topology::position`]
+    fn coord_at(&self, position: Vec2, dim: IVec2) -> Option<IVec2> {
+        let coord = position.floor().as_ivec2();
+        in_bounds(coord, dim).then_some(coord)
+    }
+
+    /// Natural coordinate of the tile at `coord`
+    fn native(&self, coord: IVec2, _dim: IVec2) -> TileCoord {
+        TileCoord::Grid {
+            x: coord.x,
+            y: coord.y,
+        }
+    }
+
+    /// Storage coordinate of a natural one, inverse of [`Topology::native`]. `None` if out of
+    /// the board or written for another kind of tiling
+    fn coord_of_native(&self, native: TileCoord, dim: IVec2) -> Option<IVec2> {
+        let TileCoord::Grid { x, y } = native else {
+            return None;
+        };
+        let coord = IVec2::new(x, y);
+        in_bounds(coord, dim).then_some(coord)
+    }
+}
+
+/// Coordinate of a tile in the natural system of its tiling
+#[cfg_attr(feature = "debug", derive(Inspectable))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum TileCoord {
+    /// Column and row, for square and triangle grids
+    Grid { x: i32, y: i32 },
+    /// Axial coordinates of hexagons, `q` along the rows and `r` down the slanted columns
+    Axial { q: i32, r: i32 },
+    /// Column and row in a layer, and the layer
+    Layered { x: i32, y: i32, z: i32 },
+}
+
+impl Default for TileCoord {
+    fn default() -> Self {
+        TileCoord::Grid { x: 0, y: 0 }
+    }
+}
+
+impl Display for TileCoord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            TileCoord::Grid { x, y } => write!(f, "({x}, {y})"),
+            TileCoord::Axial { q, r } => write!(f, "(q {q}, r {r})"),
+            TileCoord::Layered { x, y, z } => write!(f, "({x}, {y}, layer {z})"),
+        }
+    }
+}
+
+/// Topologies are told apart by name
+impl PartialEq for dyn Topology {
+    fn eq(&self, other: &Self) -> bool {
+        self.name() == other.name()
+    }
+}
+
+impl Eq for dyn Topology {}
+
+impl Hash for dyn Topology {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name().hash(state);
+    }
+}
+
+impl Default for &'static dyn Topology {
+    fn default() -> Self {
+        &Square
+    }
+}
+
+/// Neighbors of a tile, kept inline so that flood fills do not allocate
+#[derive(Debug, Clone, Copy)]
+pub struct Neighbors {
+    coords: [IVec2; MAX_NEIGHBORS],
+    len: usize,
+    next: usize,
+}
+
+impl Neighbors {
+    pub fn new() -> Self {
+        Self {
+            coords: [IVec2::ZERO; MAX_NEIGHBORS],
+            len: 0,
+            next: 0,
+        }
+    }
+
+    /// Add a neighbor, ignoring tiles already in
+    pub fn push(&mut self, coord: IVec2) {
+        if !self.coords[..self.len].contains(&coord) {
+            self.coords[self.len] = coord;
+            self.len += 1;
+        }
+    }
+
+    /// Whether every neighbor was iterated over
+    pub fn is_empty(&self) -> bool {
+        self.next >= self.len
+    }
+
+    /// In-board tiles at `deltas` from `coord`
+    pub fn offsets(coord: IVec2, dim: IVec2, deltas: &[[i32; 2]]) -> Self {
+        let mut neighbors = Self::new();
+        for &delta in deltas {
+            let neighbor = coord + IVec2::from(delta);
+            if in_bounds(neighbor, dim) {
+                neighbors.push(neighbor);
+            }
+        }
+        neighbors
+    }
+}
+
+impl Default for Neighbors {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Iterator for Neighbors {
+    type Item = IVec2;
+
+    fn next(&mut self) -> Option<IVec2> {
+        let coord = self.coords[..self.len].get(self.next).copied();
+        self.next += 1;
+        coord
+    }
+}
+
+fn in_bounds(coord: IVec2, dim: IVec2) -> bool {
+    coord.cmpge(IVec2::ZERO).all() && coord.cmplt(dim).all()
+}
+
+/// Delta coordinates for all 8 square neighbors
+const SQUARE_NEIGHBORS: [[i32; 2]; 8] = [
+    [-1, -1],
+    [0, -1],
+    [1, -1],
+    [-1, 0],
+    [1, 0],
+    [-1, 1],
+    [0, 1],
+    [1, 1],
+];
+
+/// The classic grid, 8 neighbors for every tile away from the edges
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Square;
+
+impl Topology for Square {
+    fn name(&self) -> &str {
+        SQUARE
+    }
+
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+        Neighbors::offsets(coord, dim, &SQUARE_NEIGHBORS)
+    }
+}
+
+/// Square grid whose opposite edges touch, so every tile has 8 neighbors
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Torus;
+
+impl Topology for Torus {
+    fn name(&self) -> &str {
+        "torus"
+    }
+
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+        let mut neighbors = Neighbors::new();
+        for delta in SQUARE_NEIGHBORS {
+            let neighbor = coord + IVec2::from(delta);
+            let neighbor = IVec2::new(neighbor.x.rem_euclid(dim.x), neighbor.y.rem_euclid(dim.y));
+            // narrow boards wrap onto the tile itself
+            if neighbor != coord {
+                neighbors.push(neighbor);
+            }
+        }
+        neighbors
+    }
+}
+
+/// Pointy-top hexagons, odd rows shifted half a tile right, 6 neighbors
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Hex;
+
+impl Hex {
+    /// Distance between the centers of two rows, for hexagons 1 wide
+    const ROW_HEIGHT: f32 = SQRT_3 / 2.0;
+    /// Distance from the center to a corner
+    const RADIUS: f32 = 1.0 / SQRT_3;
+}
+
+impl Topology for Hex {
+    fn name(&self) -> &str {
+        "hex"
+    }
+
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+        const EVEN: [[i32; 2]; 6] = [[-1, 0], [1, 0], [-1, -1], [0, -1], [-1, 1], [0, 1]];
+        const ODD: [[i32; 2]; 6] = [[-1, 0], [1, 0], [0, -1], [1, -1], [0, 1], [1, 1]];
+        let deltas = if coord.y % 2 == 0 { &EVEN } else { &ODD };
+        Neighbors::offsets(coord, dim, deltas)
+    }
+
+    fn extent(&self, dim: IVec2) -> Vec2 {
+        let shift = if dim.y > 1 { 0.5 } else { 0.0 };
+        Vec2::new(
+            dim.x as f32 + shift,
+            (dim.y - 1) as f32 * Self::ROW_HEIGHT + 2.0 * Self::RADIUS,
+        )
+    }
+
+    fn position(&self, coord: IVec2, _dim: IVec2) -> Vec2 {
+        let shift = if coord.y % 2 == 0 { 0.5 } else { 1.0 };
+        Vec2::new(
+            coord.x as f32 + shift,
+            coord.y as f32 * Self::ROW_HEIGHT + Self::RADIUS,
+        )
+    }
+
+    fn coord_at(&self, position: Vec2, dim: IVec2) -> Option<IVec2> {
+        // the nearest center is the hexagon holding the position
+        let row = ((position.y - Self::RADIUS) / Self::ROW_HEIGHT).round() as i32;
+        let column = position.x.floor() as i32;
+        (row - 1..=row + 1)
+            .flat_map(|y| (column - 1..=column + 1).map(move |x| IVec2::new(x, y)))
+            .filter(|&coord| in_bounds(coord, dim))
+            .map(|coord| (coord, self.position(coord, dim).distance(position)))
+            .filter(|&(_, distance)| distance <= Self::RADIUS)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(coord, _)| coord)
+    }
+
+    fn native(&self, coord: IVec2, _dim: IVec2) -> TileCoord {
+        // odd rows are shifted right, so every other row moves `q` back by one
+        TileCoord::Axial {
+            q: coord.x - (coord.y - (coord.y & 1)) / 2,
+            r: coord.y,
+        }
+    }
+
+    fn coord_of_native(&self, native: TileCoord, dim: IVec2) -> Option<IVec2> {
+        let TileCoord::Axial { q, r } = native else {
+            return None;
+        };
+        let coord = IVec2::new(q + (r - (r & 1)) / 2, r);
+        in_bounds(coord, dim).then_some(coord)
+    }
+}
+
+/// Triangles pointing up and down in turn, touching the 12 tiles they share a corner with
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Triangle;
+
+impl Triangle {
+    const ROW_HEIGHT: f32 = SQRT_3 / 2.0;
+
+    fn points_up(coord: IVec2) -> bool {
+        (coord.x + coord.y) % 2 == 0
+    }
+}
+
+impl Topology for Triangle {
+    fn name(&self) -> &str {
+        "triangle"
+    }
+
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+        // a triangle touches 5 tiles along its base and 3 at its tip
+        let (below, above) = if Self::points_up(coord) {
+            (2, 1)
+        } else {
+            (1, 2)
+        };
+        let mut neighbors = Neighbors::new();
+        for (dy, reach) in [(-1, below), (0, 2), (1, above)] {
+            for dx in -reach..=reach {
+                let neighbor = coord + IVec2::new(dx, dy);
+                if neighbor != coord && in_bounds(neighbor, dim) {
+                    neighbors.push(neighbor);
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn extent(&self, dim: IVec2) -> Vec2 {
+        Vec2::new((dim.x + 1) as f32 / 2.0, dim.y as f32 * Self::ROW_HEIGHT)
+    }
+
+    fn position(&self, coord: IVec2, _dim: IVec2) -> Vec2 {
+        // centroids are a third of the height away from the base
+        let height = if Self::points_up(coord) { 1.0 } else { 2.0 } / 3.0;
+        Vec2::new(
+            (coord.x + 1) as f32 / 2.0,
+            (coord.y as f32 + height) * Self::ROW_HEIGHT,
+        )
+    }
+
+    fn coord_at(&self, position: Vec2, dim: IVec2) -> Option<IVec2> {
+        let row = (position.y / Self::ROW_HEIGHT).floor() as i32;
+        // height in the row, 0 at the bottom
+        let v = position.y / Self::ROW_HEIGHT - row as f32;
+        let column = (position.x * 2.0).floor() as i32;
+        [column - 1, column]
+            .into_iter()
+            .map(|x| IVec2::new(x, row))
+            .filter(|&coord| in_bounds(coord, dim))
+            .find(|&coord| {
+                let half_width = if Self::points_up(coord) { 1.0 - v } else { v } / 2.0;
+                (position.x - (coord.x + 1) as f32 / 2.0).abs() <= half_width
+            })
+    }
+}
+
+/// 3D grid of `depth` layers stacked along the rows: a board of height `depth * h` is `depth`
+/// layers of height `h`, shown side by side. Tiles touch the 26 tiles of the cube around them
+#[derive(Debug, Clone, Copy)]
+pub struct Layered {
+    pub name: &'static str,
+    pub depth: i32,
+}
+
+impl Layered {
+    /// Layer and coordinate in the layer of a tile
+    fn split(&self, coord: IVec2, dim: IVec2) -> (i32, IVec2) {
+        let height = dim.y / self.depth;
+        (coord.y / height, IVec2::new(coord.x, coord.y % height))
+    }
+}
+
+impl Topology for Layered {
+    fn name(&self) -> &str {
+        self.name
+    }
+
+    fn validate(&self, dim: IVec2) -> Result<(), String> {
+        if dim.y % self.depth == 0 {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} rows do not split into {} layers",
+                dim.y, self.depth
+            ))
+        }
+    }
+
+    fn neighbors(&self, coord: IVec2, dim: IVec2) -> Neighbors {
+        let height = dim.y / self.depth;
+        let (layer, flat) = self.split(coord, dim);
+        let mut neighbors = Neighbors::new();
+        for dz in -1..=1 {
+            let z = layer + dz;
+            if !(0..self.depth).contains(&z) {
+                continue;
+            }
+            for delta in SQUARE_NEIGHBORS
+                .into_iter()
+                .map(IVec2::from)
+                .chain([IVec2::ZERO])
+            {
+                let neighbor = flat + delta;
+                if (dz != 0 || delta != IVec2::ZERO)
+                    && in_bounds(neighbor, IVec2::new(dim.x, height))
+                {
+                    neighbors.push(neighbor + IVec2::new(0, z * height));
+                }
+            }
+        }
+        neighbors
+    }
+
+    fn extent(&self, dim: IVec2) -> Vec2 {
+        // a tile wide gap between layers
+        Vec2::new(
+            ((dim.x + 1) * self.depth - 1) as f32,
+            (dim.y / self.depth) as f32,
+        )
+    }
+
+    fn position(&self, coord: IVec2, dim: IVec2) -> Vec2 {
+        let (layer, flat) = self.split(coord, dim);
+        (flat + IVec2::new(layer * (dim.x + 1), 0)).as_vec2() + 0.5
+    }
+
+    fn coord_at(&self, position: Vec2, dim: IVec2) -> Option<IVec2> {
+        let height = dim.y / self.depth;
+        let cell = position.floor().as_ivec2();
+        let layer = cell.x.div_euclid(dim.x + 1);
+        let flat = IVec2::new(cell.x.rem_euclid(dim.x + 1), cell.y);
+        ((0..self.depth).contains(&layer) && in_bounds(flat, IVec2::new(dim.x, height)))
+            .then(|| flat + IVec2::new(0, layer * height))
+    }
+
+    fn native(&self, coord: IVec2, dim: IVec2) -> TileCoord {
+        let (z, flat) = self.split(coord, dim);
+        TileCoord::Layered {
+            x: flat.x,
+            y: flat.y,
+            z,
+        }
+    }
+
+    fn coord_of_native(&self, native: TileCoord, dim: IVec2) -> Option<IVec2> {
+        let TileCoord::Layered { x, y, z } = native else {
+            return None;
+        };
+        let height = dim.y / self.depth;
+        ((0..self.depth).contains(&z) && in_bounds(IVec2::new(x, y), IVec2::new(dim.x, height)))
+            .then(|| IVec2::new(x, y + z * height))
+    }
+}
+
+/// Tilings shipped with the game
+pub const BUILTIN: [&dyn Topology; 5] = [
+    &Square,
+    &Torus,
+    &Hex,
+    &Triangle,
+    &Layered {
+        name: "layered",
+        depth: 3,
+    },
+];
+
+/// Tilings added by plugins and mods, after the built-in ones
+static REGISTERED: RwLock<Vec<&'static dyn Topology>> = RwLock::new(Vec::new());
+
+/// Make a tiling available to `BoardOptions::topology`, for the rest of the program. Returns
+/// `false`, leaving the registry as it was, if its name is taken
+pub fn register(topology: impl Topology) -> bool {
+    let mut registered = REGISTERED.write().unwrap_or_else(|e| e.into_inner());
+    if BUILTIN
+        .iter()
+        .chain(registered.iter())
+        .any(|known| known.name() == topology.name())
+    {
+        return false;
+    }
+    registered.push(Box::leak(Box::new(topology)));
+    true
+}
+
+/// Tiling registered under `name`
+pub fn get(name: &str) -> Option<&'static dyn Topology> {
+    let registered = REGISTERED.read().unwrap_or_else(|e| e.into_inner());
+    BUILTIN
+        .iter()
+        .chain(registered.iter())
+        .find(|topology| topology.name() == name)
+        .copied()
+}
+
+/// Names of every tiling, built-in ones first
+pub fn names() -> Vec<String> {
+    let registered = REGISTERED.read().unwrap_or_else(|e| e.into_inner());
+    BUILTIN
+        .iter()
+        .chain(registered.iter())
+        .map(|topology| topology.name().to_owned())
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::{IVec2, Vec2};
+
+    use super::{
+        get, names, register, Hex, Layered, Square, TileCoord, Topology, Torus, Triangle, BUILTIN,
+    };
+
+    fn sorted(neighbors: impl Iterator<Item = IVec2>) -> Vec<[i32; 2]> {
+        let mut neighbors = neighbors.map(|c| c.to_array()).collect::<Vec<_>>();
+        neighbors.sort_by_key(|&[x, y]| (y, x));
+        neighbors
+    }
+
+    #[test]
+    fn test_neighbors() {
+        let dim = IVec2::new(4, 4);
+        assert_eq!(Square.neighbors(IVec2::ZERO, dim).count(), 3);
+        assert_eq!(Torus.neighbors(IVec2::ZERO, dim).count(), 8);
+        assert_eq!(
+            sorted(Torus.neighbors(IVec2::ZERO, IVec2::new(2, 1))),
+            [[1, 0]]
+        );
+        assert_eq!(
+            sorted(Hex.neighbors(IVec2::new(1, 1), dim)),
+            [[1, 0], [2, 0], [0, 1], [2, 1], [1, 2], [2, 2]]
+        );
+        assert_eq!(
+            Triangle
+                .neighbors(IVec2::new(4, 2), IVec2::new(9, 5))
+                .count(),
+            12
+        );
+
+        let cube = Layered {
+            name: "cube",
+            depth: 3,
+        };
+        assert_eq!(
+            cube.neighbors(IVec2::new(1, 4), IVec2::new(3, 9)).count(),
+            26
+        );
+        assert_eq!(
+            sorted(cube.neighbors(IVec2::ZERO, IVec2::new(1, 3))),
+            [[0, 1]]
+        );
+        assert!(cube.validate(IVec2::new(3, 8)).is_err());
+
+        // touching is symmetric
+        for topology in BUILTIN {
+            let dim = IVec2::new(7, 9);
+            for idx in 0..63 {
+                let coord = topology.coord(idx, dim);
+                assert_eq!(topology.index(coord, dim), Some(idx));
+                for neighbor in topology.neighbors(coord, dim) {
+                    assert!(topology.neighbors(neighbor, dim).any(|c| c == coord));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_layout() {
+        for topology in BUILTIN {
+            let dim = IVec2::new(7, 9);
+            let extent = topology.extent(dim);
+            for idx in 0..63 {
+                let coord = topology.coord(idx, dim);
+                let center = topology.position(coord, dim);
+                assert!(center.cmpgt(Vec2::ZERO).all() && center.cmplt(extent).all());
+                assert_eq!(
+                    topology.coord_at(center, dim),
+                    Some(coord),
+                    "{}",
+                    topology.name()
+                );
+            }
+            assert_eq!(topology.coord_at(extent + 1.0, dim), None);
+        }
+    }
+
+    #[test]
+    fn test_native() {
+        let dim = IVec2::new(7, 9);
+        assert_eq!(
+            Hex.native(IVec2::new(3, 3), dim),
+            TileCoord::Axial { q: 2, r: 3 }
+        );
+        let layered = get("layered").unwrap();
+        assert_eq!(
+            layered.native(IVec2::new(1, 4), dim),
+            TileCoord::Layered { x: 1, y: 1, z: 1 }
+        );
+        assert_eq!(
+            Square.coord_of_native(TileCoord::Axial { q: 0, r: 0 }, dim),
+            None
+        );
+        assert_eq!(
+            Square.coord_of_native(TileCoord::Grid { x: 7, y: 0 }, dim),
+            None
+        );
+
+        for topology in BUILTIN {
+            for idx in 0..63 {
+                let coord = topology.coord(idx, dim);
+                let native = topology.native(coord, dim);
+                let text = ron::to_string(&native).unwrap();
+                let native = ron::from_str::<TileCoord>(&text).unwrap();
+                assert_eq!(
+                    topology.coord_of_native(native, dim),
+                    Some(coord),
+                    "{}",
+                    topology.name()
+                );
+            }
+        }
+    }
+
+    #[derive(Debug)]
+    struct Line;
+
+    impl Topology for Line {
+        fn name(&self) -> &str {
+            "test line"
+        }
+
+        fn neighbors(&self, coord: IVec2, dim: IVec2) -> super::Neighbors {
+            super::Neighbors::offsets(coord, dim, &[[-1, 0], [1, 0]])
+        }
+    }
+
+    #[test]
+    fn test_registry() {
+        assert_eq!(get("square").map(|t| t.name()), Some("square"));
+        assert!(get("test line").is_none());
+        assert!(register(Line));
+        assert!(!register(Line));
+        assert!(!register(Square));
+        let line = get("test line").unwrap();
+        assert_eq!(line.neighbors(IVec2::ZERO, IVec2::new(3, 1)).count(), 1);
+        assert!(names().contains(&"test line".to_owned()));
+    }
+}
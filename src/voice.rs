@@ -0,0 +1,207 @@
+//! Spoken commands of the `voice` feature, see `VoicePlugin`. A command is an action then the
+//! name of a tile as the row and column labels show it, like `reveal C7` or `flag D2`.
+//!
+//! Recognizers write out what they hear, so the column letters may come as letters or as words
+//! of the NATO alphabet (`flag delta two`) and the row as digits or number words up to 99. The
+//! [`vocabulary`] keeps the recognizer to those words.
+
+use bevy::prelude::IVec2;
+
+use crate::resources::board::coord_from_name;
+
+/// Words starting a command uncovering a tile
+const REVEAL_WORDS: [&str; 4] = ["reveal", "open", "uncover", "dig"];
+
+/// Words starting a command toggling the flag of a tile
+const FLAG_WORDS: [&str; 2] = ["flag", "mark"];
+
+/// Column letters as spoken in the NATO alphabet
+const NATO: [&str; 26] = [
+    "alfa", "bravo", "charlie", "delta", "echo", "foxtrot", "golf", "hotel", "india", "juliett",
+    "kilo", "lima", "mike", "november", "oscar", "papa", "quebec", "romeo", "sierra", "tango",
+    "uniform", "victor", "whiskey", "xray", "yankee", "zulu",
+];
+
+/// Spellings recognizers write for some of the [`NATO`] words
+const NATO_ALIASES: [(&str, char); 4] = [
+    ("alpha", 'A'),
+    ("juliet", 'J'),
+    ("whisky", 'W'),
+    ("x-ray", 'X'),
+];
+
+const UNITS: [&str; 20] = [
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// A command heard, on the tile named
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VoiceCommand {
+    Reveal(IVec2),
+    Flag(IVec2),
+}
+
+impl VoiceCommand {
+    pub fn coord(&self) -> IVec2 {
+        match *self {
+            Self::Reveal(coord) | Self::Flag(coord) => coord,
+        }
+    }
+}
+
+/// Command of a phrase heard, `None` when it is not one
+pub fn parse(phrase: &str) -> Option<VoiceCommand> {
+    let phrase = phrase.to_lowercase();
+    let words = phrase.split_whitespace().collect::<Vec<_>>();
+    let (action, tile) = words.split_first()?;
+    let coord = tile_coord(tile)?;
+    if REVEAL_WORDS.contains(action) {
+        Some(VoiceCommand::Reveal(coord))
+    } else if FLAG_WORDS.contains(action) {
+        Some(VoiceCommand::Flag(coord))
+    } else {
+        None
+    }
+}
+
+/// Every word of the commands, for the grammar of the recognizer
+pub fn vocabulary() -> Vec<String> {
+    let letters = ('a'..='z').map(String::from);
+    let words = REVEAL_WORDS
+        .iter()
+        .chain(&FLAG_WORDS)
+        .chain(&NATO)
+        .chain(NATO_ALIASES.iter().map(|(alias, _)| alias))
+        .chain(UNITS.iter().chain(&TENS))
+        .map(|&word| word.to_owned());
+    letters.chain(words).collect()
+}
+
+/// Tile of the words after the action, a name like `c7` or its letters and row apart
+fn tile_coord(words: &[&str]) -> Option<IVec2> {
+    if let [name] = words {
+        return coord_from_name(name);
+    }
+    let letters = words
+        .iter()
+        .map_while(|word| letter(word))
+        .collect::<String>();
+    let row = number(&words[letters.len()..])?;
+    coord_from_name(&format!("{letters}{row}"))
+}
+
+fn letter(word: &str) -> Option<char> {
+    match word.as_bytes() {
+        [letter] if letter.is_ascii_alphabetic() => Some(char::from(letter.to_ascii_uppercase())),
+        _ => NATO
+            .iter()
+            .position(|&nato| nato == word)
+            .map(|idx| char::from(b'A' + idx as u8))
+            .or_else(|| {
+                NATO_ALIASES
+                    .iter()
+                    .find(|(alias, _)| *alias == word)
+                    .map(|&(_, letter)| letter)
+            }),
+    }
+}
+
+/// Row of digits, or of the words of a number below 100
+fn number(words: &[&str]) -> Option<u32> {
+    let unit = |word: &str| UNITS.iter().position(|&unit| unit == word);
+    let tens = |word: &str| {
+        TENS.iter()
+            .position(|&tens| tens == word)
+            .map(|idx| idx * 10 + 20)
+    };
+    let value = match *words {
+        [digits] if digits.bytes().all(|b| b.is_ascii_digit()) => return digits.parse().ok(),
+        [word] => unit(word).or_else(|| tens(word))?,
+        [high, low] => tens(high)? + unit(low).filter(|&low| (1..10).contains(&low))?,
+        _ => return None,
+    };
+    u32::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use bevy::prelude::IVec2;
+
+    use super::{parse, vocabulary, VoiceCommand};
+
+    #[test]
+    fn test_parse() {
+        let c7 = IVec2::new(2, 6);
+        for phrase in [
+            "reveal C7",
+            "reveal c 7",
+            "open charlie seven",
+            "Uncover  C  seven",
+            "dig c 07",
+        ] {
+            assert_eq!(parse(phrase), Some(VoiceCommand::Reveal(c7)), "{phrase}");
+        }
+        assert_eq!(parse("flag D2"), Some(VoiceCommand::Flag(IVec2::new(3, 1))));
+        assert_eq!(
+            parse("mark alpha delta twenty one"),
+            Some(VoiceCommand::Flag(IVec2::new(29, 20)))
+        );
+        assert_eq!(
+            parse("flag x-ray ninety"),
+            Some(VoiceCommand::Flag(IVec2::new(23, 89)))
+        );
+
+        for phrase in [
+            "",
+            "reveal",
+            "reveal c",
+            "reveal seven",
+            "reveal c zero",
+            "reveal c twenty twenty",
+            "reveal c thirteen five",
+            "jump c7",
+            "what a game",
+        ] {
+            assert_eq!(parse(phrase), None, "{phrase}");
+        }
+    }
+
+    #[test]
+    fn test_vocabulary() {
+        let vocabulary = vocabulary();
+        let phrases = [
+            "reveal charlie seven",
+            "flag d twenty two",
+            "mark x-ray one",
+        ];
+        for phrase in phrases {
+            assert!(parse(phrase).is_some(), "{phrase}");
+            for word in phrase.split_whitespace() {
+                assert!(vocabulary.iter().any(|known| known == word), "{word}");
+            }
+        }
+    }
+}
@@ -0,0 +1,106 @@
+//! Counts the heap allocations of the board hot paths: once the buffers of a `BoardScratch` have
+//! grown, cascades on expert boards must not allocate at all.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use minesweeper::resources::{
+    board::{TileMap, TileState},
+    board_scratch::BoardScratch,
+    board_state::BoardState,
+};
+
+/// System allocator counting the allocations of every thread, tests running in parallel
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+fn count() {
+    // a thread being torn down has no counter left
+    let _ = ALLOCATIONS.try_with(|n| n.set(n.get() + 1));
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        count();
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        count();
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Allocations made by `f` on this thread
+fn allocations<R>(f: impl FnOnce() -> R) -> (R, usize) {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    (result, ALLOCATIONS.with(Cell::get) - before)
+}
+
+#[test]
+fn test_expert_cascades() {
+    let mut scratch = BoardScratch::default();
+    let mut cascades = 0;
+    for seed in 0..20 {
+        let mut tiles = TileMap::seeded(30, 16, 99, seed).unwrap();
+        let state = BoardState::new(&mut tiles);
+        let Some(zero) = (0..tiles.len()).find(|&idx| tiles.state_at(idx) == TileState::Clear(0))
+        else {
+            continue;
+        };
+        let zero = tiles.coord_of(zero);
+
+        // grow the buffers on a first run of the same cascade
+        let mut warm = state.clone();
+        let revealed = warm.click_into(zero, true, &mut scratch).len();
+        warm.swap_changes(&mut scratch.changes);
+
+        let mut state = state.clone();
+        state.swap_changes(&mut scratch.changes);
+        let (played, n) = allocations(|| {
+            let played = state.click_into(zero, true, &mut scratch).len();
+            state.swap_changes(&mut scratch.changes);
+            played
+        });
+        assert_eq!(played, revealed);
+        assert_eq!(n, 0, "a cascade of {played} tiles allocated {n} times");
+        cascades += usize::from(played > 1);
+    }
+    assert!(cascades > 0);
+}
+
+#[test]
+fn test_traversal() {
+    let mut tiles = TileMap::seeded(30, 16, 99, 7).unwrap();
+    let state = BoardState::new(&mut tiles);
+    let (mines, n) = allocations(|| {
+        let mines = (0..tiles.len())
+            .filter(|&idx| tiles.state_at(idx) == TileState::Mine)
+            .count();
+        let covers = tiles
+            .coords()
+            .filter_map(|coord| state.cover(coord))
+            .count();
+        let chords = tiles
+            .coords()
+            .map(|coord| state.chord_targets(coord).count())
+            .sum::<usize>();
+        assert_eq!((covers, chords), (tiles.len(), 0));
+        mines
+    });
+    assert_eq!(mines, 99);
+    assert_eq!(n, 0);
+}
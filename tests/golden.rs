@@ -0,0 +1,109 @@
+//! Golden files for the `Display` of `TileMap` and the ASCII export of `BoardState`, under
+//! `tests/golden`. The `Display` is compared with its ANSI codes stripped first, so a failure
+//! shows a readable diff of the layout, then with its colors. Run with `BLESS_GOLDEN=1` to
+//! write the files of a new test or rewrite them after an intended change, a missing file fails
+//! otherwise.
+
+use std::{env, fs, path::PathBuf};
+
+use bevy::prelude::IVec2;
+use minesweeper::resources::{
+    board::{TileMap, TileState},
+    board_options::Difficulty,
+    board_state::BoardState,
+};
+
+fn golden_path(name: &str) -> PathBuf {
+    [env!("CARGO_MANIFEST_DIR"), "tests", "golden", name]
+        .iter()
+        .collect()
+}
+
+fn assert_golden(name: &str, actual: &str) {
+    let path = golden_path(name);
+    if env::var_os("BLESS_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap_or_else(|e| panic!("cannot write {name}: {e}"));
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("cannot read {name}: {e}, run with BLESS_GOLDEN=1 to write it"));
+    assert!(
+        expected == actual,
+        "{name} changed, rerun with BLESS_GOLDEN=1 if intended\n\
+         --- expected\n{expected}\n--- actual\n{actual}"
+    );
+}
+
+/// Drop the escape sequences setting colors and styles
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            chars.by_ref().find(|&c| c == 'm');
+        } else {
+            stripped.push(c);
+        }
+    }
+    stripped
+}
+
+/// Reveal the safe tiles of the top row, flag the first mine and question the second one, so
+/// that the export shows every kind of tile
+fn partial_game(tile_map: &mut TileMap) -> BoardState {
+    let mut state = BoardState::new(tile_map);
+    let dim = state.dim();
+    for x in 0..dim.x {
+        if state.tile([x, 0]) != Some(TileState::Mine) {
+            state.uncover([x, 0]);
+        }
+    }
+    let mines = (0..dim.y)
+        .flat_map(|y| (0..dim.x).map(move |x| IVec2::new(x, y)))
+        .filter(|&coord| state.tile(coord) == Some(TileState::Mine))
+        .collect::<Vec<_>>();
+    let mut mines = mines.into_iter();
+    if let Some(first) = mines.next() {
+        state.cycle_mark(first, true);
+    }
+    if let Some(second) = mines.next() {
+        state.cycle_mark(second, true);
+        state.cycle_mark(second, true);
+    }
+    state
+}
+
+fn check_board(name: &str, mut tile_map: TileMap) {
+    colored::control::set_override(true);
+    let display = format!("{tile_map:#}\n");
+    assert_golden(&format!("{name}.display.txt"), &strip_ansi(&display));
+    assert_golden(&format!("{name}.display.ansi"), &display);
+
+    let state = partial_game(&mut tile_map);
+    let ascii = format!("{}\n{}", state.to_ascii(false), state.to_ascii(true));
+    assert_golden(&format!("{name}.ascii.txt"), &ascii);
+}
+
+#[test]
+fn test_strip_ansi() {
+    assert_eq!(strip_ansi("| \x1b[36m1\x1b[0m * |"), "| 1 * |");
+    assert_eq!(strip_ansi("plain"), "plain");
+}
+
+#[test]
+fn test_placed_boards() {
+    let cross = [[1, 1], [2, 1], [3, 1], [1, 2], [3, 2]];
+    check_board("cross", TileMap::with_mines(5, 5, cross).unwrap());
+    let ring = (0..9).filter(|&i| i != 4).map(|i| [i % 3, i / 3]);
+    check_board("ring", TileMap::with_mines(3, 3, ring).unwrap());
+}
+
+#[test]
+fn test_seeded_boards() {
+    for difficulty in [Difficulty::EASY, Difficulty::MEDIUM, Difficulty::EXPERT] {
+        let Difficulty { dim, n_mines } = difficulty;
+        let tile_map = TileMap::seeded(dim.x, dim.y, n_mines, 0x5eed).unwrap();
+        let name = format!("seeded_{}", difficulty.name().to_lowercase());
+        check_board(&name, tile_map);
+    }
+}
@@ -0,0 +1,173 @@
+//! Plays a seeded board to the end without a window: the moves go through the same events as
+//! the clicks, and the win must reach the HUD and the stats on disk.
+
+use std::{env, fs, path::Path};
+
+use bevy::{
+    app::App,
+    asset::AssetPlugin,
+    ecs::event::Events,
+    hierarchy::HierarchyPlugin,
+    input::InputPlugin,
+    prelude::{IVec2, MinimalPlugins, With},
+    text::Text,
+    transform::TransformPlugin,
+    window::Windows,
+};
+use minesweeper::{
+    components::{FlagCounterText, MineCounter, SegmentDisplay},
+    events::{BoardClearEvent, TileMarkEvent, TileTriggerEvent},
+    plugins::{
+        ActiveDaily, ActiveWeekly, AppState, BoardPlugin, GameStatus, HudPlugin, InsightsPlugin,
+    },
+    resources::{
+        board::TileState,
+        board_options::{BoardOptions, Difficulty, GameMode},
+        board_seed::SeededBoard,
+        board_state::{BoardState, Cover},
+        uncover_queue::UncoverQueue,
+    },
+    save::{self, Stats, STATS_FILE},
+    storage::{Location, Storage},
+};
+
+const SEED: u64 = 0x5eed;
+
+/// Frames a flood fill may take before the test gives up
+const MAX_FRAMES: usize = 1000;
+
+/// Headless app with the board alone, no other game plugin
+fn board_app(root: &Path) -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugin(AssetPlugin)
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .add_plugin(InputPlugin)
+        .insert_resource(Windows::default())
+        .insert_resource(Storage::portable(root))
+        .insert_resource(BoardOptions::default())
+        .insert_resource(SeededBoard {
+            seed: SEED,
+            mode: GameMode::Classic,
+            difficulty: Difficulty::EASY,
+        })
+        .add_state(AppState::Playing)
+        .add_plugin(BoardPlugin);
+    app
+}
+
+fn app(root: &Path) -> App {
+    let mut app = board_app(root);
+    app.init_resource::<ActiveDaily>()
+        .init_resource::<ActiveWeekly>()
+        .add_plugin(HudPlugin)
+        .add_plugin(InsightsPlugin);
+    app
+}
+
+fn send<E: Send + Sync + 'static>(app: &mut App, event: E) {
+    app.world.resource_mut::<Events<E>>().send(event);
+}
+
+/// Run frames until every queued tile is uncovered
+fn settle(app: &mut App) {
+    for _ in 0..MAX_FRAMES {
+        app.update();
+        if app.world.resource::<UncoverQueue>().is_empty() {
+            return;
+        }
+    }
+    panic!("the flood fill did not settle in {MAX_FRAMES} frames");
+}
+
+fn coords(state: &BoardState) -> impl Iterator<Item = IVec2> {
+    let dim = state.dim();
+    (0..dim.y).flat_map(move |y| (0..dim.x).map(move |x| IVec2::new(x, y)))
+}
+
+#[test]
+fn test_win_path() {
+    let root = env::temp_dir().join(format!("minesweeper-win-path-{}", std::process::id()));
+    let mut app = app(&root);
+    let mut cleared = app.world.resource::<Events<BoardClearEvent>>().get_reader();
+
+    // the board is built on entering `Playing`, and its resources land at the end of the frame
+    app.update();
+    app.update();
+    let state = app.world.resource::<BoardState>();
+    let mines = coords(state)
+        .filter(|&coord| state.tile(coord) == Some(TileState::Mine))
+        .collect::<Vec<_>>();
+    let opening = coords(state)
+        .find(|&coord| state.tile(coord) == Some(TileState::Clear(0)))
+        .expect("the seeded board has an opening");
+    assert_eq!(mines.len(), Difficulty::EASY.n_mines as usize);
+
+    for &coord in &mines {
+        send(&mut app, TileMarkEvent { coord });
+    }
+    send(&mut app, TileTriggerEvent { coord: opening });
+    settle(&mut app);
+    // events only last two frames
+    let events = app.world.resource::<Events<BoardClearEvent>>();
+    let mut n_cleared = cleared.iter(events).count();
+
+    // whatever the flood fill left covered
+    let state = app.world.resource::<BoardState>();
+    let rest = coords(state)
+        .filter(|&coord| state.cover(coord) == Some(Cover::Covered))
+        .collect::<Vec<_>>();
+    for coord in rest {
+        send(&mut app, TileTriggerEvent { coord });
+    }
+    settle(&mut app);
+
+    assert_eq!(*app.world.resource::<GameStatus>(), GameStatus::Won);
+    let events = app.world.resource::<Events<BoardClearEvent>>();
+    n_cleared += cleared.iter(events).count();
+    assert_eq!(n_cleared, 1);
+
+    let state = app.world.resource::<BoardState>();
+    assert_eq!(state.n_mines() - state.n_flags(), 0);
+    let n_mines = state.n_mines();
+    let mut counters = app.world.query_filtered::<&Text, With<FlagCounterText>>();
+    let counter = counters.single(&app.world);
+    assert_eq!(
+        counter.sections[0].value,
+        format!("Flags: {n_mines} / {n_mines}")
+    );
+    let mut mine_counters = app
+        .world
+        .query_filtered::<&SegmentDisplay, With<MineCounter>>();
+    assert_eq!(mine_counters.single(&app.world).value, 0);
+
+    let storage = app.world.resource::<Storage>();
+    let stats = save::read::<Stats>(storage, Location::Data, STATS_FILE)
+        .expect("unreadable stats")
+        .expect("no stats written");
+    assert_eq!((stats.played, stats.won), (1, 1));
+
+    fs::remove_dir_all(&root).ok();
+}
+
+#[test]
+fn test_board_alone() {
+    let root = env::temp_dir().join(format!("minesweeper-board-alone-{}", std::process::id()));
+    let mut app = board_app(&root);
+    app.update();
+    app.update();
+
+    let state = app.world.resource::<BoardState>();
+    let opening = coords(state)
+        .find(|&coord| state.tile(coord) == Some(TileState::Clear(0)))
+        .expect("the seeded board has an opening");
+    send(&mut app, TileTriggerEvent { coord: opening });
+    settle(&mut app);
+
+    let state = app.world.resource::<BoardState>();
+    assert_eq!(state.cover(opening), Some(Cover::Revealed));
+    assert_eq!(*app.world.resource::<GameStatus>(), GameStatus::Playing);
+
+    fs::remove_dir_all(&root).ok();
+}